@@ -0,0 +1,756 @@
+//! Apache Avro schema parsing and binary decoding for Schema Registry payloads.
+//!
+//! Values are rendered with the Avro JSON encoding used by the Confluent
+//! console tools: non-null union branches are wrapped in a single-entry object
+//! keyed by the branch type name, and `bytes`/`fixed` become ISO-8859-1 strings.
+//! Encoding accepts the same representation, and also bare union values that
+//! match exactly one branch shape.
+
+use std::collections::HashMap;
+
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+use crate::schema_registry::SchemaValue;
+
+#[derive(Clone, Debug)]
+enum AvroType {
+    Null,
+    Boolean,
+    Int,
+    Long,
+    Float,
+    Double,
+    Bytes,
+    String,
+    Record {
+        name: String,
+        fields: Vec<AvroField>,
+    },
+    Enum {
+        name: String,
+        symbols: Vec<String>,
+    },
+    Fixed {
+        name: String,
+        size: usize,
+    },
+    Array(Box<Self>),
+    Map(Box<Self>),
+    Union(Vec<Self>),
+    Named(String),
+}
+
+#[derive(Clone, Debug)]
+struct AvroField {
+    name: String,
+    schema: AvroType,
+    default: Option<Value>,
+}
+
+/// A parsed Avro schema together with every named type it can reference.
+#[derive(Debug)]
+pub struct AvroSchema {
+    root: AvroType,
+    named: HashMap<String, AvroType>,
+}
+
+impl AvroSchema {
+    /// Parses `schema`, first registering the named types declared by `references`.
+    pub fn parse(schema: &str, references: &[&str]) -> Result<Self> {
+        let mut named = HashMap::new();
+        for reference in references {
+            parse_type(&parse_json(reference)?, "", &mut named)?;
+        }
+        let root = parse_type(&parse_json(schema)?, "", &mut named)?;
+        Ok(Self { root, named })
+    }
+
+    /// Decodes one binary-encoded datum, rejecting trailing bytes.
+    pub fn decode(&self, bytes: &[u8]) -> Result<SchemaValue> {
+        let mut reader = AvroReader { buffer: bytes };
+        let value = self.read(&self.root, &mut reader)?;
+        if reader.buffer.is_empty() {
+            Ok(value)
+        } else {
+            Err(Error::Config(format!(
+                "Avro datum has {} trailing bytes",
+                reader.buffer.len()
+            )))
+        }
+    }
+
+    /// Validates a JSON datum against the schema and returns its binary encoding.
+    pub fn encode(&self, value: &Value) -> Result<Vec<u8>> {
+        let mut output = Vec::new();
+        self.write(&self.root, value, "datum", &mut output)?;
+        Ok(output)
+    }
+
+    fn lookup<'a>(&'a self, name: &str) -> Result<&'a AvroType> {
+        self.named
+            .get(name)
+            .or_else(|| {
+                name.rsplit_once('.')
+                    .and_then(|(_, simple)| self.named.get(simple))
+            })
+            .ok_or_else(|| Error::Config(format!("unknown Avro type {name}")))
+    }
+
+    fn read(&self, schema: &AvroType, reader: &mut AvroReader<'_>) -> Result<SchemaValue> {
+        Ok(match schema {
+            AvroType::Null => SchemaValue::Null,
+            AvroType::Boolean => match reader.take(1)?[0] {
+                0 => SchemaValue::Bool(false),
+                1 => SchemaValue::Bool(true),
+                other => return Err(Error::Config(format!("invalid Avro boolean {other}"))),
+            },
+            AvroType::Int => SchemaValue::Int(i64::from(reader.int()?)),
+            AvroType::Long => SchemaValue::Int(reader.long()?),
+            AvroType::Float => SchemaValue::Float(f64::from(f32::from_le_bytes(reader.fixed()?))),
+            AvroType::Double => SchemaValue::Float(f64::from_le_bytes(reader.fixed()?)),
+            AvroType::Bytes => SchemaValue::String(latin1(reader.bytes()?)),
+            AvroType::String => SchemaValue::String(reader.string()?),
+            AvroType::Record { fields, .. } => SchemaValue::Object(
+                fields
+                    .iter()
+                    .map(|field| Ok((field.name.clone(), self.read(&field.schema, reader)?)))
+                    .collect::<Result<_>>()?,
+            ),
+            AvroType::Enum { name, symbols } => {
+                let index = reader.int()?;
+                let symbol = usize::try_from(index)
+                    .ok()
+                    .and_then(|index| symbols.get(index))
+                    .ok_or_else(|| {
+                        Error::Config(format!("Avro enum {name} has no symbol {index}"))
+                    })?;
+                SchemaValue::String(symbol.clone())
+            }
+            AvroType::Fixed { size, .. } => SchemaValue::String(latin1(reader.take(*size)?)),
+            AvroType::Array(items) => {
+                let mut values = Vec::new();
+                while let Some(count) = reader.block_count()? {
+                    for _ in 0..count {
+                        values.push(self.read(items, reader)?);
+                    }
+                }
+                SchemaValue::Array(values)
+            }
+            AvroType::Map(values) => {
+                let mut entries = Vec::new();
+                while let Some(count) = reader.block_count()? {
+                    for _ in 0..count {
+                        let key = reader.string()?;
+                        entries.push((key, self.read(values, reader)?));
+                    }
+                }
+                SchemaValue::Object(entries)
+            }
+            AvroType::Union(branches) => {
+                let index = reader.long()?;
+                let branch = usize::try_from(index)
+                    .ok()
+                    .and_then(|index| branches.get(index))
+                    .ok_or_else(|| Error::Config(format!("Avro union has no branch {index}")))?;
+                match branch {
+                    AvroType::Null => SchemaValue::Null,
+                    branch => SchemaValue::Object(vec![(
+                        self.type_name(branch),
+                        self.read(branch, reader)?,
+                    )]),
+                }
+            }
+            AvroType::Named(name) => self.read(self.lookup(name)?, reader)?,
+        })
+    }
+
+    fn write(
+        &self,
+        schema: &AvroType,
+        value: &Value,
+        path: &str,
+        output: &mut Vec<u8>,
+    ) -> Result<()> {
+        let mismatch = || {
+            Error::Usage(format!(
+                "{path}: expected {} but found {value}",
+                self.type_name(schema)
+            ))
+        };
+        match (schema, value) {
+            (AvroType::Null, Value::Null) => {}
+            (AvroType::Boolean, Value::Bool(value)) => output.push(u8::from(*value)),
+            (AvroType::Int, Value::Number(number)) => write_long(
+                output,
+                number
+                    .as_i64()
+                    .filter(|value| i32::try_from(*value).is_ok())
+                    .ok_or_else(mismatch)?,
+            ),
+            (AvroType::Long, Value::Number(number)) => {
+                write_long(output, number.as_i64().ok_or_else(mismatch)?);
+            }
+            (AvroType::Float, Value::Number(number)) => {
+                #[expect(
+                    clippy::cast_possible_truncation,
+                    reason = "Avro floats are single precision"
+                )]
+                let value = number.as_f64().ok_or_else(mismatch)? as f32;
+                output.extend_from_slice(&value.to_le_bytes());
+            }
+            (AvroType::Double, Value::Number(number)) => {
+                output.extend_from_slice(&number.as_f64().ok_or_else(mismatch)?.to_le_bytes());
+            }
+            (AvroType::String, Value::String(text)) => write_bytes(output, text.as_bytes()),
+            (AvroType::Bytes, Value::String(text)) => {
+                write_bytes(output, &from_latin1(text).ok_or_else(mismatch)?);
+            }
+            (AvroType::Fixed { size, .. }, Value::String(text)) => {
+                let bytes = from_latin1(text)
+                    .filter(|bytes| bytes.len() == *size)
+                    .ok_or_else(mismatch)?;
+                output.extend_from_slice(&bytes);
+            }
+            (AvroType::Enum { symbols, .. }, Value::String(symbol)) => {
+                let index = symbols
+                    .iter()
+                    .position(|candidate| candidate == symbol)
+                    .ok_or_else(mismatch)?;
+                write_unsigned(output, index);
+            }
+            (AvroType::Record { name, fields }, Value::Object(object)) => {
+                self.write_record(name, fields, object, path, output)?;
+            }
+            (AvroType::Array(items), Value::Array(values)) => {
+                if !values.is_empty() {
+                    write_unsigned(output, values.len());
+                    for (index, value) in values.iter().enumerate() {
+                        self.write(items, value, &format!("{path}[{index}]"), output)?;
+                    }
+                }
+                write_long(output, 0);
+            }
+            (AvroType::Map(values), Value::Object(entries)) => {
+                if !entries.is_empty() {
+                    write_unsigned(output, entries.len());
+                    for (key, value) in entries {
+                        write_bytes(output, key.as_bytes());
+                        self.write(values, value, &format!("{path}.{key}"), output)?;
+                    }
+                }
+                write_long(output, 0);
+            }
+            (AvroType::Union(branches), value) => {
+                let (index, value) = self.union_branch(branches, value).ok_or_else(mismatch)?;
+                write_unsigned(output, index);
+                self.write(&branches[index], value, path, output)?;
+            }
+            (AvroType::Named(name), value) => {
+                self.write(self.lookup(name)?, value, path, output)?;
+            }
+            _ => return Err(mismatch()),
+        }
+        Ok(())
+    }
+
+    fn write_record(
+        &self,
+        name: &str,
+        fields: &[AvroField],
+        object: &serde_json::Map<String, Value>,
+        path: &str,
+        output: &mut Vec<u8>,
+    ) -> Result<()> {
+        if let Some(unknown) = object
+            .keys()
+            .find(|key| fields.iter().all(|field| &field.name != *key))
+        {
+            return Err(Error::Usage(format!(
+                "{path}: record {name} has no field {unknown}"
+            )));
+        }
+        for field in fields {
+            let value = object
+                .get(&field.name)
+                .or(field.default.as_ref())
+                .ok_or_else(|| Error::Usage(format!("{path}: missing field {}", field.name)))?;
+            let path = format!("{path}.{}", field.name);
+            // Union defaults always refer to the first branch.
+            match (&field.schema, object.contains_key(&field.name)) {
+                (AvroType::Union(branches), false) if !branches.is_empty() => {
+                    write_long(output, 0);
+                    self.write(&branches[0], value, &path, output)?;
+                }
+                _ => self.write(&field.schema, value, &path, output)?,
+            }
+        }
+        Ok(())
+    }
+
+    /// Picks the union branch for `value`, preferring the `{"type": value}` wrapper form.
+    fn union_branch<'a>(
+        &self,
+        branches: &[AvroType],
+        value: &'a Value,
+    ) -> Option<(usize, &'a Value)> {
+        let wrapped = match value {
+            Value::Object(object) if object.len() == 1 => {
+                object.iter().next().and_then(|(name, inner)| {
+                    branches
+                        .iter()
+                        .position(|branch| self.type_name(branch) == *name)
+                        .map(|index| (index, inner))
+                })
+            }
+            _ => None,
+        };
+        wrapped.or_else(|| {
+            branches
+                .iter()
+                .position(|branch| self.matches(branch, value))
+                .map(|index| (index, value))
+        })
+    }
+
+    /// Reports whether `value` has the JSON shape of `schema`, used to pick bare union branches.
+    fn matches(&self, schema: &AvroType, value: &Value) -> bool {
+        match (schema, value) {
+            (AvroType::Null, Value::Null)
+            | (AvroType::Boolean, Value::Bool(_))
+            | (AvroType::Float | AvroType::Double, Value::Number(_))
+            | (AvroType::String, Value::String(_))
+            | (AvroType::Array(_), Value::Array(_))
+            | (AvroType::Map(_), Value::Object(_)) => true,
+            (AvroType::Int, Value::Number(number)) => number
+                .as_i64()
+                .is_some_and(|value| i32::try_from(value).is_ok()),
+            (AvroType::Long, Value::Number(number)) => number.as_i64().is_some(),
+            (AvroType::Bytes, Value::String(text)) => from_latin1(text).is_some(),
+            (AvroType::Fixed { size, .. }, Value::String(text)) => {
+                from_latin1(text).is_some_and(|bytes| bytes.len() == *size)
+            }
+            (AvroType::Enum { symbols, .. }, Value::String(symbol)) => symbols.contains(symbol),
+            (AvroType::Record { fields, .. }, Value::Object(object)) => {
+                object
+                    .keys()
+                    .all(|key| fields.iter().any(|field| &field.name == key))
+                    && fields
+                        .iter()
+                        .all(|field| object.contains_key(&field.name) || field.default.is_some())
+            }
+            (AvroType::Named(name), value) => self
+                .lookup(name)
+                .is_ok_and(|schema| self.matches(schema, value)),
+            _ => false,
+        }
+    }
+
+    fn type_name(&self, schema: &AvroType) -> String {
+        match schema {
+            AvroType::Null => "null".into(),
+            AvroType::Boolean => "boolean".into(),
+            AvroType::Int => "int".into(),
+            AvroType::Long => "long".into(),
+            AvroType::Float => "float".into(),
+            AvroType::Double => "double".into(),
+            AvroType::Bytes => "bytes".into(),
+            AvroType::String => "string".into(),
+            AvroType::Record { name, .. }
+            | AvroType::Enum { name, .. }
+            | AvroType::Fixed { name, .. } => name.clone(),
+            AvroType::Array(_) => "array".into(),
+            AvroType::Map(_) => "map".into(),
+            AvroType::Union(_) => "union".into(),
+            AvroType::Named(name) => self
+                .lookup(name)
+                .map_or_else(|_| name.clone(), |schema| self.type_name(schema)),
+        }
+    }
+}
+
+fn parse_json(schema: &str) -> Result<Value> {
+    serde_json::from_str(schema)
+        .map_err(|error| Error::Config(format!("invalid Avro schema: {error}")))
+}
+
+fn primitive(name: &str) -> Option<AvroType> {
+    Some(match name {
+        "null" => AvroType::Null,
+        "boolean" => AvroType::Boolean,
+        "int" => AvroType::Int,
+        "long" => AvroType::Long,
+        "float" => AvroType::Float,
+        "double" => AvroType::Double,
+        "bytes" => AvroType::Bytes,
+        "string" => AvroType::String,
+        _ => return None,
+    })
+}
+
+fn full_name(name: &str, namespace: &str) -> String {
+    if name.contains('.') || namespace.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{namespace}.{name}")
+    }
+}
+
+fn parse_type(
+    value: &Value,
+    namespace: &str,
+    named: &mut HashMap<String, AvroType>,
+) -> Result<AvroType> {
+    match value {
+        Value::String(name) => {
+            Ok(primitive(name).unwrap_or_else(|| AvroType::Named(full_name(name, namespace))))
+        }
+        Value::Array(branches) => branches
+            .iter()
+            .map(|branch| parse_type(branch, namespace, named))
+            .collect::<Result<_>>()
+            .map(AvroType::Union),
+        Value::Object(object) => {
+            let kind = object
+                .get("type")
+                .ok_or_else(|| Error::Config("Avro schema object has no type".into()))?;
+            let Some(kind) = kind.as_str() else {
+                return parse_type(kind, namespace, named);
+            };
+            if let Some(primitive) = primitive(kind) {
+                return Ok(primitive);
+            }
+            let text = |key: &str| object.get(key).and_then(Value::as_str);
+            match kind {
+                "array" => Ok(AvroType::Array(Box::new(parse_type(
+                    object
+                        .get("items")
+                        .ok_or_else(|| Error::Config("Avro array has no items".into()))?,
+                    namespace,
+                    named,
+                )?))),
+                "map" => Ok(AvroType::Map(Box::new(parse_type(
+                    object
+                        .get("values")
+                        .ok_or_else(|| Error::Config("Avro map has no values".into()))?,
+                    namespace,
+                    named,
+                )?))),
+                "record" | "error" | "enum" | "fixed" => {
+                    let name = text("name")
+                        .ok_or_else(|| Error::Config(format!("Avro {kind} has no name")))?;
+                    let name = full_name(name, text("namespace").unwrap_or(namespace));
+                    let enclosing = name.rsplit_once('.').map_or("", |(namespace, _)| namespace);
+                    let schema = match kind {
+                        "enum" => AvroType::Enum {
+                            name: name.clone(),
+                            symbols: object
+                                .get("symbols")
+                                .and_then(Value::as_array)
+                                .ok_or_else(|| {
+                                    Error::Config(format!("Avro enum {name} has no symbols"))
+                                })?
+                                .iter()
+                                .map(|symbol| symbol.as_str().unwrap_or_default().to_owned())
+                                .collect(),
+                        },
+                        "fixed" => AvroType::Fixed {
+                            name: name.clone(),
+                            size: object
+                                .get("size")
+                                .and_then(Value::as_u64)
+                                .and_then(|size| usize::try_from(size).ok())
+                                .ok_or_else(|| {
+                                    Error::Config(format!("Avro fixed {name} has no size"))
+                                })?,
+                        },
+                        _ => AvroType::Record {
+                            name: name.clone(),
+                            fields: parse_fields(object, &name, enclosing, named)?,
+                        },
+                    };
+                    named.insert(name.clone(), schema);
+                    Ok(AvroType::Named(name))
+                }
+                other => Ok(AvroType::Named(full_name(other, namespace))),
+            }
+        }
+        other => Err(Error::Config(format!("invalid Avro schema {other}"))),
+    }
+}
+
+fn parse_fields(
+    record: &serde_json::Map<String, Value>,
+    name: &str,
+    namespace: &str,
+    named: &mut HashMap<String, AvroType>,
+) -> Result<Vec<AvroField>> {
+    let fields = record
+        .get("fields")
+        .and_then(Value::as_array)
+        .ok_or_else(|| Error::Config(format!("Avro record {name} has no fields")))?;
+    fields
+        .iter()
+        .map(|field| {
+            let field_name = field.get("name").and_then(Value::as_str).ok_or_else(|| {
+                Error::Config(format!("Avro record {name} has a field without a name"))
+            })?;
+            let schema = field.get("type").ok_or_else(|| {
+                Error::Config(format!("Avro field {name}.{field_name} has no type"))
+            })?;
+            Ok(AvroField {
+                name: field_name.to_owned(),
+                schema: parse_type(schema, namespace, named)?,
+                default: field.get("default").cloned(),
+            })
+        })
+        .collect()
+}
+
+fn write_long(output: &mut Vec<u8>, value: i64) {
+    write_varint(output, ((value << 1) ^ (value >> 63)).cast_unsigned());
+}
+
+/// Writes a count, length, or index, which Avro encodes as a non-negative long.
+fn write_unsigned(output: &mut Vec<u8>, value: usize) {
+    write_varint(output, (value as u64) << 1);
+}
+
+fn write_varint(output: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        output.push((value & 0x7f) as u8 | 0x80);
+        value >>= 7;
+    }
+    output.push((value & 0x7f) as u8);
+}
+
+fn write_bytes(output: &mut Vec<u8>, bytes: &[u8]) {
+    write_unsigned(output, bytes.len());
+    output.extend_from_slice(bytes);
+}
+
+fn from_latin1(text: &str) -> Option<Vec<u8>> {
+    text.chars()
+        .map(|character| u8::try_from(u32::from(character)).ok())
+        .collect()
+}
+
+fn latin1(bytes: &[u8]) -> String {
+    bytes.iter().copied().map(char::from).collect()
+}
+
+struct AvroReader<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> AvroReader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        if self.buffer.len() < length {
+            return Err(Error::Config("truncated Avro datum".into()));
+        }
+        let (head, tail) = self.buffer.split_at(length);
+        self.buffer = tail;
+        Ok(head)
+    }
+
+    fn string(&mut self) -> Result<String> {
+        String::from_utf8(self.bytes()?.to_vec())
+            .map_err(|_| Error::Config("Avro string is not valid UTF-8".into()))
+    }
+
+    fn fixed<const LENGTH: usize>(&mut self) -> Result<[u8; LENGTH]> {
+        let mut bytes = [0; LENGTH];
+        bytes.copy_from_slice(self.take(LENGTH)?);
+        Ok(bytes)
+    }
+
+    fn long(&mut self) -> Result<i64> {
+        let mut value = 0_u64;
+        for shift in (0..70).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                let magnitude = (value >> 1).cast_signed();
+                return Ok(if value & 1 == 0 {
+                    magnitude
+                } else {
+                    !magnitude
+                });
+            }
+        }
+        Err(Error::Config("Avro varint is too long".into()))
+    }
+
+    fn int(&mut self) -> Result<i32> {
+        let value = self.long()?;
+        i32::try_from(value).map_err(|_| Error::Config(format!("Avro int {value} is out of range")))
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8]> {
+        let length = self.long()?;
+        let length = usize::try_from(length)
+            .map_err(|_| Error::Config(format!("invalid Avro length {length}")))?;
+        self.take(length)
+    }
+
+    /// Returns the item count of the next array or map block, or `None` at the end marker.
+    fn block_count(&mut self) -> Result<Option<u64>> {
+        let count = self.long()?;
+        if count < 0 {
+            // A negative count is followed by the block's size in bytes.
+            self.long()?;
+        }
+        Ok((count != 0).then_some(count.unsigned_abs()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn long(value: i64) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        write_long(&mut bytes, value);
+        bytes
+    }
+
+    fn string(value: &str) -> Vec<u8> {
+        let mut bytes = long(i64::try_from(value.len()).expect("short string"));
+        bytes.extend_from_slice(value.as_bytes());
+        bytes
+    }
+
+    #[test]
+    fn avro_longs_should_round_trip_through_zigzag_varints() {
+        for value in [i64::MIN, -65, -1, 0, 1, 64, i64::MAX] {
+            let bytes = long(value);
+            let mut reader = AvroReader { buffer: &bytes };
+            assert_eq!(reader.long().expect("decodes"), value);
+            assert!(reader.buffer.is_empty());
+        }
+        assert_eq!(long(-1), [1]);
+        assert_eq!(
+            long(i64::MIN),
+            [0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 1]
+        );
+        assert_eq!(
+            long(i64::MAX),
+            [0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 1]
+        );
+    }
+
+    #[test]
+    fn avro_schema_should_decode_nested_records_in_field_order() {
+        let schema = AvroSchema::parse(
+            r#"{"type":"record","name":"User","namespace":"com.example","fields":[
+                {"name":"name","type":"string"},
+                {"name":"age","type":"int"},
+                {"name":"email","type":["null","string"]},
+                {"name":"kind","type":{"type":"enum","name":"Kind","symbols":["ADMIN","GUEST"]}},
+                {"name":"tags","type":{"type":"array","items":"string"}},
+                {"name":"scores","type":{"type":"map","values":"long"}},
+                {"name":"next","type":["null","User"]}
+            ]}"#,
+            &[],
+        )
+        .expect("schema parses");
+        let mut datum = string("ann");
+        datum.extend(long(42));
+        datum.extend(long(1));
+        datum.extend(string("ann@example.com"));
+        datum.extend(long(1));
+        datum.extend(long(2));
+        datum.extend(string("a"));
+        datum.extend(string("b"));
+        datum.extend(long(0));
+        datum.extend(long(-1));
+        datum.extend(long(4));
+        datum.extend(string("x"));
+        datum.extend(long(7));
+        datum.extend(long(0));
+        datum.extend(long(1));
+        datum.extend(string("bob"));
+        datum.extend(long(3));
+        datum.extend(long(0));
+        datum.extend(long(0));
+        datum.extend(long(0));
+        datum.extend(long(0));
+        datum.extend(long(0));
+
+        let decoded = schema.decode(&datum).expect("datum decodes");
+
+        assert_eq!(
+            serde_json::to_string(&decoded).expect("serializes"),
+            r#"{"name":"ann","age":42,"email":{"string":"ann@example.com"},"kind":"GUEST","tags":["a","b"],"scores":{"x":7},"next":{"com.example.User":{"name":"bob","age":3,"email":null,"kind":"ADMIN","tags":[],"scores":{},"next":null}}}"#
+        );
+    }
+
+    #[test]
+    fn avro_schema_should_resolve_referenced_named_types() {
+        let schema = AvroSchema::parse(
+            r#"{"type":"record","name":"Order","namespace":"shop","fields":[{"name":"item","type":"Item"}]}"#,
+            &[r#"{"type":"record","name":"Item","namespace":"shop","fields":[{"name":"sku","type":"string"},{"name":"price","type":"double"}]}"#],
+        )
+        .expect("schema parses");
+        let mut datum = string("A1");
+        datum.extend(2.5_f64.to_le_bytes());
+
+        assert_eq!(
+            serde_json::to_string(&schema.decode(&datum).expect("datum decodes"))
+                .expect("serializes"),
+            r#"{"item":{"sku":"A1","price":2.5}}"#
+        );
+        assert!(schema.decode(&[2, b'A', 0]).is_err());
+    }
+
+    #[test]
+    fn avro_schema_should_encode_validated_json_datums() {
+        let schema = AvroSchema::parse(
+            r#"{"type":"record","name":"Event","namespace":"app","fields":[
+                {"name":"id","type":"long"},
+                {"name":"note","type":["null","string"],"default":null},
+                {"name":"level","type":{"type":"enum","name":"Level","symbols":["LOW","HIGH"]},"default":"LOW"},
+                {"name":"labels","type":{"type":"map","values":"int"}},
+                {"name":"payload","type":["null","bytes","app.Level"]}
+            ]}"#,
+            &[],
+        )
+        .expect("schema parses");
+        let datum = serde_json::json!({
+            "id": -3,
+            "labels": {"a": 1},
+            "payload": {"bytes": "\u{ff}"},
+        });
+
+        let encoded = schema.encode(&datum).expect("datum encodes");
+
+        let mut expected = long(-3);
+        expected.extend(long(0));
+        expected.extend(long(0));
+        expected.extend(long(1));
+        expected.extend(string("a"));
+        expected.extend(long(1));
+        expected.extend(long(0));
+        expected.extend(long(1));
+        expected.extend([2, 0xff]);
+        assert_eq!(encoded, expected);
+        assert_eq!(
+            serde_json::to_string(&schema.decode(&encoded).expect("decodes")).expect("json"),
+            r#"{"id":-3,"note":null,"level":"LOW","labels":{"a":1},"payload":{"bytes":"ÿ"}}"#
+        );
+        let bare_branch =
+            serde_json::json!({"id": 1, "labels": {}, "payload": "HIGH", "note": "n"});
+        assert!(schema.encode(&bare_branch).is_ok());
+        for invalid in [
+            serde_json::json!({"labels": {}, "payload": null}),
+            serde_json::json!({"id": "1", "labels": {}, "payload": null}),
+            serde_json::json!({"id": 1, "labels": {}, "payload": null, "extra": true}),
+            serde_json::json!({"id": 1, "labels": {}, "payload": 5}),
+        ] {
+            assert!(
+                matches!(schema.encode(&invalid), Err(Error::Usage(_))),
+                "{invalid}"
+            );
+        }
+    }
+}
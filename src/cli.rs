@@ -4,6 +4,7 @@ use std::{ffi::OsString, path::PathBuf, time::Duration};
 
 use clap::{Args, Parser, Subcommand, ValueEnum};
 
+pub use crate::backup::BackupFormat;
 use crate::output::OutputFormat;
 
 fn parse_consumer_timeout(value: &str) -> Result<u64, String> {
@@ -13,6 +14,36 @@ fn parse_consumer_timeout(value: &str) -> Result<u64, String> {
     Ok(u64::try_from(timeout).unwrap_or(u64::MAX))
 }
 
+#[expect(
+    clippy::float_cmp,
+    reason = "-1 is an exact sentinel parsed from the argument"
+)]
+fn parse_mirror_throughput(value: &str) -> Result<f64, String> {
+    let throughput = value
+        .parse::<f64>()
+        .map_err(|error| format!("invalid throughput '{value}': {error}"))?;
+    if throughput == -1.0 || (throughput > 0.0 && throughput.is_finite()) {
+        Ok(throughput)
+    } else {
+        Err(format!(
+            "throughput must be -1 or greater than 0, got {value}"
+        ))
+    }
+}
+
+fn parse_skew_threshold(value: &str) -> Result<f64, String> {
+    let threshold = value
+        .parse::<f64>()
+        .map_err(|error| format!("invalid skew threshold '{value}': {error}"))?;
+    if threshold > 1.0 && threshold.is_finite() {
+        Ok(threshold)
+    } else {
+        Err(format!(
+            "skew threshold must be a finite ratio greater than 1, got {value}"
+        ))
+    }
+}
+
 /// Native Kafka command-line client.
 #[derive(Debug, Parser)]
 #[command(name = "kafka", version, about, propagate_version = true)]
@@ -29,9 +60,13 @@ pub struct Cli {
     #[arg(long, global = true)]
     pub timeout_ms: Option<u64>,
 
-    /// Output encoding.
-    #[arg(long, global = true, value_enum, default_value_t)]
-    pub output: OutputFormat,
+    /// Output encoding [default: table].
+    #[arg(long, global = true, value_enum)]
+    pub output: Option<OutputFormat>,
+
+    /// Named connection profile from the contexts file.
+    #[arg(long, global = true, env = "KAFKA_CLI_CONTEXT")]
+    pub context: Option<String>,
 
     /// Increase diagnostic verbosity.
     #[arg(short, long, global = true, action = clap::ArgAction::Count)]
@@ -59,6 +94,12 @@ impl Cli {
         Self::parse_from(args)
     }
 
+    /// Returns the selected output encoding.
+    #[must_use]
+    pub fn output(&self) -> OutputFormat {
+        self.output.unwrap_or_default()
+    }
+
     /// Returns the configured timeout.
     #[must_use]
     pub const fn timeout(&self) -> Duration {
@@ -69,6 +110,20 @@ impl Cli {
     }
 }
 
+impl Command {
+    /// Returns the `--bootstrap-controller` slot of commands that accept controller bootstrap.
+    pub(crate) const fn bootstrap_controller_mut(&mut self) -> Option<&mut Option<String>> {
+        match self {
+            Self::Features(args) => Some(&mut args.bootstrap_controller),
+            Self::MetadataQuorum(args) => Some(&mut args.bootstrap_controller),
+            Self::Configs(args) => Some(&mut args.bootstrap_controller),
+            Self::Cluster(args) => Some(&mut args.bootstrap_controller),
+            Self::Reassign(args) => Some(&mut args.bootstrap_controller),
+            _ => None,
+        }
+    }
+}
+
 fn compatibility_command(executable: &str) -> Option<&'static str> {
     let name = executable.strip_suffix(".sh").unwrap_or(executable);
     match name {
@@ -86,6 +141,7 @@ fn compatibility_command(executable: &str) -> Option<&'static str> {
         "kafka-replica-verification" => Some("replica-verification"),
         "kafka-dump-log" => Some("dump-log"),
         "kafka-storage" => Some("storage"),
+        "kafka-metadata-shell" => Some("metadata-shell"),
         "kafka-consumer-groups" => Some("groups"),
         "kafka-groups" => Some("all-groups"),
         "kafka-share-groups" => Some("share-groups"),
@@ -383,7 +439,7 @@ pub enum Command {
     /// Consume records and emit Kafka system-test JSON events.
     VerifiableConsumer(VerifiableConsumerArgs),
     /// Consume records to stdout.
-    Consume(ConsumeArgs),
+    Consume(Box<ConsumeArgs>),
     /// Measure classic Kafka consumer throughput.
     ConsumerPerfTest(ConsumerPerfTestArgs),
     /// Consume records through a Kafka Share group.
@@ -394,10 +450,20 @@ pub enum Command {
     VerifiableShareConsumer(VerifiableShareConsumerArgs),
     /// Verify that all replicas for selected topics contain the same data.
     ReplicaVerification(ReplicaVerificationArgs),
+    /// Snapshot topic records and configs into a local directory.
+    Backup(BackupArgs),
+    /// Replay a backup directory into a cluster.
+    Restore(RestoreArgs),
+    /// Copy records from topics in this cluster into another cluster.
+    Mirror(Box<MirrorArgs>),
+    /// Find records matching key, header, or value patterns.
+    Search(SearchArgs),
     /// Dump Kafka log segment, index, and time-index files.
     DumpLog(DumpLogArgs),
     /// Format and inspect `KRaft` storage directories.
     Storage(StorageArgs),
+    /// Browse an offline `KRaft` metadata log directory.
+    MetadataShell(MetadataShellArgs),
     /// Inspect and manage consumer groups.
     Groups(GroupsArgs),
     /// List groups of every Kafka group type.
@@ -410,6 +476,10 @@ pub enum Command {
     StreamsApplicationReset(Box<StreamsApplicationResetArgs>),
     /// Inspect and alter dynamic configuration.
     Configs(ConfigsArgs),
+    /// Check how both client transports interpret the command config file.
+    Config(ClientConfigArgs),
+    /// List, select, and show named connection contexts.
+    Context(ContextArgs),
     /// Query partition offsets.
     Offsets(OffsetsArgs),
     /// Inspect and manage ACLs.
@@ -696,6 +766,8 @@ pub enum TopicAction {
     Create(CreateTopicArgs),
     Alter(AlterTopicArgs),
     Delete(DeleteTopicArgs),
+    /// Sample records to report volume, rate, key, size, and compression statistics.
+    Stats(TopicStatsArgs),
 }
 
 #[derive(Debug, Args)]
@@ -744,6 +816,39 @@ pub struct DescribeTopicArgs {
     /// Maximum partitions requested per `DescribeTopicPartitions` response.
     #[arg(long, value_parser = clap::value_parser!(i32).range(1..))]
     pub partition_size_limit_per_response: Option<i32>,
+    /// Flag partitions whose log size, end offset, or offset growth is far from the topic mean,
+    /// and brokers leading a disproportionate share of a topic's partitions.
+    #[arg(long, conflicts_with = "topics_with_overrides")]
+    pub skew: bool,
+    /// Ratio to the topic mean (or its inverse) at which --skew flags a partition or leader.
+    #[arg(long, default_value_t = 2.0, requires = "skew", value_parser = parse_skew_threshold)]
+    pub skew_threshold: f64,
+    /// Measure offset growth by listing end offsets twice, this many milliseconds apart.
+    #[arg(long, requires = "skew")]
+    pub growth_interval_ms: Option<u64>,
+}
+
+#[derive(Debug, Args)]
+pub struct TopicStatsArgs {
+    #[arg(long)]
+    pub topic: String,
+    /// Records read from the end of each partition.
+    #[arg(long, default_value_t = 1_000, value_parser = clap::value_parser!(u64).range(1..))]
+    pub samples: u64,
+    /// Read every retained record instead of sampling.
+    #[arg(long, conflicts_with = "samples")]
+    pub all: bool,
+    /// Width of the message rate buckets in milliseconds.
+    #[arg(long, default_value_t = 60_000, value_parser = clap::value_parser!(u64).range(1..))]
+    pub bucket_ms: u64,
+    #[arg(long, value_parser = ["read_uncommitted", "read_committed"])]
+    pub isolation_level: Option<String>,
+    /// Fail when no progress is made for this many milliseconds.
+    #[arg(long, default_value_t = 30_000)]
+    pub timeout_ms: u64,
+    /// Consumer property in key=value form; overrides --command-config.
+    #[arg(long = "command-property")]
+    pub properties: Vec<String>,
 }
 
 #[derive(Debug, Args)]
@@ -781,13 +886,44 @@ pub struct AlterTopicArgs {
     #[arg(long)]
     pub topic: String,
     /// New total partition count.
-    #[arg(long)]
-    pub partitions: i32,
+    #[arg(long, required_unless_present = "replication_factor")]
+    pub partitions: Option<i32>,
     /// Full manual assignment including existing and newly added partitions.
     #[arg(long)]
     pub replica_assignment: Option<String>,
     #[arg(long)]
     pub if_exists: bool,
+    /// Plan a rack-aware reassignment that adds or removes replicas until every partition has
+    /// this many.
+    #[arg(long, conflicts_with_all = ["partitions", "replica_assignment"])]
+    pub replication_factor: Option<i32>,
+    #[arg(long, requires = "replication_factor")]
+    pub disable_rack_aware: bool,
+    /// Also write the replication-factor reassignment JSON to this file.
+    #[arg(long, requires = "replication_factor")]
+    pub output_json_file: Option<PathBuf>,
+    /// Start the replication-factor reassignment and verify it until every partition has moved.
+    #[arg(long, requires = "replication_factor")]
+    pub execute: bool,
+    /// Inter-broker replication throttle in bytes per second while executing.
+    #[arg(long, requires = "execute")]
+    pub throttle: Option<u64>,
+    /// Delay between verification checks while executing.
+    #[arg(
+        long,
+        default_value_t = 10_000,
+        requires = "execute",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub verify_interval_ms: u64,
+    /// Fail when no partition finishes moving for this many milliseconds while executing.
+    #[arg(
+        long,
+        default_value_t = 3_600_000,
+        requires = "execute",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub stall_timeout_ms: u64,
 }
 
 #[derive(Debug, Args)]
@@ -842,6 +978,36 @@ pub struct ProduceArgs {
     /// Parse each input line as {"key":...,"value":...,"partition":...,"headers":{...}}.
     #[arg(long)]
     pub json: bool,
+    /// Encoding of `key` members in --json input.
+    #[arg(long, value_enum, default_value_t, requires = "json")]
+    pub key_encoding: DataEncoding,
+    /// Encoding of `value` members in --json input.
+    #[arg(long, value_enum, default_value_t, requires = "json")]
+    pub value_encoding: DataEncoding,
+    /// Encoding of header values in --json input.
+    #[arg(long, value_enum, default_value_t, requires = "json")]
+    pub header_encoding: DataEncoding,
+    /// Schema Registry URL used to register or look up key and value schemas.
+    #[arg(long)]
+    pub schema_registry_url: Option<String>,
+    /// Avro (`.avsc`) or Protobuf (`.proto`) schema used to encode JSON keys.
+    #[arg(long)]
+    pub key_schema: Option<PathBuf>,
+    /// Avro (`.avsc`) or Protobuf (`.proto`) schema used to encode JSON values.
+    #[arg(long)]
+    pub value_schema: Option<PathBuf>,
+    /// Registry subject for key schemas; defaults to `<topic>-key`.
+    #[arg(long)]
+    pub key_subject: Option<String>,
+    /// Registry subject for value schemas; defaults to `<topic>-value`.
+    #[arg(long)]
+    pub value_subject: Option<String>,
+    /// Fully qualified Protobuf key message; defaults to the first declared message.
+    #[arg(long)]
+    pub key_message: Option<String>,
+    /// Fully qualified Protobuf value message; defaults to the first declared message.
+    #[arg(long)]
+    pub value_message: Option<String>,
     /// Default `LineMessageReader` property in key=value form.
     #[arg(
         long = "reader-property",
@@ -880,6 +1046,40 @@ impl ProduceArgs {
     }
 }
 
+/// Text representation of record keys, values, and header values in JSON records.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum DataEncoding {
+    /// UTF-8 text; consumers replace invalid sequences.
+    #[default]
+    Utf8,
+    Base64,
+    Hex,
+    /// Path of a file holding the raw bytes.
+    File,
+}
+
+/// Compression applied to `consume --output-file` segments.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
+pub enum OutputCompression {
+    #[default]
+    None,
+    Gzip,
+    Zstd,
+}
+
+/// Column written by `consume --csv`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum CsvColumn {
+    Topic,
+    Partition,
+    Offset,
+    Timestamp,
+    Key,
+    Value,
+    /// Header names and values as a JSON object.
+    Headers,
+}
+
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq, ValueEnum)]
 pub enum ProducerKeyDistribution {
     #[default]
@@ -1041,23 +1241,34 @@ pub struct DumpLogArgs {
     /// Value decoder class; only `StringDecoder` is supported natively.
     #[arg(long = "value-decoder-class")]
     pub value_decoder_class: Option<String>,
-    /// Parse `__consumer_offsets` records (not implemented natively).
+    /// Decode `__consumer_offsets` group metadata and offset commit records as JSON.
     #[arg(long = "offsets-decoder")]
     pub offsets_decoder: bool,
-    /// Parse `__transaction_state` records (not implemented natively).
+    /// Decode `__transaction_state` transaction metadata records as JSON.
     #[arg(long = "transaction-log-decoder")]
     pub transaction_log_decoder: bool,
-    /// Parse cluster metadata records (not implemented natively).
+    /// Decode `KRaft` `__cluster_metadata` records from segments and snapshots as JSON.
     #[arg(long = "cluster-metadata-decoder")]
     pub cluster_metadata_decoder: bool,
-    /// Parse remote log metadata records (not implemented natively).
+    /// Decode `__remote_log_metadata` tiered storage segment metadata records as JSON.
     #[arg(long = "remote-log-metadata-decoder")]
     pub remote_log_metadata_decoder: bool,
-    /// Parse share group state records (not implemented natively).
+    /// Decode `__share_group_state` share-partition snapshot and update records as JSON.
     #[arg(long = "share-group-state-decoder")]
     pub share_group_state_decoder: bool,
 }
 
+/// Options for Kafka's metadata shell.
+#[derive(Debug, Args)]
+pub struct MetadataShellArgs {
+    /// `KRaft` metadata log directory, or a single snapshot or segment file, to replay.
+    #[arg(long)]
+    pub snapshot: PathBuf,
+    /// Command to run non-interactively, for example `ls /image/topics/byName`.
+    #[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+    pub command: Vec<String>,
+}
+
 /// Options for Kafka's storage tool.
 #[derive(Debug, Args)]
 pub struct StorageArgs {
@@ -1135,6 +1346,193 @@ pub struct ReplicaVerificationArgs {
     pub report_interval_ms: i64,
 }
 
+#[derive(Debug, Args)]
+pub struct BackupArgs {
+    /// Topic to back up; repeat for several topics.
+    #[arg(long, required = true)]
+    pub topic: Vec<String>,
+    /// Directory receiving the manifest and one compressed record file per topic.
+    #[arg(long)]
+    pub output_dir: PathBuf,
+    #[arg(long, value_enum, default_value_t)]
+    pub format: BackupFormat,
+    #[arg(long)]
+    pub group: Option<String>,
+    #[arg(long, value_parser = ["read_uncommitted", "read_committed"])]
+    pub isolation_level: Option<String>,
+    /// Fail when no progress is made for this many milliseconds.
+    #[arg(long, default_value_t = 30_000)]
+    pub timeout_ms: u64,
+    /// Consumer property in key=value form; overrides --command-config.
+    #[arg(long = "command-property")]
+    pub properties: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct RestoreArgs {
+    /// Backup directory written by `backup`.
+    #[arg(long)]
+    pub input_dir: PathBuf,
+    /// Restore only this topic; repeat for several topics. Defaults to every backed-up topic.
+    #[arg(long)]
+    pub topic: Vec<String>,
+    /// Create missing topics with the backed-up partition count and configs.
+    #[arg(long)]
+    pub create_topics: bool,
+    /// Replication factor for created topics instead of the backed-up one.
+    #[arg(long, requires = "create_topics")]
+    pub replication_factor: Option<i32>,
+    /// Produce records with their original timestamps instead of the restore time.
+    #[arg(long)]
+    pub preserve_timestamps: bool,
+    #[arg(
+        long,
+        default_value = "none",
+        value_parser = ["none", "gzip", "snappy", "lz4", "zstd"]
+    )]
+    pub compression_type: String,
+    #[arg(long)]
+    pub acks: Option<String>,
+    #[arg(long)]
+    pub batch_size: Option<usize>,
+    #[arg(long)]
+    pub linger_ms: Option<u64>,
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub request_timeout_ms: Option<u64>,
+    #[arg(long)]
+    pub max_block_ms: Option<u64>,
+    /// Producer property in key=value form; overrides --command-config.
+    #[arg(long = "command-property")]
+    pub properties: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct MirrorArgs {
+    /// Source topic to copy; repeat for several topics.
+    #[arg(long, required = true)]
+    pub topic: Vec<String>,
+    /// Bootstrap servers of the target cluster.
+    #[arg(long)]
+    pub target_bootstrap_server: String,
+    /// Client properties file for the target cluster.
+    #[arg(long)]
+    pub target_command_config: Option<PathBuf>,
+    /// Target topic for a source topic in SOURCE=TARGET form.
+    #[arg(long)]
+    pub rename_topic: Vec<String>,
+    /// Target partition for a source partition in SOURCE=TARGET form; other partitions keep
+    /// their number.
+    #[arg(long, conflicts_with = "repartition")]
+    pub partition_map: Vec<String>,
+    /// Let the target partitioner choose partitions instead of keeping source partitions.
+    #[arg(long)]
+    pub repartition: bool,
+    /// Add or replace a header in KEY=VALUE form.
+    #[arg(long)]
+    pub set_header: Vec<String>,
+    /// Rename a header in OLD=NEW form.
+    #[arg(long)]
+    pub rename_header: Vec<String>,
+    /// Remove headers with this key.
+    #[arg(long)]
+    pub drop_header: Vec<String>,
+    /// First offset copied from every partition.
+    #[arg(long, conflicts_with = "from_time")]
+    pub from_offset: Option<i64>,
+    /// Offset at which copying stops in every partition (exclusive).
+    #[arg(long)]
+    pub to_offset: Option<i64>,
+    /// Copy records from this timestamp on: epoch milliseconds or RFC 3339.
+    #[arg(long)]
+    pub from_time: Option<String>,
+    /// Stop each partition at its first record after this timestamp.
+    #[arg(long)]
+    pub to_time: Option<String>,
+    /// Maximum records per second; -1 disables throttling.
+    #[arg(
+        long,
+        default_value_t = -1.0,
+        allow_negative_numbers = true,
+        value_parser = parse_mirror_throughput
+    )]
+    pub throughput: f64,
+    #[arg(long)]
+    pub group: Option<String>,
+    #[arg(long, value_parser = ["read_uncommitted", "read_committed"])]
+    pub isolation_level: Option<String>,
+    /// Fail when no progress is made for this many milliseconds.
+    #[arg(long, default_value_t = 30_000)]
+    pub timeout_ms: u64,
+    #[arg(
+        long,
+        default_value = "none",
+        value_parser = ["none", "gzip", "snappy", "lz4", "zstd"]
+    )]
+    pub compression_type: String,
+    #[arg(long)]
+    pub acks: Option<String>,
+    #[arg(long)]
+    pub batch_size: Option<usize>,
+    #[arg(long)]
+    pub linger_ms: Option<u64>,
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    pub request_timeout_ms: Option<u64>,
+    #[arg(long)]
+    pub max_block_ms: Option<u64>,
+    /// Source consumer property in key=value form; overrides --command-config.
+    #[arg(long = "consumer-property")]
+    pub consumer_properties: Vec<String>,
+    /// Target producer property in key=value form; overrides --target-command-config.
+    #[arg(long = "producer-property")]
+    pub producer_properties: Vec<String>,
+}
+
+#[derive(Debug, Args)]
+pub struct SearchArgs {
+    /// Topic to search; repeat for several topics.
+    #[arg(long, required_unless_present = "include", conflicts_with = "include")]
+    pub topic: Vec<String>,
+    /// Full-match regular expression selecting topics to search.
+    #[arg(long)]
+    pub include: Option<String>,
+    /// Regular expression matched against record keys.
+    #[arg(long)]
+    pub key: Option<String>,
+    /// Regular expression matched against record values.
+    #[arg(long)]
+    pub value: Option<String>,
+    /// Header in NAME=PATTERN form whose value must match the regular expression; repeat to
+    /// require several.
+    #[arg(long)]
+    pub header: Vec<String>,
+    /// First offset searched in every partition.
+    #[arg(long, conflicts_with = "from_time")]
+    pub from_offset: Option<i64>,
+    /// Offset at which searching stops in every partition (exclusive).
+    #[arg(long)]
+    pub to_offset: Option<i64>,
+    /// Search records from this timestamp on: epoch milliseconds or RFC 3339.
+    #[arg(long)]
+    pub from_time: Option<String>,
+    /// Search records up to this timestamp: epoch milliseconds or RFC 3339.
+    #[arg(long)]
+    pub to_time: Option<String>,
+    /// Stop after this many hits.
+    #[arg(long)]
+    pub max_hits: Option<usize>,
+    /// Number of consumers scanning partitions concurrently.
+    #[arg(long, default_value_t = 4, value_parser = clap::value_parser!(u16).range(1..))]
+    pub parallelism: u16,
+    #[arg(long, value_parser = ["read_uncommitted", "read_committed"])]
+    pub isolation_level: Option<String>,
+    /// Fail when no progress is made for this many milliseconds.
+    #[arg(long, default_value_t = 30_000)]
+    pub timeout_ms: u64,
+    /// Consumer property in key=value form; overrides --command-config.
+    #[arg(long = "command-property")]
+    pub properties: Vec<String>,
+}
+
 #[derive(Debug, Args)]
 pub struct VerifiableConsumerArgs {
     #[arg(long)]
@@ -1192,6 +1590,16 @@ pub struct ConsumeArgs {
     /// Kafka value deserializer class used by the default formatter.
     #[arg(long)]
     pub value_deserializer: Option<String>,
+    /// Schema Registry URL used by the Confluent Avro, Protobuf, and JSON Schema deserializers.
+    #[arg(long)]
+    pub schema_registry_url: Option<String>,
+    /// Directory of `<id>.avsc`, `<id>.proto`, or `<id>.json` schemas used without a registry.
+    #[arg(long)]
+    pub schema_dir: Option<PathBuf>,
+    /// Print each record through a template such as
+    /// `{topic}/{partition}@{offset} {timestamp:iso} {header.trace-id} {value}`.
+    #[arg(long, conflicts_with = "json")]
+    pub format_template: Option<String>,
     #[arg(long, requires = "topic", conflicts_with = "group", value_parser = clap::value_parser!(i32).range(0..))]
     pub partition: Option<i32>,
     /// Numeric offset, `earliest`, or `latest`; valid only with --partition.
@@ -1199,6 +1607,15 @@ pub struct ConsumeArgs {
     pub offset: Option<String>,
     #[arg(long, conflicts_with = "offset")]
     pub from_beginning: bool,
+    /// Start each partition at its first record at or after this time: epoch milliseconds or
+    /// RFC 3339.
+    #[arg(long, requires = "topic", conflicts_with_all = ["offset", "from_beginning"])]
+    pub from_time: Option<String>,
+    /// Stop each partition at its first record after this time and exit once every partition
+    /// has stopped.
+    #[arg(long, requires = "topic")]
+    pub to_time: Option<String>,
+    /// Exit after printing this many records; only records passing --filter-* count.
     #[arg(long, allow_negative_numbers = true)]
     pub max_messages: Option<i32>,
     /// Exit successfully after this many milliseconds without a message.
@@ -1213,8 +1630,56 @@ pub struct ConsumeArgs {
     /// Continue after a Kafka message/poll error.
     #[arg(long)]
     pub skip_message_on_error: bool,
+    /// Print only records whose key matches this regular expression.
+    #[arg(long)]
+    pub filter_key: Option<String>,
+    /// Print only records whose value matches this regular expression.
+    #[arg(long)]
+    pub filter_value: Option<String>,
+    /// Print only records carrying this header in NAME=VALUE form; repeat to require several.
+    #[arg(long)]
+    pub filter_header: Vec<String>,
+    /// Print only records timestamped at or after this time: epoch milliseconds or RFC 3339.
+    #[arg(long)]
+    pub filter_from_time: Option<String>,
+    /// Print only records timestamped at or before this time: epoch milliseconds or RFC 3339.
+    #[arg(long)]
+    pub filter_to_time: Option<String>,
+    /// Print only records whose JSON value has PATH=VALUE, such as `$.order.items[0].sku="A1"`;
+    /// VALUE is JSON or a bare string.
+    #[arg(long)]
+    pub filter_json: Vec<String>,
     #[arg(long)]
     pub json: bool,
+    /// Encoding of keys in --json output.
+    #[arg(long, value_enum, default_value_t, requires = "json")]
+    pub key_encoding: DataEncoding,
+    /// Encoding of values in --json output.
+    #[arg(long, value_enum, default_value_t, requires = "json")]
+    pub value_encoding: DataEncoding,
+    /// Encoding of header values in --json output.
+    #[arg(long, value_enum, default_value_t, requires = "json")]
+    pub header_encoding: DataEncoding,
+    /// Directory receiving payload files written by the `file` encoding.
+    #[arg(long, requires = "json")]
+    pub payload_dir: Option<PathBuf>,
+    /// Print records as CSV rows with a header row.
+    #[arg(long, conflicts_with_all = ["json", "format_template"])]
+    pub csv: bool,
+    /// Comma-separated CSV columns; defaults to every column.
+    #[arg(long, value_enum, value_delimiter = ',', requires = "csv")]
+    pub csv_columns: Vec<CsvColumn>,
+    /// Write records to this file instead of stdout.
+    #[arg(long)]
+    pub output_file: Option<PathBuf>,
+    /// Start a new numbered output file once the current one holds this many uncompressed bytes.
+    #[arg(long, requires = "output_file", value_parser = clap::value_parser!(u64).range(1..))]
+    pub rotate_bytes: Option<u64>,
+    /// Start a new numbered output file once the current one is this many milliseconds old.
+    #[arg(long, requires = "output_file", value_parser = clap::value_parser!(u64).range(1..))]
+    pub rotate_interval_ms: Option<u64>,
+    #[arg(long, value_enum, default_value_t, requires = "output_file")]
+    pub output_compression: OutputCompression,
     #[arg(long)]
     pub print_key: bool,
     #[arg(long, default_value = "\t")]
@@ -1348,8 +1813,30 @@ pub struct ShareConsumeArgs {
     pub key_deserializer: Option<String>,
     #[arg(long)]
     pub value_deserializer: Option<String>,
+    /// Schema Registry URL used by the Confluent Avro, Protobuf, and JSON Schema deserializers.
+    #[arg(long)]
+    pub schema_registry_url: Option<String>,
+    /// Directory of `<id>.avsc`, `<id>.proto`, or `<id>.json` schemas used without a registry.
+    #[arg(long)]
+    pub schema_dir: Option<PathBuf>,
+    /// Print each record through a template such as
+    /// `{topic}/{partition}@{offset} {timestamp:iso} {header.trace-id} {value}`.
+    #[arg(long, conflicts_with = "json")]
+    pub format_template: Option<String>,
     #[arg(long)]
     pub json: bool,
+    /// Encoding of keys in --json output.
+    #[arg(long, value_enum, default_value_t, requires = "json")]
+    pub key_encoding: DataEncoding,
+    /// Encoding of values in --json output.
+    #[arg(long, value_enum, default_value_t, requires = "json")]
+    pub value_encoding: DataEncoding,
+    /// Encoding of header values in --json output.
+    #[arg(long, value_enum, default_value_t, requires = "json")]
+    pub header_encoding: DataEncoding,
+    /// Directory receiving payload files written by the `file` encoding.
+    #[arg(long, requires = "json")]
+    pub payload_dir: Option<PathBuf>,
     #[arg(long)]
     pub print_key: bool,
     #[arg(long, default_value = "\t")]
@@ -1880,6 +2367,35 @@ pub struct ResetOffsetsArgs {
     pub dry_run: bool,
 }
 
+#[derive(Debug, Args)]
+pub struct ClientConfigArgs {
+    #[command(subcommand)]
+    pub action: ClientConfigAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ClientConfigAction {
+    /// Report the security settings, librdkafka properties, and admin transport setup
+    /// derived from --command-config.
+    Check,
+}
+
+#[derive(Debug, Args)]
+pub struct ContextArgs {
+    #[command(subcommand)]
+    pub action: ContextAction,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ContextAction {
+    /// List contexts, marking the current one.
+    List,
+    /// Make a context the default for later commands.
+    Use { name: String },
+    /// Show one context; defaults to --context or the current context.
+    Show { name: Option<String> },
+}
+
 #[derive(Debug, Args)]
 pub struct ConfigsArgs {
     /// Connect via a `KRaft` controller listener (routed as the admin bootstrap target).
@@ -2133,6 +2649,64 @@ pub enum ReassignAction {
         execute: bool,
     },
     List,
+    /// Propose the fewest replica moves, plus preferred-leader reordering, that even out
+    /// replica, leader, and byte counts across brokers.
+    Balance {
+        /// Comma-separated topics to balance; defaults to every non-internal topic.
+        #[arg(long)]
+        topics: Option<String>,
+        /// Comma-separated brokers to balance across; defaults to every broker.
+        #[arg(long)]
+        broker_list: Option<String>,
+        /// Maximum number of replicas the plan may move.
+        #[arg(long, default_value_t = 50)]
+        max_moves: usize,
+        #[arg(long)]
+        disable_rack_aware: bool,
+        /// Also write the proposed reassignment JSON to this file for `reassign execute`.
+        #[arg(long)]
+        output_json_file: Option<PathBuf>,
+    },
+    /// Plan moving every replica off a broker before it is decommissioned.
+    Drain(BrokerMoveArgs),
+    /// Plan moving replicas onto a newly added broker until it carries an even share.
+    Fill(BrokerMoveArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct BrokerMoveArgs {
+    #[arg(long)]
+    pub broker: i32,
+    /// Comma-separated topics to move; defaults to every non-internal topic.
+    #[arg(long)]
+    pub topics: Option<String>,
+    #[arg(long)]
+    pub disable_rack_aware: bool,
+    /// Also write the proposed reassignment JSON to this file.
+    #[arg(long)]
+    pub output_json_file: Option<PathBuf>,
+    /// Start the plan and verify it until every partition has moved.
+    #[arg(long)]
+    pub execute: bool,
+    /// Inter-broker replication throttle in bytes per second while executing.
+    #[arg(long, requires = "execute")]
+    pub throttle: Option<u64>,
+    /// Delay between verification checks while executing.
+    #[arg(
+        long,
+        default_value_t = 10_000,
+        requires = "execute",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub verify_interval_ms: u64,
+    /// Fail when no partition finishes moving for this many milliseconds while executing.
+    #[arg(
+        long,
+        default_value_t = 3_600_000,
+        requires = "execute",
+        value_parser = clap::value_parser!(u64).range(1..)
+    )]
+    pub stall_timeout_ms: u64,
 }
 
 #[derive(Debug, Args)]
@@ -2547,6 +3121,47 @@ mod tests {
         assert_eq!(args.properties.len(), 2);
     }
 
+    #[test]
+    fn mirror_throughput_should_be_disabled_or_positive() {
+        let parse = |throughput: &str| {
+            Cli::try_parse_from([
+                "kafka",
+                "mirror",
+                "--topic",
+                "orders",
+                "--target-bootstrap-server",
+                "target:9092",
+                "--throughput",
+                throughput,
+            ])
+        };
+        assert!(parse("-1").is_ok());
+        assert!(parse("2.5").is_ok());
+        for rejected in ["0", "-2", "NaN", "inf"] {
+            assert!(parse(rejected).is_err(), "{rejected}");
+        }
+    }
+
+    #[test]
+    fn skew_threshold_should_be_a_finite_ratio_above_one() {
+        let parse = |threshold: &str| {
+            Cli::try_parse_from([
+                "kafka",
+                "topics",
+                "describe",
+                "--topic",
+                "orders",
+                "--skew",
+                "--skew-threshold",
+                threshold,
+            ])
+        };
+        assert!(parse("1.5").is_ok());
+        for rejected in ["1", "0.5", "-2", "NaN", "inf"] {
+            assert!(parse(rejected).is_err(), "{rejected}");
+        }
+    }
+
     #[test]
     fn producer_perf_should_require_exactly_one_payload_source() {
         let missing = Cli::try_parse_from([
@@ -3000,6 +3615,26 @@ mod tests {
         assert_eq!(consumer.timeout_ms, None);
     }
 
+    #[test]
+    fn consumer_rotation_limits_should_be_positive() {
+        for (flag, value) in [("--rotate-bytes", "0"), ("--rotate-interval-ms", "0")] {
+            let parse = |value| {
+                Cli::try_parse_from([
+                    "kafka",
+                    "consume",
+                    "--topic",
+                    "events",
+                    "--output-file",
+                    "capture.jsonl",
+                    flag,
+                    value,
+                ])
+            };
+            assert!(parse(value).is_err(), "{flag} {value}");
+            assert!(parse("1").is_ok(), "{flag} 1");
+        }
+    }
+
     #[test]
     fn deprecated_console_properties_should_keep_their_values() {
         let producer = Cli::try_parse_from([
@@ -1,11 +1,12 @@
 //! Kafka command implementations.
 
 use std::{
+    borrow::Cow,
     collections::{BTreeMap, BTreeSet, HashMap, HashSet},
     fs,
     io::{self, Write},
     net::ToSocketAddrs,
-    path::Path,
+    path::{Path, PathBuf},
     process,
     sync::{
         Arc, LazyLock, Mutex, MutexGuard,
@@ -20,9 +21,10 @@ use base64::{
 };
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 use chrono::{
-    DateTime, Local, NaiveDateTime, Utc,
+    DateTime, Local, NaiveDateTime, SecondsFormat, Utc,
     format::{Item, StrftimeItems},
 };
+use clap::ValueEnum as _;
 use futures::StreamExt;
 use krafka::protocol::{
     AlterConfigOp, AlterableConfig, ApiKey, ApiVersionsRequest,
@@ -44,7 +46,7 @@ use rdkafka::{
     client::{ClientContext, DefaultClientContext},
     consumer::{BaseConsumer, CommitMode, Consumer, ConsumerContext, Rebalance, StreamConsumer},
     error::{KafkaError, RDKafkaErrorCode},
-    message::{BorrowedHeaders, Header, Headers, OwnedHeaders, ToBytes},
+    message::{Header, Headers, OwnedHeaders, ToBytes},
     producer::{
         BaseProducer, BaseRecord, DeliveryFuture, DeliveryResult, FutureProducer, FutureRecord,
         Producer, ProducerContext,
@@ -55,25 +57,32 @@ use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 use crate::{
+    backup::{self, BackupManifest, BackupRecord, RecordReader, RecordWriter, TopicBackup},
     cli::{
-        AclAction, AllGroupType, AllGroupsAction, Cli, ClientMetricsAction, ClusterAction, Command,
-        ConfigAction, ConfigEntityArgs, ConfigEntityType, ConsumerGroupProtocol,
-        ConsumerPerfTestArgs, DelegationTokenAction, DescribeTopicArgs, E2eLatencyArgs,
-        ElectionType, FeatureAction, GroupAction, ListTopicArgs, MetadataQuorumAction, OffsetTime,
-        ProducerKeyDistribution, ProducerPerfTestArgs, ReassignAction, ReplicaVerificationArgs,
-        ResetOffsetsArgs, ShareConsumeArgs, ShareConsumerPerfTestArgs, ShareGroupAction,
+        AclAction, AllGroupType, AllGroupsAction, AlterTopicArgs, BackupArgs, BrokerMoveArgs, Cli,
+        ClientConfigAction, ClientMetricsAction, ClusterAction, Command, ConfigAction,
+        ConfigEntityArgs, ConfigEntityType, ConsumerGroupProtocol, ConsumerPerfTestArgs,
+        ContextAction, CsvColumn, DataEncoding, DelegationTokenAction, DescribeTopicArgs,
+        E2eLatencyArgs, ElectionType, FeatureAction, GroupAction, ListTopicArgs,
+        MetadataQuorumAction, MirrorArgs, OffsetTime, ProducerKeyDistribution,
+        ProducerPerfTestArgs, ReassignAction, ReplicaVerificationArgs, ResetOffsetsArgs,
+        RestoreArgs, SearchArgs, ShareConsumeArgs, ShareConsumerPerfTestArgs, ShareGroupAction,
         ShareGroupResetOffsetsArgs, StreamsApplicationResetArgs, StreamsGroupAction,
-        StreamsGroupResetOffsetsArgs, TopicAction, TransactionAction,
+        StreamsGroupResetOffsetsArgs, TopicAction, TopicStatsArgs, TransactionAction,
         VerifiableAcknowledgementMode, VerifiableConsumerArgs, VerifiableProducerArgs,
         VerifiableShareConsumerArgs,
     },
     config,
+    context::{self, ContextFile},
     error::{Error, Result},
     ffi::{
         self, AclBinding, AclBindingFilter, AclOperation, AclPatternType, AclPermissionType,
         AclResourceType,
     },
     output::{self, OutputFormat},
+    schema_registry::{RecordEncoder, SchemaResolver, SchemaValue, registry_client},
+    security::{PrivateFiles, SecurityConfig, Setting},
+    sink::{RecordSink, Rotation},
 };
 
 type Admin = AdminClient<DefaultClientContext>;
@@ -106,11 +115,14 @@ fn write_mutation_rows(format: OutputFormat, command: &str, rows: &[MutationRow]
     clippy::large_stack_frames,
     reason = "top-level dispatch explicitly routes every Kafka command family"
 )]
-pub async fn execute(cli: Cli) -> Result<()> {
+pub async fn execute(mut cli: Cli) -> Result<()> {
+    if let Command::Context(args) = &cli.command {
+        return context_command(cli.output(), cli.context.as_deref(), &args.action);
+    }
     if let Command::Groups(args) = &cli.command
         && let GroupAction::ValidateRegex { regex } = &args.action
     {
-        return validate_group_regex(cli.output, regex);
+        return validate_group_regex(cli.output(), regex);
     }
     if let Command::Features(args) = &cli.command
         && matches!(
@@ -118,7 +130,7 @@ pub async fn execute(cli: Cli) -> Result<()> {
             FeatureAction::VersionMapping { .. } | FeatureAction::FeatureDependencies { .. }
         )
     {
-        return features_local(cli.output, &args.action);
+        return features_local(cli.output(), &args.action);
     }
     if let Command::DumpLog(args) = &cli.command {
         return crate::dump_log::dump_log_segments(&crate::dump_log::DumpLogOptions {
@@ -139,6 +151,14 @@ pub async fn execute(cli: Cli) -> Result<()> {
             share_group_state_decoder: args.share_group_state_decoder,
         });
     }
+    if let Command::MetadataShell(args) = &cli.command {
+        return crate::metadata_shell::metadata_shell(
+            &crate::metadata_shell::MetadataShellOptions {
+                snapshot: args.snapshot.clone(),
+                command: args.command.clone(),
+            },
+        );
+    }
     if let Command::Storage(args) = &cli.command {
         return crate::storage_tool::storage(match &args.action {
             crate::cli::StorageAction::RandomUuid => crate::storage_tool::StorageAction::RandomUuid,
@@ -180,6 +200,17 @@ pub async fn execute(cli: Cli) -> Result<()> {
             }
         });
     }
+    // Contexts only describe connections, so local-only commands above never read them.
+    let _context_files = context::apply(&mut cli)?;
+    if let Command::Config(args) = &cli.command {
+        let ClientConfigAction::Check = args.action;
+        return check_client_config(
+            cli.output(),
+            cli.bootstrap_server.as_deref(),
+            cli.command_config.as_deref(),
+        )
+        .await;
+    }
     let is_streams_application_reset = matches!(&cli.command, Command::StreamsApplicationReset(_));
     let allows_property_bootstrap = matches!(&cli.command, Command::ProducerPerfTest(_));
     let replica_broker_list = match &cli.command {
@@ -229,11 +260,21 @@ pub async fn execute(cli: Cli) -> Result<()> {
     .cloned();
     let client_config = config::client_config(bootstrap, command_config.as_deref())?;
     let timeout = cli.timeout();
-    let format = cli.output;
+    let format = cli.output();
     let verbose = cli.verbose > 0;
 
     match cli.command {
-        Command::Topics(args) => topics(&client_config, timeout, format, args.action).await,
+        Command::Topics(args) => {
+            Box::pin(topics(
+                &client_config,
+                bootstrap,
+                command_config.as_deref(),
+                timeout,
+                format,
+                args.action,
+            ))
+            .await
+        }
         Command::Produce(args) => produce(client_config, args).await,
         Command::ProducerPerfTest(args) => {
             producer_perf_test(
@@ -250,7 +291,7 @@ pub async fn execute(cli: Cli) -> Result<()> {
         Command::VerifiableConsumer(args) => {
             verifiable_consumer(bootstrap, command_config.as_deref(), verbose, args).await
         }
-        Command::Consume(args) => consume(client_config, timeout, args).await,
+        Command::Consume(args) => consume(client_config, timeout, *args).await,
         Command::ConsumerPerfTest(args) => {
             consumer_perf_test(bootstrap, command_config.as_deref(), args).await
         }
@@ -283,6 +324,10 @@ pub async fn execute(cli: Cli) -> Result<()> {
             ))
             .await
         }
+        Command::Backup(args) => Box::pin(backup(client_config, timeout, format, args)).await,
+        Command::Restore(args) => Box::pin(restore(client_config, timeout, format, args)).await,
+        Command::Mirror(args) => Box::pin(mirror(client_config, timeout, format, &args)).await,
+        Command::Search(args) => Box::pin(search(client_config, timeout, format, &args)).await,
         Command::ReplicaVerification(args) => {
             Box::pin(replica_verification(
                 bootstrap,
@@ -488,8 +533,319 @@ pub async fn execute(cli: Cli) -> Result<()> {
             .await
         }
         // Handled earlier without a broker bootstrap.
-        Command::DumpLog(_) | Command::Storage(_) => unreachable!("local-only commands"),
+        Command::DumpLog(_)
+        | Command::Storage(_)
+        | Command::MetadataShell(_)
+        | Command::Config(_)
+        | Command::Context(_) => {
+            unreachable!("local-only commands")
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ContextRow {
+    name: String,
+    current: bool,
+    bootstrap_server: Option<String>,
+    bootstrap_controller: Option<String>,
+    command_config: Option<String>,
+    properties: BTreeMap<String, String>,
+    output: Option<String>,
+}
+
+impl ContextRow {
+    fn new(contexts: &ContextFile, name: &str, context: &context::Context) -> Self {
+        Self {
+            name: name.to_owned(),
+            current: contexts.current.as_deref() == Some(name),
+            bootstrap_server: context.bootstrap_server.clone(),
+            bootstrap_controller: context.bootstrap_controller.clone(),
+            command_config: context
+                .command_config
+                .as_deref()
+                .map(|path| contexts.resolve(path).display().to_string()),
+            properties: context
+                .properties
+                .iter()
+                .map(|(key, value)| {
+                    let value = if is_secret_property(key) {
+                        "[hidden]".into()
+                    } else {
+                        value.to_string()
+                    };
+                    (key.clone(), value)
+                })
+                .collect(),
+            output: context.output.clone(),
+        }
+    }
+}
+
+/// Lists, selects, or shows contexts from the contexts file.
+fn context_command(
+    format: OutputFormat,
+    selected: Option<&str>,
+    action: &ContextAction,
+) -> Result<()> {
+    let contexts = ContextFile::load()?;
+    match action {
+        ContextAction::List => {
+            let rows: Vec<_> = contexts
+                .contexts
+                .iter()
+                .map(|(name, context)| ContextRow::new(&contexts, name, context))
+                .collect();
+            output::write_value(format, "context.list", &rows, |rows| {
+                output::table(
+                    [
+                        "CURRENT",
+                        "NAME",
+                        "BOOTSTRAP_SERVER",
+                        "BOOTSTRAP_CONTROLLER",
+                        "COMMAND_CONFIG",
+                        "OUTPUT",
+                    ],
+                    rows.iter().map(|row| {
+                        [
+                            if row.current { "*" } else { "" }.to_owned(),
+                            row.name.clone(),
+                            row.bootstrap_server.as_deref().unwrap_or("-").to_owned(),
+                            row.bootstrap_controller
+                                .as_deref()
+                                .unwrap_or("-")
+                                .to_owned(),
+                            row.command_config.as_deref().unwrap_or("-").to_owned(),
+                            row.output.as_deref().unwrap_or("-").to_owned(),
+                        ]
+                    }),
+                )
+            })
+        }
+        ContextAction::Use { name } => {
+            contexts.use_context(name)?;
+            write_mutation_rows(
+                format,
+                "context.use",
+                &[MutationRow {
+                    resource: name.clone(),
+                    status: "CURRENT".into(),
+                    error: None,
+                }],
+            )
+        }
+        ContextAction::Show { name } => {
+            let name = name
+                .as_deref()
+                .or(selected)
+                .or(contexts.current.as_deref())
+                .ok_or_else(|| {
+                    Error::Usage(
+                        "no current context; pass a name, use --context, or run `kafka context use`"
+                            .into(),
+                    )
+                })?;
+            let row = ContextRow::new(&contexts, name, contexts.get(name)?);
+            output::write_value(format, "context.show", &row, |row| {
+                let mut rows = vec![
+                    ["name".to_owned(), row.name.clone()],
+                    ["current".into(), row.current.to_string()],
+                ];
+                for (key, value) in [
+                    ("bootstrap-server", &row.bootstrap_server),
+                    ("bootstrap-controller", &row.bootstrap_controller),
+                    ("command-config", &row.command_config),
+                    ("output", &row.output),
+                ] {
+                    if let Some(value) = value {
+                        rows.push([key.into(), value.clone()]);
+                    }
+                }
+                rows.extend(
+                    row.properties
+                        .iter()
+                        .map(|(key, value)| [key.clone(), value.clone()]),
+                );
+                output::table(["SETTING", "VALUE"], rows)
+            })
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ClientConfigCheck {
+    file: String,
+    settings: Vec<Setting>,
+    librdkafka: TransportCheck,
+    admin: TransportCheck,
+    warnings: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct TransportCheck {
+    status: String,
+    error: Option<String>,
+    properties: BTreeMap<String, String>,
+}
+
+impl TransportCheck {
+    fn new(properties: BTreeMap<String, String>, result: Result<()>) -> Self {
+        let error = result.err().map(|error| error.to_string());
+        Self {
+            status: if error.is_some() { "ERROR" } else { "OK" }.into(),
+            error,
+            properties,
+        }
+    }
+}
+
+/// Reports how librdkafka and the pure-Rust admin transport interpret `--command-config`,
+/// creating a librdkafka client and loading the admin TLS material without connecting.
+async fn check_client_config(
+    format: OutputFormat,
+    bootstrap: Option<&str>,
+    command_config: Option<&Path>,
+) -> Result<()> {
+    let path = command_config
+        .ok_or_else(|| Error::Usage("config check requires --command-config".into()))?;
+    let mut values = config::load_properties(path)?;
+    if let Some(bootstrap) = bootstrap {
+        values.insert("bootstrap.servers".into(), bootstrap.into());
+    }
+    let security = SecurityConfig::parse(&values)?;
+
+    let librdkafka = match security.rdkafka_properties() {
+        Ok(properties) => {
+            let mut client = rdkafka::ClientConfig::new();
+            for (key, value) in &properties {
+                client.set(key, value);
+            }
+            let created = client.create::<BaseConsumer>().map(drop);
+            // librdkafka echoes rejected values in its errors.
+            let secrets: Vec<_> = properties
+                .iter()
+                .filter(|(key, value)| is_secret_property(key) && !value.is_empty())
+                .map(|(_, value)| value.clone())
+                .collect();
+            let properties = properties
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = if is_secret_property(&key) {
+                        "[hidden]".into()
+                    } else {
+                        value
+                    };
+                    (key, value)
+                })
+                .collect();
+            let mut check = TransportCheck::new(properties, created.map_err(Error::from));
+            if let Some(error) = &mut check.error {
+                for secret in &secrets {
+                    *error = error.replace(secret.as_str(), "[hidden]");
+                }
+            }
+            check
+        }
+        Err(error) => TransportCheck::new(BTreeMap::new(), Err(error)),
+    };
+    let mut admin_properties = BTreeMap::new();
+    let admin_result = admin_transport_properties(&security, &mut admin_properties).await;
+    let admin = TransportCheck::new(admin_properties, admin_result);
+
+    let check = ClientConfigCheck {
+        file: path.display().to_string(),
+        settings: security.settings,
+        librdkafka,
+        admin,
+        warnings: security.warnings,
+    };
+    output::write_value(format, "config.check", &check, |check| {
+        let mut rendered = output::table(
+            ["SETTING", "VALUE", "SOURCE"],
+            check.settings.iter().map(|setting| {
+                [
+                    setting.name.clone(),
+                    setting.value.clone(),
+                    setting.source.clone(),
+                ]
+            }),
+        );
+        let transports = [("librdkafka", &check.librdkafka), ("admin", &check.admin)];
+        rendered.push('\n');
+        rendered.push_str(&output::table(
+            ["TRANSPORT", "PROPERTY", "VALUE"],
+            transports.into_iter().flat_map(|(name, transport)| {
+                let status = transport.error.as_ref().map_or_else(
+                    || transport.status.clone(),
+                    |error| format!("{}: {error}", transport.status),
+                );
+                std::iter::once([name.to_owned(), "status".into(), status]).chain(
+                    transport
+                        .properties
+                        .iter()
+                        .map(move |(key, value)| [name.to_owned(), key.clone(), value.clone()]),
+                )
+            }),
+        ));
+        for warning in &check.warnings {
+            rendered.push_str("\nWarning: ");
+            rendered.push_str(warning);
+        }
+        rendered
+    })?;
+    let failed =
+        usize::from(check.librdkafka.error.is_some()) + usize::from(check.admin.error.is_some());
+    if failed > 0 {
+        return Err(Error::Partial { failed, total: 2 });
+    }
+    Ok(())
+}
+
+/// Builds the admin transport authentication and loads its TLS material, recording the
+/// resolved properties as it goes.
+async fn admin_transport_properties(
+    security: &SecurityConfig,
+    properties: &mut BTreeMap<String, String>,
+) -> Result<()> {
+    let mut private = PrivateFiles::default();
+    let Some(auth) = security.admin_auth(&mut private)? else {
+        properties.insert("security.protocol".into(), "PLAINTEXT".into());
+        return Ok(());
+    };
+    properties.insert(
+        "security.protocol".into(),
+        auth.security_protocol().to_string(),
+    );
+    if let Some(mechanism) = auth.sasl_mechanism() {
+        properties.insert("sasl.mechanism".into(), mechanism.to_string());
+    }
+    if let Some(tls) = auth.tls_config() {
+        for (key, path) in [
+            ("ssl.ca.location", tls.ca_cert_path()),
+            ("ssl.certificate.location", tls.client_cert_path()),
+            ("ssl.key.location", tls.client_key_path()),
+        ] {
+            if let Some(path) = path {
+                properties.insert(key.into(), path.into());
+            }
+        }
+        krafka::auth::build_tls_config(tls).await?;
     }
+    Ok(())
+}
+
+/// Client properties whose values are credentials or private keys, besides `*password*` and
+/// `*.secret` keys.
+const SECRET_PROPERTIES: &[&str] = &[
+    "sasl.jaas.config",
+    "sasl.oauthbearer.config",
+    "ssl.key.pem",
+    "ssl.keystore.key",
+    "ssl_key",
+];
+
+fn is_secret_property(key: &str) -> bool {
+    SECRET_PROPERTIES.contains(&key) || key.contains("password") || key.ends_with(".secret")
 }
 
 fn admin(config: &rdkafka::ClientConfig) -> Result<Admin> {
@@ -601,11 +957,13 @@ impl From<&DescribeTopicArgs> for TopicSelector {
     }
 }
 
-// This dispatcher mirrors the five Kafka topic actions; splitting it would
+// This dispatcher mirrors the Kafka topic actions; splitting it would
 // obscure the shared metadata and admin result handling.
 #[expect(clippy::too_many_lines)]
 async fn topics(
     config: &rdkafka::ClientConfig,
+    bootstrap: &str,
+    command_config: Option<&Path>,
     timeout: Duration,
     format: OutputFormat,
     action: TopicAction,
@@ -810,6 +1168,18 @@ async fn topics(
                         })
                 })
                 .collect::<Vec<_>>();
+            if args.skew {
+                return Box::pin(describe_skew(
+                    config,
+                    bootstrap,
+                    command_config,
+                    timeout,
+                    format,
+                    &args,
+                    &rows,
+                ))
+                .await;
+            }
             output::write_value(format, "topics.describe", &rows, |rows| {
                 output::table(
                     [
@@ -883,6 +1253,18 @@ async fn topics(
             }
         }
         TopicAction::Alter(args) => {
+            if let Some(replication_factor) = args.replication_factor {
+                return Box::pin(alter_replication_factor(
+                    config,
+                    bootstrap,
+                    command_config,
+                    timeout,
+                    format,
+                    &args,
+                    replication_factor,
+                ))
+                .await;
+            }
             let metadata = base_consumer(config)?.fetch_metadata(None, timeout)?;
             let selector = TopicSelector {
                 topic: Some(args.topic.clone()),
@@ -918,8 +1300,9 @@ async fn topics(
                 .as_deref()
                 .map(parse_replica_assignment)
                 .transpose()?;
-            let target = usize::try_from(args.partitions)
-                .ok()
+            let target = args
+                .partitions
+                .and_then(|partitions| usize::try_from(partitions).ok())
                 .filter(|partitions| *partitions > 0)
                 .ok_or_else(|| Error::Usage("--partitions must be greater than zero".into()))?;
             if assignments
@@ -1018,4876 +1401,7980 @@ async fn topics(
                 })
             }
         }
+        TopicAction::Stats(args) => {
+            topic_stats(
+                config.clone(),
+                bootstrap,
+                command_config,
+                timeout,
+                format,
+                &args,
+            )
+            .await
+        }
     }
 }
 
-fn select_topics<'a>(
-    metadata: &'a rdkafka::metadata::Metadata,
-    selector: &TopicSelector,
-) -> Result<Vec<&'a rdkafka::metadata::MetadataTopic>> {
-    let expression = if selector
-        .topic_id
-        .as_deref()
-        .is_some_and(is_nonzero_topic_id)
-    {
-        None
-    } else {
-        selector.topic.as_deref()
-    };
-    let pattern = expression.map(topic_pattern).transpose()?;
-    Ok(metadata
-        .topics()
-        .iter()
-        .filter(|topic| {
-            (!selector.exclude_internal || !topic.name().starts_with("__"))
-                && pattern
-                    .as_ref()
-                    .is_none_or(|pattern| pattern.is_match(topic.name()))
-        })
-        .collect())
+#[derive(Debug, Serialize)]
+struct SkewReport {
+    partitions: Vec<PartitionSkew>,
+    leaders: Vec<LeaderSkew>,
 }
 
-const ZERO_TOPIC_ID: &str = "AAAAAAAAAAAAAAAAAAAAAA";
-
-fn is_nonzero_topic_id(topic_id: &str) -> bool {
-    topic_id != ZERO_TOPIC_ID
+/// A partition's size, end offset, and offset growth next to their ratio to the topic mean.
+#[derive(Debug, Serialize)]
+struct PartitionSkew {
+    topic: String,
+    partition: i32,
+    leader: i32,
+    size: Option<i64>,
+    size_ratio: Option<f64>,
+    end_offset: Option<i64>,
+    end_offset_ratio: Option<f64>,
+    growth: Option<i64>,
+    growth_ratio: Option<f64>,
+    /// `size`, `end-offset`, or `growth` with a `-high` or `-low` suffix.
+    flags: Vec<String>,
+}
+
+/// How many of a topic's partitions a broker leads, relative to an even spread over the
+/// brokers hosting its replicas.
+#[derive(Debug, Serialize)]
+struct LeaderSkew {
+    topic: String,
+    broker: i32,
+    leaders: usize,
+    ratio: f64,
+    flagged: bool,
 }
 
-fn validate_topic_id(topic_id: Option<&str>) -> Result<()> {
-    let Some(topic_id) = topic_id else {
-        return Ok(());
-    };
-    // Topic IDs are 16-byte UUIDs encoded without padding. librdkafka may print the
-    // standard base64 alphabet (+/); Kafka/Java tools commonly use URL-safe (-_).
-    // Require a real 16-byte decode rather than a length/character-class check alone.
-    if decode_topic_id_bytes(topic_id).is_some() {
-        Ok(())
-    } else {
-        Err(Error::Usage(format!(
-            "invalid topic ID '{topic_id}'; expected a 22-character Kafka UUID"
-        )))
-    }
-}
+impl SkewReport {
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "leader ratios are reported as floating-point values"
+    )]
+    fn new(
+        partitions: &[PartitionSummary],
+        end_offsets: &HashMap<(String, i32), i64>,
+        growth: Option<&HashMap<(String, i32), i64>>,
+        sizes: &HashMap<(String, i32), i64>,
+        threshold: f64,
+    ) -> Self {
+        let mut topics = BTreeMap::<&str, Vec<&PartitionSummary>>::new();
+        for partition in partitions {
+            topics.entry(&partition.topic).or_default().push(partition);
+        }
+        let mut report = Self {
+            partitions: Vec::new(),
+            leaders: Vec::new(),
+        };
+        for (topic, partitions) in topics {
+            let metric = |values: &HashMap<(String, i32), i64>| {
+                let values = partitions
+                    .iter()
+                    .map(|partition| {
+                        values
+                            .get(&(topic.to_owned(), partition.partition))
+                            .copied()
+                    })
+                    .collect::<Vec<_>>();
+                let ratios = mean_ratios(&values);
+                values.into_iter().zip(ratios).collect::<Vec<_>>()
+            };
+            let sizes = metric(sizes);
+            let end_offsets = metric(end_offsets);
+            let growth = growth.map_or_else(|| vec![(None, None); partitions.len()], metric);
+            for (index, partition) in partitions.iter().enumerate() {
+                let mut flags = Vec::new();
+                for (name, ratio) in [
+                    ("size", sizes[index].1),
+                    ("end-offset", end_offsets[index].1),
+                    ("growth", growth[index].1),
+                ] {
+                    match ratio {
+                        Some(ratio) if ratio >= threshold => flags.push(format!("{name}-high")),
+                        Some(ratio) if ratio <= threshold.recip() => {
+                            flags.push(format!("{name}-low"));
+                        }
+                        _ => {}
+                    }
+                }
+                report.partitions.push(PartitionSkew {
+                    topic: topic.to_owned(),
+                    partition: partition.partition,
+                    leader: partition.leader,
+                    size: sizes[index].0,
+                    size_ratio: sizes[index].1,
+                    end_offset: end_offsets[index].0,
+                    end_offset_ratio: end_offsets[index].1,
+                    growth: growth[index].0,
+                    growth_ratio: growth[index].1,
+                    flags,
+                });
+            }
 
-fn decode_topic_id_bytes(topic_id: &str) -> Option<[u8; 16]> {
-    for engine in [&URL_SAFE_NO_PAD, &STANDARD_NO_PAD] {
-        if let Ok(bytes) = engine.decode(topic_id)
-            && let Ok(array) = <[u8; 16]>::try_from(bytes.as_slice())
-        {
-            return Some(array);
+            let brokers = partitions
+                .iter()
+                .flat_map(|partition| partition.replicas.iter().copied())
+                .collect::<BTreeSet<_>>();
+            let even_share = partitions.len() as f64 / brokers.len().max(1) as f64;
+            for broker in brokers {
+                let leaders = partitions
+                    .iter()
+                    .filter(|partition| partition.leader == broker)
+                    .count();
+                let ratio = leaders as f64 / even_share;
+                report.leaders.push(LeaderSkew {
+                    topic: topic.to_owned(),
+                    broker,
+                    leaders,
+                    ratio,
+                    flagged: ratio >= threshold,
+                });
+            }
         }
+        report
     }
-    None
-}
-
-fn topic_partition_matches(
-    selector: &TopicSelector,
-    isr_count: usize,
-    replica_count: usize,
-    min_isr: Option<usize>,
-    leader_is_available: bool,
-) -> bool {
-    (!selector.under_replicated_partitions || isr_count < replica_count)
-        && (!selector.unavailable_partitions || !leader_is_available)
-        && (!selector.under_min_isr_partitions || min_isr.is_some_and(|min| isr_count < min))
-        && (!selector.at_min_isr_partitions || min_isr == Some(isr_count))
-}
-
-fn topic_pattern(expression: &str) -> Result<Regex> {
-    Regex::new(&format!("^(?:{expression})$"))
-        .map_err(|error| Error::Usage(format!("invalid topic regular expression: {error}")))
 }
 
-fn validate_topic_creation_counts(
-    partitions: Option<i32>,
-    replication_factor: Option<i32>,
-) -> Result<()> {
-    if partitions.is_some_and(|partitions| partitions < 1) {
-        return Err(Error::Usage(
-            "--partitions must be greater than zero".into(),
-        ));
-    }
-    if replication_factor
-        .is_some_and(|replication_factor| !(1..=i32::from(i16::MAX)).contains(&replication_factor))
-    {
-        return Err(Error::Usage(format!(
-            "--replication-factor must be between 1 and {}",
-            i16::MAX
-        )));
-    }
-    Ok(())
+/// Divides each present value by the mean of the present values; `None` when the mean is zero.
+#[expect(
+    clippy::cast_precision_loss,
+    reason = "ratios to the mean are reported as floating-point values"
+)]
+fn mean_ratios(values: &[Option<i64>]) -> Vec<Option<f64>> {
+    let present = values.iter().flatten().collect::<Vec<_>>();
+    let mean = present.iter().copied().sum::<i64>() as f64 / present.len().max(1) as f64;
+    values
+        .iter()
+        .map(|value| {
+            value
+                .filter(|_| mean > 0.0)
+                .map(|value| value as f64 / mean)
+        })
+        .collect()
 }
 
-fn topic_results(
+/// Joins described partitions with end offsets and log sizes to report data and leader skew.
+async fn describe_skew(
+    config: &rdkafka::ClientConfig,
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    timeout: Duration,
     format: OutputFormat,
-    command: &str,
-    results: Vec<std::result::Result<String, (String, rdkafka::types::RDKafkaErrorCode)>>,
-    ignore_exists: bool,
-) -> Result<usize> {
-    let rows = results
-        .into_iter()
-        .map(|result| match result {
-            Ok(name) => MutationRow {
-                resource: name,
-                status: "OK".into(),
-                error: None,
-            },
-            Err((name, code))
-                if ignore_exists
-                    && code == rdkafka::types::RDKafkaErrorCode::TopicAlreadyExists =>
-            {
-                MutationRow {
-                    resource: name,
-                    status: "ALREADY_EXISTS".into(),
-                    error: None,
-                }
-            }
-            Err((name, code)) => MutationRow {
-                resource: name,
-                status: "FAILED".into(),
-                error: Some(code.to_string()),
-            },
-        })
+    args: &DescribeTopicArgs,
+    partitions: &[PartitionSummary],
+) -> Result<()> {
+    let targets = partitions
+        .iter()
+        .map(|partition| (partition.topic.clone(), partition.partition))
         .collect::<Vec<_>>();
-    let failures = rows.iter().filter(|row| row.error.is_some()).count();
-    write_mutation_rows(format, command, &rows)?;
-    Ok(failures)
-}
-
-async fn produce(mut config: rdkafka::ClientConfig, args: crate::cli::ProduceArgs) -> Result<()> {
-    apply_client_properties(&mut config, args.properties())?;
-    let max_block_ms = configure_producer(&mut config, &args)?;
-    let reader = line_reader_options(&args)?;
-    let producer: FutureProducer = config.create()?;
-    let input = io::read_to_string(io::stdin())?;
-    let mut deliveries = Vec::new();
-    for (index, line) in input.lines().enumerate() {
-        let input = producer_input(line, args.json, &reader).map_err(|error| {
-            Error::Usage(format!(
-                "invalid producer input on line {}: {error}",
-                index + 1
-            ))
-        })?;
-        let mut record = FutureRecord::to(&args.topic);
-        if let Some(value) = &input.value {
-            record = record.payload(value);
-        }
-        if let Some(key) = &input.key {
-            record = record.key(key);
-        }
-        if let Some(partition) = input.partition {
-            record = record.partition(partition);
-        }
-        if !input.headers.is_empty() {
-            let mut headers = OwnedHeaders::new_with_capacity(input.headers.len());
-            for (key, value) in &input.headers {
-                headers = headers.insert(Header {
-                    key,
-                    value: value.as_deref(),
-                });
-            }
-            record = record.headers(headers);
-        }
-        if args.sync {
-            producer
-                .send(record, Duration::from_millis(max_block_ms))
-                .await
-                .map_err(|(error, _)| Error::Kafka(error))?;
-        } else {
-            deliveries.push(
-                enqueue_with_timeout(&producer, record, Duration::from_millis(max_block_ms))
-                    .await?,
-            );
+    let end_offsets = latest_offsets(config, &targets, timeout)?;
+    let growth = match args.growth_interval_ms {
+        Some(interval) => {
+            tokio::time::sleep(Duration::from_millis(interval)).await;
+            let later = latest_offsets(config, &targets, timeout)?;
+            Some(
+                later
+                    .into_iter()
+                    .filter_map(|(key, offset)| {
+                        let earlier = end_offsets.get(&key)?;
+                        Some((key, offset - earlier))
+                    })
+                    .collect::<HashMap<_, _>>(),
+            )
         }
-    }
-    for delivery in deliveries {
-        delivery
+        None => None,
+    };
+    let mut errors = Vec::new();
+    let sizes = if targets.is_empty() {
+        HashMap::new()
+    } else {
+        partition_log_bytes(bootstrap, command_config, timeout, &targets)
             .await
-            .map_err(|_| Error::Config("producer delivery channel was canceled".into()))?
-            .map_err(|(error, _)| Error::Kafka(error))?;
-    }
-    Ok(())
+            .unwrap_or_else(|error| {
+                errors.push(format!("log directory sizes unavailable: {error}"));
+                HashMap::new()
+            })
+    };
+    let report = SkewReport::new(
+        partitions,
+        &end_offsets,
+        growth.as_ref(),
+        &sizes,
+        args.skew_threshold,
+    );
+    output::write_value_with_errors(format, "topics.describe.skew", &report, &errors, |report| {
+        let value =
+            |value: Option<i64>| value.map_or_else(|| "-".into(), |value| value.to_string());
+        let ratio =
+            |ratio: Option<f64>| ratio.map_or_else(|| "-".into(), |ratio| format!("{ratio:.2}"));
+        let partitions = output::table(
+            [
+                "TOPIC",
+                "PARTITION",
+                "LEADER",
+                "SIZE",
+                "SIZE_RATIO",
+                "END_OFFSET",
+                "END_OFFSET_RATIO",
+                "GROWTH",
+                "GROWTH_RATIO",
+                "FLAGS",
+            ],
+            report.partitions.iter().map(|row| {
+                [
+                    row.topic.clone(),
+                    row.partition.to_string(),
+                    row.leader.to_string(),
+                    value(row.size),
+                    ratio(row.size_ratio),
+                    value(row.end_offset),
+                    ratio(row.end_offset_ratio),
+                    value(row.growth),
+                    ratio(row.growth_ratio),
+                    row.flags.join(","),
+                ]
+            }),
+        );
+        let leaders = output::table(
+            ["TOPIC", "BROKER", "LEADERS", "RATIO", "FLAGGED"],
+            report.leaders.iter().map(|row| {
+                [
+                    row.topic.clone(),
+                    row.broker.to_string(),
+                    row.leaders.to_string(),
+                    format!("{:.2}", row.ratio),
+                    row.flagged.to_string(),
+                ]
+            }),
+        );
+        format!("{partitions}\n{leaders}")
+    })
 }
 
-async fn producer_perf_test(
-    bootstrap: Option<&str>,
-    command_config: Option<&Path>,
-    args: ProducerPerfTestArgs,
-) -> Result<()> {
-    let bootstrap = bootstrap.map(str::to_owned);
-    let command_config = command_config.map(Path::to_path_buf);
-    tokio::task::spawn_blocking(move || {
-        run_producer_perf_test(bootstrap.as_deref(), command_config.as_deref(), &args)
-    })
-    .await
-    .map_err(|error| Error::Config(format!("producer performance task failed: {error}")))?
+/// Log-end offsets of the given partitions; partitions whose lookup failed are omitted.
+fn latest_offsets(
+    config: &rdkafka::ClientConfig,
+    targets: &[(String, i32)],
+    timeout: Duration,
+) -> Result<HashMap<(String, i32), i64>> {
+    if targets.is_empty() {
+        return Ok(HashMap::new());
+    }
+    let client = admin(config)?;
+    Ok(ffi::list_offsets(
+        client.inner().native_ptr(),
+        targets,
+        ffi::ListOffsetSpec::Latest,
+        duration_ms(timeout)?,
+    )?
+    .into_iter()
+    .filter_map(|entry| Some(((entry.topic, entry.partition), entry.offset?)))
+    .collect())
 }
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-struct E2eHeader {
-    key: String,
-    value: Option<Vec<u8>>,
+#[derive(Debug, Serialize)]
+struct TopicStats {
+    topic: String,
+    records: i64,
+    sampled: u64,
+    /// Estimated distinct non-null keys across all partitions.
+    distinct_keys: u64,
+    /// Largest partition record count relative to the per-partition mean.
+    skew: Option<f64>,
+    compression_ratio: Option<f64>,
+    partitions: Vec<PartitionStats>,
+    rate: Vec<RateBucket>,
 }
 
-#[derive(Clone, Copy, Debug)]
-struct JavaRandom(u64);
+#[derive(Debug, Serialize)]
+struct PartitionStats {
+    partition: i32,
+    records: i64,
+    sampled: u64,
+    /// Percentage of the topic's records held by this partition.
+    share: f64,
+    distinct_keys: u64,
+    records_per_second: Option<f64>,
+    record_bytes: SizeStats,
+    header_bytes: SizeStats,
+    log_bytes: Option<i64>,
+    /// Sampled uncompressed record bytes over on-disk bytes, scaled to the whole log.
+    compression_ratio: Option<f64>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize)]
+struct SizeStats {
+    average: f64,
+    p50: u64,
+    p95: u64,
+    p99: u64,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+struct RateBucket {
+    start: i64,
+    records: u64,
+    records_per_second: f64,
+}
 
-impl JavaRandom {
-    const MULTIPLIER: u64 = 0x0005_deec_e66d;
-    const MASK: u64 = (1_u64 << 48) - 1;
+impl ConsumerArgs for TopicStatsArgs {
+    fn group(&self) -> Option<&str> {
+        None
+    }
 
-    const fn new(seed: i64) -> Self {
-        Self((seed.cast_unsigned() ^ Self::MULTIPLIER) & Self::MASK)
+    fn properties(&self) -> &[String] {
+        &self.properties
     }
 
-    fn next_bits(&mut self, bits: u32) -> u32 {
-        self.0 = self.0.wrapping_mul(Self::MULTIPLIER).wrapping_add(0xb) & Self::MASK;
-        u32::try_from(self.0 >> (48 - bits)).unwrap_or_default()
+    fn partition(&self) -> Option<i32> {
+        None
     }
 
-    fn next_index(&mut self, bound: u32) -> u32 {
-        let mask = bound - 1;
-        if bound & mask == 0 {
-            return u32::try_from((u64::from(bound) * u64::from(self.next_bits(31))) >> 31)
-                .unwrap_or_default();
-        }
-        loop {
-            let bits = self.next_bits(31);
-            let value = bits % bound;
-            let check = bits.wrapping_sub(value).wrapping_add(mask);
-            if i32::from_ne_bytes(check.to_ne_bytes()) >= 0 {
-                return value;
-            }
-        }
+    fn reads_from_beginning(&self) -> bool {
+        true
     }
-}
 
-fn e2e_random_bytes(random: &mut JavaRandom, length: i32) -> Vec<u8> {
-    let byte = b'A'.saturating_add(u8::try_from(random.next_index(26)).unwrap_or_default());
-    vec![byte; usize::try_from(length).unwrap_or_default()]
+    fn isolation_level(&self) -> Option<&str> {
+        self.isolation_level.as_deref()
+    }
 }
 
-fn e2e_headers(
-    random: &mut JavaRandom,
-    count: i32,
-    key_size: i32,
-    value_size: i32,
-) -> Vec<E2eHeader> {
-    (0..count)
-        .map(|_| E2eHeader {
-            key: String::from_utf8_lossy(&e2e_random_bytes(random, key_size)).into_owned(),
-            value: (value_size != -1).then(|| e2e_random_bytes(random, value_size)),
-        })
-        .collect()
-}
+/// Samples the tail of every partition, or the whole log with `--all`, and reports how
+/// records, keys, sizes, and arrival rate are spread across the topic.
+async fn topic_stats(
+    mut config: rdkafka::ClientConfig,
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    timeout: Duration,
+    format: OutputFormat,
+    args: &TopicStatsArgs,
+) -> Result<()> {
+    configure_consumer(&mut config, args)?;
+    let consumer: StreamConsumer = config.create()?;
+    let metadata = consumer.fetch_metadata(Some(&args.topic), timeout)?;
+    let topic = metadata
+        .topics()
+        .iter()
+        .find(|topic| topic.name() == args.topic && topic.error().is_none())
+        .ok_or_else(|| Error::Usage(format!("topic {} does not exist", args.topic)))?;
+    let mut watermarks = BTreeMap::new();
+    let mut ranges = Vec::new();
+    for partition in topic.partitions() {
+        let (low, high) = consumer.fetch_watermarks(&args.topic, partition.id(), timeout)?;
+        watermarks.insert(partition.id(), high - low);
+        let start = if args.all {
+            low
+        } else {
+            low.max(high - i64::try_from(args.samples).unwrap_or(i64::MAX))
+        };
+        if start < high {
+            ranges.push(PartitionRange {
+                topic: args.topic.clone(),
+                partition: partition.id(),
+                start,
+                end: high,
+            });
+        }
+    }
+    drop(metadata);
 
-fn e2e_percentile(latencies: &[u64], numerator: usize, denominator: usize) -> u64 {
-    let index = latencies
-        .len()
-        .saturating_mul(numerator)
-        .checked_div(denominator)
-        .unwrap_or_default()
-        .min(latencies.len().saturating_sub(1));
-    latencies.get(index).copied().unwrap_or_default()
+    let mut errors = Vec::new();
+    let partitions = watermarks
+        .keys()
+        .map(|partition| (args.topic.clone(), *partition))
+        .collect::<Vec<_>>();
+    let log_bytes = partition_log_bytes(bootstrap, command_config, timeout, &partitions)
+        .await
+        .map_or_else(
+            |error| {
+                errors.push(format!("log directory sizes unavailable: {error}"));
+                HashMap::new()
+            },
+            |sizes| {
+                sizes
+                    .into_iter()
+                    .map(|((_, partition), size)| (partition, size))
+                    .collect()
+            },
+        );
+    let mut sampler = TopicSampler::new(i64::try_from(args.bucket_ms).unwrap_or(i64::MAX));
+    scan_ranges(
+        &consumer,
+        &ranges,
+        "topics stats",
+        args.timeout_ms,
+        |message| {
+            sampler.add(message);
+            Ok(true)
+        },
+    )
+    .await?;
+    let stats = sampler.finish(&args.topic, &watermarks, &log_bytes);
+    output::write_value_with_errors(format, "topics.stats", &stats, &errors, |stats| {
+        topic_stats_table(stats)
+    })
 }
 
-fn validate_e2e_record(
-    message: &rdkafka::message::BorrowedMessage<'_>,
-    key: &[u8],
-    value: &[u8],
-    expected_headers: &[E2eHeader],
-) -> Result<()> {
-    if message.payload().unwrap_or_default() != value {
-        return Err(Error::Config(
-            "the message value read did not match the message value sent".into(),
-        ));
+/// Largest non-future replica size of each partition, from `DescribeLogDirs`.
+async fn partition_log_bytes(
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    timeout: Duration,
+    partitions: &[(String, i32)],
+) -> Result<HashMap<(String, i32), i64>> {
+    let mut grouped = BTreeMap::<&str, Vec<i32>>::new();
+    for (topic, partition) in partitions {
+        grouped.entry(topic).or_default().push(*partition);
     }
-    if message.key().unwrap_or_default() != key || message.key().is_none() {
-        return Err(Error::Config(
-            "the message key read did not match the message key sent".into(),
-        ));
+    let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
+    let directories = client
+        .describe_log_dirs(Some(
+            grouped
+                .into_iter()
+                .map(|(topic, partitions)| DescribableLogDirTopic {
+                    topic: topic.to_owned(),
+                    partitions,
+                })
+                .collect(),
+        ))
+        .await?;
+    drop(client);
+    let mut sizes = HashMap::new();
+    for directory in directories
+        .into_iter()
+        .filter(|directory| directory.error.is_none())
+    {
+        for topic in directory.topics {
+            for partition in topic
+                .partitions
+                .into_iter()
+                .filter(|partition| !partition.is_future_key)
+            {
+                let size = sizes
+                    .entry((topic.name.clone(), partition.partition_index))
+                    .or_insert(0);
+                *size = partition.partition_size.max(*size);
+            }
+        }
     }
-    let received_headers = message.headers();
-    if expected_headers.is_empty() {
-        if received_headers.is_some_and(|headers| headers.count() > 0) {
-            return Err(Error::Config(
-                "expected no message headers but received at least one".into(),
-            ));
+    Ok(sizes)
+}
+
+fn topic_stats_table(stats: &TopicStats) -> String {
+    let optional =
+        |value: Option<f64>| value.map_or_else(|| "-".into(), |value| format!("{value:.2}"));
+    let summary = output::table(
+        [
+            "TOPIC",
+            "RECORDS",
+            "SAMPLED",
+            "DISTINCT_KEYS",
+            "SKEW",
+            "COMPRESSION",
+        ],
+        [[
+            stats.topic.clone(),
+            stats.records.to_string(),
+            stats.sampled.to_string(),
+            stats.distinct_keys.to_string(),
+            optional(stats.skew),
+            optional(stats.compression_ratio),
+        ]],
+    );
+    let partitions = output::table(
+        [
+            "PARTITION",
+            "RECORDS",
+            "SHARE_%",
+            "SAMPLED",
+            "DISTINCT_KEYS",
+            "RECORDS/S",
+            "AVG_BYTES",
+            "P50",
+            "P95",
+            "P99",
+            "AVG_HEADER_BYTES",
+            "LOG_BYTES",
+            "COMPRESSION",
+        ],
+        stats.partitions.iter().map(|partition| {
+            [
+                partition.partition.to_string(),
+                partition.records.to_string(),
+                format!("{:.1}", partition.share),
+                partition.sampled.to_string(),
+                partition.distinct_keys.to_string(),
+                optional(partition.records_per_second),
+                format!("{:.1}", partition.record_bytes.average),
+                partition.record_bytes.p50.to_string(),
+                partition.record_bytes.p95.to_string(),
+                partition.record_bytes.p99.to_string(),
+                format!("{:.1}", partition.header_bytes.average),
+                partition
+                    .log_bytes
+                    .map_or_else(|| "-".into(), |bytes| bytes.to_string()),
+                optional(partition.compression_ratio),
+            ]
+        }),
+    );
+    let rate = output::table(
+        ["BUCKET_START", "RECORDS", "RECORDS/S"],
+        stats.rate.iter().map(|bucket| {
+            [
+                DateTime::<Utc>::from_timestamp_millis(bucket.start).map_or_else(
+                    || bucket.start.to_string(),
+                    |start| start.to_rfc3339_opts(SecondsFormat::Millis, true),
+                ),
+                bucket.records.to_string(),
+                format!("{:.2}", bucket.records_per_second),
+            ]
+        }),
+    );
+    format!("{summary}\n{partitions}\n{rate}")
+}
+
+/// Accumulates per-partition and per-bucket figures for `topics stats`.
+struct TopicSampler {
+    bucket_ms: i64,
+    partitions: BTreeMap<i32, PartitionSample>,
+    buckets: BTreeMap<i64, u64>,
+}
+
+#[derive(Default)]
+struct PartitionSample {
+    record_sizes: SizeHistogram,
+    header_sizes: SizeHistogram,
+    keys: KeySketch,
+    first_timestamp: Option<i64>,
+    last_timestamp: Option<i64>,
+}
+
+impl TopicSampler {
+    const fn new(bucket_ms: i64) -> Self {
+        Self {
+            bucket_ms,
+            partitions: BTreeMap::new(),
+            buckets: BTreeMap::new(),
         }
-        return Ok(());
     }
-    let Some(received_headers) = received_headers else {
-        return Err(Error::Config(
-            "expected message headers but received none".into(),
-        ));
-    };
-    if received_headers.count() != expected_headers.len() {
-        return Err(Error::Config(
-            "header count mismatch between sent and received messages".into(),
-        ));
+
+    fn add(&mut self, message: &impl Message) {
+        let sample = self.partitions.entry(message.partition()).or_default();
+        let header_bytes = message.headers().map_or(0, |headers| {
+            headers
+                .iter()
+                .map(|header| header.key.len() + header.value.map_or(0, <[u8]>::len))
+                .sum::<usize>()
+        });
+        let key_bytes = message.key().map_or(0, <[u8]>::len);
+        let value_bytes = message.payload().map_or(0, <[u8]>::len);
+        sample
+            .record_sizes
+            .insert((key_bytes + value_bytes + header_bytes) as u64);
+        sample.header_sizes.insert(header_bytes as u64);
+        if let Some(key) = message.key() {
+            sample.keys.insert(key);
+        }
+        if let Some(timestamp) = message.timestamp().to_millis() {
+            sample.first_timestamp = Some(
+                sample
+                    .first_timestamp
+                    .map_or(timestamp, |first| first.min(timestamp)),
+            );
+            sample.last_timestamp = Some(
+                sample
+                    .last_timestamp
+                    .map_or(timestamp, |last| last.max(timestamp)),
+            );
+            *self
+                .buckets
+                .entry(timestamp.div_euclid(self.bucket_ms) * self.bucket_ms)
+                .or_default() += 1;
+        }
     }
-    for (index, expected) in expected_headers.iter().enumerate() {
-        let received = received_headers.get(index);
-        if received.key != expected.key || received.value != expected.value.as_deref() {
-            return Err(Error::Config(format!(
-                "message header {index} did not match the sent header"
-            )));
+
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "shares, rates, and ratios are reported as floating-point estimates"
+    )]
+    fn finish(
+        self,
+        topic: &str,
+        records: &BTreeMap<i32, i64>,
+        log_bytes: &HashMap<i32, i64>,
+    ) -> TopicStats {
+        let total = records.values().sum::<i64>();
+        let mut keys = KeySketch::default();
+        let mut sampled = 0;
+        let (mut logical_bytes, mut stored_bytes) = (0.0, 0_i64);
+        let partitions = records
+            .iter()
+            .map(|(&partition, &count)| {
+                let sample = self.partitions.get(&partition);
+                if let Some(sample) = sample {
+                    keys.merge(&sample.keys);
+                }
+                let partition_sampled = sample.map_or(0, |sample| sample.record_sizes.count);
+                sampled += partition_sampled;
+                let record_bytes = sample.map_or_else(SizeStats::default, |sample| {
+                    SizeStats::new(&sample.record_sizes)
+                });
+                let log = log_bytes.get(&partition).copied();
+                let compression_ratio = log
+                    .filter(|bytes| *bytes > 0 && partition_sampled > 0)
+                    .map(|bytes| {
+                        let logical = record_bytes.average * count as f64;
+                        logical_bytes += logical;
+                        stored_bytes += bytes;
+                        logical / bytes as f64
+                    });
+                PartitionStats {
+                    partition,
+                    records: count,
+                    sampled: partition_sampled,
+                    share: if total > 0 {
+                        100.0 * count as f64 / total as f64
+                    } else {
+                        0.0
+                    },
+                    distinct_keys: sample.map_or(0, |sample| sample.keys.estimate()),
+                    records_per_second: sample.and_then(|sample| {
+                        let span = sample.last_timestamp? - sample.first_timestamp?;
+                        (span > 0).then(|| 1_000.0 * (partition_sampled - 1) as f64 / span as f64)
+                    }),
+                    record_bytes,
+                    header_bytes: sample.map_or_else(SizeStats::default, |sample| {
+                        SizeStats::new(&sample.header_sizes)
+                    }),
+                    log_bytes: log,
+                    compression_ratio,
+                }
+            })
+            .collect::<Vec<_>>();
+        let mean = total as f64 / records.len().max(1) as f64;
+        TopicStats {
+            topic: topic.to_owned(),
+            records: total,
+            sampled,
+            distinct_keys: keys.estimate(),
+            skew: records
+                .values()
+                .max()
+                .filter(|_| mean > 0.0)
+                .map(|largest| *largest as f64 / mean),
+            compression_ratio: (stored_bytes > 0).then(|| logical_bytes / stored_bytes as f64),
+            partitions,
+            rate: self
+                .buckets
+                .into_iter()
+                .map(|(start, records)| RateBucket {
+                    start,
+                    records,
+                    records_per_second: 1_000.0 * records as f64 / self.bucket_ms as f64,
+                })
+                .collect(),
         }
     }
-    Ok(())
 }
 
-async fn ensure_e2e_topic(
-    config: &rdkafka::ClientConfig,
-    producer: &FutureProducer,
-    topic: &str,
-) -> Result<()> {
-    let metadata = producer
-        .client()
-        .fetch_metadata(Some(topic), Duration::from_secs(10))?;
-    let exists = metadata.topics().iter().any(|metadata_topic| {
-        metadata_topic.name() == topic && !metadata_topic.partitions().is_empty()
-    });
-    if exists {
-        return Ok(());
-    }
-    println!(
-        "Topic \"{topic}\" does not exist. Will create topic with 1 partition(s) and replication factor = 1"
-    );
-    let admin: Admin = config.create()?;
-    let results = admin
-        .create_topics(
-            &[NewTopic::new(topic, 1, TopicReplication::Fixed(1))],
-            &AdminOptions::new().operation_timeout(Some(Duration::from_secs(30))),
-        )
-        .await?;
-    for result in results {
-        if let Err((name, error)) = result
-            && error != RDKafkaErrorCode::TopicAlreadyExists
-        {
-            return Err(Error::Config(format!(
-                "creation of topic {name} failed: {error}"
-            )));
+impl SizeStats {
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "average sizes are reported as floating-point values"
+    )]
+    fn new(sizes: &SizeHistogram) -> Self {
+        if sizes.count == 0 {
+            return Self::default();
+        }
+        Self {
+            average: sizes.sum as f64 / sizes.count as f64,
+            p50: sizes.percentile(50),
+            p95: sizes.percentile(95),
+            p99: sizes.percentile(99),
         }
     }
-    Ok(())
 }
 
-#[expect(
-    clippy::cast_precision_loss,
-    reason = "the original latency loop keeps setup, synchronous round trips, validation, and floating-point reporting in one lifecycle"
-)]
-async fn e2e_latency(
-    bootstrap: &str,
-    command_config: Option<&Path>,
-    args: E2eLatencyArgs,
-) -> Result<()> {
-    let mut producer_config = config::client_config(bootstrap, command_config)?;
-    producer_config
-        .set("linger.ms", "0")
-        .set("acks", &args.producer_acks);
-    producer_config.remove("max.block.ms");
-    let producer: FutureProducer = producer_config.create()?;
-    ensure_e2e_topic(&producer_config, &producer, &args.topic).await?;
+/// Log-linear histogram of sizes: exact below 128 bytes and within 1/64 above, so memory
+/// stays bounded by the number of distinct buckets rather than the records sampled.
+#[derive(Default)]
+struct SizeHistogram {
+    buckets: BTreeMap<u64, u64>,
+    count: u64,
+    sum: u64,
+    max: u64,
+}
 
-    let mut consumer_config = config::client_config(bootstrap, command_config)?;
-    consumer_config
-        .set(
-            "group.id",
-            format!("test-group-{}", Utc::now().timestamp_millis()),
-        )
-        .set("enable.auto.commit", "false")
-        .set("auto.offset.reset", "latest")
-        .set("fetch.wait.max.ms", "0");
-    let consumer: StreamConsumer = consumer_config.create()?;
-    let metadata = consumer.fetch_metadata(Some(&args.topic), Duration::from_secs(30))?;
-    let topic = metadata
-        .topics()
-        .iter()
-        .find(|topic| topic.name() == args.topic)
-        .ok_or_else(|| Error::Config(format!("topic {} was not found", args.topic)))?;
-    let mut assignment = TopicPartitionList::new();
-    for partition in topic.partitions() {
-        let (_, high) =
-            consumer.fetch_watermarks(&args.topic, partition.id(), Duration::from_secs(30))?;
-        assignment.add_partition_offset(&args.topic, partition.id(), Offset::Offset(high))?;
+impl SizeHistogram {
+    const EXACT: u64 = 128;
+    const SUB_BUCKETS: u64 = 64;
+
+    fn insert(&mut self, size: u64) {
+        *self.buckets.entry(Self::bucket(size)).or_default() += 1;
+        self.count += 1;
+        self.sum = self.sum.saturating_add(size);
+        self.max = self.max.max(size);
     }
-    consumer.assign(&assignment)?;
 
-    let mut random = JavaRandom::new(0);
-    let mut latencies = Vec::with_capacity(usize::try_from(args.num_records).unwrap_or_default());
-    let mut total_nanos = 0_u128;
-    for index in 0..args.num_records {
-        let key = e2e_random_bytes(&mut random, args.record_key_size);
-        let value = e2e_random_bytes(&mut random, args.record_size);
-        let headers = e2e_headers(
-            &mut random,
-            args.num_headers,
-            args.record_header_key_size,
-            args.record_header_size,
-        );
-        let mut owned_headers = OwnedHeaders::new_with_capacity(headers.len());
-        for header in &headers {
-            owned_headers = owned_headers.insert(Header {
-                key: &header.key,
-                value: header.value.as_deref(),
-            });
+    /// Buckets sizes below [`Self::EXACT`] one per value, and larger sizes by their top seven
+    /// bits.
+    fn bucket(size: u64) -> u64 {
+        if size < Self::EXACT {
+            return size;
         }
-        let started = Instant::now();
-        producer
-            .send(
-                FutureRecord::to(&args.topic)
-                    .key(&key)
-                    .payload(&value)
-                    .headers(owned_headers),
-                Duration::MAX,
-            )
-            .await
-            .map_err(|(error, _)| Error::Kafka(error))?;
-        let message = tokio::time::timeout(Duration::from_secs(60), consumer.recv())
-            .await
-            .map_err(|_| {
-                Error::Config("poll() timed out before finding a result (timeout:[60000ms])".into())
-            })??;
-        let elapsed = started.elapsed();
-        validate_e2e_record(&message, &key, &value, &headers)?;
-        if index % 1_000 == 0 {
-            println!("{index}\t{:.4}", elapsed.as_secs_f64() * 1_000.0);
+        let shift = size.ilog2() - Self::SUB_BUCKETS.ilog2();
+        let sub_bucket = (size >> shift) - Self::SUB_BUCKETS;
+        Self::EXACT + u64::from(shift - 1) * Self::SUB_BUCKETS + sub_bucket
+    }
+
+    /// The largest size a bucket holds.
+    const fn highest(bucket: u64) -> u64 {
+        if bucket < Self::EXACT {
+            return bucket;
         }
-        total_nanos = total_nanos.saturating_add(elapsed.as_nanos());
-        latencies.push(u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX));
+        let offset = bucket - Self::EXACT;
+        let shift = offset / Self::SUB_BUCKETS + 1;
+        let sub_bucket = offset % Self::SUB_BUCKETS + Self::SUB_BUCKETS;
+        (sub_bucket << shift) + ((1 << shift) - 1)
     }
-    println!(
-        "Avg latency: {:.4} ms",
-        total_nanos as f64 / f64::from(args.num_records) / 1_000_000.0
-    );
-    latencies.sort_unstable();
-    println!(
-        "Percentiles: 50th = {}, 99th = {}, 99.9th = {}",
-        e2e_percentile(&latencies, 1, 2),
-        e2e_percentile(&latencies, 99, 100),
-        e2e_percentile(&latencies, 999, 1_000)
-    );
-    // Assigned partitions do not require a successful group commit for latency output.
-    // Fresh single-node brokers can return NotCoordinator during the final commit while
-    // the group coordinator is still electing; do not fail the measurement for that.
-    if let Err(error) = consumer.commit_consumer_state(CommitMode::Sync) {
-        let message = error.to_string();
-        if !(message.contains("NotCoordinator") || message.contains("CoordinatorNotAvailable")) {
-            return Err(Error::Kafka(error));
+
+    /// The nearest-rank percentile, reported as the top of its bucket but never above the
+    /// largest size seen.
+    fn percentile(&self, percent: u64) -> u64 {
+        let rank = (self.count * percent).div_ceil(100).max(1);
+        let mut seen = 0;
+        for (&bucket, &count) in &self.buckets {
+            seen += count;
+            if seen >= rank {
+                return Self::highest(bucket).min(self.max);
+            }
         }
+        self.max
     }
-    Ok(())
 }
 
-#[derive(Serialize)]
-struct VerifiableLifecycleEvent {
-    timestamp: i64,
-    name: &'static str,
+/// `HyperLogLog` sketch estimating distinct keys in 4 KiB, with about 1.6% standard error.
+#[derive(Clone)]
+struct KeySketch {
+    registers: Vec<u8>,
 }
 
-#[derive(Serialize)]
-struct VerifiableSendSuccess {
-    timestamp: i64,
-    name: &'static str,
-    key: Option<String>,
-    value: String,
-    topic: String,
-    partition: i32,
-    offset: i64,
-}
-
-#[derive(Serialize)]
-struct VerifiableSendError {
-    timestamp: i64,
-    name: &'static str,
-    key: Option<String>,
-    value: String,
-    topic: String,
-    exception: &'static str,
-    message: String,
-}
+impl KeySketch {
+    const INDEX_BITS: u32 = 12;
 
-#[derive(Serialize)]
-struct VerifiableToolData {
-    timestamp: i64,
-    name: &'static str,
-    sent: u64,
-    acked: u64,
-    target_throughput: i32,
-    avg_throughput: f64,
-}
+    fn insert(&mut self, key: &[u8]) {
+        let mut hasher = std::hash::DefaultHasher::new();
+        std::hash::Hash::hash(key, &mut hasher);
+        let hash = std::hash::Hasher::finish(&hasher);
+        let index = usize::try_from(hash >> (u64::BITS - Self::INDEX_BITS)).unwrap_or_default();
+        let rank = (hash << Self::INDEX_BITS)
+            .leading_zeros()
+            .min(u64::BITS - Self::INDEX_BITS)
+            + 1;
+        let rank = u8::try_from(rank).unwrap_or(u8::MAX);
+        self.registers[index] = self.registers[index].max(rank);
+    }
 
-fn verifiable_value(prefix: Option<i32>, index: u64) -> String {
-    prefix.map_or_else(|| index.to_string(), |prefix| format!("{prefix}.{index}"))
-}
+    fn merge(&mut self, other: &Self) {
+        for (register, other) in self.registers.iter_mut().zip(&other.registers) {
+            *register = (*register).max(*other);
+        }
+    }
 
-fn verifiable_key(repeating_keys: Option<i32>, counter: &mut i32) -> Option<String> {
-    let limit = repeating_keys?;
-    let key = counter.to_string();
-    *counter = counter.saturating_add(1);
-    if *counter == limit {
-        *counter = 0;
+    #[expect(
+        clippy::cast_precision_loss,
+        clippy::cast_possible_truncation,
+        clippy::cast_sign_loss,
+        reason = "HyperLogLog estimates are computed in floating point and rounded to a count"
+    )]
+    fn estimate(&self) -> u64 {
+        let registers = self.registers.len() as f64;
+        let sum = self
+            .registers
+            .iter()
+            .map(|register| 2_f64.powi(-i32::from(*register)))
+            .sum::<f64>();
+        let raw = 0.7213 / (1.0 + 1.079 / registers) * registers * registers / sum;
+        let empty = self
+            .registers
+            .iter()
+            .map(|register| usize::from(*register == 0))
+            .sum::<usize>();
+        let estimate = if raw <= 2.5 * registers && empty > 0 {
+            // Linear counting is more accurate while many registers are still empty.
+            registers * (registers / empty as f64).ln()
+        } else {
+            raw
+        };
+        estimate.round() as u64
     }
-    Some(key)
 }
 
-fn verifiable_config(
-    bootstrap: &str,
-    command_config: Option<&Path>,
-    deprecated_config: Option<&Path>,
-    acks: i32,
-) -> Result<rdkafka::ClientConfig> {
-    let config_path = share_perf_config_path(command_config, deprecated_config)?;
-    let mut client = config::client_config(bootstrap, None)?;
-    client
-        .set("acks", acks.to_string())
-        .set("message.send.max.retries", "0");
-    if let Some(path) = config_path {
-        for (key, value) in config::load_properties(path)? {
-            client.set(config::normalize_key(&key), value);
+impl Default for KeySketch {
+    fn default() -> Self {
+        Self {
+            registers: vec![0; 1 << Self::INDEX_BITS],
         }
     }
-    Ok(client)
 }
 
-async fn print_verifiable_delivery(
-    producer: FutureProducer,
-    topic: String,
-    key: Option<String>,
-    value: String,
-    timestamp: Option<i64>,
-) -> Result<bool> {
-    let mut record = FutureRecord::to(&topic).payload(&value);
-    if let Some(key) = key.as_deref() {
-        record = record.key(key);
-    }
-    if let Some(timestamp) = timestamp {
-        record = record.timestamp(timestamp);
-    }
-    match producer.send(record, Duration::MAX).await {
-        Ok(delivery) => {
-            output::write_json_line(&VerifiableSendSuccess {
-                timestamp: Utc::now().timestamp_millis(),
-                name: "producer_send_success",
-                key,
-                value,
-                topic,
-                partition: delivery.partition,
-                offset: delivery.offset,
-            })?;
-            Ok(true)
-        }
-        Err((error, _)) => {
-            output::write_json_line(&VerifiableSendError {
-                timestamp: Utc::now().timestamp_millis(),
-                name: "producer_send_error",
-                key,
-                value,
-                topic,
-                exception: "rdkafka::KafkaError",
-                message: error.to_string(),
-            })?;
-            Ok(false)
-        }
-    }
+fn select_topics<'a>(
+    metadata: &'a rdkafka::metadata::Metadata,
+    selector: &TopicSelector,
+) -> Result<Vec<&'a rdkafka::metadata::MetadataTopic>> {
+    let expression = if selector
+        .topic_id
+        .as_deref()
+        .is_some_and(is_nonzero_topic_id)
+    {
+        None
+    } else {
+        selector.topic.as_deref()
+    };
+    let pattern = expression.map(topic_pattern).transpose()?;
+    Ok(metadata
+        .topics()
+        .iter()
+        .filter(|topic| {
+            (!selector.exclude_internal || !topic.name().starts_with("__"))
+                && pattern
+                    .as_ref()
+                    .is_none_or(|pattern| pattern.is_match(topic.name()))
+        })
+        .collect())
 }
 
-#[expect(
-    clippy::cast_precision_loss,
-    reason = "Kafka's tool_data event defines average throughput as a floating-point field"
-)]
-async fn verifiable_producer(
-    bootstrap: &str,
-    command_config: Option<&Path>,
-    args: VerifiableProducerArgs,
-) -> Result<()> {
-    if args.producer_config.is_some() {
-        println!(
-            "Option --producer.config has been deprecated and will be removed in a future version. Use --command-config instead."
-        );
-    }
-    let config = verifiable_config(
-        bootstrap,
-        command_config,
-        args.producer_config.as_deref(),
-        args.acks,
-    )?;
-    let producer: FutureProducer = config.create()?;
-    let started = Instant::now();
-    let started_ms = Utc::now().timestamp_millis();
-    output::write_json_line(&VerifiableLifecycleEvent {
-        timestamp: started_ms,
-        name: "startup_complete",
-    })?;
+const ZERO_TOPIC_ID: &str = "AAAAAAAAAAAAAAAAAAAAAA";
 
-    let max_messages = if args.max_messages < 0 {
-        u64::MAX
-    } else {
-        u64::try_from(args.max_messages).unwrap_or_default()
+fn is_nonzero_topic_id(topic_id: &str) -> bool {
+    topic_id != ZERO_TOPIC_ID
+}
+
+fn validate_topic_id(topic_id: Option<&str>) -> Result<()> {
+    let Some(topic_id) = topic_id else {
+        return Ok(());
     };
-    let mut key_counter = 0_i32;
-    let mut create_time = (args.message_create_time != -1).then_some(args.message_create_time);
-    let mut deliveries = tokio::task::JoinSet::new();
-    let mut signal = Box::pin(tokio::signal::ctrl_c());
-    let mut sent = 0_u64;
-    let mut acked = 0_u64;
-    for index in 0..max_messages {
-        while let Some(delivery) = deliveries.try_join_next() {
-            if delivery.map_err(|error| {
-                Error::Config(format!("verifiable producer task failed: {error}"))
-            })?? {
-                acked = acked.saturating_add(1);
-            }
-        }
-        tokio::select! {
-            biased;
-            _ = &mut signal => break,
-            () = tokio::task::yield_now() => {}
-        }
-        let key = verifiable_key(args.repeating_keys, &mut key_counter);
-        let value = verifiable_value(args.value_prefix, index);
-        let producer = producer.clone();
-        let topic = args.topic.clone();
-        let record_timestamp = create_time;
-        deliveries.spawn(async move {
-            print_verifiable_delivery(producer, topic, key, value, record_timestamp).await
-        });
-        sent = sent.saturating_add(1);
-        if let Some(timestamp) = create_time.as_mut() {
-            *timestamp =
-                timestamp.saturating_add(Utc::now().timestamp_millis().saturating_sub(started_ms));
-        }
-        if args.throughput == 0 && index > 0 {
-            let _ = (&mut signal).await;
-            break;
-        }
-        if args.throughput > 0 {
-            let expected = Duration::from_secs_f64(index as f64 / f64::from(args.throughput));
-            if let Some(remaining) = expected.checked_sub(started.elapsed()) {
-                tokio::select! {
-                    _ = &mut signal => break,
-                    () = tokio::time::sleep(remaining) => {}
-                }
-            }
-        }
+    // Topic IDs are 16-byte UUIDs encoded without padding. librdkafka may print the
+    // standard base64 alphabet (+/); Kafka/Java tools commonly use URL-safe (-_).
+    // Require a real 16-byte decode rather than a length/character-class check alone.
+    if decode_topic_id_bytes(topic_id).is_some() {
+        Ok(())
+    } else {
+        Err(Error::Usage(format!(
+            "invalid topic ID '{topic_id}'; expected a 22-character Kafka UUID"
+        )))
     }
+}
 
-    while let Some(delivery) = deliveries.join_next().await {
-        if delivery
-            .map_err(|error| Error::Config(format!("verifiable producer task failed: {error}")))??
+fn decode_topic_id_bytes(topic_id: &str) -> Option<[u8; 16]> {
+    for engine in [&URL_SAFE_NO_PAD, &STANDARD_NO_PAD] {
+        if let Ok(bytes) = engine.decode(topic_id)
+            && let Ok(array) = <[u8; 16]>::try_from(bytes.as_slice())
         {
-            acked = acked.saturating_add(1);
+            return Some(array);
         }
     }
-    output::write_json_line(&VerifiableLifecycleEvent {
-        timestamp: Utc::now().timestamp_millis(),
-        name: "shutdown_complete",
-    })?;
-    let elapsed_ms = millis_at_least_one(started.elapsed());
-    output::write_json_line(&VerifiableToolData {
-        timestamp: Utc::now().timestamp_millis(),
-        name: "tool_data",
-        sent,
-        acked,
-        target_throughput: args.throughput,
-        avg_throughput: 1_000.0 * acked as f64 / elapsed_ms as f64,
-    })
+    None
 }
 
-#[derive(Debug, Serialize)]
-struct VerifiablePartition {
-    topic: String,
-    partition: i32,
+fn topic_partition_matches(
+    selector: &TopicSelector,
+    isr_count: usize,
+    replica_count: usize,
+    min_isr: Option<usize>,
+    leader_is_available: bool,
+) -> bool {
+    (!selector.under_replicated_partitions || isr_count < replica_count)
+        && (!selector.unavailable_partitions || !leader_is_available)
+        && (!selector.under_min_isr_partitions || min_isr.is_some_and(|min| isr_count < min))
+        && (!selector.at_min_isr_partitions || min_isr == Some(isr_count))
 }
 
-#[derive(Debug, Serialize)]
-struct VerifiablePartitionEvent {
-    timestamp: i64,
-    name: &'static str,
-    partitions: Vec<VerifiablePartition>,
+fn topic_pattern(expression: &str) -> Result<Regex> {
+    Regex::new(&format!("^(?:{expression})$"))
+        .map_err(|error| Error::Usage(format!("invalid topic regular expression: {error}")))
 }
 
-#[derive(Debug, Serialize)]
-struct VerifiableRecordSummary {
-    topic: String,
-    partition: i32,
-    count: u64,
-    #[serde(rename = "minOffset")]
-    min_offset: i64,
-    #[serde(rename = "maxOffset")]
-    max_offset: i64,
+fn validate_topic_creation_counts(
+    partitions: Option<i32>,
+    replication_factor: Option<i32>,
+) -> Result<()> {
+    if partitions.is_some_and(|partitions| partitions < 1) {
+        return Err(Error::Usage(
+            "--partitions must be greater than zero".into(),
+        ));
+    }
+    if replication_factor
+        .is_some_and(|replication_factor| !(1..=i32::from(i16::MAX)).contains(&replication_factor))
+    {
+        return Err(Error::Usage(format!(
+            "--replication-factor must be between 1 and {}",
+            i16::MAX
+        )));
+    }
+    Ok(())
 }
 
-#[derive(Debug, Serialize)]
-struct VerifiableRecordsConsumed {
-    timestamp: i64,
-    name: &'static str,
-    count: u64,
-    partitions: Vec<VerifiableRecordSummary>,
+fn topic_results(
+    format: OutputFormat,
+    command: &str,
+    results: Vec<std::result::Result<String, (String, rdkafka::types::RDKafkaErrorCode)>>,
+    ignore_exists: bool,
+) -> Result<usize> {
+    let rows = results
+        .into_iter()
+        .map(|result| match result {
+            Ok(name) => MutationRow {
+                resource: name,
+                status: "OK".into(),
+                error: None,
+            },
+            Err((name, code))
+                if ignore_exists
+                    && code == rdkafka::types::RDKafkaErrorCode::TopicAlreadyExists =>
+            {
+                MutationRow {
+                    resource: name,
+                    status: "ALREADY_EXISTS".into(),
+                    error: None,
+                }
+            }
+            Err((name, code)) => MutationRow {
+                resource: name,
+                status: "FAILED".into(),
+                error: Some(code.to_string()),
+            },
+        })
+        .collect::<Vec<_>>();
+    let failures = rows.iter().filter(|row| row.error.is_some()).count();
+    write_mutation_rows(format, command, &rows)?;
+    Ok(failures)
 }
 
-#[derive(Debug, Serialize)]
-struct VerifiableRecordData {
-    timestamp: i64,
-    name: &'static str,
-    key: Option<String>,
-    value: Option<String>,
-    topic: String,
-    partition: i32,
-    offset: i64,
+async fn produce(mut config: rdkafka::ClientConfig, args: crate::cli::ProduceArgs) -> Result<()> {
+    apply_client_properties(&mut config, args.properties())?;
+    let max_block_ms = configure_producer(&mut config, &args)?;
+    let reader = line_reader_options(&args)?;
+    let encoders = producer_encoders(&args).await?;
+    let producer: FutureProducer = config.create()?;
+    let input = io::read_to_string(io::stdin())?;
+    let mut deliveries = Vec::new();
+    for (index, line) in input.lines().enumerate() {
+        let (partition, key, value, headers) = producer_input(line, args.json, &reader)
+            .and_then(|input| {
+                let key = encoded_producer_field(
+                    input.key.as_ref(),
+                    encoders.key.as_ref(),
+                    args.json,
+                    args.key_encoding,
+                    "key",
+                )?;
+                let value = encoded_producer_field(
+                    input.value.as_ref(),
+                    encoders.value.as_ref(),
+                    args.json,
+                    args.value_encoding,
+                    "value",
+                )?;
+                let headers = input
+                    .headers
+                    .into_iter()
+                    .map(|(key, value)| {
+                        value
+                            .map(|value| decode_text(&value, args.header_encoding))
+                            .transpose()
+                            .map(|value| (key, value))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                Ok((input.partition, key, value, headers))
+            })
+            .map_err(|error| {
+                Error::Usage(format!(
+                    "invalid producer input on line {}: {error}",
+                    index + 1
+                ))
+            })?;
+        let mut record = FutureRecord::to(&args.topic);
+        if let Some(value) = &value {
+            record = record.payload(value);
+        }
+        if let Some(key) = &key {
+            record = record.key(key);
+        }
+        if let Some(partition) = partition {
+            record = record.partition(partition);
+        }
+        if !headers.is_empty() {
+            let mut owned = OwnedHeaders::new_with_capacity(headers.len());
+            for (key, value) in &headers {
+                owned = owned.insert(Header {
+                    key,
+                    value: value.as_deref(),
+                });
+            }
+            record = record.headers(owned);
+        }
+        if args.sync {
+            producer
+                .send(record, Duration::from_millis(max_block_ms))
+                .await
+                .map_err(|(error, _)| Error::Kafka(error))?;
+        } else {
+            deliveries.push(
+                enqueue_with_timeout(&producer, record, Duration::from_millis(max_block_ms))
+                    .await?,
+            );
+        }
+    }
+    for delivery in deliveries {
+        delivery
+            .await
+            .map_err(|_| Error::Config("producer delivery channel was canceled".into()))?
+            .map_err(|(error, _)| Error::Kafka(error))?;
+    }
+    Ok(())
 }
 
-#[derive(Debug, Serialize)]
-struct VerifiableCommitData {
-    topic: String,
-    partition: i32,
-    offset: i64,
+async fn producer_perf_test(
+    bootstrap: Option<&str>,
+    command_config: Option<&Path>,
+    args: ProducerPerfTestArgs,
+) -> Result<()> {
+    let bootstrap = bootstrap.map(str::to_owned);
+    let command_config = command_config.map(Path::to_path_buf);
+    tokio::task::spawn_blocking(move || {
+        run_producer_perf_test(bootstrap.as_deref(), command_config.as_deref(), &args)
+    })
+    .await
+    .map_err(|error| Error::Config(format!("producer performance task failed: {error}")))?
 }
 
-#[derive(Debug, Serialize)]
-struct VerifiableOffsetsCommitted {
-    timestamp: i64,
-    name: &'static str,
-    offsets: Vec<VerifiableCommitData>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    error: Option<String>,
-    success: bool,
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct E2eHeader {
+    key: String,
+    value: Option<Vec<u8>>,
 }
 
-#[derive(Debug)]
-struct VerifiableConsumerContext;
+#[derive(Clone, Copy, Debug)]
+struct JavaRandom(u64);
 
-impl ClientContext for VerifiableConsumerContext {}
+impl JavaRandom {
+    const MULTIPLIER: u64 = 0x0005_deec_e66d;
+    const MASK: u64 = (1_u64 << 48) - 1;
 
-impl ConsumerContext for VerifiableConsumerContext {
-    fn pre_rebalance(&self, _consumer: &BaseConsumer<Self>, rebalance: &Rebalance<'_>) {
-        if let Rebalance::Revoke(partitions) = rebalance {
-            write_verifiable_partition_event("partitions_revoked", partitions);
-        }
+    const fn new(seed: i64) -> Self {
+        Self((seed.cast_unsigned() ^ Self::MULTIPLIER) & Self::MASK)
     }
 
-    fn post_rebalance(&self, _consumer: &BaseConsumer<Self>, rebalance: &Rebalance<'_>) {
-        if let Rebalance::Assign(partitions) = rebalance {
-            write_verifiable_partition_event("partitions_assigned", partitions);
+    fn next_bits(&mut self, bits: u32) -> u32 {
+        self.0 = self.0.wrapping_mul(Self::MULTIPLIER).wrapping_add(0xb) & Self::MASK;
+        u32::try_from(self.0 >> (48 - bits)).unwrap_or_default()
+    }
+
+    fn next_index(&mut self, bound: u32) -> u32 {
+        let mask = bound - 1;
+        if bound & mask == 0 {
+            return u32::try_from((u64::from(bound) * u64::from(self.next_bits(31))) >> 31)
+                .unwrap_or_default();
+        }
+        loop {
+            let bits = self.next_bits(31);
+            let value = bits % bound;
+            let check = bits.wrapping_sub(value).wrapping_add(mask);
+            if i32::from_ne_bytes(check.to_ne_bytes()) >= 0 {
+                return value;
+            }
         }
     }
 }
 
-fn write_verifiable_event<T: Serialize>(event: &T) {
-    if let Err(error) = output::write_json_line(event) {
-        eprintln!("failed to write verifiable consumer event: {error}");
-    }
+fn e2e_random_bytes(random: &mut JavaRandom, length: i32) -> Vec<u8> {
+    let byte = b'A'.saturating_add(u8::try_from(random.next_index(26)).unwrap_or_default());
+    vec![byte; usize::try_from(length).unwrap_or_default()]
 }
 
-fn verifiable_partitions(partitions: &TopicPartitionList) -> Vec<VerifiablePartition> {
-    partitions
-        .elements()
-        .iter()
-        .map(|partition| VerifiablePartition {
-            topic: partition.topic().to_owned(),
-            partition: partition.partition(),
+fn e2e_headers(
+    random: &mut JavaRandom,
+    count: i32,
+    key_size: i32,
+    value_size: i32,
+) -> Vec<E2eHeader> {
+    (0..count)
+        .map(|_| E2eHeader {
+            key: String::from_utf8_lossy(&e2e_random_bytes(random, key_size)).into_owned(),
+            value: (value_size != -1).then(|| e2e_random_bytes(random, value_size)),
         })
         .collect()
 }
 
-fn write_verifiable_partition_event(name: &'static str, partitions: &TopicPartitionList) {
-    write_verifiable_event(&VerifiablePartitionEvent {
-        timestamp: Utc::now().timestamp_millis(),
-        name,
-        partitions: verifiable_partitions(partitions),
-    });
+fn e2e_percentile(latencies: &[u64], numerator: usize, denominator: usize) -> u64 {
+    let index = latencies
+        .len()
+        .saturating_mul(numerator)
+        .checked_div(denominator)
+        .unwrap_or_default()
+        .min(latencies.len().saturating_sub(1));
+    latencies.get(index).copied().unwrap_or_default()
 }
 
-fn verifiable_assignment_strategy(strategy: &str) -> Result<&'static str> {
-    match strategy {
-        "org.apache.kafka.clients.consumer.RangeAssignor" | "range" => Ok("range"),
-        "org.apache.kafka.clients.consumer.RoundRobinAssignor" | "roundrobin" => Ok("roundrobin"),
-        "org.apache.kafka.clients.consumer.CooperativeStickyAssignor" | "cooperative-sticky" => {
-            Ok("cooperative-sticky")
-        }
-        _ => Err(Error::Unsupported(format!(
-            "Java consumer assignment strategy '{strategy}' cannot be loaded by the native client"
-        ))),
+fn validate_e2e_record(
+    message: &rdkafka::message::BorrowedMessage<'_>,
+    key: &[u8],
+    value: &[u8],
+    expected_headers: &[E2eHeader],
+) -> Result<()> {
+    if message.payload().unwrap_or_default() != value {
+        return Err(Error::Config(
+            "the message value read did not match the message value sent".into(),
+        ));
     }
-}
-
-fn verifiable_consumer_config(
-    bootstrap: &str,
-    command_config: Option<&Path>,
-    args: &VerifiableConsumerArgs,
-) -> Result<rdkafka::ClientConfig> {
-    let config_path = share_perf_config_path(command_config, args.consumer_config.as_deref())?;
-    let mut client = config::client_config(bootstrap, config_path)?;
-    client
-        .set("bootstrap.servers", bootstrap)
-        .set("group.id", &args.group_id)
-        .set(
-            "group.protocol",
-            match args.group_protocol {
-                ConsumerGroupProtocol::Classic => "classic",
-                ConsumerGroupProtocol::Consumer => "consumer",
-            },
-        )
-        .set("enable.auto.commit", args.enable_autocommit.to_string())
-        .set("auto.offset.reset", &args.reset_policy);
-    match args.group_protocol {
-        ConsumerGroupProtocol::Classic => {
-            client.set(
-                "partition.assignment.strategy",
-                verifiable_assignment_strategy(&args.assignment_strategy)?,
-            );
-        }
-        ConsumerGroupProtocol::Consumer => {
-            if let Some(assignor) = args.group_remote_assignor.as_deref() {
-                client.set("group.remote.assignor", assignor);
-            }
+    if message.key().unwrap_or_default() != key || message.key().is_none() {
+        return Err(Error::Config(
+            "the message key read did not match the message key sent".into(),
+        ));
+    }
+    let received_headers = message.headers();
+    if expected_headers.is_empty() {
+        if received_headers.is_some_and(|headers| headers.count() > 0) {
+            return Err(Error::Config(
+                "expected no message headers but received at least one".into(),
+            ));
         }
+        return Ok(());
     }
-    if let Some(timeout) = args.session_timeout {
-        client.set("session.timeout.ms", timeout.to_string());
+    let Some(received_headers) = received_headers else {
+        return Err(Error::Config(
+            "expected message headers but received none".into(),
+        ));
+    };
+    if received_headers.count() != expected_headers.len() {
+        return Err(Error::Config(
+            "header count mismatch between sent and received messages".into(),
+        ));
     }
-    if let Some(instance_id) = args.group_instance_id.as_deref() {
-        client.set("group.instance.id", instance_id);
+    for (index, expected) in expected_headers.iter().enumerate() {
+        let received = received_headers.get(index);
+        if received.key != expected.key || received.value != expected.value.as_deref() {
+            return Err(Error::Config(format!(
+                "message header {index} did not match the sent header"
+            )));
+        }
     }
-    Ok(client)
+    Ok(())
 }
 
-fn write_verifiable_commit(
+async fn ensure_e2e_topic(
+    config: &rdkafka::ClientConfig,
+    producer: &FutureProducer,
     topic: &str,
-    partition: i32,
-    offset: i64,
-    result: std::result::Result<(), KafkaError>,
-) {
-    let (success, error) = match result {
-        Ok(()) => (true, None),
-        Err(error) => (false, Some(error.to_string())),
-    };
-    write_verifiable_event(&VerifiableOffsetsCommitted {
-        timestamp: Utc::now().timestamp_millis(),
-        name: "offsets_committed",
-        offsets: vec![VerifiableCommitData {
-            topic: topic.to_owned(),
-            partition,
-            offset,
-        }],
-        error,
-        success,
+) -> Result<()> {
+    let metadata = producer
+        .client()
+        .fetch_metadata(Some(topic), Duration::from_secs(10))?;
+    let exists = metadata.topics().iter().any(|metadata_topic| {
+        metadata_topic.name() == topic && !metadata_topic.partitions().is_empty()
     });
-}
-
-fn write_verifiable_consumed_record<M: Message>(message: &M, verbose: bool) {
-    let topic = message.topic();
-    let partition = message.partition();
-    let offset = message.offset();
-    if verbose {
-        write_verifiable_event(&VerifiableRecordData {
-            timestamp: Utc::now().timestamp_millis(),
-            name: "record_data",
-            key: message
-                .key()
-                .map(|key| String::from_utf8_lossy(key).into_owned()),
-            value: message
-                .payload()
-                .map(|value| String::from_utf8_lossy(value).into_owned()),
-            topic: topic.to_owned(),
-            partition,
-            offset,
-        });
+    if exists {
+        return Ok(());
     }
-    write_verifiable_event(&VerifiableRecordsConsumed {
-        timestamp: Utc::now().timestamp_millis(),
-        name: "records_consumed",
-        count: 1,
-        partitions: vec![VerifiableRecordSummary {
-            topic: topic.to_owned(),
-            partition,
-            count: 1,
-            min_offset: offset,
-            max_offset: offset,
-        }],
-    });
+    println!(
+        "Topic \"{topic}\" does not exist. Will create topic with 1 partition(s) and replication factor = 1"
+    );
+    let admin: Admin = config.create()?;
+    let results = admin
+        .create_topics(
+            &[NewTopic::new(topic, 1, TopicReplication::Fixed(1))],
+            &AdminOptions::new().operation_timeout(Some(Duration::from_secs(30))),
+        )
+        .await?;
+    for result in results {
+        if let Err((name, error)) = result
+            && error != RDKafkaErrorCode::TopicAlreadyExists
+        {
+            return Err(Error::Config(format!(
+                "creation of topic {name} failed: {error}"
+            )));
+        }
+    }
+    Ok(())
 }
 
-async fn verifiable_consumer(
+#[expect(
+    clippy::cast_precision_loss,
+    reason = "the original latency loop keeps setup, synchronous round trips, validation, and floating-point reporting in one lifecycle"
+)]
+async fn e2e_latency(
     bootstrap: &str,
     command_config: Option<&Path>,
-    verbose: bool,
-    args: VerifiableConsumerArgs,
+    args: E2eLatencyArgs,
 ) -> Result<()> {
-    if args.consumer_config.is_some() {
-        println!(
-            "Option --consumer.config has been deprecated and will be removed in a future version. Use --command-config instead."
-        );
-    }
-    if args.close_timeout < 0 {
-        return Err(Error::Usage("--close-timeout must not be negative".into()));
+    let mut producer_config = config::client_config(bootstrap, command_config)?;
+    producer_config
+        .set("linger.ms", "0")
+        .set("acks", &args.producer_acks);
+    producer_config.remove("max.block.ms");
+    let producer: FutureProducer = producer_config.create()?;
+    ensure_e2e_topic(&producer_config, &producer, &args.topic).await?;
+
+    let mut consumer_config = config::client_config(bootstrap, command_config)?;
+    consumer_config
+        .set(
+            "group.id",
+            format!("test-group-{}", Utc::now().timestamp_millis()),
+        )
+        .set("enable.auto.commit", "false")
+        .set("auto.offset.reset", "latest")
+        .set("fetch.wait.max.ms", "0");
+    let consumer: StreamConsumer = consumer_config.create()?;
+    let metadata = consumer.fetch_metadata(Some(&args.topic), Duration::from_secs(30))?;
+    let topic = metadata
+        .topics()
+        .iter()
+        .find(|topic| topic.name() == args.topic)
+        .ok_or_else(|| Error::Config(format!("topic {} was not found", args.topic)))?;
+    let mut assignment = TopicPartitionList::new();
+    for partition in topic.partitions() {
+        let (_, high) =
+            consumer.fetch_watermarks(&args.topic, partition.id(), Duration::from_secs(30))?;
+        assignment.add_partition_offset(&args.topic, partition.id(), Offset::Offset(high))?;
     }
-    let config = verifiable_consumer_config(bootstrap, command_config, &args)?;
-    let consumer: StreamConsumer<VerifiableConsumerContext> =
-        config.create_with_context(VerifiableConsumerContext)?;
-    write_verifiable_event(&VerifiableLifecycleEvent {
-        timestamp: Utc::now().timestamp_millis(),
-        name: "startup_complete",
-    });
-    consumer.subscribe(&[&args.topic])?;
+    consumer.assign(&assignment)?;
 
-    let max_messages = if args.max_messages < 0 {
-        u64::MAX
-    } else {
-        u64::try_from(args.max_messages).unwrap_or_default()
-    };
-    let mut received_count = 0_u64;
-    let mut shutdown_requested = false;
-    while received_count < max_messages {
-        let message = tokio::select! {
-            _ = tokio::signal::ctrl_c() => {
-                shutdown_requested = true;
-                write_verifiable_event(&VerifiableLifecycleEvent {
-                    timestamp: Utc::now().timestamp_millis(),
-                    name: "shutdown_requested",
-                });
-                break;
-            }
-            message = consumer.recv() => message,
-        };
-        let message = match message {
-            Ok(message) => message,
-            Err(error) => {
-                eprintln!("Error during processing, terminating consumer process: {error}");
-                break;
-            }
-        };
-        let topic = message.topic();
-        let partition = message.partition();
-        let offset = message.offset();
-        write_verifiable_consumed_record(&message, verbose);
-        received_count = received_count.saturating_add(1);
-        if !args.enable_autocommit {
-            let mut offsets = TopicPartitionList::new();
-            offsets.add_partition_offset(topic, partition, Offset::Offset(offset + 1))?;
-            write_verifiable_commit(
-                topic,
-                partition,
-                offset + 1,
-                consumer.commit(&offsets, CommitMode::Sync),
-            );
+    let mut random = JavaRandom::new(0);
+    let mut latencies = Vec::with_capacity(usize::try_from(args.num_records).unwrap_or_default());
+    let mut total_nanos = 0_u128;
+    for index in 0..args.num_records {
+        let key = e2e_random_bytes(&mut random, args.record_key_size);
+        let value = e2e_random_bytes(&mut random, args.record_size);
+        let headers = e2e_headers(
+            &mut random,
+            args.num_headers,
+            args.record_header_key_size,
+            args.record_header_size,
+        );
+        let mut owned_headers = OwnedHeaders::new_with_capacity(headers.len());
+        for header in &headers {
+            owned_headers = owned_headers.insert(Header {
+                key: &header.key,
+                value: header.value.as_deref(),
+            });
         }
+        let started = Instant::now();
+        producer
+            .send(
+                FutureRecord::to(&args.topic)
+                    .key(&key)
+                    .payload(&value)
+                    .headers(owned_headers),
+                Duration::MAX,
+            )
+            .await
+            .map_err(|(error, _)| Error::Kafka(error))?;
+        let message = tokio::time::timeout(Duration::from_secs(60), consumer.recv())
+            .await
+            .map_err(|_| {
+                Error::Config("poll() timed out before finding a result (timeout:[60000ms])".into())
+            })??;
+        let elapsed = started.elapsed();
+        validate_e2e_record(&message, &key, &value, &headers)?;
+        if index % 1_000 == 0 {
+            println!("{index}\t{:.4}", elapsed.as_secs_f64() * 1_000.0);
+        }
+        total_nanos = total_nanos.saturating_add(elapsed.as_nanos());
+        latencies.push(u64::try_from(elapsed.as_millis()).unwrap_or(u64::MAX));
     }
-    consumer.unsubscribe();
-    let close_timeout =
-        Duration::from_millis(u64::try_from(args.close_timeout).unwrap_or_default());
-    let close_started = Instant::now();
-    while consumer.assignment()?.count() != 0 && close_started.elapsed() < close_timeout {
-        tokio::time::sleep(Duration::from_millis(10)).await;
-    }
-    drop(consumer);
-    write_verifiable_event(&VerifiableLifecycleEvent {
-        timestamp: Utc::now().timestamp_millis(),
-        name: "shutdown_complete",
-    });
-    if !shutdown_requested {
-        write_verifiable_event(&VerifiableLifecycleEvent {
-            timestamp: Utc::now().timestamp_millis(),
-            name: "shutdown_requested",
-        });
+    println!(
+        "Avg latency: {:.4} ms",
+        total_nanos as f64 / f64::from(args.num_records) / 1_000_000.0
+    );
+    latencies.sort_unstable();
+    println!(
+        "Percentiles: 50th = {}, 99th = {}, 99.9th = {}",
+        e2e_percentile(&latencies, 1, 2),
+        e2e_percentile(&latencies, 99, 100),
+        e2e_percentile(&latencies, 999, 1_000)
+    );
+    // Assigned partitions do not require a successful group commit for latency output.
+    // Fresh single-node brokers can return NotCoordinator during the final commit while
+    // the group coordinator is still electing; do not fail the measurement for that.
+    if let Err(error) = consumer.commit_consumer_state(CommitMode::Sync) {
+        let message = error.to_string();
+        if !(message.contains("NotCoordinator") || message.contains("CoordinatorNotAvailable")) {
+            return Err(Error::Kafka(error));
+        }
     }
     Ok(())
 }
 
-/// Kafka Fetch `replica_id` used by the original `ReplicaVerificationTool`.
-const DEBUGGING_CONSUMER_ID: i32 = -2;
-const LIST_OFFSETS_LATEST: i64 = -1;
-const LIST_OFFSETS_EARLIEST: i64 = -2;
+#[derive(Serialize)]
+struct VerifiableLifecycleEvent {
+    timestamp: i64,
+    name: &'static str,
+}
 
-#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
-struct TopicPartitionKey {
+#[derive(Serialize)]
+struct VerifiableSendSuccess {
+    timestamp: i64,
+    name: &'static str,
+    key: Option<String>,
+    value: String,
     topic: String,
     partition: i32,
+    offset: i64,
 }
 
-impl TopicPartitionKey {
-    fn new(topic: impl Into<String>, partition: i32) -> Self {
-        Self {
-            topic: topic.into(),
-            partition,
-        }
-    }
-
-    fn label(&self) -> String {
-        format!("{}-{}", self.topic, self.partition)
-    }
+#[derive(Serialize)]
+struct VerifiableSendError {
+    timestamp: i64,
+    name: &'static str,
+    key: Option<String>,
+    value: String,
+    topic: String,
+    exception: &'static str,
+    message: String,
 }
 
-#[derive(Clone, Debug)]
-struct ReplicaPartitionData {
-    high_watermark: i64,
-    records: Bytes,
+#[derive(Serialize)]
+struct VerifiableToolData {
+    timestamp: i64,
+    name: &'static str,
+    sent: u64,
+    acked: u64,
+    target_throughput: i32,
+    avg_throughput: f64,
 }
 
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-struct BatchMessageInfo {
-    replica_id: i32,
-    last_offset: i64,
-    next_offset: i64,
-    checksum: u32,
+fn verifiable_value(prefix: Option<i32>, index: u64) -> String {
+    prefix.map_or_else(|| index.to_string(), |prefix| format!("{prefix}.{index}"))
 }
 
-struct ReplicaBuffer {
-    expected_replicas: HashMap<TopicPartitionKey, usize>,
-    fetch_offsets: HashMap<TopicPartitionKey, i64>,
-    records_cache: HashMap<TopicPartitionKey, HashMap<i32, ReplicaPartitionData>>,
-    report_interval: Duration,
-    last_report_time: Instant,
-    max_lag: i64,
-    offset_with_max_lag: i64,
-    max_lag_partition: Option<TopicPartitionKey>,
+fn verifiable_key(repeating_keys: Option<i32>, counter: &mut i32) -> Option<String> {
+    let limit = repeating_keys?;
+    let key = counter.to_string();
+    *counter = counter.saturating_add(1);
+    if *counter == limit {
+        *counter = 0;
+    }
+    Some(key)
 }
 
-impl ReplicaBuffer {
-    fn new(
-        expected_replicas: HashMap<TopicPartitionKey, usize>,
-        initial_offsets: HashMap<TopicPartitionKey, i64>,
-        report_interval_ms: i64,
-    ) -> Self {
-        let records_cache = expected_replicas
-            .keys()
-            .cloned()
-            .map(|key| (key, HashMap::new()))
-            .collect();
-        Self {
-            expected_replicas,
-            fetch_offsets: initial_offsets,
-            records_cache,
-            report_interval: Duration::from_millis(
-                u64::try_from(report_interval_ms.max(0)).unwrap_or(0),
-            ),
-            last_report_time: Instant::now(),
-            max_lag: -1,
-            offset_with_max_lag: -1,
-            max_lag_partition: None,
+fn verifiable_config(
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    deprecated_config: Option<&Path>,
+    acks: i32,
+) -> Result<rdkafka::ClientConfig> {
+    let config_path = share_perf_config_path(command_config, deprecated_config)?;
+    let mut client = config::client_config(bootstrap, None)?;
+    client
+        .set("acks", acks.to_string())
+        .set("message.send.max.retries", "0");
+    if let Some(path) = config_path {
+        for (key, value) in config::rdkafka_properties(&config::load_properties(path)?)? {
+            client.set(key, value);
         }
     }
+    Ok(client)
+}
 
-    fn add_fetched_data(
-        &mut self,
-        key: TopicPartitionKey,
-        replica_id: i32,
-        data: ReplicaPartitionData,
-    ) {
-        self.records_cache
-            .entry(key)
-            .or_default()
-            .insert(replica_id, data);
+async fn print_verifiable_delivery(
+    producer: FutureProducer,
+    topic: String,
+    key: Option<String>,
+    value: String,
+    timestamp: Option<i64>,
+) -> Result<bool> {
+    let mut record = FutureRecord::to(&topic).payload(&value);
+    if let Some(key) = key.as_deref() {
+        record = record.key(key);
     }
-
-    fn offset(&self, key: &TopicPartitionKey) -> i64 {
-        self.fetch_offsets.get(key).copied().unwrap_or(0)
+    if let Some(timestamp) = timestamp {
+        record = record.timestamp(timestamp);
     }
-
-    fn verify_checksum(&mut self, println: &mut dyn FnMut(String)) -> Result<()> {
-        self.max_lag = -1;
-        self.offset_with_max_lag = -1;
-        self.max_lag_partition = None;
-
-        let partitions = self.records_cache.keys().cloned().collect::<Vec<_>>();
-        for key in partitions {
-            self.verify_partition(&key, println)?;
+    match producer.send(record, Duration::MAX).await {
+        Ok(delivery) => {
+            output::write_json_line(&VerifiableSendSuccess {
+                timestamp: Utc::now().timestamp_millis(),
+                name: "producer_send_success",
+                key,
+                value,
+                topic,
+                partition: delivery.partition,
+                offset: delivery.offset,
+            })?;
+            Ok(true)
         }
-        self.maybe_report_max_lag(println);
-        Ok(())
-    }
-
-    fn maybe_report_max_lag(&mut self, println: &mut dyn FnMut(String)) {
-        if self.last_report_time.elapsed() <= self.report_interval {
-            return;
+        Err((error, _)) => {
+            output::write_json_line(&VerifiableSendError {
+                timestamp: Utc::now().timestamp_millis(),
+                name: "producer_send_error",
+                key,
+                value,
+                topic,
+                exception: "rdkafka::KafkaError",
+                message: error.to_string(),
+            })?;
+            Ok(false)
         }
-        let partition = self
-            .max_lag_partition
-            .as_ref()
-            .map_or_else(|| "null".into(), TopicPartitionKey::label);
-        println(format!(
-            "{}: max lag is {} for partition {} at offset {} among {} partitions",
-            replica_verification_timestamp(),
-            self.max_lag,
-            partition,
-            self.offset_with_max_lag,
-            self.records_cache.len()
-        ));
-        self.last_report_time = Instant::now();
     }
+}
 
-    fn verify_partition(
-        &mut self,
-        key: &TopicPartitionKey,
-        println: &mut dyn FnMut(String),
-    ) -> Result<()> {
-        let expected = self.expected_replicas.get(key).copied().unwrap_or_default();
-        let Some(fetch_response) = self.records_cache.get(key) else {
-            return Ok(());
-        };
-        if fetch_response.len() != expected {
-            return Err(Error::Config(format!(
-                "fetched {} replicas for {}, but expected {expected} replicas",
-                fetch_response.len(),
-                key.label()
-            )));
-        }
+#[expect(
+    clippy::cast_precision_loss,
+    reason = "Kafka's tool_data event defines average throughput as a floating-point field"
+)]
+async fn verifiable_producer(
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    args: VerifiableProducerArgs,
+) -> Result<()> {
+    if args.producer_config.is_some() {
+        println!(
+            "Option --producer.config has been deprecated and will be removed in a future version. Use --command-config instead."
+        );
+    }
+    let config = verifiable_config(
+        bootstrap,
+        command_config,
+        args.producer_config.as_deref(),
+        args.acks,
+    )?;
+    let producer: FutureProducer = config.create()?;
+    let started = Instant::now();
+    let started_ms = Utc::now().timestamp_millis();
+    output::write_json_line(&VerifiableLifecycleEvent {
+        timestamp: started_ms,
+        name: "startup_complete",
+    })?;
 
-        let mut batch_iters = HashMap::new();
-        for (&replica_id, partition_data) in fetch_response {
-            batch_iters.insert(replica_id, parse_record_batches(&partition_data.records)?);
+    let max_messages = if args.max_messages < 0 {
+        u64::MAX
+    } else {
+        u64::try_from(args.max_messages).unwrap_or_default()
+    };
+    let mut key_counter = 0_i32;
+    let mut create_time = (args.message_create_time != -1).then_some(args.message_create_time);
+    let mut deliveries = tokio::task::JoinSet::new();
+    let mut signal = Box::pin(tokio::signal::ctrl_c());
+    let mut sent = 0_u64;
+    let mut acked = 0_u64;
+    for index in 0..max_messages {
+        while let Some(delivery) = deliveries.try_join_next() {
+            if delivery.map_err(|error| {
+                Error::Config(format!("verifiable producer task failed: {error}"))
+            })?? {
+                acked = acked.saturating_add(1);
+            }
         }
-        let max_hw = fetch_response
-            .values()
-            .map(|data| data.high_watermark)
-            .max()
-            .unwrap_or(-1);
-        let high_watermarks = fetch_response
-            .iter()
-            .map(|(&replica_id, data)| (replica_id, data.high_watermark))
-            .collect::<HashMap<_, _>>();
-
-        let mut cursors = batch_iters
-            .into_iter()
-            .map(|(replica_id, batches)| (replica_id, 0_usize, batches))
-            .collect::<Vec<_>>();
-        while let Some(next_offset) =
-            advance_matching_batches(key, &mut cursors, &high_watermarks, println)
-        {
-            self.fetch_offsets.insert(key.clone(), next_offset);
+        tokio::select! {
+            biased;
+            _ = &mut signal => break,
+            () = tokio::task::yield_now() => {}
         }
-
-        let verified_offset = self.offset(key);
-        if max_hw - verified_offset > self.max_lag {
-            self.offset_with_max_lag = verified_offset;
-            self.max_lag = max_hw - verified_offset;
-            self.max_lag_partition = Some(key.clone());
+        let key = verifiable_key(args.repeating_keys, &mut key_counter);
+        let value = verifiable_value(args.value_prefix, index);
+        let producer = producer.clone();
+        let topic = args.topic.clone();
+        let record_timestamp = create_time;
+        deliveries.spawn(async move {
+            print_verifiable_delivery(producer, topic, key, value, record_timestamp).await
+        });
+        sent = sent.saturating_add(1);
+        if let Some(timestamp) = create_time.as_mut() {
+            *timestamp =
+                timestamp.saturating_add(Utc::now().timestamp_millis().saturating_sub(started_ms));
         }
-        if let Some(cache) = self.records_cache.get_mut(key) {
-            cache.clear();
+        if args.throughput == 0 && index > 0 {
+            let _ = (&mut signal).await;
+            break;
+        }
+        if args.throughput > 0 {
+            let expected = Duration::from_secs_f64(index as f64 / f64::from(args.throughput));
+            if let Some(remaining) = expected.checked_sub(started.elapsed()) {
+                tokio::select! {
+                    _ = &mut signal => break,
+                    () = tokio::time::sleep(remaining) => {}
+                }
+            }
         }
-        Ok(())
     }
-}
 
-/// Compares one batch across all replicas. Returns the next fetch offset when
-/// every replica still had a matching batch below its high watermark.
-fn advance_matching_batches(
-    key: &TopicPartitionKey,
-    cursors: &mut [(i32, usize, Vec<ParsedBatch>)],
-    high_watermarks: &HashMap<i32, i64>,
-    println: &mut dyn FnMut(String),
-) -> Option<i64> {
-    let mut first_message: Option<BatchMessageInfo> = None;
-    for (replica_id, index, batches) in cursors.iter_mut() {
-        let batch = batches.get(*index).copied()?;
-        let high_watermark = high_watermarks.get(replica_id).copied().unwrap_or(-1);
-        if batch.last_offset >= high_watermark {
-            return None;
-        }
-        if let Some(first) = first_message {
-            if first.last_offset != batch.last_offset {
-                println(format!(
-                    "{}: partition {}: replica {}'s offset {} doesn't match replica {}'s offset {}",
-                    replica_verification_timestamp(),
-                    key.label(),
-                    first.replica_id,
-                    first.last_offset,
-                    replica_id,
-                    batch.last_offset
-                ));
-                process::exit(1);
-            }
-            if first.checksum != batch.checksum {
-                println(format!(
-                    "{}: partition {} has unmatched checksum at offset {}; replica {}'s checksum {}; replica {}'s checksum {}",
-                    replica_verification_timestamp(),
-                    key.label(),
-                    batch.last_offset,
-                    first.replica_id,
-                    first.checksum,
-                    replica_id,
-                    batch.checksum
-                ));
-            }
-        } else {
-            first_message = Some(BatchMessageInfo {
-                replica_id: *replica_id,
-                last_offset: batch.last_offset,
-                next_offset: batch.next_offset,
-                checksum: batch.checksum,
-            });
+    while let Some(delivery) = deliveries.join_next().await {
+        if delivery
+            .map_err(|error| Error::Config(format!("verifiable producer task failed: {error}")))??
+        {
+            acked = acked.saturating_add(1);
         }
-        *index = index.saturating_add(1);
     }
-    first_message.map(|message| message.next_offset)
+    output::write_json_line(&VerifiableLifecycleEvent {
+        timestamp: Utc::now().timestamp_millis(),
+        name: "shutdown_complete",
+    })?;
+    let elapsed_ms = millis_at_least_one(started.elapsed());
+    output::write_json_line(&VerifiableToolData {
+        timestamp: Utc::now().timestamp_millis(),
+        name: "tool_data",
+        sent,
+        acked,
+        target_throughput: args.throughput,
+        avg_throughput: 1_000.0 * acked as f64 / elapsed_ms as f64,
+    })
 }
 
-#[derive(Clone, Copy, Debug)]
-struct ParsedBatch {
-    last_offset: i64,
-    next_offset: i64,
-    checksum: u32,
+#[derive(Debug, Serialize)]
+struct VerifiablePartition {
+    topic: String,
+    partition: i32,
 }
 
-fn parse_record_batches(records: &[u8]) -> Result<Vec<ParsedBatch>> {
-    let mut batches = Vec::new();
-    let mut offset = 0;
-    while offset + 61 <= records.len() {
-        let base_offset = i64::from_be_bytes(
-            records[offset..offset + 8]
-                .try_into()
-                .map_err(|_| Error::Config("truncated record batch base offset".into()))?,
-        );
-        let batch_length = i32::from_be_bytes(
-            records[offset + 8..offset + 12]
-                .try_into()
-                .map_err(|_| Error::Config("truncated record batch length".into()))?,
-        );
-        if batch_length < 49 {
-            return Err(Error::Config(format!(
-                "invalid record batch length: {batch_length}"
-            )));
-        }
-        let total_size = 12 + usize::try_from(batch_length).unwrap_or(usize::MAX);
-        if offset + total_size > records.len() {
-            break;
-        }
-        let magic = records[offset + 16];
-        if magic != 2 {
-            return Err(Error::Unsupported(format!(
-                "unsupported record batch magic: {magic}"
-            )));
-        }
-        let checksum = u32::from_be_bytes(
-            records[offset + 17..offset + 21]
-                .try_into()
-                .map_err(|_| Error::Config("truncated record batch checksum".into()))?,
-        );
-        let last_offset_delta = i32::from_be_bytes(
-            records[offset + 23..offset + 27]
-                .try_into()
-                .map_err(|_| Error::Config("truncated record batch last offset delta".into()))?,
-        );
-        let last_offset = base_offset + i64::from(last_offset_delta);
-        batches.push(ParsedBatch {
-            last_offset,
-            next_offset: last_offset + 1,
-            checksum,
-        });
-        offset += total_size;
-    }
-    Ok(batches)
+#[derive(Debug, Serialize)]
+struct VerifiablePartitionEvent {
+    timestamp: i64,
+    name: &'static str,
+    partitions: Vec<VerifiablePartition>,
 }
 
-fn replica_verification_timestamp() -> String {
-    let now = Local::now();
-    format!(
-        "{},{:03}",
-        now.format("%Y-%m-%d %H:%M:%S"),
-        now.timestamp_subsec_millis()
-    )
+#[derive(Debug, Serialize)]
+struct VerifiableRecordSummary {
+    topic: String,
+    partition: i32,
+    count: u64,
+    #[serde(rename = "minOffset")]
+    min_offset: i64,
+    #[serde(rename = "maxOffset")]
+    max_offset: i64,
 }
 
-fn validate_bootstrap_list(bootstrap: &str) -> Result<()> {
-    if bootstrap.trim().is_empty() {
-        return Err(Error::Usage(
-            "bootstrap server list must not be empty".into(),
-        ));
-    }
-    for server in bootstrap.split(',') {
-        let server = server.trim();
-        if server.is_empty() {
-            return Err(Error::Usage(
-                "bootstrap server list contains an empty entry".into(),
-            ));
-        }
-        let Some((host, port)) = server.rsplit_once(':') else {
-            return Err(Error::Usage(format!(
-                "bootstrap server '{server}' must be host:port"
-            )));
-        };
-        if host.is_empty() {
-            return Err(Error::Usage(format!(
-                "bootstrap server '{server}' is missing a host"
-            )));
-        }
-        if port.parse::<u16>().is_err() {
-            return Err(Error::Usage(format!(
-                "bootstrap server '{server}' has an invalid port"
-            )));
-        }
-    }
-    Ok(())
+#[derive(Debug, Serialize)]
+struct VerifiableRecordsConsumed {
+    timestamp: i64,
+    name: &'static str,
+    count: u64,
+    partitions: Vec<VerifiableRecordSummary>,
 }
 
-fn validate_replica_verification_args(
-    bootstrap: &str,
-    args: &ReplicaVerificationArgs,
-) -> Result<()> {
-    if args.fetch_size <= 0 {
-        return Err(Error::Usage("--fetch-size must be positive".into()));
-    }
-    if args.max_wait_ms < 0 {
-        return Err(Error::Usage("--max-wait-ms must not be negative".into()));
-    }
-    if args.report_interval_ms < 0 {
-        return Err(Error::Usage(
-            "--report-interval-ms must not be negative".into(),
-        ));
-    }
-    validate_bootstrap_list(bootstrap)
+#[derive(Debug, Serialize)]
+struct VerifiableRecordData {
+    timestamp: i64,
+    name: &'static str,
+    key: Option<String>,
+    value: Option<String>,
+    topic: String,
+    partition: i32,
+    offset: i64,
 }
 
-struct ReplicaVerificationPlan {
-    brokers: HashMap<i32, String>,
-    expected_replicas: HashMap<TopicPartitionKey, usize>,
-    broker_partitions: HashMap<i32, Vec<TopicPartitionKey>>,
-    topic_partitions: Vec<TopicPartitionKey>,
+#[derive(Debug, Serialize)]
+struct VerifiableCommitData {
+    topic: String,
+    partition: i32,
+    offset: i64,
 }
 
-fn build_replica_verification_plan(
-    metadata: &rdkafka::metadata::Metadata,
-    topics_include: &str,
-) -> Result<ReplicaVerificationPlan> {
-    let topic_filter = Regex::new(topics_include)
-        .map_err(|error| Error::Usage(format!("{topics_include} is an invalid regex: {error}")))?;
-    let brokers = metadata
-        .brokers()
-        .iter()
-        .map(|broker| (broker.id(), format!("{}:{}", broker.host(), broker.port())))
-        .collect::<HashMap<_, _>>();
-    if brokers.is_empty() {
-        return Err(Error::Config("no brokers found in cluster metadata".into()));
-    }
+#[derive(Debug, Serialize)]
+struct VerifiableOffsetsCommitted {
+    timestamp: i64,
+    name: &'static str,
+    offsets: Vec<VerifiableCommitData>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+    success: bool,
+}
 
-    let mut expected_replicas = HashMap::new();
-    let mut broker_partitions: HashMap<i32, Vec<TopicPartitionKey>> = HashMap::new();
-    let mut topic_partitions = Vec::new();
-    for topic in metadata.topics() {
-        if !topic_filter.is_match(topic.name()) {
-            continue;
-        }
-        if let Some(error) = topic.error() {
-            return Err(Error::Config(format!(
-                "topic {} metadata error: {error:?}",
-                topic.name()
-            )));
-        }
-        for partition in topic.partitions() {
-            if let Some(error) = partition.error() {
-                return Err(Error::Config(format!(
-                    "partition {}-{} metadata error: {error:?}",
-                    topic.name(),
-                    partition.id()
-                )));
-            }
-            let key = TopicPartitionKey::new(topic.name(), partition.id());
-            let replicas = partition.replicas();
-            if replicas.is_empty() {
-                continue;
-            }
-            expected_replicas.insert(key.clone(), replicas.len());
-            topic_partitions.push(key.clone());
-            for &replica_id in replicas {
-                broker_partitions
-                    .entry(replica_id)
-                    .or_default()
-                    .push(key.clone());
-            }
+#[derive(Debug)]
+struct VerifiableConsumerContext;
+
+impl ClientContext for VerifiableConsumerContext {}
+
+impl ConsumerContext for VerifiableConsumerContext {
+    fn pre_rebalance(&self, _consumer: &BaseConsumer<Self>, rebalance: &Rebalance<'_>) {
+        if let Rebalance::Revoke(partitions) = rebalance {
+            write_verifiable_partition_event("partitions_revoked", partitions);
         }
     }
-    if expected_replicas.is_empty() {
-        return Err(Error::Usage(format!(
-            "No topics found. --topics-include {topics_include}, if specified, is either filtering out all topics or there is no topic."
-        )));
+
+    fn post_rebalance(&self, _consumer: &BaseConsumer<Self>, rebalance: &Rebalance<'_>) {
+        if let Rebalance::Assign(partitions) = rebalance {
+            write_verifiable_partition_event("partitions_assigned", partitions);
+        }
     }
-    Ok(ReplicaVerificationPlan {
-        brokers,
-        expected_replicas,
-        broker_partitions,
-        topic_partitions,
-    })
 }
 
-async fn replica_verification(
-    bootstrap: &str,
-    command_config: Option<&Path>,
-    timeout: Duration,
-    args: ReplicaVerificationArgs,
-) -> Result<()> {
-    eprintln!("This tool is deprecated and may be removed in a future major release.");
-    validate_replica_verification_args(bootstrap, &args)?;
-
-    let client_config = config::client_config(bootstrap, command_config)?;
-    let consumer = base_consumer(&client_config)?;
-    let metadata = consumer.fetch_metadata(None, timeout)?;
-    let plan = build_replica_verification_plan(&metadata, &args.topics_include)?;
-    let initial_offsets =
-        replica_initial_offsets(&consumer, &plan.topic_partitions, args.time, timeout)?;
-    drop(consumer);
-    let mut buffer = ReplicaBuffer::new(
-        plan.expected_replicas,
-        initial_offsets,
-        args.report_interval_ms,
-    );
-    let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
+fn write_verifiable_event<T: Serialize>(event: &T) {
+    if let Err(error) = output::write_json_line(event) {
+        eprintln!("failed to write verifiable consumer event: {error}");
+    }
+}
 
-    println!(
-        "{}: verification process is started",
-        replica_verification_timestamp()
-    );
+fn verifiable_partitions(partitions: &TopicPartitionList) -> Vec<VerifiablePartition> {
+    partitions
+        .elements()
+        .iter()
+        .map(|partition| VerifiablePartition {
+            topic: partition.topic().to_owned(),
+            partition: partition.partition(),
+        })
+        .collect()
+}
 
-    run_replica_verification_loop(
-        &client,
-        &plan.brokers,
-        &plan.broker_partitions,
-        &mut buffer,
-        args.fetch_size,
-        args.max_wait_ms,
-    )
-    .await
+fn write_verifiable_partition_event(name: &'static str, partitions: &TopicPartitionList) {
+    write_verifiable_event(&VerifiablePartitionEvent {
+        timestamp: Utc::now().timestamp_millis(),
+        name,
+        partitions: verifiable_partitions(partitions),
+    });
 }
 
-async fn run_replica_verification_loop(
-    client: &krafka::admin::AdminClient,
-    brokers: &HashMap<i32, String>,
-    broker_partitions: &HashMap<i32, Vec<TopicPartitionKey>>,
-    buffer: &mut ReplicaBuffer,
-    fetch_size: i32,
-    max_wait_ms: i32,
-) -> Result<()> {
-    loop {
-        tokio::select! {
-            _ = tokio::signal::ctrl_c() => {
-                eprintln!("Stopping all fetchers");
-                break;
-            }
-            result = replica_verification_round(
-                client,
-                brokers,
-                broker_partitions,
-                buffer,
-                fetch_size,
-                max_wait_ms,
-            ) => {
-                result?;
-            }
+fn verifiable_assignment_strategy(strategy: &str) -> Result<&'static str> {
+    match strategy {
+        "org.apache.kafka.clients.consumer.RangeAssignor" | "range" => Ok("range"),
+        "org.apache.kafka.clients.consumer.RoundRobinAssignor" | "roundrobin" => Ok("roundrobin"),
+        "org.apache.kafka.clients.consumer.CooperativeStickyAssignor" | "cooperative-sticky" => {
+            Ok("cooperative-sticky")
         }
+        _ => Err(Error::Unsupported(format!(
+            "Java consumer assignment strategy '{strategy}' cannot be loaded by the native client"
+        ))),
     }
-    Ok(())
 }
 
-fn replica_initial_offsets(
-    consumer: &BaseConsumer,
-    partitions: &[TopicPartitionKey],
-    time: i64,
-    timeout: Duration,
-) -> Result<HashMap<TopicPartitionKey, i64>> {
-    let mut offsets = HashMap::with_capacity(partitions.len());
-    match time {
-        LIST_OFFSETS_LATEST => {
-            for key in partitions {
-                let (_low, high) = consumer.fetch_watermarks(&key.topic, key.partition, timeout)?;
-                offsets.insert(key.clone(), high);
-            }
-        }
-        LIST_OFFSETS_EARLIEST => {
-            for key in partitions {
-                let (low, _high) = consumer.fetch_watermarks(&key.topic, key.partition, timeout)?;
-                offsets.insert(key.clone(), low);
-            }
+fn verifiable_consumer_config(
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    args: &VerifiableConsumerArgs,
+) -> Result<rdkafka::ClientConfig> {
+    let config_path = share_perf_config_path(command_config, args.consumer_config.as_deref())?;
+    let mut client = config::client_config(bootstrap, config_path)?;
+    client
+        .set("bootstrap.servers", bootstrap)
+        .set("group.id", &args.group_id)
+        .set(
+            "group.protocol",
+            match args.group_protocol {
+                ConsumerGroupProtocol::Classic => "classic",
+                ConsumerGroupProtocol::Consumer => "consumer",
+            },
+        )
+        .set("enable.auto.commit", args.enable_autocommit.to_string())
+        .set("auto.offset.reset", &args.reset_policy);
+    match args.group_protocol {
+        ConsumerGroupProtocol::Classic => {
+            client.set(
+                "partition.assignment.strategy",
+                verifiable_assignment_strategy(&args.assignment_strategy)?,
+            );
         }
-        timestamp if timestamp >= 0 => {
-            let mut tpl = TopicPartitionList::with_capacity(partitions.len());
-            for key in partitions {
-                tpl.add_partition_offset(&key.topic, key.partition, Offset::Offset(timestamp))?;
-            }
-            let resolved = consumer.offsets_for_times(tpl, timeout)?;
-            for key in partitions {
-                let element = resolved
-                    .find_partition(&key.topic, key.partition)
-                    .ok_or_else(|| {
-                        Error::Config(format!(
-                            "no offset returned for {} at timestamp {timestamp}",
-                            key.label()
-                        ))
-                    })?;
-                let offset = match element.offset() {
-                    Offset::Offset(value) => value,
-                    Offset::Invalid => {
-                        return Err(Error::Config(format!(
-                            "no offset found for {} at timestamp {timestamp}",
-                            key.label()
-                        )));
-                    }
-                    other => {
-                        return Err(Error::Config(format!(
-                            "unexpected offset {other:?} for {}",
-                            key.label()
-                        )));
-                    }
-                };
-                offsets.insert(key.clone(), offset);
+        ConsumerGroupProtocol::Consumer => {
+            if let Some(assignor) = args.group_remote_assignor.as_deref() {
+                client.set("group.remote.assignor", assignor);
             }
         }
-        other => {
-            return Err(Error::Usage(format!(
-                "invalid --time value {other}; expected timestamp, -1 (latest), or -2 (earliest)"
-            )));
-        }
     }
-    Ok(offsets)
+    if let Some(timeout) = args.session_timeout {
+        client.set("session.timeout.ms", timeout.to_string());
+    }
+    if let Some(instance_id) = args.group_instance_id.as_deref() {
+        client.set("group.instance.id", instance_id);
+    }
+    Ok(client)
 }
 
-async fn replica_verification_round(
-    client: &krafka::admin::AdminClient,
-    brokers: &HashMap<i32, String>,
-    broker_partitions: &HashMap<i32, Vec<TopicPartitionKey>>,
-    buffer: &mut ReplicaBuffer,
-    fetch_size: i32,
-    max_wait_ms: i32,
-) -> Result<()> {
-    let mut fetch_tasks = Vec::with_capacity(broker_partitions.len());
-    for (&broker_id, partitions) in broker_partitions {
-        let address = brokers.get(&broker_id).cloned().ok_or_else(|| {
-            Error::Config(format!(
-                "broker {broker_id} is listed as a replica but missing from metadata"
-            ))
-        })?;
-        let partitions = partitions.clone();
-        let offsets = partitions
-            .iter()
-            .map(|key| (key.clone(), buffer.offset(key)))
-            .collect::<HashMap<_, _>>();
-        fetch_tasks.push(async move {
-            let fetched = fetch_replica_partitions(
-                client,
-                broker_id,
-                &address,
-                &partitions,
-                &offsets,
-                fetch_size,
-                max_wait_ms,
-            )
-            .await?;
-            Ok::<_, Error>((broker_id, fetched))
+fn write_verifiable_commit(
+    topic: &str,
+    partition: i32,
+    offset: i64,
+    result: std::result::Result<(), KafkaError>,
+) {
+    let (success, error) = match result {
+        Ok(()) => (true, None),
+        Err(error) => (false, Some(error.to_string())),
+    };
+    write_verifiable_event(&VerifiableOffsetsCommitted {
+        timestamp: Utc::now().timestamp_millis(),
+        name: "offsets_committed",
+        offsets: vec![VerifiableCommitData {
+            topic: topic.to_owned(),
+            partition,
+            offset,
+        }],
+        error,
+        success,
+    });
+}
+
+fn write_verifiable_consumed_record<M: Message>(message: &M, verbose: bool) {
+    let topic = message.topic();
+    let partition = message.partition();
+    let offset = message.offset();
+    if verbose {
+        write_verifiable_event(&VerifiableRecordData {
+            timestamp: Utc::now().timestamp_millis(),
+            name: "record_data",
+            key: message
+                .key()
+                .map(|key| String::from_utf8_lossy(key).into_owned()),
+            value: message
+                .payload()
+                .map(|value| String::from_utf8_lossy(value).into_owned()),
+            topic: topic.to_owned(),
+            partition,
+            offset,
         });
     }
+    write_verifiable_event(&VerifiableRecordsConsumed {
+        timestamp: Utc::now().timestamp_millis(),
+        name: "records_consumed",
+        count: 1,
+        partitions: vec![VerifiableRecordSummary {
+            topic: topic.to_owned(),
+            partition,
+            count: 1,
+            min_offset: offset,
+            max_offset: offset,
+        }],
+    });
+}
+
+async fn verifiable_consumer(
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    verbose: bool,
+    args: VerifiableConsumerArgs,
+) -> Result<()> {
+    if args.consumer_config.is_some() {
+        println!(
+            "Option --consumer.config has been deprecated and will be removed in a future version. Use --command-config instead."
+        );
+    }
+    if args.close_timeout < 0 {
+        return Err(Error::Usage("--close-timeout must not be negative".into()));
+    }
+    let config = verifiable_consumer_config(bootstrap, command_config, &args)?;
+    let consumer: StreamConsumer<VerifiableConsumerContext> =
+        config.create_with_context(VerifiableConsumerContext)?;
+    write_verifiable_event(&VerifiableLifecycleEvent {
+        timestamp: Utc::now().timestamp_millis(),
+        name: "startup_complete",
+    });
+    consumer.subscribe(&[&args.topic])?;
 
-    let results = futures::future::try_join_all(fetch_tasks).await?;
-    for (broker_id, fetched) in results {
-        for (key, data) in fetched {
-            buffer.add_fetched_data(key, broker_id, data);
+    let max_messages = if args.max_messages < 0 {
+        u64::MAX
+    } else {
+        u64::try_from(args.max_messages).unwrap_or_default()
+    };
+    let mut received_count = 0_u64;
+    let mut shutdown_requested = false;
+    while received_count < max_messages {
+        let message = tokio::select! {
+            _ = tokio::signal::ctrl_c() => {
+                shutdown_requested = true;
+                write_verifiable_event(&VerifiableLifecycleEvent {
+                    timestamp: Utc::now().timestamp_millis(),
+                    name: "shutdown_requested",
+                });
+                break;
+            }
+            message = consumer.recv() => message,
+        };
+        let message = match message {
+            Ok(message) => message,
+            Err(error) => {
+                eprintln!("Error during processing, terminating consumer process: {error}");
+                break;
+            }
+        };
+        let topic = message.topic();
+        let partition = message.partition();
+        let offset = message.offset();
+        write_verifiable_consumed_record(&message, verbose);
+        received_count = received_count.saturating_add(1);
+        if !args.enable_autocommit {
+            let mut offsets = TopicPartitionList::new();
+            offsets.add_partition_offset(topic, partition, Offset::Offset(offset + 1))?;
+            write_verifiable_commit(
+                topic,
+                partition,
+                offset + 1,
+                consumer.commit(&offsets, CommitMode::Sync),
+            );
         }
     }
-    let mut printer = |line: String| println!("{line}");
-    buffer.verify_checksum(&mut printer)?;
+    consumer.unsubscribe();
+    let close_timeout =
+        Duration::from_millis(u64::try_from(args.close_timeout).unwrap_or_default());
+    let close_started = Instant::now();
+    while consumer.assignment()?.count() != 0 && close_started.elapsed() < close_timeout {
+        tokio::time::sleep(Duration::from_millis(10)).await;
+    }
+    drop(consumer);
+    write_verifiable_event(&VerifiableLifecycleEvent {
+        timestamp: Utc::now().timestamp_millis(),
+        name: "shutdown_complete",
+    });
+    if !shutdown_requested {
+        write_verifiable_event(&VerifiableLifecycleEvent {
+            timestamp: Utc::now().timestamp_millis(),
+            name: "shutdown_requested",
+        });
+    }
     Ok(())
 }
 
-async fn fetch_replica_partitions(
-    client: &krafka::admin::AdminClient,
-    broker_id: i32,
-    address: &str,
-    partitions: &[TopicPartitionKey],
-    offsets: &HashMap<TopicPartitionKey, i64>,
-    fetch_size: i32,
-    max_wait_ms: i32,
-) -> Result<HashMap<TopicPartitionKey, ReplicaPartitionData>> {
-    let connection = client
-        .pool()
-        .get_connection_by_id(broker_id, address)
-        .await?;
-    let version = connection
-        .negotiate_api_version(ApiKey::Fetch, 12, 4)
-        .await
-        .ok_or_else(|| Error::Unsupported("broker does not support Fetch".into()))?;
-
-    let mut topics: BTreeMap<String, Vec<krafka::protocol::FetchPartitionRequest>> =
-        BTreeMap::new();
-    for key in partitions {
-        let mut partition = krafka::protocol::FetchPartitionRequest::default();
-        partition.partition = key.partition;
-        partition.current_leader_epoch = -1;
-        partition.fetch_offset = offsets.get(key).copied().unwrap_or(0);
-        partition.last_fetched_epoch = -1;
-        partition.log_start_offset = 0;
-        partition.partition_max_bytes = fetch_size;
-        topics.entry(key.topic.clone()).or_default().push(partition);
-    }
-    let mut request = krafka::protocol::FetchRequest::default();
-    request.replica_id = DEBUGGING_CONSUMER_ID;
-    request.max_wait_ms = max_wait_ms;
-    request.min_bytes = 1;
-    request.max_bytes = i32::MAX;
-    request.isolation_level = 0;
-    request.session_id = 0;
-    request.session_epoch = -1;
-    request.topics = topics
-        .into_iter()
-        .map(|(topic, partitions)| {
-            let mut topic_request = krafka::protocol::FetchTopicRequest::default();
-            topic_request.topic = topic;
-            topic_request.partitions = partitions;
-            topic_request
-        })
-        .collect();
-    request.forgotten_topics = Vec::new();
-    request.rack_id = String::new();
+/// Kafka Fetch `replica_id` used by the original `ReplicaVerificationTool`.
+const DEBUGGING_CONSUMER_ID: i32 = -2;
+const LIST_OFFSETS_LATEST: i64 = -1;
+const LIST_OFFSETS_EARLIEST: i64 = -2;
 
-    let Ok(mut response_bytes) = connection
-        .send_request(ApiKey::Fetch, version, |buffer| {
-            request.encode_versioned(version, buffer)
-        })
-        .await
-    else {
-        // Match the original tool: when a broker is temporarily unreachable, contribute empty
-        // partition responses so verification of other brokers can continue.
-        return Ok(partitions
-            .iter()
-            .map(|key| {
-                (
-                    key.clone(),
-                    ReplicaPartitionData {
-                        high_watermark: -1,
-                        records: Bytes::new(),
-                    },
-                )
-            })
-            .collect());
-    };
-    drop(connection);
+#[derive(Clone, Debug, Eq, Hash, PartialEq, PartialOrd, Ord)]
+struct TopicPartitionKey {
+    topic: String,
+    partition: i32,
+}
 
-    let response = krafka::protocol::FetchResponse::decode_versioned(version, &mut response_bytes)?;
-    let mut fetched = HashMap::new();
-    for topic in response.responses {
-        for partition in topic.partitions {
-            let key = TopicPartitionKey::new(topic.topic.clone(), partition.partition);
-            fetched.insert(
-                key,
-                ReplicaPartitionData {
-                    high_watermark: partition.high_watermark,
-                    records: partition.records.unwrap_or_default(),
-                },
-            );
+impl TopicPartitionKey {
+    fn new(topic: impl Into<String>, partition: i32) -> Self {
+        Self {
+            topic: topic.into(),
+            partition,
         }
     }
-    for key in partitions {
-        fetched
-            .entry(key.clone())
-            .or_insert_with(|| ReplicaPartitionData {
-                high_watermark: -1,
-                records: Bytes::new(),
-            });
+
+    fn label(&self) -> String {
+        format!("{}-{}", self.topic, self.partition)
     }
-    Ok(fetched)
 }
 
-#[derive(Debug)]
-struct ProducerPerfOpaque {
-    started: Instant,
-    bytes: usize,
-    steady_state: bool,
+#[derive(Clone, Debug)]
+struct ReplicaPartitionData {
+    high_watermark: i64,
+    records: Bytes,
 }
 
-#[derive(Debug)]
-struct ProducerPerfStats {
-    started: Instant,
-    window_started: Instant,
-    reporting_interval: Duration,
-    sampling: u64,
-    latencies: Vec<u64>,
-    count: u64,
-    bytes: u64,
-    total_latency_ms: u64,
-    max_latency_ms: u64,
-    window_count: u64,
-    window_bytes: u64,
-    window_total_latency_ms: u64,
-    window_max_latency_ms: u64,
-    suppress_printing: bool,
-    steady_state: bool,
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct BatchMessageInfo {
+    replica_id: i32,
+    last_offset: i64,
+    next_offset: i64,
+    checksum: u32,
 }
 
-impl ProducerPerfStats {
-    fn new(num_records: u64, reporting_interval: Duration, steady_state: bool) -> Self {
-        let now = Instant::now();
+struct ReplicaBuffer {
+    expected_replicas: HashMap<TopicPartitionKey, usize>,
+    fetch_offsets: HashMap<TopicPartitionKey, i64>,
+    records_cache: HashMap<TopicPartitionKey, HashMap<i32, ReplicaPartitionData>>,
+    report_interval: Duration,
+    last_report_time: Instant,
+    max_lag: i64,
+    offset_with_max_lag: i64,
+    max_lag_partition: Option<TopicPartitionKey>,
+}
+
+impl ReplicaBuffer {
+    fn new(
+        expected_replicas: HashMap<TopicPartitionKey, usize>,
+        initial_offsets: HashMap<TopicPartitionKey, i64>,
+        report_interval_ms: i64,
+    ) -> Self {
+        let records_cache = expected_replicas
+            .keys()
+            .cloned()
+            .map(|key| (key, HashMap::new()))
+            .collect();
         Self {
-            started: now,
-            window_started: now,
-            reporting_interval,
-            sampling: num_records / num_records.min(500_000),
-            latencies: Vec::with_capacity(
-                usize::try_from(num_records.min(500_000)).unwrap_or(500_000),
+            expected_replicas,
+            fetch_offsets: initial_offsets,
+            records_cache,
+            report_interval: Duration::from_millis(
+                u64::try_from(report_interval_ms.max(0)).unwrap_or(0),
             ),
-            count: 0,
-            bytes: 0,
-            total_latency_ms: 0,
-            max_latency_ms: 0,
-            window_count: 0,
-            window_bytes: 0,
-            window_total_latency_ms: 0,
-            window_max_latency_ms: 0,
-            suppress_printing: false,
-            steady_state,
+            last_report_time: Instant::now(),
+            max_lag: -1,
+            offset_with_max_lag: -1,
+            max_lag_partition: None,
         }
     }
 
-    fn record(&mut self, latency_ms: u64, bytes: usize, now: Instant) {
-        let bytes = u64::try_from(bytes).unwrap_or(u64::MAX);
-        if self.count.is_multiple_of(self.sampling) {
-            self.latencies.push(latency_ms);
-        }
-        self.count = self.count.saturating_add(1);
-        self.bytes = self.bytes.saturating_add(bytes);
-        self.total_latency_ms = self.total_latency_ms.saturating_add(latency_ms);
-        self.max_latency_ms = self.max_latency_ms.max(latency_ms);
-        self.window_count = self.window_count.saturating_add(1);
-        self.window_bytes = self.window_bytes.saturating_add(bytes);
-        self.window_total_latency_ms = self.window_total_latency_ms.saturating_add(latency_ms);
-        self.window_max_latency_ms = self.window_max_latency_ms.max(latency_ms);
-        if now.duration_since(self.window_started) >= self.reporting_interval {
-            if self.steady_state && self.count == self.window_count {
-                println!("In steady state.");
-            }
-            if !self.suppress_printing {
-                self.print_window(now);
-            }
-            self.window_started = now;
-            self.window_count = 0;
-            self.window_bytes = 0;
-            self.window_total_latency_ms = 0;
-            self.window_max_latency_ms = 0;
-        }
+    fn add_fetched_data(
+        &mut self,
+        key: TopicPartitionKey,
+        replica_id: i32,
+        data: ReplicaPartitionData,
+    ) {
+        self.records_cache
+            .entry(key)
+            .or_default()
+            .insert(replica_id, data);
     }
 
-    #[expect(
-        clippy::cast_precision_loss,
-        reason = "Kafka's rate and latency output contract uses floating-point values"
-    )]
-    fn print_window(&self, now: Instant) {
-        let elapsed_ms = millis_at_least_one(now.duration_since(self.window_started));
-        let records_per_second = 1_000.0 * self.window_count as f64 / elapsed_ms as f64;
-        let mb_per_second =
-            1_000.0 * self.window_bytes as f64 / elapsed_ms as f64 / (1024.0 * 1024.0);
-        let average_latency = self.window_total_latency_ms as f64 / self.window_count as f64;
-        println!(
-            "{} records sent, {:.1} records/sec ({:.2} MB/sec), {:.1} ms avg latency, {:.1} ms max latency.",
-            self.window_count,
-            records_per_second,
-            mb_per_second,
-            average_latency,
-            self.window_max_latency_ms as f64
-        );
+    fn offset(&self, key: &TopicPartitionKey) -> i64 {
+        self.fetch_offsets.get(key).copied().unwrap_or(0)
     }
 
-    #[expect(
-        clippy::cast_precision_loss,
-        reason = "Kafka's rate and latency output contract uses floating-point values"
-    )]
-    fn total_line(&self) -> String {
-        let elapsed_ms = millis_at_least_one(self.started.elapsed());
-        let records_per_second = 1_000.0 * self.count as f64 / elapsed_ms as f64;
-        let mb_per_second = 1_000.0 * self.bytes as f64 / elapsed_ms as f64 / (1024.0 * 1024.0);
-        let average_latency = self.total_latency_ms as f64 / self.count.max(1) as f64;
-        let mut samples = self.latencies.clone();
-        samples.sort_unstable();
-        let percentiles = [(1, 2), (95, 100), (99, 100), (999, 1_000)]
-            .map(|(numerator, denominator)| sampled_percentile(&samples, numerator, denominator));
-        format!(
-            "{}{} records sent, {:.6} records/sec ({:.2} MB/sec), {:.2} ms avg latency, {:.2} ms max latency, {} ms 50th, {} ms 95th, {} ms 99th, {} ms 99.9th.",
-            self.count,
-            if self.steady_state {
-                " steady state"
-            } else {
-                ""
-            },
-            records_per_second,
-            mb_per_second,
-            average_latency,
-            self.max_latency_ms as f64,
-            percentiles[0],
-            percentiles[1],
-            percentiles[2],
-            percentiles[3]
-        )
-    }
-}
-
-fn millis_at_least_one(duration: Duration) -> u128 {
-    duration.as_millis().max(1)
-}
+    fn verify_checksum(&mut self, println: &mut dyn FnMut(String)) -> Result<()> {
+        self.max_lag = -1;
+        self.offset_with_max_lag = -1;
+        self.max_lag_partition = None;
 
-fn sampled_percentile(samples: &[u64], numerator: usize, denominator: usize) -> u64 {
-    if samples.is_empty() {
-        return 0;
+        let partitions = self.records_cache.keys().cloned().collect::<Vec<_>>();
+        for key in partitions {
+            self.verify_partition(&key, println)?;
+        }
+        self.maybe_report_max_lag(println);
+        Ok(())
     }
-    let index = samples
-        .len()
-        .saturating_mul(numerator)
-        .checked_div(denominator)
-        .unwrap_or_default()
-        .min(samples.len() - 1);
-    samples[index]
-}
-
-#[derive(Debug)]
-struct ProducerPerfShared {
-    total: ProducerPerfStats,
-    steady: Option<ProducerPerfStats>,
-    failures: u64,
-    first_error: Option<String>,
-}
 
-#[derive(Clone, Debug)]
-struct ProducerPerfContext {
-    shared: Arc<Mutex<ProducerPerfShared>>,
-}
-
-impl ClientContext for ProducerPerfContext {}
-
-impl ProducerContext for ProducerPerfContext {
-    type DeliveryOpaque = Box<ProducerPerfOpaque>;
-
-    fn delivery(&self, result: &DeliveryResult<'_>, opaque: Self::DeliveryOpaque) {
-        let mut shared = lock_unpoisoned(&self.shared);
-        match result {
-            Ok(_) => {
-                let now = Instant::now();
-                let latency = u64::try_from(now.duration_since(opaque.started).as_millis())
-                    .unwrap_or(u64::MAX);
-                shared.total.record(latency, opaque.bytes, now);
-                if opaque.steady_state
-                    && let Some(steady) = shared.steady.as_mut()
-                {
-                    steady.record(latency, opaque.bytes, now);
-                }
-            }
-            Err((error, _)) => {
-                shared.failures = shared.failures.saturating_add(1);
-                if shared.first_error.is_none() {
-                    shared.first_error = Some(error.to_string());
-                }
-                drop(shared);
-                eprintln!("{error}");
-            }
+    fn maybe_report_max_lag(&mut self, println: &mut dyn FnMut(String)) {
+        if self.last_report_time.elapsed() <= self.report_interval {
+            return;
         }
+        let partition = self
+            .max_lag_partition
+            .as_ref()
+            .map_or_else(|| "null".into(), TopicPartitionKey::label);
+        println(format!(
+            "{}: max lag is {} for partition {} at offset {} among {} partitions",
+            replica_verification_timestamp(),
+            self.max_lag,
+            partition,
+            self.offset_with_max_lag,
+            self.records_cache.len()
+        ));
+        self.last_report_time = Instant::now();
     }
-}
 
-fn lock_unpoisoned<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
-    mutex
-        .lock()
-        .unwrap_or_else(std::sync::PoisonError::into_inner)
-}
+    fn verify_partition(
+        &mut self,
+        key: &TopicPartitionKey,
+        println: &mut dyn FnMut(String),
+    ) -> Result<()> {
+        let expected = self.expected_replicas.get(key).copied().unwrap_or_default();
+        let Some(fetch_response) = self.records_cache.get(key) else {
+            return Ok(());
+        };
+        if fetch_response.len() != expected {
+            return Err(Error::Config(format!(
+                "fetched {} replicas for {}, but expected {expected} replicas",
+                fetch_response.len(),
+                key.label()
+            )));
+        }
 
-#[derive(Clone, Copy, Debug)]
-struct PerfRandom(u64);
+        let mut batch_iters = HashMap::new();
+        for (&replica_id, partition_data) in fetch_response {
+            batch_iters.insert(replica_id, parse_record_batches(&partition_data.records)?);
+        }
+        let max_hw = fetch_response
+            .values()
+            .map(|data| data.high_watermark)
+            .max()
+            .unwrap_or(-1);
+        let high_watermarks = fetch_response
+            .iter()
+            .map(|(&replica_id, data)| (replica_id, data.high_watermark))
+            .collect::<HashMap<_, _>>();
 
-impl PerfRandom {
-    const fn new(seed: i64) -> Self {
-        Self(seed.cast_unsigned())
-    }
+        let mut cursors = batch_iters
+            .into_iter()
+            .map(|(replica_id, batches)| (replica_id, 0_usize, batches))
+            .collect::<Vec<_>>();
+        while let Some(next_offset) =
+            advance_matching_batches(key, &mut cursors, &high_watermarks, println)
+        {
+            self.fetch_offsets.insert(key.clone(), next_offset);
+        }
 
-    const fn next_u32(&mut self) -> u32 {
-        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
-        let mut value = self.0;
-        value = (value ^ (value >> 33)).wrapping_mul(0x62a9_d9ed_7997_05f5);
-        value = (value ^ (value >> 28)).wrapping_mul(0xcb24_d0a5_c88c_35b3);
-        (value >> 32) as u32
+        let verified_offset = self.offset(key);
+        if max_hw - verified_offset > self.max_lag {
+            self.offset_with_max_lag = verified_offset;
+            self.max_lag = max_hw - verified_offset;
+            self.max_lag_partition = Some(key.clone());
+        }
+        if let Some(cache) = self.records_cache.get_mut(key) {
+            cache.clear();
+        }
+        Ok(())
     }
+}
 
-    fn index(&mut self, upper: usize) -> usize {
-        let bound = u32::try_from(upper).unwrap_or(u32::MAX);
-        let mask = bound - 1;
-        let mut random = self.next_u32();
-        if bound & mask == 0 {
-            return usize::try_from(random & mask).unwrap_or_default();
+/// Compares one batch across all replicas. Returns the next fetch offset when
+/// every replica still had a matching batch below its high watermark.
+fn advance_matching_batches(
+    key: &TopicPartitionKey,
+    cursors: &mut [(i32, usize, Vec<ParsedBatch>)],
+    high_watermarks: &HashMap<i32, i64>,
+    println: &mut dyn FnMut(String),
+) -> Option<i64> {
+    let mut first_message: Option<BatchMessageInfo> = None;
+    for (replica_id, index, batches) in cursors.iter_mut() {
+        let batch = batches.get(*index).copied()?;
+        let high_watermark = high_watermarks.get(replica_id).copied().unwrap_or(-1);
+        if batch.last_offset >= high_watermark {
+            return None;
         }
-        let mut candidate = random >> 1;
-        loop {
-            let value = candidate % bound;
-            let check = candidate.wrapping_add(mask).wrapping_sub(value);
-            if i32::from_ne_bytes(check.to_ne_bytes()) >= 0 {
-                return usize::try_from(value).unwrap_or_default();
+        if let Some(first) = first_message {
+            if first.last_offset != batch.last_offset {
+                println(format!(
+                    "{}: partition {}: replica {}'s offset {} doesn't match replica {}'s offset {}",
+                    replica_verification_timestamp(),
+                    key.label(),
+                    first.replica_id,
+                    first.last_offset,
+                    replica_id,
+                    batch.last_offset
+                ));
+                process::exit(1);
             }
-            random = self.next_u32();
-            candidate = random >> 1;
+            if first.checksum != batch.checksum {
+                println(format!(
+                    "{}: partition {} has unmatched checksum at offset {}; replica {}'s checksum {}; replica {}'s checksum {}",
+                    replica_verification_timestamp(),
+                    key.label(),
+                    batch.last_offset,
+                    first.replica_id,
+                    first.checksum,
+                    replica_id,
+                    batch.checksum
+                ));
+            }
+        } else {
+            first_message = Some(BatchMessageInfo {
+                replica_id: *replica_id,
+                last_offset: batch.last_offset,
+                next_offset: batch.next_offset,
+                checksum: batch.checksum,
+            });
         }
+        *index = index.saturating_add(1);
     }
+    first_message.map(|message| message.next_offset)
 }
 
-fn read_producer_payloads(path: &Path, delimiter: &str) -> Result<Vec<Vec<u8>>> {
-    let contents = fs::read_to_string(path)?;
-    if contents.is_empty() {
-        return Err(Error::Usage(
-            "payload file does not exist or is empty".into(),
-        ));
-    }
-    let delimiter = if delimiter == "\\n" { "\n" } else { delimiter };
-    let delimiter = Regex::new(delimiter)
-        .map_err(|error| Error::Usage(format!("invalid payload delimiter: {error}")))?;
-    let payloads = delimiter
-        .split(&contents)
-        .map(str::as_bytes)
-        .map(<[u8]>::to_vec)
-        .collect::<Vec<_>>();
-    let trailing_empty = delimiter
-        .find_iter(&contents)
-        .last()
-        .is_some_and(|match_| match_.end() == contents.len());
-    let end = payloads.len().saturating_sub(usize::from(trailing_empty));
-    Ok(payloads[..end].to_vec())
+#[derive(Clone, Copy, Debug)]
+struct ParsedBatch {
+    last_offset: i64,
+    next_offset: i64,
+    checksum: u32,
 }
 
-fn producer_perf_payload(
-    args: &ProducerPerfTestArgs,
-    payloads: &[Vec<u8>],
-    random: &mut PerfRandom,
-    index: u64,
-) -> Vec<u8> {
-    if !payloads.is_empty() {
-        return payloads[random.index(payloads.len())].clone();
-    }
-    if let Some(size) = args.record_size {
-        return (0..size)
-            .map(|_| b'A'.saturating_add(u8::try_from(random.index(26)).unwrap_or_default()))
-            .collect();
+fn parse_record_batches(records: &[u8]) -> Result<Vec<ParsedBatch>> {
+    let mut batches = Vec::new();
+    let mut offset = 0;
+    while offset + 61 <= records.len() {
+        let base_offset = i64::from_be_bytes(
+            records[offset..offset + 8]
+                .try_into()
+                .map_err(|_| Error::Config("truncated record batch base offset".into()))?,
+        );
+        let batch_length = i32::from_be_bytes(
+            records[offset + 8..offset + 12]
+                .try_into()
+                .map_err(|_| Error::Config("truncated record batch length".into()))?,
+        );
+        if batch_length < 49 {
+            return Err(Error::Config(format!(
+                "invalid record batch length: {batch_length}"
+            )));
+        }
+        let total_size = 12 + usize::try_from(batch_length).unwrap_or(usize::MAX);
+        if offset + total_size > records.len() {
+            break;
+        }
+        let magic = records[offset + 16];
+        if magic != 2 {
+            return Err(Error::Unsupported(format!(
+                "unsupported record batch magic: {magic}"
+            )));
+        }
+        let checksum = u32::from_be_bytes(
+            records[offset + 17..offset + 21]
+                .try_into()
+                .map_err(|_| Error::Config("truncated record batch checksum".into()))?,
+        );
+        let last_offset_delta = i32::from_be_bytes(
+            records[offset + 23..offset + 27]
+                .try_into()
+                .map_err(|_| Error::Config("truncated record batch last offset delta".into()))?,
+        );
+        let last_offset = base_offset + i64::from(last_offset_delta);
+        batches.push(ParsedBatch {
+            last_offset,
+            next_offset: last_offset + 1,
+            checksum,
+        });
+        offset += total_size;
     }
-    index.to_string().into_bytes()
+    Ok(batches)
 }
 
-fn producer_perf_key(
-    distribution: ProducerKeyDistribution,
-    range: Option<i32>,
-    index: u64,
-    random: &mut PerfRandom,
-) -> Option<String> {
-    let range = range.and_then(|value| u64::try_from(value).ok())?;
-    match distribution {
-        ProducerKeyDistribution::None => None,
-        ProducerKeyDistribution::Range => Some((index % range).to_string()),
-        ProducerKeyDistribution::Random => Some(
-            random
-                .index(usize::try_from(range).unwrap_or(usize::MAX))
-                .to_string(),
-        ),
-    }
+fn replica_verification_timestamp() -> String {
+    let now = Local::now();
+    format!(
+        "{},{:03}",
+        now.format("%Y-%m-%d %H:%M:%S"),
+        now.timestamp_subsec_millis()
+    )
 }
 
-fn validate_producer_perf_args(args: &ProducerPerfTestArgs) -> Result<(u64, u64, Duration)> {
-    let num_records = u64::try_from(args.num_records)
-        .ok()
-        .filter(|value| *value > 0)
-        .ok_or_else(|| Error::Usage("--num-records should be greater than zero".into()))?;
-    if args.record_size.is_some_and(|value| value <= 0) {
-        return Err(Error::Usage(
-            "--record-size should be greater than zero".into(),
-        ));
-    }
-    if args.transaction_duration_ms.is_some_and(|value| value <= 0) {
-        return Err(Error::Usage(
-            "--transaction-duration-ms should be greater than zero".into(),
-        ));
-    }
-    let reporting_interval = u64::try_from(args.reporting_interval)
-        .ok()
-        .filter(|value| *value > 0)
-        .ok_or_else(|| Error::Usage("--reporting-interval should be greater than zero".into()))?;
-    if args.record_key_range.is_some_and(|value| value <= 0) {
+fn validate_bootstrap_list(bootstrap: &str) -> Result<()> {
+    if bootstrap.trim().is_empty() {
         return Err(Error::Usage(
-            "--record-key-range should be greater than zero".into(),
+            "bootstrap server list must not be empty".into(),
         ));
     }
-    match (args.key_distribution, args.record_key_range) {
-        (ProducerKeyDistribution::None, Some(_)) => {
+    for server in bootstrap.split(',') {
+        let server = server.trim();
+        if server.is_empty() {
             return Err(Error::Usage(
-                "--key-distribution must be 'range' or 'random' when --record-key-range is specified"
-                    .into(),
+                "bootstrap server list contains an empty entry".into(),
             ));
         }
-        (ProducerKeyDistribution::Range | ProducerKeyDistribution::Random, None) => {
-            return Err(Error::Usage(
-                "--record-key-range is required when --key-distribution is 'range' or 'random'"
-                    .into(),
-            ));
+        let Some((host, port)) = server.rsplit_once(':') else {
+            return Err(Error::Usage(format!(
+                "bootstrap server '{server}' must be host:port"
+            )));
+        };
+        if host.is_empty() {
+            return Err(Error::Usage(format!(
+                "bootstrap server '{server}' is missing a host"
+            )));
+        }
+        if port.parse::<u16>().is_err() {
+            return Err(Error::Usage(format!(
+                "bootstrap server '{server}' has an invalid port"
+            )));
         }
-        _ => {}
-    }
-    let warmup_records = u64::try_from(args.warmup_records.max(0)).unwrap_or_default();
-    if warmup_records >= num_records {
-        return Err(Error::Usage(
-            "--warmup-records must be strictly fewer than --num-records".into(),
-        ));
     }
-    Ok((
-        num_records,
-        warmup_records,
-        Duration::from_millis(reporting_interval),
-    ))
+    Ok(())
 }
 
-#[expect(
-    clippy::too_many_lines,
-    reason = "the Kafka benchmark lifecycle keeps payload generation, transactions, throttling, delivery callbacks, and reporting explicit"
-)]
-fn run_producer_perf_test(
-    bootstrap: Option<&str>,
-    command_config: Option<&Path>,
-    args: &ProducerPerfTestArgs,
+fn validate_replica_verification_args(
+    bootstrap: &str,
+    args: &ReplicaVerificationArgs,
 ) -> Result<()> {
-    let (num_records, warmup_records, reporting_interval) = validate_producer_perf_args(args)?;
-    let config_path = share_perf_config_path(command_config, args.producer_config.as_deref())?;
-    if !args.producer_props.is_empty() {
-        println!("Option --producer-props is deprecated. Use --command-property instead.");
-    }
-    if args.producer_config.is_some() {
-        println!("Option --producer.config is deprecated. Use --command-config instead.");
-    }
-    let properties = parse_producer_perf_properties(args.properties())?;
-    let file_properties = config_path.map(config::load_properties).transpose()?;
-    let configured_bootstrap = properties
-        .iter()
-        .find_map(|(key, value)| (key == "bootstrap.servers").then(|| value.clone()))
-        .or_else(|| {
-            file_properties
-                .as_ref()
-                .and_then(|values| values.get("bootstrap.servers").cloned())
-        });
-    let resolved_bootstrap = bootstrap
-        .map(str::to_owned)
-        .or(configured_bootstrap)
-        .ok_or_else(|| {
-        Error::Usage(
-            "at least one of --bootstrap-server, --command-property, --producer-props, --producer.config or --command-config must provide bootstrap.servers"
-                .into(),
-        )
-    })?;
-    let mut config = config::client_config(&resolved_bootstrap, config_path)?;
-    for (key, value) in properties {
-        config.set(key, value);
+    if args.fetch_size <= 0 {
+        return Err(Error::Usage("--fetch-size must be positive".into()));
     }
-    if let Some(bootstrap) = bootstrap {
-        config.set("bootstrap.servers", bootstrap);
+    if args.max_wait_ms < 0 {
+        return Err(Error::Usage("--max-wait-ms must not be negative".into()));
     }
-    if config.get("client.id").is_none() {
-        config.set("client.id", "perf-producer-client");
+    if args.report_interval_ms < 0 {
+        return Err(Error::Usage(
+            "--report-interval-ms must not be negative".into(),
+        ));
     }
+    validate_bootstrap_list(bootstrap)
+}
 
-    let transactions_enabled = args.transaction_duration_ms.is_some()
-        || args.transactional_id.is_some()
-        || config.get("transactional.id").is_some();
-    let transaction_duration = if transactions_enabled {
-        let transaction_id = args.transactional_id.clone().unwrap_or_else(|| {
-            config.get("transactional.id").map_or_else(
-                || {
-                    format!(
-                        "performance-producer-{}",
-                        URL_SAFE_NO_PAD.encode(uuid::Uuid::new_v4().as_bytes())
-                    )
-                },
-                str::to_owned,
-            )
-        });
-        config.set("transactional.id", transaction_id);
-        Some(Duration::from_millis(
-            u64::try_from(args.transaction_duration_ms.unwrap_or(3_000)).unwrap_or(3_000),
-        ))
-    } else {
-        None
-    };
+struct ReplicaVerificationPlan {
+    brokers: HashMap<i32, String>,
+    expected_replicas: HashMap<TopicPartitionKey, usize>,
+    broker_partitions: HashMap<i32, Vec<TopicPartitionKey>>,
+    topic_partitions: Vec<TopicPartitionKey>,
+}
 
-    let payloads = if let Some(path) = args.payload_file.as_deref() {
-        println!("Reading payloads from: {}", path.canonicalize()?.display());
-        let payloads = read_producer_payloads(path, &args.payload_delimiter)?;
-        println!("Number of records read: {}", payloads.len());
-        payloads
-    } else {
-        Vec::new()
-    };
-    if args.payload_file.is_some() && payloads.is_empty() {
-        return Err(Error::Usage("payload file produced no records".into()));
-    }
-    if warmup_records > 0 {
-        println!(
-            "Warmup first {warmup_records} records. Steady state results will print after the complete test summary."
-        );
+fn build_replica_verification_plan(
+    metadata: &rdkafka::metadata::Metadata,
+    topics_include: &str,
+) -> Result<ReplicaVerificationPlan> {
+    let topic_filter = Regex::new(topics_include)
+        .map_err(|error| Error::Usage(format!("{topics_include} is an invalid regex: {error}")))?;
+    let brokers = metadata
+        .brokers()
+        .iter()
+        .map(|broker| (broker.id(), format!("{}:{}", broker.host(), broker.port())))
+        .collect::<HashMap<_, _>>();
+    if brokers.is_empty() {
+        return Err(Error::Config("no brokers found in cluster metadata".into()));
     }
 
-    let shared = Arc::new(Mutex::new(ProducerPerfShared {
-        total: ProducerPerfStats::new(num_records, reporting_interval, false),
-        steady: None,
-        failures: 0,
-        first_error: None,
-    }));
-    let context = ProducerPerfContext {
-        shared: Arc::clone(&shared),
-    };
-    let producer: BaseProducer<ProducerPerfContext> = config.create_with_context(context)?;
-    let operation_timeout = Duration::from_secs(60);
-    if transactions_enabled {
-        producer.init_transactions(operation_timeout)?;
-    }
-    let mut random = PerfRandom::new(args.random_seed);
-    let throttle_started = Instant::now();
-    let mut transaction_records = 0_u64;
-    let mut transaction_started = Instant::now();
-
-    for index in 0..num_records {
-        if transactions_enabled && transaction_records == 0 {
-            producer.begin_transaction()?;
-            transaction_started = Instant::now();
-        }
-        if warmup_records > 0 && index == warmup_records {
-            let mut state = lock_unpoisoned(&shared);
-            state.total.suppress_printing = true;
-            state.steady = Some(ProducerPerfStats::new(
-                num_records - warmup_records,
-                reporting_interval,
-                true,
-            ));
+    let mut expected_replicas = HashMap::new();
+    let mut broker_partitions: HashMap<i32, Vec<TopicPartitionKey>> = HashMap::new();
+    let mut topic_partitions = Vec::new();
+    for topic in metadata.topics() {
+        if !topic_filter.is_match(topic.name()) {
+            continue;
         }
-        let payload = producer_perf_payload(args, &payloads, &mut random, index);
-        let key = producer_perf_key(
-            args.key_distribution,
-            args.record_key_range,
-            index,
-            &mut random,
-        );
-        let opaque = Box::new(ProducerPerfOpaque {
-            started: Instant::now(),
-            bytes: payload.len(),
-            steady_state: index >= warmup_records && warmup_records > 0,
-        });
-        let mut record = BaseRecord::with_opaque_to(&args.topic, opaque).payload(&payload);
-        if let Some(key) = key.as_deref() {
-            record = record.key(key);
+        if let Some(error) = topic.error() {
+            return Err(Error::Config(format!(
+                "topic {} metadata error: {error:?}",
+                topic.name()
+            )));
         }
-        loop {
-            match producer.send(record) {
-                Ok(()) => break,
-                Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), returned)) => {
-                    record = returned;
-                    producer.poll(Duration::from_millis(100));
-                }
-                Err((error, _)) => return Err(Error::Kafka(error)),
+        for partition in topic.partitions() {
+            if let Some(error) = partition.error() {
+                return Err(Error::Config(format!(
+                    "partition {}-{} metadata error: {error:?}",
+                    topic.name(),
+                    partition.id()
+                )));
+            }
+            let key = TopicPartitionKey::new(topic.name(), partition.id());
+            let replicas = partition.replicas();
+            if replicas.is_empty() {
+                continue;
+            }
+            expected_replicas.insert(key.clone(), replicas.len());
+            topic_partitions.push(key.clone());
+            for &replica_id in replicas {
+                broker_partitions
+                    .entry(replica_id)
+                    .or_default()
+                    .push(key.clone());
             }
         }
-        producer.poll(Duration::ZERO);
-        transaction_records = transaction_records.saturating_add(1);
-        if let Some(duration) = transaction_duration
-            && transaction_started.elapsed() >= duration
-        {
-            producer.commit_transaction(operation_timeout)?;
-            transaction_records = 0;
-        }
-        throttle_producer(args.throughput, index, throttle_started);
-    }
-    if transactions_enabled && transaction_records > 0 {
-        producer.commit_transaction(operation_timeout)?;
-    }
-    producer.flush(operation_timeout)?;
-    let (total_line, steady_line, count, bytes, failures, first_error) = {
-        let state = lock_unpoisoned(&shared);
-        (
-            state.total.total_line(),
-            state.steady.as_ref().map(ProducerPerfStats::total_line),
-            state.total.count,
-            state.total.bytes,
-            state.failures,
-            state.first_error.clone(),
-        )
-    };
-    println!("{total_line}");
-    if let Some(steady_line) = steady_line {
-        println!("{steady_line}");
-    }
-    if args.print_metrics {
-        println!(
-            "records-sent:client-id={}: {}",
-            config.get("client.id").unwrap_or("perf-producer-client"),
-            count
-        );
-        println!(
-            "bytes-sent:client-id={}: {}",
-            config.get("client.id").unwrap_or("perf-producer-client"),
-            bytes
-        );
-        println!("record-errors: {failures}");
     }
-    if failures > 0 {
-        return Err(Error::Config(format!(
-            "{} record deliveries failed: {}",
-            failures,
-            first_error.as_deref().unwrap_or("unknown error")
+    if expected_replicas.is_empty() {
+        return Err(Error::Usage(format!(
+            "No topics found. --topics-include {topics_include}, if specified, is either filtering out all topics or there is no topic."
         )));
     }
-    Ok(())
+    Ok(ReplicaVerificationPlan {
+        brokers,
+        expected_replicas,
+        broker_partitions,
+        topic_partitions,
+    })
 }
 
-#[expect(
-    clippy::cast_precision_loss,
-    reason = "fractional records-per-second throttling requires a floating-point elapsed target"
-)]
-fn throttle_producer(throughput: f64, amount_so_far: u64, started: Instant) {
-    if throughput < 0.0 || !throughput.is_finite() {
-        return;
-    }
-    if throughput == 0.0 {
-        if amount_so_far == 0 {
-            return;
-        }
-        std::thread::park();
-        return;
-    }
-    let expected = Duration::from_secs_f64(amount_so_far as f64 / throughput);
-    if let Some(remaining) = expected.checked_sub(started.elapsed()) {
-        std::thread::sleep(remaining);
-    }
+async fn replica_verification(
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    timeout: Duration,
+    args: ReplicaVerificationArgs,
+) -> Result<()> {
+    eprintln!("This tool is deprecated and may be removed in a future major release.");
+    validate_replica_verification_args(bootstrap, &args)?;
+
+    let client_config = config::client_config(bootstrap, command_config)?;
+    let consumer = base_consumer(&client_config)?;
+    let metadata = consumer.fetch_metadata(None, timeout)?;
+    let plan = build_replica_verification_plan(&metadata, &args.topics_include)?;
+    let initial_offsets =
+        replica_initial_offsets(&consumer, &plan.topic_partitions, args.time, timeout)?;
+    drop(consumer);
+    let mut buffer = ReplicaBuffer::new(
+        plan.expected_replicas,
+        initial_offsets,
+        args.report_interval_ms,
+    );
+    let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
+
+    println!(
+        "{}: verification process is started",
+        replica_verification_timestamp()
+    );
+
+    run_replica_verification_loop(
+        &client,
+        &plan.brokers,
+        &plan.broker_partitions,
+        &mut buffer,
+        args.fetch_size,
+        args.max_wait_ms,
+    )
+    .await
 }
 
-async fn enqueue_with_timeout<K, P>(
-    producer: &FutureProducer,
-    mut record: FutureRecord<'_, K, P>,
-    timeout: Duration,
-) -> Result<DeliveryFuture>
-where
-    K: ToBytes + Sync + ?Sized,
-    P: ToBytes + Sync + ?Sized,
-{
-    let started = Instant::now();
+async fn run_replica_verification_loop(
+    client: &krafka::admin::AdminClient,
+    brokers: &HashMap<i32, String>,
+    broker_partitions: &HashMap<i32, Vec<TopicPartitionKey>>,
+    buffer: &mut ReplicaBuffer,
+    fetch_size: i32,
+    max_wait_ms: i32,
+) -> Result<()> {
     loop {
-        match producer.send_result(record) {
-            Ok(delivery) => return Ok(delivery),
-            Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), returned))
-                if started.elapsed() < timeout =>
-            {
-                record = returned;
-                tokio::time::sleep(
-                    timeout
-                        .saturating_sub(started.elapsed())
-                        .min(Duration::from_millis(100)),
-                )
-                .await;
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => {
+                eprintln!("Stopping all fetchers");
+                break;
+            }
+            result = replica_verification_round(
+                client,
+                brokers,
+                broker_partitions,
+                buffer,
+                fetch_size,
+                max_wait_ms,
+            ) => {
+                result?;
             }
-            Err((error, _)) => return Err(Error::Kafka(error)),
         }
     }
+    Ok(())
 }
 
-fn configure_producer(
-    config: &mut rdkafka::ClientConfig,
-    args: &crate::cli::ProduceArgs,
-) -> Result<u64> {
-    let property_max_block = config
-        .get("max.block.ms")
-        .map(|value| parse_u64("max.block.ms", value))
-        .transpose()?;
-    config.remove("max.block.ms");
-    if let Some(buffer_memory) = config.get("buffer.memory") {
-        let bytes = parse_positive_u64("buffer.memory", buffer_memory)?;
-        config.set(
-            "queue.buffering.max.kbytes",
-            bytes.div_ceil(1024).to_string(),
-        );
-        config.remove("buffer.memory");
-    }
-    if let Some(send_buffer) = config.get("send.buffer.bytes").map(str::to_owned) {
-        config.set("socket.send.buffer.bytes", send_buffer);
-        config.remove("send.buffer.bytes");
-    }
-
-    config.set("compression.type", &args.compression_type);
-    merge_producer_option(config, "acks", args.acks.as_deref(), "-1");
-    merge_producer_option(
-        config,
-        "batch.size",
-        args.batch_size.map(|value| value.to_string()).as_deref(),
-        "16384",
-    );
-    if let Some(value) = args.max_partition_memory_bytes {
-        config.set("batch.size", value.to_string());
-    }
-    merge_producer_option(
-        config,
-        "message.send.max.retries",
-        args.message_send_max_retries
-            .map(|value| value.to_string())
-            .as_deref(),
-        "3",
-    );
-    merge_producer_option(
-        config,
-        "retry.backoff.ms",
-        args.retry_backoff_ms
-            .map(|value| value.to_string())
-            .as_deref(),
-        "100",
-    );
-    merge_producer_option(
-        config,
-        "linger.ms",
-        args.linger_ms.map(|value| value.to_string()).as_deref(),
-        "1000",
-    );
-    merge_producer_option(
-        config,
-        "request.timeout.ms",
-        args.request_timeout_ms
-            .map(|value| value.to_string())
-            .as_deref(),
-        "1500",
-    );
-    merge_producer_option(
-        config,
-        "metadata.max.age.ms",
-        args.metadata_expiry_ms
-            .map(|value| value.to_string())
-            .as_deref(),
-        "300000",
-    );
-    merge_producer_option(
-        config,
-        "queue.buffering.max.kbytes",
-        args.max_memory_bytes
-            .map(|value| value.div_ceil(1024).to_string())
-            .as_deref(),
-        "32768",
-    );
-    merge_producer_option(
-        config,
-        "socket.send.buffer.bytes",
-        args.socket_buffer_size
-            .map(|value| value.to_string())
-            .as_deref(),
-        "102400",
-    );
-    if config.get("client.id").is_none() {
-        config.set("client.id", "console-producer");
+fn replica_initial_offsets(
+    consumer: &BaseConsumer,
+    partitions: &[TopicPartitionKey],
+    time: i64,
+    timeout: Duration,
+) -> Result<HashMap<TopicPartitionKey, i64>> {
+    let mut offsets = HashMap::with_capacity(partitions.len());
+    match time {
+        LIST_OFFSETS_LATEST => {
+            for key in partitions {
+                let (_low, high) = consumer.fetch_watermarks(&key.topic, key.partition, timeout)?;
+                offsets.insert(key.clone(), high);
+            }
+        }
+        LIST_OFFSETS_EARLIEST => {
+            for key in partitions {
+                let (low, _high) = consumer.fetch_watermarks(&key.topic, key.partition, timeout)?;
+                offsets.insert(key.clone(), low);
+            }
+        }
+        timestamp if timestamp >= 0 => {
+            let mut tpl = TopicPartitionList::with_capacity(partitions.len());
+            for key in partitions {
+                tpl.add_partition_offset(&key.topic, key.partition, Offset::Offset(timestamp))?;
+            }
+            let resolved = consumer.offsets_for_times(tpl, timeout)?;
+            for key in partitions {
+                let element = resolved
+                    .find_partition(&key.topic, key.partition)
+                    .ok_or_else(|| {
+                        Error::Config(format!(
+                            "no offset returned for {} at timestamp {timestamp}",
+                            key.label()
+                        ))
+                    })?;
+                let offset = match element.offset() {
+                    Offset::Offset(value) => value,
+                    Offset::Invalid => {
+                        return Err(Error::Config(format!(
+                            "no offset found for {} at timestamp {timestamp}",
+                            key.label()
+                        )));
+                    }
+                    other => {
+                        return Err(Error::Config(format!(
+                            "unexpected offset {other:?} for {}",
+                            key.label()
+                        )));
+                    }
+                };
+                offsets.insert(key.clone(), offset);
+            }
+        }
+        other => {
+            return Err(Error::Usage(format!(
+                "invalid --time value {other}; expected timestamp, -1 (latest), or -2 (earliest)"
+            )));
+        }
     }
-    Ok(args.max_block_ms.or(property_max_block).unwrap_or(60_000))
+    Ok(offsets)
 }
 
-fn merge_producer_option(
-    config: &mut rdkafka::ClientConfig,
-    key: &str,
-    explicit: Option<&str>,
-    default: &str,
-) {
-    if let Some(value) = explicit {
-        config.set(key, value);
-    } else if config.get(key).is_none() {
-        config.set(key, default);
+async fn replica_verification_round(
+    client: &krafka::admin::AdminClient,
+    brokers: &HashMap<i32, String>,
+    broker_partitions: &HashMap<i32, Vec<TopicPartitionKey>>,
+    buffer: &mut ReplicaBuffer,
+    fetch_size: i32,
+    max_wait_ms: i32,
+) -> Result<()> {
+    let mut fetch_tasks = Vec::with_capacity(broker_partitions.len());
+    for (&broker_id, partitions) in broker_partitions {
+        let address = brokers.get(&broker_id).cloned().ok_or_else(|| {
+            Error::Config(format!(
+                "broker {broker_id} is listed as a replica but missing from metadata"
+            ))
+        })?;
+        let partitions = partitions.clone();
+        let offsets = partitions
+            .iter()
+            .map(|key| (key.clone(), buffer.offset(key)))
+            .collect::<HashMap<_, _>>();
+        fetch_tasks.push(async move {
+            let fetched = fetch_replica_partitions(
+                client,
+                broker_id,
+                &address,
+                &partitions,
+                &offsets,
+                fetch_size,
+                max_wait_ms,
+            )
+            .await?;
+            Ok::<_, Error>((broker_id, fetched))
+        });
     }
-}
 
-fn parse_positive_u64(key: &str, value: &str) -> Result<u64> {
-    let value = parse_u64(key, value)?;
-    if value == 0 {
-        Err(Error::Usage(format!("{key} must be a positive integer")))
-    } else {
-        Ok(value)
+    let results = futures::future::try_join_all(fetch_tasks).await?;
+    for (broker_id, fetched) in results {
+        for (key, data) in fetched {
+            buffer.add_fetched_data(key, broker_id, data);
+        }
     }
+    let mut printer = |line: String| println!("{line}");
+    buffer.verify_checksum(&mut printer)?;
+    Ok(())
 }
 
-fn parse_u64(key: &str, value: &str) -> Result<u64> {
-    value
-        .parse::<u64>()
-        .map_err(|_| Error::Usage(format!("{key} must be a non-negative integer")))
-}
-
-#[derive(Debug, Deserialize, PartialEq, Eq)]
-struct ProducerInput {
-    #[serde(default)]
-    key: Option<String>,
-    value: Option<String>,
-    #[serde(default)]
-    partition: Option<i32>,
-    #[serde(default)]
-    #[serde(deserialize_with = "deserialize_producer_headers")]
-    headers: Vec<(String, Option<String>)>,
-}
-
-fn deserialize_producer_headers<'de, D>(
-    deserializer: D,
-) -> std::result::Result<Vec<(String, Option<String>)>, D::Error>
-where
-    D: serde::Deserializer<'de>,
-{
-    BTreeMap::<String, Option<String>>::deserialize(deserializer)
-        .map(|headers| headers.into_iter().collect())
-}
-
-struct LineReaderOptions {
-    parse_key: bool,
-    key_separator: String,
-    parse_headers: bool,
-    headers_delimiter: String,
-    headers_separator: Regex,
-    headers_key_separator: String,
-    ignore_error: bool,
-    null_marker: Option<String>,
-}
+async fn fetch_replica_partitions(
+    client: &krafka::admin::AdminClient,
+    broker_id: i32,
+    address: &str,
+    partitions: &[TopicPartitionKey],
+    offsets: &HashMap<TopicPartitionKey, i64>,
+    fetch_size: i32,
+    max_wait_ms: i32,
+) -> Result<HashMap<TopicPartitionKey, ReplicaPartitionData>> {
+    let connection = client
+        .pool()
+        .get_connection_by_id(broker_id, address)
+        .await?;
+    let version = connection
+        .negotiate_api_version(ApiKey::Fetch, 12, 4)
+        .await
+        .ok_or_else(|| Error::Unsupported("broker does not support Fetch".into()))?;
 
-fn line_reader_options(args: &crate::cli::ProduceArgs) -> Result<LineReaderOptions> {
-    if args.line_reader != "org.apache.kafka.tools.LineMessageReader" {
-        return Err(Error::Unsupported(format!(
-            "Java line reader class {} cannot be loaded by the native client",
-            args.line_reader
-        )));
+    let mut topics: BTreeMap<String, Vec<krafka::protocol::FetchPartitionRequest>> =
+        BTreeMap::new();
+    for key in partitions {
+        let mut partition = krafka::protocol::FetchPartitionRequest::default();
+        partition.partition = key.partition;
+        partition.current_leader_epoch = -1;
+        partition.fetch_offset = offsets.get(key).copied().unwrap_or(0);
+        partition.last_fetched_epoch = -1;
+        partition.log_start_offset = 0;
+        partition.partition_max_bytes = fetch_size;
+        topics.entry(key.topic.clone()).or_default().push(partition);
     }
-    let properties = component_properties(args.reader_config.as_deref(), args.reader_properties())?;
-    let value = |key: &str| properties.get(key);
-    let parse_key = value("parse.key").map_or(args.parse_key, |value| bool_property(value));
-    let key_separator = value("key.separator")
-        .cloned()
-        .or_else(|| args.key_separator.map(|separator| separator.to_string()))
-        .unwrap_or_else(|| "\t".into());
-    let parse_headers = value("parse.headers").is_some_and(|value| bool_property(value));
-    let headers_delimiter = value("headers.delimiter")
-        .cloned()
-        .unwrap_or_else(|| "\t".into());
-    let headers_separator_value = value("headers.separator").map_or(",", String::as_str);
-    let headers_separator = Regex::new(headers_separator_value).map_err(|error| {
-        Error::Usage(format!(
-            "invalid headers.separator regular expression: {error}"
-        ))
-    })?;
-    let headers_key_separator = value("headers.key.separator")
-        .cloned()
-        .unwrap_or_else(|| ":".into());
-    let ignore_error = value("ignore.error").is_some_and(|value| bool_property(value));
-    let null_marker = value("null.marker").cloned();
-    for (left_name, left, right_name, right) in [
-        (
-            "headers.delimiter",
-            headers_delimiter.as_str(),
-            "headers.separator",
-            headers_separator_value,
-        ),
-        (
-            "headers.delimiter",
-            headers_delimiter.as_str(),
-            "headers.key.separator",
-            headers_key_separator.as_str(),
-        ),
-        (
-            "headers.separator",
-            headers_separator_value,
-            "headers.key.separator",
-            headers_key_separator.as_str(),
-        ),
-    ] {
-        if left == right {
-            return Err(Error::Usage(format!(
-                "{left_name} and {right_name} may not be equal"
-            )));
+    let mut request = krafka::protocol::FetchRequest::default();
+    request.replica_id = DEBUGGING_CONSUMER_ID;
+    request.max_wait_ms = max_wait_ms;
+    request.min_bytes = 1;
+    request.max_bytes = i32::MAX;
+    request.isolation_level = 0;
+    request.session_id = 0;
+    request.session_epoch = -1;
+    request.topics = topics
+        .into_iter()
+        .map(|(topic, partitions)| {
+            let mut topic_request = krafka::protocol::FetchTopicRequest::default();
+            topic_request.topic = topic;
+            topic_request.partitions = partitions;
+            topic_request
+        })
+        .collect();
+    request.forgotten_topics = Vec::new();
+    request.rack_id = String::new();
+
+    let Ok(mut response_bytes) = connection
+        .send_request(ApiKey::Fetch, version, |buffer| {
+            request.encode_versioned(version, buffer)
+        })
+        .await
+    else {
+        // Match the original tool: when a broker is temporarily unreachable, contribute empty
+        // partition responses so verification of other brokers can continue.
+        return Ok(partitions
+            .iter()
+            .map(|key| {
+                (
+                    key.clone(),
+                    ReplicaPartitionData {
+                        high_watermark: -1,
+                        records: Bytes::new(),
+                    },
+                )
+            })
+            .collect());
+    };
+    drop(connection);
+
+    let response = krafka::protocol::FetchResponse::decode_versioned(version, &mut response_bytes)?;
+    let mut fetched = HashMap::new();
+    for topic in response.responses {
+        for partition in topic.partitions {
+            let key = TopicPartitionKey::new(topic.topic.clone(), partition.partition);
+            fetched.insert(
+                key,
+                ReplicaPartitionData {
+                    high_watermark: partition.high_watermark,
+                    records: partition.records.unwrap_or_default(),
+                },
+            );
         }
     }
-    if let Some(marker) = &null_marker {
-        for (name, separator) in [
-            ("key.separator", key_separator.as_str()),
-            ("headers.delimiter", headers_delimiter.as_str()),
-            ("headers.separator", headers_separator_value),
-            ("headers.key.separator", headers_key_separator.as_str()),
-        ] {
-            if marker == separator {
-                return Err(Error::Usage(format!(
-                    "null.marker and {name} may not be equal"
-                )));
-            }
-        }
+    for key in partitions {
+        fetched
+            .entry(key.clone())
+            .or_insert_with(|| ReplicaPartitionData {
+                high_watermark: -1,
+                records: Bytes::new(),
+            });
     }
-    Ok(LineReaderOptions {
-        parse_key,
-        key_separator,
-        parse_headers,
-        headers_delimiter,
-        headers_separator,
-        headers_key_separator,
-        ignore_error,
-        null_marker,
-    })
+    Ok(fetched)
 }
 
-fn bool_property(value: &str) -> bool {
-    value.trim().eq_ignore_ascii_case("true")
+#[derive(Debug)]
+struct ProducerPerfOpaque {
+    started: Instant,
+    bytes: usize,
+    steady_state: bool,
 }
 
-fn producer_input(line: &str, json: bool, options: &LineReaderOptions) -> Result<ProducerInput> {
-    if json {
-        let input: ProducerInput = serde_json::from_str(line)?;
-        if input.partition.is_some_and(|partition| partition < 0) {
-            return Err(Error::Usage("partition must be non-negative".into()));
-        }
-        Ok(input)
-    } else {
-        let (headers, remaining) = parse_line_field(
-            options.parse_headers,
-            line,
-            &options.headers_delimiter,
-            options.ignore_error,
-            "headers delimiter",
-        )?;
-        let (key, value) = parse_line_field(
-            options.parse_key,
-            remaining,
-            &options.key_separator,
-            options.ignore_error,
-            "key separator",
-        )?;
-        let headers = headers
-            .map(|headers| parse_line_headers(headers, options))
-            .transpose()?
-            .unwrap_or_default();
-        Ok(ProducerInput {
-            key: nullable_field(key, options.null_marker.as_deref()),
-            value: nullable_field(Some(value), options.null_marker.as_deref()),
-            partition: None,
-            headers,
-        })
-    }
+#[derive(Debug)]
+struct ProducerPerfStats {
+    started: Instant,
+    window_started: Instant,
+    reporting_interval: Duration,
+    sampling: u64,
+    latencies: Vec<u64>,
+    count: u64,
+    bytes: u64,
+    total_latency_ms: u64,
+    max_latency_ms: u64,
+    window_count: u64,
+    window_bytes: u64,
+    window_total_latency_ms: u64,
+    window_max_latency_ms: u64,
+    suppress_printing: bool,
+    steady_state: bool,
 }
 
-fn parse_line_field<'a>(
-    enabled: bool,
-    line: &'a str,
-    separator: &str,
-    ignore_error: bool,
-    name: &str,
-) -> Result<(Option<&'a str>, &'a str)> {
-    if !enabled {
-        return Ok((None, line));
+impl ProducerPerfStats {
+    fn new(num_records: u64, reporting_interval: Duration, steady_state: bool) -> Self {
+        let now = Instant::now();
+        Self {
+            started: now,
+            window_started: now,
+            reporting_interval,
+            sampling: num_records / num_records.min(500_000),
+            latencies: Vec::with_capacity(
+                usize::try_from(num_records.min(500_000)).unwrap_or(500_000),
+            ),
+            count: 0,
+            bytes: 0,
+            total_latency_ms: 0,
+            max_latency_ms: 0,
+            window_count: 0,
+            window_bytes: 0,
+            window_total_latency_ms: 0,
+            window_max_latency_ms: 0,
+            suppress_printing: false,
+            steady_state,
+        }
     }
-    line.find(separator).map_or_else(
-        || {
-            if ignore_error {
-                Ok((None, line))
-            } else {
-                Err(Error::Usage(format!("no {name} found in input line")))
-            }
-        },
-        |index| Ok((Some(&line[..index]), &line[index + separator.len()..])),
-    )
-}
 
-fn parse_line_headers(
-    headers: &str,
-    options: &LineReaderOptions,
-) -> Result<Vec<(String, Option<String>)>> {
-    if options.null_marker.as_deref() == Some(headers) {
-        return Ok(Vec::new());
+    fn record(&mut self, latency_ms: u64, bytes: usize, now: Instant) {
+        let bytes = u64::try_from(bytes).unwrap_or(u64::MAX);
+        if self.count.is_multiple_of(self.sampling) {
+            self.latencies.push(latency_ms);
+        }
+        self.count = self.count.saturating_add(1);
+        self.bytes = self.bytes.saturating_add(bytes);
+        self.total_latency_ms = self.total_latency_ms.saturating_add(latency_ms);
+        self.max_latency_ms = self.max_latency_ms.max(latency_ms);
+        self.window_count = self.window_count.saturating_add(1);
+        self.window_bytes = self.window_bytes.saturating_add(bytes);
+        self.window_total_latency_ms = self.window_total_latency_ms.saturating_add(latency_ms);
+        self.window_max_latency_ms = self.window_max_latency_ms.max(latency_ms);
+        if now.duration_since(self.window_started) >= self.reporting_interval {
+            if self.steady_state && self.count == self.window_count {
+                println!("In steady state.");
+            }
+            if !self.suppress_printing {
+                self.print_window(now);
+            }
+            self.window_started = now;
+            self.window_count = 0;
+            self.window_bytes = 0;
+            self.window_total_latency_ms = 0;
+            self.window_max_latency_ms = 0;
+        }
     }
-    options
-        .headers_separator
-        .split(headers)
-        .map(|pair| {
-            if let Some(index) = pair.find(&options.headers_key_separator) {
-                let key = &pair[..index];
-                if options.null_marker.as_deref() == Some(key) {
-                    return Err(Error::Usage("header key cannot equal null.marker".into()));
-                }
-                let value = &pair[index + options.headers_key_separator.len()..];
-                Ok((
-                    key.to_owned(),
-                    nullable_field(Some(value), options.null_marker.as_deref()),
-                ))
-            } else if options.ignore_error {
-                Ok((pair.to_owned(), None))
+
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "Kafka's rate and latency output contract uses floating-point values"
+    )]
+    fn print_window(&self, now: Instant) {
+        let elapsed_ms = millis_at_least_one(now.duration_since(self.window_started));
+        let records_per_second = 1_000.0 * self.window_count as f64 / elapsed_ms as f64;
+        let mb_per_second =
+            1_000.0 * self.window_bytes as f64 / elapsed_ms as f64 / (1024.0 * 1024.0);
+        let average_latency = self.window_total_latency_ms as f64 / self.window_count as f64;
+        println!(
+            "{} records sent, {:.1} records/sec ({:.2} MB/sec), {:.1} ms avg latency, {:.1} ms max latency.",
+            self.window_count,
+            records_per_second,
+            mb_per_second,
+            average_latency,
+            self.window_max_latency_ms as f64
+        );
+    }
+
+    #[expect(
+        clippy::cast_precision_loss,
+        reason = "Kafka's rate and latency output contract uses floating-point values"
+    )]
+    fn total_line(&self) -> String {
+        let elapsed_ms = millis_at_least_one(self.started.elapsed());
+        let records_per_second = 1_000.0 * self.count as f64 / elapsed_ms as f64;
+        let mb_per_second = 1_000.0 * self.bytes as f64 / elapsed_ms as f64 / (1024.0 * 1024.0);
+        let average_latency = self.total_latency_ms as f64 / self.count.max(1) as f64;
+        let mut samples = self.latencies.clone();
+        samples.sort_unstable();
+        let percentiles = [(1, 2), (95, 100), (99, 100), (999, 1_000)]
+            .map(|(numerator, denominator)| sampled_percentile(&samples, numerator, denominator));
+        format!(
+            "{}{} records sent, {:.6} records/sec ({:.2} MB/sec), {:.2} ms avg latency, {:.2} ms max latency, {} ms 50th, {} ms 95th, {} ms 99th, {} ms 99.9th.",
+            self.count,
+            if self.steady_state {
+                " steady state"
             } else {
-                Err(Error::Usage(format!(
-                    "no header key separator found in pair '{pair}'"
-                )))
-            }
-        })
-        .collect()
+                ""
+            },
+            records_per_second,
+            mb_per_second,
+            average_latency,
+            self.max_latency_ms as f64,
+            percentiles[0],
+            percentiles[1],
+            percentiles[2],
+            percentiles[3]
+        )
+    }
 }
 
-fn nullable_field(value: Option<&str>, marker: Option<&str>) -> Option<String> {
-    value.and_then(|value| (Some(value) != marker).then(|| value.to_owned()))
+fn millis_at_least_one(duration: Duration) -> u128 {
+    duration.as_millis().max(1)
 }
 
-#[derive(Serialize)]
-struct ConsumedRecord<'a> {
-    topic: &'a str,
-    partition: i32,
-    offset: i64,
-    timestamp: Option<i64>,
-    key: Option<String>,
-    value: Option<String>,
-    headers: BTreeMap<String, Option<String>>,
+fn sampled_percentile(samples: &[u64], numerator: usize, denominator: usize) -> u64 {
+    if samples.is_empty() {
+        return 0;
+    }
+    let index = samples
+        .len()
+        .saturating_mul(numerator)
+        .checked_div(denominator)
+        .unwrap_or_default()
+        .min(samples.len() - 1);
+    samples[index]
 }
 
-async fn consume(
-    mut config: rdkafka::ClientConfig,
-    _timeout: Duration,
-    args: crate::cli::ConsumeArgs,
-) -> Result<()> {
-    configure_consumer(&mut config, &args)?;
-    let formatter = message_formatter_options(&args)?;
-    let manual_offset = args
-        .partition
-        .map(|_| consumer_offset(args.offset.as_deref(), args.from_beginning))
-        .transpose()?;
-    let consumer: StreamConsumer = config.create()?;
-    if let Some(partition) = args.partition {
-        let topic = args
-            .topic
-            .as_deref()
-            .ok_or_else(|| Error::Usage("--topic is required with --partition".into()))?;
-        let mut assignment = TopicPartitionList::new();
-        assignment.add_partition_offset(topic, partition, manual_offset.unwrap_or(Offset::End))?;
-        consumer.assign(&assignment)?;
-    } else if let Some(include) = args.include.as_deref() {
-        // librdkafka compiles subscription patterns as POSIX ERE. Validating with
-        // Rust's regex engine first would reject a different language than the
-        // engine that actually performs the subscription.
-        let pattern = consumer_include_pattern(include);
-        consumer.subscribe(&[&pattern])?;
-    } else {
-        let topic = args
-            .topic
-            .as_deref()
-            .ok_or_else(|| Error::Usage("consume requires --topic or --include".into()))?;
-        consumer.subscribe(&[topic])?;
-    }
+#[derive(Debug)]
+struct ProducerPerfShared {
+    total: ProducerPerfStats,
+    steady: Option<ProducerPerfStats>,
+    failures: u64,
+    first_error: Option<String>,
+}
 
-    let mut stream = consumer.stream();
-    let mut received = 0_i64;
-    while should_consume_more(args.max_messages, received) {
-        tokio::select! {
-            _ = tokio::signal::ctrl_c() => break,
-            message = next_consumer_message(&mut stream, args.timeout_ms) => {
-                let Some(message) = message? else { break };
-                let message = match message {
-                    Ok(message) => message,
-                    Err(error) if args.skip_message_on_error => {
-                        eprintln!("skipping consumer error: {error}");
-                        continue;
-                    }
-                    Err(error) => return Err(Error::Kafka(error)),
-                };
-                if args.json {
-                    let record = ConsumedRecord {
-                        topic: message.topic(),
-                        partition: message.partition(),
-                        offset: message.offset(),
-                        timestamp: message.timestamp().to_millis(),
-                        key: message.key().map(|key| String::from_utf8_lossy(key).into_owned()),
-                        value: message.payload().map(|value| String::from_utf8_lossy(value).into_owned()),
-                        headers: collect_headers(message.headers()),
-                    };
-                    output::write_json_line(&record)?;
-                } else {
-                    write_formatted_message(&message, &formatter)?;
+#[derive(Clone, Debug)]
+struct ProducerPerfContext {
+    shared: Arc<Mutex<ProducerPerfShared>>,
+}
+
+impl ClientContext for ProducerPerfContext {}
+
+impl ProducerContext for ProducerPerfContext {
+    type DeliveryOpaque = Box<ProducerPerfOpaque>;
+
+    fn delivery(&self, result: &DeliveryResult<'_>, opaque: Self::DeliveryOpaque) {
+        let mut shared = lock_unpoisoned(&self.shared);
+        match result {
+            Ok(_) => {
+                let now = Instant::now();
+                let latency = u64::try_from(now.duration_since(opaque.started).as_millis())
+                    .unwrap_or(u64::MAX);
+                shared.total.record(latency, opaque.bytes, now);
+                if opaque.steady_state
+                    && let Some(steady) = shared.steady.as_mut()
+                {
+                    steady.record(latency, opaque.bytes, now);
+                }
+            }
+            Err((error, _)) => {
+                shared.failures = shared.failures.saturating_add(1);
+                if shared.first_error.is_none() {
+                    shared.first_error = Some(error.to_string());
                 }
-                received += 1;
+                drop(shared);
+                eprintln!("{error}");
             }
         }
     }
-    Ok(())
 }
 
-async fn share_consume(
-    bootstrap: &str,
-    command_config: Option<&Path>,
-    request_timeout: Duration,
-    args: ShareConsumeArgs,
-) -> Result<()> {
-    let formatter = message_formatter_options(&args)?;
-    let mut properties = match command_config {
-        Some(path) => config::load_properties(path)?,
-        None => std::collections::HashMap::new(),
-    };
-    for (key, value) in parse_pairs(args.properties())? {
-        properties.insert(key, value);
-    }
+fn lock_unpoisoned<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
 
-    let configured_group = properties.get("group.id").map(String::as_str);
-    if let (Some(argument), Some(configured)) = (args.group.as_deref(), configured_group)
-        && argument != configured
-    {
-        return Err(Error::Usage(format!(
-            "group ids supplied by --group and consumer properties must match: '{argument}', '{configured}'"
-        )));
-    }
-    let group = args
-        .group
-        .as_deref()
-        .or(configured_group)
-        .unwrap_or("console-share-consumer");
-    let client_id = properties
-        .get("client.id")
-        .map_or("console-share-consumer", String::as_str);
+#[derive(Clone, Copy, Debug)]
+struct PerfRandom(u64);
 
-    let mut builder = ShareConsumer::builder()
-        .bootstrap_servers(bootstrap)
-        .group_id(group)
-        .client_id(client_id)
-        .acknowledgement_mode(AcknowledgementMode::Explicit)
-        .request_timeout(
-            share_duration_property(&properties, "request.timeout.ms")?.unwrap_or(request_timeout),
-        );
-    if let Some(auth) = config::protocol_auth(&properties)? {
-        builder = builder.auth(auth);
+impl PerfRandom {
+    const fn new(seed: i64) -> Self {
+        Self(seed.cast_unsigned())
     }
-    if let Some(value) = share_i32_property(&properties, "max.poll.records")? {
-        builder = builder.max_poll_records(value);
-    } else if let Some(max_messages) = args.max_messages.filter(|value| *value > 0) {
-        builder = builder.max_poll_records(max_messages);
+
+    const fn next_u32(&mut self) -> u32 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut value = self.0;
+        value = (value ^ (value >> 33)).wrapping_mul(0x62a9_d9ed_7997_05f5);
+        value = (value ^ (value >> 28)).wrapping_mul(0xcb24_d0a5_c88c_35b3);
+        (value >> 32) as u32
     }
-    if let Some(value) = share_i32_property(&properties, "fetch.max.wait.ms")? {
-        builder = builder.fetch_max_wait_ms(value);
+
+    fn index(&mut self, upper: usize) -> usize {
+        let bound = u32::try_from(upper).unwrap_or(u32::MAX);
+        let mask = bound - 1;
+        let mut random = self.next_u32();
+        if bound & mask == 0 {
+            return usize::try_from(random & mask).unwrap_or_default();
+        }
+        let mut candidate = random >> 1;
+        loop {
+            let value = candidate % bound;
+            let check = candidate.wrapping_add(mask).wrapping_sub(value);
+            if i32::from_ne_bytes(check.to_ne_bytes()) >= 0 {
+                return usize::try_from(value).unwrap_or_default();
+            }
+            random = self.next_u32();
+            candidate = random >> 1;
+        }
     }
-    if let Some(value) = share_duration_property(&properties, "session.timeout.ms")? {
-        builder = builder.session_timeout(value);
-    }
-    if let Some(value) = share_duration_property(&properties, "heartbeat.interval.ms")? {
-        builder = builder.heartbeat_interval(value);
-    }
-    if let Some(value) = share_duration_property(&properties, "metadata.max.age.ms")? {
-        builder = builder.metadata_max_age(value);
-    }
-    if let Some(value) = share_duration_property(&properties, "metadata.max.idle.ms")? {
-        builder = builder.metadata_topic_cache_ttl(value);
-    }
-    if let Some(rack) = properties.get("client.rack") {
-        builder = builder.client_rack(rack);
+}
+
+fn read_producer_payloads(path: &Path, delimiter: &str) -> Result<Vec<Vec<u8>>> {
+    let contents = fs::read_to_string(path)?;
+    if contents.is_empty() {
+        return Err(Error::Usage(
+            "payload file does not exist or is empty".into(),
+        ));
     }
+    let delimiter = if delimiter == "\\n" { "\n" } else { delimiter };
+    let delimiter = Regex::new(delimiter)
+        .map_err(|error| Error::Usage(format!("invalid payload delimiter: {error}")))?;
+    let payloads = delimiter
+        .split(&contents)
+        .map(str::as_bytes)
+        .map(<[u8]>::to_vec)
+        .collect::<Vec<_>>();
+    let trailing_empty = delimiter
+        .find_iter(&contents)
+        .last()
+        .is_some_and(|match_| match_.end() == contents.len());
+    let end = payloads.len().saturating_sub(usize::from(trailing_empty));
+    Ok(payloads[..end].to_vec())
+}
 
-    let consumer = builder.build().await?;
-    consumer.subscribe(&[&args.topic]).await?;
-    let acknowledgement = if args.reject {
-        ShareAcknowledgeType::Reject
-    } else if args.release {
-        ShareAcknowledgeType::Release
-    } else {
-        ShareAcknowledgeType::Accept
-    };
-    let run_result = consume_share_records(&consumer, &formatter, &args, acknowledgement).await;
-    let close_result = consumer.close().await.map_err(Error::from);
-    drop(consumer);
-    eprintln!(
-        "Processed a total of {} messages",
-        run_result.as_ref().map_or(0, |count| *count)
-    );
-    if args.enable_systest_events {
-        println!("shutdown_complete");
+fn producer_perf_payload(
+    args: &ProducerPerfTestArgs,
+    payloads: &[Vec<u8>],
+    random: &mut PerfRandom,
+    index: u64,
+) -> Vec<u8> {
+    if !payloads.is_empty() {
+        return payloads[random.index(payloads.len())].clone();
     }
-    run_result.map(|_| ()).and(close_result)
+    if let Some(size) = args.record_size {
+        return (0..size)
+            .map(|_| b'A'.saturating_add(u8::try_from(random.index(26)).unwrap_or_default()))
+            .collect();
+    }
+    index.to_string().into_bytes()
 }
 
-async fn consumer_perf_test(
-    bootstrap: &str,
-    command_config: Option<&Path>,
-    args: ConsumerPerfTestArgs,
-) -> Result<()> {
-    let bootstrap = bootstrap.to_owned();
-    let command_config = command_config.map(Path::to_path_buf);
-    tokio::task::spawn_blocking(move || {
-        run_consumer_perf_test(&bootstrap, command_config.as_deref(), &args)
-    })
-    .await
-    .map_err(|error| Error::Config(format!("consumer performance task failed: {error}")))?
+fn producer_perf_key(
+    distribution: ProducerKeyDistribution,
+    range: Option<i32>,
+    index: u64,
+    random: &mut PerfRandom,
+) -> Option<String> {
+    let range = range.and_then(|value| u64::try_from(value).ok())?;
+    match distribution {
+        ProducerKeyDistribution::None => None,
+        ProducerKeyDistribution::Range => Some((index % range).to_string()),
+        ProducerKeyDistribution::Random => Some(
+            random
+                .index(usize::try_from(range).unwrap_or(usize::MAX))
+                .to_string(),
+        ),
+    }
 }
 
-#[expect(
-    clippy::too_many_lines,
-    reason = "the Kafka benchmark lifecycle keeps configuration, rebalance accounting, polling, reporting, and cleanup ordering explicit"
-)]
-fn run_consumer_perf_test(
-    bootstrap: &str,
-    command_config: Option<&Path>,
-    args: &ConsumerPerfTestArgs,
-) -> Result<()> {
-    if args.reporting_interval == 0 {
+fn validate_producer_perf_args(args: &ProducerPerfTestArgs) -> Result<(u64, u64, Duration)> {
+    let num_records = u64::try_from(args.num_records)
+        .ok()
+        .filter(|value| *value > 0)
+        .ok_or_else(|| Error::Usage("--num-records should be greater than zero".into()))?;
+    if args.record_size.is_some_and(|value| value <= 0) {
         return Err(Error::Usage(
-            "reporting interval must be greater than 0".into(),
+            "--record-size should be greater than zero".into(),
         ));
     }
-    if args.fetch_size <= 0 {
-        return Err(Error::Usage("--fetch-size must be greater than 0".into()));
-    }
-    if args.socket_buffer_size <= 0 {
+    if args.transaction_duration_ms.is_some_and(|value| value <= 0) {
         return Err(Error::Usage(
-            "--socket-buffer-size must be greater than 0".into(),
+            "--transaction-duration-ms should be greater than zero".into(),
         ));
     }
-    validate_share_date_pattern(&args.date_format)?;
-    let config_path = share_perf_config_path(command_config, args.consumer_config.as_deref())?;
-    if args.messages.is_some() {
-        println!("Warning: --messages is deprecated. Use --num-records instead.");
+    let reporting_interval = u64::try_from(args.reporting_interval)
+        .ok()
+        .filter(|value| *value > 0)
+        .ok_or_else(|| Error::Usage("--reporting-interval should be greater than zero".into()))?;
+    if args.record_key_range.is_some_and(|value| value <= 0) {
+        return Err(Error::Usage(
+            "--record-key-range should be greater than zero".into(),
+        ));
     }
-    if args.consumer_config.is_some() {
-        println!("Warning: --consumer.config is deprecated. Use --command-config instead.");
+    match (args.key_distribution, args.record_key_range) {
+        (ProducerKeyDistribution::None, Some(_)) => {
+            return Err(Error::Usage(
+                "--key-distribution must be 'range' or 'random' when --record-key-range is specified"
+                    .into(),
+            ));
+        }
+        (ProducerKeyDistribution::Range | ProducerKeyDistribution::Random, None) => {
+            return Err(Error::Usage(
+                "--record-key-range is required when --key-distribution is 'range' or 'random'"
+                    .into(),
+            ));
+        }
+        _ => {}
     }
-    if !args.hide_header {
-        print_consumer_perf_header(args.show_detailed_stats);
+    let warmup_records = u64::try_from(args.warmup_records.max(0)).unwrap_or_default();
+    if warmup_records >= num_records {
+        return Err(Error::Usage(
+            "--warmup-records must be strictly fewer than --num-records".into(),
+        ));
     }
+    Ok((
+        num_records,
+        warmup_records,
+        Duration::from_millis(reporting_interval),
+    ))
+}
 
-    let mut config = config::client_config(bootstrap, config_path)?;
-    apply_client_properties(&mut config, &args.properties)?;
-    let generated_group = format!(
-        "perf-consumer-{}",
-        Utc::now().timestamp_millis().unsigned_abs() % 100_000
-    );
-    config
-        .set(
-            "group.id",
-            args.group.as_deref().unwrap_or(&generated_group),
-        )
-        .set("max.partition.fetch.bytes", args.fetch_size.to_string())
-        .set(
-            "socket.receive.buffer.bytes",
-            args.socket_buffer_size.to_string(),
-        )
-        .set(
-            "auto.offset.reset",
-            if args.from_latest {
-                "latest"
-            } else {
-                "earliest"
-            },
+#[expect(
+    clippy::too_many_lines,
+    reason = "the Kafka benchmark lifecycle keeps payload generation, transactions, throttling, delivery callbacks, and reporting explicit"
+)]
+fn run_producer_perf_test(
+    bootstrap: Option<&str>,
+    command_config: Option<&Path>,
+    args: &ProducerPerfTestArgs,
+) -> Result<()> {
+    let (num_records, warmup_records, reporting_interval) = validate_producer_perf_args(args)?;
+    let config_path = share_perf_config_path(command_config, args.producer_config.as_deref())?;
+    if !args.producer_props.is_empty() {
+        println!("Option --producer-props is deprecated. Use --command-property instead.");
+    }
+    if args.producer_config.is_some() {
+        println!("Option --producer.config is deprecated. Use --command-config instead.");
+    }
+    let properties = parse_producer_perf_properties(args.properties())?;
+    let file_properties = config_path.map(config::load_properties).transpose()?;
+    let configured_bootstrap = properties
+        .iter()
+        .find_map(|(key, value)| (key == "bootstrap.servers").then(|| value.clone()))
+        .or_else(|| {
+            file_properties
+                .as_ref()
+                .and_then(|values| values.get("bootstrap.servers").cloned())
+        });
+    let resolved_bootstrap = bootstrap
+        .map(str::to_owned)
+        .or(configured_bootstrap)
+        .ok_or_else(|| {
+        Error::Usage(
+            "at least one of --bootstrap-server, --command-property, --producer-props, --producer.config or --command-config must provide bootstrap.servers"
+                .into(),
         )
-        .set("check.crcs", "false");
-    if config.get("client.id").is_none() {
-        config.set("client.id", "perf-consumer-client");
+    })?;
+    let mut config = config::client_config(&resolved_bootstrap, config_path)?;
+    for (key, value) in properties {
+        config.set(key, value);
     }
-    let consumer: BaseConsumer = config.create()?;
-    if let Some(topic) = args.topic.as_deref() {
-        consumer.subscribe(&[topic])?;
-    } else if let Some(include) = args.include.as_deref() {
-        consumer.subscribe(&[&consumer_include_pattern(include)])?;
+    if let Some(bootstrap) = bootstrap {
+        config.set("bootstrap.servers", bootstrap);
+    }
+    if config.get("client.id").is_none() {
+        config.set("client.id", "perf-producer-client");
     }
 
-    let start = Instant::now();
-    let start_ms = Utc::now().timestamp_millis();
-    let mut last_record = start;
-    let mut last_report = start;
-    let mut previous = SharePerfStats::default();
-    let mut stats = SharePerfStats::default();
-    let mut join_started = start;
-    let mut assigned = false;
-    let mut total_join = Duration::ZERO;
-    let mut round_join = Duration::ZERO;
-    while stats.records < args.num_records()
-        && last_record.elapsed() <= Duration::from_millis(args.timeout)
-    {
-        let message = consumer.poll(Duration::from_millis(100));
-        let now = Instant::now();
-        let currently_assigned = consumer.assignment()?.count() > 0;
-        if !assigned && currently_assigned {
-            let elapsed = join_started.elapsed();
-            total_join = total_join.saturating_add(elapsed);
-            round_join = round_join.saturating_add(elapsed);
-        } else if assigned && !currently_assigned {
-            join_started = now;
-        }
-        assigned = currently_assigned;
-        let Some(message) = message else { continue };
-        let message = message?;
-        last_record = now;
-        stats.records = stats.records.saturating_add(1);
-        stats.bytes = stats.bytes.saturating_add(
-            message
-                .key()
-                .map_or(0, |value| u64::try_from(value.len()).unwrap_or(u64::MAX))
-                .saturating_add(
-                    message
-                        .payload()
-                        .map_or(0, |value| u64::try_from(value.len()).unwrap_or(u64::MAX)),
-                ),
-        );
-        if last_report.elapsed() >= Duration::from_millis(args.reporting_interval) {
-            if args.show_detailed_stats {
-                print_consumer_perf_progress(
-                    stats,
-                    previous,
-                    last_report.elapsed(),
-                    round_join,
-                    &args.date_format,
-                );
-            }
-            round_join = Duration::ZERO;
-            previous = stats;
-            last_report = now;
-        }
+    let transactions_enabled = args.transaction_duration_ms.is_some()
+        || args.transactional_id.is_some()
+        || config.get("transactional.id").is_some();
+    let transaction_duration = if transactions_enabled {
+        let transaction_id = args.transactional_id.clone().unwrap_or_else(|| {
+            config.get("transactional.id").map_or_else(
+                || {
+                    format!(
+                        "performance-producer-{}",
+                        URL_SAFE_NO_PAD.encode(uuid::Uuid::new_v4().as_bytes())
+                    )
+                },
+                str::to_owned,
+            )
+        });
+        config.set("transactional.id", transaction_id);
+        Some(Duration::from_millis(
+            u64::try_from(args.transaction_duration_ms.unwrap_or(3_000)).unwrap_or(3_000),
+        ))
+    } else {
+        None
+    };
+
+    let payloads = if let Some(path) = args.payload_file.as_deref() {
+        println!("Reading payloads from: {}", path.canonicalize()?.display());
+        let payloads = read_producer_payloads(path, &args.payload_delimiter)?;
+        println!("Number of records read: {}", payloads.len());
+        payloads
+    } else {
+        Vec::new()
+    };
+    if args.payload_file.is_some() && payloads.is_empty() {
+        return Err(Error::Usage("payload file produced no records".into()));
     }
-    if stats.records < args.num_records() {
+    if warmup_records > 0 {
         println!(
-            "WARNING: Exiting before consuming the expected number of records: timeout ({} ms) exceeded. You can use the --timeout option to increase the timeout.",
-            args.timeout
+            "Warmup first {warmup_records} records. Steady state results will print after the complete test summary."
         );
     }
-    let end_ms = Utc::now().timestamp_millis();
-    let elapsed = start.elapsed();
-    if !args.show_detailed_stats {
-        print_consumer_perf_summary(
-            stats,
-            elapsed,
-            total_join,
-            start_ms,
-            end_ms,
-            &args.date_format,
+
+    let shared = Arc::new(Mutex::new(ProducerPerfShared {
+        total: ProducerPerfStats::new(num_records, reporting_interval, false),
+        steady: None,
+        failures: 0,
+        first_error: None,
+    }));
+    let context = ProducerPerfContext {
+        shared: Arc::clone(&shared),
+    };
+    let producer: BaseProducer<ProducerPerfContext> = config.create_with_context(context)?;
+    let operation_timeout = Duration::from_secs(60);
+    if transactions_enabled {
+        producer.init_transactions(operation_timeout)?;
+    }
+    let mut random = PerfRandom::new(args.random_seed);
+    let throttle_started = Instant::now();
+    let mut transaction_records = 0_u64;
+    let mut transaction_started = Instant::now();
+
+    for index in 0..num_records {
+        if transactions_enabled && transaction_records == 0 {
+            producer.begin_transaction()?;
+            transaction_started = Instant::now();
+        }
+        if warmup_records > 0 && index == warmup_records {
+            let mut state = lock_unpoisoned(&shared);
+            state.total.suppress_printing = true;
+            state.steady = Some(ProducerPerfStats::new(
+                num_records - warmup_records,
+                reporting_interval,
+                true,
+            ));
+        }
+        let payload = producer_perf_payload(args, &payloads, &mut random, index);
+        let key = producer_perf_key(
+            args.key_distribution,
+            args.record_key_range,
+            index,
+            &mut random,
         );
+        let opaque = Box::new(ProducerPerfOpaque {
+            started: Instant::now(),
+            bytes: payload.len(),
+            steady_state: index >= warmup_records && warmup_records > 0,
+        });
+        let mut record = BaseRecord::with_opaque_to(&args.topic, opaque).payload(&payload);
+        if let Some(key) = key.as_deref() {
+            record = record.key(key);
+        }
+        loop {
+            match producer.send(record) {
+                Ok(()) => break,
+                Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), returned)) => {
+                    record = returned;
+                    producer.poll(Duration::from_millis(100));
+                }
+                Err((error, _)) => return Err(Error::Kafka(error)),
+            }
+        }
+        producer.poll(Duration::ZERO);
+        transaction_records = transaction_records.saturating_add(1);
+        if let Some(duration) = transaction_duration
+            && transaction_started.elapsed() >= duration
+        {
+            producer.commit_transaction(operation_timeout)?;
+            transaction_records = 0;
+        }
+        throttle_producer(args.throughput, index, throttle_started);
+    }
+    if transactions_enabled && transaction_records > 0 {
+        producer.commit_transaction(operation_timeout)?;
+    }
+    producer.flush(operation_timeout)?;
+    let (total_line, steady_line, count, bytes, failures, first_error) = {
+        let state = lock_unpoisoned(&shared);
+        (
+            state.total.total_line(),
+            state.steady.as_ref().map(ProducerPerfStats::total_line),
+            state.total.count,
+            state.total.bytes,
+            state.failures,
+            state.first_error.clone(),
+        )
+    };
+    println!("{total_line}");
+    if let Some(steady_line) = steady_line {
+        println!("{steady_line}");
     }
     if args.print_metrics {
         println!(
-            "records-consumed:client-id={}: {}",
-            config.get("client.id").unwrap_or("perf-consumer-client"),
-            stats.records
+            "records-sent:client-id={}: {}",
+            config.get("client.id").unwrap_or("perf-producer-client"),
+            count
         );
         println!(
-            "bytes-consumed:client-id={}: {}",
-            config.get("client.id").unwrap_or("perf-consumer-client"),
-            stats.bytes
+            "bytes-sent:client-id={}: {}",
+            config.get("client.id").unwrap_or("perf-producer-client"),
+            bytes
         );
-        println!("assigned-partitions: {}", consumer.assignment()?.count());
+        println!("record-errors: {failures}");
+    }
+    if failures > 0 {
+        return Err(Error::Config(format!(
+            "{} record deliveries failed: {}",
+            failures,
+            first_error.as_deref().unwrap_or("unknown error")
+        )));
     }
-    consumer.unsubscribe();
     Ok(())
 }
 
-fn print_consumer_perf_header(detailed: bool) {
-    if detailed {
-        println!(
-            "time, threadId, data.consumed.in.MB, MB.sec, data.consumed.in.nMsg, nMsg.sec, rebalance.time.ms, fetch.time.ms, fetch.MB.sec, fetch.nMsg.sec"
-        );
-    } else {
-        println!(
-            "start.time, end.time, data.consumed.in.MB, MB.sec, data.consumed.in.nMsg, nMsg.sec, rebalance.time.ms, fetch.time.ms, fetch.MB.sec, fetch.nMsg.sec"
-        );
+fn throttle_producer(throughput: f64, amount_so_far: u64, started: Instant) {
+    if throughput == 0.0 && amount_so_far > 0 {
+        std::thread::park();
+        return;
+    }
+    if let Some(remaining) = throttle_delay(throughput, amount_so_far, started) {
+        std::thread::sleep(remaining);
     }
 }
 
-fn consumer_fetch_values(
-    stats: SharePerfStats,
-    elapsed: Duration,
-    join: Duration,
-) -> (f64, f64, u128) {
-    let fetch = elapsed.saturating_sub(join);
-    let (_, mb_per_sec, records_per_sec, _) = share_perf_values(stats, fetch);
-    (mb_per_sec, records_per_sec, fetch.as_millis())
+/// Sleeps without blocking the runtime until `amount_so_far` records fit under `throughput`
+/// per second; -1 disables throttling.
+async fn throttle_records(throughput: f64, amount_so_far: u64, started: Instant) {
+    if let Some(remaining) = throttle_delay(throughput, amount_so_far, started) {
+        tokio::time::sleep(remaining).await;
+    }
 }
 
-fn print_consumer_perf_progress(
-    stats: SharePerfStats,
-    previous: SharePerfStats,
-    interval: Duration,
-    join: Duration,
-    date_format: &str,
-) {
-    let delta = SharePerfStats {
-        records: stats.records.saturating_sub(previous.records),
-        bytes: stats.bytes.saturating_sub(previous.bytes),
-    };
-    let (total_mb, mb_per_sec, records_per_sec, _) = share_perf_values(stats, interval);
-    let (fetch_mb_per_sec, fetch_records_per_sec, fetch_ms) =
-        consumer_fetch_values(delta, interval, join);
-    println!(
-        "{}, 0, {:.4}, {:.4}, {}, {:.4}, {}, {}, {:.4}, {:.4}",
-        format_share_perf_time(Utc::now().timestamp_millis(), date_format),
-        total_mb,
-        mb_per_sec,
-        stats.records,
-        records_per_sec,
-        join.as_millis(),
-        fetch_ms,
-        fetch_mb_per_sec,
-        fetch_records_per_sec
-    );
+/// How long to wait before `amount_so_far` records fit under `throughput` per second since
+/// `started`, or `None` when they already do or throttling is disabled (a throughput that is
+/// not positive and finite).
+#[expect(
+    clippy::cast_precision_loss,
+    reason = "fractional records-per-second throttling requires a floating-point elapsed target"
+)]
+fn throttle_delay(throughput: f64, amount_so_far: u64, started: Instant) -> Option<Duration> {
+    if throughput <= 0.0 || !throughput.is_finite() {
+        return None;
+    }
+    Duration::from_secs_f64(amount_so_far as f64 / throughput).checked_sub(started.elapsed())
 }
 
-fn print_consumer_perf_summary(
-    stats: SharePerfStats,
-    elapsed: Duration,
-    join: Duration,
-    start_ms: i64,
-    end_ms: i64,
-    date_format: &str,
-) {
-    let (total_mb, mb_per_sec, records_per_sec, _) = share_perf_values(stats, elapsed);
-    let (fetch_mb_per_sec, fetch_records_per_sec, fetch_ms) =
-        consumer_fetch_values(stats, elapsed, join);
-    println!(
-        "{}, {}, {:.4}, {:.4}, {}, {:.4}, {}, {}, {:.4}, {:.4}",
-        format_share_perf_time(start_ms, date_format),
-        format_share_perf_time(end_ms, date_format),
-        total_mb,
-        mb_per_sec,
-        stats.records,
-        records_per_sec,
-        join.as_millis(),
-        fetch_ms,
-        fetch_mb_per_sec,
-        fetch_records_per_sec
-    );
+async fn enqueue_with_timeout<K, P>(
+    producer: &FutureProducer,
+    mut record: FutureRecord<'_, K, P>,
+    timeout: Duration,
+) -> Result<DeliveryFuture>
+where
+    K: ToBytes + Sync + ?Sized,
+    P: ToBytes + Sync + ?Sized,
+{
+    let started = Instant::now();
+    loop {
+        match producer.send_result(record) {
+            Ok(delivery) => return Ok(delivery),
+            Err((KafkaError::MessageProduction(RDKafkaErrorCode::QueueFull), returned))
+                if started.elapsed() < timeout =>
+            {
+                record = returned;
+                tokio::time::sleep(
+                    timeout
+                        .saturating_sub(started.elapsed())
+                        .min(Duration::from_millis(100)),
+                )
+                .await;
+            }
+            Err((error, _)) => return Err(Error::Kafka(error)),
+        }
+    }
 }
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
-struct SharePerfStats {
-    records: u64,
-    bytes: u64,
-}
+/// Producer tuning flags shared by commands that produce records.
+trait ProducerArgs {
+    fn compression_type(&self) -> &str;
+    fn acks(&self) -> Option<&str>;
+    fn batch_size(&self) -> Option<usize>;
+    fn linger_ms(&self) -> Option<u64>;
+    fn request_timeout_ms(&self) -> Option<u64>;
+    fn max_block_ms(&self) -> Option<u64>;
 
-#[expect(
-    clippy::too_many_lines,
-    reason = "the Kafka performance lifecycle keeps setup, concurrent collection, reporting, commit, and close ordering explicit"
-)]
-async fn share_consumer_perf_test(
-    bootstrap: &str,
-    command_config: Option<&Path>,
-    request_timeout: Duration,
-    args: ShareConsumerPerfTestArgs,
-) -> Result<()> {
-    if args.threads == 0 {
-        return Err(Error::Usage("--threads must be greater than 0".into()));
+    fn max_partition_memory_bytes(&self) -> Option<usize> {
+        None
     }
-    if args.reporting_interval == 0 {
-        return Err(Error::Usage(
-            "reporting interval must be greater than 0".into(),
-        ));
+
+    fn message_send_max_retries(&self) -> Option<u32> {
+        None
     }
-    if args.fetch_size <= 0 {
-        return Err(Error::Usage("--fetch-size must be greater than 0".into()));
+
+    fn retry_backoff_ms(&self) -> Option<u64> {
+        None
     }
-    if args.socket_buffer_size <= 0 {
-        return Err(Error::Usage(
-            "--socket-buffer-size must be greater than 0".into(),
-        ));
+
+    fn metadata_expiry_ms(&self) -> Option<u64> {
+        None
     }
-    validate_share_date_pattern(&args.date_format)?;
-    if args.messages.is_some() {
-        println!("Warning: --messages is deprecated. Use --num-records instead.");
+
+    fn max_memory_bytes(&self) -> Option<usize> {
+        None
     }
-    if args.consumer_config.is_some() {
-        println!("Warning: --consumer.config is deprecated. Use --command-config instead.");
+
+    fn socket_buffer_size(&self) -> Option<i32> {
+        None
     }
-    if !args.hide_header {
-        print_share_perf_header();
+}
+
+impl ProducerArgs for crate::cli::ProduceArgs {
+    fn compression_type(&self) -> &str {
+        &self.compression_type
     }
 
-    let config_path = share_perf_config_path(command_config, args.consumer_config.as_deref())?;
-    let mut properties = match config_path {
-        Some(path) => config::load_properties(path)?,
-        None => std::collections::HashMap::new(),
-    };
-    for (key, value) in parse_pairs(&args.properties)? {
-        properties.insert(key, value);
+    fn acks(&self) -> Option<&str> {
+        self.acks.as_deref()
     }
-    let base_client_id = properties
-        .get("client.id")
-        .cloned()
-        .unwrap_or_else(|| "perf-share-consumer-client".into());
-    let mut consumers = Vec::with_capacity(args.threads);
-    let mut client_ids = Vec::with_capacity(args.threads);
-    for index in 0..args.threads {
-        let client_id = if args.threads == 1 {
-            base_client_id.clone()
-        } else {
-            format!("{}-{}", base_client_id, index + 1)
-        };
-        let consumer = build_share_perf_consumer(
-            bootstrap,
-            &args.group,
-            &client_id,
-            &properties,
-            request_timeout,
-            args.num_records(),
-        )
-        .await?;
-        consumer.subscribe(&[&args.topic]).await?;
-        client_ids.push(client_id);
-        consumers.push(consumer);
+
+    fn batch_size(&self) -> Option<usize> {
+        self.batch_size
     }
 
-    let start = Instant::now();
-    let start_ms = Utc::now().timestamp_millis();
-    let total_records = Arc::new(AtomicU64::new(0));
-    let worker_options = SharePerfWorkerOptions {
-        target: args.num_records(),
-        timeout_ms: args.timeout,
-        reporting_interval_ms: args.reporting_interval,
-        detailed: args.show_detailed_stats,
-        date_format: &args.date_format,
-    };
-    let stats =
-        futures::future::try_join_all(consumers.iter().enumerate().map(|(index, consumer)| {
-            consume_share_perf_records(
-                consumer,
-                index + 1,
-                &worker_options,
-                Arc::clone(&total_records),
-            )
-        }))
-        .await?;
-    let end_ms = Utc::now().timestamp_millis();
-    let elapsed = start.elapsed();
-    let total = stats
-        .iter()
-        .fold(SharePerfStats::default(), |mut total, item| {
-            total.records = total.records.saturating_add(item.records);
-            total.bytes = total.bytes.saturating_add(item.bytes);
-            total
-        });
+    fn linger_ms(&self) -> Option<u64> {
+        self.linger_ms
+    }
 
-    if args.show_consumer_stats {
-        for (index, (client_id, item)) in client_ids.iter().zip(&stats).enumerate() {
-            print_share_consumer_stats(
-                index + 1,
-                client_id,
-                *item,
-                elapsed,
-                start_ms,
-                end_ms,
-                &args.date_format,
-            );
-        }
+    fn request_timeout_ms(&self) -> Option<u64> {
+        self.request_timeout_ms
     }
-    if total.records < args.num_records() {
-        println!(
-            "WARNING: Exiting before consuming the expected number of records: timeout ({} ms) exceeded. You can use the --timeout option to increase the timeout.",
-            args.timeout
-        );
+
+    fn max_block_ms(&self) -> Option<u64> {
+        self.max_block_ms
     }
 
-    let metric_snapshots = args.print_metrics.then(|| {
-        consumers
-            .iter()
-            .map(|consumer| consumer.connection_metrics().snapshot())
-            .collect::<Vec<_>>()
-    });
-    for consumer in &consumers {
-        consumer.commit_sync().await?;
+    fn max_partition_memory_bytes(&self) -> Option<usize> {
+        self.max_partition_memory_bytes
     }
-    print_share_group_stats(total, elapsed, start_ms, end_ms, &args.date_format);
-    if let Some(snapshots) = metric_snapshots {
-        for (client_id, metrics) in client_ids.iter().zip(snapshots) {
-            println!(
-                "connections-created:client-id={client_id}: {}",
-                metrics.connections_created
-            );
-            println!(
-                "connections-closed:client-id={client_id}: {}",
-                metrics.connections_closed
-            );
-            println!(
-                "connection-errors:client-id={client_id}: {}",
-                metrics.connection_errors
-            );
-            println!(
-                "active-connections:client-id={client_id}: {}",
-                metrics.active_connections
-            );
-            println!(
-                "requests-sent:client-id={client_id}: {}",
-                metrics.normal_priority_requests
-            );
-        }
+
+    fn message_send_max_retries(&self) -> Option<u32> {
+        self.message_send_max_retries
     }
-    for consumer in &consumers {
-        consumer.close().await?;
+
+    fn retry_backoff_ms(&self) -> Option<u64> {
+        self.retry_backoff_ms
     }
-    drop(consumers);
-    Ok(())
-}
 
-fn share_perf_config_path<'a>(
-    command_config: Option<&'a Path>,
-    consumer_config: Option<&'a Path>,
-) -> Result<Option<&'a Path>> {
-    if command_config.is_some() && consumer_config.is_some() {
-        return Err(Error::Usage(
-            "--consumer.config cannot be used with --command-config".into(),
-        ));
+    fn metadata_expiry_ms(&self) -> Option<u64> {
+        self.metadata_expiry_ms
     }
-    Ok(consumer_config.or(command_config))
-}
 
-async fn build_share_perf_consumer(
-    bootstrap: &str,
-    group: &str,
-    client_id: &str,
-    properties: &std::collections::HashMap<String, String>,
-    request_timeout: Duration,
-    num_records: u64,
-) -> Result<ShareConsumer> {
-    let mut builder = ShareConsumer::builder()
-        .bootstrap_servers(bootstrap)
-        .group_id(group)
-        .client_id(client_id)
-        .acknowledgement_mode(AcknowledgementMode::Implicit)
-        .request_timeout(
-            share_duration_property(properties, "request.timeout.ms")?.unwrap_or(request_timeout),
-        );
-    let auth = config::protocol_auth(properties)?;
-    if let Some(auth) = auth {
-        builder = builder.auth(auth);
+    fn max_memory_bytes(&self) -> Option<usize> {
+        self.max_memory_bytes
     }
-    let max_poll_records = share_i32_property(properties, "max.poll.records")?;
-    if let Some(value) = max_poll_records {
-        builder = builder.max_poll_records(value);
-    } else if let Ok(value) = i32::try_from(num_records)
-        && value > 0
-    {
-        builder = builder.max_poll_records(value);
+
+    fn socket_buffer_size(&self) -> Option<i32> {
+        self.socket_buffer_size
     }
-    let fetch_max_wait = share_i32_property(properties, "fetch.max.wait.ms")?;
-    if let Some(value) = fetch_max_wait {
-        builder = builder.fetch_max_wait_ms(value);
+}
+
+impl ProducerArgs for RestoreArgs {
+    fn compression_type(&self) -> &str {
+        &self.compression_type
     }
-    let session_timeout = share_duration_property(properties, "session.timeout.ms")?;
-    if let Some(value) = session_timeout {
-        builder = builder.session_timeout(value);
+
+    fn acks(&self) -> Option<&str> {
+        self.acks.as_deref()
     }
-    let heartbeat_interval = share_duration_property(properties, "heartbeat.interval.ms")?;
-    if let Some(value) = heartbeat_interval {
-        builder = builder.heartbeat_interval(value);
+
+    fn batch_size(&self) -> Option<usize> {
+        self.batch_size
     }
-    let metadata_max_age = share_duration_property(properties, "metadata.max.age.ms")?;
-    if let Some(value) = metadata_max_age {
-        builder = builder.metadata_max_age(value);
+
+    fn linger_ms(&self) -> Option<u64> {
+        self.linger_ms
     }
-    let metadata_max_idle = share_duration_property(properties, "metadata.max.idle.ms")?;
-    if let Some(value) = metadata_max_idle {
-        builder = builder.metadata_topic_cache_ttl(value);
+
+    fn request_timeout_ms(&self) -> Option<u64> {
+        self.request_timeout_ms
     }
-    if let Some(rack) = properties.get("client.rack") {
-        builder = builder.client_rack(rack);
+
+    fn max_block_ms(&self) -> Option<u64> {
+        self.max_block_ms
     }
-    Ok(builder.build().await?)
 }
 
-struct SharePerfWorkerOptions<'a> {
-    target: u64,
-    timeout_ms: u64,
-    reporting_interval_ms: u64,
-    detailed: bool,
-    date_format: &'a str,
-}
+impl ProducerArgs for MirrorArgs {
+    fn compression_type(&self) -> &str {
+        &self.compression_type
+    }
 
-async fn consume_share_perf_records(
-    consumer: &ShareConsumer,
-    index: usize,
-    options: &SharePerfWorkerOptions<'_>,
-    total_records: Arc<AtomicU64>,
-) -> Result<SharePerfStats> {
-    let mut stats = SharePerfStats::default();
-    let mut previous = stats;
-    let mut last_record = Instant::now();
-    let mut last_report = Instant::now();
-    while total_records.load(Ordering::Relaxed) < options.target
-        && last_record.elapsed() <= Duration::from_millis(options.timeout_ms)
-    {
-        let records = consumer.poll(Duration::from_millis(100)).await?;
-        let now = Instant::now();
-        if !records.is_empty() {
-            last_record = now;
-        }
-        for record in records {
-            stats.records = stats.records.saturating_add(1);
-            stats.bytes = stats.bytes.saturating_add(
-                record.key.as_ref().map_or(0, |value| value.len() as u64)
-                    + record.value.as_ref().map_or(0, |value| value.len() as u64),
-            );
-            total_records.fetch_add(1, Ordering::Relaxed);
-            if options.detailed
-                && last_report.elapsed() >= Duration::from_millis(options.reporting_interval_ms)
-            {
-                print_share_progress(
-                    index,
-                    stats,
-                    previous,
-                    last_report.elapsed(),
-                    options.date_format,
-                );
-                previous = stats;
-                last_report = now;
-            }
-        }
+    fn acks(&self) -> Option<&str> {
+        self.acks.as_deref()
+    }
+
+    fn batch_size(&self) -> Option<usize> {
+        self.batch_size
+    }
+
+    fn linger_ms(&self) -> Option<u64> {
+        self.linger_ms
+    }
+
+    fn request_timeout_ms(&self) -> Option<u64> {
+        self.request_timeout_ms
+    }
+
+    fn max_block_ms(&self) -> Option<u64> {
+        self.max_block_ms
     }
-    Ok(stats)
 }
 
-fn print_share_perf_header() {
-    println!(
-        "start.time, end.time, data.consumed.in.MB, MB.sec, nMsg.sec, data.consumed.in.nMsg, fetch.time.ms"
+fn configure_producer(config: &mut rdkafka::ClientConfig, args: &impl ProducerArgs) -> Result<u64> {
+    let property_max_block = config
+        .get("max.block.ms")
+        .map(|value| parse_u64("max.block.ms", value))
+        .transpose()?;
+    config.remove("max.block.ms");
+    if let Some(buffer_memory) = config.get("buffer.memory") {
+        let bytes = parse_positive_u64("buffer.memory", buffer_memory)?;
+        config.set(
+            "queue.buffering.max.kbytes",
+            bytes.div_ceil(1024).to_string(),
+        );
+        config.remove("buffer.memory");
+    }
+    if let Some(send_buffer) = config.get("send.buffer.bytes").map(str::to_owned) {
+        config.set("socket.send.buffer.bytes", send_buffer);
+        config.remove("send.buffer.bytes");
+    }
+
+    config.set("compression.type", args.compression_type());
+    merge_producer_option(config, "acks", args.acks(), "-1");
+    merge_producer_option(
+        config,
+        "batch.size",
+        args.batch_size().map(|value| value.to_string()).as_deref(),
+        "16384",
+    );
+    if let Some(value) = args.max_partition_memory_bytes() {
+        config.set("batch.size", value.to_string());
+    }
+    merge_producer_option(
+        config,
+        "message.send.max.retries",
+        args.message_send_max_retries()
+            .map(|value| value.to_string())
+            .as_deref(),
+        "3",
+    );
+    merge_producer_option(
+        config,
+        "retry.backoff.ms",
+        args.retry_backoff_ms()
+            .map(|value| value.to_string())
+            .as_deref(),
+        "100",
+    );
+    merge_producer_option(
+        config,
+        "linger.ms",
+        args.linger_ms().map(|value| value.to_string()).as_deref(),
+        "1000",
+    );
+    merge_producer_option(
+        config,
+        "request.timeout.ms",
+        args.request_timeout_ms()
+            .map(|value| value.to_string())
+            .as_deref(),
+        "1500",
+    );
+    merge_producer_option(
+        config,
+        "metadata.max.age.ms",
+        args.metadata_expiry_ms()
+            .map(|value| value.to_string())
+            .as_deref(),
+        "300000",
+    );
+    merge_producer_option(
+        config,
+        "queue.buffering.max.kbytes",
+        args.max_memory_bytes()
+            .map(|value| value.div_ceil(1024).to_string())
+            .as_deref(),
+        "32768",
+    );
+    merge_producer_option(
+        config,
+        "socket.send.buffer.bytes",
+        args.socket_buffer_size()
+            .map(|value| value.to_string())
+            .as_deref(),
+        "102400",
     );
+    if config.get("client.id").is_none() {
+        config.set("client.id", "console-producer");
+    }
+    Ok(args.max_block_ms().or(property_max_block).unwrap_or(60_000))
 }
 
-fn java_date_pattern(pattern: &str) -> String {
-    pattern
-        .replace("yyyy", "%Y")
-        .replace("SSS", "%.3f")
-        .replace("MM", "%m")
-        .replace("dd", "%d")
-        .replace("HH", "%H")
-        .replace("mm", "%M")
-        .replace("ss", "%S")
+fn merge_producer_option(
+    config: &mut rdkafka::ClientConfig,
+    key: &str,
+    explicit: Option<&str>,
+    default: &str,
+) {
+    if let Some(value) = explicit {
+        config.set(key, value);
+    } else if config.get(key).is_none() {
+        config.set(key, default);
+    }
 }
 
-fn validate_share_date_pattern(pattern: &str) -> Result<()> {
-    let translated = java_date_pattern(pattern);
-    if StrftimeItems::new(&translated).any(|item| item == Item::Error) {
-        return Err(Error::Usage(format!(
-            "invalid --date-format pattern '{pattern}'"
-        )));
+fn parse_positive_u64(key: &str, value: &str) -> Result<u64> {
+    let value = parse_u64(key, value)?;
+    if value == 0 {
+        Err(Error::Usage(format!("{key} must be a positive integer")))
+    } else {
+        Ok(value)
     }
-    Ok(())
 }
 
-fn format_share_perf_time(timestamp_ms: i64, pattern: &str) -> String {
-    DateTime::from_timestamp_millis(timestamp_ms).map_or_else(
-        || timestamp_ms.to_string(),
-        |value| {
-            value
-                .with_timezone(&Local)
-                .format(&java_date_pattern(pattern))
-                .to_string()
-        },
-    )
+fn parse_u64(key: &str, value: &str) -> Result<u64> {
+    value
+        .parse::<u64>()
+        .map_err(|_| Error::Usage(format!("{key} must be a non-negative integer")))
 }
 
-#[expect(
-    clippy::cast_precision_loss,
-    reason = "Kafka performance output uses floating point rates"
-)]
-fn share_perf_values(stats: SharePerfStats, elapsed: Duration) -> (f64, f64, f64, u128) {
-    let seconds = elapsed.as_secs_f64().max(f64::EPSILON);
-    let megabytes = stats.bytes as f64 / (1024.0 * 1024.0);
-    (
-        megabytes,
-        megabytes / seconds,
-        stats.records as f64 / seconds,
-        elapsed.as_millis(),
-    )
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+struct ProducerInput {
+    #[serde(default)]
+    key: Option<ProducerField>,
+    value: Option<ProducerField>,
+    #[serde(default)]
+    partition: Option<i32>,
+    #[serde(default)]
+    #[serde(deserialize_with = "deserialize_producer_headers")]
+    headers: Vec<(String, Option<String>)>,
 }
 
-fn print_share_progress(
-    index: usize,
-    stats: SharePerfStats,
-    previous: SharePerfStats,
-    interval: Duration,
-    date_format: &str,
-) {
-    let end_ms = Utc::now().timestamp_millis();
-    let interval_ms = i64::try_from(interval.as_millis()).unwrap_or(i64::MAX);
-    let delta = SharePerfStats {
-        records: stats.records.saturating_sub(previous.records),
-        bytes: stats.bytes.saturating_sub(previous.bytes),
-    };
-    let (_, mb_per_sec, records_per_sec, fetch_ms) = share_perf_values(delta, interval);
-    let total_mb = share_perf_values(stats, interval).0;
-    println!(
-        "{}, {}, {:.4}, {:.4}, {:.4}, {}, {} for share consumer {}",
-        format_share_perf_time(end_ms.saturating_sub(interval_ms), date_format),
-        format_share_perf_time(end_ms, date_format),
-        total_mb,
-        mb_per_sec,
-        records_per_sec,
-        stats.records,
-        fetch_ms,
-        index
-    );
+/// A key or value read from producer input; JSON values are only accepted with a schema.
+#[derive(Debug, Deserialize, PartialEq, Eq)]
+#[serde(untagged)]
+enum ProducerField {
+    Text(String),
+    Json(serde_json::Value),
 }
 
-fn print_share_consumer_stats(
-    index: usize,
-    client_id: &str,
-    stats: SharePerfStats,
-    elapsed: Duration,
-    start_ms: i64,
-    end_ms: i64,
-    date_format: &str,
-) {
-    let (megabytes, mb_per_sec, records_per_sec, fetch_ms) = share_perf_values(stats, elapsed);
-    println!(
-        "Share consumer {} having client id {} consumption metrics- {}, {}, {:.4}, {:.4}, {:.4}, {}, {}",
-        index,
-        client_id,
-        format_share_perf_time(start_ms, date_format),
-        format_share_perf_time(end_ms, date_format),
-        megabytes,
-        mb_per_sec,
-        records_per_sec,
-        stats.records,
-        fetch_ms
-    );
+#[derive(Default)]
+struct ProducerEncoders {
+    key: Option<RecordEncoder>,
+    value: Option<RecordEncoder>,
 }
 
-fn print_share_group_stats(
-    stats: SharePerfStats,
-    elapsed: Duration,
-    start_ms: i64,
-    end_ms: i64,
-    date_format: &str,
-) {
-    let (megabytes, mb_per_sec, records_per_sec, fetch_ms) = share_perf_values(stats, elapsed);
-    println!(
-        "{}, {}, {:.4}, {:.4}, {:.4}, {}, {}",
-        format_share_perf_time(start_ms, date_format),
-        format_share_perf_time(end_ms, date_format),
-        megabytes,
-        mb_per_sec,
-        records_per_sec,
-        stats.records,
-        fetch_ms
-    );
-}
-
-fn verifiable_event(name: &str, fields: serde_json::Value) -> serde_json::Value {
-    let mut event = serde_json::Map::new();
-    event.insert(
-        "timestamp".into(),
-        serde_json::Value::from(Utc::now().timestamp_millis()),
-    );
-    event.insert("name".into(), serde_json::Value::from(name));
-    if let serde_json::Value::Object(fields) = fields {
-        event.extend(fields);
+async fn producer_encoders(args: &crate::cli::ProduceArgs) -> Result<ProducerEncoders> {
+    let key = args.key_schema.is_some() || args.key_subject.is_some();
+    let value = args.value_schema.is_some() || args.value_subject.is_some();
+    if !key && !value {
+        return Ok(ProducerEncoders::default());
     }
-    serde_json::Value::Object(event)
+    let properties = component_properties(args.reader_config.as_deref(), args.reader_properties())?;
+    let registry = args
+        .schema_registry_url
+        .as_deref()
+        .or_else(|| properties.get("schema.registry.url").map(String::as_str))
+        .map(|url| {
+            registry_client(
+                url,
+                properties.get("basic.auth.user.info").map(String::as_str),
+            )
+        })
+        .transpose()?;
+    let mut encoders = ProducerEncoders::default();
+    if key {
+        let subject = args
+            .key_subject
+            .clone()
+            .unwrap_or_else(|| format!("{}-key", args.topic));
+        encoders.key = Some(
+            RecordEncoder::resolve(
+                registry.as_ref(),
+                args.key_schema.as_deref(),
+                &subject,
+                args.key_message.as_deref(),
+            )
+            .await?,
+        );
+    }
+    if value {
+        let subject = args
+            .value_subject
+            .clone()
+            .unwrap_or_else(|| format!("{}-value", args.topic));
+        encoders.value = Some(
+            RecordEncoder::resolve(
+                registry.as_ref(),
+                args.value_schema.as_deref(),
+                &subject,
+                args.value_message.as_deref(),
+            )
+            .await?,
+        );
+    }
+    Ok(encoders)
 }
 
-fn parse_ack_pattern(value: Option<&str>) -> Result<Vec<ShareAcknowledgeType>> {
-    let Some(value) = value.filter(|value| !value.is_empty()) else {
-        return Ok(Vec::new());
+/// Converts a producer field to record bytes, validating and encoding it when a schema applies.
+///
+/// Line input is parsed as JSON for schema encoding; `--json` input is used as the datum itself.
+fn encoded_producer_field(
+    field: Option<&ProducerField>,
+    encoder: Option<&RecordEncoder>,
+    json: bool,
+    encoding: DataEncoding,
+    name: &str,
+) -> Result<Option<Vec<u8>>> {
+    let Some(field) = field else {
+        return Ok(None);
     };
-    let pattern = value
-        .split(',')
-        .map(|item| match item.trim().to_ascii_lowercase().as_str() {
-            "accept" => Ok(ShareAcknowledgeType::Accept),
-            "release" => Ok(ShareAcknowledgeType::Release),
-            "reject" => Ok(ShareAcknowledgeType::Reject),
-            "renew" => Ok(ShareAcknowledgeType::Renew),
-            other => Err(Error::Usage(format!(
-                "invalid acknowledgement type '{other}'; expected accept, release, reject, or renew"
-            ))),
-        })
-        .collect::<Result<Vec<_>>>()?;
-    if pattern
-        .iter()
-        .all(|kind| *kind == ShareAcknowledgeType::Renew)
-    {
-        return Err(Error::Usage(
-            "--ack-pattern must contain at least one non-renew type".into(),
-        ));
+    match (field, encoder) {
+        (ProducerField::Text(text), None) => decode_text(text, encoding).map(Some),
+        (ProducerField::Json(_), None) => Err(Error::Usage(format!(
+            "{name} must be a string unless a {name} schema is configured"
+        ))),
+        (ProducerField::Text(text), Some(encoder)) if !json => {
+            encoder.encode(&serde_json::from_str(text)?).map(Some)
+        }
+        (ProducerField::Text(text), Some(encoder)) => encoder
+            .encode(&serde_json::Value::String(text.clone()))
+            .map(Some),
+        (ProducerField::Json(value), Some(encoder)) => encoder.encode(value).map(Some),
+    }
+}
+
+/// Recovers the raw bytes of a key, value, or header value given in `encoding`.
+fn decode_text(text: &str, encoding: DataEncoding) -> Result<Vec<u8>> {
+    match encoding {
+        DataEncoding::Utf8 => Ok(text.as_bytes().to_vec()),
+        DataEncoding::Base64 => STANDARD
+            .decode(text)
+            .map_err(|error| Error::Usage(format!("invalid base64 data: {error}"))),
+        DataEncoding::Hex => {
+            if !text.len().is_multiple_of(2) || !text.is_ascii() {
+                return Err(Error::Usage(format!("invalid hex data: {text}")));
+            }
+            (0..text.len())
+                .step_by(2)
+                .map(|index| {
+                    u8::from_str_radix(&text[index..index + 2], 16)
+                        .map_err(|_| Error::Usage(format!("invalid hex data: {text}")))
+                })
+                .collect()
+        }
+        DataEncoding::File => fs::read(text)
+            .map_err(|error| Error::Usage(format!("failed to read payload file {text}: {error}"))),
     }
-    Ok(pattern)
 }
 
-async fn verifiable_share_consumer(
-    rdkafka_config: &rdkafka::ClientConfig,
-    bootstrap: &str,
-    command_config: Option<&Path>,
-    request_timeout: Duration,
-    verbose: bool,
-    args: VerifiableShareConsumerArgs,
-) -> Result<()> {
-    let pattern = parse_ack_pattern(args.ack_pattern.as_deref())?;
-    if !pattern.is_empty() && args.acknowledgement_mode == VerifiableAcknowledgementMode::Auto {
-        return Err(Error::Usage(
-            "--ack-pattern requires --acknowledgement-mode sync or async".into(),
-        ));
-    }
-    output::write_json_line(&verifiable_event("startup_complete", serde_json::json!({})))?;
-    if let Some(strategy) = args.offset_reset_strategy.as_deref() {
-        let admin = admin(rdkafka_config)?;
-        ffi::incremental_alter_config(
-            admin.inner().native_ptr(),
-            rdkafka_sys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_GROUP,
-            &args.group_id,
-            &[("share.auto.offset.reset".into(), strategy.into())],
-            &[],
-            duration_ms(request_timeout)?,
-        )?;
-        output::write_json_line(&verifiable_event(
-            "offset_reset_strategy_set",
-            serde_json::json!({"offsetResetStrategy": strategy}),
-        ))?;
-    }
+/// Renders consumed keys, values, and header values for `--json` output.
+struct JsonEncodings {
+    key: DataEncoding,
+    value: DataEncoding,
+    header: DataEncoding,
+    payload_dir: Option<PathBuf>,
+}
 
-    let properties = match command_config {
-        Some(path) => config::load_properties(path)?,
-        None => std::collections::HashMap::new(),
-    };
-    let mut builder = ShareConsumer::builder()
-        .bootstrap_servers(bootstrap)
-        .group_id(&args.group_id)
-        .client_id(
-            properties
-                .get("client.id")
-                .map_or("verifiable-share-consumer", String::as_str),
-        )
-        .acknowledgement_mode(if pattern.is_empty() {
-            AcknowledgementMode::Implicit
-        } else {
-            AcknowledgementMode::Explicit
-        })
-        .request_timeout(
-            share_duration_property(&properties, "request.timeout.ms")?.unwrap_or(request_timeout),
-        );
-    if let Some(auth) = config::protocol_auth(&properties)? {
-        builder = builder.auth(auth);
-    }
-    if let Some(max) = share_i32_property(&properties, "max.poll.records")?
-        .or_else(|| (args.max_messages > 0).then_some(args.max_messages))
-    {
-        builder = builder.max_poll_records(max);
-    }
-    let consumer = builder.build().await?;
-    consumer.subscribe(&[&args.topic]).await?;
-    let result = run_verifiable_share_consumer(&consumer, &args, &pattern, verbose).await;
-    let close_result = consumer.close().await.map_err(Error::from);
-    drop(consumer);
-    output::write_json_line(&verifiable_event(
-        "shutdown_complete",
-        serde_json::json!({}),
-    ))?;
-    result.and(close_result)
+/// Location of a consumed record, used to name payload files.
+struct RecordPosition<'a> {
+    topic: &'a str,
+    partition: i32,
+    offset: i64,
+    timestamp: Option<i64>,
 }
 
-async fn run_verifiable_share_consumer(
-    consumer: &ShareConsumer,
-    args: &VerifiableShareConsumerArgs,
-    pattern: &[ShareAcknowledgeType],
-    verbose: bool,
-) -> Result<()> {
-    let mut total_acknowledged = 0_i64;
-    let mut renew_indices = BTreeMap::<(String, i32, i64), usize>::new();
-    while args.max_messages < 0 || total_acknowledged < i64::from(args.max_messages) {
-        let records = tokio::select! {
-            _ = tokio::signal::ctrl_c() => {
-                output::write_json_line(&verifiable_event("shutdown_requested", serde_json::json!({})))?;
-                break;
+impl JsonEncodings {
+    fn new(
+        key: DataEncoding,
+        value: DataEncoding,
+        header: DataEncoding,
+        payload_dir: Option<PathBuf>,
+    ) -> Result<Self> {
+        let files = [key, value, header].contains(&DataEncoding::File);
+        match &payload_dir {
+            Some(dir) if files => fs::create_dir_all(dir)?,
+            None if files => {
+                return Err(Error::Usage(
+                    "--payload-dir is required when an encoding is file".into(),
+                ));
             }
-            result = consumer.poll(Duration::from_secs(5)) => result?,
-        };
-        if records.is_empty() {
-            continue;
+            _ => {}
         }
-        let mut partitions = BTreeMap::<(String, i32), BTreeSet<i64>>::new();
-        let mut ack_counts = BTreeMap::<String, i64>::new();
-        for record in &records {
-            partitions
-                .entry((record.topic.clone(), record.partition))
-                .or_default()
-                .insert(record.offset);
-            if verbose {
-                output::write_json_line(&verifiable_event(
-                    "record_data",
-                    serde_json::json!({
-                        "key": record.key.as_deref().map(|value| String::from_utf8_lossy(value).into_owned()),
-                        "value": record.value.as_deref().map(|value| String::from_utf8_lossy(value).into_owned()),
-                        "topic": record.topic,
-                        "partition": record.partition,
-                        "offset": record.offset,
-                    }),
-                ))?;
+        Ok(Self {
+            key,
+            value,
+            header,
+            payload_dir,
+        })
+    }
+
+    fn record<'a>(
+        &self,
+        formatter: &MessageFormatterOptions,
+        position: &RecordPosition<'a>,
+        key: Option<&[u8]>,
+        value: Option<&[u8]>,
+        headers: &[(&str, Option<&[u8]>)],
+    ) -> Result<ConsumedRecord<'a>> {
+        let key = match (formatter.key_deserializer, key) {
+            (NativeDeserializer::SchemaRegistry, _) | (_, None) => {
+                consumed_value(key, formatter.key_deserializer, formatter)?
             }
-            if !pattern.is_empty() {
-                let key = (record.topic.clone(), record.partition, record.offset);
-                let pattern_len = i64::try_from(pattern.len())
-                    .map_err(|_| Error::Usage("--ack-pattern is too long".into()))?;
-                let initial_index = usize::try_from(record.offset.rem_euclid(pattern_len))
-                    .map_err(|_| Error::Usage("acknowledgement pattern index overflow".into()))?;
-                let index = *renew_indices.entry(key.clone()).or_insert(initial_index);
-                let kind = pattern[index];
-                consumer.acknowledge(record, kind).await?;
-                let name = format!("{kind:?}").to_ascii_uppercase();
-                *ack_counts.entry(name).or_default() += 1;
-                if kind == ShareAcknowledgeType::Renew {
-                    renew_indices.insert(key, (index + 1) % pattern.len());
-                } else {
-                    renew_indices.remove(&key);
-                }
+            (_, Some(key)) => Some(ConsumedValue::Text(
+                self.encode(key, self.key, position, "key")?,
+            )),
+        };
+        let value = match (formatter.value_deserializer, value) {
+            (NativeDeserializer::SchemaRegistry, _) | (_, None) => {
+                consumed_value(value, formatter.value_deserializer, formatter)?
             }
+            (_, Some(value)) => Some(ConsumedValue::Text(
+                self.encode(value, self.value, position, "value")?,
+            )),
+        };
+        let mut encoded = BTreeMap::new();
+        for (index, (name, header)) in headers.iter().enumerate() {
+            let header = header
+                .map(|header| {
+                    self.encode(header, self.header, position, &format!("header-{index}"))
+                })
+                .transpose()?;
+            encoded.insert((*name).to_owned(), header);
         }
-        let summaries = verifiable_partition_summaries(&partitions);
-        output::write_json_line(&verifiable_event(
-            "records_consumed",
-            serde_json::json!({"count": records.len(), "partitions": summaries}),
-        ))?;
-        let commit = consumer.commit_sync().await;
-        let success = commit.is_ok();
-        let mut acknowledgement_fields = serde_json::Map::from_iter([
-            (
-                "count".into(),
-                serde_json::Value::from(if success { records.len() } else { 0 }),
-            ),
-            (
-                "partitions".into(),
-                if success {
-                    serde_json::Value::from(summaries)
-                } else {
-                    serde_json::Value::Array(Vec::new())
-                },
-            ),
-            ("success".into(), serde_json::Value::from(success)),
-        ]);
-        if let Err(error) = &commit {
-            acknowledgement_fields.insert("error".into(), error.to_string().into());
-        }
-        if !pattern.is_empty() && success {
-            acknowledgement_fields
-                .insert("ackTypeCounts".into(), serde_json::to_value(ack_counts)?);
-        }
-        output::write_json_line(&verifiable_event(
-            "offsets_acknowledged",
-            serde_json::Value::Object(acknowledgement_fields),
-        ))?;
-        commit?;
-        total_acknowledged += i64::try_from(records.len())
-            .map_err(|_| Error::Config("too many consumed records".into()))?;
+        Ok(ConsumedRecord {
+            topic: position.topic,
+            partition: position.partition,
+            offset: position.offset,
+            timestamp: position.timestamp,
+            key,
+            value,
+            headers: encoded,
+        })
+    }
+
+    fn encode(
+        &self,
+        bytes: &[u8],
+        encoding: DataEncoding,
+        position: &RecordPosition<'_>,
+        part: &str,
+    ) -> Result<String> {
+        use std::fmt::Write as _;
+
+        Ok(match encoding {
+            DataEncoding::Utf8 => String::from_utf8_lossy(bytes).into_owned(),
+            DataEncoding::Base64 => STANDARD.encode(bytes),
+            DataEncoding::Hex => bytes.iter().fold(String::new(), |mut hex, byte| {
+                let _ = write!(hex, "{byte:02x}");
+                hex
+            }),
+            DataEncoding::File => {
+                let dir = self
+                    .payload_dir
+                    .as_ref()
+                    .ok_or_else(|| Error::Usage("--payload-dir is required".into()))?;
+                let path = dir.join(format!(
+                    "{}-{}-{}.{part}",
+                    position.topic, position.partition, position.offset
+                ));
+                fs::write(&path, bytes)?;
+                path.display().to_string()
+            }
+        })
     }
-    Ok(())
 }
 
-fn verifiable_partition_summaries(
-    partitions: &BTreeMap<(String, i32), BTreeSet<i64>>,
-) -> Vec<serde_json::Value> {
-    partitions
-        .iter()
-        .map(|((topic, partition), offsets)| {
-            serde_json::json!({
-                "topic": topic,
-                "partition": partition,
-                "count": offsets.len(),
-                "offsets": offsets,
-            })
-        })
-        .collect()
+fn deserialize_producer_headers<'de, D>(
+    deserializer: D,
+) -> std::result::Result<Vec<(String, Option<String>)>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    BTreeMap::<String, Option<String>>::deserialize(deserializer)
+        .map(|headers| headers.into_iter().collect())
 }
 
-async fn consume_share_records(
-    consumer: &ShareConsumer,
-    formatter: &MessageFormatterOptions,
-    args: &ShareConsumeArgs,
-    acknowledgement: ShareAcknowledgeType,
-) -> Result<i64> {
-    let mut received = 0_i64;
-    let mut idle_since = Instant::now();
-    while should_consume_more(args.max_messages, received) {
-        let finite_idle_timeout = args
-            .timeout_ms
-            .filter(|timeout| *timeout != u64::MAX)
-            .map(Duration::from_millis);
-        let poll_timeout = finite_idle_timeout.map_or(Duration::from_secs(1), |timeout| {
-            timeout
-                .saturating_sub(idle_since.elapsed())
-                .min(Duration::from_secs(1))
-        });
-        let records = tokio::select! {
-            _ = tokio::signal::ctrl_c() => break,
-            result = consumer.poll(poll_timeout) => result?,
-        };
-        if records.is_empty() {
-            if finite_idle_timeout.is_some_and(|timeout| idle_since.elapsed() >= timeout) {
-                break;
-            }
-            continue;
+struct LineReaderOptions {
+    parse_key: bool,
+    key_separator: String,
+    parse_headers: bool,
+    headers_delimiter: String,
+    headers_separator: Regex,
+    headers_key_separator: String,
+    ignore_error: bool,
+    null_marker: Option<String>,
+}
+
+fn line_reader_options(args: &crate::cli::ProduceArgs) -> Result<LineReaderOptions> {
+    if args.line_reader != "org.apache.kafka.tools.LineMessageReader" {
+        return Err(Error::Unsupported(format!(
+            "Java line reader class {} cannot be loaded by the native client",
+            args.line_reader
+        )));
+    }
+    let properties = component_properties(args.reader_config.as_deref(), args.reader_properties())?;
+    let value = |key: &str| properties.get(key);
+    let parse_key = value("parse.key").map_or(args.parse_key, |value| bool_property(value));
+    let key_separator = value("key.separator")
+        .cloned()
+        .or_else(|| args.key_separator.map(|separator| separator.to_string()))
+        .unwrap_or_else(|| "\t".into());
+    let parse_headers = value("parse.headers").is_some_and(|value| bool_property(value));
+    let headers_delimiter = value("headers.delimiter")
+        .cloned()
+        .unwrap_or_else(|| "\t".into());
+    let headers_separator_value = value("headers.separator").map_or(",", String::as_str);
+    let headers_separator = Regex::new(headers_separator_value).map_err(|error| {
+        Error::Usage(format!(
+            "invalid headers.separator regular expression: {error}"
+        ))
+    })?;
+    let headers_key_separator = value("headers.key.separator")
+        .cloned()
+        .unwrap_or_else(|| ":".into());
+    let ignore_error = value("ignore.error").is_some_and(|value| bool_property(value));
+    let null_marker = value("null.marker").cloned();
+    for (left_name, left, right_name, right) in [
+        (
+            "headers.delimiter",
+            headers_delimiter.as_str(),
+            "headers.separator",
+            headers_separator_value,
+        ),
+        (
+            "headers.delimiter",
+            headers_delimiter.as_str(),
+            "headers.key.separator",
+            headers_key_separator.as_str(),
+        ),
+        (
+            "headers.separator",
+            headers_separator_value,
+            "headers.key.separator",
+            headers_key_separator.as_str(),
+        ),
+    ] {
+        if left == right {
+            return Err(Error::Usage(format!(
+                "{left_name} and {right_name} may not be equal"
+            )));
         }
-        idle_since = Instant::now();
-        for record in records {
-            if !should_consume_more(args.max_messages, received) {
-                break;
-            }
-            let written = if args.json {
-                write_share_json(&record)
-            } else {
-                write_formatted_share_message(&record, formatter)
-            };
-            match written {
-                Ok(()) => consumer.acknowledge(&record, acknowledgement).await?,
-                Err(error) if args.reject_message_on_error => {
-                    eprintln!("error processing message, rejecting it: {error}");
-                    consumer
-                        .acknowledge(&record, ShareAcknowledgeType::Reject)
-                        .await?;
-                }
-                Err(error) => return Err(error),
+    }
+    if let Some(marker) = &null_marker {
+        for (name, separator) in [
+            ("key.separator", key_separator.as_str()),
+            ("headers.delimiter", headers_delimiter.as_str()),
+            ("headers.separator", headers_separator_value),
+            ("headers.key.separator", headers_key_separator.as_str()),
+        ] {
+            if marker == separator {
+                return Err(Error::Usage(format!(
+                    "null.marker and {name} may not be equal"
+                )));
             }
-            received += 1;
         }
-        consumer.commit_sync().await?;
     }
-    Ok(received)
-}
-
-fn share_i32_property(
-    properties: &std::collections::HashMap<String, String>,
-    key: &str,
-) -> Result<Option<i32>> {
-    properties
-        .get(key)
-        .map(|value| {
-            value
-                .parse::<i32>()
-                .map_err(|error| Error::Config(format!("invalid {key} value '{value}': {error}")))
-        })
-        .transpose()
+    Ok(LineReaderOptions {
+        parse_key,
+        key_separator,
+        parse_headers,
+        headers_delimiter,
+        headers_separator,
+        headers_key_separator,
+        ignore_error,
+        null_marker,
+    })
 }
 
-fn share_duration_property(
-    properties: &std::collections::HashMap<String, String>,
-    key: &str,
-) -> Result<Option<Duration>> {
-    properties
-        .get(key)
-        .map(|value| {
-            value
-                .parse::<u64>()
-                .map(Duration::from_millis)
-                .map_err(|error| Error::Config(format!("invalid {key} value '{value}': {error}")))
-        })
-        .transpose()
+fn bool_property(value: &str) -> bool {
+    value.trim().eq_ignore_ascii_case("true")
 }
 
-fn write_share_json(record: &krafka::consumer::ConsumerRecord) -> Result<()> {
-    let headers = record
-        .headers
-        .iter()
-        .map(|(key, value)| {
-            (
-                String::from_utf8_lossy(key).into_owned(),
-                value
-                    .as_deref()
-                    .map(|value| String::from_utf8_lossy(value).into_owned()),
-            )
+fn producer_input(line: &str, json: bool, options: &LineReaderOptions) -> Result<ProducerInput> {
+    if json {
+        let input: ProducerInput = serde_json::from_str(line)?;
+        if input.partition.is_some_and(|partition| partition < 0) {
+            return Err(Error::Usage("partition must be non-negative".into()));
+        }
+        Ok(input)
+    } else {
+        let (headers, remaining) = parse_line_field(
+            options.parse_headers,
+            line,
+            &options.headers_delimiter,
+            options.ignore_error,
+            "headers delimiter",
+        )?;
+        let (key, value) = parse_line_field(
+            options.parse_key,
+            remaining,
+            &options.key_separator,
+            options.ignore_error,
+            "key separator",
+        )?;
+        let headers = headers
+            .map(|headers| parse_line_headers(headers, options))
+            .transpose()?
+            .unwrap_or_default();
+        Ok(ProducerInput {
+            key: nullable_field(key, options.null_marker.as_deref()).map(ProducerField::Text),
+            value: nullable_field(Some(value), options.null_marker.as_deref())
+                .map(ProducerField::Text),
+            partition: None,
+            headers,
         })
-        .collect();
-    output::write_json_line(&ConsumedRecord {
-        topic: &record.topic,
-        partition: record.partition,
-        offset: record.offset,
-        timestamp: Some(record.timestamp),
-        key: record
-            .key
-            .as_deref()
-            .map(|key| String::from_utf8_lossy(key).into_owned()),
-        value: record
-            .value
-            .as_deref()
-            .map(|value| String::from_utf8_lossy(value).into_owned()),
-        headers,
-    })
-}
-
-fn consumer_include_pattern(include: &str) -> String {
-    format!("^({include})$")
+    }
 }
 
-fn should_consume_more(max_messages: Option<i32>, received: i64) -> bool {
-    match max_messages {
-        None | Some(-1) => true,
-        Some(max_messages) => received < i64::from(max_messages),
+fn parse_line_field<'a>(
+    enabled: bool,
+    line: &'a str,
+    separator: &str,
+    ignore_error: bool,
+    name: &str,
+) -> Result<(Option<&'a str>, &'a str)> {
+    if !enabled {
+        return Ok((None, line));
     }
+    line.find(separator).map_or_else(
+        || {
+            if ignore_error {
+                Ok((None, line))
+            } else {
+                Err(Error::Usage(format!("no {name} found in input line")))
+            }
+        },
+        |index| Ok((Some(&line[..index]), &line[index + separator.len()..])),
+    )
 }
 
-static CONSUMER_GROUP_SEQUENCE: AtomicU64 = AtomicU64::new(0);
-
-fn configure_consumer(
-    config: &mut rdkafka::ClientConfig,
-    args: &crate::cli::ConsumeArgs,
-) -> Result<()> {
-    let file_group = config.get("group.id").map(str::to_owned);
-    let inline = parse_pairs(args.properties())?;
-    let inline_group = inline
-        .iter()
-        .rev()
-        .find(|(key, _)| key == "group.id")
-        .map(|(_, value)| value.clone());
-    let groups = [
-        args.group.as_ref(),
-        file_group.as_ref(),
-        inline_group.as_ref(),
-    ]
-    .into_iter()
-    .flatten()
-    .collect::<BTreeSet<_>>();
-    if groups.len() > 1 {
-        return Err(Error::Usage(format!(
-            "group ids supplied by --group, --command-config, and --command-property must match: {}",
-            groups
-                .into_iter()
-                .map(|group| format!("'{group}'"))
-                .collect::<Vec<_>>()
-                .join(", ")
-        )));
-    }
-    if args.partition.is_some() && !groups.is_empty() {
-        return Err(Error::Usage(
-            "--group/group.id and --partition cannot be specified together".into(),
-        ));
-    }
-
-    for (key, value) in inline {
-        config.set(key, value);
-    }
-    if config.get("client.id").is_none() {
-        config.set("client.id", "console-consumer");
-    }
-    if let Some(group) = args.group.as_deref().or_else(|| config.get("group.id")) {
-        let group = group.to_owned();
-        config.set("group.id", group);
-    } else {
-        config.set("group.id", ephemeral_consumer_group());
-        if config.get("enable.auto.commit").is_none() {
-            config.set("enable.auto.commit", "false");
-        }
-    }
-
-    if args.from_beginning {
-        if config
-            .get("auto.offset.reset")
-            .is_some_and(|value| value != "earliest")
-        {
-            return Err(Error::Usage(
-                "--from-beginning conflicts with auto.offset.reset other than earliest".into(),
-            ));
-        }
-        config.set("auto.offset.reset", "earliest");
-    } else if config.get("auto.offset.reset").is_none() {
-        config.set("auto.offset.reset", "latest");
-    }
-    if let Some(isolation) = &args.isolation_level {
-        config.set("isolation.level", isolation);
-    } else if config.get("isolation.level").is_none() {
-        config.set("isolation.level", "read_uncommitted");
+fn parse_line_headers(
+    headers: &str,
+    options: &LineReaderOptions,
+) -> Result<Vec<(String, Option<String>)>> {
+    if options.null_marker.as_deref() == Some(headers) {
+        return Ok(Vec::new());
     }
-    Ok(())
-}
-
-fn ephemeral_consumer_group() -> String {
-    format!(
-        "console-consumer-{}-{}",
-        process::id(),
-        CONSUMER_GROUP_SEQUENCE.fetch_add(1, Ordering::Relaxed)
-    )
+    options
+        .headers_separator
+        .split(headers)
+        .map(|pair| {
+            if let Some(index) = pair.find(&options.headers_key_separator) {
+                let key = &pair[..index];
+                if options.null_marker.as_deref() == Some(key) {
+                    return Err(Error::Usage("header key cannot equal null.marker".into()));
+                }
+                let value = &pair[index + options.headers_key_separator.len()..];
+                Ok((
+                    key.to_owned(),
+                    nullable_field(Some(value), options.null_marker.as_deref()),
+                ))
+            } else if options.ignore_error {
+                Ok((pair.to_owned(), None))
+            } else {
+                Err(Error::Usage(format!(
+                    "no header key separator found in pair '{pair}'"
+                )))
+            }
+        })
+        .collect()
 }
 
-fn consumer_offset(value: Option<&str>, from_beginning: bool) -> Result<Offset> {
-    match value {
-        Some(value) if value.eq_ignore_ascii_case("earliest") => Ok(Offset::Beginning),
-        Some(value) if value.eq_ignore_ascii_case("latest") => Ok(Offset::End),
-        Some(value) => value
-            .parse::<i64>()
-            .ok()
-            .filter(|offset| *offset >= 0)
-            .map(Offset::Offset)
-            .ok_or_else(|| {
-                Error::Usage(format!(
-                    "invalid offset '{value}'; expected earliest, latest, or a non-negative integer"
-                ))
-            }),
-        None if from_beginning => Ok(Offset::Beginning),
-        None => Ok(Offset::End),
-    }
+fn nullable_field(value: Option<&str>, marker: Option<&str>) -> Option<String> {
+    value.and_then(|value| (Some(value) != marker).then(|| value.to_owned()))
 }
 
-#[expect(
-    clippy::struct_excessive_bools,
-    reason = "mirrors Kafka DefaultMessageFormatter's independent print switches"
-)]
-struct MessageFormatterOptions {
-    print_timestamp: bool,
-    print_partition: bool,
-    print_offset: bool,
-    print_delivery: bool,
-    print_epoch: bool,
-    print_headers: bool,
-    print_key: bool,
-    print_value: bool,
-    key_separator: Vec<u8>,
-    line_separator: Vec<u8>,
-    headers_separator: Vec<u8>,
-    null_literal: Vec<u8>,
-    key_deserializer: NativeDeserializer,
-    value_deserializer: NativeDeserializer,
-    headers_deserializer: NativeDeserializer,
+#[derive(Serialize)]
+struct ConsumedRecord<'a> {
+    topic: &'a str,
+    partition: i32,
+    offset: i64,
+    timestamp: Option<i64>,
+    key: Option<ConsumedValue>,
+    value: Option<ConsumedValue>,
+    headers: BTreeMap<String, Option<String>>,
 }
 
-trait FormatterArgs {
-    fn formatter(&self) -> &str;
-    fn key_deserializer(&self) -> Option<&str>;
-    fn value_deserializer(&self) -> Option<&str>;
-    fn print_key(&self) -> bool;
-    fn key_separator(&self) -> &str;
-    fn formatter_config(&self) -> Option<&Path>;
-    fn formatter_properties(&self) -> &[String];
+/// A consumed key or value: text, or the JSON decoded through its registered schema.
+#[derive(Serialize)]
+#[serde(untagged)]
+enum ConsumedValue {
+    Text(String),
+    Decoded(SchemaValue),
 }
 
-impl FormatterArgs for crate::cli::ConsumeArgs {
-    fn formatter(&self) -> &str {
-        &self.formatter
+async fn consume(
+    mut config: rdkafka::ClientConfig,
+    timeout: Duration,
+    args: crate::cli::ConsumeArgs,
+) -> Result<()> {
+    configure_consumer(&mut config, &args)?;
+    let formatter = message_formatter_options(&args)?;
+    let filter = RecordFilter::new(&args)?;
+    let layout = ConsumeLayout::new(&args)?;
+    let consumer: StreamConsumer = config.create()?;
+    let mut time_range = assign_consumer(&consumer, &args, timeout)?;
+    if time_range
+        .as_ref()
+        .is_some_and(ConsumeTimeRange::is_finished)
+    {
+        return Ok(());
     }
+    let mut sink = RecordSink::open(
+        args.output_file.as_deref(),
+        args.output_compression,
+        Rotation {
+            bytes: args.rotate_bytes,
+            interval: args.rotate_interval_ms.map(Duration::from_millis),
+        },
+        layout.preamble()?,
+    )?;
 
-    fn key_deserializer(&self) -> Option<&str> {
-        self.key_deserializer.as_deref()
+    // A time range polls in short slices so partitions whose stop offset is never delivered
+    // can be retired by position, and tracks --timeout-ms across those slices itself.
+    let idle_timeout = args
+        .timeout_ms
+        .filter(|value| *value != u64::MAX)
+        .map(Duration::from_millis);
+    let poll_ms = if time_range.is_some() {
+        Some(args.timeout_ms.map_or(1_000, |value| value.min(1_000)))
+    } else {
+        args.timeout_ms
+    };
+    let outcome = async {
+        let mut stream = consumer.stream();
+        let mut received = 0_i64;
+        let mut progress = Instant::now();
+        while should_consume_more(args.max_messages, received) {
+            tokio::select! {
+                _ = tokio::signal::ctrl_c() => break,
+                message = next_consumer_message(&mut stream, poll_ms) => {
+                    let Some(message) = message? else {
+                        let Some(range) = &mut time_range else { break };
+                        range.retire(&consumer)?;
+                        if range.is_finished()
+                            || idle_timeout.is_some_and(|timeout| progress.elapsed() >= timeout)
+                        {
+                            break;
+                        }
+                        continue;
+                    };
+                    progress = Instant::now();
+                    let message = match message {
+                        Ok(message) => message,
+                        Err(error) if args.skip_message_on_error => {
+                            eprintln!("skipping consumer error: {error}");
+                            continue;
+                        }
+                        Err(error) => return Err(Error::Kafka(error)),
+                    };
+                    if let Some(range) = &mut time_range
+                        && !range.admit(&consumer, &message)?
+                    {
+                        if range.is_finished() {
+                            break;
+                        }
+                        continue;
+                    }
+                    formatter.load_schemas(message.key(), message.payload()).await?;
+                    if filter.matches(&message, &formatter)? {
+                        sink.write_record(&layout.render(&message, &formatter)?)?;
+                        received += 1;
+                    }
+                    if time_range.as_ref().is_some_and(ConsumeTimeRange::is_finished) {
+                        break;
+                    }
+                }
+            }
+        }
+        Ok(())
     }
+    .await;
+    // Finish compressed segments even when consuming failed, so captured records stay readable.
+    let finished = sink.finish();
+    outcome.and(finished)
+}
 
-    fn value_deserializer(&self) -> Option<&str> {
-        self.value_deserializer.as_deref()
-    }
+/// How `consume` renders each record.
+enum ConsumeLayout {
+    Formatted,
+    Json(JsonEncodings),
+    Csv(Vec<CsvColumn>),
+}
 
-    fn print_key(&self) -> bool {
-        self.print_key
+impl ConsumeLayout {
+    fn new(args: &crate::cli::ConsumeArgs) -> Result<Self> {
+        if args.json {
+            return Ok(Self::Json(JsonEncodings::new(
+                args.key_encoding,
+                args.value_encoding,
+                args.header_encoding,
+                args.payload_dir.clone(),
+            )?));
+        }
+        if args.csv {
+            return Ok(Self::Csv(if args.csv_columns.is_empty() {
+                CsvColumn::value_variants().to_vec()
+            } else {
+                args.csv_columns.clone()
+            }));
+        }
+        Ok(Self::Formatted)
     }
 
-    fn key_separator(&self) -> &str {
-        &self.key_separator
+    /// Returns the CSV header row written at the start of every output.
+    fn preamble(&self) -> Result<Option<Vec<u8>>> {
+        let Self::Csv(columns) = self else {
+            return Ok(None);
+        };
+        csv_line(columns.iter().map(|column| {
+            column
+                .to_possible_value()
+                .map_or_else(String::new, |value| value.get_name().to_owned())
+        }))
+        .map(Some)
     }
 
-    fn formatter_config(&self) -> Option<&Path> {
-        self.formatter_config.as_deref()
+    fn render(
+        &self,
+        message: &rdkafka::message::BorrowedMessage<'_>,
+        formatter: &MessageFormatterOptions,
+    ) -> Result<Vec<u8>> {
+        match self {
+            Self::Formatted => formatted_message(message, formatter),
+            Self::Json(encodings) => {
+                let headers = message
+                    .headers()
+                    .map(|headers| {
+                        headers
+                            .iter()
+                            .map(|header| (header.key, header.value))
+                            .collect::<Vec<_>>()
+                    })
+                    .unwrap_or_default();
+                let position = RecordPosition {
+                    topic: message.topic(),
+                    partition: message.partition(),
+                    offset: message.offset(),
+                    timestamp: message.timestamp().to_millis(),
+                };
+                let mut line = serde_json::to_vec(&encodings.record(
+                    formatter,
+                    &position,
+                    message.key(),
+                    message.payload(),
+                    &headers,
+                )?)?;
+                line.push(b'\n');
+                Ok(line)
+            }
+            Self::Csv(columns) => csv_line(
+                columns
+                    .iter()
+                    .map(|column| csv_field(*column, message, formatter))
+                    .collect::<Result<Vec<_>>>()?,
+            ),
+        }
     }
+}
 
-    fn formatter_properties(&self) -> &[String] {
-        self.formatter_properties()
-    }
+/// Renders one CSV column; absent keys, values, and timestamps are empty.
+fn csv_field(
+    column: CsvColumn,
+    message: &impl Message,
+    formatter: &MessageFormatterOptions,
+) -> Result<String> {
+    let text = |bytes: Option<&[u8]>, deserializer| {
+        bytes
+            .map(|bytes| {
+                formatter
+                    .display(Some(bytes), deserializer)
+                    .map(|display| String::from_utf8_lossy(&display).into_owned())
+            })
+            .transpose()
+            .map(Option::unwrap_or_default)
+    };
+    Ok(match column {
+        CsvColumn::Topic => message.topic().to_owned(),
+        CsvColumn::Partition => message.partition().to_string(),
+        CsvColumn::Offset => message.offset().to_string(),
+        CsvColumn::Timestamp => message
+            .timestamp()
+            .to_millis()
+            .map_or_else(String::new, |timestamp| timestamp.to_string()),
+        CsvColumn::Key => text(message.key(), formatter.key_deserializer)?,
+        CsvColumn::Value => text(message.payload(), formatter.value_deserializer)?,
+        CsvColumn::Headers => serde_json::to_string(
+            &message
+                .headers()
+                .map(|headers| {
+                    headers
+                        .iter()
+                        .map(|header| {
+                            (
+                                header.key.to_owned(),
+                                header
+                                    .value
+                                    .map(|value| String::from_utf8_lossy(value).into_owned()),
+                            )
+                        })
+                        .collect::<BTreeMap<_, _>>()
+                })
+                .unwrap_or_default(),
+        )?,
+    })
 }
 
-impl FormatterArgs for ShareConsumeArgs {
-    fn formatter(&self) -> &str {
-        &self.formatter
+fn csv_line(fields: impl IntoIterator<Item = String>) -> Result<Vec<u8>> {
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(false)
+        .from_writer(Vec::new());
+    writer
+        .write_record(fields)
+        .map_err(|error| Error::Usage(format!("cannot write CSV record: {error}")))?;
+    writer
+        .into_inner()
+        .map_err(|error| Error::Usage(format!("cannot finish CSV record: {error}")))
+}
+
+/// Assigns or subscribes the console consumer, returning the per-partition stop state when
+/// `--from-time`/`--to-time` select a time range.
+fn assign_consumer(
+    consumer: &StreamConsumer,
+    args: &crate::cli::ConsumeArgs,
+    timeout: Duration,
+) -> Result<Option<ConsumeTimeRange>> {
+    if args.from_time.is_some() || args.to_time.is_some() {
+        return ConsumeTimeRange::assign(consumer, args, timeout).map(Some);
     }
-
-    fn key_deserializer(&self) -> Option<&str> {
-        self.key_deserializer.as_deref()
+    if let Some(partition) = args.partition {
+        let topic = args
+            .topic
+            .as_deref()
+            .ok_or_else(|| Error::Usage("--topic is required with --partition".into()))?;
+        let offset = consumer_offset(args.offset.as_deref(), args.from_beginning)?;
+        let mut assignment = TopicPartitionList::new();
+        assignment.add_partition_offset(topic, partition, offset)?;
+        consumer.assign(&assignment)?;
+    } else if let Some(include) = args.include.as_deref() {
+        // librdkafka compiles subscription patterns as POSIX ERE. Validating with
+        // Rust's regex engine first would reject a different language than the
+        // engine that actually performs the subscription.
+        let pattern = consumer_include_pattern(include);
+        consumer.subscribe(&[&pattern])?;
+    } else {
+        let topic = args
+            .topic
+            .as_deref()
+            .ok_or_else(|| Error::Usage("consume requires --topic or --include".into()))?;
+        consumer.subscribe(&[topic])?;
     }
+    Ok(None)
+}
 
-    fn value_deserializer(&self) -> Option<&str> {
-        self.value_deserializer.as_deref()
-    }
+/// Partitions still being read by `consume --from-time/--to-time` and where each stops.
+struct ConsumeTimeRange {
+    to_time: Option<i64>,
+    /// Exclusive stop offset of each unfinished partition, when already known.
+    remaining: HashMap<i32, Option<i64>>,
+    topic: String,
+}
 
-    fn print_key(&self) -> bool {
-        self.print_key
-    }
+impl ConsumeTimeRange {
+    fn assign(
+        consumer: &StreamConsumer,
+        args: &crate::cli::ConsumeArgs,
+        timeout: Duration,
+    ) -> Result<Self> {
+        let topic = args
+            .topic
+            .clone()
+            .ok_or_else(|| Error::Usage("--from-time and --to-time require --topic".into()))?;
+        let from_time = args
+            .from_time
+            .as_deref()
+            .map(|value| parse_time_bound("--from-time", value))
+            .transpose()?;
+        let to_time = args
+            .to_time
+            .as_deref()
+            .map(|value| parse_time_bound("--to-time", value))
+            .transpose()?;
+        let partitions = if let Some(partition) = args.partition {
+            vec![partition]
+        } else {
+            let metadata = consumer.fetch_metadata(Some(&topic), timeout)?;
+            metadata
+                .topics()
+                .iter()
+                .find(|candidate| candidate.name() == topic && candidate.error().is_none())
+                .ok_or_else(|| Error::Usage(format!("topic {topic} does not exist")))?
+                .partitions()
+                .iter()
+                .map(rdkafka::metadata::MetadataPartition::id)
+                .collect()
+        };
+        let keyed = partitions
+            .iter()
+            .map(|partition| (topic.clone(), *partition))
+            .collect::<Vec<_>>();
+        let starts = from_time
+            .map(|time| offsets_for_time(consumer, &keyed, time, timeout))
+            .transpose()?;
+        let stops = to_time
+            .map(|time| offsets_for_time(consumer, &keyed, time.saturating_add(1), timeout))
+            .transpose()?;
+        let to_time_passed = to_time.is_some_and(|to_time| to_time < Utc::now().timestamp_millis());
+        let default_start = consumer_offset(args.offset.as_deref(), args.from_beginning)?;
 
-    fn key_separator(&self) -> &str {
-        &self.key_separator
+        let mut assignment = TopicPartitionList::new();
+        let mut remaining = HashMap::new();
+        for (index, partition) in partitions.into_iter().enumerate() {
+            let (low, high) = consumer.fetch_watermarks(&topic, partition, timeout)?;
+            let start = starts.as_ref().map_or(default_start, |starts| {
+                starts[index].map_or(Offset::End, Offset::Offset)
+            });
+            // A partition without records after the end time stops at its current end
+            // once that time has passed, and otherwise waits for a later record.
+            let stop = match stops.as_ref().map(|stops| stops[index]) {
+                Some(Some(stop)) => Some(stop),
+                Some(None) if to_time_passed => Some(high),
+                _ => None,
+            };
+            let first = match start {
+                Offset::Offset(offset) => offset.max(low),
+                Offset::End => high,
+                _ => low,
+            };
+            if stop.is_some_and(|stop| first >= stop) {
+                continue;
+            }
+            assignment.add_partition_offset(&topic, partition, start)?;
+            remaining.insert(partition, stop);
+        }
+        consumer.assign(&assignment)?;
+        Ok(Self {
+            to_time,
+            remaining,
+            topic,
+        })
     }
 
-    fn formatter_config(&self) -> Option<&Path> {
-        self.formatter_config.as_deref()
+    /// Returns whether the record is inside the range, pausing its partition once the
+    /// range is exhausted.
+    fn admit(
+        &mut self,
+        consumer: &StreamConsumer,
+        message: &rdkafka::message::BorrowedMessage<'_>,
+    ) -> Result<bool> {
+        let partition = message.partition();
+        let Some(stop) = self.remaining.get(&partition).copied() else {
+            return Ok(false);
+        };
+        let after_end = self.to_time.is_some_and(|to_time| {
+            message
+                .timestamp()
+                .to_millis()
+                .is_some_and(|timestamp| timestamp > to_time)
+        });
+        let admitted = !after_end && stop.is_none_or(|stop| message.offset() < stop);
+        if !admitted || stop.is_some_and(|stop| message.offset() + 1 >= stop) {
+            self.remaining.remove(&partition);
+            let mut paused = TopicPartitionList::new();
+            paused.add_partition(&self.topic, partition);
+            consumer.pause(&paused)?;
+        }
+        Ok(admitted)
     }
 
-    fn formatter_properties(&self) -> &[String] {
-        self.formatter_properties()
+    /// Finishes partitions whose position reached a known stop offset without delivering it,
+    /// as when the last offsets hold transaction markers or were compacted away.
+    fn retire(&mut self, consumer: &StreamConsumer) -> Result<()> {
+        let mut stops = self
+            .remaining
+            .iter()
+            .filter_map(|(partition, stop)| Some(((self.topic.clone(), *partition), (*stop)?)))
+            .collect::<HashMap<_, _>>();
+        let known = stops.len();
+        retire_consumed_partitions(consumer, &mut stops)?;
+        if stops.len() < known {
+            self.remaining.retain(|partition, stop| {
+                stop.is_none() || stops.contains_key(&(self.topic.clone(), *partition))
+            });
+        }
+        Ok(())
+    }
+
+    fn is_finished(&self) -> bool {
+        self.remaining.is_empty()
     }
 }
 
-#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
-enum NativeDeserializer {
-    #[default]
-    Raw,
-    Utf8String,
+#[derive(Debug, Serialize)]
+struct BackupRow {
+    topic: String,
+    partitions: i32,
+    records: u64,
+    file: String,
 }
 
-fn native_deserializer(class: Option<&str>, field: &str) -> Result<NativeDeserializer> {
-    match class {
-        None | Some("") => Ok(NativeDeserializer::Raw),
-        Some("org.apache.kafka.common.serialization.StringDeserializer") => {
-            Ok(NativeDeserializer::Utf8String)
+/// Consumes every partition of the selected topics up to the high watermarks observed at
+/// startup and writes the records plus topic metadata into `--output-dir`.
+async fn backup(
+    mut config: rdkafka::ClientConfig,
+    timeout: Duration,
+    format: OutputFormat,
+    args: BackupArgs,
+) -> Result<()> {
+    let record_format = args.format;
+    let topic_configs = backup_topic_configs(&config, timeout, &args.topic).await?;
+    configure_consumer(&mut config, &args)?;
+    let consumer: StreamConsumer = config.create()?;
+    let metadata = consumer.fetch_metadata(None, timeout)?;
+    let mut topics = Vec::new();
+    let mut assignment = TopicPartitionList::new();
+    let mut remaining = HashMap::new();
+    for name in &args.topic {
+        let topic = metadata
+            .topics()
+            .iter()
+            .find(|topic| topic.name() == name && topic.error().is_none())
+            .ok_or_else(|| Error::Usage(format!("topic {name} does not exist")))?;
+        for partition in topic.partitions() {
+            let (low, high) = consumer.fetch_watermarks(name, partition.id(), timeout)?;
+            if low < high {
+                assignment.add_partition_offset(name, partition.id(), Offset::Offset(low))?;
+                remaining.insert((name.clone(), partition.id()), high);
+            }
         }
-        Some(class) => Err(Error::Unsupported(format!(
-            "Java {field} deserializer class {class} cannot be loaded by the native client"
-        ))),
+        topics.push(TopicBackup {
+            name: name.clone(),
+            partitions: i32::try_from(topic.partitions().len()).unwrap_or(i32::MAX),
+            replication_factor: topic.partitions().first().map_or(1, |partition| {
+                i32::try_from(partition.replicas().len()).unwrap_or(i32::MAX)
+            }),
+            configs: topic_configs.get(name).cloned().unwrap_or_default(),
+            records: 0,
+            file: TopicBackup::file_name(name, record_format),
+        });
     }
-}
-
-fn deserialize_for_display(
-    bytes: Option<&[u8]>,
-    deserializer: NativeDeserializer,
-    null_literal: &[u8],
-) -> Vec<u8> {
-    let bytes = bytes.unwrap_or(null_literal);
-    match deserializer {
-        NativeDeserializer::Raw => bytes.to_vec(),
-        NativeDeserializer::Utf8String => String::from_utf8_lossy(bytes).into_owned().into_bytes(),
+    drop(metadata);
+    fs::create_dir_all(&args.output_dir)?;
+    let mut writers = topics
+        .iter()
+        .map(|topic| {
+            RecordWriter::create(&args.output_dir.join(&topic.file), record_format)
+                .map(|writer| (topic.name.clone(), (writer, 0_u64)))
+        })
+        .collect::<Result<HashMap<_, _>>>()?;
+    if !remaining.is_empty() {
+        consumer.assign(&assignment)?;
     }
-}
 
-fn is_utf8_encoding(value: &str) -> bool {
-    value
-        .chars()
-        .filter(|character| !matches!(character, '-' | '_'))
-        .collect::<String>()
-        .eq_ignore_ascii_case("UTF8")
-}
+    drain_backup_partitions(
+        &consumer,
+        remaining,
+        &mut writers,
+        Duration::from_millis(args.timeout_ms),
+    )
+    .await?;
 
-fn message_formatter_options(args: &impl FormatterArgs) -> Result<MessageFormatterOptions> {
-    if args.formatter() != "org.apache.kafka.tools.consumer.DefaultMessageFormatter" {
-        return Err(Error::Unsupported(format!(
-            "Java formatter class {} cannot be loaded by the native client",
-            args.formatter()
-        )));
+    for topic in &mut topics {
+        let (writer, records) = writers.remove(&topic.name).ok_or_else(|| {
+            Error::Config(format!("no backup file is open for topic {}", topic.name))
+        })?;
+        writer.finish()?;
+        topic.records = records;
     }
-    let properties = component_properties(args.formatter_config(), args.formatter_properties())?;
-    let value = |key: &str| properties.get(key);
-    let key_deserializer = native_deserializer(
-        value("key.deserializer")
-            .map(String::as_str)
-            .or_else(|| args.key_deserializer()),
-        "key",
-    )?;
-    let value_deserializer = native_deserializer(
-        value("value.deserializer")
-            .map(String::as_str)
-            .or_else(|| args.value_deserializer()),
-        "value",
+    let rows = topics
+        .iter()
+        .map(|topic| BackupRow {
+            topic: topic.name.clone(),
+            partitions: topic.partitions,
+            records: topic.records,
+            file: args.output_dir.join(&topic.file).display().to_string(),
+        })
+        .collect::<Vec<_>>();
+    backup::write_manifest(
+        &args.output_dir,
+        &BackupManifest {
+            format: record_format,
+            topics,
+        },
     )?;
-    let headers_deserializer =
-        native_deserializer(value("headers.deserializer").map(String::as_str), "headers")?;
-    for (key, applies) in [
-        (
-            "deserializer.encoding",
-            key_deserializer == NativeDeserializer::Utf8String
-                || value_deserializer == NativeDeserializer::Utf8String,
-        ),
-        (
-            "key.deserializer.encoding",
-            key_deserializer == NativeDeserializer::Utf8String,
-        ),
-        (
-            "value.deserializer.encoding",
-            value_deserializer == NativeDeserializer::Utf8String,
-        ),
-    ] {
-        if applies && let Some(encoding) = value(key).filter(|encoding| !is_utf8_encoding(encoding))
-        {
-            return Err(Error::Unsupported(format!(
-                "StringDeserializer encoding {encoding} is not supported by the native client"
-            )));
-        }
-    }
-    let flag = |key: &str, default: bool| value(key).map_or(default, |value| bool_property(value));
-    Ok(MessageFormatterOptions {
-        print_timestamp: flag("print.timestamp", false),
-        print_partition: flag("print.partition", false),
-        print_offset: flag("print.offset", false),
-        print_delivery: flag("print.delivery", false),
-        print_epoch: flag("print.epoch", false),
-        print_headers: flag("print.headers", false),
-        print_key: flag("print.key", args.print_key()),
-        print_value: flag("print.value", true),
-        key_separator: value("key.separator")
-            .map_or_else(|| args.key_separator().as_bytes(), String::as_bytes)
-            .to_vec(),
-        line_separator: value("line.separator")
-            .map_or(b"\n".as_slice(), String::as_bytes)
-            .to_vec(),
-        headers_separator: value("headers.separator")
-            .map_or(b",".as_slice(), String::as_bytes)
-            .to_vec(),
-        null_literal: value("null.literal")
-            .map_or(b"null".as_slice(), String::as_bytes)
-            .to_vec(),
-        key_deserializer,
-        value_deserializer,
-        headers_deserializer,
+    output::write_value(format, "backup", &rows, |rows| {
+        output::table(
+            ["TOPIC", "PARTITIONS", "RECORDS", "FILE"],
+            rows.iter().map(|row| {
+                [
+                    row.topic.clone(),
+                    row.partitions.to_string(),
+                    row.records.to_string(),
+                    row.file.clone(),
+                ]
+            }),
+        )
     })
 }
 
-fn write_formatted_message(
-    message: &rdkafka::message::BorrowedMessage<'_>,
-    options: &MessageFormatterOptions,
+/// Consumes assigned partitions until each reaches its recorded high watermark.
+async fn drain_backup_partitions(
+    consumer: &StreamConsumer,
+    mut remaining: HashMap<(String, i32), i64>,
+    writers: &mut HashMap<String, (RecordWriter, u64)>,
+    idle_timeout: Duration,
 ) -> Result<()> {
-    let mut fields = Vec::<Vec<u8>>::new();
-    if options.print_timestamp {
-        fields.push(match message.timestamp() {
-            rdkafka::Timestamp::NotAvailable => b"NO_TIMESTAMP".to_vec(),
-            rdkafka::Timestamp::CreateTime(timestamp) => {
-                format!("CreateTime:{timestamp}").into_bytes()
+    let mut stream = consumer.stream();
+    let mut progress = Instant::now();
+    while !remaining.is_empty() {
+        if let Some(message) = next_consumer_message(&mut stream, Some(1_000)).await? {
+            let message = message?;
+            progress = Instant::now();
+            let key = (message.topic().to_owned(), message.partition());
+            // Records appended after startup belong to the next backup.
+            let Some(high) = remaining.get(&key).copied() else {
+                continue;
+            };
+            if message.offset() < high {
+                let (writer, records) = writers.get_mut(message.topic()).ok_or_else(|| {
+                    Error::Config(format!(
+                        "no backup file is open for topic {}",
+                        message.topic()
+                    ))
+                })?;
+                writer.write(&backup_record(&message))?;
+                *records += 1;
             }
-            rdkafka::Timestamp::LogAppendTime(timestamp) => {
-                format!("LogAppendTime:{timestamp}").into_bytes()
+            if message.offset() + 1 >= high {
+                remaining.remove(&key);
             }
-        });
-    }
-    if options.print_partition {
-        fields.push(format!("Partition:{}", message.partition()).into_bytes());
-    }
-    if options.print_offset {
-        fields.push(format!("Offset:{}", message.offset()).into_bytes());
-    }
-    if options.print_delivery {
-        fields.push(b"Delivery:NOT_PRESENT".to_vec());
-    }
-    if options.print_epoch {
-        fields.push(formatted_leader_epoch(ffi::message_leader_epoch(message)));
-    }
-    if options.print_headers {
-        fields.push(formatted_headers(message.headers(), options));
-    }
-    if options.print_key {
-        fields.push(deserialize_for_display(
-            message.key(),
-            options.key_deserializer,
-            &options.null_literal,
-        ));
-    }
-    if options.print_value {
-        fields.push(deserialize_for_display(
-            message.payload(),
-            options.value_deserializer,
-            &options.null_literal,
-        ));
-    }
-    let mut stdout = io::stdout().lock();
-    for (index, field) in fields.iter().enumerate() {
-        if index > 0 {
-            stdout.write_all(&options.key_separator)?;
+            continue;
+        }
+        retire_consumed_partitions(consumer, &mut remaining)?;
+        if !remaining.is_empty() && progress.elapsed() >= idle_timeout {
+            return Err(Error::Config(format!(
+                "backup made no progress for {} ms with {} partitions incomplete",
+                idle_timeout.as_millis(),
+                remaining.len()
+            )));
         }
-        stdout.write_all(field)?;
-    }
-    if options.print_value {
-        stdout.write_all(&options.line_separator)?;
     }
     Ok(())
 }
 
-fn write_formatted_share_message(
-    record: &krafka::consumer::ConsumerRecord,
-    options: &MessageFormatterOptions,
-) -> Result<()> {
-    let mut fields = Vec::<Vec<u8>>::new();
-    if options.print_timestamp {
-        let kind = if record.timestamp_type == 1 {
-            "LogAppendTime"
-        } else {
-            "CreateTime"
-        };
-        fields.push(format!("{kind}:{}", record.timestamp).into_bytes());
-    }
-    if options.print_partition {
-        fields.push(format!("Partition:{}", record.partition).into_bytes());
+fn backup_record(message: &rdkafka::message::BorrowedMessage<'_>) -> BackupRecord {
+    BackupRecord {
+        partition: message.partition(),
+        offset: message.offset(),
+        timestamp: message.timestamp().to_millis(),
+        key: message.key().map(<[u8]>::to_vec),
+        value: message.payload().map(<[u8]>::to_vec),
+        headers: message
+            .headers()
+            .map(|headers| {
+                headers
+                    .iter()
+                    .map(|header| (header.key.to_owned(), header.value.map(<[u8]>::to_vec)))
+                    .collect()
+            })
+            .unwrap_or_default(),
     }
-    if options.print_offset {
-        fields.push(format!("Offset:{}", record.offset).into_bytes());
-    }
-    if options.print_delivery {
-        fields.push(record.delivery_count.map_or_else(
-            || b"Delivery:NOT_PRESENT".to_vec(),
-            |count| format!("Delivery:{count}").into_bytes(),
-        ));
-    }
-    if options.print_epoch {
-        fields.push(formatted_leader_epoch(record.leader_epoch));
-    }
-    if options.print_headers {
-        fields.push(formatted_share_headers(&record.headers, options));
-    }
-    if options.print_key {
-        fields.push(deserialize_for_display(
-            record.key.as_deref(),
-            options.key_deserializer,
-            &options.null_literal,
-        ));
-    }
-    if options.print_value {
-        fields.push(deserialize_for_display(
-            record.value.as_deref(),
-            options.value_deserializer,
-            &options.null_literal,
-        ));
-    }
-    let mut stdout = io::stdout().lock();
-    for (index, field) in fields.iter().enumerate() {
-        if index > 0 {
-            stdout.write_all(&options.key_separator)?;
-        }
-        stdout.write_all(field)?;
-    }
-    if options.print_value {
-        stdout.write_all(&options.line_separator)?;
+}
+
+/// Returns the dynamic config overrides of each topic.
+async fn backup_topic_configs(
+    config: &rdkafka::ClientConfig,
+    timeout: Duration,
+    topics: &[String],
+) -> Result<HashMap<String, BTreeMap<String, String>>> {
+    let resources = topics
+        .iter()
+        .map(|topic| ResourceSpecifier::Topic(topic))
+        .collect::<Vec<_>>();
+    let mut configs = HashMap::new();
+    for resource in admin(config)?
+        .describe_configs(
+            &resources,
+            &AdminOptions::new().request_timeout(Some(timeout)),
+        )
+        .await?
+    {
+        let resource = resource.map_err(|code| Error::Config(code.to_string()))?;
+        let OwnedResourceSpecifier::Topic(topic) = resource.specifier else {
+            continue;
+        };
+        let entries = resource
+            .entries
+            .into_iter()
+            .filter(|entry| entry.source == ConfigSource::DynamicTopic && !entry.is_sensitive)
+            .filter_map(|entry| entry.value.map(|value| (entry.name, value)))
+            .collect();
+        configs.insert(topic, entries);
     }
-    Ok(())
+    Ok(configs)
 }
 
-fn formatted_leader_epoch(epoch: Option<i32>) -> Vec<u8> {
-    epoch.map_or_else(
-        || b"Epoch:NOT_PRESENT".to_vec(),
-        |epoch| format!("Epoch:{epoch}").into_bytes(),
-    )
+#[derive(Debug, Serialize)]
+struct RestoreRow {
+    topic: String,
+    created: bool,
+    records: u64,
 }
 
-fn formatted_headers(
-    headers: Option<&rdkafka::message::BorrowedHeaders>,
-    options: &MessageFormatterOptions,
-) -> Vec<u8> {
-    let Some(headers) = headers.filter(|headers| headers.count() > 0) else {
-        return b"NO_HEADERS".to_vec();
+/// Replays a backup directory, producing every record to its original partition.
+async fn restore(
+    mut config: rdkafka::ClientConfig,
+    timeout: Duration,
+    format: OutputFormat,
+    args: RestoreArgs,
+) -> Result<()> {
+    let manifest = backup::read_manifest(&args.input_dir)?;
+    if let Some(missing) = args
+        .topic
+        .iter()
+        .find(|topic| !manifest.topics.iter().any(|backup| &backup.name == *topic))
+    {
+        return Err(Error::Usage(format!(
+            "topic {missing} is not in the backup"
+        )));
+    }
+    let topics = manifest
+        .topics
+        .iter()
+        .filter(|topic| args.topic.is_empty() || args.topic.contains(&topic.name))
+        .collect::<Vec<_>>();
+    let created = if args.create_topics {
+        create_restored_topics(&config, timeout, &topics, args.replication_factor).await?
+    } else {
+        HashSet::new()
     };
-    let mut result = Vec::new();
-    for (index, header) in headers.iter().enumerate() {
-        if index > 0 {
-            result.extend_from_slice(&options.headers_separator);
+    let metadata = base_consumer(&config)?.fetch_metadata(None, timeout)?;
+    for topic in &topics {
+        let partitions = metadata
+            .topics()
+            .iter()
+            .find(|candidate| candidate.name() == topic.name && candidate.error().is_none())
+            .map(|candidate| candidate.partitions().len())
+            .ok_or_else(|| {
+                Error::Usage(format!(
+                    "topic {} does not exist; pass --create-topics to create it",
+                    topic.name
+                ))
+            })?;
+        if i32::try_from(partitions).unwrap_or(i32::MAX) < topic.partitions {
+            return Err(Error::Usage(format!(
+                "topic {} has {partitions} partitions but the backup needs {}",
+                topic.name, topic.partitions
+            )));
         }
-        result.extend_from_slice(header.key.as_bytes());
-        result.push(b':');
-        result.extend_from_slice(&deserialize_for_display(
-            header.value,
-            options.headers_deserializer,
-            &options.null_literal,
-        ));
     }
-    result
+    drop(metadata);
+
+    apply_client_properties(&mut config, &args.properties)?;
+    let max_block = Duration::from_millis(configure_producer(&mut config, &args)?);
+    let producer: FutureProducer = config.create()?;
+    let mut rows = Vec::new();
+    for topic in topics {
+        let mut deliveries = Vec::new();
+        for record in RecordReader::open(&args.input_dir.join(&topic.file), manifest.format)? {
+            let record = record?;
+            let mut future = FutureRecord::to(&topic.name).partition(record.partition);
+            if let Some(key) = &record.key {
+                future = future.key(key);
+            }
+            if let Some(value) = &record.value {
+                future = future.payload(value);
+            }
+            if let Some(timestamp) = record.timestamp.filter(|_| args.preserve_timestamps) {
+                future = future.timestamp(timestamp);
+            }
+            if !record.headers.is_empty() {
+                let mut headers = OwnedHeaders::new_with_capacity(record.headers.len());
+                for (key, value) in &record.headers {
+                    headers = headers.insert(Header {
+                        key,
+                        value: value.as_deref(),
+                    });
+                }
+                future = future.headers(headers);
+            }
+            deliveries.push(enqueue_with_timeout(&producer, future, max_block).await?);
+        }
+        let records = deliveries.len() as u64;
+        for delivery in deliveries {
+            delivery
+                .await
+                .map_err(|_| Error::Config("producer delivery channel was canceled".into()))?
+                .map_err(|(error, _)| Error::Kafka(error))?;
+        }
+        rows.push(RestoreRow {
+            topic: topic.name.clone(),
+            created: created.contains(&topic.name),
+            records,
+        });
+    }
+    output::write_value(format, "restore", &rows, |rows| {
+        output::table(
+            ["TOPIC", "CREATED", "RECORDS"],
+            rows.iter().map(|row| {
+                [
+                    row.topic.clone(),
+                    row.created.to_string(),
+                    row.records.to_string(),
+                ]
+            }),
+        )
+    })
 }
 
-fn formatted_share_headers(
-    headers: &[(bytes::Bytes, Option<bytes::Bytes>)],
-    options: &MessageFormatterOptions,
-) -> Vec<u8> {
-    if headers.is_empty() {
-        return b"NO_HEADERS".to_vec();
+/// Creates backed-up topics missing from the target cluster and returns their names.
+async fn create_restored_topics(
+    config: &rdkafka::ClientConfig,
+    timeout: Duration,
+    topics: &[&TopicBackup],
+    replication_factor: Option<i32>,
+) -> Result<HashSet<String>> {
+    let metadata = base_consumer(config)?.fetch_metadata(None, timeout)?;
+    let missing = topics
+        .iter()
+        .filter(|topic| {
+            !metadata
+                .topics()
+                .iter()
+                .any(|candidate| candidate.name() == topic.name && candidate.error().is_none())
+        })
+        .collect::<Vec<_>>();
+    drop(metadata);
+    if missing.is_empty() {
+        return Ok(HashSet::new());
     }
-    let mut result = Vec::new();
-    for (index, (key, value)) in headers.iter().enumerate() {
-        if index > 0 {
-            result.extend_from_slice(&options.headers_separator);
+    let new_topics = missing
+        .iter()
+        .map(|topic| {
+            topic.configs.iter().fold(
+                NewTopic::new(
+                    &topic.name,
+                    topic.partitions,
+                    TopicReplication::Fixed(replication_factor.unwrap_or(topic.replication_factor)),
+                ),
+                |new_topic, (key, value)| new_topic.set(key, value),
+            )
+        })
+        .collect::<Vec<_>>();
+    let mut created = HashSet::new();
+    for result in admin(config)?
+        .create_topics(
+            &new_topics,
+            &AdminOptions::new().operation_timeout(Some(timeout)),
+        )
+        .await?
+    {
+        match result {
+            Ok(name) => {
+                created.insert(name);
+            }
+            Err((_, RDKafkaErrorCode::TopicAlreadyExists)) => {}
+            Err((name, code)) => {
+                return Err(Error::Config(format!(
+                    "failed to create topic {name}: {code}"
+                )));
+            }
         }
-        result.extend_from_slice(key);
-        result.push(b':');
-        result.extend_from_slice(&deserialize_for_display(
-            value.as_deref(),
-            options.headers_deserializer,
-            &options.null_literal,
-        ));
     }
-    result
+    Ok(created)
 }
 
-async fn next_consumer_message<'a>(
-    stream: &mut (
-             impl futures::Stream<
-        Item = std::result::Result<
-            rdkafka::message::BorrowedMessage<'a>,
-            rdkafka::error::KafkaError,
-        >,
-    > + Unpin
-         ),
-    timeout_ms: Option<u64>,
-) -> Result<
-    Option<std::result::Result<rdkafka::message::BorrowedMessage<'a>, rdkafka::error::KafkaError>>,
-> {
-    if let Some(timeout_ms) = timeout_ms.filter(|value| *value != u64::MAX) {
-        tokio::time::timeout(Duration::from_millis(timeout_ms), stream.next())
-            .await
-            .map_or_else(|_| Ok(None), Ok)
-    } else {
-        Ok(stream.next().await)
-    }
+#[derive(Debug, Serialize)]
+struct MirrorRow {
+    source: String,
+    target: String,
+    records: u64,
 }
 
-fn collect_headers(headers: Option<&BorrowedHeaders>) -> BTreeMap<String, Option<String>> {
-    let mut result = BTreeMap::new();
-    if let Some(headers) = headers {
-        for header in headers.iter() {
-            result.insert(
-                header.key.to_owned(),
-                header
-                    .value
-                    .map(|value| String::from_utf8_lossy(value).into_owned()),
-            );
-        }
-    }
-    result
+/// Topic, partition, and header rewrites applied to mirrored records.
+struct MirrorRewrite {
+    topics: HashMap<String, String>,
+    partitions: HashMap<i32, i32>,
+    repartition: bool,
+    set_headers: Vec<(String, String)>,
+    rename_headers: HashMap<String, String>,
+    drop_headers: HashSet<String>,
 }
 
-fn apply_client_properties(
-    config: &mut rdkafka::ClientConfig,
-    properties: &[String],
-) -> Result<()> {
-    for (key, value) in parse_pairs(properties)? {
-        config.set(key, value);
+impl MirrorRewrite {
+    fn new(args: &MirrorArgs) -> Result<Self> {
+        let partitions = parse_pairs(&args.partition_map)?
+            .into_iter()
+            .map(
+                |(source, target)| match (source.parse::<i32>(), target.parse::<i32>()) {
+                    (Ok(source), Ok(target)) if source >= 0 && target >= 0 => Ok((source, target)),
+                    _ => Err(Error::Usage(format!(
+                        "--partition-map expects partition numbers, got {source}={target}"
+                    ))),
+                },
+            )
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            topics: parse_pairs(&args.rename_topic)?.into_iter().collect(),
+            partitions,
+            repartition: args.repartition,
+            set_headers: parse_pairs(&args.set_header)?,
+            rename_headers: parse_pairs(&args.rename_header)?.into_iter().collect(),
+            drop_headers: args.drop_header.iter().cloned().collect(),
+        })
+    }
+
+    fn topic<'a>(&'a self, source: &'a str) -> &'a str {
+        self.topics.get(source).map_or(source, String::as_str)
+    }
+
+    fn partition(&self, source: i32) -> Option<i32> {
+        (!self.repartition).then(|| self.partitions.get(&source).copied().unwrap_or(source))
+    }
+
+    /// Drops, then renames, then sets headers.
+    fn headers<'a>(
+        &'a self,
+        headers: impl Iterator<Item = (&'a str, Option<&'a [u8]>)>,
+    ) -> Vec<(&'a str, Option<&'a [u8]>)> {
+        let mut headers = headers
+            .filter(|(key, _)| !self.drop_headers.contains(*key))
+            .map(|(key, value)| {
+                (
+                    self.rename_headers.get(key).map_or(key, String::as_str),
+                    value,
+                )
+            })
+            .filter(|(key, _)| !self.set_headers.iter().any(|(set, _)| set == key))
+            .collect::<Vec<_>>();
+        headers.extend(
+            self.set_headers
+                .iter()
+                .map(|(key, value)| (key.as_str(), Some(value.as_bytes()))),
+        );
+        headers
     }
-    Ok(())
 }
 
-async fn groups(
-    config: &rdkafka::ClientConfig,
-    bootstrap: &str,
-    command_config: Option<&Path>,
+/// Copies the selected source partitions into the target cluster.
+async fn mirror(
+    mut source: rdkafka::ClientConfig,
     timeout: Duration,
     format: OutputFormat,
-    action: GroupAction,
-    verbose: bool,
+    args: &MirrorArgs,
 ) -> Result<()> {
-    match action {
-        GroupAction::ValidateRegex { .. } => Err(Error::Usage(
-            "validate-regex must be handled before client configuration".into(),
-        )),
-        GroupAction::List { state, group_type } => {
-            list_groups(
-                config,
-                bootstrap,
-                command_config,
-                timeout,
-                format,
-                state.as_deref(),
-                group_type.as_deref(),
-            )
-            .await
-        }
-        GroupAction::Describe {
-            group,
-            all_groups,
-            members,
-            state,
-            offsets: _,
-        } => {
-            let mode = if members {
-                GroupDescribeMode::Members
-            } else if state {
-                GroupDescribeMode::State
-            } else {
-                GroupDescribeMode::Offsets
-            };
-            let groups = resolve_group_names(config, timeout, &group, all_groups)?;
-            let protocol = GroupProtocolContext {
-                bootstrap,
-                command_config,
-            };
-            describe_group_details(config, protocol, timeout, format, &groups, mode, verbose).await
-        }
-        GroupAction::Delete {
-            group,
-            all_groups,
-            execute,
-        } => {
-            let groups = resolve_group_names(config, timeout, &group, all_groups)?;
-            if groups.is_empty() {
-                return Err(Error::Usage("no consumer groups matched".into()));
-            }
-            if !execute {
-                let rows = groups
-                    .into_iter()
-                    .map(|group| GroupDeleteRow {
-                        group,
-                        status: "PREVIEW".into(),
-                        error: None,
-                    })
-                    .collect::<Vec<_>>();
-                return write_group_delete_rows(format, &rows);
-            }
-            let names = groups.iter().map(String::as_str).collect::<Vec<_>>();
-            let results = admin(config)?
-                .delete_groups(&names, &AdminOptions::new())
-                .await?;
-            let failures = results.iter().filter(|result| result.is_err()).count();
-            let rows = results
-                .into_iter()
-                .map(|result| match result {
-                    Ok(group) => GroupDeleteRow {
-                        group,
-                        status: "DELETED".into(),
-                        error: None,
-                    },
-                    Err((group, error)) => GroupDeleteRow {
-                        group,
-                        status: "FAILED".into(),
-                        error: Some(error.to_string()),
-                    },
-                })
-                .collect::<Vec<_>>();
-            write_group_delete_rows(format, &rows)?;
-            if failures == 0 {
-                Ok(())
-            } else {
-                Err(Error::Partial {
-                    failed: failures,
-                    total: names.len(),
-                })
-            }
-        }
-        GroupAction::ResetOffsets(args) => reset_offsets(config, timeout, format, &args),
-        GroupAction::DeleteOffsets {
-            group,
-            topic,
-            execute,
-        } => delete_group_offsets_command(config, timeout, format, &group, &topic, execute),
-    }
-}
-
-#[derive(Debug, Clone, Serialize)]
-struct AllGroupRow {
-    group: String,
-    group_type: String,
-    protocol: String,
-}
+    let rewrite = MirrorRewrite::new(args)?;
+    let window = ScanWindow::new(
+        args.from_offset,
+        args.to_offset,
+        args.from_time.as_deref(),
+        args.to_time.as_deref(),
+    )?;
+    configure_consumer(&mut source, args)?;
+    let consumer: StreamConsumer = source.create()?;
+    let remaining = assign_ranges(&consumer, &window.resolve(&consumer, &args.topic, timeout)?)?;
 
-async fn list_all_groups(
-    bootstrap: &str,
-    command_config: Option<&Path>,
-    timeout: Duration,
-    format: OutputFormat,
-    action: AllGroupsAction,
-) -> Result<()> {
-    let AllGroupsAction::List {
-        group_type,
-        protocol,
-        consumer,
-        share,
-        streams,
-    } = action;
-    let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
-    let groups = client.list_consumer_groups().await?;
-    drop(client);
-    let mut rows = groups
-        .into_iter()
-        .map(|group| AllGroupRow {
-            group: group.group_id,
-            group_type: group
-                .group_type
-                .as_ref()
-                .map_or_else(String::new, |kind| group_type_label(&kind.to_string())),
-            protocol: group.protocol_type,
-        })
-        .filter(|row| {
-            all_group_matches(
-                row,
-                group_type,
-                protocol.as_deref(),
-                consumer,
-                share,
-                streams,
-            )
+    let mut target = config::client_config(
+        &args.target_bootstrap_server,
+        args.target_command_config.as_deref(),
+    )?;
+    apply_client_properties(&mut target, &args.producer_properties)?;
+    let max_block = Duration::from_millis(configure_producer(&mut target, args)?);
+    let producer: FutureProducer = target.create()?;
+    let copied =
+        copy_mirror_records(&consumer, &producer, remaining, &rewrite, args, max_block).await?;
+
+    let rows = args
+        .topic
+        .iter()
+        .map(|topic| MirrorRow {
+            source: topic.clone(),
+            target: rewrite.topic(topic).to_owned(),
+            records: copied.get(topic).copied().unwrap_or_default(),
         })
         .collect::<Vec<_>>();
-    rows.sort_by(|left, right| left.group.cmp(&right.group));
-    output::write_value(format, "all-groups.list", &rows, |rows| {
+    output::write_value(format, "mirror", &rows, |rows| {
         output::table(
-            ["GROUP", "TYPE", "PROTOCOL"],
+            ["SOURCE", "TARGET", "RECORDS"],
             rows.iter().map(|row| {
                 [
-                    row.group.clone(),
-                    row.group_type.clone(),
-                    row.protocol.clone(),
+                    row.source.clone(),
+                    row.target.clone(),
+                    row.records.to_string(),
                 ]
             }),
         )
     })
 }
 
-fn all_group_matches(
-    row: &AllGroupRow,
-    group_type: Option<AllGroupType>,
-    protocol: Option<&str>,
-    consumer: bool,
-    share: bool,
-    streams: bool,
-) -> bool {
-    let type_matches = group_type.is_none_or(|requested| {
-        row.group_type.eq_ignore_ascii_case(match requested {
-            AllGroupType::Classic => "classic",
-            AllGroupType::Consumer => "consumer",
-            AllGroupType::Share => "share",
-            AllGroupType::Streams => "streams",
+/// Offset and timestamp bounds applied to every partition a command scans.
+struct ScanWindow {
+    from_offset: Option<i64>,
+    to_offset: Option<i64>,
+    from_time: Option<i64>,
+    to_time: Option<i64>,
+}
+
+/// The `[start, end)` offsets of one partition inside a [`ScanWindow`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct PartitionRange {
+    topic: String,
+    partition: i32,
+    start: i64,
+    end: i64,
+}
+
+impl ScanWindow {
+    fn new(
+        from_offset: Option<i64>,
+        to_offset: Option<i64>,
+        from_time: Option<&str>,
+        to_time: Option<&str>,
+    ) -> Result<Self> {
+        Ok(Self {
+            from_offset,
+            to_offset,
+            from_time: from_time
+                .map(|value| parse_time_bound("--from-time", value))
+                .transpose()?,
+            to_time: to_time
+                .map(|value| parse_time_bound("--to-time", value))
+                .transpose()?,
         })
-    });
-    let protocol_matches = protocol.is_none_or(|requested| row.protocol == requested);
-    if group_type.is_some() || protocol.is_some() {
-        return type_matches && protocol_matches;
     }
-    if consumer {
-        return row.protocol == "consumer"
-            || row.protocol.is_empty()
-            || row.group_type.eq_ignore_ascii_case("consumer");
+
+    /// Resolves the window against current watermarks and offsets-for-times, dropping
+    /// partitions without records inside it.
+    fn resolve(
+        &self,
+        consumer: &impl Consumer,
+        topics: &[String],
+        timeout: Duration,
+    ) -> Result<Vec<PartitionRange>> {
+        let metadata = consumer.fetch_metadata(None, timeout)?;
+        let mut partitions = Vec::new();
+        for name in topics {
+            let topic = metadata
+                .topics()
+                .iter()
+                .find(|topic| topic.name() == name && topic.error().is_none())
+                .ok_or_else(|| Error::Usage(format!("topic {name} does not exist")))?;
+            partitions.extend(
+                topic
+                    .partitions()
+                    .iter()
+                    .map(|partition| (name.clone(), partition.id())),
+            );
+        }
+        drop(metadata);
+        let starts = self
+            .from_time
+            .map(|time| offsets_for_time(consumer, &partitions, time, timeout))
+            .transpose()?;
+        let stops = self
+            .to_time
+            .map(|time| offsets_for_time(consumer, &partitions, time.saturating_add(1), timeout))
+            .transpose()?;
+
+        let mut ranges = Vec::new();
+        for (index, (topic, partition)) in partitions.into_iter().enumerate() {
+            let (low, high) = consumer.fetch_watermarks(&topic, partition, timeout)?;
+            // Offsets-for-times answers nothing when no record is new enough.
+            let start = starts
+                .as_ref()
+                .map_or_else(
+                    || self.from_offset.unwrap_or(low),
+                    |starts| starts[index].unwrap_or(high),
+                )
+                .max(low);
+            let end = [
+                Some(high),
+                self.to_offset,
+                stops.as_ref().and_then(|stops| stops[index]),
+            ]
+            .into_iter()
+            .flatten()
+            .min()
+            .unwrap_or(high);
+            if start < end {
+                ranges.push(PartitionRange {
+                    topic,
+                    partition,
+                    start,
+                    end,
+                });
+            }
+        }
+        Ok(ranges)
     }
-    if share {
-        return row.group_type.eq_ignore_ascii_case("share");
+}
+
+/// Resolves the first offset at or after `timestamp` in each partition, in input order.
+fn offsets_for_time(
+    consumer: &impl Consumer,
+    partitions: &[(String, i32)],
+    timestamp: i64,
+    timeout: Duration,
+) -> Result<Vec<Option<i64>>> {
+    let mut request = TopicPartitionList::with_capacity(partitions.len());
+    for (topic, partition) in partitions {
+        request.add_partition_offset(topic, *partition, Offset::Offset(timestamp))?;
     }
-    if streams {
-        return row.group_type.eq_ignore_ascii_case("streams");
+    let resolved = consumer.offsets_for_times(request, timeout)?;
+    Ok(partitions
+        .iter()
+        .map(|(topic, partition)| {
+            resolved
+                .find_partition(topic, *partition)
+                .and_then(|element| element.offset().to_raw())
+                .filter(|offset| *offset >= 0)
+        })
+        .collect())
+}
+
+/// Assigns every range at its start offset and returns the exclusive end of each.
+fn assign_ranges(
+    consumer: &impl Consumer,
+    ranges: &[PartitionRange],
+) -> Result<HashMap<(String, i32), i64>> {
+    let mut assignment = TopicPartitionList::with_capacity(ranges.len());
+    for range in ranges {
+        assignment.add_partition_offset(
+            &range.topic,
+            range.partition,
+            Offset::Offset(range.start),
+        )?;
     }
-    true
+    if !ranges.is_empty() {
+        consumer.assign(&assignment)?;
+    }
+    Ok(ranges
+        .iter()
+        .map(|range| ((range.topic.clone(), range.partition), range.end))
+        .collect())
 }
 
-fn group_type_label(group_type: &str) -> String {
-    match group_type.to_ascii_lowercase().as_str() {
-        "classic" => "Classic".into(),
-        "consumer" => "Consumer".into(),
-        "share" => "Share".into(),
-        "streams" => "Streams".into(),
-        _ => group_type.to_owned(),
+/// Drops partitions whose consumer position reached their end offset.
+///
+/// Transaction markers occupy the last offsets of some partitions without ever being
+/// delivered, so completion cannot be judged by delivered records alone.
+fn retire_consumed_partitions(
+    consumer: &impl Consumer,
+    remaining: &mut HashMap<(String, i32), i64>,
+) -> Result<()> {
+    for element in consumer.position()?.elements() {
+        if let Offset::Offset(position) = element.offset() {
+            let key = (element.topic().to_owned(), element.partition());
+            if remaining.get(&key).is_some_and(|end| position >= *end) {
+                remaining.remove(&key);
+            }
+        }
     }
+    Ok(())
 }
 
-#[derive(Debug)]
-struct ShareGroupDescriptionWithCoordinator {
-    coordinator_id: i32,
-    coordinator: String,
-    description: krafka::protocol::ShareGroupDescription,
+/// Produces every in-range source record to the target and returns per-topic counts.
+async fn copy_mirror_records(
+    consumer: &StreamConsumer,
+    producer: &FutureProducer,
+    mut remaining: HashMap<(String, i32), i64>,
+    rewrite: &MirrorRewrite,
+    args: &MirrorArgs,
+    max_block: Duration,
+) -> Result<HashMap<String, u64>> {
+    let mut stream = consumer.stream();
+    let mut copied = HashMap::new();
+    let mut deliveries = Vec::new();
+    let mut sent = 0_u64;
+    let started = Instant::now();
+    let mut progress = Instant::now();
+    while !remaining.is_empty() {
+        let Some(message) = next_consumer_message(&mut stream, Some(1_000)).await? else {
+            retire_consumed_partitions(consumer, &mut remaining)?;
+            if !remaining.is_empty() && progress.elapsed() >= Duration::from_millis(args.timeout_ms)
+            {
+                return Err(Error::Config(format!(
+                    "mirror made no progress for {} ms with {} partitions incomplete",
+                    args.timeout_ms,
+                    remaining.len()
+                )));
+            }
+            continue;
+        };
+        let message = message?;
+        progress = Instant::now();
+        let key = (message.topic().to_owned(), message.partition());
+        let Some(end) = remaining.get(&key).copied() else {
+            continue;
+        };
+        if message.offset() + 1 >= end {
+            remaining.remove(&key);
+        }
+        if message.offset() >= end {
+            continue;
+        }
+
+        let headers = rewrite.headers(
+            message
+                .headers()
+                .into_iter()
+                .flat_map(|headers| headers.iter().map(|header| (header.key, header.value))),
+        );
+        let mut record = FutureRecord::to(rewrite.topic(message.topic()));
+        if let Some(partition) = rewrite.partition(message.partition()) {
+            record = record.partition(partition);
+        }
+        if let Some(key) = message.key() {
+            record = record.key(key);
+        }
+        if let Some(value) = message.payload() {
+            record = record.payload(value);
+        }
+        if let Some(timestamp) = message.timestamp().to_millis() {
+            record = record.timestamp(timestamp);
+        }
+        if !headers.is_empty() {
+            let mut owned = OwnedHeaders::new_with_capacity(headers.len());
+            for (key, value) in headers {
+                owned = owned.insert(Header { key, value });
+            }
+            record = record.headers(owned);
+        }
+        deliveries.push(enqueue_with_timeout(producer, record, max_block).await?);
+        *copied.entry(message.topic().to_owned()).or_insert(0_u64) += 1;
+        sent += 1;
+        throttle_records(args.throughput, sent, started).await;
+    }
+    for delivery in deliveries {
+        delivery
+            .await
+            .map_err(|_| Error::Config("producer delivery channel was canceled".into()))?
+            .map_err(|(error, _)| Error::Kafka(error))?;
+    }
+    Ok(copied)
 }
 
 #[derive(Debug, Serialize)]
-struct ShareGroupListRow {
-    group: String,
-    state: Option<String>,
+struct SearchHit {
+    topic: String,
+    partition: i32,
+    offset: i64,
+    timestamp: Option<i64>,
+    key: Option<String>,
 }
 
-#[derive(Debug)]
-struct StreamsGroupDescriptionWithCoordinator {
-    coordinator_id: i32,
-    coordinator: String,
-    description: StreamsGroupDescription,
+/// Key, value, and header patterns a `search` hit must all match.
+struct SearchPattern {
+    key: Option<Regex>,
+    value: Option<Regex>,
+    headers: Vec<(String, Regex)>,
 }
 
-#[derive(Debug)]
-struct StreamsGroupDescription {
-    group_id: String,
-    group_state: String,
-    group_epoch: i32,
-    assignment_epoch: i32,
-    topology: Option<StreamsTopology>,
-    members: Vec<StreamsGroupMember>,
-    topology_description: Option<StreamsTopologyDescription>,
-    topology_description_status: Option<i8>,
-    assignor: Option<String>,
-}
-
-#[derive(Debug)]
-struct StreamsTopology {
-    subtopologies: Vec<StreamsSubtopology>,
-}
-
-#[derive(Debug)]
-struct StreamsSubtopology {
-    id: String,
-    source_topics: Vec<String>,
-    repartition_source_topics: Vec<String>,
-    state_changelog_topics: Vec<String>,
-}
-
-#[derive(Debug)]
-struct StreamsGroupMember {
-    member_id: String,
-    member_epoch: i32,
-    client_id: String,
-    topology_epoch: i32,
-    process_id: String,
-    assignment: StreamsTaskAssignment,
-    target_assignment: StreamsTaskAssignment,
-    is_classic: bool,
-}
+impl SearchPattern {
+    fn new(args: &SearchArgs) -> Result<Self> {
+        let regex = |flag: &str, pattern: &str| {
+            Regex::new(pattern)
+                .map_err(|error| Error::Usage(format!("invalid {flag} pattern: {error}")))
+        };
+        if args.key.is_none() && args.value.is_none() && args.header.is_empty() {
+            return Err(Error::Usage(
+                "search requires --key, --value, or --header".into(),
+            ));
+        }
+        Ok(Self {
+            key: args
+                .key
+                .as_deref()
+                .map(|key| regex("--key", key))
+                .transpose()?,
+            value: args
+                .value
+                .as_deref()
+                .map(|value| regex("--value", value))
+                .transpose()?,
+            headers: parse_pairs(&args.header)?
+                .into_iter()
+                .map(|(name, pattern)| Ok((name, regex("--header", &pattern)?)))
+                .collect::<Result<_>>()?,
+        })
+    }
 
-#[derive(Debug, Default)]
-struct StreamsTaskAssignment {
-    active: Vec<StreamsTaskIds>,
-    standby: Vec<StreamsTaskIds>,
-    warmup: Vec<StreamsTaskIds>,
+    fn matches(&self, message: &impl Message) -> bool {
+        let text_matches = |pattern: Option<&Regex>, bytes: Option<&[u8]>| {
+            pattern.is_none_or(|pattern| {
+                bytes.is_some_and(|bytes| pattern.is_match(&String::from_utf8_lossy(bytes)))
+            })
+        };
+        text_matches(self.key.as_ref(), message.key())
+            && text_matches(self.value.as_ref(), message.payload())
+            && self.headers.iter().all(|(name, pattern)| {
+                message.headers().is_some_and(|headers| {
+                    headers.iter().any(|header| {
+                        header.key == name && text_matches(Some(pattern), header.value)
+                    })
+                })
+            })
+    }
 }
 
-#[derive(Debug)]
-struct StreamsTaskIds {
-    subtopology_id: String,
-    partitions: Vec<i32>,
-}
+/// Scans the selected topics with `--parallelism` consumers and reports matching records.
+async fn search(
+    mut config: rdkafka::ClientConfig,
+    timeout: Duration,
+    format: OutputFormat,
+    args: &SearchArgs,
+) -> Result<()> {
+    let pattern = SearchPattern::new(args)?;
+    let window = ScanWindow::new(
+        args.from_offset,
+        args.to_offset,
+        args.from_time.as_deref(),
+        args.to_time.as_deref(),
+    )?;
+    configure_consumer(&mut config, args)?;
+    let metadata_consumer = base_consumer(&config)?;
+    let topics = match args.include.as_deref() {
+        Some(include) => {
+            let include = Regex::new(&format!("^(?:{include})$"))
+                .map_err(|error| Error::Usage(format!("invalid --include pattern: {error}")))?;
+            let mut topics = metadata_consumer
+                .fetch_metadata(None, timeout)?
+                .topics()
+                .iter()
+                .map(|topic| topic.name().to_owned())
+                .filter(|topic| include.is_match(topic))
+                .collect::<Vec<_>>();
+            topics.sort();
+            topics
+        }
+        None => args.topic.clone(),
+    };
+    let ranges = window.resolve(&metadata_consumer, &topics, timeout)?;
+    drop(metadata_consumer);
 
-#[derive(Debug)]
-struct StreamsTopologyDescription {
-    subtopologies: Vec<StreamsTopologyDescriptionSubtopology>,
-    global_stores: Vec<StreamsTopologyGlobalStore>,
+    let workers = usize::from(args.parallelism).min(ranges.len().max(1));
+    let mut shares = vec![Vec::new(); workers];
+    for (index, range) in ranges.into_iter().enumerate() {
+        shares[index % workers].push(range);
+    }
+    let found = AtomicU64::new(0);
+    let scans = shares
+        .iter()
+        .map(|share| search_partitions(&config, share, &pattern, &found, args))
+        .collect::<Vec<_>>();
+    let mut hits = futures::future::try_join_all(scans)
+        .await?
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+    hits.sort_by(|left, right| {
+        (&left.topic, left.partition, left.offset).cmp(&(
+            &right.topic,
+            right.partition,
+            right.offset,
+        ))
+    });
+    if let Some(max_hits) = args.max_hits {
+        hits.truncate(max_hits);
+    }
+    output::write_value(format, "search", &hits, |hits| {
+        output::table(
+            ["TOPIC", "PARTITION", "OFFSET", "TIMESTAMP", "KEY"],
+            hits.iter().map(|hit| {
+                [
+                    hit.topic.clone(),
+                    hit.partition.to_string(),
+                    hit.offset.to_string(),
+                    hit.timestamp
+                        .map_or_else(|| "-".into(), |timestamp| timestamp.to_string()),
+                    hit.key.clone().unwrap_or_else(|| "-".into()),
+                ]
+            }),
+        )
+    })
 }
 
-#[derive(Debug)]
-struct StreamsTopologyDescriptionSubtopology {
-    id: String,
-    nodes: Vec<StreamsTopologyNode>,
+/// Scans one worker's share of partition ranges, stopping early once `found` reaches
+/// `--max-hits` across all workers.
+async fn search_partitions(
+    config: &rdkafka::ClientConfig,
+    ranges: &[PartitionRange],
+    pattern: &SearchPattern,
+    found: &AtomicU64,
+    args: &SearchArgs,
+) -> Result<Vec<SearchHit>> {
+    let limit = args.max_hits.map_or(u64::MAX, |max_hits| {
+        u64::try_from(max_hits).unwrap_or(u64::MAX)
+    });
+    let consumer: StreamConsumer = config.create()?;
+    let mut hits = Vec::new();
+    scan_ranges(&consumer, ranges, "search", args.timeout_ms, |message| {
+        if found.load(Ordering::Relaxed) >= limit {
+            return Ok(false);
+        }
+        if pattern.matches(message) {
+            found.fetch_add(1, Ordering::Relaxed);
+            hits.push(SearchHit {
+                topic: message.topic().to_owned(),
+                partition: message.partition(),
+                offset: message.offset(),
+                timestamp: message.timestamp().to_millis(),
+                key: message
+                    .key()
+                    .map(|key| String::from_utf8_lossy(key).into_owned()),
+            });
+        }
+        Ok(true)
+    })
+    .await?;
+    Ok(hits)
 }
 
-#[derive(Debug)]
-struct StreamsTopologyGlobalStore {
-    source: StreamsTopologyNode,
-    processor: StreamsTopologyNode,
+/// Reads every range to its end, passing each record inside a range to `visit` until it
+/// returns `false`. Fails once `idle_timeout_ms` passes without a record.
+async fn scan_ranges(
+    consumer: &StreamConsumer,
+    ranges: &[PartitionRange],
+    command: &str,
+    idle_timeout_ms: u64,
+    mut visit: impl FnMut(&rdkafka::message::BorrowedMessage<'_>) -> Result<bool>,
+) -> Result<()> {
+    let mut remaining = assign_ranges(consumer, ranges)?;
+    let mut stream = consumer.stream();
+    let mut progress = Instant::now();
+    while !remaining.is_empty() {
+        let Some(message) = next_consumer_message(&mut stream, Some(1_000)).await? else {
+            retire_consumed_partitions(consumer, &mut remaining)?;
+            if !remaining.is_empty() && progress.elapsed() >= Duration::from_millis(idle_timeout_ms)
+            {
+                return Err(Error::Config(format!(
+                    "{command} made no progress for {idle_timeout_ms} ms with {} partitions \
+                     incomplete",
+                    remaining.len()
+                )));
+            }
+            continue;
+        };
+        let message = message?;
+        progress = Instant::now();
+        let key = (message.topic().to_owned(), message.partition());
+        let Some(end) = remaining.get(&key).copied() else {
+            continue;
+        };
+        if message.offset() + 1 >= end {
+            remaining.remove(&key);
+        }
+        if message.offset() < end && !visit(&message)? {
+            break;
+        }
+    }
+    Ok(())
 }
 
-#[derive(Debug)]
-struct StreamsTopologyNode {
-    name: String,
-    node_type: i8,
-    source_topics: Vec<String>,
-    sink_topic: Option<String>,
-    stores: Vec<String>,
-    successors: Vec<String>,
+/// Parses a time bound given as epoch milliseconds or an RFC 3339 timestamp.
+fn parse_time_bound(flag: &str, value: &str) -> Result<i64> {
+    value
+        .parse::<i64>()
+        .or_else(|_| parse_datetime_millis(value))
+        .map_err(|_| {
+            Error::Usage(format!(
+                "{flag} must be epoch milliseconds or an RFC 3339 timestamp"
+            ))
+        })
 }
 
-#[derive(Debug, Serialize)]
-struct StreamsGroupStateRow {
-    group: String,
-    coordinator: String,
-    coordinator_id: i32,
-    assignor: String,
-    state: String,
-    group_epoch: Option<i32>,
-    assignment_epoch: Option<i32>,
-    members: usize,
+/// Record predicates from `consume --filter-*`; a record is printed only when all match.
+struct RecordFilter {
+    key: Option<Regex>,
+    value: Option<Regex>,
+    headers: Vec<(String, String)>,
+    from_time: Option<i64>,
+    to_time: Option<i64>,
+    json: Vec<(Vec<JsonPathSegment>, serde_json::Value)>,
 }
 
-#[derive(Debug, Serialize)]
-struct StreamsGroupMemberRow {
-    group: String,
-    member: String,
-    process: String,
-    client_id: String,
-    assignments: String,
-    member_protocol: Option<String>,
-    member_epoch: Option<i32>,
-    topology_epoch: Option<i32>,
-    assignment_epoch: Option<i32>,
+#[derive(Debug, PartialEq, Eq)]
+enum JsonPathSegment {
+    Field(String),
+    Index(usize),
 }
 
-#[derive(Debug, Serialize)]
-struct StreamsGroupOffsetRow {
-    group: String,
-    topic: String,
-    partition: i32,
-    current_offset: Option<i64>,
-    leader_epoch: Option<i32>,
-    log_end_offset: i64,
-    lag: i64,
-}
+impl RecordFilter {
+    fn new(args: &crate::cli::ConsumeArgs) -> Result<Self> {
+        let regex = |flag: &str, pattern: Option<&str>| {
+            pattern
+                .map(|pattern| {
+                    Regex::new(pattern)
+                        .map_err(|error| Error::Usage(format!("invalid {flag} pattern: {error}")))
+                })
+                .transpose()
+        };
+        let time = |flag: &str, value: Option<&str>| {
+            value.map(|value| parse_time_bound(flag, value)).transpose()
+        };
+        let json = args
+            .filter_json
+            .iter()
+            .map(|predicate| {
+                let (path, value) = predicate.split_once('=').ok_or_else(|| {
+                    Error::Usage(format!("--filter-json expects PATH=VALUE, got {predicate}"))
+                })?;
+                let value = serde_json::from_str(value)
+                    .unwrap_or_else(|_| serde_json::Value::String(value.to_owned()));
+                Ok((parse_json_path(path)?, value))
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self {
+            key: regex("--filter-key", args.filter_key.as_deref())?,
+            value: regex("--filter-value", args.filter_value.as_deref())?,
+            headers: parse_pairs(&args.filter_header)?,
+            from_time: time("--filter-from-time", args.filter_from_time.as_deref())?,
+            to_time: time("--filter-to-time", args.filter_to_time.as_deref())?,
+            json,
+        })
+    }
 
-#[derive(Debug, Serialize)]
-struct StreamsTopologyRow {
-    group: String,
-    scope: String,
-    topology: String,
-    node: String,
-    node_type: String,
-    source_topics: String,
-    sink_topic: Option<String>,
-    stores: String,
-    successors: String,
+    fn matches(&self, message: &impl Message, formatter: &MessageFormatterOptions) -> Result<bool> {
+        if self.from_time.is_some() || self.to_time.is_some() {
+            let Some(timestamp) = message.timestamp().to_millis() else {
+                return Ok(false);
+            };
+            if self.from_time.is_some_and(|from| timestamp < from)
+                || self.to_time.is_some_and(|to| timestamp > to)
+            {
+                return Ok(false);
+            }
+        }
+        let header_matches = |(name, expected): &(String, String)| {
+            message.headers().is_some_and(|headers| {
+                headers.iter().any(|header| {
+                    header.key == name
+                        && header
+                            .value
+                            .is_some_and(|value| String::from_utf8_lossy(value) == *expected)
+                })
+            })
+        };
+        if !self.headers.iter().all(header_matches) {
+            return Ok(false);
+        }
+        if let Some(pattern) = &self.key {
+            let key = formatter.display(message.key(), formatter.key_deserializer)?;
+            if message.key().is_none() || !pattern.is_match(&String::from_utf8_lossy(&key)) {
+                return Ok(false);
+            }
+        }
+        if self.value.is_none() && self.json.is_empty() {
+            return Ok(true);
+        }
+        if message.payload().is_none() {
+            return Ok(false);
+        }
+        let value = formatter.display(message.payload(), formatter.value_deserializer)?;
+        if let Some(pattern) = &self.value
+            && !pattern.is_match(&String::from_utf8_lossy(&value))
+        {
+            return Ok(false);
+        }
+        if self.json.is_empty() {
+            return Ok(true);
+        }
+        let Ok(document) = serde_json::from_slice::<serde_json::Value>(&value) else {
+            return Ok(false);
+        };
+        Ok(self
+            .json
+            .iter()
+            .all(|(path, expected)| json_path_value(&document, path) == Some(expected)))
+    }
 }
 
-async fn share_group_ids(client: &krafka::admin::AdminClient) -> Result<Vec<String>> {
-    group_ids_by_type(client, "share").await
+/// Parses a `$.field[0].nested` path; the leading `$` is optional.
+fn parse_json_path(path: &str) -> Result<Vec<JsonPathSegment>> {
+    let invalid = || Error::Usage(format!("invalid --filter-json path: {path}"));
+    let mut rest = path.strip_prefix('$').unwrap_or(path);
+    let mut segments = Vec::new();
+    while !rest.is_empty() {
+        if let Some(index) = rest.strip_prefix('[') {
+            let (index, tail) = index.split_once(']').ok_or_else(invalid)?;
+            segments.push(JsonPathSegment::Index(
+                index.parse().map_err(|_| invalid())?,
+            ));
+            rest = tail;
+        } else {
+            let field = rest.strip_prefix('.').ok_or_else(invalid)?;
+            let end = field.find(['.', '[']).unwrap_or(field.len());
+            if end == 0 {
+                return Err(invalid());
+            }
+            segments.push(JsonPathSegment::Field(field[..end].to_owned()));
+            rest = &field[end..];
+        }
+    }
+    Ok(segments)
 }
 
-async fn group_ids_by_type(
-    client: &krafka::admin::AdminClient,
-    expected_type: &str,
-) -> Result<Vec<String>> {
-    let mut groups = client
-        .list_consumer_groups()
-        .await?
-        .into_iter()
-        .filter(|group| {
-            group
-                .group_type
-                .as_ref()
-                .is_some_and(|kind| kind.to_string().eq_ignore_ascii_case(expected_type))
+fn json_path_value<'a>(
+    document: &'a serde_json::Value,
+    path: &[JsonPathSegment],
+) -> Option<&'a serde_json::Value> {
+    path.iter()
+        .try_fold(document, |value, segment| match segment {
+            JsonPathSegment::Field(field) => value.get(field),
+            JsonPathSegment::Index(index) => value.get(index),
         })
-        .map(|group| group.group_id)
-        .collect::<Vec<_>>();
-    groups.sort();
-    Ok(groups)
 }
 
-async fn list_streams_groups(
-    client: &krafka::admin::AdminClient,
-    format: OutputFormat,
-    state: Option<&str>,
+async fn share_consume(
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    request_timeout: Duration,
+    args: ShareConsumeArgs,
 ) -> Result<()> {
-    let group_ids = group_ids_by_type(client, "streams").await?;
-    let rows = if let Some(state_filter) = state {
-        let states = if state_filter.is_empty() {
-            BTreeSet::new()
-        } else {
-            parse_streams_group_states(state_filter)?
-        };
-        describe_streams_groups(client, &group_ids, false, false)
-            .await?
-            .into_iter()
-            .filter(|group| states.is_empty() || states.contains(&group.description.group_state))
-            .map(|group| ShareGroupListRow {
-                group: group.description.group_id,
-                state: Some(group.description.group_state),
-            })
-            .collect::<Vec<_>>()
-    } else {
-        group_ids
-            .into_iter()
-            .map(|group| ShareGroupListRow { group, state: None })
-            .collect()
-    };
-    output::write_value(format, "streams-groups.list", &rows, |rows| {
-        if rows.iter().any(|row| row.state.is_some()) {
-            output::table(
-                ["GROUP", "STATE"],
-                rows.iter().map(|row| {
-                    [
-                        row.group.clone(),
-                        row.state.as_deref().unwrap_or("-").to_owned(),
-                    ]
-                }),
-            )
-        } else {
-            output::table(["GROUP"], rows.iter().map(|row| [row.group.clone()]))
-        }
-    })
-}
-
-fn parse_streams_group_states(value: &str) -> Result<BTreeSet<String>> {
-    value
-        .split(',')
-        .map(str::trim)
-        .map(|state| match state.to_ascii_lowercase().as_str() {
-            "empty" => Ok("Empty".into()),
-            "notready" => Ok("NotReady".into()),
-            "stable" => Ok("Stable".into()),
-            "assigning" => Ok("Assigning".into()),
-            "reconciling" => Ok("Reconciling".into()),
-            "dead" => Ok("Dead".into()),
-            _ => Err(Error::Usage(format!(
-                "invalid Streams group state '{state}'; expected Empty, NotReady, Stable, Assigning, Reconciling, or Dead"
-            ))),
-        })
-        .collect()
-}
+    let formatter = message_formatter_options(&args)?;
+    let mut properties = match command_config {
+        Some(path) => config::load_properties(path)?,
+        None => std::collections::HashMap::new(),
+    };
+    for (key, value) in parse_pairs(args.properties())? {
+        properties.insert(key, value);
+    }
 
-fn streams_tasks(tasks: &[StreamsTaskIds], label: &str) -> String {
-    tasks
-        .iter()
-        .map(|task| {
-            let partitions = task
-                .partitions
-                .iter()
-                .map(i32::to_string)
-                .collect::<Vec<_>>()
-                .join(",");
-            format!("{label}: {}:[{partitions}]", task.subtopology_id)
-        })
-        .collect::<Vec<_>>()
-        .join("; ")
-}
+    let configured_group = properties.get("group.id").map(String::as_str);
+    if let (Some(argument), Some(configured)) = (args.group.as_deref(), configured_group)
+        && argument != configured
+    {
+        return Err(Error::Usage(format!(
+            "group ids supplied by --group and consumer properties must match: '{argument}', '{configured}'"
+        )));
+    }
+    let group = args
+        .group
+        .as_deref()
+        .or(configured_group)
+        .unwrap_or("console-share-consumer");
+    let client_id = properties
+        .get("client.id")
+        .map_or("console-share-consumer", String::as_str);
 
-fn streams_assignment(
-    assignment: &StreamsTaskAssignment,
-    target: Option<&StreamsTaskAssignment>,
-) -> String {
-    let mut values = [
-        streams_tasks(&assignment.active, "ACTIVE"),
-        streams_tasks(&assignment.standby, "STANDBY"),
-        streams_tasks(&assignment.warmup, "WARMUP"),
-    ]
-    .into_iter()
-    .filter(|value| !value.is_empty())
-    .collect::<Vec<_>>();
-    if let Some(target) = target {
-        values.extend(
-            [
-                streams_tasks(&target.active, "TARGET-ACTIVE"),
-                streams_tasks(&target.standby, "TARGET-STANDBY"),
-                streams_tasks(&target.warmup, "TARGET-WARMUP"),
-            ]
-            .into_iter()
-            .filter(|value| !value.is_empty()),
+    let mut builder = ShareConsumer::builder()
+        .bootstrap_servers(bootstrap)
+        .group_id(group)
+        .client_id(client_id)
+        .acknowledgement_mode(AcknowledgementMode::Explicit)
+        .request_timeout(
+            share_duration_property(&properties, "request.timeout.ms")?.unwrap_or(request_timeout),
         );
+    let mut private = PrivateFiles::default();
+    if let Some(auth) = config::protocol_auth(&properties, &mut private)? {
+        builder = builder.auth(auth);
     }
-    values.join("; ")
+    if let Some(value) = share_i32_property(&properties, "max.poll.records")? {
+        builder = builder.max_poll_records(value);
+    } else if let Some(max_messages) = args.max_messages.filter(|value| *value > 0) {
+        builder = builder.max_poll_records(max_messages);
+    }
+    if let Some(value) = share_i32_property(&properties, "fetch.max.wait.ms")? {
+        builder = builder.fetch_max_wait_ms(value);
+    }
+    if let Some(value) = share_duration_property(&properties, "session.timeout.ms")? {
+        builder = builder.session_timeout(value);
+    }
+    if let Some(value) = share_duration_property(&properties, "heartbeat.interval.ms")? {
+        builder = builder.heartbeat_interval(value);
+    }
+    if let Some(value) = share_duration_property(&properties, "metadata.max.age.ms")? {
+        builder = builder.metadata_max_age(value);
+    }
+    if let Some(value) = share_duration_property(&properties, "metadata.max.idle.ms")? {
+        builder = builder.metadata_topic_cache_ttl(value);
+    }
+    if let Some(rack) = properties.get("client.rack") {
+        builder = builder.client_rack(rack);
+    }
+
+    let consumer = builder.build().await?;
+    drop(private);
+    consumer.subscribe(&[&args.topic]).await?;
+    let acknowledgement = if args.reject {
+        ShareAcknowledgeType::Reject
+    } else if args.release {
+        ShareAcknowledgeType::Release
+    } else {
+        ShareAcknowledgeType::Accept
+    };
+    let run_result = consume_share_records(&consumer, &formatter, &args, acknowledgement).await;
+    let close_result = consumer.close().await.map_err(Error::from);
+    drop(consumer);
+    eprintln!(
+        "Processed a total of {} messages",
+        run_result.as_ref().map_or(0, |count| *count)
+    );
+    if args.enable_systest_events {
+        println!("shutdown_complete");
+    }
+    run_result.map(|_| ()).and(close_result)
 }
 
-fn write_streams_group_states(
-    format: OutputFormat,
-    descriptions: Vec<StreamsGroupDescriptionWithCoordinator>,
-    verbose: bool,
+async fn consumer_perf_test(
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    args: ConsumerPerfTestArgs,
 ) -> Result<()> {
-    let rows = descriptions
-        .into_iter()
-        .map(|group| StreamsGroupStateRow {
-            group: group.description.group_id,
-            coordinator: group.coordinator,
-            coordinator_id: group.coordinator_id,
-            assignor: group.description.assignor.unwrap_or_default(),
-            state: group.description.group_state,
-            group_epoch: verbose.then_some(group.description.group_epoch),
-            assignment_epoch: verbose.then_some(group.description.assignment_epoch),
-            members: group.description.members.len(),
-        })
-        .collect::<Vec<_>>();
-    output::write_value(format, "streams-groups.describe.state", &rows, |rows| {
-        if verbose {
-            output::table(
-                [
-                    "GROUP",
-                    "COORDINATOR (ID)",
-                    "ASSIGNOR",
-                    "STATE",
-                    "GROUP-EPOCH",
-                    "TARGET-ASSIGNMENT-EPOCH",
-                    "#MEMBERS",
-                ],
-                rows.iter().map(|row| {
-                    [
-                        row.group.clone(),
-                        format!("{} ({})", row.coordinator, row.coordinator_id),
-                        row.assignor.clone(),
-                        row.state.clone(),
-                        row.group_epoch
-                            .map_or_else(|| "-".into(), |v| v.to_string()),
-                        row.assignment_epoch
-                            .map_or_else(|| "-".into(), |v| v.to_string()),
-                        row.members.to_string(),
-                    ]
-                }),
-            )
-        } else {
-            output::table(
-                ["GROUP", "COORDINATOR (ID)", "ASSIGNOR", "STATE", "#MEMBERS"],
-                rows.iter().map(|row| {
-                    [
-                        row.group.clone(),
-                        format!("{} ({})", row.coordinator, row.coordinator_id),
-                        row.assignor.clone(),
-                        row.state.clone(),
-                        row.members.to_string(),
-                    ]
-                }),
-            )
-        }
+    let bootstrap = bootstrap.to_owned();
+    let command_config = command_config.map(Path::to_path_buf);
+    tokio::task::spawn_blocking(move || {
+        run_consumer_perf_test(&bootstrap, command_config.as_deref(), &args)
     })
+    .await
+    .map_err(|error| Error::Config(format!("consumer performance task failed: {error}")))?
 }
 
-fn write_streams_group_members(
-    format: OutputFormat,
-    descriptions: Vec<StreamsGroupDescriptionWithCoordinator>,
-    verbose: bool,
+#[expect(
+    clippy::too_many_lines,
+    reason = "the Kafka benchmark lifecycle keeps configuration, rebalance accounting, polling, reporting, and cleanup ordering explicit"
+)]
+fn run_consumer_perf_test(
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    args: &ConsumerPerfTestArgs,
 ) -> Result<()> {
-    let rows = descriptions
-        .into_iter()
-        .flat_map(|group| {
-            let group_id = group.description.group_id;
-            let assignment_epoch = group.description.assignment_epoch;
-            group.description.members.into_iter().map(move |member| {
-                let target = verbose.then_some(&member.target_assignment);
-                StreamsGroupMemberRow {
-                    group: group_id.clone(),
-                    member: member.member_id,
-                    process: member.process_id,
-                    client_id: member.client_id,
-                    assignments: streams_assignment(&member.assignment, target),
-                    member_protocol: verbose.then(|| {
-                        if member.is_classic {
-                            "classic"
-                        } else {
-                            "streams"
-                        }
-                        .into()
-                    }),
-                    member_epoch: verbose.then_some(member.member_epoch),
-                    topology_epoch: verbose.then_some(member.topology_epoch),
-                    assignment_epoch: verbose.then_some(assignment_epoch),
-                }
-            })
-        })
-        .collect::<Vec<_>>();
-    output::write_value(format, "streams-groups.describe.members", &rows, |rows| {
-        if verbose {
-            output::table(
+    if args.reporting_interval == 0 {
+        return Err(Error::Usage(
+            "reporting interval must be greater than 0".into(),
+        ));
+    }
+    if args.fetch_size <= 0 {
+        return Err(Error::Usage("--fetch-size must be greater than 0".into()));
+    }
+    if args.socket_buffer_size <= 0 {
+        return Err(Error::Usage(
+            "--socket-buffer-size must be greater than 0".into(),
+        ));
+    }
+    validate_share_date_pattern(&args.date_format)?;
+    let config_path = share_perf_config_path(command_config, args.consumer_config.as_deref())?;
+    if args.messages.is_some() {
+        println!("Warning: --messages is deprecated. Use --num-records instead.");
+    }
+    if args.consumer_config.is_some() {
+        println!("Warning: --consumer.config is deprecated. Use --command-config instead.");
+    }
+    if !args.hide_header {
+        print_consumer_perf_header(args.show_detailed_stats);
+    }
+
+    let mut config = config::client_config(bootstrap, config_path)?;
+    apply_client_properties(&mut config, &args.properties)?;
+    let generated_group = format!(
+        "perf-consumer-{}",
+        Utc::now().timestamp_millis().unsigned_abs() % 100_000
+    );
+    config
+        .set(
+            "group.id",
+            args.group.as_deref().unwrap_or(&generated_group),
+        )
+        .set("max.partition.fetch.bytes", args.fetch_size.to_string())
+        .set(
+            "socket.receive.buffer.bytes",
+            args.socket_buffer_size.to_string(),
+        )
+        .set(
+            "auto.offset.reset",
+            if args.from_latest {
+                "latest"
+            } else {
+                "earliest"
+            },
+        )
+        .set("check.crcs", "false");
+    if config.get("client.id").is_none() {
+        config.set("client.id", "perf-consumer-client");
+    }
+    let consumer: BaseConsumer = config.create()?;
+    if let Some(topic) = args.topic.as_deref() {
+        consumer.subscribe(&[topic])?;
+    } else if let Some(include) = args.include.as_deref() {
+        consumer.subscribe(&[&consumer_include_pattern(include)])?;
+    }
+
+    let start = Instant::now();
+    let start_ms = Utc::now().timestamp_millis();
+    let mut last_record = start;
+    let mut last_report = start;
+    let mut previous = SharePerfStats::default();
+    let mut stats = SharePerfStats::default();
+    let mut join_started = start;
+    let mut assigned = false;
+    let mut total_join = Duration::ZERO;
+    let mut round_join = Duration::ZERO;
+    while stats.records < args.num_records()
+        && last_record.elapsed() <= Duration::from_millis(args.timeout)
+    {
+        let message = consumer.poll(Duration::from_millis(100));
+        let now = Instant::now();
+        let currently_assigned = consumer.assignment()?.count() > 0;
+        if !assigned && currently_assigned {
+            let elapsed = join_started.elapsed();
+            total_join = total_join.saturating_add(elapsed);
+            round_join = round_join.saturating_add(elapsed);
+        } else if assigned && !currently_assigned {
+            join_started = now;
+        }
+        assigned = currently_assigned;
+        let Some(message) = message else { continue };
+        let message = message?;
+        last_record = now;
+        stats.records = stats.records.saturating_add(1);
+        stats.bytes = stats.bytes.saturating_add(
+            message
+                .key()
+                .map_or(0, |value| u64::try_from(value.len()).unwrap_or(u64::MAX))
+                .saturating_add(
+                    message
+                        .payload()
+                        .map_or(0, |value| u64::try_from(value.len()).unwrap_or(u64::MAX)),
+                ),
+        );
+        if last_report.elapsed() >= Duration::from_millis(args.reporting_interval) {
+            if args.show_detailed_stats {
+                print_consumer_perf_progress(
+                    stats,
+                    previous,
+                    last_report.elapsed(),
+                    round_join,
+                    &args.date_format,
+                );
+            }
+            round_join = Duration::ZERO;
+            previous = stats;
+            last_report = now;
+        }
+    }
+    if stats.records < args.num_records() {
+        println!(
+            "WARNING: Exiting before consuming the expected number of records: timeout ({} ms) exceeded. You can use the --timeout option to increase the timeout.",
+            args.timeout
+        );
+    }
+    let end_ms = Utc::now().timestamp_millis();
+    let elapsed = start.elapsed();
+    if !args.show_detailed_stats {
+        print_consumer_perf_summary(
+            stats,
+            elapsed,
+            total_join,
+            start_ms,
+            end_ms,
+            &args.date_format,
+        );
+    }
+    if args.print_metrics {
+        println!(
+            "records-consumed:client-id={}: {}",
+            config.get("client.id").unwrap_or("perf-consumer-client"),
+            stats.records
+        );
+        println!(
+            "bytes-consumed:client-id={}: {}",
+            config.get("client.id").unwrap_or("perf-consumer-client"),
+            stats.bytes
+        );
+        println!("assigned-partitions: {}", consumer.assignment()?.count());
+    }
+    consumer.unsubscribe();
+    Ok(())
+}
+
+fn print_consumer_perf_header(detailed: bool) {
+    if detailed {
+        println!(
+            "time, threadId, data.consumed.in.MB, MB.sec, data.consumed.in.nMsg, nMsg.sec, rebalance.time.ms, fetch.time.ms, fetch.MB.sec, fetch.nMsg.sec"
+        );
+    } else {
+        println!(
+            "start.time, end.time, data.consumed.in.MB, MB.sec, data.consumed.in.nMsg, nMsg.sec, rebalance.time.ms, fetch.time.ms, fetch.MB.sec, fetch.nMsg.sec"
+        );
+    }
+}
+
+fn consumer_fetch_values(
+    stats: SharePerfStats,
+    elapsed: Duration,
+    join: Duration,
+) -> (f64, f64, u128) {
+    let fetch = elapsed.saturating_sub(join);
+    let (_, mb_per_sec, records_per_sec, _) = share_perf_values(stats, fetch);
+    (mb_per_sec, records_per_sec, fetch.as_millis())
+}
+
+fn print_consumer_perf_progress(
+    stats: SharePerfStats,
+    previous: SharePerfStats,
+    interval: Duration,
+    join: Duration,
+    date_format: &str,
+) {
+    let delta = SharePerfStats {
+        records: stats.records.saturating_sub(previous.records),
+        bytes: stats.bytes.saturating_sub(previous.bytes),
+    };
+    let (total_mb, mb_per_sec, records_per_sec, _) = share_perf_values(stats, interval);
+    let (fetch_mb_per_sec, fetch_records_per_sec, fetch_ms) =
+        consumer_fetch_values(delta, interval, join);
+    println!(
+        "{}, 0, {:.4}, {:.4}, {}, {:.4}, {}, {}, {:.4}, {:.4}",
+        format_share_perf_time(Utc::now().timestamp_millis(), date_format),
+        total_mb,
+        mb_per_sec,
+        stats.records,
+        records_per_sec,
+        join.as_millis(),
+        fetch_ms,
+        fetch_mb_per_sec,
+        fetch_records_per_sec
+    );
+}
+
+fn print_consumer_perf_summary(
+    stats: SharePerfStats,
+    elapsed: Duration,
+    join: Duration,
+    start_ms: i64,
+    end_ms: i64,
+    date_format: &str,
+) {
+    let (total_mb, mb_per_sec, records_per_sec, _) = share_perf_values(stats, elapsed);
+    let (fetch_mb_per_sec, fetch_records_per_sec, fetch_ms) =
+        consumer_fetch_values(stats, elapsed, join);
+    println!(
+        "{}, {}, {:.4}, {:.4}, {}, {:.4}, {}, {}, {:.4}, {:.4}",
+        format_share_perf_time(start_ms, date_format),
+        format_share_perf_time(end_ms, date_format),
+        total_mb,
+        mb_per_sec,
+        stats.records,
+        records_per_sec,
+        join.as_millis(),
+        fetch_ms,
+        fetch_mb_per_sec,
+        fetch_records_per_sec
+    );
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct SharePerfStats {
+    records: u64,
+    bytes: u64,
+}
+
+#[expect(
+    clippy::too_many_lines,
+    reason = "the Kafka performance lifecycle keeps setup, concurrent collection, reporting, commit, and close ordering explicit"
+)]
+async fn share_consumer_perf_test(
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    request_timeout: Duration,
+    args: ShareConsumerPerfTestArgs,
+) -> Result<()> {
+    if args.threads == 0 {
+        return Err(Error::Usage("--threads must be greater than 0".into()));
+    }
+    if args.reporting_interval == 0 {
+        return Err(Error::Usage(
+            "reporting interval must be greater than 0".into(),
+        ));
+    }
+    if args.fetch_size <= 0 {
+        return Err(Error::Usage("--fetch-size must be greater than 0".into()));
+    }
+    if args.socket_buffer_size <= 0 {
+        return Err(Error::Usage(
+            "--socket-buffer-size must be greater than 0".into(),
+        ));
+    }
+    validate_share_date_pattern(&args.date_format)?;
+    if args.messages.is_some() {
+        println!("Warning: --messages is deprecated. Use --num-records instead.");
+    }
+    if args.consumer_config.is_some() {
+        println!("Warning: --consumer.config is deprecated. Use --command-config instead.");
+    }
+    if !args.hide_header {
+        print_share_perf_header();
+    }
+
+    let config_path = share_perf_config_path(command_config, args.consumer_config.as_deref())?;
+    let mut properties = match config_path {
+        Some(path) => config::load_properties(path)?,
+        None => std::collections::HashMap::new(),
+    };
+    for (key, value) in parse_pairs(&args.properties)? {
+        properties.insert(key, value);
+    }
+    let base_client_id = properties
+        .get("client.id")
+        .cloned()
+        .unwrap_or_else(|| "perf-share-consumer-client".into());
+    let mut consumers = Vec::with_capacity(args.threads);
+    let mut client_ids = Vec::with_capacity(args.threads);
+    for index in 0..args.threads {
+        let client_id = if args.threads == 1 {
+            base_client_id.clone()
+        } else {
+            format!("{}-{}", base_client_id, index + 1)
+        };
+        let consumer = build_share_perf_consumer(
+            bootstrap,
+            &args.group,
+            &client_id,
+            &properties,
+            request_timeout,
+            args.num_records(),
+        )
+        .await?;
+        consumer.subscribe(&[&args.topic]).await?;
+        client_ids.push(client_id);
+        consumers.push(consumer);
+    }
+
+    let start = Instant::now();
+    let start_ms = Utc::now().timestamp_millis();
+    let total_records = Arc::new(AtomicU64::new(0));
+    let worker_options = SharePerfWorkerOptions {
+        target: args.num_records(),
+        timeout_ms: args.timeout,
+        reporting_interval_ms: args.reporting_interval,
+        detailed: args.show_detailed_stats,
+        date_format: &args.date_format,
+    };
+    let stats =
+        futures::future::try_join_all(consumers.iter().enumerate().map(|(index, consumer)| {
+            consume_share_perf_records(
+                consumer,
+                index + 1,
+                &worker_options,
+                Arc::clone(&total_records),
+            )
+        }))
+        .await?;
+    let end_ms = Utc::now().timestamp_millis();
+    let elapsed = start.elapsed();
+    let total = stats
+        .iter()
+        .fold(SharePerfStats::default(), |mut total, item| {
+            total.records = total.records.saturating_add(item.records);
+            total.bytes = total.bytes.saturating_add(item.bytes);
+            total
+        });
+
+    if args.show_consumer_stats {
+        for (index, (client_id, item)) in client_ids.iter().zip(&stats).enumerate() {
+            print_share_consumer_stats(
+                index + 1,
+                client_id,
+                *item,
+                elapsed,
+                start_ms,
+                end_ms,
+                &args.date_format,
+            );
+        }
+    }
+    if total.records < args.num_records() {
+        println!(
+            "WARNING: Exiting before consuming the expected number of records: timeout ({} ms) exceeded. You can use the --timeout option to increase the timeout.",
+            args.timeout
+        );
+    }
+
+    let metric_snapshots = args.print_metrics.then(|| {
+        consumers
+            .iter()
+            .map(|consumer| consumer.connection_metrics().snapshot())
+            .collect::<Vec<_>>()
+    });
+    for consumer in &consumers {
+        consumer.commit_sync().await?;
+    }
+    print_share_group_stats(total, elapsed, start_ms, end_ms, &args.date_format);
+    if let Some(snapshots) = metric_snapshots {
+        for (client_id, metrics) in client_ids.iter().zip(snapshots) {
+            println!(
+                "connections-created:client-id={client_id}: {}",
+                metrics.connections_created
+            );
+            println!(
+                "connections-closed:client-id={client_id}: {}",
+                metrics.connections_closed
+            );
+            println!(
+                "connection-errors:client-id={client_id}: {}",
+                metrics.connection_errors
+            );
+            println!(
+                "active-connections:client-id={client_id}: {}",
+                metrics.active_connections
+            );
+            println!(
+                "requests-sent:client-id={client_id}: {}",
+                metrics.normal_priority_requests
+            );
+        }
+    }
+    for consumer in &consumers {
+        consumer.close().await?;
+    }
+    drop(consumers);
+    Ok(())
+}
+
+fn share_perf_config_path<'a>(
+    command_config: Option<&'a Path>,
+    consumer_config: Option<&'a Path>,
+) -> Result<Option<&'a Path>> {
+    if command_config.is_some() && consumer_config.is_some() {
+        return Err(Error::Usage(
+            "--consumer.config cannot be used with --command-config".into(),
+        ));
+    }
+    Ok(consumer_config.or(command_config))
+}
+
+async fn build_share_perf_consumer(
+    bootstrap: &str,
+    group: &str,
+    client_id: &str,
+    properties: &std::collections::HashMap<String, String>,
+    request_timeout: Duration,
+    num_records: u64,
+) -> Result<ShareConsumer> {
+    let mut builder = ShareConsumer::builder()
+        .bootstrap_servers(bootstrap)
+        .group_id(group)
+        .client_id(client_id)
+        .acknowledgement_mode(AcknowledgementMode::Implicit)
+        .request_timeout(
+            share_duration_property(properties, "request.timeout.ms")?.unwrap_or(request_timeout),
+        );
+    let mut private = PrivateFiles::default();
+    let auth = config::protocol_auth(properties, &mut private)?;
+    if let Some(auth) = auth {
+        builder = builder.auth(auth);
+    }
+    let max_poll_records = share_i32_property(properties, "max.poll.records")?;
+    if let Some(value) = max_poll_records {
+        builder = builder.max_poll_records(value);
+    } else if let Ok(value) = i32::try_from(num_records)
+        && value > 0
+    {
+        builder = builder.max_poll_records(value);
+    }
+    let fetch_max_wait = share_i32_property(properties, "fetch.max.wait.ms")?;
+    if let Some(value) = fetch_max_wait {
+        builder = builder.fetch_max_wait_ms(value);
+    }
+    let session_timeout = share_duration_property(properties, "session.timeout.ms")?;
+    if let Some(value) = session_timeout {
+        builder = builder.session_timeout(value);
+    }
+    let heartbeat_interval = share_duration_property(properties, "heartbeat.interval.ms")?;
+    if let Some(value) = heartbeat_interval {
+        builder = builder.heartbeat_interval(value);
+    }
+    let metadata_max_age = share_duration_property(properties, "metadata.max.age.ms")?;
+    if let Some(value) = metadata_max_age {
+        builder = builder.metadata_max_age(value);
+    }
+    let metadata_max_idle = share_duration_property(properties, "metadata.max.idle.ms")?;
+    if let Some(value) = metadata_max_idle {
+        builder = builder.metadata_topic_cache_ttl(value);
+    }
+    if let Some(rack) = properties.get("client.rack") {
+        builder = builder.client_rack(rack);
+    }
+    Ok(builder.build().await?)
+}
+
+struct SharePerfWorkerOptions<'a> {
+    target: u64,
+    timeout_ms: u64,
+    reporting_interval_ms: u64,
+    detailed: bool,
+    date_format: &'a str,
+}
+
+async fn consume_share_perf_records(
+    consumer: &ShareConsumer,
+    index: usize,
+    options: &SharePerfWorkerOptions<'_>,
+    total_records: Arc<AtomicU64>,
+) -> Result<SharePerfStats> {
+    let mut stats = SharePerfStats::default();
+    let mut previous = stats;
+    let mut last_record = Instant::now();
+    let mut last_report = Instant::now();
+    while total_records.load(Ordering::Relaxed) < options.target
+        && last_record.elapsed() <= Duration::from_millis(options.timeout_ms)
+    {
+        let records = consumer.poll(Duration::from_millis(100)).await?;
+        let now = Instant::now();
+        if !records.is_empty() {
+            last_record = now;
+        }
+        for record in records {
+            stats.records = stats.records.saturating_add(1);
+            stats.bytes = stats.bytes.saturating_add(
+                record.key.as_ref().map_or(0, |value| value.len() as u64)
+                    + record.value.as_ref().map_or(0, |value| value.len() as u64),
+            );
+            total_records.fetch_add(1, Ordering::Relaxed);
+            if options.detailed
+                && last_report.elapsed() >= Duration::from_millis(options.reporting_interval_ms)
+            {
+                print_share_progress(
+                    index,
+                    stats,
+                    previous,
+                    last_report.elapsed(),
+                    options.date_format,
+                );
+                previous = stats;
+                last_report = now;
+            }
+        }
+    }
+    Ok(stats)
+}
+
+fn print_share_perf_header() {
+    println!(
+        "start.time, end.time, data.consumed.in.MB, MB.sec, nMsg.sec, data.consumed.in.nMsg, fetch.time.ms"
+    );
+}
+
+fn java_date_pattern(pattern: &str) -> String {
+    pattern
+        .replace("yyyy", "%Y")
+        .replace("SSS", "%.3f")
+        .replace("MM", "%m")
+        .replace("dd", "%d")
+        .replace("HH", "%H")
+        .replace("mm", "%M")
+        .replace("ss", "%S")
+}
+
+fn validate_share_date_pattern(pattern: &str) -> Result<()> {
+    let translated = java_date_pattern(pattern);
+    if StrftimeItems::new(&translated).any(|item| item == Item::Error) {
+        return Err(Error::Usage(format!(
+            "invalid --date-format pattern '{pattern}'"
+        )));
+    }
+    Ok(())
+}
+
+fn format_share_perf_time(timestamp_ms: i64, pattern: &str) -> String {
+    DateTime::from_timestamp_millis(timestamp_ms).map_or_else(
+        || timestamp_ms.to_string(),
+        |value| {
+            value
+                .with_timezone(&Local)
+                .format(&java_date_pattern(pattern))
+                .to_string()
+        },
+    )
+}
+
+#[expect(
+    clippy::cast_precision_loss,
+    reason = "Kafka performance output uses floating point rates"
+)]
+fn share_perf_values(stats: SharePerfStats, elapsed: Duration) -> (f64, f64, f64, u128) {
+    let seconds = elapsed.as_secs_f64().max(f64::EPSILON);
+    let megabytes = stats.bytes as f64 / (1024.0 * 1024.0);
+    (
+        megabytes,
+        megabytes / seconds,
+        stats.records as f64 / seconds,
+        elapsed.as_millis(),
+    )
+}
+
+fn print_share_progress(
+    index: usize,
+    stats: SharePerfStats,
+    previous: SharePerfStats,
+    interval: Duration,
+    date_format: &str,
+) {
+    let end_ms = Utc::now().timestamp_millis();
+    let interval_ms = i64::try_from(interval.as_millis()).unwrap_or(i64::MAX);
+    let delta = SharePerfStats {
+        records: stats.records.saturating_sub(previous.records),
+        bytes: stats.bytes.saturating_sub(previous.bytes),
+    };
+    let (_, mb_per_sec, records_per_sec, fetch_ms) = share_perf_values(delta, interval);
+    let total_mb = share_perf_values(stats, interval).0;
+    println!(
+        "{}, {}, {:.4}, {:.4}, {:.4}, {}, {} for share consumer {}",
+        format_share_perf_time(end_ms.saturating_sub(interval_ms), date_format),
+        format_share_perf_time(end_ms, date_format),
+        total_mb,
+        mb_per_sec,
+        records_per_sec,
+        stats.records,
+        fetch_ms,
+        index
+    );
+}
+
+fn print_share_consumer_stats(
+    index: usize,
+    client_id: &str,
+    stats: SharePerfStats,
+    elapsed: Duration,
+    start_ms: i64,
+    end_ms: i64,
+    date_format: &str,
+) {
+    let (megabytes, mb_per_sec, records_per_sec, fetch_ms) = share_perf_values(stats, elapsed);
+    println!(
+        "Share consumer {} having client id {} consumption metrics- {}, {}, {:.4}, {:.4}, {:.4}, {}, {}",
+        index,
+        client_id,
+        format_share_perf_time(start_ms, date_format),
+        format_share_perf_time(end_ms, date_format),
+        megabytes,
+        mb_per_sec,
+        records_per_sec,
+        stats.records,
+        fetch_ms
+    );
+}
+
+fn print_share_group_stats(
+    stats: SharePerfStats,
+    elapsed: Duration,
+    start_ms: i64,
+    end_ms: i64,
+    date_format: &str,
+) {
+    let (megabytes, mb_per_sec, records_per_sec, fetch_ms) = share_perf_values(stats, elapsed);
+    println!(
+        "{}, {}, {:.4}, {:.4}, {:.4}, {}, {}",
+        format_share_perf_time(start_ms, date_format),
+        format_share_perf_time(end_ms, date_format),
+        megabytes,
+        mb_per_sec,
+        records_per_sec,
+        stats.records,
+        fetch_ms
+    );
+}
+
+fn verifiable_event(name: &str, fields: serde_json::Value) -> serde_json::Value {
+    let mut event = serde_json::Map::new();
+    event.insert(
+        "timestamp".into(),
+        serde_json::Value::from(Utc::now().timestamp_millis()),
+    );
+    event.insert("name".into(), serde_json::Value::from(name));
+    if let serde_json::Value::Object(fields) = fields {
+        event.extend(fields);
+    }
+    serde_json::Value::Object(event)
+}
+
+fn parse_ack_pattern(value: Option<&str>) -> Result<Vec<ShareAcknowledgeType>> {
+    let Some(value) = value.filter(|value| !value.is_empty()) else {
+        return Ok(Vec::new());
+    };
+    let pattern = value
+        .split(',')
+        .map(|item| match item.trim().to_ascii_lowercase().as_str() {
+            "accept" => Ok(ShareAcknowledgeType::Accept),
+            "release" => Ok(ShareAcknowledgeType::Release),
+            "reject" => Ok(ShareAcknowledgeType::Reject),
+            "renew" => Ok(ShareAcknowledgeType::Renew),
+            other => Err(Error::Usage(format!(
+                "invalid acknowledgement type '{other}'; expected accept, release, reject, or renew"
+            ))),
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if pattern
+        .iter()
+        .all(|kind| *kind == ShareAcknowledgeType::Renew)
+    {
+        return Err(Error::Usage(
+            "--ack-pattern must contain at least one non-renew type".into(),
+        ));
+    }
+    Ok(pattern)
+}
+
+async fn verifiable_share_consumer(
+    rdkafka_config: &rdkafka::ClientConfig,
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    request_timeout: Duration,
+    verbose: bool,
+    args: VerifiableShareConsumerArgs,
+) -> Result<()> {
+    let pattern = parse_ack_pattern(args.ack_pattern.as_deref())?;
+    if !pattern.is_empty() && args.acknowledgement_mode == VerifiableAcknowledgementMode::Auto {
+        return Err(Error::Usage(
+            "--ack-pattern requires --acknowledgement-mode sync or async".into(),
+        ));
+    }
+    output::write_json_line(&verifiable_event("startup_complete", serde_json::json!({})))?;
+    if let Some(strategy) = args.offset_reset_strategy.as_deref() {
+        let admin = admin(rdkafka_config)?;
+        ffi::incremental_alter_config(
+            admin.inner().native_ptr(),
+            rdkafka_sys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_GROUP,
+            &args.group_id,
+            &[("share.auto.offset.reset".into(), strategy.into())],
+            &[],
+            duration_ms(request_timeout)?,
+        )?;
+        output::write_json_line(&verifiable_event(
+            "offset_reset_strategy_set",
+            serde_json::json!({"offsetResetStrategy": strategy}),
+        ))?;
+    }
+
+    let properties = match command_config {
+        Some(path) => config::load_properties(path)?,
+        None => std::collections::HashMap::new(),
+    };
+    let mut builder = ShareConsumer::builder()
+        .bootstrap_servers(bootstrap)
+        .group_id(&args.group_id)
+        .client_id(
+            properties
+                .get("client.id")
+                .map_or("verifiable-share-consumer", String::as_str),
+        )
+        .acknowledgement_mode(if pattern.is_empty() {
+            AcknowledgementMode::Implicit
+        } else {
+            AcknowledgementMode::Explicit
+        })
+        .request_timeout(
+            share_duration_property(&properties, "request.timeout.ms")?.unwrap_or(request_timeout),
+        );
+    let mut private = PrivateFiles::default();
+    if let Some(auth) = config::protocol_auth(&properties, &mut private)? {
+        builder = builder.auth(auth);
+    }
+    if let Some(max) = share_i32_property(&properties, "max.poll.records")?
+        .or_else(|| (args.max_messages > 0).then_some(args.max_messages))
+    {
+        builder = builder.max_poll_records(max);
+    }
+    let consumer = builder.build().await?;
+    drop(private);
+    consumer.subscribe(&[&args.topic]).await?;
+    let result = run_verifiable_share_consumer(&consumer, &args, &pattern, verbose).await;
+    let close_result = consumer.close().await.map_err(Error::from);
+    drop(consumer);
+    output::write_json_line(&verifiable_event(
+        "shutdown_complete",
+        serde_json::json!({}),
+    ))?;
+    result.and(close_result)
+}
+
+async fn run_verifiable_share_consumer(
+    consumer: &ShareConsumer,
+    args: &VerifiableShareConsumerArgs,
+    pattern: &[ShareAcknowledgeType],
+    verbose: bool,
+) -> Result<()> {
+    let mut total_acknowledged = 0_i64;
+    let mut renew_indices = BTreeMap::<(String, i32, i64), usize>::new();
+    while args.max_messages < 0 || total_acknowledged < i64::from(args.max_messages) {
+        let records = tokio::select! {
+            _ = tokio::signal::ctrl_c() => {
+                output::write_json_line(&verifiable_event("shutdown_requested", serde_json::json!({})))?;
+                break;
+            }
+            result = consumer.poll(Duration::from_secs(5)) => result?,
+        };
+        if records.is_empty() {
+            continue;
+        }
+        let mut partitions = BTreeMap::<(String, i32), BTreeSet<i64>>::new();
+        let mut ack_counts = BTreeMap::<String, i64>::new();
+        for record in &records {
+            partitions
+                .entry((record.topic.clone(), record.partition))
+                .or_default()
+                .insert(record.offset);
+            if verbose {
+                output::write_json_line(&verifiable_event(
+                    "record_data",
+                    serde_json::json!({
+                        "key": record.key.as_deref().map(|value| String::from_utf8_lossy(value).into_owned()),
+                        "value": record.value.as_deref().map(|value| String::from_utf8_lossy(value).into_owned()),
+                        "topic": record.topic,
+                        "partition": record.partition,
+                        "offset": record.offset,
+                    }),
+                ))?;
+            }
+            if !pattern.is_empty() {
+                let key = (record.topic.clone(), record.partition, record.offset);
+                let pattern_len = i64::try_from(pattern.len())
+                    .map_err(|_| Error::Usage("--ack-pattern is too long".into()))?;
+                let initial_index = usize::try_from(record.offset.rem_euclid(pattern_len))
+                    .map_err(|_| Error::Usage("acknowledgement pattern index overflow".into()))?;
+                let index = *renew_indices.entry(key.clone()).or_insert(initial_index);
+                let kind = pattern[index];
+                consumer.acknowledge(record, kind).await?;
+                let name = format!("{kind:?}").to_ascii_uppercase();
+                *ack_counts.entry(name).or_default() += 1;
+                if kind == ShareAcknowledgeType::Renew {
+                    renew_indices.insert(key, (index + 1) % pattern.len());
+                } else {
+                    renew_indices.remove(&key);
+                }
+            }
+        }
+        let summaries = verifiable_partition_summaries(&partitions);
+        output::write_json_line(&verifiable_event(
+            "records_consumed",
+            serde_json::json!({"count": records.len(), "partitions": summaries}),
+        ))?;
+        let commit = consumer.commit_sync().await;
+        let success = commit.is_ok();
+        let mut acknowledgement_fields = serde_json::Map::from_iter([
+            (
+                "count".into(),
+                serde_json::Value::from(if success { records.len() } else { 0 }),
+            ),
+            (
+                "partitions".into(),
+                if success {
+                    serde_json::Value::from(summaries)
+                } else {
+                    serde_json::Value::Array(Vec::new())
+                },
+            ),
+            ("success".into(), serde_json::Value::from(success)),
+        ]);
+        if let Err(error) = &commit {
+            acknowledgement_fields.insert("error".into(), error.to_string().into());
+        }
+        if !pattern.is_empty() && success {
+            acknowledgement_fields
+                .insert("ackTypeCounts".into(), serde_json::to_value(ack_counts)?);
+        }
+        output::write_json_line(&verifiable_event(
+            "offsets_acknowledged",
+            serde_json::Value::Object(acknowledgement_fields),
+        ))?;
+        commit?;
+        total_acknowledged += i64::try_from(records.len())
+            .map_err(|_| Error::Config("too many consumed records".into()))?;
+    }
+    Ok(())
+}
+
+fn verifiable_partition_summaries(
+    partitions: &BTreeMap<(String, i32), BTreeSet<i64>>,
+) -> Vec<serde_json::Value> {
+    partitions
+        .iter()
+        .map(|((topic, partition), offsets)| {
+            serde_json::json!({
+                "topic": topic,
+                "partition": partition,
+                "count": offsets.len(),
+                "offsets": offsets,
+            })
+        })
+        .collect()
+}
+
+async fn consume_share_records(
+    consumer: &ShareConsumer,
+    formatter: &MessageFormatterOptions,
+    args: &ShareConsumeArgs,
+    acknowledgement: ShareAcknowledgeType,
+) -> Result<i64> {
+    let encodings = JsonEncodings::new(
+        args.key_encoding,
+        args.value_encoding,
+        args.header_encoding,
+        args.payload_dir.clone(),
+    )?;
+    let mut received = 0_i64;
+    let mut idle_since = Instant::now();
+    while should_consume_more(args.max_messages, received) {
+        let finite_idle_timeout = args
+            .timeout_ms
+            .filter(|timeout| *timeout != u64::MAX)
+            .map(Duration::from_millis);
+        let poll_timeout = finite_idle_timeout.map_or(Duration::from_secs(1), |timeout| {
+            timeout
+                .saturating_sub(idle_since.elapsed())
+                .min(Duration::from_secs(1))
+        });
+        let records = tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            result = consumer.poll(poll_timeout) => result?,
+        };
+        if records.is_empty() {
+            if finite_idle_timeout.is_some_and(|timeout| idle_since.elapsed() >= timeout) {
+                break;
+            }
+            continue;
+        }
+        idle_since = Instant::now();
+        for record in records {
+            if !should_consume_more(args.max_messages, received) {
+                break;
+            }
+            let written = match formatter
+                .load_schemas(record.key.as_deref(), record.value.as_deref())
+                .await
+            {
+                Ok(()) if args.json => write_share_json(&record, formatter, &encodings),
+                Ok(()) => write_formatted_share_message(&record, formatter),
+                Err(error) => Err(error),
+            };
+            match written {
+                Ok(()) => consumer.acknowledge(&record, acknowledgement).await?,
+                Err(error) if args.reject_message_on_error => {
+                    eprintln!("error processing message, rejecting it: {error}");
+                    consumer
+                        .acknowledge(&record, ShareAcknowledgeType::Reject)
+                        .await?;
+                }
+                Err(error) => return Err(error),
+            }
+            received += 1;
+        }
+        consumer.commit_sync().await?;
+    }
+    Ok(received)
+}
+
+fn share_i32_property(
+    properties: &std::collections::HashMap<String, String>,
+    key: &str,
+) -> Result<Option<i32>> {
+    properties
+        .get(key)
+        .map(|value| {
+            value
+                .parse::<i32>()
+                .map_err(|error| Error::Config(format!("invalid {key} value '{value}': {error}")))
+        })
+        .transpose()
+}
+
+fn share_duration_property(
+    properties: &std::collections::HashMap<String, String>,
+    key: &str,
+) -> Result<Option<Duration>> {
+    properties
+        .get(key)
+        .map(|value| {
+            value
+                .parse::<u64>()
+                .map(Duration::from_millis)
+                .map_err(|error| Error::Config(format!("invalid {key} value '{value}': {error}")))
+        })
+        .transpose()
+}
+
+fn write_share_json(
+    record: &krafka::consumer::ConsumerRecord,
+    formatter: &MessageFormatterOptions,
+    encodings: &JsonEncodings,
+) -> Result<()> {
+    let names = record
+        .headers
+        .iter()
+        .map(|(key, _)| String::from_utf8_lossy(key))
+        .collect::<Vec<_>>();
+    let headers = names
+        .iter()
+        .zip(&record.headers)
+        .map(|(name, (_, value))| (name.as_ref(), value.as_deref()))
+        .collect::<Vec<_>>();
+    let position = RecordPosition {
+        topic: &record.topic,
+        partition: record.partition,
+        offset: record.offset,
+        timestamp: Some(record.timestamp),
+    };
+    output::write_json_line(&encodings.record(
+        formatter,
+        &position,
+        record.key.as_deref(),
+        record.value.as_deref(),
+        &headers,
+    )?)
+}
+
+fn consumed_value(
+    bytes: Option<&[u8]>,
+    deserializer: NativeDeserializer,
+    formatter: &MessageFormatterOptions,
+) -> Result<Option<ConsumedValue>> {
+    bytes
+        .map(|bytes| match (deserializer, &formatter.schemas) {
+            (NativeDeserializer::SchemaRegistry, Some(schemas)) => {
+                schemas.decode(bytes).map(ConsumedValue::Decoded)
+            }
+            _ => Ok(ConsumedValue::Text(
+                String::from_utf8_lossy(bytes).into_owned(),
+            )),
+        })
+        .transpose()
+}
+
+fn consumer_include_pattern(include: &str) -> String {
+    format!("^({include})$")
+}
+
+fn should_consume_more(max_messages: Option<i32>, received: i64) -> bool {
+    match max_messages {
+        None | Some(-1) => true,
+        Some(max_messages) => received < i64::from(max_messages),
+    }
+}
+
+static CONSUMER_GROUP_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+/// Consumer group, offset, and isolation flags shared by commands that consume records.
+trait ConsumerArgs {
+    fn group(&self) -> Option<&str>;
+    fn properties(&self) -> &[String];
+    fn partition(&self) -> Option<i32>;
+    fn reads_from_beginning(&self) -> bool;
+    fn isolation_level(&self) -> Option<&str>;
+}
+
+impl ConsumerArgs for crate::cli::ConsumeArgs {
+    fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+
+    fn properties(&self) -> &[String] {
+        self.properties()
+    }
+
+    fn partition(&self) -> Option<i32> {
+        self.partition
+    }
+
+    fn reads_from_beginning(&self) -> bool {
+        self.from_beginning
+    }
+
+    fn isolation_level(&self) -> Option<&str> {
+        self.isolation_level.as_deref()
+    }
+}
+
+impl ConsumerArgs for BackupArgs {
+    fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+
+    fn properties(&self) -> &[String] {
+        &self.properties
+    }
+
+    fn partition(&self) -> Option<i32> {
+        None
+    }
+
+    fn reads_from_beginning(&self) -> bool {
+        true
+    }
+
+    fn isolation_level(&self) -> Option<&str> {
+        self.isolation_level.as_deref()
+    }
+}
+
+impl ConsumerArgs for MirrorArgs {
+    fn group(&self) -> Option<&str> {
+        self.group.as_deref()
+    }
+
+    fn properties(&self) -> &[String] {
+        &self.consumer_properties
+    }
+
+    fn partition(&self) -> Option<i32> {
+        None
+    }
+
+    fn reads_from_beginning(&self) -> bool {
+        true
+    }
+
+    fn isolation_level(&self) -> Option<&str> {
+        self.isolation_level.as_deref()
+    }
+}
+
+impl ConsumerArgs for SearchArgs {
+    fn group(&self) -> Option<&str> {
+        None
+    }
+
+    fn properties(&self) -> &[String] {
+        &self.properties
+    }
+
+    fn partition(&self) -> Option<i32> {
+        None
+    }
+
+    fn reads_from_beginning(&self) -> bool {
+        true
+    }
+
+    fn isolation_level(&self) -> Option<&str> {
+        self.isolation_level.as_deref()
+    }
+}
+
+fn configure_consumer(config: &mut rdkafka::ClientConfig, args: &impl ConsumerArgs) -> Result<()> {
+    let file_group = config.get("group.id").map(str::to_owned);
+    let inline = parse_pairs(args.properties())?;
+    let inline_group = inline
+        .iter()
+        .rev()
+        .find(|(key, _)| key == "group.id")
+        .map(|(_, value)| value.clone());
+    let groups = [args.group(), file_group.as_deref(), inline_group.as_deref()]
+        .into_iter()
+        .flatten()
+        .collect::<BTreeSet<_>>();
+    if groups.len() > 1 {
+        return Err(Error::Usage(format!(
+            "group ids supplied by --group, --command-config, and --command-property must match: {}",
+            groups
+                .into_iter()
+                .map(|group| format!("'{group}'"))
+                .collect::<Vec<_>>()
+                .join(", ")
+        )));
+    }
+    if args.partition().is_some() && !groups.is_empty() {
+        return Err(Error::Usage(
+            "--group/group.id and --partition cannot be specified together".into(),
+        ));
+    }
+
+    for (key, value) in inline {
+        config.set(key, value);
+    }
+    if config.get("client.id").is_none() {
+        config.set("client.id", "console-consumer");
+    }
+    if let Some(group) = args.group().or_else(|| config.get("group.id")) {
+        let group = group.to_owned();
+        config.set("group.id", group);
+    } else {
+        config.set("group.id", ephemeral_consumer_group());
+        if config.get("enable.auto.commit").is_none() {
+            config.set("enable.auto.commit", "false");
+        }
+    }
+
+    if args.reads_from_beginning() {
+        if config
+            .get("auto.offset.reset")
+            .is_some_and(|value| value != "earliest")
+        {
+            return Err(Error::Usage(
+                "--from-beginning conflicts with auto.offset.reset other than earliest".into(),
+            ));
+        }
+        config.set("auto.offset.reset", "earliest");
+    } else if config.get("auto.offset.reset").is_none() {
+        config.set("auto.offset.reset", "latest");
+    }
+    if let Some(isolation) = args.isolation_level() {
+        config.set("isolation.level", isolation);
+    } else if config.get("isolation.level").is_none() {
+        config.set("isolation.level", "read_uncommitted");
+    }
+    Ok(())
+}
+
+fn ephemeral_consumer_group() -> String {
+    format!(
+        "console-consumer-{}-{}",
+        process::id(),
+        CONSUMER_GROUP_SEQUENCE.fetch_add(1, Ordering::Relaxed)
+    )
+}
+
+fn consumer_offset(value: Option<&str>, from_beginning: bool) -> Result<Offset> {
+    match value {
+        Some(value) if value.eq_ignore_ascii_case("earliest") => Ok(Offset::Beginning),
+        Some(value) if value.eq_ignore_ascii_case("latest") => Ok(Offset::End),
+        Some(value) => value
+            .parse::<i64>()
+            .ok()
+            .filter(|offset| *offset >= 0)
+            .map(Offset::Offset)
+            .ok_or_else(|| {
+                Error::Usage(format!(
+                    "invalid offset '{value}'; expected earliest, latest, or a non-negative integer"
+                ))
+            }),
+        None if from_beginning => Ok(Offset::Beginning),
+        None => Ok(Offset::End),
+    }
+}
+
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "mirrors Kafka DefaultMessageFormatter's independent print switches"
+)]
+struct MessageFormatterOptions {
+    print_timestamp: bool,
+    print_partition: bool,
+    print_offset: bool,
+    print_delivery: bool,
+    print_epoch: bool,
+    print_headers: bool,
+    print_key: bool,
+    print_value: bool,
+    key_separator: Vec<u8>,
+    line_separator: Vec<u8>,
+    headers_separator: Vec<u8>,
+    null_literal: Vec<u8>,
+    key_deserializer: NativeDeserializer,
+    value_deserializer: NativeDeserializer,
+    headers_deserializer: NativeDeserializer,
+    schemas: Option<SchemaResolver>,
+    /// Replaces the fixed field layout when `--format-template` is given.
+    template: Option<MessageTemplate>,
+}
+
+impl MessageFormatterOptions {
+    /// Fetches the schemas a record's key and value need before they are formatted.
+    async fn load_schemas(&self, key: Option<&[u8]>, value: Option<&[u8]>) -> Result<()> {
+        let Some(schemas) = &self.schemas else {
+            return Ok(());
+        };
+        for (bytes, deserializer) in [
+            (key, self.key_deserializer),
+            (value, self.value_deserializer),
+        ] {
+            if deserializer == NativeDeserializer::SchemaRegistry {
+                schemas.load(bytes).await?;
+            }
+        }
+        Ok(())
+    }
+
+    fn display(&self, bytes: Option<&[u8]>, deserializer: NativeDeserializer) -> Result<Vec<u8>> {
+        match (bytes, deserializer, &self.schemas) {
+            (Some(bytes), NativeDeserializer::SchemaRegistry, Some(schemas)) => {
+                Ok(serde_json::to_vec(&schemas.decode(bytes)?)?)
+            }
+            _ => Ok(deserialize_for_display(
+                bytes,
+                deserializer,
+                &self.null_literal,
+            )),
+        }
+    }
+}
+
+trait FormatterArgs {
+    fn formatter(&self) -> &str;
+    fn key_deserializer(&self) -> Option<&str>;
+    fn value_deserializer(&self) -> Option<&str>;
+    fn print_key(&self) -> bool;
+    fn key_separator(&self) -> &str;
+    fn formatter_config(&self) -> Option<&Path>;
+    fn formatter_properties(&self) -> &[String];
+    fn schema_registry_url(&self) -> Option<&str>;
+    fn schema_dir(&self) -> Option<&Path>;
+    fn format_template(&self) -> Option<&str>;
+}
+
+impl FormatterArgs for crate::cli::ConsumeArgs {
+    fn formatter(&self) -> &str {
+        &self.formatter
+    }
+
+    fn key_deserializer(&self) -> Option<&str> {
+        self.key_deserializer.as_deref()
+    }
+
+    fn value_deserializer(&self) -> Option<&str> {
+        self.value_deserializer.as_deref()
+    }
+
+    fn print_key(&self) -> bool {
+        self.print_key
+    }
+
+    fn key_separator(&self) -> &str {
+        &self.key_separator
+    }
+
+    fn formatter_config(&self) -> Option<&Path> {
+        self.formatter_config.as_deref()
+    }
+
+    fn formatter_properties(&self) -> &[String] {
+        self.formatter_properties()
+    }
+
+    fn schema_registry_url(&self) -> Option<&str> {
+        self.schema_registry_url.as_deref()
+    }
+
+    fn schema_dir(&self) -> Option<&Path> {
+        self.schema_dir.as_deref()
+    }
+
+    fn format_template(&self) -> Option<&str> {
+        self.format_template.as_deref()
+    }
+}
+
+impl FormatterArgs for ShareConsumeArgs {
+    fn formatter(&self) -> &str {
+        &self.formatter
+    }
+
+    fn key_deserializer(&self) -> Option<&str> {
+        self.key_deserializer.as_deref()
+    }
+
+    fn value_deserializer(&self) -> Option<&str> {
+        self.value_deserializer.as_deref()
+    }
+
+    fn print_key(&self) -> bool {
+        self.print_key
+    }
+
+    fn key_separator(&self) -> &str {
+        &self.key_separator
+    }
+
+    fn formatter_config(&self) -> Option<&Path> {
+        self.formatter_config.as_deref()
+    }
+
+    fn formatter_properties(&self) -> &[String] {
+        self.formatter_properties()
+    }
+
+    fn schema_registry_url(&self) -> Option<&str> {
+        self.schema_registry_url.as_deref()
+    }
+
+    fn schema_dir(&self) -> Option<&Path> {
+        self.schema_dir.as_deref()
+    }
+
+    fn format_template(&self) -> Option<&str> {
+        self.format_template.as_deref()
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum NativeDeserializer {
+    #[default]
+    Raw,
+    Utf8String,
+    /// Confluent wire-format payloads decoded through Schema Registry.
+    SchemaRegistry,
+}
+
+fn native_deserializer(class: Option<&str>, field: &str) -> Result<NativeDeserializer> {
+    match class {
+        None | Some("") => Ok(NativeDeserializer::Raw),
+        Some("org.apache.kafka.common.serialization.StringDeserializer") => {
+            Ok(NativeDeserializer::Utf8String)
+        }
+        Some(
+            "io.confluent.kafka.serializers.KafkaAvroDeserializer"
+            | "io.confluent.kafka.serializers.protobuf.KafkaProtobufDeserializer"
+            | "io.confluent.kafka.serializers.json.KafkaJsonSchemaDeserializer",
+        ) => Ok(NativeDeserializer::SchemaRegistry),
+        Some(class) => Err(Error::Unsupported(format!(
+            "Java {field} deserializer class {class} cannot be loaded by the native client"
+        ))),
+    }
+}
+
+fn deserialize_for_display(
+    bytes: Option<&[u8]>,
+    deserializer: NativeDeserializer,
+    null_literal: &[u8],
+) -> Vec<u8> {
+    let bytes = bytes.unwrap_or(null_literal);
+    match deserializer {
+        // Present schema-framed payloads are decoded by MessageFormatterOptions::display.
+        NativeDeserializer::Raw | NativeDeserializer::SchemaRegistry => bytes.to_vec(),
+        NativeDeserializer::Utf8String => String::from_utf8_lossy(bytes).into_owned().into_bytes(),
+    }
+}
+
+fn is_utf8_encoding(value: &str) -> bool {
+    value
+        .chars()
+        .filter(|character| !matches!(character, '-' | '_'))
+        .collect::<String>()
+        .eq_ignore_ascii_case("UTF8")
+}
+
+fn message_formatter_options(args: &impl FormatterArgs) -> Result<MessageFormatterOptions> {
+    if args.formatter() != "org.apache.kafka.tools.consumer.DefaultMessageFormatter" {
+        return Err(Error::Unsupported(format!(
+            "Java formatter class {} cannot be loaded by the native client",
+            args.formatter()
+        )));
+    }
+    let properties = component_properties(args.formatter_config(), args.formatter_properties())?;
+    let value = |key: &str| properties.get(key);
+    let key_deserializer = native_deserializer(
+        value("key.deserializer")
+            .map(String::as_str)
+            .or_else(|| args.key_deserializer()),
+        "key",
+    )?;
+    let value_deserializer = native_deserializer(
+        value("value.deserializer")
+            .map(String::as_str)
+            .or_else(|| args.value_deserializer()),
+        "value",
+    )?;
+    let headers_deserializer =
+        native_deserializer(value("headers.deserializer").map(String::as_str), "headers")?;
+    if headers_deserializer == NativeDeserializer::SchemaRegistry {
+        return Err(Error::Unsupported(
+            "Schema Registry deserializers are not supported for headers".into(),
+        ));
+    }
+    let schemas = [key_deserializer, value_deserializer]
+        .contains(&NativeDeserializer::SchemaRegistry)
+        .then(|| {
+            SchemaResolver::new(
+                args.schema_registry_url()
+                    .or_else(|| value("schema.registry.url").map(String::as_str)),
+                value("basic.auth.user.info").map(String::as_str),
+                args.schema_dir(),
+            )
+        })
+        .transpose()?;
+    for (key, applies) in [
+        (
+            "deserializer.encoding",
+            key_deserializer == NativeDeserializer::Utf8String
+                || value_deserializer == NativeDeserializer::Utf8String,
+        ),
+        (
+            "key.deserializer.encoding",
+            key_deserializer == NativeDeserializer::Utf8String,
+        ),
+        (
+            "value.deserializer.encoding",
+            value_deserializer == NativeDeserializer::Utf8String,
+        ),
+    ] {
+        if applies && let Some(encoding) = value(key).filter(|encoding| !is_utf8_encoding(encoding))
+        {
+            return Err(Error::Unsupported(format!(
+                "StringDeserializer encoding {encoding} is not supported by the native client"
+            )));
+        }
+    }
+    let flag = |key: &str, default: bool| value(key).map_or(default, |value| bool_property(value));
+    Ok(MessageFormatterOptions {
+        print_timestamp: flag("print.timestamp", false),
+        print_partition: flag("print.partition", false),
+        print_offset: flag("print.offset", false),
+        print_delivery: flag("print.delivery", false),
+        print_epoch: flag("print.epoch", false),
+        print_headers: flag("print.headers", false),
+        print_key: flag("print.key", args.print_key()),
+        print_value: flag("print.value", true),
+        key_separator: value("key.separator")
+            .map_or_else(|| args.key_separator().as_bytes(), String::as_bytes)
+            .to_vec(),
+        line_separator: value("line.separator")
+            .map_or(b"\n".as_slice(), String::as_bytes)
+            .to_vec(),
+        headers_separator: value("headers.separator")
+            .map_or(b",".as_slice(), String::as_bytes)
+            .to_vec(),
+        null_literal: value("null.literal")
+            .map_or(b"null".as_slice(), String::as_bytes)
+            .to_vec(),
+        key_deserializer,
+        value_deserializer,
+        headers_deserializer,
+        schemas,
+        template: args
+            .format_template()
+            .map(MessageTemplate::parse)
+            .transpose()?,
+    })
+}
+
+fn formatted_message(
+    message: &rdkafka::message::BorrowedMessage<'_>,
+    options: &MessageFormatterOptions,
+) -> Result<Vec<u8>> {
+    if let Some(template) = &options.template {
+        return template.line(&TemplateRecord::from_message(message), options);
+    }
+    let mut fields = Vec::<Vec<u8>>::new();
+    if options.print_timestamp {
+        fields.push(match message.timestamp() {
+            rdkafka::Timestamp::NotAvailable => b"NO_TIMESTAMP".to_vec(),
+            rdkafka::Timestamp::CreateTime(timestamp) => {
+                format!("CreateTime:{timestamp}").into_bytes()
+            }
+            rdkafka::Timestamp::LogAppendTime(timestamp) => {
+                format!("LogAppendTime:{timestamp}").into_bytes()
+            }
+        });
+    }
+    if options.print_partition {
+        fields.push(format!("Partition:{}", message.partition()).into_bytes());
+    }
+    if options.print_offset {
+        fields.push(format!("Offset:{}", message.offset()).into_bytes());
+    }
+    if options.print_delivery {
+        fields.push(b"Delivery:NOT_PRESENT".to_vec());
+    }
+    if options.print_epoch {
+        fields.push(formatted_leader_epoch(ffi::message_leader_epoch(message)));
+    }
+    if options.print_headers {
+        fields.push(formatted_headers(message.headers(), options));
+    }
+    if options.print_key {
+        fields.push(options.display(message.key(), options.key_deserializer)?);
+    }
+    if options.print_value {
+        fields.push(options.display(message.payload(), options.value_deserializer)?);
+    }
+    let mut line = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        if index > 0 {
+            line.extend_from_slice(&options.key_separator);
+        }
+        line.extend_from_slice(field);
+    }
+    if options.print_value {
+        line.extend_from_slice(&options.line_separator);
+    }
+    Ok(line)
+}
+
+fn write_formatted_share_message(
+    record: &krafka::consumer::ConsumerRecord,
+    options: &MessageFormatterOptions,
+) -> Result<()> {
+    if let Some(template) = &options.template {
+        let line = template.line(&TemplateRecord::from_share_record(record), options)?;
+        io::stdout().lock().write_all(&line)?;
+        return Ok(());
+    }
+    let mut fields = Vec::<Vec<u8>>::new();
+    if options.print_timestamp {
+        let kind = if record.timestamp_type == 1 {
+            "LogAppendTime"
+        } else {
+            "CreateTime"
+        };
+        fields.push(format!("{kind}:{}", record.timestamp).into_bytes());
+    }
+    if options.print_partition {
+        fields.push(format!("Partition:{}", record.partition).into_bytes());
+    }
+    if options.print_offset {
+        fields.push(format!("Offset:{}", record.offset).into_bytes());
+    }
+    if options.print_delivery {
+        fields.push(record.delivery_count.map_or_else(
+            || b"Delivery:NOT_PRESENT".to_vec(),
+            |count| format!("Delivery:{count}").into_bytes(),
+        ));
+    }
+    if options.print_epoch {
+        fields.push(formatted_leader_epoch(record.leader_epoch));
+    }
+    if options.print_headers {
+        fields.push(formatted_share_headers(&record.headers, options));
+    }
+    if options.print_key {
+        fields.push(options.display(record.key.as_deref(), options.key_deserializer)?);
+    }
+    if options.print_value {
+        fields.push(options.display(record.value.as_deref(), options.value_deserializer)?);
+    }
+    let mut stdout = io::stdout().lock();
+    for (index, field) in fields.iter().enumerate() {
+        if index > 0 {
+            stdout.write_all(&options.key_separator)?;
+        }
+        stdout.write_all(field)?;
+    }
+    if options.print_value {
+        stdout.write_all(&options.line_separator)?;
+    }
+    Ok(())
+}
+
+/// A parsed `--format-template`: literal text interleaved with `{field}` placeholders.
+#[derive(Debug, PartialEq, Eq)]
+struct MessageTemplate(Vec<TemplatePart>);
+
+#[derive(Debug, PartialEq, Eq)]
+enum TemplatePart {
+    Literal(String),
+    Field(TemplateField),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum TemplateField {
+    Topic,
+    Partition,
+    Offset,
+    /// Epoch milliseconds, or RFC 3339 in UTC when `iso` is set.
+    Timestamp {
+        iso: bool,
+    },
+    Key,
+    Value,
+    Header(String),
+    Headers,
+    Epoch,
+    Delivery,
+}
+
+/// The record fields a template can reference, shared by classic and share consumers.
+struct TemplateRecord<'a> {
+    topic: &'a str,
+    partition: i32,
+    offset: i64,
+    timestamp: Option<i64>,
+    key: Option<&'a [u8]>,
+    value: Option<&'a [u8]>,
+    headers: Vec<(Cow<'a, str>, Option<&'a [u8]>)>,
+    leader_epoch: Option<i32>,
+    delivery_count: Option<i16>,
+}
+
+impl<'a> TemplateRecord<'a> {
+    fn from_message(message: &'a rdkafka::message::BorrowedMessage<'_>) -> Self {
+        Self {
+            topic: message.topic(),
+            partition: message.partition(),
+            offset: message.offset(),
+            timestamp: message.timestamp().to_millis(),
+            key: message.key(),
+            value: message.payload(),
+            headers: message
+                .headers()
+                .map(|headers| {
+                    headers
+                        .iter()
+                        .map(|header| (Cow::Borrowed(header.key), header.value))
+                        .collect()
+                })
+                .unwrap_or_default(),
+            leader_epoch: ffi::message_leader_epoch(message),
+            delivery_count: None,
+        }
+    }
+
+    fn from_share_record(record: &'a krafka::consumer::ConsumerRecord) -> Self {
+        Self {
+            topic: &record.topic,
+            partition: record.partition,
+            offset: record.offset,
+            timestamp: Some(record.timestamp),
+            key: record.key.as_deref(),
+            value: record.value.as_deref(),
+            headers: record
+                .headers
+                .iter()
+                .map(|(key, value)| (String::from_utf8_lossy(key), value.as_deref()))
+                .collect(),
+            leader_epoch: record.leader_epoch,
+            delivery_count: record.delivery_count,
+        }
+    }
+}
+
+impl MessageTemplate {
+    /// Parses `{field}` placeholders; `{{` and `}}` produce literal braces.
+    fn parse(template: &str) -> Result<Self> {
+        let mut parts = Vec::new();
+        let mut literal = String::new();
+        let mut characters = template.chars().peekable();
+        while let Some(character) = characters.next() {
+            match character {
+                '{' if characters.next_if_eq(&'{').is_some() => literal.push('{'),
+                '}' if characters.next_if_eq(&'}').is_some() => literal.push('}'),
+                '{' => {
+                    let mut name = String::new();
+                    loop {
+                        match characters.next() {
+                            Some('}') => break,
+                            Some(character) => name.push(character),
+                            None => {
+                                return Err(Error::Usage(format!(
+                                    "unclosed placeholder {{{name} in --format-template"
+                                )));
+                            }
+                        }
+                    }
+                    if !literal.is_empty() {
+                        parts.push(TemplatePart::Literal(std::mem::take(&mut literal)));
+                    }
+                    parts.push(TemplatePart::Field(TemplateField::parse(&name)?));
+                }
+                '}' => {
+                    return Err(Error::Usage(
+                        "unmatched } in --format-template; write }} for a literal brace".into(),
+                    ));
+                }
+                character => literal.push(character),
+            }
+        }
+        if !literal.is_empty() {
+            parts.push(TemplatePart::Literal(literal));
+        }
+        Ok(Self(parts))
+    }
+
+    fn render(
+        &self,
+        record: &TemplateRecord<'_>,
+        options: &MessageFormatterOptions,
+    ) -> Result<Vec<u8>> {
+        let mut line = Vec::new();
+        for part in &self.0 {
+            match part {
+                TemplatePart::Literal(text) => line.extend_from_slice(text.as_bytes()),
+                TemplatePart::Field(field) => line.extend(field.render(record, options)?),
+            }
+        }
+        Ok(line)
+    }
+
+    fn line(
+        &self,
+        record: &TemplateRecord<'_>,
+        options: &MessageFormatterOptions,
+    ) -> Result<Vec<u8>> {
+        let mut line = self.render(record, options)?;
+        line.extend_from_slice(&options.line_separator);
+        Ok(line)
+    }
+}
+
+impl TemplateField {
+    fn parse(name: &str) -> Result<Self> {
+        Ok(match name {
+            "topic" => Self::Topic,
+            "partition" => Self::Partition,
+            "offset" => Self::Offset,
+            "timestamp" | "timestamp:ms" => Self::Timestamp { iso: false },
+            "timestamp:iso" => Self::Timestamp { iso: true },
+            "key" => Self::Key,
+            "value" => Self::Value,
+            "headers" => Self::Headers,
+            "epoch" => Self::Epoch,
+            "delivery" => Self::Delivery,
+            _ => match name.strip_prefix("header.") {
+                Some(header) if !header.is_empty() => Self::Header(header.to_owned()),
+                _ => {
+                    return Err(Error::Usage(format!(
+                        "unknown --format-template field {{{name}}}; expected topic, partition, \
+                         offset, timestamp[:ms|:iso], key, value, headers, header.NAME, epoch, \
+                         or delivery"
+                    )));
+                }
+            },
+        })
+    }
+
+    /// Renders the field, substituting `null.literal` for absent values.
+    fn render(
+        &self,
+        record: &TemplateRecord<'_>,
+        options: &MessageFormatterOptions,
+    ) -> Result<Vec<u8>> {
+        let number = |value: Option<String>| {
+            value.map_or_else(|| options.null_literal.clone(), String::into_bytes)
+        };
+        Ok(match self {
+            Self::Topic => record.topic.as_bytes().to_vec(),
+            Self::Partition => record.partition.to_string().into_bytes(),
+            Self::Offset => record.offset.to_string().into_bytes(),
+            Self::Timestamp { iso: false } => {
+                number(record.timestamp.map(|timestamp| timestamp.to_string()))
+            }
+            Self::Timestamp { iso: true } => number(
+                record
+                    .timestamp
+                    .and_then(DateTime::<Utc>::from_timestamp_millis)
+                    .map(|timestamp| timestamp.to_rfc3339_opts(SecondsFormat::Millis, true)),
+            ),
+            Self::Key => options.display(record.key, options.key_deserializer)?,
+            Self::Value => options.display(record.value, options.value_deserializer)?,
+            Self::Header(name) => deserialize_for_display(
+                record
+                    .headers
+                    .iter()
+                    .find(|(key, _)| key == name)
+                    .and_then(|(_, value)| *value),
+                options.headers_deserializer,
+                &options.null_literal,
+            ),
+            Self::Headers => {
+                let mut result = Vec::new();
+                for (index, (key, value)) in record.headers.iter().enumerate() {
+                    if index > 0 {
+                        result.extend_from_slice(&options.headers_separator);
+                    }
+                    result.extend_from_slice(key.as_bytes());
+                    result.push(b':');
+                    result.extend_from_slice(&deserialize_for_display(
+                        *value,
+                        options.headers_deserializer,
+                        &options.null_literal,
+                    ));
+                }
+                result
+            }
+            Self::Epoch => number(record.leader_epoch.map(|epoch| epoch.to_string())),
+            Self::Delivery => number(record.delivery_count.map(|count| count.to_string())),
+        })
+    }
+}
+
+fn formatted_leader_epoch(epoch: Option<i32>) -> Vec<u8> {
+    epoch.map_or_else(
+        || b"Epoch:NOT_PRESENT".to_vec(),
+        |epoch| format!("Epoch:{epoch}").into_bytes(),
+    )
+}
+
+fn formatted_headers(
+    headers: Option<&rdkafka::message::BorrowedHeaders>,
+    options: &MessageFormatterOptions,
+) -> Vec<u8> {
+    let Some(headers) = headers.filter(|headers| headers.count() > 0) else {
+        return b"NO_HEADERS".to_vec();
+    };
+    let mut result = Vec::new();
+    for (index, header) in headers.iter().enumerate() {
+        if index > 0 {
+            result.extend_from_slice(&options.headers_separator);
+        }
+        result.extend_from_slice(header.key.as_bytes());
+        result.push(b':');
+        result.extend_from_slice(&deserialize_for_display(
+            header.value,
+            options.headers_deserializer,
+            &options.null_literal,
+        ));
+    }
+    result
+}
+
+fn formatted_share_headers(
+    headers: &[(bytes::Bytes, Option<bytes::Bytes>)],
+    options: &MessageFormatterOptions,
+) -> Vec<u8> {
+    if headers.is_empty() {
+        return b"NO_HEADERS".to_vec();
+    }
+    let mut result = Vec::new();
+    for (index, (key, value)) in headers.iter().enumerate() {
+        if index > 0 {
+            result.extend_from_slice(&options.headers_separator);
+        }
+        result.extend_from_slice(key);
+        result.push(b':');
+        result.extend_from_slice(&deserialize_for_display(
+            value.as_deref(),
+            options.headers_deserializer,
+            &options.null_literal,
+        ));
+    }
+    result
+}
+
+async fn next_consumer_message<'a>(
+    stream: &mut (
+             impl futures::Stream<
+        Item = std::result::Result<
+            rdkafka::message::BorrowedMessage<'a>,
+            rdkafka::error::KafkaError,
+        >,
+    > + Unpin
+         ),
+    timeout_ms: Option<u64>,
+) -> Result<
+    Option<std::result::Result<rdkafka::message::BorrowedMessage<'a>, rdkafka::error::KafkaError>>,
+> {
+    if let Some(timeout_ms) = timeout_ms.filter(|value| *value != u64::MAX) {
+        tokio::time::timeout(Duration::from_millis(timeout_ms), stream.next())
+            .await
+            .map_or_else(|_| Ok(None), Ok)
+    } else {
+        Ok(stream.next().await)
+    }
+}
+
+fn apply_client_properties(
+    config: &mut rdkafka::ClientConfig,
+    properties: &[String],
+) -> Result<()> {
+    for (key, value) in parse_pairs(properties)? {
+        config.set(key, value);
+    }
+    Ok(())
+}
+
+async fn groups(
+    config: &rdkafka::ClientConfig,
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    timeout: Duration,
+    format: OutputFormat,
+    action: GroupAction,
+    verbose: bool,
+) -> Result<()> {
+    match action {
+        GroupAction::ValidateRegex { .. } => Err(Error::Usage(
+            "validate-regex must be handled before client configuration".into(),
+        )),
+        GroupAction::List { state, group_type } => {
+            list_groups(
+                config,
+                bootstrap,
+                command_config,
+                timeout,
+                format,
+                state.as_deref(),
+                group_type.as_deref(),
+            )
+            .await
+        }
+        GroupAction::Describe {
+            group,
+            all_groups,
+            members,
+            state,
+            offsets: _,
+        } => {
+            let mode = if members {
+                GroupDescribeMode::Members
+            } else if state {
+                GroupDescribeMode::State
+            } else {
+                GroupDescribeMode::Offsets
+            };
+            let groups = resolve_group_names(config, timeout, &group, all_groups)?;
+            let protocol = GroupProtocolContext {
+                bootstrap,
+                command_config,
+            };
+            describe_group_details(config, protocol, timeout, format, &groups, mode, verbose).await
+        }
+        GroupAction::Delete {
+            group,
+            all_groups,
+            execute,
+        } => {
+            let groups = resolve_group_names(config, timeout, &group, all_groups)?;
+            if groups.is_empty() {
+                return Err(Error::Usage("no consumer groups matched".into()));
+            }
+            if !execute {
+                let rows = groups
+                    .into_iter()
+                    .map(|group| GroupDeleteRow {
+                        group,
+                        status: "PREVIEW".into(),
+                        error: None,
+                    })
+                    .collect::<Vec<_>>();
+                return write_group_delete_rows(format, &rows);
+            }
+            let names = groups.iter().map(String::as_str).collect::<Vec<_>>();
+            let results = admin(config)?
+                .delete_groups(&names, &AdminOptions::new())
+                .await?;
+            let failures = results.iter().filter(|result| result.is_err()).count();
+            let rows = results
+                .into_iter()
+                .map(|result| match result {
+                    Ok(group) => GroupDeleteRow {
+                        group,
+                        status: "DELETED".into(),
+                        error: None,
+                    },
+                    Err((group, error)) => GroupDeleteRow {
+                        group,
+                        status: "FAILED".into(),
+                        error: Some(error.to_string()),
+                    },
+                })
+                .collect::<Vec<_>>();
+            write_group_delete_rows(format, &rows)?;
+            if failures == 0 {
+                Ok(())
+            } else {
+                Err(Error::Partial {
+                    failed: failures,
+                    total: names.len(),
+                })
+            }
+        }
+        GroupAction::ResetOffsets(args) => reset_offsets(config, timeout, format, &args),
+        GroupAction::DeleteOffsets {
+            group,
+            topic,
+            execute,
+        } => delete_group_offsets_command(config, timeout, format, &group, &topic, execute),
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct AllGroupRow {
+    group: String,
+    group_type: String,
+    protocol: String,
+}
+
+async fn list_all_groups(
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    timeout: Duration,
+    format: OutputFormat,
+    action: AllGroupsAction,
+) -> Result<()> {
+    let AllGroupsAction::List {
+        group_type,
+        protocol,
+        consumer,
+        share,
+        streams,
+    } = action;
+    let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
+    let groups = client.list_consumer_groups().await?;
+    drop(client);
+    let mut rows = groups
+        .into_iter()
+        .map(|group| AllGroupRow {
+            group: group.group_id,
+            group_type: group
+                .group_type
+                .as_ref()
+                .map_or_else(String::new, |kind| group_type_label(&kind.to_string())),
+            protocol: group.protocol_type,
+        })
+        .filter(|row| {
+            all_group_matches(
+                row,
+                group_type,
+                protocol.as_deref(),
+                consumer,
+                share,
+                streams,
+            )
+        })
+        .collect::<Vec<_>>();
+    rows.sort_by(|left, right| left.group.cmp(&right.group));
+    output::write_value(format, "all-groups.list", &rows, |rows| {
+        output::table(
+            ["GROUP", "TYPE", "PROTOCOL"],
+            rows.iter().map(|row| {
+                [
+                    row.group.clone(),
+                    row.group_type.clone(),
+                    row.protocol.clone(),
+                ]
+            }),
+        )
+    })
+}
+
+fn all_group_matches(
+    row: &AllGroupRow,
+    group_type: Option<AllGroupType>,
+    protocol: Option<&str>,
+    consumer: bool,
+    share: bool,
+    streams: bool,
+) -> bool {
+    let type_matches = group_type.is_none_or(|requested| {
+        row.group_type.eq_ignore_ascii_case(match requested {
+            AllGroupType::Classic => "classic",
+            AllGroupType::Consumer => "consumer",
+            AllGroupType::Share => "share",
+            AllGroupType::Streams => "streams",
+        })
+    });
+    let protocol_matches = protocol.is_none_or(|requested| row.protocol == requested);
+    if group_type.is_some() || protocol.is_some() {
+        return type_matches && protocol_matches;
+    }
+    if consumer {
+        return row.protocol == "consumer"
+            || row.protocol.is_empty()
+            || row.group_type.eq_ignore_ascii_case("consumer");
+    }
+    if share {
+        return row.group_type.eq_ignore_ascii_case("share");
+    }
+    if streams {
+        return row.group_type.eq_ignore_ascii_case("streams");
+    }
+    true
+}
+
+fn group_type_label(group_type: &str) -> String {
+    match group_type.to_ascii_lowercase().as_str() {
+        "classic" => "Classic".into(),
+        "consumer" => "Consumer".into(),
+        "share" => "Share".into(),
+        "streams" => "Streams".into(),
+        _ => group_type.to_owned(),
+    }
+}
+
+#[derive(Debug)]
+struct ShareGroupDescriptionWithCoordinator {
+    coordinator_id: i32,
+    coordinator: String,
+    description: krafka::protocol::ShareGroupDescription,
+}
+
+#[derive(Debug, Serialize)]
+struct ShareGroupListRow {
+    group: String,
+    state: Option<String>,
+}
+
+#[derive(Debug)]
+struct StreamsGroupDescriptionWithCoordinator {
+    coordinator_id: i32,
+    coordinator: String,
+    description: StreamsGroupDescription,
+}
+
+#[derive(Debug)]
+struct StreamsGroupDescription {
+    group_id: String,
+    group_state: String,
+    group_epoch: i32,
+    assignment_epoch: i32,
+    topology: Option<StreamsTopology>,
+    members: Vec<StreamsGroupMember>,
+    topology_description: Option<StreamsTopologyDescription>,
+    topology_description_status: Option<i8>,
+    assignor: Option<String>,
+}
+
+#[derive(Debug)]
+struct StreamsTopology {
+    subtopologies: Vec<StreamsSubtopology>,
+}
+
+#[derive(Debug)]
+struct StreamsSubtopology {
+    id: String,
+    source_topics: Vec<String>,
+    repartition_source_topics: Vec<String>,
+    state_changelog_topics: Vec<String>,
+}
+
+#[derive(Debug)]
+struct StreamsGroupMember {
+    member_id: String,
+    member_epoch: i32,
+    client_id: String,
+    topology_epoch: i32,
+    process_id: String,
+    assignment: StreamsTaskAssignment,
+    target_assignment: StreamsTaskAssignment,
+    is_classic: bool,
+}
+
+#[derive(Debug, Default)]
+struct StreamsTaskAssignment {
+    active: Vec<StreamsTaskIds>,
+    standby: Vec<StreamsTaskIds>,
+    warmup: Vec<StreamsTaskIds>,
+}
+
+#[derive(Debug)]
+struct StreamsTaskIds {
+    subtopology_id: String,
+    partitions: Vec<i32>,
+}
+
+#[derive(Debug)]
+struct StreamsTopologyDescription {
+    subtopologies: Vec<StreamsTopologyDescriptionSubtopology>,
+    global_stores: Vec<StreamsTopologyGlobalStore>,
+}
+
+#[derive(Debug)]
+struct StreamsTopologyDescriptionSubtopology {
+    id: String,
+    nodes: Vec<StreamsTopologyNode>,
+}
+
+#[derive(Debug)]
+struct StreamsTopologyGlobalStore {
+    source: StreamsTopologyNode,
+    processor: StreamsTopologyNode,
+}
+
+#[derive(Debug)]
+struct StreamsTopologyNode {
+    name: String,
+    node_type: i8,
+    source_topics: Vec<String>,
+    sink_topic: Option<String>,
+    stores: Vec<String>,
+    successors: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct StreamsGroupStateRow {
+    group: String,
+    coordinator: String,
+    coordinator_id: i32,
+    assignor: String,
+    state: String,
+    group_epoch: Option<i32>,
+    assignment_epoch: Option<i32>,
+    members: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct StreamsGroupMemberRow {
+    group: String,
+    member: String,
+    process: String,
+    client_id: String,
+    assignments: String,
+    member_protocol: Option<String>,
+    member_epoch: Option<i32>,
+    topology_epoch: Option<i32>,
+    assignment_epoch: Option<i32>,
+}
+
+#[derive(Debug, Serialize)]
+struct StreamsGroupOffsetRow {
+    group: String,
+    topic: String,
+    partition: i32,
+    current_offset: Option<i64>,
+    leader_epoch: Option<i32>,
+    log_end_offset: i64,
+    lag: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct StreamsTopologyRow {
+    group: String,
+    scope: String,
+    topology: String,
+    node: String,
+    node_type: String,
+    source_topics: String,
+    sink_topic: Option<String>,
+    stores: String,
+    successors: String,
+}
+
+async fn share_group_ids(client: &krafka::admin::AdminClient) -> Result<Vec<String>> {
+    group_ids_by_type(client, "share").await
+}
+
+async fn group_ids_by_type(
+    client: &krafka::admin::AdminClient,
+    expected_type: &str,
+) -> Result<Vec<String>> {
+    let mut groups = client
+        .list_consumer_groups()
+        .await?
+        .into_iter()
+        .filter(|group| {
+            group
+                .group_type
+                .as_ref()
+                .is_some_and(|kind| kind.to_string().eq_ignore_ascii_case(expected_type))
+        })
+        .map(|group| group.group_id)
+        .collect::<Vec<_>>();
+    groups.sort();
+    Ok(groups)
+}
+
+async fn list_streams_groups(
+    client: &krafka::admin::AdminClient,
+    format: OutputFormat,
+    state: Option<&str>,
+) -> Result<()> {
+    let group_ids = group_ids_by_type(client, "streams").await?;
+    let rows = if let Some(state_filter) = state {
+        let states = if state_filter.is_empty() {
+            BTreeSet::new()
+        } else {
+            parse_streams_group_states(state_filter)?
+        };
+        describe_streams_groups(client, &group_ids, false, false)
+            .await?
+            .into_iter()
+            .filter(|group| states.is_empty() || states.contains(&group.description.group_state))
+            .map(|group| ShareGroupListRow {
+                group: group.description.group_id,
+                state: Some(group.description.group_state),
+            })
+            .collect::<Vec<_>>()
+    } else {
+        group_ids
+            .into_iter()
+            .map(|group| ShareGroupListRow { group, state: None })
+            .collect()
+    };
+    output::write_value(format, "streams-groups.list", &rows, |rows| {
+        if rows.iter().any(|row| row.state.is_some()) {
+            output::table(
+                ["GROUP", "STATE"],
+                rows.iter().map(|row| {
+                    [
+                        row.group.clone(),
+                        row.state.as_deref().unwrap_or("-").to_owned(),
+                    ]
+                }),
+            )
+        } else {
+            output::table(["GROUP"], rows.iter().map(|row| [row.group.clone()]))
+        }
+    })
+}
+
+fn parse_streams_group_states(value: &str) -> Result<BTreeSet<String>> {
+    value
+        .split(',')
+        .map(str::trim)
+        .map(|state| match state.to_ascii_lowercase().as_str() {
+            "empty" => Ok("Empty".into()),
+            "notready" => Ok("NotReady".into()),
+            "stable" => Ok("Stable".into()),
+            "assigning" => Ok("Assigning".into()),
+            "reconciling" => Ok("Reconciling".into()),
+            "dead" => Ok("Dead".into()),
+            _ => Err(Error::Usage(format!(
+                "invalid Streams group state '{state}'; expected Empty, NotReady, Stable, Assigning, Reconciling, or Dead"
+            ))),
+        })
+        .collect()
+}
+
+fn streams_tasks(tasks: &[StreamsTaskIds], label: &str) -> String {
+    tasks
+        .iter()
+        .map(|task| {
+            let partitions = task
+                .partitions
+                .iter()
+                .map(i32::to_string)
+                .collect::<Vec<_>>()
+                .join(",");
+            format!("{label}: {}:[{partitions}]", task.subtopology_id)
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+fn streams_assignment(
+    assignment: &StreamsTaskAssignment,
+    target: Option<&StreamsTaskAssignment>,
+) -> String {
+    let mut values = [
+        streams_tasks(&assignment.active, "ACTIVE"),
+        streams_tasks(&assignment.standby, "STANDBY"),
+        streams_tasks(&assignment.warmup, "WARMUP"),
+    ]
+    .into_iter()
+    .filter(|value| !value.is_empty())
+    .collect::<Vec<_>>();
+    if let Some(target) = target {
+        values.extend(
+            [
+                streams_tasks(&target.active, "TARGET-ACTIVE"),
+                streams_tasks(&target.standby, "TARGET-STANDBY"),
+                streams_tasks(&target.warmup, "TARGET-WARMUP"),
+            ]
+            .into_iter()
+            .filter(|value| !value.is_empty()),
+        );
+    }
+    values.join("; ")
+}
+
+fn write_streams_group_states(
+    format: OutputFormat,
+    descriptions: Vec<StreamsGroupDescriptionWithCoordinator>,
+    verbose: bool,
+) -> Result<()> {
+    let rows = descriptions
+        .into_iter()
+        .map(|group| StreamsGroupStateRow {
+            group: group.description.group_id,
+            coordinator: group.coordinator,
+            coordinator_id: group.coordinator_id,
+            assignor: group.description.assignor.unwrap_or_default(),
+            state: group.description.group_state,
+            group_epoch: verbose.then_some(group.description.group_epoch),
+            assignment_epoch: verbose.then_some(group.description.assignment_epoch),
+            members: group.description.members.len(),
+        })
+        .collect::<Vec<_>>();
+    output::write_value(format, "streams-groups.describe.state", &rows, |rows| {
+        if verbose {
+            output::table(
+                [
+                    "GROUP",
+                    "COORDINATOR (ID)",
+                    "ASSIGNOR",
+                    "STATE",
+                    "GROUP-EPOCH",
+                    "TARGET-ASSIGNMENT-EPOCH",
+                    "#MEMBERS",
+                ],
+                rows.iter().map(|row| {
+                    [
+                        row.group.clone(),
+                        format!("{} ({})", row.coordinator, row.coordinator_id),
+                        row.assignor.clone(),
+                        row.state.clone(),
+                        row.group_epoch
+                            .map_or_else(|| "-".into(), |v| v.to_string()),
+                        row.assignment_epoch
+                            .map_or_else(|| "-".into(), |v| v.to_string()),
+                        row.members.to_string(),
+                    ]
+                }),
+            )
+        } else {
+            output::table(
+                ["GROUP", "COORDINATOR (ID)", "ASSIGNOR", "STATE", "#MEMBERS"],
+                rows.iter().map(|row| {
+                    [
+                        row.group.clone(),
+                        format!("{} ({})", row.coordinator, row.coordinator_id),
+                        row.assignor.clone(),
+                        row.state.clone(),
+                        row.members.to_string(),
+                    ]
+                }),
+            )
+        }
+    })
+}
+
+fn write_streams_group_members(
+    format: OutputFormat,
+    descriptions: Vec<StreamsGroupDescriptionWithCoordinator>,
+    verbose: bool,
+) -> Result<()> {
+    let rows = descriptions
+        .into_iter()
+        .flat_map(|group| {
+            let group_id = group.description.group_id;
+            let assignment_epoch = group.description.assignment_epoch;
+            group.description.members.into_iter().map(move |member| {
+                let target = verbose.then_some(&member.target_assignment);
+                StreamsGroupMemberRow {
+                    group: group_id.clone(),
+                    member: member.member_id,
+                    process: member.process_id,
+                    client_id: member.client_id,
+                    assignments: streams_assignment(&member.assignment, target),
+                    member_protocol: verbose.then(|| {
+                        if member.is_classic {
+                            "classic"
+                        } else {
+                            "streams"
+                        }
+                        .into()
+                    }),
+                    member_epoch: verbose.then_some(member.member_epoch),
+                    topology_epoch: verbose.then_some(member.topology_epoch),
+                    assignment_epoch: verbose.then_some(assignment_epoch),
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    output::write_value(format, "streams-groups.describe.members", &rows, |rows| {
+        if verbose {
+            output::table(
                 [
                     "GROUP",
                     "TARGET-ASSIGNMENT-EPOCH",
@@ -10055,6 +13542,10 @@ fn metadata_version_level(level: i16) -> Result<MetadataVersionSpec> {
         .ok_or_else(|| Error::Usage(format!("unknown metadata.version {level}")))
 }
 
+#[expect(
+    clippy::collapsible_match,
+    reason = "each feature's default reads as its own threshold table"
+)]
 fn feature_default_level(feature: &str, metadata_level: i16) -> i16 {
     match feature {
         "kraft.version" => i16::from(metadata_level >= 21),
@@ -13107,89 +16598,309 @@ async fn describe_protocol_configs(
     command_config: Option<&Path>,
     timeout: Duration,
     format: OutputFormat,
-    output_command: &str,
-    kind: ConfigEntityType,
-    name: Option<String>,
+    output_command: &str,
+    kind: ConfigEntityType,
+    name: Option<String>,
+    all: bool,
+) -> Result<()> {
+    let resource_type = protocol_config_resource_type(kind)
+        .ok_or_else(|| Error::Usage("invalid protocol config resource".into()))?;
+    let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
+    let names = if let Some(name) = name {
+        vec![name]
+    } else if matches!(kind, ConfigEntityType::ClientMetrics) {
+        client.list_client_metrics_resources().await?
+    } else if matches!(kind, ConfigEntityType::Broker) {
+        client
+            .describe_cluster()
+            .await?
+            .brokers
+            .into_iter()
+            .map(|broker| broker.broker_id.to_string())
+            .collect()
+    } else {
+        return Err(Error::Usage(
+            "broker-logger describe requires --entity-name".into(),
+        ));
+    };
+    let routing_name = names.first().map_or("", String::as_str);
+    let (broker_id, address) = protocol_config_broker(&client, kind, routing_name).await?;
+    let connection = client
+        .pool()
+        .get_connection_by_id(broker_id, &address)
+        .await?;
+    let request = DescribeConfigsRequest {
+        resources: names
+            .iter()
+            .map(|name| DescribeConfigsResource {
+                resource_type,
+                resource_name: name.clone(),
+                config_names: None,
+            })
+            .collect(),
+        include_synonyms: all,
+        include_documentation: false,
+    };
+    let version = connection
+        .negotiate_api_version(
+            ApiKey::DescribeConfigs,
+            versions::DESCRIBE_CONFIGS_MAX,
+            versions::DESCRIBE_CONFIGS_MIN,
+        )
+        .await
+        .ok_or_else(|| Error::Unsupported("broker does not support DescribeConfigs".into()))?;
+    let mut response = connection
+        .send_request(ApiKey::DescribeConfigs, version, |buffer| {
+            request.encode_versioned(version, buffer)
+        })
+        .await?;
+    drop(connection);
+    drop(client);
+    let response =
+        krafka::protocol::DescribeConfigsResponse::decode_versioned(version, &mut response)?;
+    let mut rows = Vec::new();
+    for resource in response.results {
+        if !resource.error_code.is_ok() {
+            return Err(Error::Config(
+                resource
+                    .error_message
+                    .unwrap_or_else(|| format!("{:?}", resource.error_code)),
+            ));
+        }
+        rows.extend(resource.configs.into_iter().filter_map(|entry| {
+            (all || protocol_dynamic_config_source(kind, entry.config_source)).then(|| {
+                ConfigDescriptionRow {
+                    entity_type: config_entity_type_name(kind).into(),
+                    entity_name: resource.resource_name.clone(),
+                    name: entry.name,
+                    value: entry.value,
+                    source: entry.config_source.to_string(),
+                    sensitive: entry.is_sensitive,
+                }
+            })
+        }));
+    }
+    output::write_value(format, output_command, &rows, |rows| {
+        output::table(
+            [
+                "ENTITY_TYPE",
+                "ENTITY_NAME",
+                "NAME",
+                "VALUE",
+                "SOURCE",
+                "SENSITIVE",
+            ],
+            rows.iter().map(|row| {
+                [
+                    row.entity_type.clone(),
+                    row.entity_name.clone(),
+                    row.name.clone(),
+                    row.value.as_deref().unwrap_or("null").to_owned(),
+                    row.source.clone(),
+                    row.sensitive.to_string(),
+                ]
+            }),
+        )
+    })
+}
+
+const fn protocol_dynamic_config_source(kind: ConfigEntityType, source: i8) -> bool {
+    matches!(
+        (kind, source),
+        (ConfigEntityType::Broker, 3)
+            | (ConfigEntityType::BrokerLogger, 6)
+            | (ConfigEntityType::ClientMetrics, 7)
+    )
+}
+
+#[expect(
+    clippy::too_many_arguments,
+    reason = "arguments mirror a Kafka IncrementalAlterConfigs resource and command context"
+)]
+async fn alter_protocol_config(
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    timeout: Duration,
+    format: OutputFormat,
+    output_command: &str,
+    kind: ConfigEntityType,
+    name: &str,
+    add: &[(String, String)],
+    delete: &[String],
+) -> Result<()> {
+    let resource_type = protocol_config_resource_type(kind)
+        .ok_or_else(|| Error::Usage("invalid protocol config resource".into()))?;
+    let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
+    let (broker_id, address) = protocol_config_broker(&client, kind, name).await?;
+    let connection = client
+        .pool()
+        .get_connection_by_id(broker_id, &address)
+        .await?;
+    let request = IncrementalAlterConfigsRequest {
+        resources: vec![IncrementalAlterConfigsResource {
+            resource_type,
+            resource_name: name.into(),
+            configs: add
+                .iter()
+                .map(|(key, value)| AlterableConfig {
+                    name: key.clone(),
+                    config_operation: AlterConfigOp::Set,
+                    value: Some(value.clone()),
+                })
+                .chain(delete.iter().map(|key| AlterableConfig {
+                    name: key.clone(),
+                    config_operation: AlterConfigOp::Delete,
+                    value: None,
+                }))
+                .collect(),
+        }],
+        validate_only: false,
+    };
+    let version = connection
+        .negotiate_api_version(
+            ApiKey::IncrementalAlterConfigs,
+            versions::INCREMENTAL_ALTER_CONFIGS_MAX,
+            versions::INCREMENTAL_ALTER_CONFIGS_MIN,
+        )
+        .await
+        .ok_or_else(|| {
+            Error::Unsupported("broker does not support IncrementalAlterConfigs".into())
+        })?;
+    let mut response = connection
+        .send_request(ApiKey::IncrementalAlterConfigs, version, |buffer| {
+            request.encode_versioned(version, buffer)
+        })
+        .await?;
+    drop(connection);
+    drop(client);
+    let response = krafka::protocol::IncrementalAlterConfigsResponse::decode_versioned(
+        version,
+        &mut response,
+    )?;
+    let rows = response
+        .results
+        .into_iter()
+        .map(|result| MutationRow {
+            resource: format!("{}:{}", config_entity_type_name(kind), result.resource_name),
+            status: if result.error_code.is_ok() {
+                "ALTERED"
+            } else {
+                "FAILED"
+            }
+            .into(),
+            error: (!result.error_code.is_ok()).then(|| {
+                result
+                    .error_message
+                    .unwrap_or_else(|| format!("{:?}", result.error_code))
+            }),
+        })
+        .collect::<Vec<_>>();
+    let failures = rows.iter().filter(|row| row.error.is_some()).count();
+    write_mutation_rows(format, output_command, &rows)?;
+    if failures == 0 {
+        Ok(())
+    } else {
+        Err(Error::Partial {
+            failed: failures,
+            total: rows.len(),
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ConfigChangeRow<'a> {
+    operation: &'static str,
+    name: &'a str,
+    value: Option<&'a str>,
+}
+
+fn config_change_preview(
+    format: OutputFormat,
+    output_command: &str,
+    pairs: &[(String, String)],
+    delete: &[String],
+) -> Result<()> {
+    let rows = pairs
+        .iter()
+        .map(|(key, value)| ConfigChangeRow {
+            operation: "SET",
+            name: key,
+            value: Some(value.as_str()),
+        })
+        .chain(delete.iter().map(|key| ConfigChangeRow {
+            operation: "DELETE",
+            name: key,
+            value: None,
+        }))
+        .collect::<Vec<_>>();
+    output::write_value(format, output_command, &rows, |rows| {
+        output::table(
+            ["OPERATION", "NAME", "VALUE"],
+            rows.iter().map(|row| {
+                [
+                    row.operation.to_owned(),
+                    row.name.to_owned(),
+                    row.value.unwrap_or("-").to_owned(),
+                ]
+            }),
+        )
+    })
+}
+
+#[derive(Debug, Serialize)]
+struct ConfigDescriptionRow {
+    entity_type: String,
+    entity_name: String,
+    name: String,
+    value: Option<String>,
+    source: String,
+    sensitive: bool,
+}
+
+async fn describe_resource_configs(
+    config: &rdkafka::ClientConfig,
+    timeout: Duration,
+    format: OutputFormat,
+    entity_type: ConfigEntityType,
+    entity_name: Option<String>,
     all: bool,
 ) -> Result<()> {
-    let resource_type = protocol_config_resource_type(kind)
-        .ok_or_else(|| Error::Usage("invalid protocol config resource".into()))?;
-    let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
-    let names = if let Some(name) = name {
-        vec![name]
-    } else if matches!(kind, ConfigEntityType::ClientMetrics) {
-        client.list_client_metrics_resources().await?
-    } else if matches!(kind, ConfigEntityType::Broker) {
-        client
-            .describe_cluster()
-            .await?
-            .brokers
-            .into_iter()
-            .map(|broker| broker.broker_id.to_string())
-            .collect()
-    } else {
-        return Err(Error::Usage(
-            "broker-logger describe requires --entity-name".into(),
-        ));
-    };
-    let routing_name = names.first().map_or("", String::as_str);
-    let (broker_id, address) = protocol_config_broker(&client, kind, routing_name).await?;
-    let connection = client
-        .pool()
-        .get_connection_by_id(broker_id, &address)
-        .await?;
-    let request = DescribeConfigsRequest {
-        resources: names
-            .iter()
-            .map(|name| DescribeConfigsResource {
-                resource_type,
-                resource_name: name.clone(),
-                config_names: None,
-            })
-            .collect(),
-        include_synonyms: all,
-        include_documentation: false,
+    let names = match entity_name {
+        Some(name) => vec![name],
+        None => config_entity_names(config, timeout, entity_type)?,
     };
-    let version = connection
-        .negotiate_api_version(
-            ApiKey::DescribeConfigs,
-            versions::DESCRIBE_CONFIGS_MAX,
-            versions::DESCRIBE_CONFIGS_MIN,
+    let specifiers = names
+        .iter()
+        .map(|name| resource(entity_type, name))
+        .collect::<Result<Vec<_>>>()?;
+    let results = admin(config)?
+        .describe_configs(
+            &specifiers,
+            &AdminOptions::new().request_timeout(Some(timeout)),
         )
-        .await
-        .ok_or_else(|| Error::Unsupported("broker does not support DescribeConfigs".into()))?;
-    let mut response = connection
-        .send_request(ApiKey::DescribeConfigs, version, |buffer| {
-            request.encode_versioned(version, buffer)
-        })
         .await?;
-    drop(connection);
-    drop(client);
-    let response =
-        krafka::protocol::DescribeConfigsResponse::decode_versioned(version, &mut response)?;
-    let mut rows = Vec::new();
-    for resource in response.results {
-        if !resource.error_code.is_ok() {
-            return Err(Error::Config(
-                resource
-                    .error_message
-                    .unwrap_or_else(|| format!("{:?}", resource.error_code)),
-            ));
-        }
-        rows.extend(resource.configs.into_iter().filter_map(|entry| {
-            (all || protocol_dynamic_config_source(kind, entry.config_source)).then(|| {
-                ConfigDescriptionRow {
-                    entity_type: config_entity_type_name(kind).into(),
-                    entity_name: resource.resource_name.clone(),
+    let resources = results
+        .into_iter()
+        .map(|item| item.map_err(|code| Error::Config(code.to_string())))
+        .collect::<Result<Vec<_>>>()?;
+    let kind = config_entity_type_name(entity_type);
+    let rows = resources
+        .into_iter()
+        .flat_map(|resource| {
+            let entity_name = owned_resource_name(&resource.specifier);
+            resource.entries.into_iter().filter_map(move |entry| {
+                (all || dynamic_config_source(&entry.source)).then(|| ConfigDescriptionRow {
+                    entity_type: kind.into(),
+                    entity_name: entity_name.clone(),
                     name: entry.name,
                     value: entry.value,
-                    source: entry.config_source.to_string(),
+                    source: format!("{:?}", entry.source),
                     sensitive: entry.is_sensitive,
-                }
+                })
             })
-        }));
-    }
-    output::write_value(format, output_command, &rows, |rows| {
+        })
+        .collect::<Vec<_>>();
+    output::write_value(format, "configs.describe", &rows, |rows| {
         output::table(
             [
                 "ENTITY_TYPE",
@@ -13213,2787 +16924,3532 @@ async fn describe_protocol_configs(
     })
 }
 
-const fn protocol_dynamic_config_source(kind: ConfigEntityType, source: i8) -> bool {
-    matches!(
-        (kind, source),
-        (ConfigEntityType::Broker, 3)
-            | (ConfigEntityType::BrokerLogger, 6)
-            | (ConfigEntityType::ClientMetrics, 7)
-    )
-}
-
-#[expect(
-    clippy::too_many_arguments,
-    reason = "arguments mirror a Kafka IncrementalAlterConfigs resource and command context"
-)]
-async fn alter_protocol_config(
-    bootstrap: &str,
-    command_config: Option<&Path>,
-    timeout: Duration,
-    format: OutputFormat,
-    output_command: &str,
-    kind: ConfigEntityType,
-    name: &str,
+fn config_entity_names(
+    config: &rdkafka::ClientConfig,
+    timeout: Duration,
+    kind: ConfigEntityType,
+) -> Result<Vec<String>> {
+    let consumer = base_consumer(config)?;
+    match kind {
+        ConfigEntityType::Topic => Ok(consumer
+            .fetch_metadata(None, timeout)?
+            .topics()
+            .iter()
+            .filter(|topic| topic.error().is_none())
+            .map(|topic| topic.name().to_owned())
+            .collect()),
+        ConfigEntityType::Broker => Ok(consumer
+            .fetch_metadata(None, timeout)?
+            .brokers()
+            .iter()
+            .map(|broker| broker.id().to_string())
+            .collect()),
+        ConfigEntityType::Group => {
+            let admin = admin(config)?;
+            Ok(ffi::list_consumer_groups(
+                admin.inner().native_ptr(),
+                &[],
+                &[],
+                duration_ms(timeout)?,
+            )?
+            .into_iter()
+            .map(|group| group.group)
+            .collect())
+        }
+        ConfigEntityType::User => Err(Error::Usage(
+            "SCRAM users must use the credential Admin API".into(),
+        )),
+        ConfigEntityType::Client | ConfigEntityType::Ip => Err(Error::Usage(
+            "client and IP entities must use the quota Admin API".into(),
+        )),
+        ConfigEntityType::BrokerLogger | ConfigEntityType::ClientMetrics => Err(Error::Usage(
+            "this resource type must use the protocol config API".into(),
+        )),
+    }
+}
+
+const fn config_entity_type_name(kind: ConfigEntityType) -> &'static str {
+    match kind {
+        ConfigEntityType::Topic => "topics",
+        ConfigEntityType::Broker => "brokers",
+        ConfigEntityType::Group => "groups",
+        ConfigEntityType::User => "users",
+        ConfigEntityType::Client => "clients",
+        ConfigEntityType::Ip => "ips",
+        ConfigEntityType::BrokerLogger => "broker-loggers",
+        ConfigEntityType::ClientMetrics => "client-metrics",
+    }
+}
+
+fn owned_resource_name(specifier: &OwnedResourceSpecifier) -> String {
+    match specifier {
+        OwnedResourceSpecifier::Topic(name) | OwnedResourceSpecifier::Group(name) => name.clone(),
+        OwnedResourceSpecifier::Broker(id) => id.to_string(),
+    }
+}
+
+const fn dynamic_config_source(source: &ConfigSource) -> bool {
+    matches!(
+        source,
+        ConfigSource::DynamicTopic
+            | ConfigSource::DynamicBroker
+            | ConfigSource::DynamicDefaultBroker
+    )
+}
+
+fn alter_user_scram(
+    config: &rdkafka::ClientConfig,
+    timeout: Duration,
+    format: OutputFormat,
+    user: &str,
+    add: &[(String, String)],
+    delete: &[String],
+    execute: bool,
+) -> Result<()> {
+    let changes = parse_scram_changes(add, delete)?;
+    let rows = changes
+        .iter()
+        .map(|change| match change {
+            ffi::ScramCredentialAlteration::Upsert {
+                mechanism,
+                iterations,
+                ..
+            } => MutationRow {
+                resource: format!("{user}:{}", scram_mechanism_name(*mechanism)),
+                status: if execute {
+                    format!("UPSERTED ({iterations} iterations)")
+                } else {
+                    format!("PREVIEW UPSERT ({iterations} iterations)")
+                },
+                error: None,
+            },
+            ffi::ScramCredentialAlteration::Delete { mechanism } => MutationRow {
+                resource: format!("{user}:{}", scram_mechanism_name(*mechanism)),
+                status: if execute {
+                    "DELETED".into()
+                } else {
+                    "PREVIEW DELETE".into()
+                },
+                error: None,
+            },
+        })
+        .collect::<Vec<_>>();
+    if execute {
+        let client = admin(config)?;
+        ffi::alter_user_scram_credentials(
+            client.inner().native_ptr(),
+            user,
+            &changes,
+            duration_ms(timeout)?,
+        )?;
+    }
+    write_mutation_rows(format, "configs.alter.scram", &rows)
+}
+
+const fn native_resource_type(kind: ConfigEntityType) -> rdkafka_sys::rd_kafka_ResourceType_t {
+    match kind {
+        ConfigEntityType::Topic => rdkafka_sys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_TOPIC,
+        ConfigEntityType::Broker => rdkafka_sys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_BROKER,
+        ConfigEntityType::Group => rdkafka_sys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_GROUP,
+        ConfigEntityType::User => rdkafka_sys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_UNKNOWN,
+        ConfigEntityType::Client
+        | ConfigEntityType::Ip
+        | ConfigEntityType::BrokerLogger
+        | ConfigEntityType::ClientMetrics => {
+            rdkafka_sys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_UNKNOWN
+        }
+    }
+}
+
+fn resource(kind: ConfigEntityType, name: &str) -> Result<ResourceSpecifier<'_>> {
+    Ok(match kind {
+        ConfigEntityType::Topic => ResourceSpecifier::Topic(name),
+        ConfigEntityType::Group => ResourceSpecifier::Group(name),
+        ConfigEntityType::Broker => ResourceSpecifier::Broker(
+            name.parse()
+                .map_err(|_| Error::Usage("broker entity name must be an integer".into()))?,
+        ),
+        ConfigEntityType::User => {
+            return Err(Error::Config(
+                "user configuration must use the SCRAM Admin API".into(),
+            ));
+        }
+        ConfigEntityType::Client | ConfigEntityType::Ip => {
+            return Err(Error::Config(
+                "client and IP configuration must use the quota Admin API".into(),
+            ));
+        }
+        ConfigEntityType::BrokerLogger | ConfigEntityType::ClientMetrics => {
+            return Err(Error::Config(
+                "this resource type must use the protocol config API".into(),
+            ));
+        }
+    })
+}
+
+fn parse_scram_changes(
     add: &[(String, String)],
     delete: &[String],
-) -> Result<()> {
-    let resource_type = protocol_config_resource_type(kind)
-        .ok_or_else(|| Error::Usage("invalid protocol config resource".into()))?;
-    let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
-    let (broker_id, address) = protocol_config_broker(&client, kind, name).await?;
-    let connection = client
-        .pool()
-        .get_connection_by_id(broker_id, &address)
-        .await?;
-    let request = IncrementalAlterConfigsRequest {
-        resources: vec![IncrementalAlterConfigsResource {
-            resource_type,
-            resource_name: name.into(),
-            configs: add
-                .iter()
-                .map(|(key, value)| AlterableConfig {
-                    name: key.clone(),
-                    config_operation: AlterConfigOp::Set,
-                    value: Some(value.clone()),
+) -> Result<Vec<ffi::ScramCredentialAlteration>> {
+    let mut mechanisms = BTreeSet::new();
+    let mut changes = Vec::new();
+    for (name, value) in add {
+        let mechanism = parse_scram_mechanism(name)?;
+        if !mechanisms.insert(mechanism_key(mechanism)) {
+            return Err(Error::Usage(format!(
+                "duplicate SCRAM alteration for {}",
+                scram_mechanism_name(mechanism)
+            )));
+        }
+        let body = value
+            .strip_prefix('[')
+            .and_then(|value| value.strip_suffix(']'))
+            .unwrap_or(value);
+        let properties = body
+            .split(',')
+            .map(|item| {
+                item.split_once('=').ok_or_else(|| {
+                    Error::Usage(format!("invalid SCRAM credential property: {item}"))
                 })
-                .chain(delete.iter().map(|key| AlterableConfig {
-                    name: key.clone(),
-                    config_operation: AlterConfigOp::Delete,
-                    value: None,
-                }))
-                .collect(),
-        }],
-        validate_only: false,
-    };
-    let version = connection
-        .negotiate_api_version(
-            ApiKey::IncrementalAlterConfigs,
-            versions::INCREMENTAL_ALTER_CONFIGS_MAX,
-            versions::INCREMENTAL_ALTER_CONFIGS_MIN,
-        )
-        .await
-        .ok_or_else(|| {
-            Error::Unsupported("broker does not support IncrementalAlterConfigs".into())
+            })
+            .collect::<Result<BTreeMap<_, _>>>()?;
+        let password = properties
+            .get("password")
+            .filter(|password| !password.is_empty())
+            .ok_or_else(|| Error::Usage("SCRAM credential requires password".into()))?;
+        let iterations = properties.get("iterations").map_or(Ok(4096), |value| {
+            value
+                .parse::<i32>()
+                .map_err(|_| Error::Usage(format!("invalid SCRAM iteration count: {value}")))
         })?;
-    let mut response = connection
-        .send_request(ApiKey::IncrementalAlterConfigs, version, |buffer| {
-            request.encode_versioned(version, buffer)
-        })
-        .await?;
-    drop(connection);
-    drop(client);
-    let response = krafka::protocol::IncrementalAlterConfigsResponse::decode_versioned(
-        version,
-        &mut response,
-    )?;
-    let rows = response
-        .results
-        .into_iter()
-        .map(|result| MutationRow {
-            resource: format!("{}:{}", config_entity_type_name(kind), result.resource_name),
-            status: if result.error_code.is_ok() {
-                "ALTERED"
-            } else {
-                "FAILED"
-            }
-            .into(),
-            error: (!result.error_code.is_ok()).then(|| {
-                result
-                    .error_message
-                    .unwrap_or_else(|| format!("{:?}", result.error_code))
-            }),
-        })
-        .collect::<Vec<_>>();
-    let failures = rows.iter().filter(|row| row.error.is_some()).count();
-    write_mutation_rows(format, output_command, &rows)?;
-    if failures == 0 {
-        Ok(())
-    } else {
-        Err(Error::Partial {
-            failed: failures,
-            total: rows.len(),
-        })
+        if iterations < 4096 {
+            return Err(Error::Usage(
+                "SCRAM iteration count must be at least 4096".into(),
+            ));
+        }
+        if properties
+            .keys()
+            .any(|key| !matches!(*key, "iterations" | "password"))
+        {
+            return Err(Error::Usage(
+                "SCRAM credentials only accept iterations and password".into(),
+            ));
+        }
+        changes.push(ffi::ScramCredentialAlteration::Upsert {
+            mechanism,
+            iterations,
+            password: password.as_bytes().to_vec(),
+        });
+    }
+    for name in delete {
+        let mechanism = parse_scram_mechanism(name)?;
+        if !mechanisms.insert(mechanism_key(mechanism)) {
+            return Err(Error::Usage(format!(
+                "duplicate SCRAM alteration for {}",
+                scram_mechanism_name(mechanism)
+            )));
+        }
+        changes.push(ffi::ScramCredentialAlteration::Delete { mechanism });
     }
+    Ok(changes)
 }
 
-#[derive(Debug, Serialize)]
-struct ConfigChangeRow<'a> {
-    operation: &'static str,
-    name: &'a str,
-    value: Option<&'a str>,
+fn parse_scram_mechanism(value: &str) -> Result<ffi::ScramMechanism> {
+    match value.to_ascii_uppercase().as_str() {
+        "SCRAM-SHA-256" => Ok(ffi::ScramMechanism::Sha256),
+        "SCRAM-SHA-512" => Ok(ffi::ScramMechanism::Sha512),
+        _ => Err(Error::Usage(format!(
+            "unsupported user config {value}; expected SCRAM-SHA-256 or SCRAM-SHA-512"
+        ))),
+    }
 }
 
-fn config_change_preview(
+const fn mechanism_key(value: ffi::ScramMechanism) -> u8 {
+    match value {
+        ffi::ScramMechanism::Sha256 => 1,
+        ffi::ScramMechanism::Sha512 => 2,
+    }
+}
+
+const fn scram_mechanism_name(value: ffi::ScramMechanism) -> &'static str {
+    match value {
+        ffi::ScramMechanism::Sha256 => "SCRAM-SHA-256",
+        ffi::ScramMechanism::Sha512 => "SCRAM-SHA-512",
+    }
+}
+
+async fn offsets(
+    config: &rdkafka::ClientConfig,
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    timeout: Duration,
     format: OutputFormat,
-    output_command: &str,
-    pairs: &[(String, String)],
-    delete: &[String],
+    args: &crate::cli::OffsetsArgs,
 ) -> Result<()> {
-    let rows = pairs
+    let config = offsets_client_config(config);
+    let consumer = base_consumer(&config)?;
+    let metadata = consumer.fetch_metadata(None, timeout)?;
+    let patterns = args
+        .topic_partitions
+        .as_deref()
+        .map(parse_topic_partition_patterns)
+        .transpose()?;
+    let topic_filter = args.topic.as_deref().map(topic_pattern).transpose()?;
+    let selected = parse_partitions(args.partitions.as_deref())?;
+    let targets = metadata
+        .topics()
         .iter()
-        .map(|(key, value)| ConfigChangeRow {
-            operation: "SET",
-            name: key,
-            value: Some(value.as_str()),
+        .filter(|topic| !args.exclude_internal_topics || !topic.name().starts_with("__"))
+        .flat_map(|topic| {
+            let topic_name = topic.name();
+            let topic_filter = &topic_filter;
+            let patterns = &patterns;
+            let selected = &selected;
+            topic
+                .partitions()
+                .iter()
+                .filter(move |partition| {
+                    patterns.as_ref().map_or_else(
+                        || {
+                            topic_filter
+                                .as_ref()
+                                .is_none_or(|pattern| pattern.is_match(topic_name))
+                                && selected
+                                    .as_ref()
+                                    .is_none_or(|ids| ids.contains(&partition.id()))
+                        },
+                        |patterns| {
+                            patterns
+                                .iter()
+                                .any(|pattern| pattern.matches(topic_name, partition.id()))
+                        },
+                    )
+                })
+                .map(move |partition| (topic_name.to_owned(), partition.id()))
         })
-        .chain(delete.iter().map(|key| ConfigChangeRow {
-            operation: "DELETE",
-            name: key,
-            value: None,
-        }))
         .collect::<Vec<_>>();
-    output::write_value(format, output_command, &rows, |rows| {
+    if targets.is_empty() {
+        return Err(Error::Usage(
+            "no topic-partitions matched the supplied filters".into(),
+        ));
+    }
+    let spec = if let Some(timestamp) = args.timestamp {
+        if timestamp < 0 {
+            return Err(Error::Usage("--timestamp must be non-negative".into()));
+        }
+        ffi::ListOffsetSpec::Timestamp(timestamp)
+    } else {
+        match args.time {
+            OffsetTime::Earliest => ffi::ListOffsetSpec::Earliest,
+            OffsetTime::Latest => ffi::ListOffsetSpec::Latest,
+            OffsetTime::MaxTimestamp => ffi::ListOffsetSpec::MaxTimestamp,
+            OffsetTime::EarliestLocal => ffi::ListOffsetSpec::EarliestLocal,
+            OffsetTime::LatestTiered => ffi::ListOffsetSpec::LatestTiered,
+            OffsetTime::EarliestPendingUpload => ffi::ListOffsetSpec::EarliestPendingUpload,
+        }
+    };
+    let rows = if let Some(timestamp) = tiered_offset_timestamp(spec) {
+        protocol_list_offsets(bootstrap, command_config, timeout, &targets, timestamp).await?
+    } else {
+        let client = admin(&config)?;
+        ffi::list_offsets(
+            client.inner().native_ptr(),
+            &targets,
+            spec,
+            duration_ms(timeout)?,
+        )?
+    };
+    output::write_value(format, "offsets", &rows, |rows| {
         output::table(
-            ["OPERATION", "NAME", "VALUE"],
+            ["TOPIC", "PARTITION", "OFFSET", "TIMESTAMP", "ERROR"],
             rows.iter().map(|row| {
                 [
-                    row.operation.to_owned(),
-                    row.name.to_owned(),
-                    row.value.unwrap_or("-").to_owned(),
+                    row.topic.clone(),
+                    row.partition.to_string(),
+                    row.offset
+                        .map_or_else(|| "N/A".into(), |value| value.to_string()),
+                    row.timestamp
+                        .map_or_else(|| "N/A".into(), |value| value.to_string()),
+                    row.error.clone().unwrap_or_default(),
                 ]
             }),
         )
     })
 }
 
-#[derive(Debug, Serialize)]
-struct ConfigDescriptionRow {
-    entity_type: String,
-    entity_name: String,
-    name: String,
-    value: Option<String>,
-    source: String,
-    sensitive: bool,
+const fn tiered_offset_timestamp(spec: ffi::ListOffsetSpec) -> Option<i64> {
+    match spec {
+        ffi::ListOffsetSpec::EarliestLocal => Some(-4),
+        ffi::ListOffsetSpec::LatestTiered => Some(-5),
+        ffi::ListOffsetSpec::EarliestPendingUpload => Some(-6),
+        _ => None,
+    }
 }
 
-async fn describe_resource_configs(
-    config: &rdkafka::ClientConfig,
+async fn protocol_list_offsets(
+    bootstrap: &str,
+    command_config: Option<&Path>,
     timeout: Duration,
-    format: OutputFormat,
-    entity_type: ConfigEntityType,
-    entity_name: Option<String>,
-    all: bool,
-) -> Result<()> {
-    let names = match entity_name {
-        Some(name) => vec![name],
-        None => config_entity_names(config, timeout, entity_type)?,
-    };
-    let specifiers = names
+    targets: &[(String, i32)],
+    timestamp: i64,
+) -> Result<Vec<ffi::ListOffsetEntry>> {
+    let mut grouped = BTreeMap::<&str, Vec<i32>>::new();
+    for (topic, partition) in targets {
+        grouped.entry(topic).or_default().push(*partition);
+    }
+    let topic_partitions = grouped
         .iter()
-        .map(|name| resource(entity_type, name))
-        .collect::<Result<Vec<_>>>()?;
-    let results = admin(config)?
-        .describe_configs(
-            &specifiers,
-            &AdminOptions::new().request_timeout(Some(timeout)),
+        .map(|(topic, partitions)| (*topic, partitions.as_slice()))
+        .collect::<Vec<_>>();
+    let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
+    let results = client
+        .list_offsets(
+            &topic_partitions,
+            krafka::admin::OffsetSpec::Timestamp(timestamp),
         )
         .await?;
-    let resources = results
-        .into_iter()
-        .map(|item| item.map_err(|code| Error::Config(code.to_string())))
-        .collect::<Result<Vec<_>>>()?;
-    let kind = config_entity_type_name(entity_type);
-    let rows = resources
+    drop(client);
+    let mut rows = results
         .into_iter()
-        .flat_map(|resource| {
-            let entity_name = owned_resource_name(&resource.specifier);
-            resource.entries.into_iter().filter_map(move |entry| {
-                (all || dynamic_config_source(&entry.source)).then(|| ConfigDescriptionRow {
-                    entity_type: kind.into(),
-                    entity_name: entity_name.clone(),
-                    name: entry.name,
-                    value: entry.value,
-                    source: format!("{:?}", entry.source),
-                    sensitive: entry.is_sensitive,
-                })
+        .filter_map(|result| {
+            if result.offset == -1 && result.error.is_none() {
+                return None;
+            }
+            Some(ffi::ListOffsetEntry {
+                topic: result.topic,
+                partition: result.partition,
+                offset: (result.offset >= 0).then_some(result.offset),
+                timestamp: (result.timestamp >= 0).then_some(result.timestamp),
+                error: result.error,
             })
         })
         .collect::<Vec<_>>();
-    output::write_value(format, "configs.describe", &rows, |rows| {
-        output::table(
-            [
-                "ENTITY_TYPE",
-                "ENTITY_NAME",
-                "NAME",
-                "VALUE",
-                "SOURCE",
-                "SENSITIVE",
-            ],
-            rows.iter().map(|row| {
-                [
-                    row.entity_type.clone(),
-                    row.entity_name.clone(),
-                    row.name.clone(),
-                    row.value.as_deref().unwrap_or("null").to_owned(),
-                    row.source.clone(),
-                    row.sensitive.to_string(),
-                ]
-            }),
-        )
-    })
+    rows.sort_by(|left, right| {
+        left.topic
+            .cmp(&right.topic)
+            .then(left.partition.cmp(&right.partition))
+    });
+    Ok(rows)
 }
 
-fn config_entity_names(
-    config: &rdkafka::ClientConfig,
-    timeout: Duration,
-    kind: ConfigEntityType,
-) -> Result<Vec<String>> {
-    let consumer = base_consumer(config)?;
-    match kind {
-        ConfigEntityType::Topic => Ok(consumer
-            .fetch_metadata(None, timeout)?
-            .topics()
-            .iter()
-            .filter(|topic| topic.error().is_none())
-            .map(|topic| topic.name().to_owned())
-            .collect()),
-        ConfigEntityType::Broker => Ok(consumer
-            .fetch_metadata(None, timeout)?
-            .brokers()
-            .iter()
-            .map(|broker| broker.id().to_string())
-            .collect()),
-        ConfigEntityType::Group => {
-            let admin = admin(config)?;
-            Ok(ffi::list_consumer_groups(
-                admin.inner().native_ptr(),
-                &[],
-                &[],
-                duration_ms(timeout)?,
-            )?
-            .into_iter()
-            .map(|group| group.group)
-            .collect())
-        }
-        ConfigEntityType::User => Err(Error::Usage(
-            "SCRAM users must use the credential Admin API".into(),
-        )),
-        ConfigEntityType::Client | ConfigEntityType::Ip => Err(Error::Usage(
-            "client and IP entities must use the quota Admin API".into(),
-        )),
-        ConfigEntityType::BrokerLogger | ConfigEntityType::ClientMetrics => Err(Error::Usage(
-            "this resource type must use the protocol config API".into(),
-        )),
+fn offsets_client_config(config: &rdkafka::ClientConfig) -> rdkafka::ClientConfig {
+    let mut config = config.clone();
+    config.set("client.id", "GetOffsetShell");
+    config
+}
+
+struct TopicPartitionPattern {
+    topic: Regex,
+    start: Option<i32>,
+    end: Option<i32>,
+}
+
+impl TopicPartitionPattern {
+    fn matches(&self, topic: &str, partition: i32) -> bool {
+        self.topic.is_match(topic)
+            && self.start.is_none_or(|start| partition >= start)
+            && self.end.is_none_or(|end| partition < end)
     }
 }
 
-const fn config_entity_type_name(kind: ConfigEntityType) -> &'static str {
-    match kind {
-        ConfigEntityType::Topic => "topics",
-        ConfigEntityType::Broker => "brokers",
-        ConfigEntityType::Group => "groups",
-        ConfigEntityType::User => "users",
-        ConfigEntityType::Client => "clients",
-        ConfigEntityType::Ip => "ips",
-        ConfigEntityType::BrokerLogger => "broker-loggers",
-        ConfigEntityType::ClientMetrics => "client-metrics",
+fn parse_topic_partition_patterns(value: &str) -> Result<Vec<TopicPartitionPattern>> {
+    split_java_list(value)
+        .into_iter()
+        .map(|item| {
+            let (topic, partitions) = item
+                .split_once(':')
+                .map_or((item, None), |(topic, range)| (topic, Some(range)));
+            let topic = topic_pattern(if topic.is_empty() { ".*" } else { topic })?;
+            let (start, end) = match partitions {
+                None | Some("") => (None, None),
+                Some(range) if range.contains('-') => {
+                    let (start, end) = range
+                        .split_once('-')
+                        .ok_or_else(|| Error::Usage(format!("invalid partition range: {range}")))?;
+                    let start = parse_optional_partition_bound(start)?;
+                    let end = parse_optional_partition_bound(end)?;
+                    // Half-open bounds must be non-negative when present (open start uses empty left).
+                    if start.is_some_and(|bound| bound < 0) || end.is_some_and(|bound| bound < 0) {
+                        return Err(Error::Usage(format!(
+                            "partition bounds in {range} must be non-negative"
+                        )));
+                    }
+                    (start, end)
+                }
+                Some(partition) => {
+                    let partition = partition
+                        .parse::<i32>()
+                        .map_err(|_| Error::Usage(format!("invalid partition: {partition}")))?;
+                    if partition < 0 {
+                        return Err(Error::Usage(format!(
+                            "partition {partition} must be non-negative"
+                        )));
+                    }
+                    (Some(partition), Some(partition.saturating_add(1)))
+                }
+            };
+            if start.is_some_and(|start| end.is_some_and(|end| start >= end)) {
+                return Err(Error::Usage("partition range must be increasing".into()));
+            }
+            Ok(TopicPartitionPattern { topic, start, end })
+        })
+        .collect()
+}
+
+fn split_java_list(value: &str) -> Vec<&str> {
+    if value.is_empty() {
+        return vec![""];
+    }
+    let mut items = value.split(',').collect::<Vec<_>>();
+    while items.last() == Some(&"") {
+        items.pop();
     }
+    items
 }
 
-fn owned_resource_name(specifier: &OwnedResourceSpecifier) -> String {
-    match specifier {
-        OwnedResourceSpecifier::Topic(name) | OwnedResourceSpecifier::Group(name) => name.clone(),
-        OwnedResourceSpecifier::Broker(id) => id.to_string(),
+fn parse_optional_partition_bound(value: &str) -> Result<Option<i32>> {
+    if value.is_empty() {
+        Ok(None)
+    } else {
+        value
+            .parse::<i32>()
+            .map(Some)
+            .map_err(|_| Error::Usage(format!("invalid partition bound: {value}")))
     }
 }
 
-const fn dynamic_config_source(source: &ConfigSource) -> bool {
-    matches!(
-        source,
-        ConfigSource::DynamicTopic
-            | ConfigSource::DynamicBroker
-            | ConfigSource::DynamicDefaultBroker
-    )
+#[derive(Debug, Deserialize)]
+struct DeleteRecordsFile {
+    #[serde(default)]
+    version: Option<u8>,
+    partitions: Vec<DeleteRecordsPartition>,
+}
+#[derive(Debug, Deserialize)]
+struct DeleteRecordsPartition {
+    topic: String,
+    partition: i32,
+    offset: i64,
 }
 
-fn alter_user_scram(
+async fn delete_records(
     config: &rdkafka::ClientConfig,
     timeout: Duration,
     format: OutputFormat,
-    user: &str,
-    add: &[(String, String)],
-    delete: &[String],
+    path: &Path,
     execute: bool,
 ) -> Result<()> {
-    let changes = parse_scram_changes(add, delete)?;
-    let rows = changes
-        .iter()
-        .map(|change| match change {
-            ffi::ScramCredentialAlteration::Upsert {
-                mechanism,
-                iterations,
-                ..
-            } => MutationRow {
-                resource: format!("{user}:{}", scram_mechanism_name(*mechanism)),
-                status: if execute {
-                    format!("UPSERTED ({iterations} iterations)")
-                } else {
-                    format!("PREVIEW UPSERT ({iterations} iterations)")
-                },
-                error: None,
-            },
-            ffi::ScramCredentialAlteration::Delete { mechanism } => MutationRow {
-                resource: format!("{user}:{}", scram_mechanism_name(*mechanism)),
-                status: if execute {
-                    "DELETED".into()
-                } else {
-                    "PREVIEW DELETE".into()
-                },
+    let input = read_delete_records(path)?;
+    let mut offsets = TopicPartitionList::new();
+    for item in &input.partitions {
+        offsets.add_partition_offset(&item.topic, item.partition, Offset::Offset(item.offset))?;
+    }
+    if !execute {
+        let rows = input
+            .partitions
+            .iter()
+            .map(|item| MutationRow {
+                resource: format!("{}:{}", item.topic, item.partition),
+                status: format!("PREVIEW BEFORE {}", item.offset),
                 error: None,
-            },
+            })
+            .collect::<Vec<_>>();
+        return write_mutation_rows(format, "delete-records", &rows);
+    }
+    let result = admin(config)?
+        .delete_records(
+            &offsets,
+            &AdminOptions::new().operation_timeout(Some(timeout)),
+        )
+        .await?;
+    let rows = result
+        .elements()
+        .into_iter()
+        .map(|element| MutationRow {
+            resource: format!("{}:{}", element.topic(), element.partition()),
+            status: format!("LOW_WATERMARK {:?}", element.offset()),
+            error: element.error().err().map(|error| error.to_string()),
         })
         .collect::<Vec<_>>();
-    if execute {
-        let client = admin(config)?;
-        ffi::alter_user_scram_credentials(
-            client.inner().native_ptr(),
-            user,
-            &changes,
-            duration_ms(timeout)?,
-        )?;
-    }
-    write_mutation_rows(format, "configs.alter.scram", &rows)
+    write_mutation_rows(format, "delete-records", &rows)
 }
 
-const fn native_resource_type(kind: ConfigEntityType) -> rdkafka_sys::rd_kafka_ResourceType_t {
-    match kind {
-        ConfigEntityType::Topic => rdkafka_sys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_TOPIC,
-        ConfigEntityType::Broker => rdkafka_sys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_BROKER,
-        ConfigEntityType::Group => rdkafka_sys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_GROUP,
-        ConfigEntityType::User => rdkafka_sys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_UNKNOWN,
-        ConfigEntityType::Client
-        | ConfigEntityType::Ip
-        | ConfigEntityType::BrokerLogger
-        | ConfigEntityType::ClientMetrics => {
-            rdkafka_sys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_UNKNOWN
-        }
+fn read_delete_records(path: &Path) -> Result<DeleteRecordsFile> {
+    let input: DeleteRecordsFile = serde_json::from_reader(std::fs::File::open(path)?)?;
+    if input.version.is_some_and(|version| version != 1) {
+        return Err(Error::Usage("delete-records JSON version must be 1".into()));
     }
-}
-
-fn resource(kind: ConfigEntityType, name: &str) -> Result<ResourceSpecifier<'_>> {
-    Ok(match kind {
-        ConfigEntityType::Topic => ResourceSpecifier::Topic(name),
-        ConfigEntityType::Group => ResourceSpecifier::Group(name),
-        ConfigEntityType::Broker => ResourceSpecifier::Broker(
-            name.parse()
-                .map_err(|_| Error::Usage("broker entity name must be an integer".into()))?,
-        ),
-        ConfigEntityType::User => {
-            return Err(Error::Config(
-                "user configuration must use the SCRAM Admin API".into(),
-            ));
-        }
-        ConfigEntityType::Client | ConfigEntityType::Ip => {
-            return Err(Error::Config(
-                "client and IP configuration must use the quota Admin API".into(),
-            ));
-        }
-        ConfigEntityType::BrokerLogger | ConfigEntityType::ClientMetrics => {
-            return Err(Error::Config(
-                "this resource type must use the protocol config API".into(),
-            ));
-        }
-    })
-}
-
-fn parse_scram_changes(
-    add: &[(String, String)],
-    delete: &[String],
-) -> Result<Vec<ffi::ScramCredentialAlteration>> {
-    let mut mechanisms = BTreeSet::new();
-    let mut changes = Vec::new();
-    for (name, value) in add {
-        let mechanism = parse_scram_mechanism(name)?;
-        if !mechanisms.insert(mechanism_key(mechanism)) {
-            return Err(Error::Usage(format!(
-                "duplicate SCRAM alteration for {}",
-                scram_mechanism_name(mechanism)
-            )));
-        }
-        let body = value
-            .strip_prefix('[')
-            .and_then(|value| value.strip_suffix(']'))
-            .unwrap_or(value);
-        let properties = body
-            .split(',')
-            .map(|item| {
-                item.split_once('=').ok_or_else(|| {
-                    Error::Usage(format!("invalid SCRAM credential property: {item}"))
-                })
-            })
-            .collect::<Result<BTreeMap<_, _>>>()?;
-        let password = properties
-            .get("password")
-            .filter(|password| !password.is_empty())
-            .ok_or_else(|| Error::Usage("SCRAM credential requires password".into()))?;
-        let iterations = properties.get("iterations").map_or(Ok(4096), |value| {
-            value
-                .parse::<i32>()
-                .map_err(|_| Error::Usage(format!("invalid SCRAM iteration count: {value}")))
-        })?;
-        if iterations < 4096 {
-            return Err(Error::Usage(
-                "SCRAM iteration count must be at least 4096".into(),
-            ));
-        }
-        if properties
-            .keys()
-            .any(|key| !matches!(*key, "iterations" | "password"))
-        {
+    if input.partitions.is_empty() {
+        return Err(Error::Usage(
+            "delete-records partition list cannot be empty".into(),
+        ));
+    }
+    let mut targets = BTreeSet::new();
+    for partition in &input.partitions {
+        if partition.topic.is_empty() || partition.partition < 0 || partition.offset < 0 {
             return Err(Error::Usage(
-                "SCRAM credentials only accept iterations and password".into(),
+                "delete-records entries require a topic and non-negative partition/offset".into(),
             ));
         }
-        changes.push(ffi::ScramCredentialAlteration::Upsert {
-            mechanism,
-            iterations,
-            password: password.as_bytes().to_vec(),
-        });
-    }
-    for name in delete {
-        let mechanism = parse_scram_mechanism(name)?;
-        if !mechanisms.insert(mechanism_key(mechanism)) {
+        if !targets.insert((partition.topic.as_str(), partition.partition)) {
             return Err(Error::Usage(format!(
-                "duplicate SCRAM alteration for {}",
-                scram_mechanism_name(mechanism)
+                "duplicate delete-records target {}:{}",
+                partition.topic, partition.partition
             )));
         }
-        changes.push(ffi::ScramCredentialAlteration::Delete { mechanism });
-    }
-    Ok(changes)
-}
-
-fn parse_scram_mechanism(value: &str) -> Result<ffi::ScramMechanism> {
-    match value.to_ascii_uppercase().as_str() {
-        "SCRAM-SHA-256" => Ok(ffi::ScramMechanism::Sha256),
-        "SCRAM-SHA-512" => Ok(ffi::ScramMechanism::Sha512),
-        _ => Err(Error::Usage(format!(
-            "unsupported user config {value}; expected SCRAM-SHA-256 or SCRAM-SHA-512"
-        ))),
-    }
-}
-
-const fn mechanism_key(value: ffi::ScramMechanism) -> u8 {
-    match value {
-        ffi::ScramMechanism::Sha256 => 1,
-        ffi::ScramMechanism::Sha512 => 2,
     }
+    Ok(input)
 }
 
-const fn scram_mechanism_name(value: ffi::ScramMechanism) -> &'static str {
-    match value {
-        ffi::ScramMechanism::Sha256 => "SCRAM-SHA-256",
-        ffi::ScramMechanism::Sha512 => "SCRAM-SHA-512",
-    }
+#[derive(Debug, Serialize)]
+struct ApiVersionRow {
+    broker: i32,
+    host: String,
+    api: String,
+    api_key: i16,
+    min_version: i16,
+    max_version: i16,
 }
 
-async fn offsets(
-    config: &rdkafka::ClientConfig,
+async fn api_versions(
     bootstrap: &str,
     command_config: Option<&Path>,
     timeout: Duration,
     format: OutputFormat,
-    args: &crate::cli::OffsetsArgs,
+    broker: Option<i32>,
 ) -> Result<()> {
-    let config = offsets_client_config(config);
-    let consumer = base_consumer(&config)?;
-    let metadata = consumer.fetch_metadata(None, timeout)?;
-    let patterns = args
-        .topic_partitions
-        .as_deref()
-        .map(parse_topic_partition_patterns)
-        .transpose()?;
-    let topic_filter = args.topic.as_deref().map(topic_pattern).transpose()?;
-    let selected = parse_partitions(args.partitions.as_deref())?;
-    let targets = metadata
-        .topics()
+    let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
+    let cluster = client.describe_cluster().await?;
+    let mut rows = Vec::new();
+    for endpoint in cluster
+        .brokers
         .iter()
-        .filter(|topic| !args.exclude_internal_topics || !topic.name().starts_with("__"))
-        .flat_map(|topic| {
-            let topic_name = topic.name();
-            let topic_filter = &topic_filter;
-            let patterns = &patterns;
-            let selected = &selected;
-            topic
-                .partitions()
-                .iter()
-                .filter(move |partition| {
-                    patterns.as_ref().map_or_else(
-                        || {
-                            topic_filter
-                                .as_ref()
-                                .is_none_or(|pattern| pattern.is_match(topic_name))
-                                && selected
-                                    .as_ref()
-                                    .is_none_or(|ids| ids.contains(&partition.id()))
-                        },
-                        |patterns| {
-                            patterns
-                                .iter()
-                                .any(|pattern| pattern.matches(topic_name, partition.id()))
-                        },
-                    )
-                })
-                .map(move |partition| (topic_name.to_owned(), partition.id()))
-        })
-        .collect::<Vec<_>>();
-    if targets.is_empty() {
-        return Err(Error::Usage(
-            "no topic-partitions matched the supplied filters".into(),
-        ));
-    }
-    let spec = if let Some(timestamp) = args.timestamp {
-        if timestamp < 0 {
-            return Err(Error::Usage("--timestamp must be non-negative".into()));
-        }
-        ffi::ListOffsetSpec::Timestamp(timestamp)
-    } else {
-        match args.time {
-            OffsetTime::Earliest => ffi::ListOffsetSpec::Earliest,
-            OffsetTime::Latest => ffi::ListOffsetSpec::Latest,
-            OffsetTime::MaxTimestamp => ffi::ListOffsetSpec::MaxTimestamp,
-            OffsetTime::EarliestLocal => ffi::ListOffsetSpec::EarliestLocal,
-            OffsetTime::LatestTiered => ffi::ListOffsetSpec::LatestTiered,
-            OffsetTime::EarliestPendingUpload => ffi::ListOffsetSpec::EarliestPendingUpload,
+        .filter(|endpoint| broker.is_none_or(|id| id == endpoint.broker_id))
+    {
+        let address = format!("{}:{}", endpoint.host, endpoint.port);
+        let connection = client
+            .pool()
+            .get_connection_by_id(endpoint.broker_id, &address)
+            .await?;
+        for (api_key, min_version, max_version) in broker_api_versions(&connection).await? {
+            rows.push(ApiVersionRow {
+                broker: endpoint.broker_id,
+                host: address.clone(),
+                api: format!("{:?}", ApiKey::from(api_key)),
+                api_key,
+                min_version,
+                max_version,
+            });
         }
-    };
-    let rows = if let Some(timestamp) = tiered_offset_timestamp(spec) {
-        protocol_list_offsets(bootstrap, command_config, timeout, &targets, timestamp).await?
-    } else {
-        let client = admin(&config)?;
-        ffi::list_offsets(
-            client.inner().native_ptr(),
-            &targets,
-            spec,
-            duration_ms(timeout)?,
-        )?
-    };
-    output::write_value(format, "offsets", &rows, |rows| {
+        drop(connection);
+    }
+    drop(cluster);
+    drop(client);
+    output::write_value(format, "api-versions", &rows, |rows| {
         output::table(
-            ["TOPIC", "PARTITION", "OFFSET", "TIMESTAMP", "ERROR"],
+            ["BROKER", "HOST", "API", "KEY", "MIN", "MAX"],
             rows.iter().map(|row| {
                 [
-                    row.topic.clone(),
-                    row.partition.to_string(),
-                    row.offset
-                        .map_or_else(|| "N/A".into(), |value| value.to_string()),
-                    row.timestamp
-                        .map_or_else(|| "N/A".into(), |value| value.to_string()),
-                    row.error.clone().unwrap_or_default(),
+                    row.broker.to_string(),
+                    row.host.clone(),
+                    row.api.clone(),
+                    row.api_key.to_string(),
+                    row.min_version.to_string(),
+                    row.max_version.to_string(),
                 ]
             }),
         )
     })
 }
 
-const fn tiered_offset_timestamp(spec: ffi::ListOffsetSpec) -> Option<i64> {
-    match spec {
-        ffi::ListOffsetSpec::EarliestLocal => Some(-4),
-        ffi::ListOffsetSpec::LatestTiered => Some(-5),
-        ffi::ListOffsetSpec::EarliestPendingUpload => Some(-6),
-        _ => None,
+async fn broker_api_versions(
+    connection: &krafka::network::BrokerConnection,
+) -> Result<Vec<(i16, i16, i16)>> {
+    let mut response = connection
+        .send_request(ApiKey::ApiVersions, 0, |_| Ok(()))
+        .await?;
+    let error_code = i16::decode(&mut response)?;
+    if error_code != 0 {
+        return Err(Error::Config(format!(
+            "ApiVersions failed with Kafka error code {error_code}"
+        )));
+    }
+    let count = decode_acl_count(&mut response)?;
+    let mut versions = Vec::with_capacity(count);
+    for _ in 0..count {
+        versions.push((
+            i16::decode(&mut response)?,
+            i16::decode(&mut response)?,
+            i16::decode(&mut response)?,
+        ));
     }
+    Ok(versions)
 }
 
-async fn protocol_list_offsets(
+#[derive(Serialize)]
+struct BrokerRow {
+    id: i32,
+    host: String,
+    port: u16,
+    rack: Option<String>,
+    fenced: bool,
+    endpoint_type: String,
+}
+
+fn broker_table(rows: &[BrokerRow]) -> String {
+    output::table(
+        ["ID", "HOST", "PORT", "RACK", "STATE", "ENDPOINT_TYPE"],
+        rows.iter().map(|row| {
+            [
+                row.id.to_string(),
+                row.host.clone(),
+                row.port.to_string(),
+                row.rack.as_deref().unwrap_or("-").to_owned(),
+                if row.fenced { "fenced" } else { "unfenced" }.into(),
+                row.endpoint_type.clone(),
+            ]
+        }),
+    )
+}
+
+async fn fenced_cluster_rows(
     bootstrap: &str,
     command_config: Option<&Path>,
     timeout: Duration,
-    targets: &[(String, i32)],
-    timestamp: i64,
-) -> Result<Vec<ffi::ListOffsetEntry>> {
-    let mut grouped = BTreeMap::<&str, Vec<i32>>::new();
-    for (topic, partition) in targets {
-        grouped.entry(topic).or_default().push(*partition);
-    }
-    let topic_partitions = grouped
-        .iter()
-        .map(|(topic, partitions)| (*topic, partitions.as_slice()))
-        .collect::<Vec<_>>();
+) -> Result<Vec<BrokerRow>> {
     let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
-    let results = client
-        .list_offsets(
-            &topic_partitions,
-            krafka::admin::OffsetSpec::Timestamp(timestamp),
+    let cluster = client.describe_cluster().await?;
+    let broker = cluster
+        .brokers
+        .first()
+        .ok_or_else(|| Error::Config("cluster returned no reachable broker".into()))?;
+    let connection = client
+        .pool()
+        .get_connection_by_id(
+            broker.broker_id,
+            &format!("{}:{}", broker.host, broker.port),
         )
         .await?;
+    let version = connection
+        .negotiate_api_version(ApiKey::DescribeCluster, 2, 2)
+        .await
+        .ok_or_else(|| {
+            Error::Unsupported(
+                "broker does not support fenced broker listing (DescribeCluster v2)".into(),
+            )
+        })?;
+    let request = DescribeClusterRequest {
+        include_cluster_authorized_operations: false,
+        endpoint_type: 1,
+        include_fenced_brokers: true,
+    };
+    let mut response = connection
+        .send_request(ApiKey::DescribeCluster, version, |buffer| {
+            request.encode_versioned(version, buffer)
+        })
+        .await?;
+    drop(connection);
     drop(client);
-    let mut rows = results
+    let response = DescribeClusterResponse::decode_versioned(version, &mut response)?;
+    if !response.error_code.is_ok() {
+        return Err(Error::Config(
+            response
+                .error_message
+                .unwrap_or_else(|| format!("{:?}", response.error_code)),
+        ));
+    }
+    response
+        .brokers
         .into_iter()
-        .filter_map(|result| {
-            if result.offset == -1 && result.error.is_none() {
-                return None;
-            }
-            Some(ffi::ListOffsetEntry {
-                topic: result.topic,
-                partition: result.partition,
-                offset: (result.offset >= 0).then_some(result.offset),
-                timestamp: (result.timestamp >= 0).then_some(result.timestamp),
-                error: result.error,
+        .map(|broker| {
+            Ok(BrokerRow {
+                id: broker.broker_id,
+                host: broker.host,
+                port: u16::try_from(broker.port).map_err(|_| {
+                    Error::Config(format!("broker {} returned invalid port", broker.broker_id))
+                })?,
+                rack: broker.rack,
+                fenced: broker.is_fenced,
+                endpoint_type: "broker".into(),
             })
         })
-        .collect::<Vec<_>>();
-    rows.sort_by(|left, right| {
-        left.topic
-            .cmp(&right.topic)
-            .then(left.partition.cmp(&right.partition))
-    });
-    Ok(rows)
-}
-
-fn offsets_client_config(config: &rdkafka::ClientConfig) -> rdkafka::ClientConfig {
-    let mut config = config.clone();
-    config.set("client.id", "GetOffsetShell");
-    config
+        .collect()
 }
 
-struct TopicPartitionPattern {
-    topic: Regex,
-    start: Option<i32>,
-    end: Option<i32>,
+async fn cluster(
+    config: &rdkafka::ClientConfig,
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    timeout: Duration,
+    format: OutputFormat,
+    action: &ClusterAction,
+) -> Result<()> {
+    match action {
+        ClusterAction::Id => {
+            let client = admin(config)?;
+            let id = ffi::describe_cluster(client.inner().native_ptr(), duration_ms(timeout)?)?
+                .cluster_id;
+            output::write_value(format, "cluster.id", &id, |id| {
+                output::table(["CLUSTER_ID"], [[id.clone()]])
+            })
+        }
+        ClusterAction::ListEndpoints {
+            include_fenced_brokers,
+        } => {
+            let rows = if *include_fenced_brokers {
+                fenced_cluster_rows(bootstrap, command_config, timeout).await?
+            } else {
+                let client = admin(config)?;
+                ffi::describe_cluster(client.inner().native_ptr(), duration_ms(timeout)?)?
+                    .nodes
+                    .into_iter()
+                    .map(|broker| BrokerRow {
+                        id: broker.id,
+                        host: broker.host,
+                        port: broker.port,
+                        rack: broker.rack,
+                        fenced: false,
+                        endpoint_type: "broker".into(),
+                    })
+                    .collect()
+            };
+            output::write_value(format, "cluster.list-endpoints", &rows, |rows| {
+                broker_table(rows)
+            })
+        }
+        ClusterAction::ApiVersions => {
+            api_versions(bootstrap, command_config, timeout, format, None).await
+        }
+        ClusterAction::Unregister { id, execute } => {
+            if !execute {
+                return write_mutation_rows(
+                    format,
+                    "cluster.unregister",
+                    &[MutationRow {
+                        resource: format!("broker:{id}"),
+                        status: "PREVIEW".into(),
+                        error: None,
+                    }],
+                );
+            }
+            let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
+            unregister_broker(&client, *id).await?;
+            write_mutation_rows(
+                format,
+                "cluster.unregister",
+                &[MutationRow {
+                    resource: format!("broker:{id}"),
+                    status: "UNREGISTERED".into(),
+                    error: None,
+                }],
+            )
+        }
+    }
 }
 
-impl TopicPartitionPattern {
-    fn matches(&self, topic: &str, partition: i32) -> bool {
-        self.topic.is_match(topic)
-            && self.start.is_none_or(|start| partition >= start)
-            && self.end.is_none_or(|end| partition < end)
+async fn unregister_broker(client: &krafka::admin::AdminClient, broker_id: i32) -> Result<()> {
+    let connection = client.get_controller_connection().await?;
+    let mut response = connection
+        .send_request(ApiKey::UnregisterBroker, 0, |buffer| {
+            broker_id.try_encode(buffer)?;
+            TaggedFields::default().try_encode(buffer)
+        })
+        .await?;
+    drop(connection);
+    let _throttle_time_ms = i32::decode(&mut response)?;
+    let error_code = i16::decode(&mut response)?;
+    let error_message = KafkaString::decode_compact(&mut response)?.0;
+    let _tagged_fields = TaggedFields::decode(&mut response)?;
+    if error_code == 0 {
+        Ok(())
+    } else {
+        Err(Error::Config(error_message.unwrap_or_else(|| {
+            format!("UnregisterBroker failed with Kafka error code {error_code}")
+        })))
     }
 }
 
-fn parse_topic_partition_patterns(value: &str) -> Result<Vec<TopicPartitionPattern>> {
-    split_java_list(value)
-        .into_iter()
-        .map(|item| {
-            let (topic, partitions) = item
-                .split_once(':')
-                .map_or((item, None), |(topic, range)| (topic, Some(range)));
-            let topic = topic_pattern(if topic.is_empty() { ".*" } else { topic })?;
-            let (start, end) = match partitions {
-                None | Some("") => (None, None),
-                Some(range) if range.contains('-') => {
-                    let (start, end) = range
-                        .split_once('-')
-                        .ok_or_else(|| Error::Usage(format!("invalid partition range: {range}")))?;
-                    let start = parse_optional_partition_bound(start)?;
-                    let end = parse_optional_partition_bound(end)?;
-                    // Half-open bounds must be non-negative when present (open start uses empty left).
-                    if start.is_some_and(|bound| bound < 0) || end.is_some_and(|bound| bound < 0) {
-                        return Err(Error::Usage(format!(
-                            "partition bounds in {range} must be non-negative"
-                        )));
-                    }
-                    (start, end)
-                }
-                Some(partition) => {
-                    let partition = partition
-                        .parse::<i32>()
-                        .map_err(|_| Error::Usage(format!("invalid partition: {partition}")))?;
-                    if partition < 0 {
-                        return Err(Error::Usage(format!(
-                            "partition {partition} must be non-negative"
-                        )));
-                    }
-                    (Some(partition), Some(partition.saturating_add(1)))
+fn acls(
+    config: &rdkafka::ClientConfig,
+    timeout: Duration,
+    format: OutputFormat,
+    action: &AclAction,
+) -> Result<()> {
+    let client = admin(config)?;
+    let timeout_ms = duration_ms(timeout)?;
+    match action {
+        AclAction::List(filter) => {
+            let rows = acl_list_rows(client.inner().native_ptr(), filter, timeout_ms)?;
+            output::write_value(format, "acls.list", &rows, |rows| acl_table(rows))
+        }
+        AclAction::Add(mutation) => {
+            let operations =
+                if mutation.operation.is_empty() && !mutation.producer && !mutation.consumer {
+                    vec![AclOperation::All]
+                } else {
+                    acl_operations(&mutation.operation)?
+                };
+            let bindings = acl_bindings(mutation, &operations)?;
+            if !mutation.execute {
+                let rows = bindings.into_iter().map(acl_row).collect::<Vec<_>>();
+                return output::write_value(format, "acls.add.preview", &rows, |rows| {
+                    acl_table(rows)
+                });
+            }
+            let (bindings, already_exists) =
+                missing_acl_bindings(client.inner().native_ptr(), bindings, timeout_ms)?;
+            let result = if bindings.is_empty() {
+                ffi::AclMutationResult {
+                    matched: 0,
+                    failures: 0,
+                    errors: Vec::new(),
                 }
+            } else {
+                ffi::create_acls(client.inner().native_ptr(), &bindings, timeout_ms)?
             };
-            if start.is_some_and(|start| end.is_some_and(|end| start >= end)) {
-                return Err(Error::Usage("partition range must be increasing".into()));
+            write_acl_mutation_result(
+                format,
+                "acls.add",
+                &format!(
+                    "CREATED {}; ALREADY_EXISTS {already_exists}",
+                    result.matched.saturating_sub(result.failures)
+                ),
+                &result,
+            )?;
+            if result.failures == 0 {
+                Ok(())
+            } else {
+                Err(Error::Partial {
+                    failed: result.failures,
+                    total: bindings.len(),
+                })
             }
-            Ok(TopicPartitionPattern { topic, start, end })
-        })
-        .collect()
-}
-
-fn split_java_list(value: &str) -> Vec<&str> {
-    if value.is_empty() {
-        return vec![""];
-    }
-    let mut items = value.split(',').collect::<Vec<_>>();
-    while items.last() == Some(&"") {
-        items.pop();
-    }
-    items
-}
-
-fn parse_optional_partition_bound(value: &str) -> Result<Option<i32>> {
-    if value.is_empty() {
-        Ok(None)
-    } else {
-        value
-            .parse::<i32>()
-            .map(Some)
-            .map_err(|_| Error::Usage(format!("invalid partition bound: {value}")))
+        }
+        AclAction::Remove(mutation) => {
+            let operations = acl_operations(&mutation.operation)?;
+            let filters = acl_removal_filters(mutation, &operations)?;
+            if !mutation.execute {
+                let mut rows = Vec::new();
+                for filter in filters {
+                    rows.extend(
+                        ffi::describe_acls(client.inner().native_ptr(), &filter, timeout_ms)?
+                            .into_iter()
+                            .map(acl_row),
+                    );
+                }
+                return output::write_value(format, "acls.remove.preview", &rows, |rows| {
+                    acl_table(rows)
+                });
+            }
+            let total = filters.len();
+            let result = ffi::delete_acls(client.inner().native_ptr(), &filters, timeout_ms)?;
+            write_acl_mutation_result(
+                format,
+                "acls.remove",
+                &format!("DELETED {}", result.matched),
+                &result,
+            )?;
+            if result.failures == 0 {
+                Ok(())
+            } else {
+                Err(Error::Partial {
+                    failed: result.failures,
+                    total,
+                })
+            }
+        }
     }
 }
 
-#[derive(Debug, Deserialize)]
-struct DeleteRecordsFile {
-    #[serde(default)]
-    version: Option<u8>,
-    partitions: Vec<DeleteRecordsPartition>,
-}
-#[derive(Debug, Deserialize)]
-struct DeleteRecordsPartition {
-    topic: String,
-    partition: i32,
-    offset: i64,
+fn missing_acl_bindings(
+    client: *mut rdkafka_sys::rd_kafka_t,
+    requested: Vec<AclBinding>,
+    timeout_ms: i32,
+) -> Result<(Vec<AclBinding>, usize)> {
+    let mut queried_resources = HashSet::new();
+    let mut existing = HashSet::new();
+    for binding in &requested {
+        let resource = (
+            binding.resource_type,
+            binding.resource_name.clone(),
+            binding.pattern_type,
+        );
+        if queried_resources.insert(resource) {
+            existing.extend(ffi::describe_acls(
+                client,
+                &AclBindingFilter {
+                    resource_type: binding.resource_type,
+                    resource_name: Some(binding.resource_name.clone()),
+                    pattern_type: binding.pattern_type,
+                    principal: None,
+                    host: None,
+                    operation: AclOperation::Any,
+                    permission_type: AclPermissionType::Any,
+                },
+                timeout_ms,
+            )?);
+        }
+    }
+    Ok(filter_missing_acl_bindings(requested, &existing))
 }
 
-async fn delete_records(
-    config: &rdkafka::ClientConfig,
-    timeout: Duration,
-    format: OutputFormat,
-    path: &Path,
-    execute: bool,
-) -> Result<()> {
-    let input = read_delete_records(path)?;
-    let mut offsets = TopicPartitionList::new();
-    for item in &input.partitions {
-        offsets.add_partition_offset(&item.topic, item.partition, Offset::Offset(item.offset))?;
-    }
-    if !execute {
-        let rows = input
-            .partitions
-            .iter()
-            .map(|item| MutationRow {
-                resource: format!("{}:{}", item.topic, item.partition),
-                status: format!("PREVIEW BEFORE {}", item.offset),
-                error: None,
-            })
-            .collect::<Vec<_>>();
-        return write_mutation_rows(format, "delete-records", &rows);
-    }
-    let result = admin(config)?
-        .delete_records(
-            &offsets,
-            &AdminOptions::new().operation_timeout(Some(timeout)),
-        )
-        .await?;
-    let rows = result
-        .elements()
+fn filter_missing_acl_bindings(
+    requested: Vec<AclBinding>,
+    existing: &HashSet<AclBinding>,
+) -> (Vec<AclBinding>, usize) {
+    let requested_count = requested.len();
+    let missing = requested
         .into_iter()
-        .map(|element| MutationRow {
-            resource: format!("{}:{}", element.topic(), element.partition()),
-            status: format!("LOW_WATERMARK {:?}", element.offset()),
-            error: element.error().err().map(|error| error.to_string()),
-        })
+        .filter(|binding| !existing.contains(binding))
         .collect::<Vec<_>>();
-    write_mutation_rows(format, "delete-records", &rows)
+    let already_exists = requested_count.saturating_sub(missing.len());
+    (missing, already_exists)
 }
 
-fn read_delete_records(path: &Path) -> Result<DeleteRecordsFile> {
-    let input: DeleteRecordsFile = serde_json::from_reader(std::fs::File::open(path)?)?;
-    if input.version.is_some_and(|version| version != 1) {
-        return Err(Error::Usage("delete-records JSON version must be 1".into()));
-    }
-    if input.partitions.is_empty() {
-        return Err(Error::Usage(
-            "delete-records partition list cannot be empty".into(),
-        ));
-    }
-    let mut targets = BTreeSet::new();
-    for partition in &input.partitions {
-        if partition.topic.is_empty() || partition.partition < 0 || partition.offset < 0 {
-            return Err(Error::Usage(
-                "delete-records entries require a topic and non-negative partition/offset".into(),
-            ));
-        }
-        if !targets.insert((partition.topic.as_str(), partition.partition)) {
-            return Err(Error::Usage(format!(
-                "duplicate delete-records target {}:{}",
-                partition.topic, partition.partition
-            )));
+fn acl_list_rows(
+    client: *mut rdkafka_sys::rd_kafka_t,
+    filter: &crate::cli::AclFilterArgs,
+    timeout_ms: i32,
+) -> Result<Vec<AclRow>> {
+    let resources = acl_resources(filter)?;
+    let resources = if resources.is_empty() {
+        vec![(AclResourceType::Any, None)]
+    } else {
+        resources
+            .into_iter()
+            .map(|(resource_type, name)| (resource_type, Some(name)))
+            .collect()
+    };
+    let principals = normalized_acl_values(&filter.principal, "principal")?;
+    let mut seen = BTreeSet::new();
+    let mut rows = Vec::new();
+    for (resource_type, resource_name) in resources {
+        let acl_filter = AclBindingFilter {
+            resource_type,
+            resource_name,
+            pattern_type: acl_wire_pattern(filter.resource_pattern_type),
+            principal: None,
+            host: None,
+            operation: AclOperation::Any,
+            permission_type: AclPermissionType::Any,
+        };
+        for binding in ffi::describe_acls(client, &acl_filter, timeout_ms)? {
+            if !principals.is_empty() && !principals.contains(&binding.principal) {
+                continue;
+            }
+            let row = acl_row(binding);
+            let key = (
+                row.resource_type.clone(),
+                row.resource_name.clone(),
+                row.pattern_type.clone(),
+                row.principal.clone(),
+                row.host.clone(),
+                row.operation.clone(),
+                row.permission.clone(),
+            );
+            if seen.insert(key) {
+                rows.push(row);
+            }
         }
     }
-    Ok(input)
-}
-
-#[derive(Debug, Serialize)]
-struct ApiVersionRow {
-    broker: i32,
-    host: String,
-    api: String,
-    api_key: i16,
-    min_version: i16,
-    max_version: i16,
+    Ok(rows)
 }
 
-async fn api_versions(
-    bootstrap: &str,
-    command_config: Option<&Path>,
-    timeout: Duration,
+fn write_acl_mutation_result(
     format: OutputFormat,
-    broker: Option<i32>,
+    command: &str,
+    status: &str,
+    result: &ffi::AclMutationResult,
 ) -> Result<()> {
-    let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
-    let cluster = client.describe_cluster().await?;
-    let mut rows = Vec::new();
-    for endpoint in cluster
-        .brokers
-        .iter()
-        .filter(|endpoint| broker.is_none_or(|id| id == endpoint.broker_id))
-    {
-        let address = format!("{}:{}", endpoint.host, endpoint.port);
-        let connection = client
-            .pool()
-            .get_connection_by_id(endpoint.broker_id, &address)
-            .await?;
-        for (api_key, min_version, max_version) in broker_api_versions(&connection).await? {
-            rows.push(ApiVersionRow {
-                broker: endpoint.broker_id,
-                host: address.clone(),
-                api: format!("{:?}", ApiKey::from(api_key)),
-                api_key,
-                min_version,
-                max_version,
-            });
-        }
-        drop(connection);
-    }
-    drop(cluster);
-    drop(client);
-    output::write_value(format, "api-versions", &rows, |rows| {
+    let rows = [MutationRow {
+        resource: "acl-bindings".into(),
+        status: status.into(),
+        error: None,
+    }];
+    output::write_value_with_errors(format, command, &rows, &result.errors, |rows| {
         output::table(
-            ["BROKER", "HOST", "API", "KEY", "MIN", "MAX"],
+            ["RESOURCE", "STATUS", "ERROR"],
             rows.iter().map(|row| {
                 [
-                    row.broker.to_string(),
-                    row.host.clone(),
-                    row.api.clone(),
-                    row.api_key.to_string(),
-                    row.min_version.to_string(),
-                    row.max_version.to_string(),
+                    row.resource.clone(),
+                    row.status.clone(),
+                    row.error.as_deref().unwrap_or("-").to_owned(),
                 ]
             }),
         )
     })
 }
 
-async fn broker_api_versions(
-    connection: &krafka::network::BrokerConnection,
-) -> Result<Vec<(i16, i16, i16)>> {
-    let mut response = connection
-        .send_request(ApiKey::ApiVersions, 0, |_| Ok(()))
-        .await?;
-    let error_code = i16::decode(&mut response)?;
-    if error_code != 0 {
-        return Err(Error::Config(format!(
-            "ApiVersions failed with Kafka error code {error_code}"
-        )));
+fn acl_operations(values: &[String]) -> Result<Vec<AclOperation>> {
+    let mut operations = Vec::new();
+    for value in values {
+        let operation = match value.trim().to_ascii_lowercase().as_str() {
+            "all" => AclOperation::All,
+            "read" => AclOperation::Read,
+            "write" => AclOperation::Write,
+            "create" => AclOperation::Create,
+            "delete" => AclOperation::Delete,
+            "alter" => AclOperation::Alter,
+            "describe" => AclOperation::Describe,
+            "cluster-action" => AclOperation::ClusterAction,
+            "describe-configs" => AclOperation::DescribeConfigs,
+            "alter-configs" => AclOperation::AlterConfigs,
+            "idempotent-write" => AclOperation::IdempotentWrite,
+            "two-phase-commit" | "create-tokens" | "describe-tokens" => {
+                return Err(Error::Unsupported(format!(
+                    "librdkafka 2.12 does not support ACL operation: {value}"
+                )));
+            }
+            _ => return Err(Error::Usage(format!("unknown ACL operation: {value}"))),
+        };
+        if !operations.contains(&operation) {
+            operations.push(operation);
+        }
+    }
+    Ok(operations)
+}
+
+fn normalized_acl_values(values: &[String], label: &str) -> Result<BTreeSet<String>> {
+    values
+        .iter()
+        .map(|value| {
+            let value = value.trim();
+            if value.is_empty() {
+                Err(Error::Usage(format!("ACL {label} cannot be empty")))
+            } else {
+                Ok(value.to_owned())
+            }
+        })
+        .collect()
+}
+
+fn acl_resources(filter: &crate::cli::AclFilterArgs) -> Result<Vec<(AclResourceType, String)>> {
+    let mut resources = Vec::new();
+    for topic in normalized_acl_values(&filter.topic, "topic")? {
+        resources.push((AclResourceType::Topic, topic));
     }
-    let count = decode_acl_count(&mut response)?;
-    let mut versions = Vec::with_capacity(count);
-    for _ in 0..count {
-        versions.push((
-            i16::decode(&mut response)?,
-            i16::decode(&mut response)?,
-            i16::decode(&mut response)?,
+    for group in normalized_acl_values(&filter.group, "group")? {
+        resources.push((AclResourceType::Group, group));
+    }
+    if filter.cluster {
+        resources.push((AclResourceType::Cluster, "kafka-cluster".into()));
+    }
+    for transactional_id in normalized_acl_values(&filter.transactional_id, "transactional ID")? {
+        resources.push((AclResourceType::TransactionalId, transactional_id));
+    }
+    if !filter.delegation_token.is_empty() {
+        return Err(Error::Unsupported(
+            "librdkafka does not support delegation-token ACL resources".into(),
         ));
     }
-    Ok(versions)
-}
-
-#[derive(Serialize)]
-struct BrokerRow {
-    id: i32,
-    host: String,
-    port: u16,
-    rack: Option<String>,
-    fenced: bool,
-    endpoint_type: String,
+    Ok(resources)
 }
 
-fn broker_table(rows: &[BrokerRow]) -> String {
-    output::table(
-        ["ID", "HOST", "PORT", "RACK", "STATE", "ENDPOINT_TYPE"],
-        rows.iter().map(|row| {
-            [
-                row.id.to_string(),
-                row.host.clone(),
-                row.port.to_string(),
-                row.rack.as_deref().unwrap_or("-").to_owned(),
-                if row.fenced { "fenced" } else { "unfenced" }.into(),
-                row.endpoint_type.clone(),
-            ]
-        }),
-    )
+const fn acl_wire_pattern(pattern: crate::cli::AclResourcePattern) -> AclPatternType {
+    match pattern {
+        crate::cli::AclResourcePattern::Any => AclPatternType::Any,
+        crate::cli::AclResourcePattern::Literal => AclPatternType::Literal,
+        crate::cli::AclResourcePattern::Match => AclPatternType::Match,
+        crate::cli::AclResourcePattern::Prefixed => AclPatternType::Prefixed,
+    }
 }
 
-async fn fenced_cluster_rows(
-    bootstrap: &str,
-    command_config: Option<&Path>,
-    timeout: Duration,
-) -> Result<Vec<BrokerRow>> {
-    let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
-    let cluster = client.describe_cluster().await?;
-    let broker = cluster
-        .brokers
-        .first()
-        .ok_or_else(|| Error::Config("cluster returned no reachable broker".into()))?;
-    let connection = client
-        .pool()
-        .get_connection_by_id(
-            broker.broker_id,
-            &format!("{}:{}", broker.host, broker.port),
-        )
-        .await?;
-    let version = connection
-        .negotiate_api_version(ApiKey::DescribeCluster, 2, 2)
-        .await
-        .ok_or_else(|| {
-            Error::Unsupported(
-                "broker does not support fenced broker listing (DescribeCluster v2)".into(),
-            )
-        })?;
-    let request = DescribeClusterRequest {
-        include_cluster_authorized_operations: false,
-        endpoint_type: 1,
-        include_fenced_brokers: true,
-    };
-    let mut response = connection
-        .send_request(ApiKey::DescribeCluster, version, |buffer| {
-            request.encode_versioned(version, buffer)
-        })
-        .await?;
-    drop(connection);
-    drop(client);
-    let response = DescribeClusterResponse::decode_versioned(version, &mut response)?;
-    if !response.error_code.is_ok() {
-        return Err(Error::Config(
-            response
-                .error_message
-                .unwrap_or_else(|| format!("{:?}", response.error_code)),
+fn acl_bindings(
+    mutation: &crate::cli::AclMutationArgs,
+    operations: &[AclOperation],
+) -> Result<Vec<AclBinding>> {
+    if matches!(
+        mutation.filter.resource_pattern_type,
+        crate::cli::AclResourcePattern::Any | crate::cli::AclResourcePattern::Match
+    ) {
+        return Err(Error::Usage(
+            "ACL creation requires literal or prefixed resource pattern type".into(),
         ));
     }
-    response
-        .brokers
+    validate_acl_entry_values(mutation)?;
+    let resources = acl_mutation_resources(mutation, operations)?;
+    validate_acl_resource_operations(&resources)?;
+    let mut principals = normalized_acl_values(&mutation.allow_principal, "allow principal")?
         .into_iter()
-        .map(|broker| {
-            Ok(BrokerRow {
-                id: broker.broker_id,
-                host: broker.host,
-                port: u16::try_from(broker.port).map_err(|_| {
-                    Error::Config(format!("broker {} returned invalid port", broker.broker_id))
-                })?,
-                rack: broker.rack,
-                fenced: broker.is_fenced,
-                endpoint_type: "broker".into(),
-            })
-        })
-        .collect()
-}
-
-async fn cluster(
-    config: &rdkafka::ClientConfig,
-    bootstrap: &str,
-    command_config: Option<&Path>,
-    timeout: Duration,
-    format: OutputFormat,
-    action: &ClusterAction,
-) -> Result<()> {
-    match action {
-        ClusterAction::Id => {
-            let client = admin(config)?;
-            let id = ffi::describe_cluster(client.inner().native_ptr(), duration_ms(timeout)?)?
-                .cluster_id;
-            output::write_value(format, "cluster.id", &id, |id| {
-                output::table(["CLUSTER_ID"], [[id.clone()]])
-            })
-        }
-        ClusterAction::ListEndpoints {
-            include_fenced_brokers,
-        } => {
-            let rows = if *include_fenced_brokers {
-                fenced_cluster_rows(bootstrap, command_config, timeout).await?
+        .map(|principal| (principal, AclPermissionType::Allow))
+        .chain(
+            normalized_acl_values(&mutation.deny_principal, "deny principal")?
+                .into_iter()
+                .map(|principal| (principal, AclPermissionType::Deny)),
+        )
+        .collect::<Vec<_>>();
+    if principals.is_empty() {
+        principals.extend(
+            normalized_acl_values(&mutation.filter.principal, "principal")?
+                .into_iter()
+                .map(|principal| (principal, AclPermissionType::Allow)),
+        );
+    }
+    if principals.is_empty() {
+        return Err(Error::Usage(
+            "provide --allow-principal or --deny-principal".into(),
+        ));
+    }
+    let allow_hosts = normalized_acl_values(&mutation.allow_host, "allow host")?;
+    let deny_hosts = normalized_acl_values(&mutation.deny_host, "deny host")?;
+    let mut bindings = Vec::new();
+    for (resource_type, resource_name, operations) in resources {
+        for (principal, permission) in &principals {
+            let configured_hosts = if *permission == AclPermissionType::Allow {
+                &allow_hosts
             } else {
-                let client = admin(config)?;
-                ffi::describe_cluster(client.inner().native_ptr(), duration_ms(timeout)?)?
-                    .nodes
-                    .into_iter()
-                    .map(|broker| BrokerRow {
-                        id: broker.id,
-                        host: broker.host,
-                        port: broker.port,
-                        rack: broker.rack,
-                        fenced: false,
-                        endpoint_type: "broker".into(),
-                    })
-                    .collect()
+                &deny_hosts
             };
-            output::write_value(format, "cluster.list-endpoints", &rows, |rows| {
-                broker_table(rows)
-            })
+            let hosts = if configured_hosts.is_empty() {
+                vec![mutation.host.as_deref().map_or("*", str::trim)]
+            } else {
+                configured_hosts.iter().map(String::as_str).collect()
+            };
+            for host in hosts {
+                for operation in &operations {
+                    bindings.push(AclBinding {
+                        resource_type,
+                        resource_name: resource_name.clone(),
+                        pattern_type: acl_wire_pattern(mutation.filter.resource_pattern_type),
+                        principal: principal.clone(),
+                        host: host.trim().to_owned(),
+                        operation: *operation,
+                        permission_type: *permission,
+                    });
+                }
+            }
+        }
+    }
+    Ok(bindings)
+}
+
+fn acl_removal_filters(
+    mutation: &crate::cli::AclMutationArgs,
+    operations: &[AclOperation],
+) -> Result<Vec<AclBindingFilter>> {
+    validate_acl_entry_values(mutation)?;
+    let resources = if mutation.producer || mutation.consumer {
+        acl_mutation_resources(mutation, operations)?
+    } else {
+        let resources = acl_resources(&mutation.filter)?;
+        if resources.is_empty() {
+            vec![(AclResourceType::Any, String::new(), operations.to_vec())]
+        } else {
+            resources
+                .into_iter()
+                .map(|(resource_type, resource_name)| {
+                    (resource_type, resource_name, operations.to_vec())
+                })
+                .collect()
         }
-        ClusterAction::ApiVersions => {
-            api_versions(bootstrap, command_config, timeout, format, None).await
+    };
+    let mut entries = acl_removal_entries(mutation)?;
+    if entries.is_empty() {
+        entries.extend(
+            normalized_acl_values(&mutation.filter.principal, "principal")?
+                .into_iter()
+                .map(|principal| {
+                    (
+                        principal,
+                        mutation.host.as_deref().map_or("*", str::trim).to_owned(),
+                        AclPermissionType::Any,
+                    )
+                }),
+        );
+    }
+    if !entries.is_empty() {
+        validate_acl_resource_operations(&resources)?;
+    }
+    let mut filters = Vec::new();
+    for (resource_type, resource_name, operations) in resources {
+        if entries.is_empty() {
+            filters.push(AclBindingFilter {
+                resource_type,
+                resource_name: (!resource_name.is_empty()).then(|| resource_name.clone()),
+                pattern_type: acl_wire_pattern(mutation.filter.resource_pattern_type),
+                principal: None,
+                host: None,
+                operation: AclOperation::Any,
+                permission_type: AclPermissionType::Any,
+            });
+            continue;
         }
-        ClusterAction::Unregister { id, execute } => {
-            if !execute {
-                return write_mutation_rows(
-                    format,
-                    "cluster.unregister",
-                    &[MutationRow {
-                        resource: format!("broker:{id}"),
-                        status: "PREVIEW".into(),
-                        error: None,
-                    }],
-                );
+        let operations = if operations.is_empty() {
+            vec![AclOperation::All]
+        } else {
+            operations
+        };
+        for (principal, host, permission) in &entries {
+            for operation in &operations {
+                filters.push(AclBindingFilter {
+                    resource_type,
+                    resource_name: (!resource_name.is_empty()).then(|| resource_name.clone()),
+                    pattern_type: acl_wire_pattern(mutation.filter.resource_pattern_type),
+                    principal: Some(principal.clone()),
+                    host: Some(host.clone()),
+                    operation: *operation,
+                    permission_type: *permission,
+                });
             }
-            let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
-            unregister_broker(&client, *id).await?;
-            write_mutation_rows(
-                format,
-                "cluster.unregister",
-                &[MutationRow {
-                    resource: format!("broker:{id}"),
-                    status: "UNREGISTERED".into(),
-                    error: None,
-                }],
-            )
         }
     }
+    Ok(filters)
 }
 
-async fn unregister_broker(client: &krafka::admin::AdminClient, broker_id: i32) -> Result<()> {
-    let connection = client.get_controller_connection().await?;
-    let mut response = connection
-        .send_request(ApiKey::UnregisterBroker, 0, |buffer| {
-            broker_id.try_encode(buffer)?;
-            TaggedFields::default().try_encode(buffer)
-        })
-        .await?;
-    drop(connection);
-    let _throttle_time_ms = i32::decode(&mut response)?;
-    let error_code = i16::decode(&mut response)?;
-    let error_message = KafkaString::decode_compact(&mut response)?.0;
-    let _tagged_fields = TaggedFields::decode(&mut response)?;
-    if error_code == 0 {
-        Ok(())
-    } else {
-        Err(Error::Config(error_message.unwrap_or_else(|| {
-            format!("UnregisterBroker failed with Kafka error code {error_code}")
-        })))
+fn acl_removal_entries(
+    mutation: &crate::cli::AclMutationArgs,
+) -> Result<Vec<(String, String, AclPermissionType)>> {
+    let allow_principals = normalized_acl_values(&mutation.allow_principal, "allow principal")?;
+    let deny_principals = normalized_acl_values(&mutation.deny_principal, "deny principal")?;
+    let allow_hosts = normalized_acl_values(&mutation.allow_host, "allow host")?;
+    let deny_hosts = normalized_acl_values(&mutation.deny_host, "deny host")?;
+    let default_host = mutation.host.as_deref().map_or("*", str::trim);
+    let allowed = allow_principals.iter().flat_map(|principal| {
+        let hosts = if allow_hosts.is_empty() {
+            vec![default_host]
+        } else {
+            allow_hosts.iter().map(String::as_str).collect()
+        };
+        hosts
+            .into_iter()
+            .map(move |host| (principal.clone(), host.to_owned(), AclPermissionType::Allow))
+    });
+    let denied = deny_principals.iter().flat_map(|principal| {
+        let hosts = if deny_hosts.is_empty() {
+            vec![default_host]
+        } else {
+            deny_hosts.iter().map(String::as_str).collect()
+        };
+        hosts
+            .into_iter()
+            .map(move |host| (principal.clone(), host.to_owned(), AclPermissionType::Deny))
+    });
+    Ok(allowed.chain(denied).collect())
+}
+
+fn validate_acl_entry_values(mutation: &crate::cli::AclMutationArgs) -> Result<()> {
+    normalized_acl_values(&mutation.allow_principal, "allow principal")?;
+    normalized_acl_values(&mutation.deny_principal, "deny principal")?;
+    normalized_acl_values(&mutation.allow_host, "allow host")?;
+    normalized_acl_values(&mutation.deny_host, "deny host")?;
+    if mutation
+        .host
+        .as_deref()
+        .is_some_and(|host| host.trim().is_empty())
+    {
+        return Err(Error::Usage("ACL host cannot be empty".into()));
     }
+    Ok(())
 }
 
-fn acls(
-    config: &rdkafka::ClientConfig,
-    timeout: Duration,
-    format: OutputFormat,
-    action: &AclAction,
+fn validate_acl_resource_operations(
+    resources: &[(AclResourceType, String, Vec<AclOperation>)],
 ) -> Result<()> {
-    let client = admin(config)?;
-    let timeout_ms = duration_ms(timeout)?;
-    match action {
-        AclAction::List(filter) => {
-            let rows = acl_list_rows(client.inner().native_ptr(), filter, timeout_ms)?;
-            output::write_value(format, "acls.list", &rows, |rows| acl_table(rows))
-        }
-        AclAction::Add(mutation) => {
-            let operations =
-                if mutation.operation.is_empty() && !mutation.producer && !mutation.consumer {
-                    vec![AclOperation::All]
-                } else {
-                    acl_operations(&mutation.operation)?
-                };
-            let bindings = acl_bindings(mutation, &operations)?;
-            if !mutation.execute {
-                let rows = bindings.into_iter().map(acl_row).collect::<Vec<_>>();
-                return output::write_value(format, "acls.add.preview", &rows, |rows| {
-                    acl_table(rows)
-                });
-            }
-            let (bindings, already_exists) =
-                missing_acl_bindings(client.inner().native_ptr(), bindings, timeout_ms)?;
-            let result = if bindings.is_empty() {
-                ffi::AclMutationResult {
-                    matched: 0,
-                    failures: 0,
-                    errors: Vec::new(),
-                }
-            } else {
-                ffi::create_acls(client.inner().native_ptr(), &bindings, timeout_ms)?
-            };
-            write_acl_mutation_result(
-                format,
-                "acls.add",
-                &format!(
-                    "CREATED {}; ALREADY_EXISTS {already_exists}",
-                    result.matched.saturating_sub(result.failures)
-                ),
-                &result,
-            )?;
-            if result.failures == 0 {
-                Ok(())
-            } else {
-                Err(Error::Partial {
-                    failed: result.failures,
-                    total: bindings.len(),
-                })
-            }
+    for (resource_type, _, operations) in resources {
+        if let Some(operation) = operations
+            .iter()
+            .find(|operation| !acl_operation_supported(*resource_type, **operation))
+        {
+            return Err(Error::Usage(format!(
+                "ACL resource type {resource_type:?} does not support operation {operation:?}"
+            )));
         }
-        AclAction::Remove(mutation) => {
-            let operations = acl_operations(&mutation.operation)?;
-            let filters = acl_removal_filters(mutation, &operations)?;
-            if !mutation.execute {
-                let mut rows = Vec::new();
-                for filter in filters {
-                    rows.extend(
-                        ffi::describe_acls(client.inner().native_ptr(), &filter, timeout_ms)?
-                            .into_iter()
-                            .map(acl_row),
-                    );
-                }
-                return output::write_value(format, "acls.remove.preview", &rows, |rows| {
-                    acl_table(rows)
-                });
-            }
-            let total = filters.len();
-            let result = ffi::delete_acls(client.inner().native_ptr(), &filters, timeout_ms)?;
-            write_acl_mutation_result(
-                format,
-                "acls.remove",
-                &format!("DELETED {}", result.matched),
-                &result,
-            )?;
-            if result.failures == 0 {
-                Ok(())
-            } else {
-                Err(Error::Partial {
-                    failed: result.failures,
-                    total,
-                })
-            }
+    }
+    Ok(())
+}
+
+const fn acl_operation_supported(resource_type: AclResourceType, operation: AclOperation) -> bool {
+    if matches!(operation, AclOperation::Any | AclOperation::All) {
+        return true;
+    }
+    match resource_type {
+        AclResourceType::Any => true,
+        AclResourceType::Topic => matches!(
+            operation,
+            AclOperation::Read
+                | AclOperation::Write
+                | AclOperation::Create
+                | AclOperation::Delete
+                | AclOperation::Alter
+                | AclOperation::Describe
+                | AclOperation::DescribeConfigs
+                | AclOperation::AlterConfigs
+        ),
+        AclResourceType::Group => matches!(
+            operation,
+            AclOperation::Read
+                | AclOperation::Delete
+                | AclOperation::Describe
+                | AclOperation::DescribeConfigs
+                | AclOperation::AlterConfigs
+        ),
+        AclResourceType::Cluster => matches!(
+            operation,
+            AclOperation::Create
+                | AclOperation::Alter
+                | AclOperation::Describe
+                | AclOperation::ClusterAction
+                | AclOperation::DescribeConfigs
+                | AclOperation::AlterConfigs
+                | AclOperation::IdempotentWrite
+        ),
+        AclResourceType::TransactionalId => {
+            matches!(operation, AclOperation::Write | AclOperation::Describe)
         }
     }
 }
 
-fn missing_acl_bindings(
-    client: *mut rdkafka_sys::rd_kafka_t,
-    requested: Vec<AclBinding>,
-    timeout_ms: i32,
-) -> Result<(Vec<AclBinding>, usize)> {
-    let mut queried_resources = HashSet::new();
-    let mut existing = HashSet::new();
-    for binding in &requested {
-        let resource = (
-            binding.resource_type,
-            binding.resource_name.clone(),
-            binding.pattern_type,
-        );
-        if queried_resources.insert(resource) {
-            existing.extend(ffi::describe_acls(
-                client,
-                &AclBindingFilter {
-                    resource_type: binding.resource_type,
-                    resource_name: Some(binding.resource_name.clone()),
-                    pattern_type: binding.pattern_type,
-                    principal: None,
-                    host: None,
-                    operation: AclOperation::Any,
-                    permission_type: AclPermissionType::Any,
-                },
-                timeout_ms,
-            )?);
+fn acl_mutation_resources(
+    mutation: &crate::cli::AclMutationArgs,
+    operations: &[AclOperation],
+) -> Result<Vec<(AclResourceType, String, Vec<AclOperation>)>> {
+    if !mutation.producer && !mutation.consumer {
+        let resources = acl_resources(&mutation.filter)?;
+        if resources.is_empty() {
+            return Err(Error::Usage("an ACL resource selector is required".into()));
         }
+        return Ok(resources
+            .into_iter()
+            .map(|(resource_type, resource_name)| {
+                (resource_type, resource_name, operations.to_vec())
+            })
+            .collect());
     }
-    Ok(filter_missing_acl_bindings(requested, &existing))
-}
-
-fn filter_missing_acl_bindings(
-    requested: Vec<AclBinding>,
-    existing: &HashSet<AclBinding>,
-) -> (Vec<AclBinding>, usize) {
-    let requested_count = requested.len();
-    let missing = requested
+    if !mutation.deny_principal.is_empty() || !mutation.deny_host.is_empty() {
+        return Err(Error::Usage(
+            "role ACLs only support allow principals and hosts".into(),
+        ));
+    }
+    if mutation.consumer
+        && !mutation.producer
+        && (mutation.filter.cluster || !mutation.filter.transactional_id.is_empty())
+    {
+        return Err(Error::Usage(
+            "--consumer cannot be combined with --cluster or --transactional-id unless --producer is also set"
+                .into(),
+        ));
+    }
+    let topics = normalized_acl_values(&mutation.filter.topic, "topic")?;
+    if topics.is_empty() {
+        return Err(Error::Usage(
+            "--producer and --consumer require --topic".into(),
+        ));
+    }
+    let mut topic_operations = Vec::new();
+    if mutation.producer {
+        topic_operations.extend([
+            AclOperation::Write,
+            AclOperation::Describe,
+            AclOperation::Create,
+        ]);
+    }
+    if mutation.consumer {
+        for operation in [AclOperation::Read, AclOperation::Describe] {
+            if !topic_operations.contains(&operation) {
+                topic_operations.push(operation);
+            }
+        }
+    }
+    let mut resources = topics
         .into_iter()
-        .filter(|binding| !existing.contains(binding))
+        .map(|topic| (AclResourceType::Topic, topic, topic_operations.clone()))
         .collect::<Vec<_>>();
-    let already_exists = requested_count.saturating_sub(missing.len());
-    (missing, already_exists)
+    if mutation.consumer {
+        let groups = normalized_acl_values(&mutation.filter.group, "group")?;
+        if groups.is_empty() {
+            return Err(Error::Usage("--consumer requires --group".into()));
+        }
+        resources.extend(
+            groups
+                .into_iter()
+                .map(|group| (AclResourceType::Group, group, vec![AclOperation::Read])),
+        );
+    }
+    if mutation.producer {
+        resources.extend(
+            normalized_acl_values(&mutation.filter.transactional_id, "transactional ID")?
+                .into_iter()
+                .map(|transactional_id| {
+                    (
+                        AclResourceType::TransactionalId,
+                        transactional_id,
+                        vec![AclOperation::Write, AclOperation::Describe],
+                    )
+                }),
+        );
+    }
+    if mutation.idempotent {
+        resources.push((
+            AclResourceType::Cluster,
+            "kafka-cluster".into(),
+            vec![AclOperation::IdempotentWrite],
+        ));
+    }
+    Ok(resources)
 }
 
-fn acl_list_rows(
-    client: *mut rdkafka_sys::rd_kafka_t,
-    filter: &crate::cli::AclFilterArgs,
-    timeout_ms: i32,
-) -> Result<Vec<AclRow>> {
-    let resources = acl_resources(filter)?;
-    let resources = if resources.is_empty() {
-        vec![(AclResourceType::Any, None)]
-    } else {
-        resources
-            .into_iter()
-            .map(|(resource_type, name)| (resource_type, Some(name)))
-            .collect()
-    };
-    let principals = normalized_acl_values(&filter.principal, "principal")?;
-    let mut seen = BTreeSet::new();
-    let mut rows = Vec::new();
-    for (resource_type, resource_name) in resources {
-        let acl_filter = AclBindingFilter {
-            resource_type,
-            resource_name,
-            pattern_type: acl_wire_pattern(filter.resource_pattern_type),
-            principal: None,
-            host: None,
-            operation: AclOperation::Any,
-            permission_type: AclPermissionType::Any,
-        };
-        for binding in ffi::describe_acls(client, &acl_filter, timeout_ms)? {
-            if !principals.is_empty() && !principals.contains(&binding.principal) {
-                continue;
-            }
-            let row = acl_row(binding);
-            let key = (
-                row.resource_type.clone(),
-                row.resource_name.clone(),
-                row.pattern_type.clone(),
-                row.principal.clone(),
-                row.host.clone(),
-                row.operation.clone(),
-                row.permission.clone(),
-            );
-            if seen.insert(key) {
-                rows.push(row);
-            }
-        }
+fn acl_row(binding: AclBinding) -> AclRow {
+    AclRow {
+        resource_type: format!("{:?}", binding.resource_type),
+        resource_name: binding.resource_name,
+        pattern_type: format!("{:?}", binding.pattern_type),
+        principal: binding.principal,
+        host: binding.host,
+        operation: format!("{:?}", binding.operation),
+        permission: format!("{:?}", binding.permission_type),
     }
-    Ok(rows)
 }
 
-fn write_acl_mutation_result(
-    format: OutputFormat,
-    command: &str,
-    status: &str,
-    result: &ffi::AclMutationResult,
-) -> Result<()> {
-    let rows = [MutationRow {
-        resource: "acl-bindings".into(),
-        status: status.into(),
-        error: None,
-    }];
-    output::write_value_with_errors(format, command, &rows, &result.errors, |rows| {
-        output::table(
-            ["RESOURCE", "STATUS", "ERROR"],
-            rows.iter().map(|row| {
-                [
-                    row.resource.clone(),
-                    row.status.clone(),
-                    row.error.as_deref().unwrap_or("-").to_owned(),
-                ]
-            }),
-        )
-    })
+fn decode_acl_count(buffer: &mut bytes::Bytes) -> Result<usize> {
+    let count = i32::decode(buffer)?;
+    usize::try_from(count).map_err(|_| Error::Config("negative Kafka array length".into()))
+}
+
+fn decode_required_acl_string(buffer: &mut bytes::Bytes, field: &str) -> Result<String> {
+    KafkaString::decode(buffer)?
+        .0
+        .ok_or_else(|| Error::Config(format!("broker returned null {field}")))
+}
+
+#[derive(Debug, Serialize)]
+struct AclRow {
+    resource_type: String,
+    resource_name: String,
+    pattern_type: String,
+    principal: String,
+    host: String,
+    operation: String,
+    permission: String,
+}
+
+fn acl_table(rows: &[AclRow]) -> String {
+    output::table(
+        [
+            "RESOURCE",
+            "NAME",
+            "PATTERN",
+            "PRINCIPAL",
+            "HOST",
+            "OPERATION",
+            "PERMISSION",
+        ],
+        rows.iter().map(|row| {
+            [
+                row.resource_type.clone(),
+                row.resource_name.clone(),
+                row.pattern_type.clone(),
+                row.principal.clone(),
+                row.host.clone(),
+                row.operation.clone(),
+                row.permission.clone(),
+            ]
+        }),
+    )
 }
 
-fn acl_operations(values: &[String]) -> Result<Vec<AclOperation>> {
-    let mut operations = Vec::new();
-    for value in values {
-        let operation = match value.trim().to_ascii_lowercase().as_str() {
-            "all" => AclOperation::All,
-            "read" => AclOperation::Read,
-            "write" => AclOperation::Write,
-            "create" => AclOperation::Create,
-            "delete" => AclOperation::Delete,
-            "alter" => AclOperation::Alter,
-            "describe" => AclOperation::Describe,
-            "cluster-action" => AclOperation::ClusterAction,
-            "describe-configs" => AclOperation::DescribeConfigs,
-            "alter-configs" => AclOperation::AlterConfigs,
-            "idempotent-write" => AclOperation::IdempotentWrite,
-            "two-phase-commit" | "create-tokens" | "describe-tokens" => {
-                return Err(Error::Unsupported(format!(
-                    "librdkafka 2.12 does not support ACL operation: {value}"
-                )));
-            }
-            _ => return Err(Error::Usage(format!("unknown ACL operation: {value}"))),
-        };
-        if !operations.contains(&operation) {
-            operations.push(operation);
-        }
-    }
-    Ok(operations)
+fn duration_ms(duration: Duration) -> Result<i32> {
+    i32::try_from(duration.as_millis())
+        .map_err(|_| Error::Usage("timeout exceeds librdkafka's supported range".into()))
 }
 
-fn normalized_acl_values(values: &[String], label: &str) -> Result<BTreeSet<String>> {
-    values
-        .iter()
-        .map(|value| {
-            let value = value.trim();
-            if value.is_empty() {
-                Err(Error::Usage(format!("ACL {label} cannot be empty")))
-            } else {
-                Ok(value.to_owned())
-            }
-        })
-        .collect()
+#[derive(Debug, Deserialize)]
+struct TopicsToMoveFile {
+    topics: Vec<TopicToMove>,
 }
 
-fn acl_resources(filter: &crate::cli::AclFilterArgs) -> Result<Vec<(AclResourceType, String)>> {
-    let mut resources = Vec::new();
-    for topic in normalized_acl_values(&filter.topic, "topic")? {
-        resources.push((AclResourceType::Topic, topic));
-    }
-    for group in normalized_acl_values(&filter.group, "group")? {
-        resources.push((AclResourceType::Group, group));
-    }
-    if filter.cluster {
-        resources.push((AclResourceType::Cluster, "kafka-cluster".into()));
-    }
-    for transactional_id in normalized_acl_values(&filter.transactional_id, "transactional ID")? {
-        resources.push((AclResourceType::TransactionalId, transactional_id));
-    }
-    if !filter.delegation_token.is_empty() {
-        return Err(Error::Unsupported(
-            "librdkafka does not support delegation-token ACL resources".into(),
-        ));
-    }
-    Ok(resources)
+#[derive(Debug, Deserialize)]
+struct TopicToMove {
+    topic: String,
 }
 
-const fn acl_wire_pattern(pattern: crate::cli::AclResourcePattern) -> AclPatternType {
-    match pattern {
-        crate::cli::AclResourcePattern::Any => AclPatternType::Any,
-        crate::cli::AclResourcePattern::Literal => AclPatternType::Literal,
-        crate::cli::AclResourcePattern::Match => AclPatternType::Match,
-        crate::cli::AclResourcePattern::Prefixed => AclPatternType::Prefixed,
-    }
+#[derive(Debug, Deserialize, Serialize)]
+struct ReassignmentFile {
+    version: u8,
+    partitions: Vec<ReassignmentPartition>,
 }
 
-fn acl_bindings(
-    mutation: &crate::cli::AclMutationArgs,
-    operations: &[AclOperation],
-) -> Result<Vec<AclBinding>> {
-    if matches!(
-        mutation.filter.resource_pattern_type,
-        crate::cli::AclResourcePattern::Any | crate::cli::AclResourcePattern::Match
-    ) {
-        return Err(Error::Usage(
-            "ACL creation requires literal or prefixed resource pattern type".into(),
-        ));
-    }
-    validate_acl_entry_values(mutation)?;
-    let resources = acl_mutation_resources(mutation, operations)?;
-    validate_acl_resource_operations(&resources)?;
-    let mut principals = normalized_acl_values(&mutation.allow_principal, "allow principal")?
-        .into_iter()
-        .map(|principal| (principal, AclPermissionType::Allow))
-        .chain(
-            normalized_acl_values(&mutation.deny_principal, "deny principal")?
-                .into_iter()
-                .map(|principal| (principal, AclPermissionType::Deny)),
-        )
-        .collect::<Vec<_>>();
-    if principals.is_empty() {
-        principals.extend(
-            normalized_acl_values(&mutation.filter.principal, "principal")?
+#[derive(Debug, Clone, Deserialize, Serialize)]
+struct ReassignmentPartition {
+    topic: String,
+    partition: i32,
+    replicas: Vec<i32>,
+    #[serde(default)]
+    log_dirs: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct ReassignmentStatus {
+    topic: String,
+    partition: i32,
+    replicas: Vec<i32>,
+    adding_replicas: Vec<i32>,
+    removing_replicas: Vec<i32>,
+    complete: bool,
+}
+
+#[expect(
+    clippy::too_many_lines,
+    reason = "the branches correspond directly to Kafka's reassignment actions"
+)]
+async fn reassign(
+    config: &rdkafka::ClientConfig,
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    timeout: Duration,
+    format: OutputFormat,
+    action: &ReassignAction,
+) -> Result<()> {
+    match action {
+        ReassignAction::Generate {
+            topics_to_move_json_file,
+            broker_list,
+            disable_rack_aware,
+        } => {
+            Box::pin(generate_reassignment(
+                config,
+                bootstrap,
+                command_config,
+                timeout,
+                format,
+                topics_to_move_json_file,
+                broker_list,
+                *disable_rack_aware,
+            ))
+            .await
+        }
+        ReassignAction::Execute {
+            reassignment_json_file,
+            additional,
+            disallow_replication_factor_change,
+            throttle,
+            replica_alter_log_dirs_throttle,
+            execute,
+        } => {
+            let plan = read_reassignment(reassignment_json_file)?;
+            if !execute {
+                return write_reassignment_mutation_rows(
+                    format,
+                    "reassign.execute",
+                    &plan,
+                    "PREVIEW EXECUTE",
+                );
+            }
+            Box::pin(start_reassignment(
+                config,
+                bootstrap,
+                command_config,
+                timeout,
+                format,
+                &plan,
+                *additional,
+                *disallow_replication_factor_change,
+                *throttle,
+                *replica_alter_log_dirs_throttle,
+            ))
+            .await?;
+            write_reassignment_mutation_rows(format, "reassign.execute", &plan, "STARTED")
+        }
+        ReassignAction::Cancel {
+            reassignment_json_file,
+            preserve_throttles,
+            execute,
+        } => {
+            let plan = read_reassignment(reassignment_json_file)?;
+            if !execute {
+                return write_reassignment_mutation_rows(
+                    format,
+                    "reassign.cancel",
+                    &plan,
+                    "PREVIEW CANCEL",
+                );
+            }
+            let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
+            let result = client
+                .alter_partition_reassignments(reassignment_topics(&plan, true), timeout)
+                .await?;
+            let brokers = client
+                .describe_cluster()
+                .await?
+                .brokers
                 .into_iter()
-                .map(|principal| (principal, AclPermissionType::Allow)),
-        );
-    }
-    if principals.is_empty() {
-        return Err(Error::Usage(
-            "provide --allow-principal or --deny-principal".into(),
-        ));
-    }
-    let allow_hosts = normalized_acl_values(&mutation.allow_host, "allow host")?;
-    let deny_hosts = normalized_acl_values(&mutation.deny_host, "deny host")?;
-    let mut bindings = Vec::new();
-    for (resource_type, resource_name, operations) in resources {
-        for (principal, permission) in &principals {
-            let configured_hosts = if *permission == AclPermissionType::Allow {
-                &allow_hosts
-            } else {
-                &deny_hosts
-            };
-            let hosts = if configured_hosts.is_empty() {
-                vec![mutation.host.as_deref().map_or("*", str::trim)]
-            } else {
-                configured_hosts.iter().map(String::as_str).collect()
-            };
-            for host in hosts {
-                for operation in &operations {
-                    bindings.push(AclBinding {
-                        resource_type,
-                        resource_name: resource_name.clone(),
-                        pattern_type: acl_wire_pattern(mutation.filter.resource_pattern_type),
-                        principal: principal.clone(),
-                        host: host.trim().to_owned(),
-                        operation: *operation,
-                        permission_type: *permission,
-                    });
+                .map(|broker| broker.broker_id)
+                .collect::<BTreeSet<_>>();
+            drop(client);
+            let rows = reassignment_result_rows(&result, "CANCELLED");
+            let failures = rows.iter().filter(|row| row.error.is_some()).count();
+            if failures == 0 {
+                if !preserve_throttles {
+                    clear_reassignment_throttles(config, timeout, &plan, &brokers)?;
                 }
+                write_reassignment_mutation_rows(format, "reassign.cancel", &plan, "CANCELLED")
+            } else {
+                write_mutation_rows(format, "reassign.cancel", &rows)?;
+                Err(Error::Partial {
+                    failed: failures,
+                    total: plan.partitions.len(),
+                })
             }
         }
-    }
-    Ok(bindings)
-}
-
-fn acl_removal_filters(
-    mutation: &crate::cli::AclMutationArgs,
-    operations: &[AclOperation],
-) -> Result<Vec<AclBindingFilter>> {
-    validate_acl_entry_values(mutation)?;
-    let resources = if mutation.producer || mutation.consumer {
-        acl_mutation_resources(mutation, operations)?
-    } else {
-        let resources = acl_resources(&mutation.filter)?;
-        if resources.is_empty() {
-            vec![(AclResourceType::Any, String::new(), operations.to_vec())]
-        } else {
-            resources
-                .into_iter()
-                .map(|(resource_type, resource_name)| {
-                    (resource_type, resource_name, operations.to_vec())
-                })
-                .collect()
+        ReassignAction::Verify {
+            reassignment_json_file,
+            preserve_throttles,
+        } => {
+            let plan = read_reassignment(reassignment_json_file)?;
+            let statuses = verify_reassignment(
+                config,
+                bootstrap,
+                command_config,
+                timeout,
+                &plan,
+                *preserve_throttles,
+            )
+            .await?;
+            output::write_value(format, "reassign.verify", &statuses, |rows| {
+                reassignment_table(rows)
+            })
         }
-    };
-    let mut entries = acl_removal_entries(mutation)?;
-    if entries.is_empty() {
-        entries.extend(
-            normalized_acl_values(&mutation.filter.principal, "principal")?
-                .into_iter()
-                .map(|principal| {
-                    (
-                        principal,
-                        mutation.host.as_deref().map_or("*", str::trim).to_owned(),
-                        AclPermissionType::Any,
-                    )
-                }),
-        );
-    }
-    if !entries.is_empty() {
-        validate_acl_resource_operations(&resources)?;
-    }
-    let mut filters = Vec::new();
-    for (resource_type, resource_name, operations) in resources {
-        if entries.is_empty() {
-            filters.push(AclBindingFilter {
-                resource_type,
-                resource_name: (!resource_name.is_empty()).then(|| resource_name.clone()),
-                pattern_type: acl_wire_pattern(mutation.filter.resource_pattern_type),
-                principal: None,
-                host: None,
-                operation: AclOperation::Any,
-                permission_type: AclPermissionType::Any,
-            });
-            continue;
+        ReassignAction::Balance {
+            topics,
+            broker_list,
+            max_moves,
+            disable_rack_aware,
+            output_json_file,
+        } => {
+            Box::pin(balance_reassignment(
+                config,
+                bootstrap,
+                command_config,
+                timeout,
+                format,
+                topics.as_deref(),
+                broker_list.as_deref(),
+                *max_moves,
+                *disable_rack_aware,
+                output_json_file.as_deref(),
+            ))
+            .await
         }
-        let operations = if operations.is_empty() {
-            vec![AclOperation::All]
-        } else {
-            operations
-        };
-        for (principal, host, permission) in &entries {
-            for operation in &operations {
-                filters.push(AclBindingFilter {
-                    resource_type,
-                    resource_name: (!resource_name.is_empty()).then(|| resource_name.clone()),
-                    pattern_type: acl_wire_pattern(mutation.filter.resource_pattern_type),
-                    principal: Some(principal.clone()),
-                    host: Some(host.clone()),
-                    operation: *operation,
-                    permission_type: *permission,
-                });
-            }
+        ReassignAction::Drain(args) => {
+            Box::pin(move_broker_replicas(
+                config,
+                bootstrap,
+                command_config,
+                timeout,
+                format,
+                args,
+                BrokerMove::Drain,
+            ))
+            .await
+        }
+        ReassignAction::Fill(args) => {
+            Box::pin(move_broker_replicas(
+                config,
+                bootstrap,
+                command_config,
+                timeout,
+                format,
+                args,
+                BrokerMove::Fill,
+            ))
+            .await
+        }
+        ReassignAction::List => {
+            let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
+            let running = client.list_partition_reassignments(None, timeout).await?;
+            drop(client);
+            let statuses = running
+                .into_iter()
+                .flat_map(|topic| {
+                    topic
+                        .partitions
+                        .into_iter()
+                        .map(move |partition| ReassignmentStatus {
+                            topic: topic.name.clone(),
+                            partition: partition.partition_index,
+                            replicas: partition.replicas,
+                            adding_replicas: partition.adding_replicas,
+                            removing_replicas: partition.removing_replicas,
+                            complete: false,
+                        })
+                })
+                .collect::<Vec<_>>();
+            output::write_value(format, "reassign.list", &statuses, |rows| {
+                reassignment_table(rows)
+            })
         }
     }
-    Ok(filters)
-}
-
-fn acl_removal_entries(
-    mutation: &crate::cli::AclMutationArgs,
-) -> Result<Vec<(String, String, AclPermissionType)>> {
-    let allow_principals = normalized_acl_values(&mutation.allow_principal, "allow principal")?;
-    let deny_principals = normalized_acl_values(&mutation.deny_principal, "deny principal")?;
-    let allow_hosts = normalized_acl_values(&mutation.allow_host, "allow host")?;
-    let deny_hosts = normalized_acl_values(&mutation.deny_host, "deny host")?;
-    let default_host = mutation.host.as_deref().map_or("*", str::trim);
-    let allowed = allow_principals.iter().flat_map(|principal| {
-        let hosts = if allow_hosts.is_empty() {
-            vec![default_host]
-        } else {
-            allow_hosts.iter().map(String::as_str).collect()
-        };
-        hosts
-            .into_iter()
-            .map(move |host| (principal.clone(), host.to_owned(), AclPermissionType::Allow))
-    });
-    let denied = deny_principals.iter().flat_map(|principal| {
-        let hosts = if deny_hosts.is_empty() {
-            vec![default_host]
-        } else {
-            deny_hosts.iter().map(String::as_str).collect()
-        };
-        hosts
-            .into_iter()
-            .map(move |host| (principal.clone(), host.to_owned(), AclPermissionType::Deny))
-    });
-    Ok(allowed.chain(denied).collect())
 }
 
-fn validate_acl_entry_values(mutation: &crate::cli::AclMutationArgs) -> Result<()> {
-    normalized_acl_values(&mutation.allow_principal, "allow principal")?;
-    normalized_acl_values(&mutation.deny_principal, "deny principal")?;
-    normalized_acl_values(&mutation.allow_host, "allow host")?;
-    normalized_acl_values(&mutation.deny_host, "deny host")?;
-    if mutation
-        .host
-        .as_deref()
-        .is_some_and(|host| host.trim().is_empty())
-    {
-        return Err(Error::Usage("ACL host cannot be empty".into()));
-    }
-    Ok(())
+fn reassignment_count(running: &[krafka::admin::PartitionReassignmentInfo]) -> usize {
+    running.iter().map(|topic| topic.partitions.len()).sum()
 }
 
-fn validate_acl_resource_operations(
-    resources: &[(AclResourceType, String, Vec<AclOperation>)],
+fn reject_replication_factor_changes(
+    config: &rdkafka::ClientConfig,
+    timeout: Duration,
+    plan: &ReassignmentFile,
 ) -> Result<()> {
-    for (resource_type, _, operations) in resources {
-        if let Some(operation) = operations
-            .iter()
-            .find(|operation| !acl_operation_supported(*resource_type, **operation))
-        {
-            return Err(Error::Usage(format!(
-                "ACL resource type {resource_type:?} does not support operation {operation:?}"
-            )));
-        }
-    }
-    Ok(())
+    let current = current_replicas(config, timeout, plan)?;
+    validate_replication_factors(plan, &current)
 }
 
-const fn acl_operation_supported(resource_type: AclResourceType, operation: AclOperation) -> bool {
-    if matches!(operation, AclOperation::Any | AclOperation::All) {
-        return true;
-    }
-    match resource_type {
-        AclResourceType::Any => true,
-        AclResourceType::Topic => matches!(
-            operation,
-            AclOperation::Read
-                | AclOperation::Write
-                | AclOperation::Create
-                | AclOperation::Delete
-                | AclOperation::Alter
-                | AclOperation::Describe
-                | AclOperation::DescribeConfigs
-                | AclOperation::AlterConfigs
-        ),
-        AclResourceType::Group => matches!(
-            operation,
-            AclOperation::Read
-                | AclOperation::Delete
-                | AclOperation::Describe
-                | AclOperation::DescribeConfigs
-                | AclOperation::AlterConfigs
-        ),
-        AclResourceType::Cluster => matches!(
-            operation,
-            AclOperation::Create
-                | AclOperation::Alter
-                | AclOperation::Describe
-                | AclOperation::ClusterAction
-                | AclOperation::DescribeConfigs
-                | AclOperation::AlterConfigs
-                | AclOperation::IdempotentWrite
-        ),
-        AclResourceType::TransactionalId => {
-            matches!(operation, AclOperation::Write | AclOperation::Describe)
-        }
+fn validate_replication_factors(
+    plan: &ReassignmentFile,
+    current: &BTreeMap<(String, i32), Vec<i32>>,
+) -> Result<()> {
+    let changes = plan
+        .partitions
+        .iter()
+        .filter_map(|target| {
+            let key = (target.topic.clone(), target.partition);
+            current.get(&key).map_or_else(
+                || {
+                    Some(format!(
+                        "{}:{} does not exist in cluster metadata",
+                        target.topic, target.partition
+                    ))
+                },
+                |replicas| {
+                    (replicas.len() != target.replicas.len()).then(|| {
+                        format!(
+                            "{}:{} would change replication factor from {} to {}",
+                            target.topic,
+                            target.partition,
+                            replicas.len(),
+                            target.replicas.len()
+                        )
+                    })
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+    if changes.is_empty() {
+        Ok(())
+    } else {
+        Err(Error::Usage(format!(
+            "--disallow-replication-factor-change rejected the plan: {}",
+            changes.join("; ")
+        )))
     }
 }
 
-fn acl_mutation_resources(
-    mutation: &crate::cli::AclMutationArgs,
-    operations: &[AclOperation],
-) -> Result<Vec<(AclResourceType, String, Vec<AclOperation>)>> {
-    if !mutation.producer && !mutation.consumer {
-        let resources = acl_resources(&mutation.filter)?;
-        if resources.is_empty() {
-            return Err(Error::Usage("an ACL resource selector is required".into()));
+#[derive(Debug, Default)]
+struct PartitionMove {
+    sources: BTreeSet<i32>,
+    destinations: BTreeSet<i32>,
+}
+
+type ReassignmentMoveMap = BTreeMap<(String, i32), PartitionMove>;
+
+fn reassignment_move_map(
+    plan: &ReassignmentFile,
+    running: &[krafka::admin::PartitionReassignmentInfo],
+    current: &BTreeMap<(String, i32), Vec<i32>>,
+) -> Result<ReassignmentMoveMap> {
+    let mut moves = ReassignmentMoveMap::new();
+    for topic in running {
+        for partition in &topic.partitions {
+            let adding = partition
+                .adding_replicas
+                .iter()
+                .copied()
+                .collect::<BTreeSet<_>>();
+            moves.insert(
+                (topic.name.clone(), partition.partition_index),
+                PartitionMove {
+                    sources: partition
+                        .replicas
+                        .iter()
+                        .copied()
+                        .filter(|broker| !adding.contains(broker))
+                        .collect(),
+                    destinations: adding,
+                },
+            );
         }
-        return Ok(resources
-            .into_iter()
-            .map(|(resource_type, resource_name)| {
-                (resource_type, resource_name, operations.to_vec())
-            })
-            .collect());
     }
-    if !mutation.deny_principal.is_empty() || !mutation.deny_host.is_empty() {
-        return Err(Error::Usage(
-            "role ACLs only support allow principals and hosts".into(),
-        ));
+    for target in &plan.partitions {
+        let key = (target.topic.clone(), target.partition);
+        let sources = moves.get(&key).map_or_else(
+            || {
+                current
+                    .get(&key)
+                    .map(|replicas| replicas.iter().copied().collect())
+            },
+            |movement| Some(movement.sources.clone()),
+        );
+        let sources = sources.ok_or_else(|| {
+            Error::Usage(format!(
+                "{}:{} does not exist in cluster metadata",
+                target.topic, target.partition
+            ))
+        })?;
+        let destinations = target
+            .replicas
+            .iter()
+            .copied()
+            .filter(|broker| !sources.contains(broker))
+            .collect();
+        moves.insert(
+            key,
+            PartitionMove {
+                sources,
+                destinations,
+            },
+        );
     }
-    if mutation.consumer
-        && !mutation.producer
-        && (mutation.filter.cluster || !mutation.filter.transactional_id.is_empty())
-    {
+    Ok(moves)
+}
+
+/// Starts `plan`, applying throttles first. Per-partition failures are written as mutation rows
+/// and reported as a partial failure.
+#[expect(
+    clippy::too_many_arguments,
+    reason = "arguments map directly to the reassign execute CLI contract"
+)]
+async fn start_reassignment(
+    config: &rdkafka::ClientConfig,
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    timeout: Duration,
+    format: OutputFormat,
+    plan: &ReassignmentFile,
+    additional: bool,
+    disallow_replication_factor_change: bool,
+    throttle: Option<u64>,
+    replica_alter_log_dirs_throttle: Option<u64>,
+) -> Result<()> {
+    let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
+    let active = client.list_partition_reassignments(None, timeout).await?;
+    if !additional && reassignment_count(&active) != 0 {
         return Err(Error::Usage(
-            "--consumer cannot be combined with --cluster or --transactional-id unless --producer is also set"
+            "cannot execute while a partition reassignment is active; use --additional to add to the existing reassignment"
                 .into(),
         ));
     }
-    let topics = normalized_acl_values(&mutation.filter.topic, "topic")?;
-    if topics.is_empty() {
-        return Err(Error::Usage(
-            "--producer and --consumer require --topic".into(),
-        ));
-    }
-    let mut topic_operations = Vec::new();
-    if mutation.producer {
-        topic_operations.extend([
-            AclOperation::Write,
-            AclOperation::Describe,
-            AclOperation::Create,
-        ]);
+    if disallow_replication_factor_change {
+        reject_replication_factor_changes(config, timeout, plan)?;
     }
-    if mutation.consumer {
-        for operation in [AclOperation::Read, AclOperation::Describe] {
-            if !topic_operations.contains(&operation) {
-                topic_operations.push(operation);
-            }
+    apply_reassignment_throttles(
+        config,
+        timeout,
+        plan,
+        &active,
+        throttle,
+        replica_alter_log_dirs_throttle,
+    )?;
+    let result = client
+        .alter_partition_reassignments(reassignment_topics(plan, false), timeout)
+        .await?;
+    let rows = reassignment_result_rows(&result, "STARTED");
+    let failures = rows.iter().filter(|row| row.error.is_some()).count();
+    if failures == 0 {
+        let log_dir_rows = alter_reassignment_log_dirs(&client, plan).await?;
+        drop(client);
+        let log_dir_failures = log_dir_rows
+            .iter()
+            .filter(|row| row.error.is_some())
+            .count();
+        if log_dir_failures != 0 {
+            write_mutation_rows(format, "reassign.execute.log-dirs", &log_dir_rows)?;
+            return Err(Error::Partial {
+                failed: log_dir_failures,
+                total: plan.partitions.len(),
+            });
         }
+        Ok(())
+    } else {
+        drop(client);
+        write_mutation_rows(format, "reassign.execute", &rows)?;
+        Err(Error::Partial {
+            failed: failures,
+            total: plan.partitions.len(),
+        })
     }
-    let mut resources = topics
+}
+
+/// Reports how far `plan` has progressed, clearing its throttles once no reassignment is
+/// running unless `preserve_throttles` is set.
+async fn verify_reassignment(
+    config: &rdkafka::ClientConfig,
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    timeout: Duration,
+    plan: &ReassignmentFile,
+    preserve_throttles: bool,
+) -> Result<Vec<ReassignmentStatus>> {
+    let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
+    let running = client.list_partition_reassignments(None, timeout).await?;
+    let log_dirs_ongoing = reassignment_log_dirs_ongoing(&client, plan).await?;
+    let brokers = client
+        .describe_cluster()
+        .await?
+        .brokers
         .into_iter()
-        .map(|topic| (AclResourceType::Topic, topic, topic_operations.clone()))
-        .collect::<Vec<_>>();
-    if mutation.consumer {
-        let groups = normalized_acl_values(&mutation.filter.group, "group")?;
-        if groups.is_empty() {
-            return Err(Error::Usage("--consumer requires --group".into()));
-        }
-        resources.extend(
-            groups
-                .into_iter()
-                .map(|group| (AclResourceType::Group, group, vec![AclOperation::Read])),
-        );
+        .map(|broker| broker.broker_id)
+        .collect::<BTreeSet<_>>();
+    drop(client);
+    let current = current_replicas(config, timeout, plan)?;
+    let statuses = reassignment_statuses(plan, &running, &current);
+    if reassignment_count(&running) == 0 && !log_dirs_ongoing && !preserve_throttles {
+        clear_reassignment_throttles(config, timeout, plan, &brokers)?;
     }
-    if mutation.producer {
-        resources.extend(
-            normalized_acl_values(&mutation.filter.transactional_id, "transactional ID")?
-                .into_iter()
-                .map(|transactional_id| {
+    Ok(statuses)
+}
+
+fn checked_throttle(value: u64, option: &str) -> Result<String> {
+    i64::try_from(value)
+        .map(|value| value.to_string())
+        .map_err(|_| Error::Usage(format!("{option} must not exceed {}", i64::MAX)))
+}
+
+fn apply_reassignment_throttles(
+    config: &rdkafka::ClientConfig,
+    timeout: Duration,
+    plan: &ReassignmentFile,
+    running: &[krafka::admin::PartitionReassignmentInfo],
+    inter_broker: Option<u64>,
+    log_dirs: Option<u64>,
+) -> Result<()> {
+    if inter_broker.is_none() && log_dirs.is_none() {
+        return Ok(());
+    }
+    let current = current_replicas(config, timeout, plan)?;
+    let moves = reassignment_move_map(plan, running, &current)?;
+    let admin = admin(config)?;
+    let timeout_ms = duration_ms(timeout)?;
+    if let Some(rate) = inter_broker {
+        let rate = checked_throttle(rate, "--throttle")?;
+        let mut topic_values = BTreeMap::<String, (BTreeSet<String>, BTreeSet<String>)>::new();
+        let mut brokers = BTreeSet::new();
+        for ((topic, partition), movement) in &moves {
+            let values = topic_values.entry(topic.clone()).or_default();
+            for broker in &movement.sources {
+                values.0.insert(format!("{partition}:{broker}"));
+                brokers.insert(*broker);
+            }
+            for broker in &movement.destinations {
+                values.1.insert(format!("{partition}:{broker}"));
+                brokers.insert(*broker);
+            }
+        }
+        for (topic, (leaders, followers)) in topic_values {
+            ffi::incremental_alter_config(
+                admin.inner().native_ptr(),
+                rdkafka_sys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_TOPIC,
+                &topic,
+                &[
                     (
-                        AclResourceType::TransactionalId,
-                        transactional_id,
-                        vec![AclOperation::Write, AclOperation::Describe],
-                    )
-                }),
-        );
+                        "leader.replication.throttled.replicas".into(),
+                        leaders.into_iter().collect::<Vec<_>>().join(","),
+                    ),
+                    (
+                        "follower.replication.throttled.replicas".into(),
+                        followers.into_iter().collect::<Vec<_>>().join(","),
+                    ),
+                ],
+                &[],
+                timeout_ms,
+            )?;
+        }
+        for broker in brokers {
+            ffi::incremental_alter_config(
+                admin.inner().native_ptr(),
+                rdkafka_sys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_BROKER,
+                &broker.to_string(),
+                &[
+                    ("leader.replication.throttled.rate".into(), rate.clone()),
+                    ("follower.replication.throttled.rate".into(), rate.clone()),
+                ],
+                &[],
+                timeout_ms,
+            )?;
+        }
     }
-    if mutation.idempotent {
-        resources.push((
-            AclResourceType::Cluster,
-            "kafka-cluster".into(),
-            vec![AclOperation::IdempotentWrite],
-        ));
+    if let Some(rate) = log_dirs {
+        let rate = checked_throttle(rate, "--replica-alter-log-dirs-throttle")?;
+        for broker in broker_log_dir_plan(plan).keys() {
+            ffi::incremental_alter_config(
+                admin.inner().native_ptr(),
+                rdkafka_sys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_BROKER,
+                &broker.to_string(),
+                &[(
+                    "replica.alter.log.dirs.io.max.bytes.per.second".into(),
+                    rate.clone(),
+                )],
+                &[],
+                timeout_ms,
+            )?;
+        }
     }
-    Ok(resources)
+    Ok(())
 }
 
-fn acl_row(binding: AclBinding) -> AclRow {
-    AclRow {
-        resource_type: format!("{:?}", binding.resource_type),
-        resource_name: binding.resource_name,
-        pattern_type: format!("{:?}", binding.pattern_type),
-        principal: binding.principal,
-        host: binding.host,
-        operation: format!("{:?}", binding.operation),
-        permission: format!("{:?}", binding.permission_type),
+async fn reassignment_log_dirs_ongoing(
+    client: &krafka::admin::AdminClient,
+    plan: &ReassignmentFile,
+) -> Result<bool> {
+    let targets = plan
+        .partitions
+        .iter()
+        .flat_map(|partition| {
+            partition
+                .replicas
+                .iter()
+                .zip(&partition.log_dirs)
+                .filter(|(_, directory)| directory.as_str() != "any")
+                .map(|(broker, _)| (partition.topic.as_str(), partition.partition, *broker))
+        })
+        .collect::<BTreeSet<_>>();
+    if targets.is_empty() {
+        return Ok(false);
     }
+    let topics = reassignment_filters(plan)
+        .into_iter()
+        .map(|topic| DescribableLogDirTopic {
+            topic: topic.name,
+            partitions: topic.partition_indexes,
+        })
+        .collect();
+    Ok(client
+        .describe_log_dirs(Some(topics))
+        .await?
+        .iter()
+        .any(|directory| {
+            directory.topics.iter().any(|topic| {
+                topic.partitions.iter().any(|partition| {
+                    partition.is_future_key
+                        && targets.contains(&(
+                            topic.name.as_str(),
+                            partition.partition_index,
+                            directory.broker_id,
+                        ))
+                })
+            })
+        }))
 }
 
-fn decode_acl_count(buffer: &mut bytes::Bytes) -> Result<usize> {
-    let count = i32::decode(buffer)?;
-    usize::try_from(count).map_err(|_| Error::Config("negative Kafka array length".into()))
-}
-
-fn decode_required_acl_string(buffer: &mut bytes::Bytes, field: &str) -> Result<String> {
-    KafkaString::decode(buffer)?
-        .0
-        .ok_or_else(|| Error::Config(format!("broker returned null {field}")))
-}
-
-#[derive(Debug, Serialize)]
-struct AclRow {
-    resource_type: String,
-    resource_name: String,
-    pattern_type: String,
-    principal: String,
-    host: String,
-    operation: String,
-    permission: String,
-}
-
-fn acl_table(rows: &[AclRow]) -> String {
-    output::table(
-        [
-            "RESOURCE",
-            "NAME",
-            "PATTERN",
-            "PRINCIPAL",
-            "HOST",
-            "OPERATION",
-            "PERMISSION",
-        ],
-        rows.iter().map(|row| {
-            [
-                row.resource_type.clone(),
-                row.resource_name.clone(),
-                row.pattern_type.clone(),
-                row.principal.clone(),
-                row.host.clone(),
-                row.operation.clone(),
-                row.permission.clone(),
-            ]
-        }),
-    )
-}
-
-fn duration_ms(duration: Duration) -> Result<i32> {
-    i32::try_from(duration.as_millis())
-        .map_err(|_| Error::Usage("timeout exceeds librdkafka's supported range".into()))
-}
-
-#[derive(Debug, Deserialize)]
-struct TopicsToMoveFile {
-    topics: Vec<TopicToMove>,
+fn clear_reassignment_throttles(
+    config: &rdkafka::ClientConfig,
+    timeout: Duration,
+    plan: &ReassignmentFile,
+    cluster_brokers: &BTreeSet<i32>,
+) -> Result<()> {
+    let admin = admin(config)?;
+    let timeout_ms = duration_ms(timeout)?;
+    let mut brokers = cluster_brokers.clone();
+    let mut topics = BTreeSet::new();
+    for partition in &plan.partitions {
+        topics.insert(partition.topic.as_str());
+        brokers.extend(&partition.replicas);
+    }
+    let topic_deletes = [
+        "leader.replication.throttled.replicas".into(),
+        "follower.replication.throttled.replicas".into(),
+    ];
+    for topic in topics {
+        ffi::incremental_alter_config(
+            admin.inner().native_ptr(),
+            rdkafka_sys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_TOPIC,
+            topic,
+            &[],
+            &topic_deletes,
+            timeout_ms,
+        )?;
+    }
+    let broker_deletes = [
+        "leader.replication.throttled.rate".into(),
+        "follower.replication.throttled.rate".into(),
+        "replica.alter.log.dirs.io.max.bytes.per.second".into(),
+    ];
+    for broker in brokers {
+        ffi::incremental_alter_config(
+            admin.inner().native_ptr(),
+            rdkafka_sys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_BROKER,
+            &broker.to_string(),
+            &[],
+            &broker_deletes,
+            timeout_ms,
+        )?;
+    }
+    Ok(())
 }
 
-#[derive(Debug, Deserialize)]
-struct TopicToMove {
-    topic: String,
+fn write_reassignment_mutation_rows(
+    format: OutputFormat,
+    command: &str,
+    plan: &ReassignmentFile,
+    status: &str,
+) -> Result<()> {
+    let rows = plan
+        .partitions
+        .iter()
+        .map(|partition| MutationRow {
+            resource: format!("{}:{}", partition.topic, partition.partition),
+            status: status.into(),
+            error: None,
+        })
+        .collect::<Vec<_>>();
+    write_mutation_rows(format, command, &rows)
 }
 
-#[derive(Debug, Deserialize, Serialize)]
-struct ReassignmentFile {
-    version: u8,
-    partitions: Vec<ReassignmentPartition>,
+fn reassignment_result_rows(
+    result: &krafka::admin::AlterReassignmentsResult,
+    success_status: &str,
+) -> Vec<MutationRow> {
+    let mut rows = result
+        .topics
+        .iter()
+        .flat_map(|topic| {
+            topic.partitions.iter().map(|partition| MutationRow {
+                resource: format!("{}:{}", topic.name, partition.partition_index),
+                status: if partition.error.is_some() {
+                    "FAILED".into()
+                } else {
+                    success_status.into()
+                },
+                error: partition.error.clone(),
+            })
+        })
+        .collect::<Vec<_>>();
+    if let Some(error) = &result.error {
+        rows.push(MutationRow {
+            resource: "reassignment-request".into(),
+            status: "FAILED".into(),
+            error: Some(error.clone()),
+        });
+    }
+    rows
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
-struct ReassignmentPartition {
-    topic: String,
-    partition: i32,
-    replicas: Vec<i32>,
-    #[serde(default)]
-    log_dirs: Vec<String>,
-}
+type BrokerLogDirPlan = BTreeMap<i32, BTreeMap<String, BTreeMap<String, Vec<i32>>>>;
 
-#[derive(Debug, Serialize)]
-struct ReassignmentStatus {
-    topic: String,
-    partition: i32,
-    replicas: Vec<i32>,
-    adding_replicas: Vec<i32>,
-    removing_replicas: Vec<i32>,
-    complete: bool,
+fn broker_log_dir_plan(plan: &ReassignmentFile) -> BrokerLogDirPlan {
+    let mut moves = BrokerLogDirPlan::new();
+    for partition in &plan.partitions {
+        for (broker, log_dir) in partition.replicas.iter().zip(&partition.log_dirs) {
+            if log_dir != "any" {
+                moves
+                    .entry(*broker)
+                    .or_default()
+                    .entry(log_dir.clone())
+                    .or_default()
+                    .entry(partition.topic.clone())
+                    .or_default()
+                    .push(partition.partition);
+            }
+        }
+    }
+    moves
 }
 
-#[expect(
-    clippy::too_many_lines,
-    reason = "the branches correspond directly to Kafka's reassignment actions"
-)]
-async fn reassign(
-    config: &rdkafka::ClientConfig,
-    bootstrap: &str,
-    command_config: Option<&Path>,
-    timeout: Duration,
-    format: OutputFormat,
-    action: &ReassignAction,
-) -> Result<()> {
-    match action {
-        ReassignAction::Generate {
-            topics_to_move_json_file,
-            broker_list,
-            disable_rack_aware,
-        } => {
-            generate_reassignment(
-                config,
-                bootstrap,
-                command_config,
-                timeout,
-                format,
-                topics_to_move_json_file,
-                broker_list,
-                *disable_rack_aware,
-            )
-            .await
-        }
-        ReassignAction::Execute {
-            reassignment_json_file,
-            additional,
-            disallow_replication_factor_change,
-            throttle,
-            replica_alter_log_dirs_throttle,
-            execute,
-        } => {
-            let plan = read_reassignment(reassignment_json_file)?;
-            if !execute {
-                return write_reassignment_mutation_rows(
-                    format,
-                    "reassign.execute",
-                    &plan,
-                    "PREVIEW EXECUTE",
-                );
-            }
-            let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
-            let active = client.list_partition_reassignments(None, timeout).await?;
-            if !additional && reassignment_count(&active) != 0 {
-                return Err(Error::Usage(
-                    "cannot execute while a partition reassignment is active; use --additional to add to the existing reassignment"
-                        .into(),
-                ));
-            }
-            if *disallow_replication_factor_change {
-                reject_replication_factor_changes(config, timeout, &plan)?;
-            }
-            apply_reassignment_throttles(
-                config,
-                timeout,
-                &plan,
-                &active,
-                *throttle,
-                *replica_alter_log_dirs_throttle,
-            )?;
-            let result = client
-                .alter_partition_reassignments(reassignment_topics(&plan, false), timeout)
-                .await?;
-            let rows = reassignment_result_rows(&result, "STARTED");
-            let failures = rows.iter().filter(|row| row.error.is_some()).count();
-            if failures == 0 {
-                let log_dir_rows = alter_reassignment_log_dirs(&client, &plan).await?;
-                drop(client);
-                let log_dir_failures = log_dir_rows
-                    .iter()
-                    .filter(|row| row.error.is_some())
-                    .count();
-                if log_dir_failures != 0 {
-                    write_mutation_rows(format, "reassign.execute.log-dirs", &log_dir_rows)?;
-                    return Err(Error::Partial {
-                        failed: log_dir_failures,
-                        total: plan.partitions.len(),
-                    });
+async fn alter_reassignment_log_dirs(
+    client: &krafka::admin::AdminClient,
+    plan: &ReassignmentFile,
+) -> Result<Vec<MutationRow>> {
+    let moves = broker_log_dir_plan(plan);
+    if moves.is_empty() {
+        return Ok(Vec::new());
+    }
+    let cluster = client.describe_cluster().await?;
+    let mut rows = Vec::new();
+    for (broker_id, directories) in moves {
+        let broker = cluster
+            .brokers
+            .iter()
+            .find(|broker| broker.broker_id == broker_id)
+            .ok_or_else(|| Error::Usage(format!("broker {broker_id} was not described")))?;
+        let address = format!("{}:{}", broker.host, broker.port);
+        let connection = client
+            .pool()
+            .get_connection_by_id(broker_id, &address)
+            .await?;
+        let directory_count = i32::try_from(directories.len())
+            .map_err(|_| Error::Usage("too many replica log directories".into()))?;
+        let mut response = connection
+            .send_request(ApiKey::AlterReplicaLogDirs, 1, |buffer| {
+                directory_count.try_encode(buffer)?;
+                for (directory, topics) in &directories {
+                    KafkaString(Some(directory.clone())).try_encode(buffer)?;
+                    i32::try_from(topics.len())
+                        .map_err(|_| krafka::KrafkaError::config("too many log-dir topics"))?
+                        .try_encode(buffer)?;
+                    for (topic, partitions) in topics {
+                        KafkaString(Some(topic.clone())).try_encode(buffer)?;
+                        i32::try_from(partitions.len())
+                            .map_err(|_| {
+                                krafka::KrafkaError::config("too many log-dir partitions")
+                            })?
+                            .try_encode(buffer)?;
+                        for partition in partitions {
+                            partition.try_encode(buffer)?;
+                        }
+                    }
                 }
-                write_reassignment_mutation_rows(format, "reassign.execute", &plan, "STARTED")
-            } else {
-                drop(client);
-                write_mutation_rows(format, "reassign.execute", &rows)?;
-                Err(Error::Partial {
-                    failed: failures,
-                    total: plan.partitions.len(),
-                })
+                Ok(())
+            })
+            .await?;
+        drop(connection);
+        let _throttle_time_ms = i32::decode(&mut response)?;
+        let topic_count = decode_acl_count(&mut response)?;
+        for _ in 0..topic_count {
+            let topic = decode_required_acl_string(&mut response, "log-dir topic")?;
+            let partition_count = decode_acl_count(&mut response)?;
+            for _ in 0..partition_count {
+                let partition = i32::decode(&mut response)?;
+                let error_code = i16::decode(&mut response)?;
+                rows.push(MutationRow {
+                    resource: format!("broker:{broker_id}:{topic}:{partition}"),
+                    status: if error_code == 0 {
+                        "LOG_DIR_MOVED".into()
+                    } else {
+                        "FAILED".into()
+                    },
+                    error: (error_code != 0).then(|| format!("Kafka error code {error_code}")),
+                });
             }
         }
-        ReassignAction::Cancel {
-            reassignment_json_file,
-            preserve_throttles,
-            execute,
-        } => {
-            let plan = read_reassignment(reassignment_json_file)?;
-            if !execute {
-                return write_reassignment_mutation_rows(
-                    format,
-                    "reassign.cancel",
-                    &plan,
-                    "PREVIEW CANCEL",
-                );
-            }
-            let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
-            let result = client
-                .alter_partition_reassignments(reassignment_topics(&plan, true), timeout)
-                .await?;
-            let brokers = client
-                .describe_cluster()
-                .await?
-                .brokers
-                .into_iter()
-                .map(|broker| broker.broker_id)
-                .collect::<BTreeSet<_>>();
-            drop(client);
-            let rows = reassignment_result_rows(&result, "CANCELLED");
-            let failures = rows.iter().filter(|row| row.error.is_some()).count();
-            if failures == 0 {
-                if !preserve_throttles {
-                    clear_reassignment_throttles(config, timeout, &plan, &brokers)?;
-                }
-                write_reassignment_mutation_rows(format, "reassign.cancel", &plan, "CANCELLED")
-            } else {
-                write_mutation_rows(format, "reassign.cancel", &rows)?;
-                Err(Error::Partial {
-                    failed: failures,
-                    total: plan.partitions.len(),
-                })
-            }
+    }
+    Ok(rows)
+}
+
+fn read_reassignment(path: &Path) -> Result<ReassignmentFile> {
+    let plan: ReassignmentFile = serde_json::from_reader(std::fs::File::open(path)?)?;
+    if plan.version != 1 {
+        return Err(Error::Usage(format!(
+            "unsupported reassignment JSON version: {}",
+            plan.version
+        )));
+    }
+    if plan.partitions.is_empty() {
+        return Err(Error::Usage(
+            "partition reassignment list cannot be empty".into(),
+        ));
+    }
+    let mut targets = BTreeSet::new();
+    for partition in &plan.partitions {
+        if partition.topic.is_empty() || partition.partition < 0 {
+            return Err(Error::Usage(
+                "reassignment topic must be non-empty and partition must be non-negative".into(),
+            ));
         }
-        ReassignAction::Verify {
-            reassignment_json_file,
-            preserve_throttles,
-        } => {
-            let plan = read_reassignment(reassignment_json_file)?;
-            let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
-            let running = client.list_partition_reassignments(None, timeout).await?;
-            let log_dirs_ongoing = reassignment_log_dirs_ongoing(&client, &plan).await?;
-            let brokers = client
-                .describe_cluster()
-                .await?
-                .brokers
-                .into_iter()
-                .map(|broker| broker.broker_id)
-                .collect::<BTreeSet<_>>();
-            drop(client);
-            let current = current_replicas(config, timeout, &plan)?;
-            let statuses = reassignment_statuses(&plan, &running, &current);
-            if reassignment_count(&running) == 0 && !log_dirs_ongoing && !preserve_throttles {
-                clear_reassignment_throttles(config, timeout, &plan, &brokers)?;
-            }
-            output::write_value(format, "reassign.verify", &statuses, |rows| {
-                reassignment_table(rows)
-            })
+        if !targets.insert((partition.topic.as_str(), partition.partition)) {
+            return Err(Error::Usage(format!(
+                "duplicate reassignment target {}:{}",
+                partition.topic, partition.partition
+            )));
         }
-        ReassignAction::List => {
-            let client = config::protocol_admin(bootstrap, timeout, command_config).await?;
-            let running = client.list_partition_reassignments(None, timeout).await?;
-            drop(client);
-            let statuses = running
-                .into_iter()
-                .flat_map(|topic| {
-                    topic
-                        .partitions
-                        .into_iter()
-                        .map(move |partition| ReassignmentStatus {
-                            topic: topic.name.clone(),
-                            partition: partition.partition_index,
-                            replicas: partition.replicas,
-                            adding_replicas: partition.adding_replicas,
-                            removing_replicas: partition.removing_replicas,
-                            complete: false,
-                        })
-                })
-                .collect::<Vec<_>>();
-            output::write_value(format, "reassign.list", &statuses, |rows| {
-                reassignment_table(rows)
-            })
+        if partition.replicas.is_empty() {
+            return Err(Error::Usage(format!(
+                "replica list cannot be empty for {}:{}",
+                partition.topic, partition.partition
+            )));
+        }
+        let mut replicas = partition.replicas.clone();
+        replicas.sort_unstable();
+        replicas.dedup();
+        if replicas.len() != partition.replicas.len() {
+            return Err(Error::Usage(format!(
+                "replica list contains duplicates for {}:{}",
+                partition.topic, partition.partition
+            )));
+        }
+        if !partition.log_dirs.is_empty() && partition.log_dirs.len() != partition.replicas.len() {
+            return Err(Error::Usage(format!(
+                "log_dirs count must match replicas for {}:{}",
+                partition.topic, partition.partition
+            )));
         }
     }
+    Ok(plan)
 }
 
-fn reassignment_count(running: &[krafka::admin::PartitionReassignmentInfo]) -> usize {
-    running.iter().map(|topic| topic.partitions.len()).sum()
-}
-
-fn reject_replication_factor_changes(
-    config: &rdkafka::ClientConfig,
-    timeout: Duration,
-    plan: &ReassignmentFile,
-) -> Result<()> {
-    let current = current_replicas(config, timeout, plan)?;
-    validate_replication_factors(plan, &current)
-}
-
-fn validate_replication_factors(
-    plan: &ReassignmentFile,
-    current: &BTreeMap<(String, i32), Vec<i32>>,
-) -> Result<()> {
-    let changes = plan
-        .partitions
-        .iter()
-        .filter_map(|target| {
-            let key = (target.topic.clone(), target.partition);
-            current.get(&key).map_or_else(
-                || {
-                    Some(format!(
-                        "{}:{} does not exist in cluster metadata",
-                        target.topic, target.partition
-                    ))
-                },
-                |replicas| {
-                    (replicas.len() != target.replicas.len()).then(|| {
-                        format!(
-                            "{}:{} would change replication factor from {} to {}",
-                            target.topic,
-                            target.partition,
-                            replicas.len(),
-                            target.replicas.len()
-                        )
-                    })
-                },
-            )
-        })
-        .collect::<Vec<_>>();
-    if changes.is_empty() {
-        Ok(())
-    } else {
-        Err(Error::Usage(format!(
-            "--disallow-replication-factor-change rejected the plan: {}",
-            changes.join("; ")
-        )))
+fn reassignment_topics(plan: &ReassignmentFile, cancel: bool) -> Vec<ReassignableTopic> {
+    let mut topics: BTreeMap<String, Vec<ReassignablePartition>> = BTreeMap::new();
+    for partition in &plan.partitions {
+        topics
+            .entry(partition.topic.clone())
+            .or_default()
+            .push(ReassignablePartition {
+                partition_index: partition.partition,
+                replicas: (!cancel).then(|| partition.replicas.clone()),
+            });
     }
+    topics
+        .into_iter()
+        .map(|(name, partitions)| ReassignableTopic { name, partitions })
+        .collect()
 }
 
-#[derive(Debug, Default)]
-struct PartitionMove {
-    sources: BTreeSet<i32>,
-    destinations: BTreeSet<i32>,
+fn reassignment_filters(plan: &ReassignmentFile) -> Vec<ListPartitionReassignmentsTopic> {
+    let mut topics: BTreeMap<String, Vec<i32>> = BTreeMap::new();
+    for partition in &plan.partitions {
+        topics
+            .entry(partition.topic.clone())
+            .or_default()
+            .push(partition.partition);
+    }
+    topics
+        .into_iter()
+        .map(
+            |(name, partition_indexes)| ListPartitionReassignmentsTopic {
+                name,
+                partition_indexes,
+            },
+        )
+        .collect()
 }
 
-type ReassignmentMoveMap = BTreeMap<(String, i32), PartitionMove>;
-
-fn reassignment_move_map(
+fn reassignment_statuses(
     plan: &ReassignmentFile,
     running: &[krafka::admin::PartitionReassignmentInfo],
     current: &BTreeMap<(String, i32), Vec<i32>>,
-) -> Result<ReassignmentMoveMap> {
-    let mut moves = ReassignmentMoveMap::new();
-    for topic in running {
-        for partition in &topic.partitions {
-            let adding = partition
-                .adding_replicas
+) -> Vec<ReassignmentStatus> {
+    plan.partitions
+        .iter()
+        .map(|target| {
+            let active = running
                 .iter()
-                .copied()
-                .collect::<BTreeSet<_>>();
-            moves.insert(
-                (topic.name.clone(), partition.partition_index),
-                PartitionMove {
-                    sources: partition
-                        .replicas
+                .find(|topic| topic.name == target.topic)
+                .and_then(|topic| {
+                    topic
+                        .partitions
                         .iter()
-                        .copied()
-                        .filter(|broker| !adding.contains(broker))
-                        .collect(),
-                    destinations: adding,
-                },
-            );
-        }
-    }
-    for target in &plan.partitions {
-        let key = (target.topic.clone(), target.partition);
-        let sources = moves.get(&key).map_or_else(
-            || {
-                current
-                    .get(&key)
-                    .map(|replicas| replicas.iter().copied().collect())
-            },
-            |movement| Some(movement.sources.clone()),
-        );
-        let sources = sources.ok_or_else(|| {
-            Error::Usage(format!(
-                "{}:{} does not exist in cluster metadata",
-                target.topic, target.partition
-            ))
-        })?;
-        let destinations = target
-            .replicas
-            .iter()
-            .copied()
-            .filter(|broker| !sources.contains(broker))
-            .collect();
-        moves.insert(
-            key,
-            PartitionMove {
-                sources,
-                destinations,
-            },
-        );
-    }
-    Ok(moves)
-}
-
-fn checked_throttle(value: u64, option: &str) -> Result<String> {
-    i64::try_from(value)
-        .map(|value| value.to_string())
-        .map_err(|_| Error::Usage(format!("{option} must not exceed {}", i64::MAX)))
-}
-
-fn apply_reassignment_throttles(
-    config: &rdkafka::ClientConfig,
-    timeout: Duration,
-    plan: &ReassignmentFile,
-    running: &[krafka::admin::PartitionReassignmentInfo],
-    inter_broker: Option<u64>,
-    log_dirs: Option<u64>,
-) -> Result<()> {
-    if inter_broker.is_none() && log_dirs.is_none() {
-        return Ok(());
-    }
-    let current = current_replicas(config, timeout, plan)?;
-    let moves = reassignment_move_map(plan, running, &current)?;
-    let admin = admin(config)?;
-    let timeout_ms = duration_ms(timeout)?;
-    if let Some(rate) = inter_broker {
-        let rate = checked_throttle(rate, "--throttle")?;
-        let mut topic_values = BTreeMap::<String, (BTreeSet<String>, BTreeSet<String>)>::new();
-        let mut brokers = BTreeSet::new();
-        for ((topic, partition), movement) in &moves {
-            let values = topic_values.entry(topic.clone()).or_default();
-            for broker in &movement.sources {
-                values.0.insert(format!("{partition}:{broker}"));
-                brokers.insert(*broker);
-            }
-            for broker in &movement.destinations {
-                values.1.insert(format!("{partition}:{broker}"));
-                brokers.insert(*broker);
-            }
-        }
-        for (topic, (leaders, followers)) in topic_values {
-            ffi::incremental_alter_config(
-                admin.inner().native_ptr(),
-                rdkafka_sys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_TOPIC,
-                &topic,
-                &[
-                    (
-                        "leader.replication.throttled.replicas".into(),
-                        leaders.into_iter().collect::<Vec<_>>().join(","),
-                    ),
-                    (
-                        "follower.replication.throttled.replicas".into(),
-                        followers.into_iter().collect::<Vec<_>>().join(","),
-                    ),
-                ],
-                &[],
-                timeout_ms,
-            )?;
-        }
-        for broker in brokers {
-            ffi::incremental_alter_config(
-                admin.inner().native_ptr(),
-                rdkafka_sys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_BROKER,
-                &broker.to_string(),
-                &[
-                    ("leader.replication.throttled.rate".into(), rate.clone()),
-                    ("follower.replication.throttled.rate".into(), rate.clone()),
-                ],
-                &[],
-                timeout_ms,
-            )?;
-        }
-    }
-    if let Some(rate) = log_dirs {
-        let rate = checked_throttle(rate, "--replica-alter-log-dirs-throttle")?;
-        for broker in broker_log_dir_plan(plan).keys() {
-            ffi::incremental_alter_config(
-                admin.inner().native_ptr(),
-                rdkafka_sys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_BROKER,
-                &broker.to_string(),
-                &[(
-                    "replica.alter.log.dirs.io.max.bytes.per.second".into(),
-                    rate.clone(),
-                )],
-                &[],
-                timeout_ms,
-            )?;
-        }
-    }
-    Ok(())
+                        .find(|partition| partition.partition_index == target.partition)
+                });
+            let current_replicas = current
+                .get(&(target.topic.clone(), target.partition))
+                .cloned()
+                .unwrap_or_default();
+            ReassignmentStatus {
+                topic: target.topic.clone(),
+                partition: target.partition,
+                replicas: active.map_or_else(
+                    || current_replicas.clone(),
+                    |partition| partition.replicas.clone(),
+                ),
+                adding_replicas: active
+                    .map(|partition| partition.adding_replicas.clone())
+                    .unwrap_or_default(),
+                removing_replicas: active
+                    .map(|partition| partition.removing_replicas.clone())
+                    .unwrap_or_default(),
+                complete: active.is_none() && current_replicas == target.replicas,
+            }
+        })
+        .collect()
 }
 
-async fn reassignment_log_dirs_ongoing(
-    client: &krafka::admin::AdminClient,
+fn current_replicas(
+    config: &rdkafka::ClientConfig,
+    timeout: Duration,
     plan: &ReassignmentFile,
-) -> Result<bool> {
+) -> Result<BTreeMap<(String, i32), Vec<i32>>> {
+    let metadata = base_consumer(config)?.fetch_metadata(None, timeout)?;
     let targets = plan
         .partitions
         .iter()
-        .flat_map(|partition| {
-            partition
-                .replicas
-                .iter()
-                .zip(&partition.log_dirs)
-                .filter(|(_, directory)| directory.as_str() != "any")
-                .map(|(broker, _)| (partition.topic.as_str(), partition.partition, *broker))
-        })
+        .map(|partition| (partition.topic.as_str(), partition.partition))
         .collect::<BTreeSet<_>>();
-    if targets.is_empty() {
-        return Ok(false);
-    }
-    let topics = reassignment_filters(plan)
-        .into_iter()
-        .map(|topic| DescribableLogDirTopic {
-            topic: topic.name,
-            partitions: topic.partition_indexes,
-        })
-        .collect();
-    Ok(client
-        .describe_log_dirs(Some(topics))
-        .await?
+    Ok(metadata
+        .topics()
         .iter()
-        .any(|directory| {
-            directory.topics.iter().any(|topic| {
-                topic.partitions.iter().any(|partition| {
-                    partition.is_future_key
-                        && targets.contains(&(
-                            topic.name.as_str(),
-                            partition.partition_index,
-                            directory.broker_id,
-                        ))
+        .flat_map(|topic| {
+            topic
+                .partitions()
+                .iter()
+                .filter(|partition| targets.contains(&(topic.name(), partition.id())))
+                .map(|partition| {
+                    (
+                        (topic.name().to_owned(), partition.id()),
+                        partition.replicas().to_vec(),
+                    )
                 })
-            })
-        }))
+        })
+        .collect())
 }
 
-fn clear_reassignment_throttles(
+fn reassignment_table(rows: &[ReassignmentStatus]) -> String {
+    output::table(
+        [
+            "TOPIC",
+            "PARTITION",
+            "STATUS",
+            "REPLICAS",
+            "ADDING",
+            "REMOVING",
+        ],
+        rows.iter().map(|row| {
+            [
+                row.topic.clone(),
+                row.partition.to_string(),
+                if row.complete {
+                    "COMPLETED".into()
+                } else {
+                    "IN_PROGRESS".into()
+                },
+                csv_numbers(&row.replicas),
+                csv_numbers(&row.adding_replicas),
+                csv_numbers(&row.removing_replicas),
+            ]
+        }),
+    )
+}
+
+#[expect(
+    clippy::too_many_arguments,
+    reason = "arguments and phases mirror Kafka's generate-assignment command contract"
+)]
+async fn generate_reassignment(
     config: &rdkafka::ClientConfig,
+    bootstrap: &str,
+    command_config: Option<&Path>,
     timeout: Duration,
-    plan: &ReassignmentFile,
-    cluster_brokers: &BTreeSet<i32>,
+    format: OutputFormat,
+    path: &Path,
+    broker_list: &str,
+    disable_rack_aware: bool,
 ) -> Result<()> {
-    let admin = admin(config)?;
-    let timeout_ms = duration_ms(timeout)?;
-    let mut brokers = cluster_brokers.clone();
-    let mut topics = BTreeSet::new();
-    for partition in &plan.partitions {
-        topics.insert(partition.topic.as_str());
-        brokers.extend(&partition.replicas);
-    }
-    let topic_deletes = [
-        "leader.replication.throttled.replicas".into(),
-        "follower.replication.throttled.replicas".into(),
-    ];
-    for topic in topics {
-        ffi::incremental_alter_config(
-            admin.inner().native_ptr(),
-            rdkafka_sys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_TOPIC,
-            topic,
-            &[],
-            &topic_deletes,
-            timeout_ms,
-        )?;
+    let input: TopicsToMoveFile = serde_json::from_reader(std::fs::File::open(path)?)?;
+    let brokers = parse_partitions(Some(broker_list))?
+        .filter(|brokers| !brokers.is_empty())
+        .ok_or_else(|| Error::Usage("--broker-list must not be empty".into()))?;
+    if brokers.iter().collect::<BTreeSet<_>>().len() != brokers.len() {
+        return Err(Error::Usage(
+            "--broker-list contains duplicate broker IDs".into(),
+        ));
     }
-    let broker_deletes = [
-        "leader.replication.throttled.rate".into(),
-        "follower.replication.throttled.rate".into(),
-        "replica.alter.log.dirs.io.max.bytes.per.second".into(),
-    ];
-    for broker in brokers {
-        ffi::incremental_alter_config(
-            admin.inner().native_ptr(),
-            rdkafka_sys::rd_kafka_ResourceType_t::RD_KAFKA_RESOURCE_BROKER,
-            &broker.to_string(),
-            &[],
-            &broker_deletes,
-            timeout_ms,
-        )?;
+    if input
+        .topics
+        .iter()
+        .map(|topic| topic.topic.as_str())
+        .collect::<BTreeSet<_>>()
+        .len()
+        != input.topics.len()
+    {
+        return Err(Error::Usage(
+            "topics-to-move JSON contains duplicate topics".into(),
+        ));
     }
-    Ok(())
-}
-
-fn write_reassignment_mutation_rows(
-    format: OutputFormat,
-    command: &str,
-    plan: &ReassignmentFile,
-    status: &str,
-) -> Result<()> {
-    let rows = plan
-        .partitions
+    let metadata = base_consumer(config)?.fetch_metadata(None, timeout)?;
+    let known_brokers = metadata
+        .brokers()
         .iter()
-        .map(|partition| MutationRow {
-            resource: format!("{}:{}", partition.topic, partition.partition),
-            status: status.into(),
-            error: None,
-        })
+        .map(rdkafka::metadata::MetadataBroker::id)
         .collect::<Vec<_>>();
-    write_mutation_rows(format, command, &rows)
-}
-
-fn reassignment_result_rows(
-    result: &krafka::admin::AlterReassignmentsResult,
-    success_status: &str,
-) -> Vec<MutationRow> {
-    let mut rows = result
-        .topics
+    if let Some(unknown) = brokers.iter().find(|id| !known_brokers.contains(id)) {
+        return Err(Error::Usage(format!(
+            "broker {unknown} is not in cluster metadata"
+        )));
+    }
+    let broker_racks = broker_racks(
+        bootstrap,
+        command_config,
+        timeout,
+        &brokers,
+        disable_rack_aware,
+    )
+    .await?;
+    let mut partitions = Vec::new();
+    for selected in &input.topics {
+        let topic = metadata
+            .topics()
+            .iter()
+            .find(|topic| topic.name() == selected.topic)
+            .ok_or_else(|| Error::Usage(format!("topic {} not found", selected.topic)))?;
+        let replication_factor = topic
+            .partitions()
+            .first()
+            .map_or(0, |partition| partition.replicas().len());
+        let assignments = striped_replica_assignment(
+            &broker_racks,
+            topic.partitions().len(),
+            replication_factor,
+        )?;
+        for (partition, replicas) in topic.partitions().iter().zip(assignments) {
+            if replication_factor > brokers.len() {
+                return Err(Error::Usage(format!(
+                    "topic {} requires {replication_factor} distinct brokers, but only {} were supplied",
+                    selected.topic,
+                    brokers.len()
+                )));
+            }
+            partitions.push(ReassignmentPartition {
+                topic: selected.topic.clone(),
+                partition: partition.id(),
+                log_dirs: vec!["any".into(); replicas.len()],
+                replicas,
+            });
+        }
+    }
+    let proposal = ReassignmentFile {
+        version: 1,
+        partitions,
+    };
+    output::write_value(format, "reassign.generate", &proposal, |proposal| {
+        output::table(
+            ["TOPIC", "PARTITION", "REPLICAS", "LOG_DIRS"],
+            proposal.partitions.iter().map(|partition| {
+                [
+                    partition.topic.clone(),
+                    partition.partition.to_string(),
+                    csv_numbers(&partition.replicas),
+                    partition.log_dirs.join(","),
+                ]
+            }),
+        )
+    })
+}
+
+/// Looks up each broker's rack, or `None` for all brokers when rack awareness is disabled.
+/// Fails when only some of the brokers have a rack.
+async fn broker_racks(
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    timeout: Duration,
+    brokers: &[i32],
+    disable_rack_aware: bool,
+) -> Result<Vec<(i32, Option<String>)>> {
+    let protocol = config::protocol_admin(bootstrap, timeout, command_config).await?;
+    let cluster = protocol.describe_cluster().await?;
+    drop(protocol);
+    let broker_racks = brokers
         .iter()
-        .flat_map(|topic| {
-            topic.partitions.iter().map(|partition| MutationRow {
-                resource: format!("{}:{}", topic.name, partition.partition_index),
-                status: if partition.error.is_some() {
-                    "FAILED".into()
-                } else {
-                    success_status.into()
-                },
-                error: partition.error.clone(),
-            })
+        .map(|broker_id| {
+            cluster
+                .brokers
+                .iter()
+                .find(|broker| broker.broker_id == *broker_id)
+                .map(|broker| {
+                    (
+                        *broker_id,
+                        (!disable_rack_aware).then(|| broker.rack.clone()).flatten(),
+                    )
+                })
+                .ok_or_else(|| Error::Usage(format!("broker {broker_id} was not described")))
         })
-        .collect::<Vec<_>>();
-    if let Some(error) = &result.error {
-        rows.push(MutationRow {
-            resource: "reassignment-request".into(),
-            status: "FAILED".into(),
-            error: Some(error.clone()),
-        });
+        .collect::<Result<Vec<_>>>()?;
+    let rackless = broker_racks
+        .iter()
+        .filter(|(_, rack)| rack.is_none())
+        .count();
+    if !disable_rack_aware && rackless != 0 && rackless != broker_racks.len() {
+        return Err(Error::Usage(
+            "not all brokers have rack information; use --disable-rack-aware".into(),
+        ));
     }
-    rows
+    Ok(broker_racks)
 }
 
-type BrokerLogDirPlan = BTreeMap<i32, BTreeMap<String, BTreeMap<String, Vec<i32>>>>;
-
-fn broker_log_dir_plan(plan: &ReassignmentFile) -> BrokerLogDirPlan {
-    let mut moves = BrokerLogDirPlan::new();
-    for partition in &plan.partitions {
-        for (broker, log_dir) in partition.replicas.iter().zip(&partition.log_dirs) {
-            if log_dir != "any" {
-                moves
-                    .entry(*broker)
-                    .or_default()
-                    .entry(log_dir.clone())
-                    .or_default()
-                    .entry(partition.topic.clone())
-                    .or_default()
-                    .push(partition.partition);
+fn striped_replica_assignment(
+    brokers: &[(i32, Option<String>)],
+    partition_count: usize,
+    replication_factor: usize,
+) -> Result<Vec<Vec<i32>>> {
+    if replication_factor == 0 {
+        return Err(Error::Usage("replication factor must be positive".into()));
+    }
+    if replication_factor > brokers.len() {
+        return Err(Error::Usage(format!(
+            "replication factor {replication_factor} exceeds {} available brokers",
+            brokers.len()
+        )));
+    }
+    let mut racks = BTreeMap::<Option<String>, Vec<i32>>::new();
+    for (broker, rack) in brokers {
+        racks.entry(rack.clone()).or_default().push(*broker);
+    }
+    for rack in racks.values_mut() {
+        rack.sort_unstable();
+    }
+    let max_rack_size = racks.values().map(Vec::len).max().unwrap_or_default();
+    let mut striped = Vec::with_capacity(brokers.len());
+    for index in 0..max_rack_size {
+        for rack in racks.values() {
+            if let Some(broker) = rack.get(index) {
+                striped.push(*broker);
             }
         }
     }
-    moves
-}
-
-async fn alter_reassignment_log_dirs(
-    client: &krafka::admin::AdminClient,
-    plan: &ReassignmentFile,
-) -> Result<Vec<MutationRow>> {
-    let moves = broker_log_dir_plan(plan);
-    if moves.is_empty() {
-        return Ok(Vec::new());
-    }
-    let cluster = client.describe_cluster().await?;
-    let mut rows = Vec::new();
-    for (broker_id, directories) in moves {
-        let broker = cluster
-            .brokers
-            .iter()
-            .find(|broker| broker.broker_id == broker_id)
-            .ok_or_else(|| Error::Usage(format!("broker {broker_id} was not described")))?;
-        let address = format!("{}:{}", broker.host, broker.port);
-        let connection = client
-            .pool()
-            .get_connection_by_id(broker_id, &address)
-            .await?;
-        let directory_count = i32::try_from(directories.len())
-            .map_err(|_| Error::Usage("too many replica log directories".into()))?;
-        let mut response = connection
-            .send_request(ApiKey::AlterReplicaLogDirs, 1, |buffer| {
-                directory_count.try_encode(buffer)?;
-                for (directory, topics) in &directories {
-                    KafkaString(Some(directory.clone())).try_encode(buffer)?;
-                    i32::try_from(topics.len())
-                        .map_err(|_| krafka::KrafkaError::config("too many log-dir topics"))?
-                        .try_encode(buffer)?;
-                    for (topic, partitions) in topics {
-                        KafkaString(Some(topic.clone())).try_encode(buffer)?;
-                        i32::try_from(partitions.len())
-                            .map_err(|_| {
-                                krafka::KrafkaError::config("too many log-dir partitions")
-                            })?
-                            .try_encode(buffer)?;
-                        for partition in partitions {
-                            partition.try_encode(buffer)?;
-                        }
+    let rack_by_broker = brokers
+        .iter()
+        .map(|(broker, rack)| (*broker, rack.clone()))
+        .collect::<BTreeMap<_, _>>();
+    let desired_distinct_racks = replication_factor.min(racks.len());
+    let mut assignments = Vec::with_capacity(partition_count);
+    for partition in 0..partition_count {
+        let start = partition % striped.len();
+        let leader = striped[start];
+        let mut replicas = vec![leader];
+        let mut used_racks = BTreeSet::from([rack_by_broker[&leader].clone()]);
+        for offset in 1..striped.len() {
+            let candidate = striped[(start + offset) % striped.len()];
+            let rack = &rack_by_broker[&candidate];
+            if used_racks.len() < desired_distinct_racks && used_racks.insert(rack.clone()) {
+                replicas.push(candidate);
+                if replicas.len() == replication_factor {
+                    break;
+                }
+            }
+        }
+        if replicas.len() < replication_factor {
+            for offset in 1..striped.len() {
+                let candidate = striped[(start + offset) % striped.len()];
+                if !replicas.contains(&candidate) {
+                    replicas.push(candidate);
+                    if replicas.len() == replication_factor {
+                        break;
                     }
                 }
-                Ok(())
-            })
-            .await?;
-        drop(connection);
-        let _throttle_time_ms = i32::decode(&mut response)?;
-        let topic_count = decode_acl_count(&mut response)?;
-        for _ in 0..topic_count {
-            let topic = decode_required_acl_string(&mut response, "log-dir topic")?;
-            let partition_count = decode_acl_count(&mut response)?;
-            for _ in 0..partition_count {
-                let partition = i32::decode(&mut response)?;
-                let error_code = i16::decode(&mut response)?;
-                rows.push(MutationRow {
-                    resource: format!("broker:{broker_id}:{topic}:{partition}"),
-                    status: if error_code == 0 {
-                        "LOG_DIR_MOVED".into()
-                    } else {
-                        "FAILED".into()
-                    },
-                    error: (error_code != 0).then(|| format!("Kafka error code {error_code}")),
-                });
             }
         }
+        assignments.push(replicas);
     }
-    Ok(rows)
+    Ok(assignments)
 }
 
-fn read_reassignment(path: &Path) -> Result<ReassignmentFile> {
-    let plan: ReassignmentFile = serde_json::from_reader(std::fs::File::open(path)?)?;
-    if plan.version != 1 {
-        return Err(Error::Usage(format!(
-            "unsupported reassignment JSON version: {}",
-            plan.version
-        )));
-    }
-    if plan.partitions.is_empty() {
-        return Err(Error::Usage(
-            "partition reassignment list cannot be empty".into(),
-        ));
-    }
-    let mut targets = BTreeSet::new();
-    for partition in &plan.partitions {
-        if partition.topic.is_empty() || partition.partition < 0 {
-            return Err(Error::Usage(
-                "reassignment topic must be non-empty and partition must be non-negative".into(),
-            ));
-        }
-        if !targets.insert((partition.topic.as_str(), partition.partition)) {
-            return Err(Error::Usage(format!(
-                "duplicate reassignment target {}:{}",
-                partition.topic, partition.partition
-            )));
-        }
-        if partition.replicas.is_empty() {
-            return Err(Error::Usage(format!(
-                "replica list cannot be empty for {}:{}",
-                partition.topic, partition.partition
-            )));
+/// A partition's replicas while a balancing plan is built; `replicas[0]` is the preferred
+/// leader.
+#[derive(Debug, Clone)]
+struct BalancedPartition {
+    topic: String,
+    partition: i32,
+    replicas: Vec<i32>,
+    bytes: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct BrokerLoad {
+    broker: i32,
+    rack: Option<String>,
+    replicas_before: usize,
+    replicas_after: usize,
+    leaders_before: usize,
+    leaders_after: usize,
+    bytes_before: i64,
+    bytes_after: i64,
+}
+
+/// A rack's summed broker loads; the same figures as [`BrokerLoad`], per rack.
+#[derive(Debug, Default, Serialize)]
+struct RackLoad {
+    rack: String,
+    brokers: usize,
+    replicas_before: usize,
+    replicas_after: usize,
+    leaders_before: usize,
+    leaders_after: usize,
+    bytes_before: i64,
+    bytes_after: i64,
+}
+
+#[derive(Debug, Serialize)]
+struct BalanceProposal {
+    /// Replicas the plan moves, adds, or removes; leader reordering is not counted.
+    moves: usize,
+    brokers: Vec<BrokerLoad>,
+    /// Per-rack totals; empty when brokers have no rack or rack awareness is disabled.
+    racks: Vec<RackLoad>,
+    reassignment: ReassignmentFile,
+}
+
+impl BalanceProposal {
+    fn new(
+        moves: usize,
+        before: &[BalancedPartition],
+        after: &[BalancedPartition],
+        racks: &BTreeMap<i32, Option<String>>,
+    ) -> Self {
+        let brokers = broker_loads(before, after, racks);
+        Self {
+            moves,
+            racks: rack_loads(&brokers),
+            brokers,
+            reassignment: ReassignmentFile {
+                version: 1,
+                partitions: before
+                    .iter()
+                    .zip(after)
+                    .filter(|(before, after)| before.replicas != after.replicas)
+                    .map(|(_, after)| ReassignmentPartition {
+                        topic: after.topic.clone(),
+                        partition: after.partition,
+                        log_dirs: vec!["any".into(); after.replicas.len()],
+                        replicas: after.replicas.clone(),
+                    })
+                    .collect(),
+            },
         }
-        let mut replicas = partition.replicas.clone();
-        replicas.sort_unstable();
-        replicas.dedup();
-        if replicas.len() != partition.replicas.len() {
-            return Err(Error::Usage(format!(
-                "replica list contains duplicates for {}:{}",
-                partition.topic, partition.partition
-            )));
+    }
+}
+
+#[expect(
+    clippy::too_many_arguments,
+    reason = "arguments map directly to the reassign balance CLI contract"
+)]
+async fn balance_reassignment(
+    config: &rdkafka::ClientConfig,
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    timeout: Duration,
+    format: OutputFormat,
+    topics: Option<&str>,
+    broker_list: Option<&str>,
+    max_moves: usize,
+    disable_rack_aware: bool,
+    output_json_file: Option<&Path>,
+) -> Result<()> {
+    let (mut partitions, known_brokers) = cluster_partitions(config, timeout, topics)?;
+    let brokers = match broker_list {
+        Some(list) => {
+            let brokers = parse_partitions(Some(list))?
+                .filter(|brokers| !brokers.is_empty())
+                .ok_or_else(|| Error::Usage("--broker-list must not be empty".into()))?;
+            if let Some(unknown) = brokers.iter().find(|id| !known_brokers.contains(id)) {
+                return Err(Error::Usage(format!(
+                    "broker {unknown} is not in cluster metadata"
+                )));
+            }
+            brokers
         }
-        if !partition.log_dirs.is_empty() && partition.log_dirs.len() != partition.replicas.len() {
-            return Err(Error::Usage(format!(
-                "log_dirs count must match replicas for {}:{}",
-                partition.topic, partition.partition
-            )));
+        None => known_brokers.into_iter().collect(),
+    };
+    let racks = broker_racks(
+        bootstrap,
+        command_config,
+        timeout,
+        &brokers,
+        disable_rack_aware,
+    )
+    .await?
+    .into_iter()
+    .collect::<BTreeMap<_, _>>();
+    let errors = Box::pin(load_partition_bytes(
+        bootstrap,
+        command_config,
+        timeout,
+        &mut partitions,
+    ))
+    .await;
+
+    let before = partitions.clone();
+    let moves = balance_replicas(&mut partitions, &racks, max_moves, None);
+    balance_leaders(&mut partitions, &racks);
+    let proposal = BalanceProposal::new(moves, &before, &partitions, &racks);
+    write_balance_proposal(
+        format,
+        "reassign.balance",
+        &proposal,
+        &errors,
+        output_json_file,
+    )
+}
+
+#[derive(Debug, Clone, Copy)]
+enum BrokerMove {
+    Drain,
+    Fill,
+}
+
+impl BrokerMove {
+    const fn command(self) -> &'static str {
+        match self {
+            Self::Drain => "reassign.drain",
+            Self::Fill => "reassign.fill",
         }
     }
-    Ok(plan)
 }
 
-fn reassignment_topics(plan: &ReassignmentFile, cancel: bool) -> Vec<ReassignableTopic> {
-    let mut topics: BTreeMap<String, Vec<ReassignablePartition>> = BTreeMap::new();
-    for partition in &plan.partitions {
-        topics
-            .entry(partition.topic.clone())
-            .or_default()
-            .push(ReassignablePartition {
-                partition_index: partition.partition,
-                replicas: (!cancel).then(|| partition.replicas.clone()),
-            });
+async fn move_broker_replicas(
+    config: &rdkafka::ClientConfig,
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    timeout: Duration,
+    format: OutputFormat,
+    args: &BrokerMoveArgs,
+    direction: BrokerMove,
+) -> Result<()> {
+    let (mut partitions, brokers) = cluster_partitions(config, timeout, args.topics.as_deref())?;
+    if !brokers.contains(&args.broker) {
+        return Err(Error::Usage(format!(
+            "broker {} is not in cluster metadata",
+            args.broker
+        )));
     }
-    topics
-        .into_iter()
-        .map(|(name, partitions)| ReassignableTopic { name, partitions })
-        .collect()
-}
+    let brokers = brokers.into_iter().collect::<Vec<_>>();
+    let racks = broker_racks(
+        bootstrap,
+        command_config,
+        timeout,
+        &brokers,
+        args.disable_rack_aware,
+    )
+    .await?
+    .into_iter()
+    .collect::<BTreeMap<_, _>>();
+    let errors = Box::pin(load_partition_bytes(
+        bootstrap,
+        command_config,
+        timeout,
+        &mut partitions,
+    ))
+    .await;
 
-fn reassignment_filters(plan: &ReassignmentFile) -> Vec<ListPartitionReassignmentsTopic> {
-    let mut topics: BTreeMap<String, Vec<i32>> = BTreeMap::new();
-    for partition in &plan.partitions {
-        topics
-            .entry(partition.topic.clone())
-            .or_default()
-            .push(partition.partition);
+    let before = partitions.clone();
+    let moves = match direction {
+        BrokerMove::Drain => drain_broker(&mut partitions, &racks, args.broker)?,
+        BrokerMove::Fill => {
+            balance_replicas(&mut partitions, &racks, usize::MAX, Some(args.broker))
+        }
+    };
+    let proposal = BalanceProposal::new(moves, &before, &partitions, &racks);
+    if !args.execute || proposal.reassignment.partitions.is_empty() {
+        return write_balance_proposal(
+            format,
+            direction.command(),
+            &proposal,
+            &errors,
+            args.output_json_file.as_deref(),
+        );
+    }
+    if let Some(path) = &args.output_json_file {
+        write_reassignment_file(path, &proposal.reassignment)?;
     }
-    topics
-        .into_iter()
-        .map(
-            |(name, partition_indexes)| ListPartitionReassignmentsTopic {
-                name,
-                partition_indexes,
-            },
-        )
-        .collect()
-}
 
-fn reassignment_statuses(
-    plan: &ReassignmentFile,
-    running: &[krafka::admin::PartitionReassignmentInfo],
-    current: &BTreeMap<(String, i32), Vec<i32>>,
-) -> Vec<ReassignmentStatus> {
-    plan.partitions
-        .iter()
-        .map(|target| {
-            let active = running
-                .iter()
-                .find(|topic| topic.name == target.topic)
-                .and_then(|topic| {
-                    topic
-                        .partitions
-                        .iter()
-                        .find(|partition| partition.partition_index == target.partition)
-                });
-            let current_replicas = current
-                .get(&(target.topic.clone(), target.partition))
-                .cloned()
-                .unwrap_or_default();
-            ReassignmentStatus {
-                topic: target.topic.clone(),
-                partition: target.partition,
-                replicas: active.map_or_else(
-                    || current_replicas.clone(),
-                    |partition| partition.replicas.clone(),
-                ),
-                adding_replicas: active
-                    .map(|partition| partition.adding_replicas.clone())
-                    .unwrap_or_default(),
-                removing_replicas: active
-                    .map(|partition| partition.removing_replicas.clone())
-                    .unwrap_or_default(),
-                complete: active.is_none() && current_replicas == target.replicas,
-            }
-        })
-        .collect()
+    let statuses = Box::pin(execute_until_verified(
+        config,
+        bootstrap,
+        command_config,
+        timeout,
+        format,
+        &proposal.reassignment,
+        true,
+        args.throttle,
+        Duration::from_millis(args.verify_interval_ms),
+        Duration::from_millis(args.stall_timeout_ms),
+    ))
+    .await?;
+    output::write_value_with_errors(format, direction.command(), &statuses, &errors, |rows| {
+        reassignment_table(rows)
+    })
 }
 
-fn current_replicas(
+/// Starts `plan` with an optional inter-broker throttle, then verifies it every
+/// `verify_interval` until every partition has moved, which also removes the throttles. Fails
+/// once no further partition has finished for `stall_timeout`. Drain and fill pass
+/// `disallow_replication_factor_change` so a plan can never alter the factor.
+#[expect(
+    clippy::too_many_arguments,
+    reason = "the chained execute and verify steps share the reassign connection options"
+)]
+async fn execute_until_verified(
     config: &rdkafka::ClientConfig,
+    bootstrap: &str,
+    command_config: Option<&Path>,
     timeout: Duration,
+    format: OutputFormat,
     plan: &ReassignmentFile,
-) -> Result<BTreeMap<(String, i32), Vec<i32>>> {
-    let metadata = base_consumer(config)?.fetch_metadata(None, timeout)?;
-    let targets = plan
-        .partitions
-        .iter()
-        .map(|partition| (partition.topic.as_str(), partition.partition))
-        .collect::<BTreeSet<_>>();
-    Ok(metadata
-        .topics()
-        .iter()
-        .flat_map(|topic| {
-            topic
-                .partitions()
-                .iter()
-                .filter(|partition| targets.contains(&(topic.name(), partition.id())))
-                .map(|partition| {
-                    (
-                        (topic.name().to_owned(), partition.id()),
-                        partition.replicas().to_vec(),
-                    )
-                })
-        })
-        .collect())
+    disallow_replication_factor_change: bool,
+    throttle: Option<u64>,
+    verify_interval: Duration,
+    stall_timeout: Duration,
+) -> Result<Vec<ReassignmentStatus>> {
+    Box::pin(start_reassignment(
+        config,
+        bootstrap,
+        command_config,
+        timeout,
+        format,
+        plan,
+        false,
+        disallow_replication_factor_change,
+        throttle,
+        None,
+    ))
+    .await?;
+    let mut progress = (0, Instant::now());
+    loop {
+        let statuses =
+            verify_reassignment(config, bootstrap, command_config, timeout, plan, false).await?;
+        let complete = statuses.iter().filter(|status| status.complete).count();
+        if complete == statuses.len() {
+            return Ok(statuses);
+        }
+        if complete > progress.0 {
+            progress = (complete, Instant::now());
+        } else if progress.1.elapsed() >= stall_timeout {
+            return Err(Error::Config(format!(
+                "reassignment made no progress for {} ms with {} of {} partitions incomplete; \
+                 it continues in the cluster, so follow it with reassign verify",
+                stall_timeout.as_millis(),
+                statuses.len() - complete,
+                statuses.len()
+            )));
+        }
+        eprintln!(
+            "{complete} of {} partitions reassigned; checking again in {} ms",
+            statuses.len(),
+            verify_interval.as_millis()
+        );
+        tokio::time::sleep(verify_interval).await;
+    }
 }
 
-fn reassignment_table(rows: &[ReassignmentStatus]) -> String {
-    output::table(
-        [
-            "TOPIC",
-            "PARTITION",
-            "STATUS",
-            "REPLICAS",
-            "ADDING",
-            "REMOVING",
-        ],
-        rows.iter().map(|row| {
-            [
-                row.topic.clone(),
-                row.partition.to_string(),
-                if row.complete {
-                    "COMPLETED".into()
-                } else {
-                    "IN_PROGRESS".into()
-                },
-                csv_numbers(&row.replicas),
-                csv_numbers(&row.adding_replicas),
-                csv_numbers(&row.removing_replicas),
-            ]
-        }),
-    )
+/// Moves every replica off `broker` onto the eligible broker with the fewest replicas (then
+/// bytes) that keeps the partition's rack spread. Replaced replicas keep their position, so a
+/// drained preferred leader is succeeded by its replacement.
+fn drain_broker(
+    partitions: &mut [BalancedPartition],
+    racks: &BTreeMap<i32, Option<String>>,
+    broker: i32,
+) -> Result<usize> {
+    let (mut replicas, mut bytes) = broker_replica_load(partitions, racks);
+    let mut moves = 0;
+    for partition in partitions {
+        let Some(position) = partition
+            .replicas
+            .iter()
+            .position(|replica| *replica == broker)
+        else {
+            continue;
+        };
+        let spread = rack_spread(&partition.replicas, racks);
+        let target = racks
+            .keys()
+            .copied()
+            .filter(|target| *target != broker && !partition.replicas.contains(target))
+            .filter(|target| {
+                let after_move = partition
+                    .replicas
+                    .iter()
+                    .map(|replica| if *replica == broker { target } else { replica });
+                rack_spread(after_move, racks) >= spread
+            })
+            .min_by_key(|target| (replicas[target], bytes[target], *target))
+            .ok_or_else(|| {
+                Error::Usage(format!(
+                    "no broker can take partition {}-{} from broker {broker} without reducing its rack spread; use --disable-rack-aware to ignore racks",
+                    partition.topic, partition.partition
+                ))
+            })?;
+        partition.replicas[position] = target;
+        *replicas.entry(broker).or_default() -= 1;
+        *replicas.entry(target).or_default() += 1;
+        *bytes.entry(broker).or_default() -= partition.bytes;
+        *bytes.entry(target).or_default() += partition.bytes;
+        moves += 1;
+    }
+    Ok(moves)
 }
 
-#[expect(
-    clippy::too_many_arguments,
-    clippy::too_many_lines,
-    reason = "arguments and phases mirror Kafka's generate-assignment command contract"
-)]
-async fn generate_reassignment(
+async fn alter_replication_factor(
     config: &rdkafka::ClientConfig,
     bootstrap: &str,
     command_config: Option<&Path>,
     timeout: Duration,
     format: OutputFormat,
-    path: &Path,
-    broker_list: &str,
-    disable_rack_aware: bool,
+    args: &AlterTopicArgs,
+    replication_factor: i32,
 ) -> Result<()> {
-    let input: TopicsToMoveFile = serde_json::from_reader(std::fs::File::open(path)?)?;
-    let brokers = parse_partitions(Some(broker_list))?
-        .filter(|brokers| !brokers.is_empty())
-        .ok_or_else(|| Error::Usage("--broker-list must not be empty".into()))?;
-    if brokers.iter().collect::<BTreeSet<_>>().len() != brokers.len() {
-        return Err(Error::Usage(
-            "--broker-list contains duplicate broker IDs".into(),
-        ));
-    }
-    if input
-        .topics
+    let replication_factor = usize::try_from(replication_factor)
+        .ok()
+        .filter(|replication_factor| *replication_factor > 0)
+        .ok_or_else(|| Error::Usage("--replication-factor must be greater than zero".into()))?;
+    let pattern = topic_pattern(&args.topic)?;
+    let metadata = base_consumer(config)?.fetch_metadata(None, timeout)?;
+    let brokers = metadata
+        .brokers()
         .iter()
-        .map(|topic| topic.topic.as_str())
-        .collect::<BTreeSet<_>>()
-        .len()
-        != input.topics.len()
-    {
-        return Err(Error::Usage(
-            "topics-to-move JSON contains duplicate topics".into(),
-        ));
+        .map(rdkafka::metadata::MetadataBroker::id)
+        .collect::<Vec<_>>();
+    let mut selected = BTreeSet::new();
+    let mut partitions = Vec::new();
+    for topic in metadata.topics() {
+        if topic.error().is_some() {
+            continue;
+        }
+        if pattern.is_match(topic.name()) {
+            selected.insert(topic.name().to_owned());
+        }
+        for partition in topic.partitions() {
+            partitions.push(BalancedPartition {
+                topic: topic.name().to_owned(),
+                partition: partition.id(),
+                replicas: partition.replicas().to_vec(),
+                bytes: 0,
+            });
+        }
+    }
+    drop(metadata);
+    if selected.is_empty() {
+        if args.if_exists {
+            return write_mutation_rows(
+                format,
+                "topics.alter",
+                &[MutationRow {
+                    resource: args.topic.clone(),
+                    status: "NO_MATCH".into(),
+                    error: None,
+                }],
+            );
+        }
+        return Err(Error::Usage("no topics matched --topic".into()));
     }
-    let metadata = base_consumer(config)?.fetch_metadata(None, timeout)?;
-    let known_brokers = metadata
-        .brokers()
-        .iter()
-        .map(rdkafka::metadata::MetadataBroker::id)
-        .collect::<Vec<_>>();
-    if let Some(unknown) = brokers.iter().find(|id| !known_brokers.contains(id)) {
+    if replication_factor > brokers.len() {
         return Err(Error::Usage(format!(
-            "broker {unknown} is not in cluster metadata"
+            "--replication-factor {replication_factor} exceeds the {} available brokers",
+            brokers.len()
         )));
     }
-    let protocol = config::protocol_admin(bootstrap, timeout, command_config).await?;
-    let cluster = protocol.describe_cluster().await?;
-    drop(protocol);
-    let broker_racks = brokers
-        .iter()
-        .map(|broker_id| {
-            cluster
-                .brokers
+    let racks = broker_racks(
+        bootstrap,
+        command_config,
+        timeout,
+        &brokers,
+        args.disable_rack_aware,
+    )
+    .await?
+    .into_iter()
+    .collect::<BTreeMap<_, _>>();
+    let errors = Box::pin(load_partition_bytes(
+        bootstrap,
+        command_config,
+        timeout,
+        &mut partitions,
+    ))
+    .await;
+
+    let before = partitions.clone();
+    let moves = change_replication_factor(&mut partitions, &selected, &racks, replication_factor);
+    let proposal = BalanceProposal::new(moves, &before, &partitions, &racks);
+    if !args.execute || proposal.reassignment.partitions.is_empty() {
+        return write_balance_proposal(
+            format,
+            "topics.alter",
+            &proposal,
+            &errors,
+            args.output_json_file.as_deref(),
+        );
+    }
+    if let Some(path) = &args.output_json_file {
+        write_reassignment_file(path, &proposal.reassignment)?;
+    }
+    let statuses = Box::pin(execute_until_verified(
+        config,
+        bootstrap,
+        command_config,
+        timeout,
+        format,
+        &proposal.reassignment,
+        false,
+        args.throttle,
+        Duration::from_millis(args.verify_interval_ms),
+        Duration::from_millis(args.stall_timeout_ms),
+    ))
+    .await?;
+    output::write_value_with_errors(format, "topics.alter", &statuses, &errors, |rows| {
+        reassignment_table(rows)
+    })
+}
+
+/// Adds or removes replicas of the `topics` partitions until each has `replication_factor`.
+/// New replicas go to the least-loaded broker on a rack the partition does not span yet, if
+/// any, and are appended so the preferred leader stays. Removal never drops the preferred
+/// leader and takes the most-loaded replica from the rack holding the most of them. Returns the
+/// number of replicas added or removed.
+fn change_replication_factor(
+    partitions: &mut [BalancedPartition],
+    topics: &BTreeSet<String>,
+    racks: &BTreeMap<i32, Option<String>>,
+    replication_factor: usize,
+) -> usize {
+    let (mut replicas, mut bytes) = broker_replica_load(partitions, racks);
+    let mut changes = 0;
+    for partition in partitions
+        .iter_mut()
+        .filter(|partition| topics.contains(&partition.topic))
+    {
+        while partition.replicas.len() < replication_factor {
+            let covered = partition
+                .replicas
                 .iter()
-                .find(|broker| broker.broker_id == *broker_id)
-                .map(|broker| {
+                .map(|replica| racks.get(replica))
+                .collect::<BTreeSet<_>>();
+            let Some(target) = racks
+                .keys()
+                .copied()
+                .filter(|broker| !partition.replicas.contains(broker))
+                .min_by_key(|broker| {
                     (
-                        *broker_id,
-                        (!disable_rack_aware).then(|| broker.rack.clone()).flatten(),
+                        covered.contains(&racks.get(broker)),
+                        replicas[broker],
+                        bytes[broker],
+                        *broker,
                     )
                 })
-                .ok_or_else(|| Error::Usage(format!("broker {broker_id} was not described")))
-        })
-        .collect::<Result<Vec<_>>>()?;
-    let rackless = broker_racks
-        .iter()
-        .filter(|(_, rack)| rack.is_none())
-        .count();
-    if !disable_rack_aware && rackless != 0 && rackless != broker_racks.len() {
-        return Err(Error::Usage(
-            "not all brokers have rack information; use --disable-rack-aware".into(),
-        ));
+            else {
+                break;
+            };
+            partition.replicas.push(target);
+            *replicas.entry(target).or_default() += 1;
+            *bytes.entry(target).or_default() += partition.bytes;
+            changes += 1;
+        }
+        while partition.replicas.len() > replication_factor.max(1) {
+            let mut per_rack = BTreeMap::<Option<&Option<String>>, usize>::new();
+            for replica in &partition.replicas {
+                *per_rack.entry(racks.get(replica)).or_default() += 1;
+            }
+            let Some(position) = (1..partition.replicas.len()).max_by_key(|position| {
+                let broker = partition.replicas[*position];
+                (
+                    per_rack[&racks.get(&broker)],
+                    replicas.get(&broker).copied().unwrap_or_default(),
+                    bytes.get(&broker).copied().unwrap_or_default(),
+                    broker,
+                )
+            }) else {
+                break;
+            };
+            let broker = partition.replicas.remove(position);
+            if let Some(count) = replicas.get_mut(&broker) {
+                *count -= 1;
+            }
+            if let Some(size) = bytes.get_mut(&broker) {
+                *size -= partition.bytes;
+            }
+            changes += 1;
+        }
     }
+    changes
+}
+
+/// Reads the replicas of every partition of `topics` (or of every non-internal topic) and the
+/// brokers in cluster metadata.
+fn cluster_partitions(
+    config: &rdkafka::ClientConfig,
+    timeout: Duration,
+    topics: Option<&str>,
+) -> Result<(Vec<BalancedPartition>, BTreeSet<i32>)> {
+    let metadata = base_consumer(config)?.fetch_metadata(None, timeout)?;
+    let brokers = metadata
+        .brokers()
+        .iter()
+        .map(rdkafka::metadata::MetadataBroker::id)
+        .collect::<BTreeSet<_>>();
+    let selected_topics = topics.map(parse_topic_names).transpose()?;
     let mut partitions = Vec::new();
-    for selected in &input.topics {
-        let topic = metadata
-            .topics()
-            .iter()
-            .find(|topic| topic.name() == selected.topic)
-            .ok_or_else(|| Error::Usage(format!("topic {} not found", selected.topic)))?;
-        let replication_factor = topic
-            .partitions()
-            .first()
-            .map_or(0, |partition| partition.replicas().len());
-        let assignments = striped_replica_assignment(
-            &broker_racks,
-            topic.partitions().len(),
-            replication_factor,
-        )?;
-        for (partition, replicas) in topic.partitions().iter().zip(assignments) {
-            if replication_factor > brokers.len() {
-                return Err(Error::Usage(format!(
-                    "topic {} requires {replication_factor} distinct brokers, but only {} were supplied",
-                    selected.topic,
-                    brokers.len()
-                )));
-            }
-            partitions.push(ReassignmentPartition {
-                topic: selected.topic.clone(),
+    for topic in metadata.topics() {
+        let selected = selected_topics.as_ref().map_or_else(
+            || !topic.name().starts_with("__"),
+            |names| names.iter().any(|name| name == topic.name()),
+        );
+        if !selected || topic.error().is_some() {
+            continue;
+        }
+        for partition in topic.partitions() {
+            partitions.push(BalancedPartition {
+                topic: topic.name().to_owned(),
                 partition: partition.id(),
-                log_dirs: vec!["any".into(); replicas.len()],
-                replicas,
+                replicas: partition.replicas().to_vec(),
+                bytes: 0,
             });
         }
     }
-    let proposal = ReassignmentFile {
-        version: 1,
-        partitions,
-    };
-    output::write_value(format, "reassign.generate", &proposal, |proposal| {
-        output::table(
-            ["TOPIC", "PARTITION", "REPLICAS", "LOG_DIRS"],
-            proposal.partitions.iter().map(|partition| {
+    if let Some(missing) = selected_topics
+        .iter()
+        .flatten()
+        .find(|name| !partitions.iter().any(|partition| &partition.topic == *name))
+    {
+        return Err(Error::Usage(format!("topic {missing} not found")));
+    }
+    Ok((partitions, brokers))
+}
+
+/// Fills in partition sizes from the log directories. Sizes stay zero when they cannot be read,
+/// and the returned message explains that the plan only balances replica counts.
+async fn load_partition_bytes(
+    bootstrap: &str,
+    command_config: Option<&Path>,
+    timeout: Duration,
+    partitions: &mut [BalancedPartition],
+) -> Vec<String> {
+    if partitions.is_empty() {
+        return Vec::new();
+    }
+    let targets = partitions
+        .iter()
+        .map(|partition| (partition.topic.clone(), partition.partition))
+        .collect::<Vec<_>>();
+    match partition_log_bytes(bootstrap, command_config, timeout, &targets).await {
+        Ok(sizes) => {
+            for partition in partitions {
+                partition.bytes = sizes
+                    .get(&(partition.topic.clone(), partition.partition))
+                    .copied()
+                    .unwrap_or_default();
+            }
+            Vec::new()
+        }
+        Err(error) => vec![format!(
+            "log directory sizes unavailable, balancing by replica count only: {error}"
+        )],
+    }
+}
+
+fn write_balance_proposal(
+    format: OutputFormat,
+    command: &str,
+    proposal: &BalanceProposal,
+    errors: &[String],
+    output_json_file: Option<&Path>,
+) -> Result<()> {
+    if let Some(path) = output_json_file {
+        write_reassignment_file(path, &proposal.reassignment)?;
+    }
+    output::write_value_with_errors(format, command, proposal, errors, |proposal| {
+        let brokers = output::table(
+            ["BROKER", "RACK", "REPLICAS", "LEADERS", "BYTES"],
+            proposal.brokers.iter().map(|load| {
+                [
+                    load.broker.to_string(),
+                    load.rack.clone().unwrap_or_else(|| "-".into()),
+                    format!("{} -> {}", load.replicas_before, load.replicas_after),
+                    format!("{} -> {}", load.leaders_before, load.leaders_after),
+                    format!("{} -> {}", load.bytes_before, load.bytes_after),
+                ]
+            }),
+        );
+        let racks = if proposal.racks.is_empty() {
+            String::new()
+        } else {
+            let racks = output::table(
+                ["RACK", "BROKERS", "REPLICAS", "LEADERS", "BYTES"],
+                proposal.racks.iter().map(|load| {
+                    [
+                        load.rack.clone(),
+                        load.brokers.to_string(),
+                        format!("{} -> {}", load.replicas_before, load.replicas_after),
+                        format!("{} -> {}", load.leaders_before, load.leaders_after),
+                        format!("{} -> {}", load.bytes_before, load.bytes_after),
+                    ]
+                }),
+            );
+            format!("{racks}\n")
+        };
+        let plan = output::table(
+            ["TOPIC", "PARTITION", "REPLICAS"],
+            proposal.reassignment.partitions.iter().map(|partition| {
                 [
                     partition.topic.clone(),
                     partition.partition.to_string(),
                     csv_numbers(&partition.replicas),
-                    partition.log_dirs.join(","),
                 ]
             }),
+        );
+        format!(
+            "{brokers}\n{racks}{plan}\nReplica moves: {}",
+            proposal.moves
         )
     })
 }
 
-fn striped_replica_assignment(
-    brokers: &[(i32, Option<String>)],
-    partition_count: usize,
-    replication_factor: usize,
-) -> Result<Vec<Vec<i32>>> {
-    if replication_factor == 0 {
-        return Err(Error::Usage("replication factor must be positive".into()));
-    }
-    if replication_factor > brokers.len() {
-        return Err(Error::Usage(format!(
-            "replication factor {replication_factor} exceeds {} available brokers",
-            brokers.len()
-        )));
-    }
-    let mut racks = BTreeMap::<Option<String>, Vec<i32>>::new();
-    for (broker, rack) in brokers {
-        racks.entry(rack.clone()).or_default().push(*broker);
-    }
-    for rack in racks.values_mut() {
-        rack.sort_unstable();
-    }
-    let max_rack_size = racks.values().map(Vec::len).max().unwrap_or_default();
-    let mut striped = Vec::with_capacity(brokers.len());
-    for index in 0..max_rack_size {
-        for rack in racks.values() {
-            if let Some(broker) = rack.get(index) {
-                striped.push(*broker);
+fn write_reassignment_file(path: &Path, plan: &ReassignmentFile) -> Result<()> {
+    let mut file = std::fs::File::create(path)?;
+    serde_json::to_writer_pretty(&mut file, plan)?;
+    writeln!(file)?;
+    Ok(())
+}
+
+/// Number of distinct racks `replicas` span; brokers without a rack count as one rack.
+fn rack_spread<'a>(
+    replicas: impl IntoIterator<Item = &'a i32>,
+    racks: &BTreeMap<i32, Option<String>>,
+) -> usize {
+    replicas
+        .into_iter()
+        .map(|replica| racks.get(replica))
+        .collect::<BTreeSet<_>>()
+        .len()
+}
+
+/// Greedily applies the replica move that most reduces the squared deviation of per-broker
+/// replica counts and bytes from their means, until no move helps or `max_moves` is reached.
+/// Only brokers in `racks` give or receive replicas (only `onto` receives them when set), and a
+/// move never reduces the number of distinct racks a partition spans.
+#[expect(
+    clippy::cast_precision_loss,
+    reason = "balance scores compare normalized floating-point deviations"
+)]
+fn balance_replicas(
+    partitions: &mut [BalancedPartition],
+    racks: &BTreeMap<i32, Option<String>>,
+    max_moves: usize,
+    onto: Option<i32>,
+) -> usize {
+    let mut moves = 0;
+    while moves < max_moves {
+        let (replicas, bytes) = broker_replica_load(partitions, racks);
+        let mean_replicas = replicas.values().sum::<usize>() as f64 / racks.len().max(1) as f64;
+        let mean_bytes = bytes.values().sum::<i64>() as f64 / racks.len().max(1) as f64;
+        let mut best: Option<(f64, usize, usize, i32)> = None;
+        for (index, partition) in partitions.iter().enumerate() {
+            let distinct_racks = rack_spread(&partition.replicas, racks);
+            let size = partition.bytes as f64;
+            for (position, source) in partition.replicas.iter().enumerate() {
+                if !racks.contains_key(source) {
+                    continue;
+                }
+                for target in racks.keys() {
+                    if partition.replicas.contains(target)
+                        || onto.is_some_and(|onto| onto != *target)
+                    {
+                        continue;
+                    }
+                    let after_move = partition
+                        .replicas
+                        .iter()
+                        .map(|replica| if replica == source { target } else { replica });
+                    if rack_spread(after_move, racks) < distinct_racks {
+                        continue;
+                    }
+                    // Change in sum((load - mean)^2 / mean^2) when one replica moves.
+                    let mut delta = 2.0
+                        * ((replicas[target] as f64 - replicas[source] as f64) + 1.0)
+                        / mean_replicas.powi(2);
+                    if mean_bytes > 0.0 {
+                        delta += 2.0 * size * ((bytes[target] - bytes[source]) as f64 + size)
+                            / mean_bytes.powi(2);
+                    }
+                    if delta < -1e-12 && best.is_none_or(|(best, ..)| delta < best) {
+                        best = Some((delta, index, position, *target));
+                    }
+                }
             }
         }
+        let Some((_, index, position, target)) = best else {
+            break;
+        };
+        partitions[index].replicas[position] = target;
+        moves += 1;
     }
-    let rack_by_broker = brokers
-        .iter()
-        .map(|(broker, rack)| (*broker, rack.clone()))
+    moves
+}
+
+/// Reorders replica lists so preferred leaders are spread evenly, without moving data.
+fn balance_leaders(partitions: &mut [BalancedPartition], racks: &BTreeMap<i32, Option<String>>) {
+    let mut leaders = racks
+        .keys()
+        .map(|broker| (*broker, 0_usize))
         .collect::<BTreeMap<_, _>>();
-    let desired_distinct_racks = replication_factor.min(racks.len());
-    let mut assignments = Vec::with_capacity(partition_count);
-    for partition in 0..partition_count {
-        let start = partition % striped.len();
-        let leader = striped[start];
-        let mut replicas = vec![leader];
-        let mut used_racks = BTreeSet::from([rack_by_broker[&leader].clone()]);
-        for offset in 1..striped.len() {
-            let candidate = striped[(start + offset) % striped.len()];
-            let rack = &rack_by_broker[&candidate];
-            if used_racks.len() < desired_distinct_racks && used_racks.insert(rack.clone()) {
-                replicas.push(candidate);
-                if replicas.len() == replication_factor {
-                    break;
+    for partition in partitions.iter() {
+        if let Some(count) = partition
+            .replicas
+            .first()
+            .and_then(|leader| leaders.get_mut(leader))
+        {
+            *count += 1;
+        }
+    }
+    // Every swap lowers the sum of squared leader counts, so this terminates.
+    loop {
+        let mut best: Option<(usize, usize, usize)> = None;
+        for (index, partition) in partitions.iter().enumerate() {
+            let Some(leader_count) = partition
+                .replicas
+                .first()
+                .and_then(|leader| leaders.get(leader))
+            else {
+                continue;
+            };
+            for (position, replica) in partition.replicas.iter().enumerate().skip(1) {
+                if let Some(count) = leaders.get(replica)
+                    && count + 1 < *leader_count
+                    && best.is_none_or(|(gap, ..)| leader_count - count > gap)
+                {
+                    best = Some((leader_count - count, index, position));
                 }
             }
         }
-        if replicas.len() < replication_factor {
-            for offset in 1..striped.len() {
-                let candidate = striped[(start + offset) % striped.len()];
-                if !replicas.contains(&candidate) {
-                    replicas.push(candidate);
-                    if replicas.len() == replication_factor {
-                        break;
-                    }
-                }
+        let Some((_, index, position)) = best else {
+            break;
+        };
+        let replicas = &mut partitions[index].replicas;
+        *leaders.entry(replicas[0]).or_default() -= 1;
+        *leaders.entry(replicas[position]).or_default() += 1;
+        replicas.swap(0, position);
+    }
+}
+
+fn broker_replica_load(
+    partitions: &[BalancedPartition],
+    racks: &BTreeMap<i32, Option<String>>,
+) -> (BTreeMap<i32, usize>, BTreeMap<i32, i64>) {
+    let mut replicas = racks
+        .keys()
+        .map(|broker| (*broker, 0))
+        .collect::<BTreeMap<_, _>>();
+    let mut bytes = racks
+        .keys()
+        .map(|broker| (*broker, 0))
+        .collect::<BTreeMap<_, _>>();
+    for partition in partitions {
+        for replica in &partition.replicas {
+            if let Some(count) = replicas.get_mut(replica) {
+                *count += 1;
+                *bytes.entry(*replica).or_default() += partition.bytes;
             }
         }
-        assignments.push(replicas);
     }
-    Ok(assignments)
+    (replicas, bytes)
+}
+
+fn broker_loads(
+    before: &[BalancedPartition],
+    after: &[BalancedPartition],
+    racks: &BTreeMap<i32, Option<String>>,
+) -> Vec<BrokerLoad> {
+    let leaders = |partitions: &[BalancedPartition], broker: i32| {
+        partitions
+            .iter()
+            .filter(|partition| partition.replicas.first() == Some(&broker))
+            .count()
+    };
+    let (replicas_before, bytes_before) = broker_replica_load(before, racks);
+    let (replicas_after, bytes_after) = broker_replica_load(after, racks);
+    racks
+        .iter()
+        .map(|(broker, rack)| BrokerLoad {
+            broker: *broker,
+            rack: rack.clone(),
+            replicas_before: replicas_before[broker],
+            replicas_after: replicas_after[broker],
+            leaders_before: leaders(before, *broker),
+            leaders_after: leaders(after, *broker),
+            bytes_before: bytes_before[broker],
+            bytes_after: bytes_after[broker],
+        })
+        .collect()
+}
+
+fn rack_loads(brokers: &[BrokerLoad]) -> Vec<RackLoad> {
+    let mut racks = BTreeMap::<&str, RackLoad>::new();
+    for load in brokers {
+        let Some(rack) = load.rack.as_deref() else {
+            continue;
+        };
+        let total = racks.entry(rack).or_insert_with(|| RackLoad {
+            rack: rack.to_owned(),
+            ..RackLoad::default()
+        });
+        total.brokers += 1;
+        total.replicas_before += load.replicas_before;
+        total.replicas_after += load.replicas_after;
+        total.leaders_before += load.leaders_before;
+        total.leaders_after += load.leaders_after;
+        total.bytes_before += load.bytes_before;
+        total.bytes_after += load.bytes_after;
+    }
+    racks.into_values().collect()
 }
 
 #[expect(
@@ -16451,6 +20907,30 @@ mod tests {
         }
     }
 
+    #[test]
+    fn secret_properties_should_cover_inline_keys_and_credentials() {
+        for key in [
+            "ssl.key.pem",
+            "ssl_key",
+            "ssl.keystore.password",
+            "ssl.key.password",
+            "sasl.password",
+            "sasl.jaas.config",
+            "sasl.oauthbearer.config",
+            "sasl.oauthbearer.client.secret",
+        ] {
+            assert!(is_secret_property(key), "{key}");
+        }
+        for key in [
+            "ssl.certificate.pem",
+            "ssl.ca.pem",
+            "sasl.username",
+            "client.id",
+        ] {
+            assert!(!is_secret_property(key), "{key}");
+        }
+    }
+
     #[test]
     fn resolve_bootstrap_should_prefer_controller_when_server_absent() {
         assert_eq!(
@@ -16962,417 +21442,1265 @@ mod tests {
     }
 
     #[test]
-    fn quota_entities_should_pair_named_client_and_default_user() {
-        let names = ["billing".to_owned(), String::new()];
-        let entities = quota_entities(
-            &[ConfigEntityType::Client, ConfigEntityType::User],
-            &names,
-            false,
-            true,
-        )
-        .expect("mixed quota entity");
-
-        assert_eq!(entities, [("client-id", Some("billing")), ("user", None)]);
+    fn quota_entities_should_pair_named_client_and_default_user() {
+        let names = ["billing".to_owned(), String::new()];
+        let entities = quota_entities(
+            &[ConfigEntityType::Client, ConfigEntityType::User],
+            &names,
+            false,
+            true,
+        )
+        .expect("mixed quota entity");
+
+        assert_eq!(entities, [("client-id", Some("billing")), ("user", None)]);
+    }
+
+    #[test]
+    fn config_entity_types_should_reject_duplicates() {
+        assert!(matches!(
+            validate_config_entity_types(&[ConfigEntityType::Topic, ConfigEntityType::Topic]),
+            Err(Error::Usage(message)) if message.contains("duplicate")
+        ));
+    }
+
+    #[test]
+    fn config_broker_entity_name_should_require_an_integer() {
+        assert!(matches!(
+            validate_config_entity_names(
+                &[ConfigEntityType::BrokerLogger],
+                &["not-a-broker".into()],
+                false,
+                false,
+            ),
+            Err(Error::Usage(message)) if message.contains("integer broker ID")
+        ));
+    }
+
+    #[test]
+    fn config_ip_entity_name_should_accept_ip_literal() {
+        let result = validate_config_entity_names(
+            &[ConfigEntityType::Ip],
+            &["127.0.0.1".into()],
+            false,
+            false,
+        );
+
+        assert!(result.is_ok(), "IP validation failed: {result:?}");
+    }
+
+    #[test]
+    fn config_ip_entity_name_should_reject_invalid_host() {
+        assert!(matches!(
+            validate_config_entity_names(
+                &[ConfigEntityType::Ip],
+                &["invalid host name with spaces".into()],
+                false,
+                false,
+            ),
+            Err(Error::Usage(message)) if message.contains("valid IP or resolvable host")
+        ));
+    }
+
+    #[test]
+    fn config_alter_should_reject_empty_entity_name() {
+        assert!(matches!(
+            validate_config_entity_names(
+                &[ConfigEntityType::User],
+                &[String::new()],
+                true,
+                false,
+            ),
+            Err(Error::Usage(message)) if message.contains("--entity-default")
+        ));
+    }
+
+    #[test]
+    fn config_broker_logger_describe_should_require_entity_name() {
+        assert!(matches!(
+            validate_config_entity_names(
+                &[ConfigEntityType::BrokerLogger],
+                &[],
+                false,
+                false,
+            ),
+            Err(Error::Usage(message)) if message.contains("requires --entity-name")
+        ));
+    }
+
+    #[test]
+    fn config_keys_should_reject_characters_disallowed_by_kafka() {
+        assert!(matches!(
+            validate_config_keys(&[("retention ms".into(), "1000".into())]),
+            Err(Error::Usage(message)) if message.contains("invalid character")
+        ));
+    }
+
+    #[test]
+    fn config_deletions_should_trim_each_comma_separated_key() {
+        assert_eq!(
+            normalize_config_deletions(vec![" retention.ms".into(), "segment.bytes ".into()]),
+            ["retention.ms", "segment.bytes"]
+        );
+    }
+
+    #[test]
+    fn client_properties_should_override_existing_configuration() {
+        let mut config = rdkafka::ClientConfig::new();
+        config.set("client.id", "before");
+
+        apply_client_properties(&mut config, &["client.id=after".into()])
+            .expect("valid client property");
+
+        assert_eq!(config.get("client.id"), Some("after"));
+    }
+
+    #[test]
+    fn replica_assignment_should_validate_brokers_and_replication_factor() {
+        assert_eq!(
+            parse_replica_assignment("1:2, 2:3").expect("valid assignment"),
+            [vec![1, 2], vec![2, 3]]
+        );
+        assert!(matches!(
+            parse_replica_assignment("1:1,1:2"),
+            Err(Error::Usage(_))
+        ));
+        assert!(matches!(
+            parse_replica_assignment("1,1:2"),
+            Err(Error::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn parse_partitions_should_reject_non_integer() {
+        let result = parse_partitions(Some("0,nope"));
+        assert!(matches!(result, Err(Error::Usage(_))));
+    }
+
+    #[test]
+    fn topic_partition_pattern_should_match_bounded_ranges() {
+        let patterns = parse_topic_partition_patterns("events:1-3,audit:5-")
+            .expect("valid topic-partition patterns");
+
+        assert!(patterns[0].matches("events", 2) && patterns[1].matches("audit", 8));
+    }
+
+    #[test]
+    fn topic_partition_pattern_should_reject_reversed_ranges() {
+        assert!(matches!(
+            parse_topic_partition_patterns("events:3-1"),
+            Err(Error::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn topic_partition_pattern_should_drop_java_trailing_empty_rules() {
+        let patterns = parse_topic_partition_patterns("events:0,").expect("trailing comma");
+
+        assert!(patterns[0].matches("events", 0) && !patterns[0].matches("audit", 0));
+    }
+
+    #[test]
+    fn partition_list_should_follow_java_trailing_empty_semantics() {
+        assert_eq!(
+            (
+                parse_partitions(Some("0,")).expect("trailing comma"),
+                parse_partitions(Some("")).expect("empty list"),
+            ),
+            (Some(vec![0]), None)
+        );
+    }
+
+    #[test]
+    fn offsets_client_should_use_original_client_id() {
+        let mut config = rdkafka::ClientConfig::new();
+        config.set("client.id", "custom");
+
+        assert_eq!(
+            offsets_client_config(&config).get("client.id"),
+            Some("GetOffsetShell")
+        );
+    }
+
+    #[test]
+    fn tiered_offset_timestamp_should_match_kafka_protocol_sentinels() {
+        assert_eq!(
+            [
+                tiered_offset_timestamp(ffi::ListOffsetSpec::EarliestLocal),
+                tiered_offset_timestamp(ffi::ListOffsetSpec::LatestTiered),
+                tiered_offset_timestamp(ffi::ListOffsetSpec::EarliestPendingUpload),
+            ],
+            [Some(-4), Some(-5), Some(-6)]
+        );
+    }
+
+    #[test]
+    fn parse_topic_names_should_trim_and_reject_empty_lists() {
+        assert_eq!(
+            parse_topic_names(" orders, payments ").expect("valid topics"),
+            ["orders", "payments"]
+        );
+        assert!(matches!(parse_topic_names(", ,"), Err(Error::Usage(_))));
+    }
+
+    #[test]
+    fn topic_pattern_should_use_java_style_full_match() {
+        let pattern = topic_pattern("events-.*").expect("valid expression");
+        assert!(pattern.is_match("events-orders"));
+        assert!(!pattern.is_match("archived-events-orders"));
+    }
+
+    #[test]
+    fn consumer_include_should_be_anchored_without_rust_regex_validation() {
+        assert_eq!(
+            consumer_include_pattern("integration-jso{,1}n"),
+            "^(integration-jso{,1}n)$"
+        );
+    }
+
+    #[test]
+    fn topic_id_should_reject_malformed_kafka_uuid() {
+        assert!(matches!(
+            validate_topic_id(Some("not-a-topic-id")),
+            Err(Error::Usage(_))
+        ));
+    }
+
+    #[test]
+    fn topic_id_should_accept_librdkafka_standard_base64_uuid() {
+        assert!(validate_topic_id(Some("YcqKQkG1QC+w8OkFq/qppA")).is_ok());
+    }
+
+    #[test]
+    fn zero_topic_id_should_not_override_topic_name() {
+        assert!(!is_nonzero_topic_id(ZERO_TOPIC_ID));
     }
 
     #[test]
-    fn config_entity_types_should_reject_duplicates() {
+    fn topic_creation_should_reject_non_positive_partition_count() {
         assert!(matches!(
-            validate_config_entity_types(&[ConfigEntityType::Topic, ConfigEntityType::Topic]),
-            Err(Error::Usage(message)) if message.contains("duplicate")
+            validate_topic_creation_counts(Some(0), None),
+            Err(Error::Usage(_))
         ));
     }
 
     #[test]
-    fn config_broker_entity_name_should_require_an_integer() {
+    fn topic_creation_should_reject_replication_factor_above_short_max() {
         assert!(matches!(
-            validate_config_entity_names(
-                &[ConfigEntityType::BrokerLogger],
-                &["not-a-broker".into()],
-                false,
-                false,
-            ),
-            Err(Error::Usage(message)) if message.contains("integer broker ID")
+            validate_topic_creation_counts(None, Some(i32::from(i16::MAX) + 1)),
+            Err(Error::Usage(_))
         ));
     }
 
     #[test]
-    fn config_ip_entity_name_should_accept_ip_literal() {
-        let result = validate_config_entity_names(
-            &[ConfigEntityType::Ip],
-            &["127.0.0.1".into()],
-            false,
-            false,
-        );
+    fn topic_pattern_should_reject_invalid_expression() {
+        assert!(matches!(topic_pattern("["), Err(Error::Usage(_))));
+    }
 
-        assert!(result.is_ok(), "IP validation failed: {result:?}");
+    #[test]
+    fn topic_partition_should_match_when_isr_is_under_minimum() {
+        let selector = TopicSelector {
+            under_min_isr_partitions: true,
+            ..topic_selector()
+        };
+
+        assert!(topic_partition_matches(&selector, 1, 3, Some(2), true));
     }
 
     #[test]
-    fn config_ip_entity_name_should_reject_invalid_host() {
-        assert!(matches!(
-            validate_config_entity_names(
-                &[ConfigEntityType::Ip],
-                &["invalid host name with spaces".into()],
-                false,
-                false,
-            ),
-            Err(Error::Usage(message)) if message.contains("valid IP or resolvable host")
-        ));
+    fn topic_partition_should_match_when_isr_is_at_minimum() {
+        let selector = TopicSelector {
+            at_min_isr_partitions: true,
+            ..topic_selector()
+        };
+
+        assert!(topic_partition_matches(&selector, 2, 3, Some(2), true));
     }
 
     #[test]
-    fn config_alter_should_reject_empty_entity_name() {
-        assert!(matches!(
-            validate_config_entity_names(
-                &[ConfigEntityType::User],
-                &[String::new()],
-                true,
-                false,
-            ),
-            Err(Error::Usage(message)) if message.contains("--entity-default")
-        ));
+    fn topic_partition_should_report_a_leader_missing_from_live_brokers() {
+        let selector = TopicSelector {
+            unavailable_partitions: true,
+            ..topic_selector()
+        };
+
+        assert!(topic_partition_matches(&selector, 1, 1, None, false));
     }
 
     #[test]
-    fn config_broker_logger_describe_should_require_entity_name() {
+    fn producer_input_should_parse_json_key_partition_and_headers() {
+        let args = crate::cli::ProduceArgs {
+            topic: "events".into(),
+            line_reader: "org.apache.kafka.tools.LineMessageReader".into(),
+            key_separator: None,
+            parse_key: false,
+            compression_type: "none".into(),
+            acks: Some("all".into()),
+            sync: false,
+            batch_size: None,
+            max_partition_memory_bytes: None,
+            message_send_max_retries: None,
+            retry_backoff_ms: None,
+            linger_ms: None,
+            request_timeout_ms: None,
+            metadata_expiry_ms: None,
+            max_block_ms: Some(60_000),
+            max_memory_bytes: None,
+            socket_buffer_size: None,
+            json: true,
+            schema_registry_url: None,
+            key_schema: None,
+            value_schema: None,
+            key_subject: None,
+            value_subject: None,
+            key_message: None,
+            value_message: None,
+            key_encoding: DataEncoding::Utf8,
+            value_encoding: DataEncoding::Utf8,
+            header_encoding: DataEncoding::Utf8,
+            reader_properties: Vec::new(),
+            deprecated_reader_properties: Vec::new(),
+            reader_config: None,
+            properties: Vec::new(),
+            deprecated_properties: Vec::new(),
+        };
+        let options = line_reader_options(&args).expect("reader options");
+        let input = producer_input(
+            r#"{"key":"order-1","value":"created","partition":2,"headers":{"trace":"abc","empty":null}}"#,
+            true,
+            &options,
+        )
+        .expect("valid JSON record");
+        assert_eq!(input.key, Some(ProducerField::Text("order-1".into())));
+        assert_eq!(input.value, Some(ProducerField::Text("created".into())));
+        assert_eq!(input.partition, Some(2));
+        assert!(
+            input
+                .headers
+                .iter()
+                .any(|(key, value)| key == "trace" && value.as_deref() == Some("abc"))
+        );
+        assert!(
+            input
+                .headers
+                .iter()
+                .any(|(key, value)| key == "empty" && value.is_none())
+        );
+    }
+
+    #[tokio::test]
+    async fn schema_encoded_producer_fields_should_validate_json_datums() {
+        let directory = tempfile::tempdir().expect("temp dir");
+        let schema = directory.path().join("5.avsc");
+        std::fs::write(
+            &schema,
+            r#"{"type":"record","name":"R","fields":[{"name":"n","type":"int"}]}"#,
+        )
+        .expect("schema written");
+        let encoder = RecordEncoder::resolve(None, Some(&schema), "events-value", None)
+            .await
+            .expect("encoder");
+        let json = ProducerField::Json(serde_json::json!({"n": 2}));
+        let line = ProducerField::Text(r#"{"n": 2}"#.into());
+
+        for (field, json_input) in [(&json, true), (&line, false)] {
+            assert_eq!(
+                encoded_producer_field(
+                    Some(field),
+                    Some(&encoder),
+                    json_input,
+                    DataEncoding::Utf8,
+                    "value"
+                )
+                .expect("encodes"),
+                Some(vec![0, 0, 0, 0, 5, 4])
+            );
+        }
+        assert!(
+            encoded_producer_field(
+                Some(&line),
+                Some(&encoder),
+                true,
+                DataEncoding::Utf8,
+                "value"
+            )
+            .is_err()
+        );
         assert!(matches!(
-            validate_config_entity_names(
-                &[ConfigEntityType::BrokerLogger],
-                &[],
-                false,
-                false,
-            ),
-            Err(Error::Usage(message)) if message.contains("requires --entity-name")
+            encoded_producer_field(Some(&json), None, true, DataEncoding::Utf8, "value"),
+            Err(Error::Usage(message)) if message.contains("value schema")
         ));
+        assert_eq!(
+            encoded_producer_field(None, Some(&encoder), true, DataEncoding::Utf8, "value")
+                .expect("tombstone"),
+            None
+        );
     }
 
     #[test]
-    fn config_keys_should_reject_characters_disallowed_by_kafka() {
+    fn producer_input_should_reject_negative_json_partition() {
+        let args = crate::cli::ProduceArgs {
+            topic: "events".into(),
+            line_reader: "org.apache.kafka.tools.LineMessageReader".into(),
+            key_separator: None,
+            parse_key: false,
+            compression_type: "none".into(),
+            acks: Some("all".into()),
+            sync: false,
+            batch_size: None,
+            max_partition_memory_bytes: None,
+            message_send_max_retries: None,
+            retry_backoff_ms: None,
+            linger_ms: None,
+            request_timeout_ms: None,
+            metadata_expiry_ms: None,
+            max_block_ms: Some(60_000),
+            max_memory_bytes: None,
+            socket_buffer_size: None,
+            json: true,
+            schema_registry_url: None,
+            key_schema: None,
+            value_schema: None,
+            key_subject: None,
+            value_subject: None,
+            key_message: None,
+            value_message: None,
+            key_encoding: DataEncoding::Utf8,
+            value_encoding: DataEncoding::Utf8,
+            header_encoding: DataEncoding::Utf8,
+            reader_properties: Vec::new(),
+            deprecated_reader_properties: Vec::new(),
+            reader_config: None,
+            properties: Vec::new(),
+            deprecated_properties: Vec::new(),
+        };
+        let options = line_reader_options(&args).expect("reader options");
         assert!(matches!(
-            validate_config_keys(&[("retention ms".into(), "1000".into())]),
-            Err(Error::Usage(message)) if message.contains("invalid character")
+            producer_input(r#"{"value":"bad","partition":-1}"#, true, &options),
+            Err(Error::Usage(_))
         ));
     }
 
     #[test]
-    fn config_deletions_should_trim_each_comma_separated_key() {
+    fn producer_properties_should_parse_headers_key_and_null_marker() {
+        let cli = Cli::try_parse_from([
+            "kafka",
+            "--bootstrap-server",
+            "localhost:9092",
+            "produce",
+            "--topic",
+            "events",
+            "--property",
+            "parse.headers=true",
+            "--property",
+            "headers.delimiter=|",
+            "--property",
+            "parse.key=true",
+            "--property",
+            "key.separator=|",
+            "--property",
+            "null.marker=NULL",
+        ])
+        .expect("producer properties");
+        let Command::Produce(args) = cli.command else {
+            panic!("expected produce command");
+        };
+        let options = line_reader_options(&args).expect("reader options");
+
+        let input = producer_input("trace:abc,empty:NULL|order-1|created", false, &options)
+            .expect("line input");
+
         assert_eq!(
-            normalize_config_deletions(vec![" retention.ms".into(), "segment.bytes ".into()]),
-            ["retention.ms", "segment.bytes"]
+            input,
+            ProducerInput {
+                key: Some(ProducerField::Text("order-1".into())),
+                value: Some(ProducerField::Text("created".into())),
+                partition: None,
+                headers: vec![("trace".into(), Some("abc".into())), ("empty".into(), None)],
+            }
         );
     }
 
     #[test]
-    fn client_properties_should_override_existing_configuration() {
-        let mut config = rdkafka::ClientConfig::new();
-        config.set("client.id", "before");
+    fn consumer_properties_should_configure_default_formatter() {
+        let cli = Cli::try_parse_from([
+            "kafka",
+            "--bootstrap-server",
+            "localhost:9092",
+            "consume",
+            "--topic",
+            "events",
+            "--property",
+            "print.partition=true",
+            "--property",
+            "print.headers=true",
+            "--property",
+            "print.key=true",
+            "--property",
+            "key.separator=|",
+            "--property",
+            "null.literal=NULL",
+        ])
+        .expect("consumer properties");
+        let Command::Consume(args) = cli.command else {
+            panic!("expected consume command");
+        };
 
-        apply_client_properties(&mut config, &["client.id=after".into()])
-            .expect("valid client property");
+        let options = message_formatter_options(&*args).expect("formatter options");
 
-        assert_eq!(config.get("client.id"), Some("after"));
+        assert!(options.print_partition && options.print_headers && options.print_key);
+        assert_eq!(options.key_separator, b"|");
+        assert_eq!(options.null_literal, b"NULL");
     }
 
     #[test]
-    fn replica_assignment_should_validate_brokers_and_replication_factor() {
+    fn string_deserializer_should_follow_kafka_utf8_display_semantics() {
         assert_eq!(
-            parse_replica_assignment("1:2, 2:3").expect("valid assignment"),
-            [vec![1, 2], vec![2, 3]]
+            native_deserializer(
+                Some("org.apache.kafka.common.serialization.StringDeserializer"),
+                "value"
+            )
+            .expect("string deserializer"),
+            NativeDeserializer::Utf8String
+        );
+        assert_eq!(
+            deserialize_for_display(
+                Some(b"valid UTF-8"),
+                NativeDeserializer::Utf8String,
+                b"null"
+            ),
+            b"valid UTF-8"
+        );
+        assert_eq!(
+            String::from_utf8(deserialize_for_display(
+                Some(&[b'a', 0xff, b'b']),
+                NativeDeserializer::Utf8String,
+                b"null"
+            ))
+            .expect("lossy UTF-8 result"),
+            "a\u{fffd}b"
         );
         assert!(matches!(
-            parse_replica_assignment("1:1,1:2"),
-            Err(Error::Usage(_))
-        ));
-        assert!(matches!(
-            parse_replica_assignment("1,1:2"),
-            Err(Error::Usage(_))
+            native_deserializer(Some("example.CustomDeserializer"), "value"),
+            Err(Error::Unsupported(message)) if message.contains("example.CustomDeserializer")
         ));
     }
 
     #[test]
-    fn parse_partitions_should_reject_non_integer() {
-        let result = parse_partitions(Some("0,nope"));
-        assert!(matches!(result, Err(Error::Usage(_))));
+    fn formatted_leader_epoch_should_render_present_epoch() {
+        assert_eq!(formatted_leader_epoch(Some(12)), b"Epoch:12");
     }
 
     #[test]
-    fn topic_partition_pattern_should_match_bounded_ranges() {
-        let patterns = parse_topic_partition_patterns("events:1-3,audit:5-")
-            .expect("valid topic-partition patterns");
-
-        assert!(patterns[0].matches("events", 2) && patterns[1].matches("audit", 8));
+    fn formatted_leader_epoch_should_render_missing_epoch() {
+        assert_eq!(formatted_leader_epoch(None), b"Epoch:NOT_PRESENT");
     }
 
-    #[test]
-    fn topic_partition_pattern_should_reject_reversed_ranges() {
+    #[tokio::test]
+    async fn backup_and_restore_should_reuse_client_plumbing() {
+        let directory = tempfile::tempdir().expect("temp dir");
+        let dir = directory.path().to_str().expect("utf-8 path");
+        let Command::Backup(backup_args) = Cli::try_parse_from([
+            "kafka",
+            "--bootstrap-server",
+            "localhost:9092",
+            "backup",
+            "--topic",
+            "events",
+            "--output-dir",
+            dir,
+            "--isolation-level",
+            "read_committed",
+        ])
+        .expect("backup arguments")
+        .command
+        else {
+            panic!("expected backup command");
+        };
+        let mut config = rdkafka::ClientConfig::new();
+        configure_consumer(&mut config, &backup_args).expect("consumer config");
+        assert_eq!(config.get("auto.offset.reset"), Some("earliest"));
+        assert_eq!(config.get("isolation.level"), Some("read_committed"));
+
+        backup::write_manifest(
+            directory.path(),
+            &BackupManifest {
+                format: backup::BackupFormat::Binary,
+                topics: Vec::new(),
+            },
+        )
+        .expect("manifest");
+        let Command::Restore(restore_args) = Cli::try_parse_from([
+            "kafka",
+            "--bootstrap-server",
+            "localhost:9092",
+            "restore",
+            "--input-dir",
+            dir,
+            "--topic",
+            "events",
+            "--compression-type",
+            "zstd",
+            "--acks",
+            "1",
+        ])
+        .expect("restore arguments")
+        .command
+        else {
+            panic!("expected restore command");
+        };
+        let mut config = rdkafka::ClientConfig::new();
+        assert_eq!(
+            configure_producer(&mut config, &restore_args).expect("producer config"),
+            60_000
+        );
+        assert_eq!(config.get("compression.type"), Some("zstd"));
+        assert_eq!(config.get("acks"), Some("1"));
         assert!(matches!(
-            parse_topic_partition_patterns("events:3-1"),
-            Err(Error::Usage(_))
+            restore(config, Duration::from_secs(1), OutputFormat::Json, restore_args).await,
+            Err(Error::Usage(message)) if message.contains("not in the backup")
         ));
     }
 
     #[test]
-    fn topic_partition_pattern_should_drop_java_trailing_empty_rules() {
-        let patterns = parse_topic_partition_patterns("events:0,").expect("trailing comma");
-
-        assert!(patterns[0].matches("events", 0) && !patterns[0].matches("audit", 0));
-    }
+    fn record_filters_should_require_every_predicate() {
+        let cli = Cli::try_parse_from([
+            "kafka",
+            "--bootstrap-server",
+            "localhost:9092",
+            "consume",
+            "--topic",
+            "orders",
+            "--filter-key",
+            "^order-",
+            "--filter-header",
+            "source=web",
+            "--filter-from-time",
+            "1000",
+            "--filter-to-time",
+            "2000",
+            "--filter-json",
+            "$.items[1].sku=B2",
+            "--filter-json",
+            "$.total=12.5",
+        ])
+        .expect("consumer arguments");
+        let Command::Consume(args) = cli.command else {
+            panic!("expected consume command");
+        };
+        let formatter = message_formatter_options(&*args).expect("formatter options");
+        let filter = RecordFilter::new(&args).expect("filter");
+        let message = |key: &str, timestamp: i64, source: &str, value: &str| {
+            rdkafka::message::OwnedMessage::new(
+                Some(value.as_bytes().to_vec()),
+                Some(key.as_bytes().to_vec()),
+                "orders".into(),
+                rdkafka::Timestamp::CreateTime(timestamp),
+                0,
+                0,
+                Some(OwnedHeaders::new().insert(Header {
+                    key: "source",
+                    value: Some(source),
+                })),
+            )
+        };
+        let value = r#"{"items":[{"sku":"A1"},{"sku":"B2"}],"total":12.5}"#;
 
-    #[test]
-    fn partition_list_should_follow_java_trailing_empty_semantics() {
+        assert!(
+            filter
+                .matches(&message("order-1", 1500, "web", value), &formatter)
+                .expect("evaluated")
+        );
+        for rejected in [
+            message("refund-1", 1500, "web", value),
+            message("order-1", 2500, "web", value),
+            message("order-1", 1500, "mobile", value),
+            message("order-1", 1500, "web", r#"{"items":[],"total":12.5}"#),
+            message("order-1", 1500, "web", "not json"),
+        ] {
+            assert!(!filter.matches(&rejected, &formatter).expect("evaluated"));
+        }
         assert_eq!(
-            (
-                parse_partitions(Some("0,")).expect("trailing comma"),
-                parse_partitions(Some("")).expect("empty list"),
-            ),
-            (Some(vec![0]), None)
+            parse_json_path("$.a[2].b").expect("path"),
+            vec![
+                JsonPathSegment::Field("a".into()),
+                JsonPathSegment::Index(2),
+                JsonPathSegment::Field("b".into()),
+            ]
         );
+        assert!(parse_json_path("$..a").is_err());
     }
 
     #[test]
-    fn offsets_client_should_use_original_client_id() {
-        let mut config = rdkafka::ClientConfig::new();
-        config.set("client.id", "custom");
+    #[expect(
+        clippy::literal_string_with_formatting_args,
+        reason = "format templates share Rust's brace placeholder syntax"
+    )]
+    fn format_templates_should_render_record_fields() {
+        let cli = Cli::try_parse_from([
+            "kafka",
+            "--bootstrap-server",
+            "localhost:9092",
+            "consume",
+            "--topic",
+            "orders",
+            "--format-template",
+            "{topic}/{partition}@{offset} {timestamp:iso} {header.trace-id} {header.missing} {{{key}}}={value}",
+        ])
+        .expect("consumer arguments");
+        let Command::Consume(args) = cli.command else {
+            panic!("expected consume command");
+        };
+        let formatter = message_formatter_options(&*args).expect("formatter options");
+        let record = TemplateRecord {
+            topic: "orders",
+            partition: 2,
+            offset: 41,
+            timestamp: Some(1_700_000_000_123),
+            key: Some(b"order-7"),
+            value: Some(b"paid"),
+            headers: vec![(Cow::Borrowed("trace-id"), Some(b"abc".as_slice()))],
+            leader_epoch: None,
+            delivery_count: Some(3),
+        };
+        let template = formatter.template.as_ref().expect("template");
 
         assert_eq!(
-            offsets_client_config(&config).get("client.id"),
-            Some("GetOffsetShell")
+            String::from_utf8(template.render(&record, &formatter).expect("rendered"))
+                .expect("utf-8"),
+            "orders/2@41 2023-11-14T22:13:20.123Z abc null {order-7}=paid"
         );
-    }
-
-    #[test]
-    fn tiered_offset_timestamp_should_match_kafka_protocol_sentinels() {
         assert_eq!(
-            [
-                tiered_offset_timestamp(ffi::ListOffsetSpec::EarliestLocal),
-                tiered_offset_timestamp(ffi::ListOffsetSpec::LatestTiered),
-                tiered_offset_timestamp(ffi::ListOffsetSpec::EarliestPendingUpload),
-            ],
-            [Some(-4), Some(-5), Some(-6)]
+            MessageTemplate::parse("{delivery} {epoch} {headers} {timestamp}")
+                .expect("template")
+                .render(&record, &formatter)
+                .expect("rendered"),
+            b"3 null trace-id:abc 1700000000123"
         );
+        for invalid in ["{offset", "offset}", "{unknown}", "{header.}"] {
+            assert!(matches!(
+                MessageTemplate::parse(invalid),
+                Err(Error::Usage(_))
+            ));
+        }
     }
 
     #[test]
-    fn parse_topic_names_should_trim_and_reject_empty_lists() {
-        assert_eq!(
-            parse_topic_names(" orders, payments ").expect("valid topics"),
-            ["orders", "payments"]
+    fn csv_layout_should_write_selected_columns() {
+        let parse = |extra: &[&str]| {
+            let mut arguments = vec![
+                "kafka",
+                "--bootstrap-server",
+                "localhost:9092",
+                "consume",
+                "--topic",
+                "orders",
+                "--csv",
+            ];
+            arguments.extend_from_slice(extra);
+            let Command::Consume(args) = Cli::try_parse_from(arguments)
+                .expect("consumer arguments")
+                .command
+            else {
+                panic!("expected consume command");
+            };
+            args
+        };
+        let message = rdkafka::message::OwnedMessage::new(
+            Some(b"say \"hi\", ok".to_vec()),
+            None,
+            "orders".into(),
+            rdkafka::Timestamp::CreateTime(1_000),
+            3,
+            17,
+            Some(OwnedHeaders::new().insert(Header {
+                key: "source",
+                value: Some("web"),
+            })),
         );
-        assert!(matches!(parse_topic_names(", ,"), Err(Error::Usage(_))));
-    }
-
-    #[test]
-    fn topic_pattern_should_use_java_style_full_match() {
-        let pattern = topic_pattern("events-.*").expect("valid expression");
-        assert!(pattern.is_match("events-orders"));
-        assert!(!pattern.is_match("archived-events-orders"));
-    }
+        let args = parse(&["--csv-columns", "offset,key,value,headers"]);
+        let formatter = message_formatter_options(&*args).expect("formatter options");
+        let ConsumeLayout::Csv(columns) = ConsumeLayout::new(&args).expect("layout") else {
+            panic!("expected CSV layout");
+        };
+        let row = csv_line(
+            columns
+                .iter()
+                .map(|column| csv_field(*column, &message, &formatter))
+                .collect::<Result<Vec<_>>>()
+                .expect("fields"),
+        )
+        .expect("row");
 
-    #[test]
-    fn consumer_include_should_be_anchored_without_rust_regex_validation() {
         assert_eq!(
-            consumer_include_pattern("integration-jso{,1}n"),
-            "^(integration-jso{,1}n)$"
+            String::from_utf8(row).expect("utf-8"),
+            "17,,\"say \"\"hi\"\", ok\",\"{\"\"source\"\":\"\"web\"\"}\"\n"
+        );
+        assert_eq!(
+            ConsumeLayout::new(&parse(&[]))
+                .expect("layout")
+                .preamble()
+                .expect("preamble"),
+            Some(b"topic,partition,offset,timestamp,key,value,headers\n".to_vec())
+        );
+        assert!(
+            Cli::try_parse_from([
+                "kafka",
+                "--bootstrap-server",
+                "localhost:9092",
+                "consume",
+                "--topic",
+                "orders",
+                "--csv",
+                "--json",
+            ])
+            .is_err()
         );
     }
 
     #[test]
-    fn topic_id_should_reject_malformed_kafka_uuid() {
-        assert!(matches!(
-            validate_topic_id(Some("not-a-topic-id")),
-            Err(Error::Usage(_))
-        ));
-    }
-
-    #[test]
-    fn topic_id_should_accept_librdkafka_standard_base64_uuid() {
-        assert!(validate_topic_id(Some("YcqKQkG1QC+w8OkFq/qppA")).is_ok());
-    }
+    fn balance_should_even_replicas_and_leaders_within_rack_and_move_limits() {
+        let partition = |partition, replicas: &[i32], bytes| BalancedPartition {
+            topic: "orders".into(),
+            partition,
+            replicas: replicas.to_vec(),
+            bytes,
+        };
+        let racks = BTreeMap::from([
+            (1, Some("a".to_owned())),
+            (2, Some("b".to_owned())),
+            (3, Some("a".to_owned())),
+            (4, Some("b".to_owned())),
+        ]);
+        let before = vec![
+            partition(0, &[1, 2], 100),
+            partition(1, &[1, 2], 100),
+            partition(2, &[1, 2], 100),
+            partition(3, &[1, 2], 100),
+        ];
 
-    #[test]
-    fn zero_topic_id_should_not_override_topic_name() {
-        assert!(!is_nonzero_topic_id(ZERO_TOPIC_ID));
-    }
+        let mut limited = before.clone();
+        assert_eq!(balance_replicas(&mut limited, &racks, 1, None), 1);
 
-    #[test]
-    fn topic_creation_should_reject_non_positive_partition_count() {
-        assert!(matches!(
-            validate_topic_creation_counts(Some(0), None),
-            Err(Error::Usage(_))
-        ));
+        let mut partitions = before.clone();
+        let moves = balance_replicas(&mut partitions, &racks, 50, None);
+        balance_leaders(&mut partitions, &racks);
+        assert_eq!(moves, 4);
+        for partition in &partitions {
+            let spread = partition
+                .replicas
+                .iter()
+                .map(|replica| &racks[replica])
+                .collect::<BTreeSet<_>>();
+            assert_eq!(spread.len(), 2, "{partition:?}");
+        }
+        let loads = broker_loads(&before, &partitions, &racks);
+        assert!(loads.iter().all(|load| load.replicas_after == 2));
+        assert!(loads.iter().all(|load| load.bytes_after == 200));
+        assert!(loads.iter().all(|load| load.leaders_after == 1));
+        assert_eq!(loads[0].leaders_before, 4);
+        assert_eq!(loads[0].bytes_before, 400);
+        let totals = rack_loads(&loads);
+        assert_eq!(totals.len(), 2);
+        assert_eq!(
+            (
+                totals[0].brokers,
+                totals[0].leaders_before,
+                totals[0].leaders_after,
+                totals[0].bytes_after,
+            ),
+            (2, 4, 2, 400)
+        );
+        assert!(
+            rack_loads(&broker_loads(
+                &before,
+                &partitions,
+                &BTreeMap::from([(1, None)])
+            ))
+            .is_empty()
+        );
     }
 
     #[test]
-    fn topic_creation_should_reject_replication_factor_above_short_max() {
-        assert!(matches!(
-            validate_topic_creation_counts(None, Some(i32::from(i16::MAX) + 1)),
-            Err(Error::Usage(_))
-        ));
-    }
+    fn drain_and_fill_should_move_replicas_off_and_onto_one_broker_keeping_racks() {
+        let partition = |partition, replicas: &[i32]| BalancedPartition {
+            topic: "orders".into(),
+            partition,
+            replicas: replicas.to_vec(),
+            bytes: 10,
+        };
+        let racks = BTreeMap::from([
+            (1, Some("a".to_owned())),
+            (2, Some("b".to_owned())),
+            (3, Some("a".to_owned())),
+            (4, Some("b".to_owned())),
+        ]);
+        let mut partitions = vec![
+            partition(0, &[1, 2]),
+            partition(1, &[2, 1]),
+            partition(2, &[3, 4]),
+            partition(3, &[2, 3]),
+        ];
 
-    #[test]
-    fn topic_pattern_should_reject_invalid_expression() {
-        assert!(matches!(topic_pattern("["), Err(Error::Usage(_))));
-    }
+        assert_eq!(
+            drain_broker(&mut partitions, &racks, 1).expect("drainable"),
+            2
+        );
+        assert_eq!(partitions[0].replicas, [3, 2]);
+        assert_eq!(partitions[1].replicas, [2, 3]);
+        assert_eq!(partitions[3].replicas, [2, 3]);
+        assert!(
+            partitions
+                .iter()
+                .all(|partition| rack_spread(&partition.replicas, &racks) == 2)
+        );
 
-    #[test]
-    fn topic_partition_should_match_when_isr_is_under_minimum() {
-        let selector = TopicSelector {
-            under_min_isr_partitions: true,
-            ..topic_selector()
-        };
+        let mut filled = partitions.clone();
+        let moves = balance_replicas(&mut filled, &racks, usize::MAX, Some(1));
+        assert_eq!(moves, 2);
+        let loads = broker_loads(&partitions, &filled, &racks);
+        assert_eq!((loads[0].replicas_before, loads[0].replicas_after), (0, 2));
+        assert!(filled.iter().zip(&partitions).all(|(after, before)| {
+            after.replicas == before.replicas || after.replicas.contains(&1)
+        }));
 
-        assert!(topic_partition_matches(&selector, 1, 3, Some(2), true));
+        let lonely = BTreeMap::from([(1, Some("a".to_owned())), (2, Some("b".to_owned()))]);
+        let mut unmovable = vec![partition(0, &[1, 2])];
+        assert!(drain_broker(&mut unmovable, &lonely, 1).is_err());
     }
 
     #[test]
-    fn topic_partition_should_match_when_isr_is_at_minimum() {
-        let selector = TopicSelector {
-            at_min_isr_partitions: true,
-            ..topic_selector()
+    fn replication_factor_changes_should_spread_racks_and_keep_leaders() {
+        let partition = |topic: &str, partition, replicas: &[i32]| BalancedPartition {
+            topic: topic.into(),
+            partition,
+            replicas: replicas.to_vec(),
+            bytes: 10,
         };
+        let racks = BTreeMap::from([
+            (1, Some("a".to_owned())),
+            (2, Some("a".to_owned())),
+            (3, Some("b".to_owned())),
+            (4, Some("c".to_owned())),
+        ]);
+        let mut partitions = vec![
+            partition("orders", 0, &[1]),
+            partition("orders", 1, &[2]),
+            partition("audit", 0, &[3, 4]),
+        ];
+        let orders = BTreeSet::from(["orders".to_owned()]);
+
+        assert_eq!(
+            change_replication_factor(&mut partitions, &orders, &racks, 3),
+            4
+        );
+        for partition in &partitions[..2] {
+            assert_eq!(partition.replicas.len(), 3);
+            assert_eq!(rack_spread(&partition.replicas, &racks), 3, "{partition:?}");
+        }
+        assert_eq!(partitions[0].replicas[0], 1);
+        assert_eq!(partitions[1].replicas[0], 2);
+        assert_eq!(partitions[2].replicas, [3, 4]);
 
-        assert!(topic_partition_matches(&selector, 2, 3, Some(2), true));
+        partitions[0].replicas = vec![1, 2, 3];
+        assert_eq!(
+            change_replication_factor(&mut partitions, &orders, &racks, 2),
+            2
+        );
+        assert_eq!(partitions[0].replicas, [1, 3]);
+        assert_eq!(partitions[1].replicas.len(), 2);
+        assert_eq!(partitions[1].replicas[0], 2);
+        assert_eq!(rack_spread(&partitions[1].replicas, &racks), 2);
     }
 
     #[test]
-    fn topic_partition_should_report_a_leader_missing_from_live_brokers() {
-        let selector = TopicSelector {
-            unavailable_partitions: true,
-            ..topic_selector()
+    fn skew_report_should_flag_outlier_partitions_and_leader_concentration() {
+        let partition = |partition: i32, leader: i32| PartitionSummary {
+            topic: "orders".into(),
+            topic_id: String::new(),
+            partition,
+            leader,
+            replication_factor: 2,
+            replicas: vec![1, 2],
+            isr: vec![1, 2],
+            configs: Vec::new(),
         };
+        let values = |values: [i64; 4]| {
+            (0..)
+                .zip(values)
+                .map(|(partition, value)| (("orders".to_owned(), partition), value))
+                .collect::<HashMap<_, _>>()
+        };
+        let report = SkewReport::new(
+            &[
+                partition(0, 1),
+                partition(1, 1),
+                partition(2, 1),
+                partition(3, 1),
+            ],
+            &values([100, 100, 100, 100]),
+            Some(&values([10, 10, 0, 80])),
+            &values([1_000, 1_000, 1_000, 4_000]),
+            2.0,
+        );
 
-        assert!(topic_partition_matches(&selector, 1, 1, None, false));
+        let flags = report
+            .partitions
+            .iter()
+            .map(|partition| partition.flags.join(","))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            flags,
+            [
+                "growth-low",
+                "growth-low",
+                "growth-low",
+                "size-high,growth-high"
+            ]
+        );
+        assert!(
+            report.partitions[3]
+                .size_ratio
+                .is_some_and(|ratio| (ratio - 4.0 / 1.75).abs() < 1e-9)
+        );
+        assert_eq!(report.partitions[0].end_offset_ratio, Some(1.0));
+        assert_eq!(
+            report
+                .leaders
+                .iter()
+                .map(|leader| (leader.broker, leader.leaders, leader.flagged))
+                .collect::<Vec<_>>(),
+            [(1, 4, true), (2, 0, false)]
+        );
+        assert_eq!(mean_ratios(&[Some(0), None]), [None, None]);
     }
 
     #[test]
-    fn producer_input_should_parse_json_key_partition_and_headers() {
-        let args = crate::cli::ProduceArgs {
-            topic: "events".into(),
-            line_reader: "org.apache.kafka.tools.LineMessageReader".into(),
-            key_separator: None,
-            parse_key: false,
-            compression_type: "none".into(),
-            acks: Some("all".into()),
-            sync: false,
-            batch_size: None,
-            max_partition_memory_bytes: None,
-            message_send_max_retries: None,
-            retry_backoff_ms: None,
-            linger_ms: None,
-            request_timeout_ms: None,
-            metadata_expiry_ms: None,
-            max_block_ms: Some(60_000),
-            max_memory_bytes: None,
-            socket_buffer_size: None,
-            json: true,
-            reader_properties: Vec::new(),
-            deprecated_reader_properties: Vec::new(),
-            reader_config: None,
-            properties: Vec::new(),
-            deprecated_properties: Vec::new(),
+    fn topic_sampler_should_report_partition_spread_sizes_and_rates() {
+        let message = |partition: i32, key: &str, timestamp: i64| {
+            rdkafka::message::OwnedMessage::new(
+                Some(vec![0; 10]),
+                Some(key.as_bytes().to_vec()),
+                "orders".into(),
+                rdkafka::Timestamp::CreateTime(timestamp),
+                partition,
+                0,
+                Some(OwnedHeaders::new().insert(Header {
+                    key: "id",
+                    value: Some("ab"),
+                })),
+            )
         };
-        let options = line_reader_options(&args).expect("reader options");
-        let input = producer_input(
-            r#"{"key":"order-1","value":"created","partition":2,"headers":{"trace":"abc","empty":null}}"#,
-            true,
-            &options,
-        )
-        .expect("valid JSON record");
-        assert_eq!(input.key.as_deref(), Some("order-1"));
-        assert_eq!(input.value.as_deref(), Some("created"));
-        assert_eq!(input.partition, Some(2));
-        assert!(
-            input
-                .headers
-                .iter()
-                .any(|(key, value)| key == "trace" && value.as_deref() == Some("abc"))
+        let mut sampler = TopicSampler::new(1_000);
+        for (partition, key, timestamp) in
+            [(0, "a", 0), (0, "b", 500), (0, "a", 1_000), (1, "c", 1_500)]
+        {
+            sampler.add(&message(partition, key, timestamp));
+        }
+        let stats = sampler.finish(
+            "orders",
+            &BTreeMap::from([(0, 30), (1, 10), (2, 0)]),
+            &HashMap::from([(0, 150)]),
         );
-        assert!(
-            input
-                .headers
-                .iter()
-                .any(|(key, value)| key == "empty" && value.is_none())
+
+        assert_eq!(
+            (stats.records, stats.sampled, stats.distinct_keys),
+            (40, 4, 3)
+        );
+        assert_eq!(stats.skew, Some(2.25));
+        let partition = &stats.partitions[0];
+        assert!((partition.share - 75.0).abs() < f64::EPSILON);
+        assert_eq!(partition.distinct_keys, 2);
+        assert_eq!(partition.records_per_second, Some(2.0));
+        assert_eq!(partition.record_bytes.p99, 15);
+        assert!((partition.header_bytes.average - 4.0).abs() < f64::EPSILON);
+        assert_eq!(partition.compression_ratio, Some(3.0));
+        assert_eq!(stats.compression_ratio, Some(3.0));
+        assert_eq!(stats.partitions[2].sampled, 0);
+        assert_eq!(
+            stats.rate,
+            vec![
+                RateBucket {
+                    start: 0,
+                    records: 2,
+                    records_per_second: 2.0,
+                },
+                RateBucket {
+                    start: 1_000,
+                    records: 2,
+                    records_per_second: 2.0,
+                },
+            ]
+        );
+
+        let mut histogram = SizeHistogram::default();
+        for size in (1..=100).rev() {
+            histogram.insert(size);
+        }
+        let sizes = SizeStats::new(&histogram);
+        assert_eq!((sizes.p50, sizes.p95, sizes.p99), (50, 95, 99));
+        let mut histogram = SizeHistogram::default();
+        for size in (1..=1_000_000).step_by(7) {
+            histogram.insert(size);
+        }
+        assert!(histogram.buckets.len() < 1_000);
+        for (percent, exact) in [(50, 500_000), (95, 950_000), (99, 990_000)] {
+            let estimate = histogram.percentile(percent);
+            assert!(
+                estimate.abs_diff(exact) * 64 < exact,
+                "p{percent}: {estimate}"
+            );
+        }
+        assert_eq!(
+            SizeHistogram::highest(SizeHistogram::bucket(u64::MAX)),
+            u64::MAX
         );
+        let mut sketch = KeySketch::default();
+        for key in 0..20_000_u32 {
+            sketch.insert(&key.to_be_bytes());
+        }
+        assert!(sketch.estimate().abs_diff(20_000) < 1_000);
     }
 
     #[test]
-    fn producer_input_should_reject_negative_json_partition() {
-        let args = crate::cli::ProduceArgs {
-            topic: "events".into(),
-            line_reader: "org.apache.kafka.tools.LineMessageReader".into(),
-            key_separator: None,
-            parse_key: false,
-            compression_type: "none".into(),
-            acks: Some("all".into()),
-            sync: false,
-            batch_size: None,
-            max_partition_memory_bytes: None,
-            message_send_max_retries: None,
-            retry_backoff_ms: None,
-            linger_ms: None,
-            request_timeout_ms: None,
-            metadata_expiry_ms: None,
-            max_block_ms: Some(60_000),
-            max_memory_bytes: None,
-            socket_buffer_size: None,
-            json: true,
-            reader_properties: Vec::new(),
-            deprecated_reader_properties: Vec::new(),
-            reader_config: None,
-            properties: Vec::new(),
-            deprecated_properties: Vec::new(),
+    fn search_patterns_should_match_key_value_and_headers() {
+        let parse = |extra: &[&str]| {
+            let mut arguments = vec![
+                "kafka",
+                "--bootstrap-server",
+                "localhost:9092",
+                "search",
+                "--topic",
+                "orders",
+            ];
+            arguments.extend_from_slice(extra);
+            let Command::Search(args) = Cli::try_parse_from(arguments)
+                .expect("search arguments")
+                .command
+            else {
+                panic!("expected search command");
+            };
+            SearchPattern::new(&args)
         };
-        let options = line_reader_options(&args).expect("reader options");
-        assert!(matches!(
-            producer_input(r#"{"value":"bad","partition":-1}"#, true, &options),
-            Err(Error::Usage(_))
-        ));
+        let message = |key: &str, value: &str, region: Option<&str>| {
+            rdkafka::message::OwnedMessage::new(
+                Some(value.as_bytes().to_vec()),
+                Some(key.as_bytes().to_vec()),
+                "orders".into(),
+                rdkafka::Timestamp::CreateTime(1_000),
+                0,
+                0,
+                region.map(|region| {
+                    OwnedHeaders::new().insert(Header {
+                        key: "region",
+                        value: Some(region),
+                    })
+                }),
+            )
+        };
+        let pattern = parse(&[
+            "--key",
+            "^cust-4",
+            "--value",
+            "refund",
+            "--header",
+            "region=^eu-",
+        ])
+        .expect("pattern");
+
+        assert!(pattern.matches(&message("cust-42", "full refund", Some("eu-west"))));
+        for rejected in [
+            message("cust-17", "full refund", Some("eu-west")),
+            message("cust-42", "purchase", Some("eu-west")),
+            message("cust-42", "full refund", Some("us-east")),
+            message("cust-42", "full refund", None),
+        ] {
+            assert!(!pattern.matches(&rejected));
+        }
+        assert!(matches!(parse(&[]), Err(Error::Usage(_))));
+        assert!(matches!(parse(&["--key", "("]), Err(Error::Usage(_))));
     }
 
     #[test]
-    fn producer_properties_should_parse_headers_key_and_null_marker() {
-        let cli = Cli::try_parse_from([
+    fn throttle_delay_should_pace_records_and_ignore_disabled_rates() {
+        let started = Instant::now();
+        let delay = throttle_delay(10.0, 20, started).expect("ahead of the rate");
+        assert!(delay > Duration::from_millis(1_900) && delay <= Duration::from_secs(2));
+        assert_eq!(throttle_delay(10.0, 0, started), None);
+        for disabled in [-1.0, 0.0, f64::INFINITY, f64::NAN] {
+            assert_eq!(throttle_delay(disabled, 20, started), None, "{disabled}");
+        }
+    }
+
+    #[test]
+    fn mirror_rewrite_should_map_topics_partitions_and_headers() {
+        let Command::Mirror(args) = Cli::try_parse_from([
             "kafka",
             "--bootstrap-server",
-            "localhost:9092",
-            "produce",
+            "source:9092",
+            "mirror",
             "--topic",
-            "events",
-            "--property",
-            "parse.headers=true",
-            "--property",
-            "headers.delimiter=|",
-            "--property",
-            "parse.key=true",
-            "--property",
-            "key.separator=|",
-            "--property",
-            "null.marker=NULL",
+            "orders",
+            "--topic",
+            "payments",
+            "--target-bootstrap-server",
+            "target:9092",
+            "--rename-topic",
+            "orders=orders-copy",
+            "--partition-map",
+            "2=0",
+            "--drop-header",
+            "secret",
+            "--rename-header",
+            "trace=trace-id",
+            "--set-header",
+            "origin=prod",
+            "--from-time",
+            "2024-01-01T00:00:00Z",
         ])
-        .expect("producer properties");
-        let Command::Produce(args) = cli.command else {
-            panic!("expected produce command");
+        .expect("mirror arguments")
+        .command
+        else {
+            panic!("expected mirror command");
         };
-        let options = line_reader_options(&args).expect("reader options");
-
-        let input = producer_input("trace:abc,empty:NULL|order-1|created", false, &options)
-            .expect("line input");
+        let rewrite = MirrorRewrite::new(&args).expect("rewrite");
 
+        assert_eq!(rewrite.topic("orders"), "orders-copy");
+        assert_eq!(rewrite.topic("payments"), "payments");
+        assert_eq!(rewrite.partition(2), Some(0));
+        assert_eq!(rewrite.partition(1), Some(1));
         assert_eq!(
-            input,
-            ProducerInput {
-                key: Some("order-1".into()),
-                value: Some("created".into()),
-                partition: None,
-                headers: vec![("trace".into(), Some("abc".into())), ("empty".into(), None)],
-            }
+            rewrite.headers(
+                [
+                    ("trace", Some(&b"abc"[..])),
+                    ("secret", Some(&b"x"[..])),
+                    ("origin", None),
+                    ("empty", None),
+                ]
+                .into_iter()
+            ),
+            vec![
+                ("trace-id", Some(&b"abc"[..])),
+                ("empty", None),
+                ("origin", Some(&b"prod"[..])),
+            ]
+        );
+        assert_eq!(
+            parse_time_bound("--from-time", args.from_time.as_deref().expect("from time"))
+                .expect("rfc 3339"),
+            1_704_067_200_000
+        );
+        assert_eq!(
+            parse_time_bound("--to-time", "1700").expect("millis"),
+            1_700
         );
+        assert!(parse_time_bound("--to-time", "yesterday").is_err());
     }
 
     #[test]
-    fn consumer_properties_should_configure_default_formatter() {
+    fn binary_encodings_should_round_trip_through_json_records() {
+        let directory = tempfile::tempdir().expect("temp dir");
+        let payloads = directory.path().join("payloads");
+        let bytes = [0_u8, 0xff, b'k', 0x80];
         let cli = Cli::try_parse_from([
             "kafka",
             "--bootstrap-server",
@@ -17380,70 +22708,117 @@ mod tests {
             "consume",
             "--topic",
             "events",
-            "--property",
-            "print.partition=true",
-            "--property",
-            "print.headers=true",
-            "--property",
-            "print.key=true",
-            "--property",
-            "key.separator=|",
-            "--property",
-            "null.literal=NULL",
+            "--json",
+            "--key-encoding",
+            "hex",
+            "--value-encoding",
+            "file",
+            "--header-encoding",
+            "base64",
+            "--payload-dir",
+            payloads.to_str().expect("utf-8 path"),
         ])
-        .expect("consumer properties");
+        .expect("consumer arguments");
         let Command::Consume(args) = cli.command else {
             panic!("expected consume command");
         };
-
-        let options = message_formatter_options(&args).expect("formatter options");
-
-        assert!(options.print_partition && options.print_headers && options.print_key);
-        assert_eq!(options.key_separator, b"|");
-        assert_eq!(options.null_literal, b"NULL");
-    }
-
-    #[test]
-    fn string_deserializer_should_follow_kafka_utf8_display_semantics() {
-        assert_eq!(
-            native_deserializer(
-                Some("org.apache.kafka.common.serialization.StringDeserializer"),
-                "value"
+        let formatter = message_formatter_options(&*args).expect("formatter options");
+        let encodings = JsonEncodings::new(
+            args.key_encoding,
+            args.value_encoding,
+            args.header_encoding,
+            args.payload_dir,
+        )
+        .expect("encodings");
+        let position = RecordPosition {
+            topic: "events",
+            partition: 2,
+            offset: 7,
+            timestamp: None,
+        };
+        let record = encodings
+            .record(
+                &formatter,
+                &position,
+                Some(&bytes),
+                Some(&bytes),
+                &[("trace", Some(&bytes)), ("empty", None)],
             )
-            .expect("string deserializer"),
-            NativeDeserializer::Utf8String
-        );
-        assert_eq!(
-            deserialize_for_display(
-                Some(b"valid UTF-8"),
-                NativeDeserializer::Utf8String,
-                b"null"
-            ),
-            b"valid UTF-8"
-        );
-        assert_eq!(
-            String::from_utf8(deserialize_for_display(
-                Some(&[b'a', 0xff, b'b']),
-                NativeDeserializer::Utf8String,
-                b"null"
-            ))
-            .expect("lossy UTF-8 result"),
-            "a\u{fffd}b"
-        );
+            .expect("record");
+        let json = serde_json::to_value(&record).expect("json");
+        let value_path = payloads.join("events-2-7.value");
+
+        assert_eq!(json["key"], "00ff6b80");
+        assert_eq!(json["value"], value_path.display().to_string());
+        assert_eq!(json["headers"]["trace"], "AP9rgA==");
+        assert!(json["headers"]["empty"].is_null());
+        for (text, encoding) in [
+            ("00ff6b80", DataEncoding::Hex),
+            ("AP9rgA==", DataEncoding::Base64),
+            (value_path.to_str().expect("utf-8 path"), DataEncoding::File),
+        ] {
+            assert_eq!(decode_text(text, encoding).expect("decodes"), bytes);
+        }
+        assert!(decode_text("abc", DataEncoding::Hex).is_err());
         assert!(matches!(
-            native_deserializer(Some("example.CustomDeserializer"), "value"),
-            Err(Error::Unsupported(message)) if message.contains("example.CustomDeserializer")
+            JsonEncodings::new(DataEncoding::Utf8, DataEncoding::File, DataEncoding::Utf8, None),
+            Err(Error::Usage(message)) if message.contains("--payload-dir")
         ));
     }
 
     #[test]
-    fn formatted_leader_epoch_should_render_present_epoch() {
-        assert_eq!(formatted_leader_epoch(Some(12)), b"Epoch:12");
-    }
+    fn confluent_deserializers_should_require_a_schema_source() {
+        for class in [
+            "io.confluent.kafka.serializers.KafkaAvroDeserializer",
+            "io.confluent.kafka.serializers.protobuf.KafkaProtobufDeserializer",
+            "io.confluent.kafka.serializers.json.KafkaJsonSchemaDeserializer",
+        ] {
+            assert_eq!(
+                native_deserializer(Some(class), "value").expect("schema deserializer"),
+                NativeDeserializer::SchemaRegistry
+            );
+        }
+        let parse = |extra: &[&str]| {
+            let mut arguments = vec![
+                "kafka",
+                "--bootstrap-server",
+                "localhost:9092",
+                "consume",
+                "--topic",
+                "events",
+                "--value-deserializer",
+                "io.confluent.kafka.serializers.KafkaAvroDeserializer",
+            ];
+            arguments.extend_from_slice(extra);
+            let Command::Consume(args) = Cli::try_parse_from(arguments)
+                .expect("consumer arguments")
+                .command
+            else {
+                panic!("expected consume command");
+            };
+            args
+        };
 
-    #[test]
-    fn formatted_leader_epoch_should_render_missing_epoch() {
-        assert_eq!(formatted_leader_epoch(None), b"Epoch:NOT_PRESENT");
+        assert!(matches!(
+            message_formatter_options(&*parse(&[])),
+            Err(Error::Usage(message)) if message.contains("--schema-registry-url")
+        ));
+        let options = message_formatter_options(&*parse(&[
+            "--formatter-property",
+            "schema.registry.url=http://localhost:8081",
+        ]))
+        .expect("registry from formatter property");
+        assert!(options.schemas.is_some());
+        assert_eq!(options.key_deserializer, NativeDeserializer::Raw);
+        assert!(matches!(
+            message_formatter_options(&*parse(&[
+                "--schema-dir",
+                ".",
+                "--formatter-property",
+                "headers.deserializer=io.confluent.kafka.serializers.KafkaAvroDeserializer",
+            ])),
+            Err(Error::Unsupported(_))
+        ));
     }
 
     #[test]
@@ -17587,7 +22962,7 @@ mod tests {
         let Command::Consume(args) = cli.command else {
             panic!("expected consume command");
         };
-        let options = message_formatter_options(&args).expect("formatter options");
+        let options = message_formatter_options(&*args).expect("formatter options");
         assert_eq!(options.key_deserializer, NativeDeserializer::Utf8String);
         assert_eq!(options.value_deserializer, NativeDeserializer::Utf8String);
     }
@@ -17624,7 +22999,7 @@ mod tests {
         let Command::Consume(consumer) = consumer.command else {
             panic!("expected consume command");
         };
-        message_formatter_options(&consumer).expect("default formatter");
+        message_formatter_options(&*consumer).expect("default formatter");
     }
 
     #[test]
@@ -17663,7 +23038,7 @@ mod tests {
             panic!("expected consume command");
         };
         assert!(matches!(
-            message_formatter_options(&consumer),
+            message_formatter_options(&*consumer),
             Err(Error::Unsupported(message)) if message.contains("example.CustomFormatter")
         ));
     }
@@ -17684,7 +23059,7 @@ mod tests {
         };
         let mut config = rdkafka::ClientConfig::new();
 
-        configure_consumer(&mut config, &args).expect("consumer configuration");
+        configure_consumer(&mut config, &*args).expect("consumer configuration");
 
         assert!(
             config
@@ -17717,7 +23092,7 @@ mod tests {
         };
         let mut config = rdkafka::ClientConfig::new();
         assert!(matches!(
-            configure_consumer(&mut config, &args),
+            configure_consumer(&mut config, &*args),
             Err(Error::Usage(message)) if message.contains("must match")
         ));
 
@@ -17738,7 +23113,7 @@ mod tests {
         };
         let mut config = rdkafka::ClientConfig::new();
         assert!(matches!(
-            configure_consumer(&mut config, &args),
+            configure_consumer(&mut config, &*args),
             Err(Error::Usage(message)) if message.contains("auto.offset.reset")
         ));
     }
@@ -1,17 +1,28 @@
-//! Kafka client property loading and normalization.
+//! Kafka client property loading and translation for both client transports.
 
 use std::{collections::HashMap, fs::File, path::Path};
 
 use krafka::{
     admin::{AdminClient, AdminClientBuilder},
-    auth::{AuthConfig, TlsConfig},
+    auth::AuthConfig,
 };
 use rdkafka::ClientConfig;
 
-use crate::error::{Error, Result};
+use crate::{
+    config_provider,
+    error::{Error, Result},
+    security::{PrivateFiles, SecurityConfig},
+};
 
-/// Loads a Kafka properties file.
+/// Loads a Kafka properties file, resolving `${provider:[path:]key}` secret references.
 pub fn load_properties(path: &Path) -> Result<HashMap<String, String>> {
+    let mut values = read_properties(path)?;
+    config_provider::resolve(&mut values)?;
+    Ok(values)
+}
+
+/// Reads a Kafka properties file verbatim, leaving secret references unresolved.
+pub(crate) fn read_properties(path: &Path) -> Result<HashMap<String, String>> {
     let file = File::open(path)?;
     java_properties::read(file).map_err(|error| Error::Config(error.to_string()))
 }
@@ -25,12 +36,19 @@ pub fn client_config(bootstrap: &str, path: Option<&Path>) -> Result<ClientConfi
     values.insert("bootstrap.servers".into(), bootstrap.into());
 
     let mut config = ClientConfig::new();
-    for (key, value) in values {
-        config.set(normalize_key(&key), value);
+    for (key, value) in rdkafka_properties(&values)? {
+        config.set(key, value);
     }
     Ok(config)
 }
 
+/// Translates Java-compatible properties, including security settings, for librdkafka.
+pub(crate) fn rdkafka_properties(
+    values: &HashMap<String, String>,
+) -> Result<Vec<(String, String)>> {
+    SecurityConfig::parse(values)?.rdkafka_properties()
+}
+
 /// Builds the pure-Rust admin client used for APIs absent from librdkafka.
 pub async fn protocol_admin(
     bootstrap: &str,
@@ -47,90 +65,21 @@ pub async fn protocol_admin(
         .client_id("kafka-cli-admin")
         .connect_timeout(connect_timeout)
         .request_timeout(timeout);
-    let auth = protocol_auth(&values)?;
+    let mut private = PrivateFiles::default();
+    let auth = protocol_auth(&values, &mut private)?;
     if let Some(auth) = auth {
         builder = builder.auth(auth);
     }
     Ok(builder.build().await?)
 }
 
-pub(crate) fn protocol_auth(values: &HashMap<String, String>) -> Result<Option<AuthConfig>> {
-    let security = values
-        .get("security.protocol")
-        .map_or("PLAINTEXT", String::as_str)
-        .to_ascii_uppercase();
-    let tls = || {
-        let mut tls = TlsConfig::new().with_native_roots();
-        if let Some(ca) = values
-            .get("ssl.ca.location")
-            .or_else(|| values.get("ssl.truststore.location"))
-        {
-            tls = tls.with_ca_cert(ca);
-        }
-        if let (Some(cert), Some(key)) = (
-            values.get("ssl.certificate.location"),
-            values.get("ssl.key.location"),
-        ) {
-            tls = tls.with_client_cert(cert, key);
-        }
-        tls
-    };
-    let credentials = || -> Result<(&str, &str)> {
-        let username = values
-            .get("sasl.username")
-            .map(String::as_str)
-            .ok_or_else(|| Error::Config("sasl.username is required".into()))?;
-        let password = values
-            .get("sasl.password")
-            .map(String::as_str)
-            .ok_or_else(|| Error::Config("sasl.password is required".into()))?;
-        Ok((username, password))
-    };
-    let mechanism = values
-        .get("sasl.mechanism")
-        .map_or("PLAIN", String::as_str)
-        .to_ascii_uppercase();
-    let auth = match (security.as_str(), mechanism.as_str()) {
-        ("PLAINTEXT", _) => None,
-        ("SSL", _) => Some(AuthConfig::ssl(tls())),
-        ("SASL_PLAINTEXT", "PLAIN") => {
-            let (username, password) = credentials()?;
-            Some(AuthConfig::sasl_plain(username, password)?)
-        }
-        ("SASL_PLAINTEXT", "SCRAM-SHA-256") => {
-            let (username, password) = credentials()?;
-            Some(AuthConfig::sasl_scram_sha256(username, password))
-        }
-        ("SASL_PLAINTEXT", "SCRAM-SHA-512") => {
-            let (username, password) = credentials()?;
-            Some(AuthConfig::sasl_scram_sha512(username, password))
-        }
-        ("SASL_SSL", "PLAIN") => {
-            let (username, password) = credentials()?;
-            Some(AuthConfig::sasl_plain_ssl(username, password, tls())?)
-        }
-        ("SASL_SSL", mechanism) => {
-            return Err(Error::Config(format!(
-                "the pure-Rust admin transport does not yet support {mechanism} over TLS"
-            )));
-        }
-        (protocol, _) => {
-            return Err(Error::Config(format!(
-                "unsupported security.protocol: {protocol}"
-            )));
-        }
-    };
-    Ok(auth)
-}
-
-pub(crate) fn normalize_key(key: &str) -> &str {
-    match key {
-        "ssl.truststore.location" => "ssl.ca.location",
-        "ssl.keystore.location" => "ssl.certificate.location",
-        "ssl.key.location" => "ssl.key.location",
-        "ssl.key.password" => "ssl.key.password",
-        _ => key,
-    }
+/// Builds the admin transport authentication from the same security model as librdkafka.
+/// Converted TLS files live in `private`, which must be kept until the client is built.
+pub(crate) fn protocol_auth(
+    values: &HashMap<String, String>,
+    private: &mut PrivateFiles,
+) -> Result<Option<AuthConfig>> {
+    SecurityConfig::parse(values)?.admin_auth(private)
 }
 
 #[cfg(test)]
@@ -151,8 +100,19 @@ mod tests {
     }
 
     #[test]
-    fn normalize_key_should_map_java_truststore() {
-        assert_eq!(normalize_key("ssl.truststore.location"), "ssl.ca.location");
+    fn rdkafka_properties_should_map_java_truststore() {
+        let values = HashMap::from([
+            ("security.protocol".into(), "SSL".into()),
+            ("ssl.truststore.location".into(), "/etc/kafka/ca.pem".into()),
+            ("ssl.truststore.type".into(), "PEM".into()),
+        ]);
+        let properties = rdkafka_properties(&values).expect("rdkafka properties");
+        assert!(properties.contains(&("ssl.ca.location".into(), "/etc/kafka/ca.pem".into())));
+        assert!(
+            !properties
+                .iter()
+                .any(|(key, _)| key.starts_with("ssl.truststore"))
+        );
     }
 
     #[test]
@@ -163,17 +123,50 @@ mod tests {
             ("sasl.username".into(), "alice".into()),
             ("sasl.password".into(), "secret".into()),
         ]);
-        assert!(protocol_auth(&values).is_ok());
+        assert!(protocol_auth(&values, &mut PrivateFiles::default()).is_ok());
     }
 
     #[test]
-    fn normalize_key_should_map_keystore_and_pass_through_unknown() {
-        assert_eq!(
-            normalize_key("ssl.keystore.location"),
-            "ssl.certificate.location"
-        );
-        assert_eq!(normalize_key("ssl.key.password"), "ssl.key.password");
-        assert_eq!(normalize_key("bootstrap.servers"), "bootstrap.servers");
+    fn protocol_auth_should_build_scram_and_oauthbearer_over_tls() {
+        for mechanism in ["SCRAM-SHA-256", "SCRAM-SHA-512"] {
+            let values = HashMap::from([
+                ("security.protocol".into(), "SASL_SSL".into()),
+                ("sasl.mechanism".into(), mechanism.into()),
+                ("sasl.username".into(), "alice".into()),
+                ("sasl.password".into(), "secret".into()),
+            ]);
+            let auth = protocol_auth(&values, &mut PrivateFiles::default())
+                .expect("scram over tls")
+                .expect("auth config");
+            assert!(auth.requires_tls() && auth.scram_credentials().is_some());
+        }
+        let values = HashMap::from([
+            ("security.protocol".into(), "SASL_SSL".into()),
+            ("sasl.mechanism".into(), "OAUTHBEARER".into()),
+            ("sasl.oauthbearer.config".into(), "principal=alice".into()),
+        ]);
+        let auth = protocol_auth(&values, &mut PrivateFiles::default())
+            .expect("oauthbearer over tls")
+            .expect("auth config");
+        assert!(auth.requires_tls() && auth.oauthbearer_token().is_some());
+    }
+
+    #[test]
+    fn rdkafka_properties_should_map_pem_keystore_and_pass_through_unknown() {
+        let mut certificate = tempfile::NamedTempFile::new().expect("certificate file");
+        writeln!(certificate, "-----BEGIN CERTIFICATE-----").expect("write");
+        writeln!(certificate, "-----END CERTIFICATE-----").expect("write");
+        let location = certificate.path().display().to_string();
+        let values = HashMap::from([
+            ("security.protocol".into(), "SSL".into()),
+            ("ssl.keystore.location".into(), location.clone()),
+            ("ssl.key.location".into(), "/etc/kafka/client.key".into()),
+            ("bootstrap.servers".into(), "broker:9092".into()),
+        ]);
+        let properties = rdkafka_properties(&values).expect("rdkafka properties");
+        assert!(properties.contains(&("ssl.certificate.location".into(), location)));
+        assert!(properties.contains(&("ssl.key.location".into(), "/etc/kafka/client.key".into())));
+        assert!(properties.contains(&("bootstrap.servers".into(), "broker:9092".into())));
     }
 
     #[test]
@@ -184,7 +177,8 @@ mod tests {
             ("sasl.username".into(), "alice".into()),
             // missing password
         ]);
-        let err = protocol_auth(&values).expect_err("password required");
+        let err =
+            protocol_auth(&values, &mut PrivateFiles::default()).expect_err("password required");
         assert!(
             err.to_string().contains("password")
                 || err.to_string().contains("sasl")
@@ -0,0 +1,286 @@
+//! Secret references in properties files, using Kafka's config provider syntax.
+//!
+//! As in Java clients, `config.providers` declares the providers a file may use, and a value
+//! may then embed `${provider:[path:]key}` references to them, resolved before either client
+//! transport sees the properties:
+//!
+//! ```properties
+//! config.providers=env,file,exec
+//! sasl.username=${env:KAFKA_USERNAME}
+//! sasl.password=${file:/run/secrets/kafka.properties:password}
+//! ssl.key.password=${exec:/usr/local/bin/vault-read:kafka/key-password}
+//! ```
+//!
+//! - `env`, or any alias whose `config.providers.<alias>.class` is Kafka's
+//!   `EnvVarConfigProvider`, reads an environment variable.
+//! - `file`, or an alias of Kafka's `FileConfigProvider`, reads `key` from another properties
+//!   file.
+//! - `exec` runs `path` with `key` as its only argument, or `key` with none, and uses its
+//!   standard output without the trailing newline. It has no Java equivalent and is only
+//!   available when declared by that name.
+//!
+//! References to undeclared providers are left untouched, like any other `${` text. The
+//! `config.providers` declarations themselves are dropped because librdkafka rejects them.
+//! Each reference is resolved once per process.
+
+use std::{
+    collections::HashMap,
+    path::Path,
+    process::{Command, Stdio},
+    sync::{Mutex, PoisonError},
+};
+
+use crate::error::{Error, Result};
+
+/// Resolved references, so `exec` programs run once however many clients a command builds.
+static RESOLVED: Mutex<Option<HashMap<String, String>>> = Mutex::new(None);
+
+/// A built-in config provider.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Provider {
+    Env,
+    File,
+    Exec,
+}
+
+/// Replaces references to declared providers in `values` and drops the declarations.
+pub fn resolve(values: &mut HashMap<String, String>) -> Result<()> {
+    let providers = declared_providers(values)?;
+    values.retain(|key, _| key != "config.providers" && !key.starts_with("config.providers."));
+    if providers.is_empty() {
+        return Ok(());
+    }
+    for (key, value) in values.iter_mut() {
+        if value.contains("${") {
+            *value = resolve_value(value, &providers).map_err(|error| {
+                Error::Config(format!("cannot resolve a reference in {key}: {error}"))
+            })?;
+        }
+    }
+    Ok(())
+}
+
+/// Maps each alias in `config.providers` to the built-in provider implementing it.
+fn declared_providers(values: &HashMap<String, String>) -> Result<HashMap<String, Provider>> {
+    let Some(aliases) = values.get("config.providers") else {
+        return Ok(HashMap::new());
+    };
+    aliases
+        .split(',')
+        .map(str::trim)
+        .filter(|alias| !alias.is_empty())
+        .map(|alias| {
+            let class = values.get(&format!("config.providers.{alias}.class"));
+            let provider = match (alias, class.map(|class| class.rsplit('.').next())) {
+                (_, Some(Some("EnvVarConfigProvider"))) | ("env", None) => Provider::Env,
+                (_, Some(Some("FileConfigProvider"))) | ("file", None) => Provider::File,
+                ("exec", None) => Provider::Exec,
+                (_, Some(_)) => {
+                    return Err(Error::Config(format!(
+                        "unsupported config provider class for {alias}: {}",
+                        class.map_or("", String::as_str)
+                    )));
+                }
+                (_, None) => {
+                    return Err(Error::Config(format!(
+                        "config provider {alias} needs config.providers.{alias}.class"
+                    )));
+                }
+            };
+            Ok((alias.to_owned(), provider))
+        })
+        .collect()
+}
+
+fn resolve_value(
+    value: &str,
+    providers: &HashMap<String, Provider>,
+) -> std::result::Result<String, String> {
+    let mut resolved = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find("${") {
+        resolved.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let Some(end) = rest.find('}') else {
+            break;
+        };
+        let reference = &rest[2..end];
+        let provider = reference
+            .split_once(':')
+            .and_then(|(alias, location)| Some((*providers.get(alias)?, location)));
+        match provider {
+            Some((provider, location)) => resolved.push_str(&lookup(provider, location)?),
+            None => resolved.push_str(&rest[..=end]),
+        }
+        rest = &rest[end + 1..];
+    }
+    resolved.push_str(rest);
+    Ok(resolved)
+}
+
+/// Resolves the `[path:]key` of one reference, consulting the process cache first.
+fn lookup(provider: Provider, location: &str) -> std::result::Result<String, String> {
+    let cache_key = format!("{provider:?}:{location}");
+    let cached = RESOLVED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .as_ref()
+        .and_then(|resolved| resolved.get(&cache_key).cloned());
+    if let Some(value) = cached {
+        return Ok(value);
+    }
+    let (path, key) = match location.split_once(':') {
+        Some((path, key)) => (Some(path), key),
+        None => (None, location),
+    };
+    let value = match provider {
+        Provider::Env => {
+            std::env::var(key).map_err(|_| format!("environment variable {key} is not set"))?
+        }
+        Provider::File => {
+            let path = path.ok_or_else(|| "file references need a path and a key".to_owned())?;
+            file_value(Path::new(path), key)?
+        }
+        Provider::Exec => match path {
+            Some(program) => exec_value(program, Some(key))?,
+            None => exec_value(key, None)?,
+        },
+    };
+    RESOLVED
+        .lock()
+        .unwrap_or_else(PoisonError::into_inner)
+        .get_or_insert_with(HashMap::new)
+        .insert(cache_key, value.clone());
+    Ok(value)
+}
+
+fn file_value(path: &Path, key: &str) -> std::result::Result<String, String> {
+    let file = std::fs::File::open(path)
+        .map_err(|error| format!("cannot read {}: {error}", path.display()))?;
+    let mut values = java_properties::read(file)
+        .map_err(|error| format!("cannot parse {}: {error}", path.display()))?;
+    values
+        .remove(key)
+        .ok_or_else(|| format!("{} has no key {key}", path.display()))
+}
+
+fn exec_value(program: &str, argument: Option<&str>) -> std::result::Result<String, String> {
+    let output = Command::new(program)
+        .args(argument)
+        .stdin(Stdio::null())
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|error| format!("cannot run {program}: {error}"))?;
+    if !output.status.success() {
+        return Err(format!("{program} failed with {}", output.status));
+    }
+    let value = String::from_utf8(output.stdout)
+        .map_err(|_| format!("{program} printed a value that is not UTF-8"))?;
+    Ok(value.trim_end_matches(['\r', '\n']).to_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use super::*;
+
+    fn properties(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(key, value)| ((*key).to_owned(), (*value).to_owned()))
+            .collect()
+    }
+
+    #[test]
+    fn references_should_resolve_declared_providers_inside_larger_values() {
+        let mut secrets = tempfile::NamedTempFile::new().expect("secrets file");
+        writeln!(secrets, "kafka.password=s3cret").expect("write secrets");
+        let path = secrets.path().display().to_string();
+        let home = std::env::var("HOME").expect("HOME is set");
+        let jaas = format!(
+            "PlainLoginModule required username=\"alice\" password=\"${{secrets:{path}:kafka.password}}\";"
+        );
+        let mut values = properties(&[
+            ("sasl.jaas.config", &jaas),
+            ("ssl.ca.location", "${env:HOME}/ca.pem"),
+            ("config.providers", "secrets, env"),
+            (
+                "config.providers.secrets.class",
+                "org.apache.kafka.common.config.provider.FileConfigProvider",
+            ),
+        ]);
+        resolve(&mut values).expect("resolve references");
+        assert_eq!(
+            values["sasl.jaas.config"],
+            "PlainLoginModule required username=\"alice\" password=\"s3cret\";"
+        );
+        assert_eq!(values["ssl.ca.location"], format!("{home}/ca.pem"));
+        assert_eq!(values.len(), 2);
+    }
+
+    #[test]
+    fn references_should_leave_undeclared_providers_and_plain_text_untouched() {
+        let literal = [
+            ("sasl.password", "pa${ss"),
+            ("client.id", "${env:HOME}"),
+            ("ssl.key.password", "${exec:/bin/echo:hunter2}"),
+        ];
+        let mut values = properties(&literal);
+        resolve(&mut values).expect("nothing is declared");
+        assert_eq!(values, properties(&literal));
+
+        let mut values = properties(&literal);
+        values.insert("config.providers".into(), "env".into());
+        resolve(&mut values).expect("exec is not declared");
+        assert_eq!(values["sasl.password"], "pa${ss");
+        assert_ne!(values["client.id"], "${env:HOME}");
+        assert_eq!(values["ssl.key.password"], "${exec:/bin/echo:hunter2}");
+    }
+
+    #[test]
+    fn references_should_report_the_property_without_its_value() {
+        for (reference, message) in [
+            ("${env:KAFKA_CLI_TEST_UNSET_VARIABLE}", "is not set"),
+            (
+                "${file:/no/such/file:password}",
+                "cannot read /no/such/file",
+            ),
+        ] {
+            let mut values = properties(&[
+                ("sasl.password", reference),
+                ("config.providers", "env,file"),
+            ]);
+            let error = resolve(&mut values).expect_err(reference).to_string();
+            assert!(error.contains("sasl.password"), "{error}");
+            assert!(error.contains(message), "{error}");
+        }
+        let mut values = properties(&[
+            ("config.providers", "vault"),
+            (
+                "config.providers.vault.class",
+                "com.example.VaultConfigProvider",
+            ),
+        ]);
+        let error = resolve(&mut values).expect_err("unknown class").to_string();
+        assert!(error.contains("VaultConfigProvider"), "{error}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn exec_references_should_use_program_output_without_trailing_newline() {
+        let mut values = properties(&[
+            ("sasl.password", "${exec:/bin/echo:hunter2}"),
+            ("ssl.key.password", "${exec:/bin/false}"),
+            ("config.providers", "exec"),
+        ]);
+        let error = resolve(&mut values).expect_err("false fails").to_string();
+        assert!(error.contains("ssl.key.password"), "{error}");
+        let mut values = properties(&[
+            ("sasl.password", "${exec:/bin/echo:hunter2}"),
+            ("config.providers", "exec"),
+        ]);
+        resolve(&mut values).expect("echo succeeds");
+        assert_eq!(values["sasl.password"], "hunter2");
+    }
+}
@@ -0,0 +1,388 @@
+//! Named connection profiles read from a TOML contexts file.
+//!
+//! The file lives at `$XDG_CONFIG_HOME/kafka-cli/contexts.toml` (or
+//! `~/.config/kafka-cli/contexts.toml`), overridable with `KAFKA_CLI_CONTEXTS_FILE`:
+//!
+//! ```toml
+//! current = "dev"
+//!
+//! [contexts.dev]
+//! bootstrap-server = "localhost:9092"
+//! output = "json"
+//!
+//! [contexts.prod]
+//! bootstrap-server = "kafka-1.prod:9093"
+//! bootstrap-controller = "controller-1.prod:9094"
+//! command-config = "prod.properties"
+//!
+//! [contexts.prod.properties]
+//! "client.id" = "ops-cli"
+//! ```
+//!
+//! The selected context (`--context`, else `current`) only fills in what the invocation left
+//! unset: explicit flags and their environment variables always win. Commands that accept
+//! `--bootstrap-controller` use the context's controller address only when it has no
+//! `bootstrap-server`.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+use toml_edit::DocumentMut;
+
+use crate::{
+    cli::Cli,
+    config,
+    error::{Error, Result},
+    output::OutputFormat,
+    security::PrivateFiles,
+};
+
+/// Overrides the contexts file location.
+pub const CONTEXTS_FILE_ENV: &str = "KAFKA_CLI_CONTEXTS_FILE";
+
+/// A named cluster connection profile.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Context {
+    pub bootstrap_server: Option<String>,
+    pub bootstrap_controller: Option<String>,
+    pub command_config: Option<PathBuf>,
+    #[serde(default)]
+    pub properties: BTreeMap<String, PropertyValue>,
+    pub output: Option<String>,
+}
+
+/// An inline client property; TOML numbers and booleans are accepted for convenience.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum PropertyValue {
+    Text(String),
+    Integer(i64),
+    Float(f64),
+    Boolean(bool),
+}
+
+impl std::fmt::Display for PropertyValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Text(value) => f.write_str(value),
+            Self::Integer(value) => write!(f, "{value}"),
+            Self::Float(value) => write!(f, "{value}"),
+            Self::Boolean(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawContextFile {
+    current: Option<String>,
+    #[serde(default)]
+    contexts: BTreeMap<String, Context>,
+}
+
+/// The parsed contexts file.
+#[derive(Debug)]
+pub struct ContextFile {
+    pub path: PathBuf,
+    pub current: Option<String>,
+    pub contexts: BTreeMap<String, Context>,
+}
+
+impl ContextFile {
+    /// Loads the contexts file from its default location; a missing file has no contexts.
+    pub fn load() -> Result<Self> {
+        Self::load_from(contexts_path()?)
+    }
+
+    /// Loads a contexts file; a missing file has no contexts.
+    pub fn load_from(path: PathBuf) -> Result<Self> {
+        let file = match fs::read_to_string(&path) {
+            Ok(text) => toml_edit::de::from_str::<RawContextFile>(&text).map_err(|error| {
+                Error::Config(format!("invalid contexts file {}: {error}", path.display()))
+            })?,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => RawContextFile::default(),
+            Err(error) => return Err(error.into()),
+        };
+        Ok(Self {
+            path,
+            current: file.current,
+            contexts: file.contexts,
+        })
+    }
+
+    /// Returns a context by name.
+    pub fn get(&self, name: &str) -> Result<&Context> {
+        self.contexts.get(name).ok_or_else(|| {
+            let known = self.contexts.keys().cloned().collect::<Vec<_>>();
+            Error::Usage(if known.is_empty() {
+                format!(
+                    "context {name} not found: {} defines no contexts",
+                    self.path.display()
+                )
+            } else {
+                format!(
+                    "context {name} not found; known contexts: {}",
+                    known.join(", ")
+                )
+            })
+        })
+    }
+
+    /// Records `name` as the current context, preserving the rest of the file.
+    pub fn use_context(&self, name: &str) -> Result<()> {
+        self.get(name)?;
+        let text = fs::read_to_string(&self.path)?;
+        let mut document = text.parse::<DocumentMut>().map_err(|error| {
+            Error::Config(format!(
+                "invalid contexts file {}: {error}",
+                self.path.display()
+            ))
+        })?;
+        document["current"] = toml_edit::value(name);
+        fs::write(&self.path, document.to_string())?;
+        Ok(())
+    }
+
+    /// Resolves a configured path: `~/` is the home directory and relative paths are relative
+    /// to the contexts file.
+    #[must_use]
+    pub fn resolve(&self, path: &Path) -> PathBuf {
+        if let Ok(relative) = path.strip_prefix("~")
+            && let Some(home) = std::env::var_os("HOME")
+        {
+            return PathBuf::from(home).join(relative);
+        }
+        match self.path.parent() {
+            Some(directory) if path.is_relative() => directory.join(path),
+            _ => path.to_owned(),
+        }
+    }
+}
+
+impl Context {
+    /// Parses the configured output encoding.
+    pub fn output_format(&self) -> Result<Option<OutputFormat>> {
+        self.output
+            .as_deref()
+            .map(|output| {
+                <OutputFormat as clap::ValueEnum>::from_str(output, true)
+                    .map_err(|_| Error::Config(format!("unsupported context output: {output}")))
+            })
+            .transpose()
+    }
+}
+
+/// Returns the contexts file location.
+pub fn contexts_path() -> Result<PathBuf> {
+    if let Some(path) = std::env::var_os(CONTEXTS_FILE_ENV).filter(|path| !path.is_empty()) {
+        return Ok(PathBuf::from(path));
+    }
+    let config_home = std::env::var_os("XDG_CONFIG_HOME")
+        .filter(|path| !path.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))
+        .ok_or_else(|| {
+            Error::Config(format!(
+                "cannot locate the contexts file; set {CONTEXTS_FILE_ENV}"
+            ))
+        })?;
+    Ok(config_home.join("kafka-cli").join("contexts.toml"))
+}
+
+/// Fills unset connection flags on `cli` from the selected context. The returned files hold
+/// merged context properties and must be kept until the command finishes.
+pub fn apply(cli: &mut Cli) -> Result<PrivateFiles> {
+    let mut private = PrivateFiles::default();
+    let path = match contexts_path() {
+        Ok(path) => path,
+        Err(_) if cli.context.is_none() => return Ok(private),
+        Err(error) => return Err(error),
+    };
+    let contexts = ContextFile::load_from(path)?;
+    let Some(name) = cli.context.clone().or_else(|| contexts.current.clone()) else {
+        return Ok(private);
+    };
+    let context = contexts.get(&name)?;
+    apply_context(cli, &contexts, context, &mut private)?;
+    Ok(private)
+}
+
+fn apply_context(
+    cli: &mut Cli,
+    contexts: &ContextFile,
+    context: &Context,
+    private: &mut PrivateFiles,
+) -> Result<()> {
+    let controller = cli.command.bootstrap_controller_mut();
+    let explicit_controller = controller.as_ref().is_some_and(|slot| slot.is_some());
+    if cli.bootstrap_server.is_none() && !explicit_controller {
+        match (
+            controller,
+            &context.bootstrap_server,
+            &context.bootstrap_controller,
+        ) {
+            (Some(slot), None, Some(address)) => *slot = Some(address.clone()),
+            _ => cli.bootstrap_server.clone_from(&context.bootstrap_server),
+        }
+    }
+    if cli.command_config.is_none() {
+        let file = context
+            .command_config
+            .as_deref()
+            .map(|path| contexts.resolve(path));
+        cli.command_config = if context.properties.is_empty() {
+            file
+        } else {
+            // Secret references stay unresolved so only the client resolves them.
+            let mut values = file
+                .as_deref()
+                .map(config::read_properties)
+                .transpose()?
+                .unwrap_or_default();
+            values.extend(
+                context
+                    .properties
+                    .iter()
+                    .map(|(key, value)| (key.clone(), value.to_string())),
+            );
+            Some(write_properties(&values, private)?)
+        };
+    }
+    if cli.output.is_none() {
+        cli.output = context.output_format()?;
+    }
+    Ok(())
+}
+
+/// Writes merged context properties where the command config path is expected.
+fn write_properties(
+    values: &HashMap<String, String>,
+    private: &mut PrivateFiles,
+) -> Result<PathBuf> {
+    let mut text = Vec::new();
+    java_properties::write(&mut text, values)
+        .map_err(|error| Error::Config(format!("cannot write context properties: {error}")))?;
+    private.write("context", "properties", &text)
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+
+    const CONTEXTS: &str = r#"# shared clusters
+current = "dev"
+
+[contexts.dev]
+bootstrap-server = "localhost:9092"
+output = "json"
+
+[contexts.prod]
+bootstrap-server = "kafka-1.prod:9093"
+bootstrap-controller = "controller-1.prod:9094"
+command-config = "prod.properties"
+
+[contexts.prod.properties]
+"client.id" = "ops-cli"
+"request.timeout.ms" = 5000
+"#;
+
+    fn contexts(directory: &Path) -> ContextFile {
+        let path = directory.join("contexts.toml");
+        fs::write(&path, CONTEXTS).expect("contexts file");
+        fs::write(
+            directory.join("prod.properties"),
+            "security.protocol=SASL_SSL\nclient.id=from-file\n",
+        )
+        .expect("properties file");
+        ContextFile::load_from(path).expect("load contexts")
+    }
+
+    fn parse(args: &[&str]) -> Cli {
+        Cli::try_parse_from(args).expect("parse cli")
+    }
+
+    #[test]
+    fn contexts_should_fill_only_unset_connection_flags() {
+        let directory = tempfile::tempdir().expect("temp dir");
+        let contexts = contexts(directory.path());
+        let dev = contexts.get("dev").expect("dev");
+
+        let mut cli = parse(&["kafka", "topics", "list"]);
+        apply_context(&mut cli, &contexts, dev, &mut PrivateFiles::default()).expect("apply dev");
+        assert_eq!(cli.bootstrap_server.as_deref(), Some("localhost:9092"));
+        assert_eq!(cli.output(), OutputFormat::Json);
+
+        let mut cli = parse(&[
+            "kafka",
+            "--bootstrap-server",
+            "override:9092",
+            "--output",
+            "table",
+            "topics",
+            "list",
+        ]);
+        apply_context(&mut cli, &contexts, dev, &mut PrivateFiles::default()).expect("apply dev");
+        assert_eq!(cli.bootstrap_server.as_deref(), Some("override:9092"));
+        assert_eq!(cli.output(), OutputFormat::Table);
+    }
+
+    #[test]
+    fn contexts_should_route_controllers_and_merge_inline_properties() {
+        let directory = tempfile::tempdir().expect("temp dir");
+        let contexts = contexts(directory.path());
+        let prod = contexts.get("prod").expect("prod");
+
+        let mut cli = parse(&["kafka", "reassign", "balance"]);
+        apply_context(&mut cli, &contexts, prod, &mut PrivateFiles::default()).expect("apply prod");
+        assert_eq!(cli.bootstrap_server.as_deref(), Some("kafka-1.prod:9093"));
+        assert_eq!(
+            cli.command.bootstrap_controller_mut().cloned().flatten(),
+            None
+        );
+
+        let controller_only = Context {
+            bootstrap_server: None,
+            ..prod.clone()
+        };
+        let mut cli = parse(&["kafka", "metadata-quorum", "describe", "--status"]);
+        let mut private = PrivateFiles::default();
+        apply_context(&mut cli, &contexts, &controller_only, &mut private).expect("apply prod");
+        assert_eq!(cli.bootstrap_server, None);
+        assert_eq!(
+            cli.command.bootstrap_controller_mut().cloned().flatten(),
+            Some("controller-1.prod:9094".into())
+        );
+        let properties =
+            config::load_properties(cli.command_config.as_deref().expect("merged config"))
+                .expect("merged properties");
+        assert_eq!(properties["security.protocol"], "SASL_SSL");
+        assert_eq!(properties["client.id"], "ops-cli");
+        assert_eq!(properties["request.timeout.ms"], "5000");
+
+        let mut cli = parse(&["kafka", "topics", "list"]);
+        apply_context(&mut cli, &contexts, prod, &mut PrivateFiles::default()).expect("apply prod");
+        assert_eq!(cli.bootstrap_server.as_deref(), Some("kafka-1.prod:9093"));
+
+        assert!(contexts.get("staging").is_err());
+    }
+
+    #[test]
+    fn use_context_should_preserve_the_rest_of_the_file() {
+        let directory = tempfile::tempdir().expect("temp dir");
+        let contexts = contexts(directory.path());
+        contexts.use_context("prod").expect("use prod");
+        let text = fs::read_to_string(&contexts.path).expect("contexts file");
+        assert!(text.starts_with("# shared clusters\ncurrent = \"prod\"\n"));
+        assert!(text.contains("\"request.timeout.ms\" = 5000"));
+        let reloaded = ContextFile::load_from(contexts.path.clone()).expect("reload");
+        assert_eq!(reloaded.current.as_deref(), Some("prod"));
+        assert!(contexts.use_context("staging").is_err());
+    }
+}
@@ -10,7 +10,10 @@ use std::{
 use bytes::Bytes;
 use krafka::protocol::{Compression, LazyRecordBatch};
 
-use crate::error::{Error, Result};
+use crate::{
+    error::{Error, Result},
+    internal_records, metadata_records,
+};
 
 const RECORD_INDENT: &str = "|";
 const DEFAULT_MAX_MESSAGE_SIZE: i32 = 5 * 1024 * 1024;
@@ -54,6 +57,101 @@ impl DumpLogOptions {
     const fn is_deep_iteration(&self) -> bool {
         self.deep_iteration || self.should_print_data_log()
     }
+
+    /// Selects the record parser with `DumpLogSegments` decoder precedence.
+    const fn record_parser(&self) -> Option<RecordParser> {
+        if !self.should_print_data_log() {
+            None
+        } else if self.offsets_decoder {
+            Some(RecordParser::Offsets)
+        } else if self.transaction_log_decoder {
+            Some(RecordParser::TransactionLog)
+        } else if self.cluster_metadata_decoder {
+            Some(RecordParser::ClusterMetadata)
+        } else if self.remote_log_metadata_decoder {
+            Some(RecordParser::RemoteLogMetadata)
+        } else if self.share_group_state_decoder {
+            Some(RecordParser::ShareGroupState)
+        } else {
+            Some(RecordParser::String)
+        }
+    }
+}
+
+/// Renders record keys and payloads when printing data log contents.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RecordParser {
+    /// UTF-8 key and value (`StringDecoder`).
+    String,
+    /// `__consumer_offsets` group metadata and offset commits.
+    Offsets,
+    /// `__transaction_state` transaction metadata.
+    TransactionLog,
+    /// `KRaft` `__cluster_metadata` records.
+    ClusterMetadata,
+    /// `__remote_log_metadata` tiered storage segment metadata.
+    RemoteLogMetadata,
+    /// `__share_group_state` share-partition snapshots and updates.
+    ShareGroupState,
+}
+
+impl RecordParser {
+    fn parse(
+        self,
+        offset: i64,
+        key: Option<&Bytes>,
+        value: Option<&Bytes>,
+    ) -> (Option<String>, Option<String>) {
+        match self {
+            Self::String => (
+                Some(decode_string_field(key)),
+                Some(decode_string_field(value)),
+            ),
+            Self::Offsets => internal_records::parse_offsets_record(
+                offset,
+                key.map(Bytes::as_ref),
+                value.map(Bytes::as_ref),
+            ),
+            Self::TransactionLog => internal_records::parse_transaction_log_record(
+                offset,
+                key.map(Bytes::as_ref),
+                value.map(Bytes::as_ref),
+            ),
+            Self::ClusterMetadata => {
+                metadata_records::parse_cluster_metadata_record(offset, value.map(Bytes::as_ref))
+            }
+            Self::RemoteLogMetadata => {
+                internal_records::parse_remote_log_metadata_record(offset, value.map(Bytes::as_ref))
+            }
+            Self::ShareGroupState => internal_records::parse_share_group_state_record(
+                offset,
+                key.map(Bytes::as_ref),
+                value.map(Bytes::as_ref),
+            ),
+        }
+    }
+
+    /// Prints ` key: .. payload: ..`, omitting whichever part the parser did not produce.
+    fn write_contents(
+        self,
+        offset: i64,
+        key: Option<&Bytes>,
+        value: Option<&Bytes>,
+        skip_record_metadata: bool,
+        out: &mut impl Write,
+    ) -> Result<()> {
+        let (key, payload) = self.parse(offset, key, value);
+        let prefix = format!("{RECORD_INDENT} ");
+        let mut separator = if skip_record_metadata { &prefix } else { " " };
+        if let Some(key) = key {
+            write!(out, "{separator}key: {key}")?;
+            separator = " ";
+        }
+        if let Some(payload) = payload {
+            write!(out, "{separator}payload: {payload}")?;
+        }
+        Ok(())
+    }
 }
 
 /// Dump Kafka log, index, and time-index segment files.
@@ -80,7 +178,7 @@ pub fn dump_log_segments(opts: &DumpLogOptions) -> Result<()> {
         match suffix {
             "log" => dump_log_file(
                 path,
-                opts.should_print_data_log(),
+                opts.record_parser(),
                 opts.is_deep_iteration(),
                 opts.skip_record_metadata,
                 opts.max_bytes,
@@ -94,7 +192,7 @@ pub fn dump_log_segments(opts: &DumpLogOptions) -> Result<()> {
                 } else {
                     dump_log_file(
                         path,
-                        opts.should_print_data_log(),
+                        opts.record_parser(),
                         opts.is_deep_iteration(),
                         opts.skip_record_metadata,
                         opts.max_bytes,
@@ -126,7 +224,7 @@ pub fn dump_log_segments(opts: &DumpLogOptions) -> Result<()> {
             // Producer-state snapshots are typically `<offset>.snapshot` (no epoch dash).
             "snapshot" if is_kraft_snapshot_name(name) => dump_log_file(
                 path,
-                opts.should_print_data_log(),
+                opts.record_parser(),
                 opts.is_deep_iteration(),
                 opts.skip_record_metadata,
                 opts.max_bytes,
@@ -166,25 +264,6 @@ fn validate_options(opts: &DumpLogOptions) -> Result<()> {
     if opts.max_bytes <= 0 {
         return Err(Error::Usage("--max-bytes must be positive".into()));
     }
-    for (flag, enabled) in [
-        ("--offsets-decoder", opts.offsets_decoder),
-        ("--transaction-log-decoder", opts.transaction_log_decoder),
-        ("--cluster-metadata-decoder", opts.cluster_metadata_decoder),
-        (
-            "--remote-log-metadata-decoder",
-            opts.remote_log_metadata_decoder,
-        ),
-        (
-            "--share-group-state-decoder",
-            opts.share_group_state_decoder,
-        ),
-    ] {
-        if enabled {
-            return Err(Error::Unsupported(format!(
-                "{flag} requires Kafka coordinator record codecs not available natively"
-            )));
-        }
-    }
     for (name, class) in [
         ("key", opts.key_decoder.as_deref()),
         ("value", opts.value_decoder.as_deref()),
@@ -209,7 +288,7 @@ fn is_string_decoder(class: &str) -> bool {
 
 fn dump_log_file(
     path: &Path,
-    print_contents: bool,
+    parser: Option<RecordParser>,
     deep: bool,
     skip_record_metadata: bool,
     max_bytes: i32,
@@ -225,6 +304,8 @@ fn dump_log_file(
             if let Some(base) = name.split('.').next().and_then(|s| s.parse::<i64>().ok()) {
                 writeln!(out, "Log starting offset: {base}")?;
             }
+        } else if let Some((end_offset, epoch)) = kraft_snapshot_id(name) {
+            writeln!(out, "Snapshot end offset: {end_offset}, epoch: {epoch}")?;
         } else if extension.eq_ignore_ascii_case("checkpoint") {
             writeln!(out, "Kafka binary bootstrap checkpoint (record batches)")?;
         } else if extension.eq_ignore_ascii_case("snapshot") {
@@ -266,7 +347,7 @@ fn dump_log_file(
             batch_bytes,
             position,
             deep,
-            print_contents,
+            parser,
             skip_record_metadata,
             path,
             &mut last_offset,
@@ -291,6 +372,47 @@ fn dump_log_file(
     Ok(())
 }
 
+/// Reads every data record value from a segment or snapshot file, skipping control batches.
+///
+/// Returns `(offset, value)` pairs in file order. A truncated trailing batch ends the scan
+/// the same way `dump_log_file` stops at invalid trailing bytes.
+pub fn read_record_values(path: &Path) -> Result<Vec<(i64, Option<Bytes>)>> {
+    let buffer = std::fs::read(path)?;
+    let mut values = Vec::new();
+    let mut cursor = 0_usize;
+    while buffer.len() - cursor >= 12 {
+        let remaining = &buffer[cursor..];
+        let batch_length = i32::from_be_bytes(remaining[8..12].try_into().unwrap_or([0; 4]));
+        if batch_length < 49 {
+            break;
+        }
+        let total = 12 + usize::try_from(batch_length).unwrap_or(usize::MAX);
+        if remaining.len() < total {
+            break;
+        }
+        let mut batch_bytes = &remaining[..total];
+        let lazy = LazyRecordBatch::decode(&mut batch_bytes).map_err(|error| {
+            Error::Config(format!(
+                "failed to decode record batch at position {cursor} in {}: {error}",
+                path.display()
+            ))
+        })?;
+        if !lazy.attributes.is_control_batch {
+            let records = lazy.decode_all().map_err(|error| {
+                Error::Config(format!("failed to decode records in batch: {error}"))
+            })?;
+            values.extend(records.into_iter().map(|record| {
+                (
+                    lazy.base_offset + i64::from(record.offset_delta),
+                    record.value,
+                )
+            }));
+        }
+        cursor += total;
+    }
+    Ok(values)
+}
+
 #[expect(
     clippy::too_many_arguments,
     reason = "batch dump mirrors DumpLogSegments print path"
@@ -299,7 +421,7 @@ fn decode_and_print_batch(
     batch_bytes: &[u8],
     position: i64,
     deep: bool,
-    print_contents: bool,
+    parser: Option<RecordParser>,
     skip_record_metadata: bool,
     path: &Path,
     last_offset: &mut i64,
@@ -395,15 +517,16 @@ fn decode_and_print_batch(
             }
         }
 
-        if print_contents && !is_control {
-            let prefix = if skip_record_metadata {
-                format!("{RECORD_INDENT} ")
-            } else {
-                " ".into()
-            };
-            let key = decode_string_field(record.key.as_ref());
-            let value = decode_string_field(record.value.as_ref());
-            write!(out, "{prefix}key: {key} payload: {value}")?;
+        if let Some(parser) = parser
+            && !is_control
+        {
+            parser.write_contents(
+                offset,
+                record.key.as_ref(),
+                record.value.as_ref(),
+                skip_record_metadata,
+                out,
+            )?;
         }
         writeln!(out)?;
     }
@@ -748,19 +871,25 @@ fn dump_non_binary_bootstrap_file(
     Ok(())
 }
 
-/// `KRaft` metadata snapshots use names like `00000000000000000000-0000000000.snapshot`.
+/// `KRaft` metadata snapshots use names like `00000000000000000000-0000000000.checkpoint`
+/// (older tooling wrote the same `<offset>-<epoch>` stem with a `.snapshot` suffix).
 fn is_kraft_snapshot_name(name: &str) -> bool {
-    let Some(stem) = name.strip_suffix(".snapshot") else {
-        return false;
-    };
+    kraft_snapshot_id(name).is_some()
+}
+
+/// Parses the snapshot end offset and epoch from a `KRaft` snapshot file name.
+#[must_use]
+pub fn kraft_snapshot_id(name: &str) -> Option<(i64, i32)> {
+    let stem = name
+        .strip_suffix(".checkpoint")
+        .or_else(|| name.strip_suffix(".snapshot"))?;
     // offset-epoch form has a single dash between two numeric components.
-    let Some((left, right)) = stem.split_once('-') else {
-        return false;
-    };
-    !left.is_empty()
-        && !right.is_empty()
-        && left.chars().all(|c| c.is_ascii_digit())
-        && right.chars().all(|c| c.is_ascii_digit())
+    let (left, right) = stem.split_once('-')?;
+    let is_numeric = |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit());
+    if !is_numeric(left) || !is_numeric(right) {
+        return None;
+    }
+    Some((left.parse().ok()?, right.parse().ok()?))
 }
 
 /// `AbortedTxn` v0: version(i16) + 4×i64 fields = 34 bytes (Kafka `TransactionIndex`).
@@ -931,7 +1060,7 @@ mod tests {
         let mut non_consecutive = BTreeMap::new();
         dump_log_file(
             &path,
-            true,
+            Some(RecordParser::String),
             true,
             false,
             i32::MAX,
@@ -1005,7 +1134,7 @@ mod tests {
         let mut non_consecutive = BTreeMap::new();
         dump_log_file(
             &path,
-            true,
+            Some(RecordParser::String),
             true,
             false,
             i32::MAX,
@@ -1031,7 +1160,7 @@ mod tests {
         let mut non_consecutive = BTreeMap::new();
         dump_log_file(
             &path,
-            false,
+            None,
             true,
             false,
             i32::MAX,
@@ -1044,6 +1173,85 @@ mod tests {
         assert!(text.contains("leaderId=3"));
     }
 
+    #[test]
+    fn dump_log_should_decode_consumer_offsets_records() {
+        let mut key = Vec::new();
+        key.extend_from_slice(&1_i16.to_be_bytes());
+        for part in ["group", "orders"] {
+            key.extend_from_slice(&i16::try_from(part.len()).expect("len").to_be_bytes());
+            key.extend_from_slice(part.as_bytes());
+        }
+        key.extend_from_slice(&0_i32.to_be_bytes());
+        let mut value = Vec::new();
+        value.extend_from_slice(&3_i16.to_be_bytes());
+        value.extend_from_slice(&17_i64.to_be_bytes()); // offset
+        value.extend_from_slice(&(-1_i32).to_be_bytes()); // leaderEpoch
+        value.extend_from_slice(&0_i16.to_be_bytes()); // metadata
+        value.extend_from_slice(&1_700_000_000_000_i64.to_be_bytes());
+        let mut batch = RecordBatch::new();
+        batch.add_record(Record::new(
+            Some(Bytes::from(key)),
+            Some(Bytes::from(value)),
+        ));
+        let dir = TempDir::new().expect("temp");
+        let path = dir.path().join("00000000000000000000.log");
+        std::fs::write(&path, batch.encode().expect("encode")).expect("write");
+        let mut out = Vec::new();
+        let mut non_consecutive = BTreeMap::new();
+        dump_log_file(
+            &path,
+            Some(RecordParser::Offsets),
+            true,
+            false,
+            i32::MAX,
+            &mut non_consecutive,
+            &mut out,
+        )
+        .expect("dump");
+        let text = String::from_utf8(out).expect("utf8");
+        assert!(
+            text.contains(r#" key: {"type":"1","data":{"group":"group","topic":"orders","partition":0}} payload: {"version":"3","data":{"offset":17,"leaderEpoch":-1,"metadata":"","commitTimestamp":1700000000000}}"#),
+            "{text}"
+        );
+    }
+
+    #[test]
+    fn dump_log_should_decode_cluster_metadata_snapshot_records() {
+        let mut batch = RecordBatch::new();
+        batch.add_record(Record::new(
+            None,
+            Some(Bytes::from(metadata_records::tests::topic_record(
+                "orders", 0,
+            ))),
+        ));
+        let dir = TempDir::new().expect("temp");
+        let path = dir
+            .path()
+            .join("00000000000000000120-0000000003.checkpoint");
+        std::fs::write(&path, batch.encode().expect("encode")).expect("write");
+        let mut out = Vec::new();
+        let mut non_consecutive = BTreeMap::new();
+        dump_log_file(
+            &path,
+            Some(RecordParser::ClusterMetadata),
+            true,
+            true,
+            i32::MAX,
+            &mut non_consecutive,
+            &mut out,
+        )
+        .expect("dump");
+        let text = String::from_utf8(out).expect("utf8");
+        assert!(
+            text.contains("Snapshot end offset: 120, epoch: 3"),
+            "{text}"
+        );
+        assert!(
+            text.contains(r#"| payload: {"type":"TOPIC_RECORD","version":0,"data":{"name":"orders","topicId":"AAAAAAAAAAAAAAAAAAAAAA"}}"#),
+            "{text}"
+        );
+    }
+
     #[test]
     fn dump_txnindex_should_print_aborted_transactions() {
         let dir = TempDir::new().expect("temp");
@@ -1097,6 +1305,11 @@ mod tests {
             "00000000000000000000-0000000000.snapshot"
         ));
         assert!(!is_kraft_snapshot_name("00000000000000000042.snapshot"));
+        assert_eq!(
+            kraft_snapshot_id("00000000000000000120-0000000003.checkpoint"),
+            Some((120, 3))
+        );
+        assert!(!is_kraft_snapshot_name("bootstrap.checkpoint"));
     }
 
     #[test]
@@ -1213,21 +1426,11 @@ mod tests {
     }
 
     #[test]
-    fn validate_options_should_reject_coordinator_decoders_and_custom_classes() {
+    fn validate_options_should_reject_custom_decoder_classes() {
         let mut opts = default_opts(vec![PathBuf::from("x.log")]);
-        opts.offsets_decoder = true;
-        let err = validate_options(&opts).expect_err("offsets decoder");
-        assert!(err.to_string().contains("offsets-decoder"), "{err}");
+        opts.share_group_state_decoder = true;
+        validate_options(&opts).expect("native share group state decoder");
 
-        opts.offsets_decoder = false;
-        opts.cluster_metadata_decoder = true;
-        let err = validate_options(&opts).expect_err("cluster decoder");
-        assert!(
-            err.to_string().contains("cluster-metadata-decoder"),
-            "{err}"
-        );
-
-        opts.cluster_metadata_decoder = false;
         opts.key_decoder = Some("com.example.CustomKey".into());
         let err = validate_options(&opts).expect_err("custom key");
         assert!(err.to_string().contains("CustomKey"), "{err}");
@@ -1251,6 +1454,17 @@ mod tests {
         opts.value_decoder = Some("string".into());
         assert!(opts.should_print_data_log());
         assert!(opts.is_deep_iteration());
+        assert_eq!(opts.record_parser(), Some(RecordParser::String));
+        opts.share_group_state_decoder = true;
+        assert_eq!(opts.record_parser(), Some(RecordParser::ShareGroupState));
+        opts.remote_log_metadata_decoder = true;
+        assert_eq!(opts.record_parser(), Some(RecordParser::RemoteLogMetadata));
+        opts.cluster_metadata_decoder = true;
+        assert_eq!(opts.record_parser(), Some(RecordParser::ClusterMetadata));
+        opts.transaction_log_decoder = true;
+        assert_eq!(opts.record_parser(), Some(RecordParser::TransactionLog));
+        opts.offsets_decoder = true;
+        assert_eq!(opts.record_parser(), Some(RecordParser::Offsets));
     }
 
     #[test]
@@ -0,0 +1,1021 @@
+//! Decoders for records stored in Kafka's internal topics.
+//!
+//! Output mirrors the coordinator record parsers used by `DumpLogSegments` and the
+//! console consumer formatters: keys render as `{"type":..,"data":..}` and values as
+//! `{"version":..,"data":..}`, with generated-schema field names.
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use serde::Serialize;
+
+use crate::{
+    error::{Error, Result},
+    record_schema::{SchemaReader, check_version},
+};
+
+#[derive(Serialize)]
+struct KeyJson<'a, T> {
+    #[serde(rename = "type")]
+    record_type: String,
+    data: &'a T,
+}
+
+#[derive(Serialize)]
+struct ValueJson<'a, T> {
+    version: String,
+    data: &'a T,
+}
+
+/// Rendered key and optional value of one decoded coordinator record.
+type RenderedRecord = (String, Option<String>);
+
+fn render<K: Serialize, V: Serialize>(
+    record_type: i16,
+    key: &K,
+    value: Option<(i16, V)>,
+) -> Result<RenderedRecord> {
+    let key = serde_json::to_string(&KeyJson {
+        record_type: record_type.to_string(),
+        data: key,
+    })?;
+    let value = value
+        .map(|(version, data)| {
+            serde_json::to_string(&ValueJson {
+                version: version.to_string(),
+                data: &data,
+            })
+        })
+        .transpose()?;
+    Ok((key, value))
+}
+
+/// Splits a version-prefixed key or value into its version and body.
+fn split_version<'a>(bytes: &'a [u8], what: &str) -> Result<(i16, &'a [u8])> {
+    match bytes {
+        [high, low, body @ ..] => Ok((i16::from_be_bytes([*high, *low]), body)),
+        _ => Err(Error::Config(format!(
+            "{what} is too short to hold a version"
+        ))),
+    }
+}
+
+/// Shared driver mirroring Kafka's `CoordinatorRecordMessageParser`.
+///
+/// `decode` receives the key record type, key body, and optional versioned value body and
+/// returns `None` for record types it does not know.
+fn parse_coordinator_record(
+    offset: i64,
+    key: Option<&[u8]>,
+    value: Option<&[u8]>,
+    decode: impl FnOnce(i16, &[u8], Option<(i16, &[u8])>) -> Result<Option<RenderedRecord>>,
+) -> (Option<String>, Option<String>) {
+    let Some(key) = key else {
+        return (
+            Some(format!(
+                "Failed to decode message at offset {offset} using the specified decoder (message had a missing key)"
+            )),
+            None,
+        );
+    };
+    let decoded = split_version(key, "record key").and_then(|(record_type, key)| {
+        let value = value
+            .map(|value| split_version(value, "record value"))
+            .transpose()?;
+        Ok((record_type, decode(record_type, key, value)?))
+    });
+    match decoded {
+        Ok((_, Some((key, value)))) => {
+            (Some(key), Some(value.unwrap_or_else(|| "<DELETE>".into())))
+        }
+        Ok((record_type, None)) => (
+            Some(format!(
+                "Unknown record type {record_type} at offset {offset}, skipping."
+            )),
+            None,
+        ),
+        Err(error) => (
+            Some(format!("Error at offset {offset}, skipping. {error}")),
+            None,
+        ),
+    }
+}
+
+/// Decodes a `__consumer_offsets` record like Kafka's `OffsetsMessageParser`.
+pub fn parse_offsets_record(
+    offset: i64,
+    key: Option<&[u8]>,
+    value: Option<&[u8]>,
+) -> (Option<String>, Option<String>) {
+    parse_coordinator_record(
+        offset,
+        key,
+        value,
+        |record_type, key, value| match record_type {
+            0 | 1 => {
+                let key = OffsetCommitKey::decode(key)?;
+                let value = value
+                    .map(|(version, body)| {
+                        OffsetCommitValue::decode(version, body).map(|data| (version, data))
+                    })
+                    .transpose()?;
+                render(record_type, &key, value).map(Some)
+            }
+            2 => {
+                let key = GroupMetadataKey::decode(key)?;
+                let value = value
+                    .map(|(version, body)| {
+                        GroupMetadataValue::decode(version, body).map(|data| (version, data))
+                    })
+                    .transpose()?;
+                render(record_type, &key, value).map(Some)
+            }
+            _ => Ok(None),
+        },
+    )
+}
+
+/// Decodes a `__transaction_state` record like Kafka's `TransactionLogMessageParser`.
+pub fn parse_transaction_log_record(
+    offset: i64,
+    key: Option<&[u8]>,
+    value: Option<&[u8]>,
+) -> (Option<String>, Option<String>) {
+    parse_coordinator_record(offset, key, value, |record_type, key, value| {
+        if record_type != 0 {
+            return Ok(None);
+        }
+        let key = TransactionLogKey {
+            transactional_id: SchemaReader::new(key, false).string()?,
+        };
+        let value = value
+            .map(|(version, body)| {
+                TransactionLogValue::decode(version, body).map(|data| (version, data))
+            })
+            .transpose()?;
+        render(record_type, &key, value).map(Some)
+    })
+}
+
+#[derive(Debug, Serialize)]
+struct OffsetCommitKey {
+    group: String,
+    topic: String,
+    partition: i32,
+}
+
+impl OffsetCommitKey {
+    fn decode(body: &[u8]) -> Result<Self> {
+        let mut reader = SchemaReader::new(body, false);
+        Ok(Self {
+            group: reader.string()?,
+            topic: reader.string()?,
+            partition: reader.i32()?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct OffsetCommitValue {
+    offset: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    leader_epoch: Option<i32>,
+    metadata: String,
+    commit_timestamp: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expire_timestamp: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    topic_id: Option<String>,
+}
+
+impl OffsetCommitValue {
+    fn decode(version: i16, body: &[u8]) -> Result<Self> {
+        check_version("OffsetCommitValue", version, 4)?;
+        let mut reader = SchemaReader::new(body, version >= 4);
+        let offset = reader.i64()?;
+        let leader_epoch = if version >= 3 {
+            Some(reader.i32()?)
+        } else {
+            None
+        };
+        let metadata = reader.string()?;
+        let commit_timestamp = reader.i64()?;
+        let expire_timestamp = if version == 1 {
+            Some(reader.i64()?)
+        } else {
+            None
+        };
+        let mut topic_id = None;
+        reader.tagged_fields(|tag, field| {
+            if tag == 0 {
+                topic_id = Some(field.uuid()?);
+                return Ok(true);
+            }
+            Ok(false)
+        })?;
+        Ok(Self {
+            offset,
+            leader_epoch,
+            metadata,
+            commit_timestamp,
+            expire_timestamp,
+            topic_id,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct GroupMetadataKey {
+    group: String,
+}
+
+impl GroupMetadataKey {
+    fn decode(body: &[u8]) -> Result<Self> {
+        Ok(Self {
+            group: SchemaReader::new(body, false).string()?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GroupMetadataValue {
+    protocol_type: String,
+    generation: i32,
+    protocol: Option<String>,
+    leader: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    current_state_timestamp: Option<i64>,
+    members: Vec<GroupMemberMetadata>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GroupMemberMetadata {
+    member_id: String,
+    #[expect(
+        clippy::option_option,
+        reason = "distinguishes a field absent from the schema version from a null value"
+    )]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    group_instance_id: Option<Option<String>>,
+    client_id: String,
+    client_host: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    rebalance_timeout: Option<i32>,
+    session_timeout: i32,
+    subscription: String,
+    assignment: String,
+}
+
+impl GroupMetadataValue {
+    fn decode(version: i16, body: &[u8]) -> Result<Self> {
+        check_version("GroupMetadataValue", version, 4)?;
+        let mut reader = SchemaReader::new(body, version >= 4);
+        let protocol_type = reader.string()?;
+        let generation = reader.i32()?;
+        let protocol = reader.nullable_string()?;
+        let leader = reader.nullable_string()?;
+        let current_state_timestamp = if version >= 2 {
+            Some(reader.i64()?)
+        } else {
+            None
+        };
+        let members = reader.array(|member| {
+            let member_id = member.string()?;
+            let group_instance_id = if version >= 3 {
+                Some(member.nullable_string()?)
+            } else {
+                None
+            };
+            let client_id = member.string()?;
+            let client_host = member.string()?;
+            let rebalance_timeout = if version >= 1 {
+                Some(member.i32()?)
+            } else {
+                None
+            };
+            let session_timeout = member.i32()?;
+            let subscription = STANDARD.encode(member.bytes()?);
+            let assignment = STANDARD.encode(member.bytes()?);
+            member.skip_tagged_fields()?;
+            Ok(GroupMemberMetadata {
+                member_id,
+                group_instance_id,
+                client_id,
+                client_host,
+                rebalance_timeout,
+                session_timeout,
+                subscription,
+                assignment,
+            })
+        })?;
+        reader.skip_tagged_fields()?;
+        Ok(Self {
+            protocol_type,
+            generation,
+            protocol,
+            leader,
+            current_state_timestamp,
+            members,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionLogKey {
+    transactional_id: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionLogValue {
+    producer_id: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    previous_producer_id: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_producer_id: Option<i64>,
+    producer_epoch: i16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    next_producer_epoch: Option<i16>,
+    transaction_timeout_ms: i32,
+    transaction_status: i8,
+    transaction_partitions: Option<Vec<TransactionPartitions>>,
+    transaction_last_update_timestamp_ms: i64,
+    transaction_start_timestamp_ms: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_transaction_version: Option<i16>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TransactionPartitions {
+    topic: String,
+    partition_ids: Vec<i32>,
+}
+
+impl TransactionLogValue {
+    fn decode(version: i16, body: &[u8]) -> Result<Self> {
+        check_version("TransactionLogValue", version, 1)?;
+        let mut reader = SchemaReader::new(body, version >= 1);
+        let producer_id = reader.i64()?;
+        let producer_epoch = reader.i16()?;
+        let transaction_timeout_ms = reader.i32()?;
+        let transaction_status = reader.i8()?;
+        let transaction_partitions = reader.nullable_array(|partitions| {
+            let topic = partitions.string()?;
+            let partition_ids = partitions.array(SchemaReader::i32)?;
+            partitions.skip_tagged_fields()?;
+            Ok(TransactionPartitions {
+                topic,
+                partition_ids,
+            })
+        })?;
+        let transaction_last_update_timestamp_ms = reader.i64()?;
+        let transaction_start_timestamp_ms = reader.i64()?;
+        // Tagged fields are only rendered when they differ from their defaults.
+        let mut previous_producer_id = None;
+        let mut next_producer_id = None;
+        let mut next_producer_epoch = None;
+        let mut client_transaction_version = None;
+        reader.tagged_fields(|tag, field| {
+            match tag {
+                0 => previous_producer_id = Some(field.i64()?).filter(|id| *id != -1),
+                1 => next_producer_id = Some(field.i64()?).filter(|id| *id != -1),
+                2 => client_transaction_version = Some(field.i16()?).filter(|v| *v != 0),
+                3 => next_producer_epoch = Some(field.i16()?).filter(|epoch| *epoch != -1),
+                _ => return Ok(false),
+            }
+            Ok(true)
+        })?;
+        Ok(Self {
+            producer_id,
+            previous_producer_id,
+            next_producer_id,
+            producer_epoch,
+            next_producer_epoch,
+            transaction_timeout_ms,
+            transaction_status,
+            transaction_partitions,
+            transaction_last_update_timestamp_ms,
+            transaction_start_timestamp_ms,
+            client_transaction_version,
+        })
+    }
+}
+
+/// Decodes a `__share_group_state` record like Kafka's `ShareGroupStateMessageParser`.
+pub fn parse_share_group_state_record(
+    offset: i64,
+    key: Option<&[u8]>,
+    value: Option<&[u8]>,
+) -> (Option<String>, Option<String>) {
+    parse_coordinator_record(offset, key, value, |record_type, key, value| {
+        let key = match record_type {
+            0 | 1 => SharePartitionKey::decode(key)?,
+            _ => return Ok(None),
+        };
+        let Some((version, body)) = value else {
+            return render(record_type, &key, None::<(i16, ShareUpdateValue)>).map(Some);
+        };
+        if record_type == 0 {
+            let value = ShareSnapshotValue::decode(version, body)?;
+            render(record_type, &key, Some((version, value))).map(Some)
+        } else {
+            let value = ShareUpdateValue::decode(version, body)?;
+            render(record_type, &key, Some((version, value))).map(Some)
+        }
+    })
+}
+
+/// Shared shape of `ShareSnapshotKey` and `ShareUpdateKey`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SharePartitionKey {
+    group_id: String,
+    topic_id: String,
+    partition: i32,
+}
+
+impl SharePartitionKey {
+    fn decode(body: &[u8]) -> Result<Self> {
+        let mut reader = SchemaReader::new(body, false);
+        Ok(Self {
+            group_id: reader.string()?,
+            topic_id: reader.uuid()?,
+            partition: reader.i32()?,
+        })
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ShareSnapshotValue {
+    snapshot_epoch: i32,
+    state_epoch: i32,
+    leader_epoch: i32,
+    start_offset: i64,
+    create_timestamp: i64,
+    write_timestamp: i64,
+    state_batches: Vec<ShareStateBatch>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ShareUpdateValue {
+    snapshot_epoch: i32,
+    leader_epoch: i32,
+    start_offset: i64,
+    state_batches: Vec<ShareStateBatch>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ShareStateBatch {
+    first_offset: i64,
+    last_offset: i64,
+    delivery_state: i8,
+    delivery_count: i16,
+}
+
+fn decode_share_state_batch(reader: &mut SchemaReader<'_>) -> Result<ShareStateBatch> {
+    let batch = ShareStateBatch {
+        first_offset: reader.i64()?,
+        last_offset: reader.i64()?,
+        delivery_state: reader.i8()?,
+        delivery_count: reader.i16()?,
+    };
+    reader.skip_tagged_fields()?;
+    Ok(batch)
+}
+
+impl ShareSnapshotValue {
+    fn decode(version: i16, body: &[u8]) -> Result<Self> {
+        check_version("ShareSnapshotValue", version, 0)?;
+        let mut reader = SchemaReader::new(body, true);
+        let value = Self {
+            snapshot_epoch: reader.i32()?,
+            state_epoch: reader.i32()?,
+            leader_epoch: reader.i32()?,
+            start_offset: reader.i64()?,
+            create_timestamp: reader.i64()?,
+            write_timestamp: reader.i64()?,
+            state_batches: reader.array(decode_share_state_batch)?,
+        };
+        reader.skip_tagged_fields()?;
+        Ok(value)
+    }
+}
+
+impl ShareUpdateValue {
+    fn decode(version: i16, body: &[u8]) -> Result<Self> {
+        check_version("ShareUpdateValue", version, 0)?;
+        let mut reader = SchemaReader::new(body, true);
+        let value = Self {
+            snapshot_epoch: reader.i32()?,
+            leader_epoch: reader.i32()?,
+            start_offset: reader.i64()?,
+            state_batches: reader.array(decode_share_state_batch)?,
+        };
+        reader.skip_tagged_fields()?;
+        Ok(value)
+    }
+}
+
+/// Decodes a `__remote_log_metadata` record value like Kafka's `RemoteLogMetadataParser`.
+///
+/// Values are `RemoteLogMetadataSerde` frames; the key is unused, so only a payload is
+/// produced, rendered as `{"type":..,"version":..,"data":..}`.
+pub fn parse_remote_log_metadata_record(
+    offset: i64,
+    value: Option<&[u8]>,
+) -> (Option<String>, Option<String>) {
+    let payload = value
+        .ok_or_else(|| Error::Config("remote log metadata record has no value".into()))
+        .and_then(decode_remote_log_metadata)
+        .unwrap_or_else(|error| format!("Error at offset {offset}, skipping. {error}"));
+    (None, Some(payload))
+}
+
+#[derive(Serialize)]
+struct RemoteLogMetadataJson<'a, T> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    version: i16,
+    data: &'a T,
+}
+
+fn render_remote_log_metadata<T: Serialize>(
+    record_type: &'static str,
+    version: i16,
+    data: &T,
+) -> Result<String> {
+    Ok(serde_json::to_string(&RemoteLogMetadataJson {
+        record_type,
+        version,
+        data,
+    })?)
+}
+
+fn decode_remote_log_metadata(bytes: &[u8]) -> Result<String> {
+    let mut reader = SchemaReader::new(bytes, true);
+    let (api_key, version) = reader.message_frame("remote log metadata record")?;
+    let reader = &mut reader;
+    match api_key {
+        0 => render_remote_log_metadata(
+            "RemoteLogSegmentMetadataRecord",
+            version,
+            &RemoteLogSegmentMetadataRecord::decode(version, reader)?,
+        ),
+        1 => render_remote_log_metadata(
+            "RemoteLogSegmentMetadataUpdateRecord",
+            version,
+            &RemoteLogSegmentMetadataUpdateRecord::decode(version, reader)?,
+        ),
+        2 => render_remote_log_metadata(
+            "RemotePartitionDeleteMetadataRecord",
+            version,
+            &RemotePartitionDeleteMetadataRecord::decode(version, reader)?,
+        ),
+        _ => Err(Error::Config(format!(
+            "Unknown remote log metadata api key {api_key}"
+        ))),
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TopicIdPartition {
+    name: String,
+    id: String,
+    partition: i32,
+}
+
+fn decode_topic_id_partition(reader: &mut SchemaReader<'_>) -> Result<TopicIdPartition> {
+    let partition = TopicIdPartition {
+        name: reader.string()?,
+        id: reader.uuid()?,
+        partition: reader.i32()?,
+    };
+    reader.skip_tagged_fields()?;
+    Ok(partition)
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteLogSegmentId {
+    topic_id_partition: TopicIdPartition,
+    id: String,
+}
+
+fn decode_remote_log_segment_id(reader: &mut SchemaReader<'_>) -> Result<RemoteLogSegmentId> {
+    let segment_id = RemoteLogSegmentId {
+        topic_id_partition: decode_topic_id_partition(reader)?,
+        id: reader.uuid()?,
+    };
+    reader.skip_tagged_fields()?;
+    Ok(segment_id)
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteLogSegmentMetadataRecord {
+    remote_log_segment_id: RemoteLogSegmentId,
+    start_offset: i64,
+    end_offset: i64,
+    broker_id: i32,
+    max_timestamp_ms: i64,
+    event_timestamp_ms: i64,
+    segment_leader_epochs: Vec<SegmentLeaderEpoch>,
+    segment_size_in_bytes: i32,
+    custom_metadata: Option<String>,
+    remote_log_segment_state: i8,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    txn_index_empty: bool,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SegmentLeaderEpoch {
+    leader_epoch: i32,
+    offset: i64,
+}
+
+impl RemoteLogSegmentMetadataRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("RemoteLogSegmentMetadataRecord", version, 0)?;
+        let mut record = Self {
+            remote_log_segment_id: decode_remote_log_segment_id(reader)?,
+            start_offset: reader.i64()?,
+            end_offset: reader.i64()?,
+            broker_id: reader.i32()?,
+            max_timestamp_ms: reader.i64()?,
+            event_timestamp_ms: reader.i64()?,
+            segment_leader_epochs: reader.array(|epoch| {
+                let data = SegmentLeaderEpoch {
+                    leader_epoch: epoch.i32()?,
+                    offset: epoch.i64()?,
+                };
+                epoch.skip_tagged_fields()?;
+                Ok(data)
+            })?,
+            segment_size_in_bytes: reader.i32()?,
+            custom_metadata: reader.nullable_bytes()?.map(|bytes| STANDARD.encode(bytes)),
+            remote_log_segment_state: reader.i8()?,
+            txn_index_empty: false,
+        };
+        reader.tagged_fields(|tag, field| {
+            if tag == 0 {
+                record.txn_index_empty = field.bool()?;
+                return Ok(true);
+            }
+            Ok(false)
+        })?;
+        Ok(record)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RemoteLogSegmentMetadataUpdateRecord {
+    remote_log_segment_id: RemoteLogSegmentId,
+    broker_id: i32,
+    event_timestamp_ms: i64,
+    custom_metadata: Option<String>,
+    remote_log_segment_state: i8,
+}
+
+impl RemoteLogSegmentMetadataUpdateRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("RemoteLogSegmentMetadataUpdateRecord", version, 0)?;
+        let record = Self {
+            remote_log_segment_id: decode_remote_log_segment_id(reader)?,
+            broker_id: reader.i32()?,
+            event_timestamp_ms: reader.i64()?,
+            custom_metadata: reader.nullable_bytes()?.map(|bytes| STANDARD.encode(bytes)),
+            remote_log_segment_state: reader.i8()?,
+        };
+        reader.skip_tagged_fields()?;
+        Ok(record)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct RemotePartitionDeleteMetadataRecord {
+    topic_id_partition: TopicIdPartition,
+    broker_id: i32,
+    event_timestamp_ms: i64,
+    remote_partition_delete_state: i8,
+}
+
+impl RemotePartitionDeleteMetadataRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("RemotePartitionDeleteMetadataRecord", version, 0)?;
+        let record = Self {
+            topic_id_partition: decode_topic_id_partition(reader)?,
+            broker_id: reader.i32()?,
+            event_timestamp_ms: reader.i64()?,
+            remote_partition_delete_state: reader.i8()?,
+        };
+        reader.skip_tagged_fields()?;
+        Ok(record)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::record_schema::tests::SchemaWriter;
+
+    fn offset_commit_key(record_type: i16) -> Vec<u8> {
+        SchemaWriter::new(false)
+            .i16(record_type)
+            .string("payments")
+            .string("orders")
+            .i32(3)
+            .finish()
+    }
+
+    #[test]
+    fn offsets_parser_should_decode_offset_commit_versions() {
+        let v1 = SchemaWriter::new(false)
+            .i16(1)
+            .i64(42)
+            .string("meta")
+            .i64(1_700_000_000_000)
+            .i64(1_700_000_600_000)
+            .finish();
+        let (key, value) = parse_offsets_record(7, Some(&offset_commit_key(1)), Some(&v1));
+        assert_eq!(
+            key.as_deref(),
+            Some(r#"{"type":"1","data":{"group":"payments","topic":"orders","partition":3}}"#)
+        );
+        assert_eq!(
+            value.as_deref(),
+            Some(
+                r#"{"version":"1","data":{"offset":42,"metadata":"meta","commitTimestamp":1700000000000,"expireTimestamp":1700000600000}}"#
+            )
+        );
+
+        let v4 = SchemaWriter::new(true)
+            .i16(4)
+            .i64(43)
+            .i32(5)
+            .string("")
+            .i64(1_700_000_000_000)
+            .empty_tagged_fields()
+            .finish();
+        let (_, value) = parse_offsets_record(8, Some(&offset_commit_key(1)), Some(&v4));
+        assert_eq!(
+            value.as_deref(),
+            Some(
+                r#"{"version":"4","data":{"offset":43,"leaderEpoch":5,"metadata":"","commitTimestamp":1700000000000}}"#
+            )
+        );
+    }
+
+    #[test]
+    fn offsets_parser_should_decode_group_metadata_members() {
+        let key = SchemaWriter::new(false).i16(2).string("payments").finish();
+        let value = SchemaWriter::new(false)
+            .i16(3)
+            .string("consumer")
+            .i32(9)
+            .nullable_string(Some("range"))
+            .nullable_string(Some("member-1"))
+            .i64(1_700_000_000_000)
+            .array_len(1)
+            .string("member-1")
+            .nullable_string(None)
+            .string("client")
+            .string("/127.0.0.1")
+            .i32(300_000)
+            .i32(45_000)
+            .bytes(&[0, 1])
+            .bytes(&[])
+            .finish();
+        let (key, value) = parse_offsets_record(0, Some(&key), Some(&value));
+        assert_eq!(
+            key.as_deref(),
+            Some(r#"{"type":"2","data":{"group":"payments"}}"#)
+        );
+        let value = value.expect("value");
+        assert!(value.starts_with(r#"{"version":"3","data":{"protocolType":"consumer","generation":9,"protocol":"range","leader":"member-1","currentStateTimestamp":1700000000000,"members":[{"memberId":"member-1","groupInstanceId":null,"clientId":"client""#), "{value}");
+        assert!(
+            value.contains(r#""subscription":"AAE=","assignment":"""#),
+            "{value}"
+        );
+    }
+
+    #[test]
+    fn offsets_parser_should_report_tombstones_and_unknown_types() {
+        let (_, value) = parse_offsets_record(1, Some(&offset_commit_key(1)), None);
+        assert_eq!(value.as_deref(), Some("<DELETE>"));
+
+        let key = SchemaWriter::new(false).i16(42).string("x").finish();
+        let (key, value) = parse_offsets_record(11, Some(&key), None);
+        assert_eq!(
+            key.as_deref(),
+            Some("Unknown record type 42 at offset 11, skipping.")
+        );
+        assert!(value.is_none());
+
+        let (key, _) = parse_offsets_record(12, None, None);
+        assert!(key.expect("message").contains("missing key"));
+
+        let truncated = SchemaWriter::new(false).i16(1).string("g").finish();
+        let (key, value) = parse_offsets_record(13, Some(&truncated), None);
+        assert!(key.expect("message").starts_with("Error at offset 13"));
+        assert!(value.is_none());
+    }
+
+    #[test]
+    fn transaction_log_parser_should_decode_ongoing_transaction() {
+        let key = SchemaWriter::new(false).i16(0).string("txn-1").finish();
+        let v0 = SchemaWriter::new(false)
+            .i16(0)
+            .i64(1000)
+            .i16(2)
+            .i32(60_000)
+            .i8(1)
+            .array_len(1)
+            .string("orders")
+            .array_len(2)
+            .i32(0)
+            .i32(4)
+            .i64(1_700_000_000_500)
+            .i64(1_700_000_000_000)
+            .finish();
+        let (key_json, value_json) = parse_transaction_log_record(5, Some(&key), Some(&v0));
+        assert_eq!(
+            key_json.as_deref(),
+            Some(r#"{"type":"0","data":{"transactionalId":"txn-1"}}"#)
+        );
+        assert_eq!(
+            value_json.as_deref(),
+            Some(
+                r#"{"version":"0","data":{"producerId":1000,"producerEpoch":2,"transactionTimeoutMs":60000,"transactionStatus":1,"transactionPartitions":[{"topic":"orders","partitionIds":[0,4]}],"transactionLastUpdateTimestampMs":1700000000500,"transactionStartTimestampMs":1700000000000}}"#
+            )
+        );
+    }
+
+    #[test]
+    fn transaction_log_parser_should_render_non_default_tagged_fields() {
+        let key = SchemaWriter::new(false).i16(0).string("txn-2").finish();
+        let mut value = SchemaWriter::new(true);
+        value
+            .i16(1)
+            .i64(7)
+            .i16(0)
+            .i32(1_000)
+            .i8(0)
+            .null_array()
+            .i64(2)
+            .i64(-1)
+            .unsigned_varint(2)
+            .unsigned_varint(0)
+            .unsigned_varint(8)
+            .i64(6)
+            .unsigned_varint(2)
+            .unsigned_varint(2)
+            .i16(2);
+        let value = value.finish();
+        let (_, value_json) = parse_transaction_log_record(6, Some(&key), Some(&value));
+        assert_eq!(
+            value_json.as_deref(),
+            Some(
+                r#"{"version":"1","data":{"producerId":7,"previousProducerId":6,"producerEpoch":0,"transactionTimeoutMs":1000,"transactionStatus":0,"transactionPartitions":null,"transactionLastUpdateTimestampMs":2,"transactionStartTimestampMs":-1,"clientTransactionVersion":2}}"#
+            )
+        );
+    }
+
+    #[test]
+    fn share_group_state_parser_should_decode_snapshots_and_updates() {
+        let key = SchemaWriter::new(false)
+            .i16(0)
+            .string("workers")
+            .uuid([0; 16])
+            .i32(2)
+            .finish();
+        let value = SchemaWriter::new(true)
+            .i16(0)
+            .i32(3)
+            .i32(1)
+            .i32(5)
+            .i64(100)
+            .i64(1_700_000_000_000)
+            .i64(1_700_000_000_500)
+            .array_len(1)
+            .i64(100)
+            .i64(109)
+            .i8(2)
+            .i16(1)
+            .empty_tagged_fields()
+            .empty_tagged_fields()
+            .finish();
+        let (key_json, value_json) = parse_share_group_state_record(4, Some(&key), Some(&value));
+        assert_eq!(
+            key_json.as_deref(),
+            Some(
+                r#"{"type":"0","data":{"groupId":"workers","topicId":"AAAAAAAAAAAAAAAAAAAAAA","partition":2}}"#
+            )
+        );
+        assert_eq!(
+            value_json.as_deref(),
+            Some(
+                r#"{"version":"0","data":{"snapshotEpoch":3,"stateEpoch":1,"leaderEpoch":5,"startOffset":100,"createTimestamp":1700000000000,"writeTimestamp":1700000000500,"stateBatches":[{"firstOffset":100,"lastOffset":109,"deliveryState":2,"deliveryCount":1}]}}"#
+            )
+        );
+
+        let update_key = SchemaWriter::new(false)
+            .i16(1)
+            .string("workers")
+            .uuid([0; 16])
+            .i32(2)
+            .finish();
+        let update = SchemaWriter::new(true)
+            .i16(0)
+            .i32(3)
+            .i32(5)
+            .i64(110)
+            .array_len(0)
+            .empty_tagged_fields()
+            .finish();
+        let (_, value_json) = parse_share_group_state_record(5, Some(&update_key), Some(&update));
+        assert_eq!(
+            value_json.as_deref(),
+            Some(
+                r#"{"version":"0","data":{"snapshotEpoch":3,"leaderEpoch":5,"startOffset":110,"stateBatches":[]}}"#
+            )
+        );
+    }
+
+    #[test]
+    fn remote_log_metadata_parser_should_decode_segment_metadata() {
+        let mut value = SchemaWriter::new(true);
+        value
+            .unsigned_varint(0)
+            .unsigned_varint(2)
+            .unsigned_varint(0)
+            .string("orders")
+            .uuid([0; 16])
+            .i32(1)
+            .empty_tagged_fields()
+            .i32(3)
+            .i64(1_700_000_000_000)
+            .i8(1)
+            .empty_tagged_fields();
+        let value = value.finish();
+        let (key, payload) = parse_remote_log_metadata_record(9, Some(&value));
+        assert!(key.is_none());
+        assert_eq!(
+            payload.as_deref(),
+            Some(
+                r#"{"type":"RemotePartitionDeleteMetadataRecord","version":0,"data":{"topicIdPartition":{"name":"orders","id":"AAAAAAAAAAAAAAAAAAAAAA","partition":1},"brokerId":3,"eventTimestampMs":1700000000000,"remotePartitionDeleteState":1}}"#
+            )
+        );
+
+        let segment = SchemaWriter::new(true)
+            .unsigned_varint(0)
+            .unsigned_varint(0)
+            .unsigned_varint(0)
+            .string("orders")
+            .uuid([0; 16])
+            .i32(1)
+            .empty_tagged_fields()
+            .uuid([1; 16])
+            .empty_tagged_fields()
+            .i64(0)
+            .i64(99)
+            .i32(3)
+            .i64(1_700_000_000_000)
+            .i64(1_700_000_000_100)
+            .array_len(1)
+            .i32(4)
+            .i64(0)
+            .empty_tagged_fields()
+            .i32(4096)
+            .bytes(&[])
+            .i8(0)
+            .empty_tagged_fields()
+            .finish();
+        let (_, payload) = parse_remote_log_metadata_record(10, Some(&segment));
+        let payload = payload.expect("payload");
+        assert!(
+            payload.starts_with(r#"{"type":"RemoteLogSegmentMetadataRecord","version":0,"#),
+            "{payload}"
+        );
+        assert!(
+            payload.contains(r#""segmentLeaderEpochs":[{"leaderEpoch":4,"offset":0}],"segmentSizeInBytes":4096,"customMetadata":"","remoteLogSegmentState":0}}"#),
+            "{payload}"
+        );
+
+        let (_, payload) = parse_remote_log_metadata_record(11, Some(&[0, 7, 0]));
+        assert!(
+            payload
+                .expect("payload")
+                .starts_with("Error at offset 11, skipping.")
+        );
+    }
+}
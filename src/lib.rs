@@ -1,12 +1,25 @@
 //! Native Kafka command-line client implementation.
 
+mod avro;
+mod backup;
 pub mod cli;
 pub mod commands;
 pub mod config;
+mod config_provider;
+mod context;
 pub mod dump_log;
 pub mod error;
 mod ffi;
+mod internal_records;
+mod metadata_records;
+pub mod metadata_shell;
+mod oauth;
 pub mod output;
+mod protobuf;
+mod record_schema;
+mod schema_registry;
+mod security;
+mod sink;
 pub mod storage_tool;
 
 use cli::Cli;
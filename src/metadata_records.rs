@@ -0,0 +1,993 @@
+//! `KRaft` `__cluster_metadata` record decoding (`MetadataRecordSerde`).
+//!
+//! Records are framed as `frameVersion`, `apiKey`, and `version` unsigned varints followed by
+//! a flexible generated message. JSON rendering follows `MetadataJsonConverters`: tagged
+//! fields are only printed when they differ from their schema defaults.
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use serde::Serialize;
+
+use crate::{
+    error::{Error, Result},
+    record_schema::{SchemaReader, check_version},
+};
+
+/// A decoded metadata record together with its schema version.
+#[derive(Debug)]
+pub struct VersionedMetadataRecord {
+    pub version: i16,
+    pub record: MetadataRecord,
+}
+
+/// Metadata records understood natively, keyed by `MetadataRecordType` API key.
+#[derive(Debug, Serialize)]
+#[serde(untagged)]
+pub enum MetadataRecord {
+    RegisterBroker(RegisterBrokerRecord),
+    UnregisterBroker(UnregisterBrokerRecord),
+    Topic(TopicRecord),
+    Partition(PartitionRecord),
+    Config(ConfigRecord),
+    PartitionChange(PartitionChangeRecord),
+    AccessControlEntry(AccessControlEntryRecord),
+    FenceBroker(BrokerEpochRecord),
+    UnfenceBroker(BrokerEpochRecord),
+    RemoveTopic(RemoveTopicRecord),
+    DelegationToken(DelegationTokenRecord),
+    UserScramCredential(UserScramCredentialRecord),
+    FeatureLevel(FeatureLevelRecord),
+    ClientQuota(ClientQuotaRecord),
+    ProducerIds(ProducerIdsRecord),
+    BrokerRegistrationChange(BrokerRegistrationChangeRecord),
+    RemoveAccessControlEntry(RemoveAccessControlEntryRecord),
+    NoOp(EmptyRecord),
+    ZkMigrationState(ZkMigrationStateRecord),
+    RemoveUserScramCredential(RemoveUserScramCredentialRecord),
+    BeginTransaction(BeginTransactionRecord),
+    EndTransaction(EmptyRecord),
+    AbortTransaction(AbortTransactionRecord),
+    RemoveDelegationToken(RemoveDelegationTokenRecord),
+    RegisterController(RegisterControllerRecord),
+}
+
+impl MetadataRecord {
+    /// Returns the `MetadataRecordType` constant name printed by Kafka tools.
+    #[must_use]
+    pub const fn type_name(&self) -> &'static str {
+        match self {
+            Self::RegisterBroker(_) => "REGISTER_BROKER_RECORD",
+            Self::UnregisterBroker(_) => "UNREGISTER_BROKER_RECORD",
+            Self::Topic(_) => "TOPIC_RECORD",
+            Self::Partition(_) => "PARTITION_RECORD",
+            Self::Config(_) => "CONFIG_RECORD",
+            Self::PartitionChange(_) => "PARTITION_CHANGE_RECORD",
+            Self::AccessControlEntry(_) => "ACCESS_CONTROL_ENTRY_RECORD",
+            Self::FenceBroker(_) => "FENCE_BROKER_RECORD",
+            Self::UnfenceBroker(_) => "UNFENCE_BROKER_RECORD",
+            Self::RemoveTopic(_) => "REMOVE_TOPIC_RECORD",
+            Self::DelegationToken(_) => "DELEGATION_TOKEN_RECORD",
+            Self::UserScramCredential(_) => "USER_SCRAM_CREDENTIAL_RECORD",
+            Self::FeatureLevel(_) => "FEATURE_LEVEL_RECORD",
+            Self::ClientQuota(_) => "CLIENT_QUOTA_RECORD",
+            Self::ProducerIds(_) => "PRODUCER_IDS_RECORD",
+            Self::BrokerRegistrationChange(_) => "BROKER_REGISTRATION_CHANGE_RECORD",
+            Self::RemoveAccessControlEntry(_) => "REMOVE_ACCESS_CONTROL_ENTRY_RECORD",
+            Self::NoOp(_) => "NO_OP_RECORD",
+            Self::ZkMigrationState(_) => "ZK_MIGRATION_STATE_RECORD",
+            Self::RemoveUserScramCredential(_) => "REMOVE_USER_SCRAM_CREDENTIAL_RECORD",
+            Self::BeginTransaction(_) => "BEGIN_TRANSACTION_RECORD",
+            Self::EndTransaction(_) => "END_TRANSACTION_RECORD",
+            Self::AbortTransaction(_) => "ABORT_TRANSACTION_RECORD",
+            Self::RemoveDelegationToken(_) => "REMOVE_DELEGATION_TOKEN_RECORD",
+            Self::RegisterController(_) => "REGISTER_CONTROLLER_RECORD",
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct MetadataRecordJson<'a> {
+    #[serde(rename = "type")]
+    record_type: &'static str,
+    version: i16,
+    data: &'a MetadataRecord,
+}
+
+/// Renders a decoded record as `{"type":..,"version":..,"data":..}`.
+pub fn metadata_record_json(record: &VersionedMetadataRecord) -> Result<String> {
+    Ok(serde_json::to_string(&MetadataRecordJson {
+        record_type: record.record.type_name(),
+        version: record.version,
+        data: &record.record,
+    })?)
+}
+
+/// Decodes a `__cluster_metadata` record value like Kafka's `ClusterMetadataLogMessageParser`.
+///
+/// Metadata records carry no key, so only a payload is produced.
+pub fn parse_cluster_metadata_record(
+    offset: i64,
+    value: Option<&[u8]>,
+) -> (Option<String>, Option<String>) {
+    let payload = value
+        .ok_or_else(|| Error::Config("metadata record has no value".into()))
+        .and_then(decode_metadata_record)
+        .and_then(|record| metadata_record_json(&record))
+        .unwrap_or_else(|error| format!("Error at {offset}, skipping. {error}"));
+    (None, Some(payload))
+}
+
+/// Decodes one framed metadata record value.
+pub fn decode_metadata_record(bytes: &[u8]) -> Result<VersionedMetadataRecord> {
+    let mut reader = SchemaReader::new(bytes, true);
+    let (api_key, version) = reader.message_frame("metadata record")?;
+    let reader = &mut reader;
+    let record = match api_key {
+        0 => MetadataRecord::RegisterBroker(RegisterBrokerRecord::decode(version, reader)?),
+        1 => MetadataRecord::UnregisterBroker(UnregisterBrokerRecord::decode(version, reader)?),
+        2 => MetadataRecord::Topic(TopicRecord::decode(version, reader)?),
+        3 => MetadataRecord::Partition(PartitionRecord::decode(version, reader)?),
+        4 => MetadataRecord::Config(ConfigRecord::decode(version, reader)?),
+        5 => MetadataRecord::PartitionChange(PartitionChangeRecord::decode(version, reader)?),
+        6 => MetadataRecord::AccessControlEntry(AccessControlEntryRecord::decode(version, reader)?),
+        7 => MetadataRecord::FenceBroker(BrokerEpochRecord::decode(
+            "FenceBrokerRecord",
+            version,
+            reader,
+        )?),
+        8 => MetadataRecord::UnfenceBroker(BrokerEpochRecord::decode(
+            "UnfenceBrokerRecord",
+            version,
+            reader,
+        )?),
+        9 => MetadataRecord::RemoveTopic(RemoveTopicRecord::decode(version, reader)?),
+        10 => MetadataRecord::DelegationToken(DelegationTokenRecord::decode(version, reader)?),
+        11 => {
+            MetadataRecord::UserScramCredential(UserScramCredentialRecord::decode(version, reader)?)
+        }
+        12 => MetadataRecord::FeatureLevel(FeatureLevelRecord::decode(version, reader)?),
+        14 => MetadataRecord::ClientQuota(ClientQuotaRecord::decode(version, reader)?),
+        15 => MetadataRecord::ProducerIds(ProducerIdsRecord::decode(version, reader)?),
+        17 => MetadataRecord::BrokerRegistrationChange(BrokerRegistrationChangeRecord::decode(
+            version, reader,
+        )?),
+        18 => MetadataRecord::RemoveAccessControlEntry(RemoveAccessControlEntryRecord::decode(
+            version, reader,
+        )?),
+        20 => MetadataRecord::NoOp(EmptyRecord::decode("NoOpRecord", version, reader)?),
+        21 => MetadataRecord::ZkMigrationState(ZkMigrationStateRecord::decode(version, reader)?),
+        22 => MetadataRecord::RemoveUserScramCredential(RemoveUserScramCredentialRecord::decode(
+            version, reader,
+        )?),
+        23 => MetadataRecord::BeginTransaction(BeginTransactionRecord::decode(version, reader)?),
+        24 => MetadataRecord::EndTransaction(EmptyRecord::decode(
+            "EndTransactionRecord",
+            version,
+            reader,
+        )?),
+        25 => MetadataRecord::AbortTransaction(AbortTransactionRecord::decode(version, reader)?),
+        26 => MetadataRecord::RemoveDelegationToken(RemoveDelegationTokenRecord::decode(
+            version, reader,
+        )?),
+        27 => {
+            MetadataRecord::RegisterController(RegisterControllerRecord::decode(version, reader)?)
+        }
+        _ => {
+            return Err(Error::Config(format!("Unknown metadata id {api_key}")));
+        }
+    };
+    Ok(VersionedMetadataRecord { version, record })
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterBrokerRecord {
+    pub broker_id: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_migrating_zk_broker: Option<bool>,
+    pub incarnation_id: String,
+    pub broker_epoch: i64,
+    pub end_points: Vec<Endpoint>,
+    pub features: Vec<SupportedFeature>,
+    pub rack: Option<String>,
+    pub fenced: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub in_controlled_shutdown: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_dirs: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Endpoint {
+    pub name: String,
+    pub host: String,
+    pub port: u16,
+    pub security_protocol: i16,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupportedFeature {
+    pub name: String,
+    pub min_supported_version: i16,
+    pub max_supported_version: i16,
+}
+
+fn decode_endpoint(reader: &mut SchemaReader<'_>) -> Result<Endpoint> {
+    let endpoint = Endpoint {
+        name: reader.string()?,
+        host: reader.string()?,
+        port: reader.u16()?,
+        security_protocol: reader.i16()?,
+    };
+    reader.skip_tagged_fields()?;
+    Ok(endpoint)
+}
+
+fn decode_supported_feature(reader: &mut SchemaReader<'_>) -> Result<SupportedFeature> {
+    let feature = SupportedFeature {
+        name: reader.string()?,
+        min_supported_version: reader.i16()?,
+        max_supported_version: reader.i16()?,
+    };
+    reader.skip_tagged_fields()?;
+    Ok(feature)
+}
+
+impl RegisterBrokerRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("RegisterBrokerRecord", version, 3)?;
+        let broker_id = reader.i32()?;
+        let is_migrating_zk_broker = if version >= 2 {
+            Some(reader.bool()?)
+        } else {
+            None
+        };
+        let record = Self {
+            broker_id,
+            is_migrating_zk_broker,
+            incarnation_id: reader.uuid()?,
+            broker_epoch: reader.i64()?,
+            end_points: reader.array(decode_endpoint)?,
+            features: reader.array(decode_supported_feature)?,
+            rack: reader.nullable_string()?,
+            fenced: reader.bool()?,
+            in_controlled_shutdown: if version >= 1 {
+                Some(reader.bool()?)
+            } else {
+                None
+            },
+            log_dirs: if version >= 3 {
+                Some(reader.array(SchemaReader::uuid)?)
+            } else {
+                None
+            },
+        };
+        reader.skip_tagged_fields()?;
+        Ok(record)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UnregisterBrokerRecord {
+    pub broker_id: i32,
+    pub broker_epoch: i64,
+}
+
+impl UnregisterBrokerRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("UnregisterBrokerRecord", version, 0)?;
+        let record = Self {
+            broker_id: reader.i32()?,
+            broker_epoch: reader.i64()?,
+        };
+        reader.skip_tagged_fields()?;
+        Ok(record)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TopicRecord {
+    pub name: String,
+    pub topic_id: String,
+}
+
+impl TopicRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("TopicRecord", version, 0)?;
+        let record = Self {
+            name: reader.string()?,
+            topic_id: reader.uuid()?,
+        };
+        reader.skip_tagged_fields()?;
+        Ok(record)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartitionRecord {
+    pub partition_id: i32,
+    pub topic_id: String,
+    pub replicas: Vec<i32>,
+    pub isr: Vec<i32>,
+    pub removing_replicas: Vec<i32>,
+    pub adding_replicas: Vec<i32>,
+    pub leader: i32,
+    #[serde(skip_serializing_if = "is_zero_recovery_state")]
+    pub leader_recovery_state: i8,
+    pub leader_epoch: i32,
+    pub partition_epoch: i32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub directories: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eligible_leader_replicas: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_known_elr: Option<Vec<i32>>,
+}
+
+#[expect(
+    clippy::trivially_copy_pass_by_ref,
+    reason = "serde skip_serializing_if passes fields by reference"
+)]
+const fn is_zero_recovery_state(value: &i8) -> bool {
+    *value == 0
+}
+
+impl PartitionRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("PartitionRecord", version, 2)?;
+        let mut record = Self {
+            partition_id: reader.i32()?,
+            topic_id: reader.uuid()?,
+            replicas: reader.array(SchemaReader::i32)?,
+            isr: reader.array(SchemaReader::i32)?,
+            removing_replicas: reader.array(SchemaReader::i32)?,
+            adding_replicas: reader.array(SchemaReader::i32)?,
+            leader: reader.i32()?,
+            leader_recovery_state: 0,
+            leader_epoch: reader.i32()?,
+            partition_epoch: reader.i32()?,
+            directories: if version >= 1 {
+                Some(reader.array(SchemaReader::uuid)?)
+            } else {
+                None
+            },
+            eligible_leader_replicas: None,
+            last_known_elr: None,
+        };
+        reader.tagged_fields(|tag, field| {
+            match tag {
+                0 => record.leader_recovery_state = field.i8()?,
+                1 if version >= 2 => {
+                    record.eligible_leader_replicas = field.nullable_array(SchemaReader::i32)?;
+                }
+                2 if version >= 2 => {
+                    record.last_known_elr = field.nullable_array(SchemaReader::i32)?;
+                }
+                _ => return Ok(false),
+            }
+            Ok(true)
+        })?;
+        Ok(record)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigRecord {
+    pub resource_type: i8,
+    pub resource_name: String,
+    pub name: String,
+    pub value: Option<String>,
+}
+
+impl ConfigRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("ConfigRecord", version, 0)?;
+        let record = Self {
+            resource_type: reader.i8()?,
+            resource_name: reader.string()?,
+            name: reader.string()?,
+            value: reader.nullable_string()?,
+        };
+        reader.skip_tagged_fields()?;
+        Ok(record)
+    }
+}
+
+/// Partition delta; every field except the identity is tagged and absent when unchanged.
+#[derive(Debug, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PartitionChangeRecord {
+    pub partition_id: i32,
+    pub topic_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub isr: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub leader: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replicas: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub removing_replicas: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub adding_replicas: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub leader_recovery_state: Option<i8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub directories: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub eligible_leader_replicas: Option<Vec<i32>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_known_elr: Option<Vec<i32>>,
+}
+
+impl PartitionChangeRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("PartitionChangeRecord", version, 2)?;
+        let mut record = Self {
+            partition_id: reader.i32()?,
+            topic_id: reader.uuid()?,
+            ..Self::default()
+        };
+        reader.tagged_fields(|tag, field| {
+            match tag {
+                0 => record.isr = field.nullable_array(SchemaReader::i32)?,
+                1 => record.leader = Some(field.i32()?).filter(|leader| *leader != -2),
+                2 => record.replicas = field.nullable_array(SchemaReader::i32)?,
+                3 => record.removing_replicas = field.nullable_array(SchemaReader::i32)?,
+                4 => record.adding_replicas = field.nullable_array(SchemaReader::i32)?,
+                5 => {
+                    record.leader_recovery_state = Some(field.i8()?).filter(|state| *state != -1);
+                }
+                6 if version >= 2 => {
+                    record.eligible_leader_replicas = field.nullable_array(SchemaReader::i32)?;
+                }
+                7 if version >= 2 => {
+                    record.last_known_elr = field.nullable_array(SchemaReader::i32)?;
+                }
+                8 if version >= 1 => {
+                    record.directories = field.nullable_array(SchemaReader::uuid)?;
+                }
+                _ => return Ok(false),
+            }
+            Ok(true)
+        })?;
+        Ok(record)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccessControlEntryRecord {
+    pub id: String,
+    pub resource_type: i8,
+    pub resource_name: String,
+    pub pattern_type: i8,
+    pub principal: String,
+    pub host: String,
+    pub operation: i8,
+    pub permission_type: i8,
+}
+
+impl AccessControlEntryRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("AccessControlEntryRecord", version, 0)?;
+        let record = Self {
+            id: reader.uuid()?,
+            resource_type: reader.i8()?,
+            resource_name: reader.string()?,
+            pattern_type: reader.i8()?,
+            principal: reader.string()?,
+            host: reader.string()?,
+            operation: reader.i8()?,
+            permission_type: reader.i8()?,
+        };
+        reader.skip_tagged_fields()?;
+        Ok(record)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveAccessControlEntryRecord {
+    pub id: String,
+}
+
+impl RemoveAccessControlEntryRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("RemoveAccessControlEntryRecord", version, 0)?;
+        let record = Self { id: reader.uuid()? };
+        reader.skip_tagged_fields()?;
+        Ok(record)
+    }
+}
+
+/// Shared shape of `FenceBrokerRecord` and `UnfenceBrokerRecord`.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokerEpochRecord {
+    pub id: i32,
+    pub epoch: i64,
+}
+
+impl BrokerEpochRecord {
+    fn decode(schema: &str, version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version(schema, version, 0)?;
+        let record = Self {
+            id: reader.i32()?,
+            epoch: reader.i64()?,
+        };
+        reader.skip_tagged_fields()?;
+        Ok(record)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveTopicRecord {
+    pub topic_id: String,
+}
+
+impl RemoveTopicRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("RemoveTopicRecord", version, 0)?;
+        let record = Self {
+            topic_id: reader.uuid()?,
+        };
+        reader.skip_tagged_fields()?;
+        Ok(record)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DelegationTokenRecord {
+    pub owner: String,
+    pub requester: String,
+    pub renewers: Vec<String>,
+    pub issue_timestamp: i64,
+    pub max_timestamp: i64,
+    pub expiration_timestamp: i64,
+    pub token_id: String,
+}
+
+impl DelegationTokenRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("DelegationTokenRecord", version, 0)?;
+        let record = Self {
+            owner: reader.string()?,
+            requester: reader.string()?,
+            renewers: reader.array(SchemaReader::string)?,
+            issue_timestamp: reader.i64()?,
+            max_timestamp: reader.i64()?,
+            expiration_timestamp: reader.i64()?,
+            token_id: reader.string()?,
+        };
+        reader.skip_tagged_fields()?;
+        Ok(record)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveDelegationTokenRecord {
+    pub token_id: String,
+}
+
+impl RemoveDelegationTokenRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("RemoveDelegationTokenRecord", version, 0)?;
+        let record = Self {
+            token_id: reader.string()?,
+        };
+        reader.skip_tagged_fields()?;
+        Ok(record)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserScramCredentialRecord {
+    pub name: String,
+    pub mechanism: i8,
+    pub salt: String,
+    pub stored_key: String,
+    pub server_key: String,
+    pub iterations: i32,
+}
+
+impl UserScramCredentialRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("UserScramCredentialRecord", version, 0)?;
+        let record = Self {
+            name: reader.string()?,
+            mechanism: reader.i8()?,
+            salt: STANDARD.encode(reader.bytes()?),
+            stored_key: STANDARD.encode(reader.bytes()?),
+            server_key: STANDARD.encode(reader.bytes()?),
+            iterations: reader.i32()?,
+        };
+        reader.skip_tagged_fields()?;
+        Ok(record)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoveUserScramCredentialRecord {
+    pub name: String,
+    pub mechanism: i8,
+}
+
+impl RemoveUserScramCredentialRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("RemoveUserScramCredentialRecord", version, 0)?;
+        let record = Self {
+            name: reader.string()?,
+            mechanism: reader.i8()?,
+        };
+        reader.skip_tagged_fields()?;
+        Ok(record)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeatureLevelRecord {
+    pub name: String,
+    pub feature_level: i16,
+}
+
+impl FeatureLevelRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("FeatureLevelRecord", version, 0)?;
+        let record = Self {
+            name: reader.string()?,
+            feature_level: reader.i16()?,
+        };
+        reader.skip_tagged_fields()?;
+        Ok(record)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClientQuotaRecord {
+    pub entity: Vec<QuotaEntity>,
+    pub key: String,
+    pub value: f64,
+    pub remove: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotaEntity {
+    pub entity_type: String,
+    pub entity_name: Option<String>,
+}
+
+impl ClientQuotaRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("ClientQuotaRecord", version, 0)?;
+        let record = Self {
+            entity: reader.array(|entity| {
+                let data = QuotaEntity {
+                    entity_type: entity.string()?,
+                    entity_name: entity.nullable_string()?,
+                };
+                entity.skip_tagged_fields()?;
+                Ok(data)
+            })?,
+            key: reader.string()?,
+            value: reader.f64()?,
+            remove: reader.bool()?,
+        };
+        reader.skip_tagged_fields()?;
+        Ok(record)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProducerIdsRecord {
+    pub broker_id: i32,
+    pub broker_epoch: i64,
+    pub next_producer_id: i64,
+}
+
+impl ProducerIdsRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("ProducerIdsRecord", version, 0)?;
+        let record = Self {
+            broker_id: reader.i32()?,
+            broker_epoch: reader.i64()?,
+            next_producer_id: reader.i64()?,
+        };
+        reader.skip_tagged_fields()?;
+        Ok(record)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BrokerRegistrationChangeRecord {
+    pub broker_id: i32,
+    pub broker_epoch: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fenced: Option<i8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub in_controlled_shutdown: Option<i8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_dirs: Option<Vec<String>>,
+}
+
+impl BrokerRegistrationChangeRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("BrokerRegistrationChangeRecord", version, 2)?;
+        let mut record = Self {
+            broker_id: reader.i32()?,
+            broker_epoch: reader.i64()?,
+            fenced: None,
+            in_controlled_shutdown: None,
+            log_dirs: None,
+        };
+        reader.tagged_fields(|tag, field| {
+            match tag {
+                0 => record.fenced = Some(field.i8()?).filter(|fenced| *fenced != 0),
+                1 if version >= 1 => {
+                    record.in_controlled_shutdown = Some(field.i8()?).filter(|state| *state != 0);
+                }
+                2 if version >= 2 => record.log_dirs = field.nullable_array(SchemaReader::uuid)?,
+                _ => return Ok(false),
+            }
+            Ok(true)
+        })?;
+        Ok(record)
+    }
+}
+
+/// Records without fields (`NoOpRecord`, `EndTransactionRecord`).
+#[derive(Debug, Serialize)]
+pub struct EmptyRecord {}
+
+impl EmptyRecord {
+    fn decode(schema: &str, version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version(schema, version, 0)?;
+        reader.skip_tagged_fields()?;
+        Ok(Self {})
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ZkMigrationStateRecord {
+    pub zk_migration_state: i8,
+}
+
+impl ZkMigrationStateRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("ZkMigrationStateRecord", version, 0)?;
+        let record = Self {
+            zk_migration_state: reader.i8()?,
+        };
+        reader.skip_tagged_fields()?;
+        Ok(record)
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BeginTransactionRecord {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl BeginTransactionRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("BeginTransactionRecord", version, 0)?;
+        let mut name = None;
+        reader.tagged_fields(|tag, field| {
+            if tag == 0 {
+                name = field.nullable_string()?;
+                return Ok(true);
+            }
+            Ok(false)
+        })?;
+        Ok(Self { name })
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AbortTransactionRecord {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl AbortTransactionRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("AbortTransactionRecord", version, 0)?;
+        let mut reason = None;
+        reader.tagged_fields(|tag, field| {
+            if tag == 0 {
+                reason = field.nullable_string()?;
+                return Ok(true);
+            }
+            Ok(false)
+        })?;
+        Ok(Self { reason })
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegisterControllerRecord {
+    pub controller_id: i32,
+    pub incarnation_id: String,
+    pub zk_migration_ready: bool,
+    pub end_points: Vec<Endpoint>,
+    pub features: Vec<SupportedFeature>,
+}
+
+impl RegisterControllerRecord {
+    fn decode(version: i16, reader: &mut SchemaReader<'_>) -> Result<Self> {
+        check_version("RegisterControllerRecord", version, 0)?;
+        let record = Self {
+            controller_id: reader.i32()?,
+            incarnation_id: reader.uuid()?,
+            zk_migration_ready: reader.bool()?,
+            end_points: reader.array(decode_endpoint)?,
+            features: reader.array(decode_supported_feature)?,
+        };
+        reader.skip_tagged_fields()?;
+        Ok(record)
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::record_schema::tests::SchemaWriter;
+
+    /// Starts a framed metadata record with the given API key and version.
+    pub fn metadata_writer(api_key: u32, version: u32) -> SchemaWriter {
+        let mut writer = SchemaWriter::new(true);
+        writer
+            .unsigned_varint(0)
+            .unsigned_varint(api_key)
+            .unsigned_varint(version);
+        writer
+    }
+
+    pub fn topic_record(name: &str, id: u8) -> Vec<u8> {
+        metadata_writer(2, 0)
+            .string(name)
+            .uuid([id; 16])
+            .empty_tagged_fields()
+            .finish()
+    }
+
+    #[test]
+    fn metadata_parser_should_render_topic_and_feature_records() {
+        let (key, payload) = parse_cluster_metadata_record(0, Some(&topic_record("orders", 0)));
+        assert!(key.is_none());
+        assert_eq!(
+            payload.as_deref(),
+            Some(
+                r#"{"type":"TOPIC_RECORD","version":0,"data":{"name":"orders","topicId":"AAAAAAAAAAAAAAAAAAAAAA"}}"#
+            )
+        );
+
+        let feature = metadata_writer(12, 0)
+            .string("metadata.version")
+            .i16(21)
+            .empty_tagged_fields()
+            .finish();
+        let (_, payload) = parse_cluster_metadata_record(1, Some(&feature));
+        assert_eq!(
+            payload.as_deref(),
+            Some(
+                r#"{"type":"FEATURE_LEVEL_RECORD","version":0,"data":{"name":"metadata.version","featureLevel":21}}"#
+            )
+        );
+    }
+
+    #[test]
+    fn metadata_parser_should_decode_partition_records_with_tagged_fields() {
+        let partition = metadata_writer(3, 1)
+            .i32(0)
+            .uuid([0; 16])
+            .array_len(2)
+            .i32(1)
+            .i32(2)
+            .array_len(1)
+            .i32(1)
+            .array_len(0)
+            .array_len(0)
+            .i32(1)
+            .i32(4)
+            .i32(7)
+            .array_len(0)
+            .unsigned_varint(1)
+            .unsigned_varint(0)
+            .unsigned_varint(1)
+            .i8(1)
+            .finish();
+        let (_, payload) = parse_cluster_metadata_record(2, Some(&partition));
+        assert_eq!(
+            payload.as_deref(),
+            Some(
+                r#"{"type":"PARTITION_RECORD","version":1,"data":{"partitionId":0,"topicId":"AAAAAAAAAAAAAAAAAAAAAA","replicas":[1,2],"isr":[1],"removingReplicas":[],"addingReplicas":[],"leader":1,"leaderRecoveryState":1,"leaderEpoch":4,"partitionEpoch":7,"directories":[]}}"#
+            )
+        );
+
+        let change = metadata_writer(5, 0)
+            .i32(0)
+            .uuid([0; 16])
+            .unsigned_varint(2)
+            .unsigned_varint(0)
+            .unsigned_varint(9)
+            .array_len(2)
+            .i32(1)
+            .i32(2)
+            .unsigned_varint(1)
+            .unsigned_varint(4)
+            .i32(2)
+            .finish();
+        let (_, payload) = parse_cluster_metadata_record(3, Some(&change));
+        assert_eq!(
+            payload.as_deref(),
+            Some(
+                r#"{"type":"PARTITION_CHANGE_RECORD","version":0,"data":{"partitionId":0,"topicId":"AAAAAAAAAAAAAAAAAAAAAA","isr":[1,2],"leader":2}}"#
+            )
+        );
+    }
+
+    #[test]
+    fn metadata_parser_should_decode_broker_registration() {
+        let broker = metadata_writer(0, 3)
+            .i32(1)
+            .bool(false)
+            .uuid([1; 16])
+            .i64(10)
+            .array_len(1)
+            .string("PLAINTEXT")
+            .string("localhost")
+            .i16(9092)
+            .i16(0)
+            .empty_tagged_fields()
+            .array_len(1)
+            .string("metadata.version")
+            .i16(1)
+            .i16(21)
+            .empty_tagged_fields()
+            .nullable_string(Some("rack-a"))
+            .bool(true)
+            .bool(false)
+            .array_len(0)
+            .empty_tagged_fields()
+            .finish();
+        let record = decode_metadata_record(&broker).expect("decode broker");
+        let MetadataRecord::RegisterBroker(registration) = &record.record else {
+            panic!("unexpected record {record:?}");
+        };
+        assert_eq!(registration.end_points[0].port, 9092);
+        assert_eq!(registration.rack.as_deref(), Some("rack-a"));
+        assert!(registration.fenced);
+    }
+
+    #[test]
+    fn metadata_parser_should_report_unknown_and_malformed_records() {
+        let unknown = metadata_writer(99, 0).finish();
+        let (_, payload) = parse_cluster_metadata_record(5, Some(&unknown));
+        assert_eq!(
+            payload.as_deref(),
+            Some("Error at 5, skipping. configuration error: Unknown metadata id 99")
+        );
+        let (_, payload) = parse_cluster_metadata_record(6, None);
+        assert!(payload.expect("payload").starts_with("Error at 6"));
+    }
+}
@@ -0,0 +1,898 @@
+//! Offline `KRaft` metadata shell (`kafka-metadata-shell.sh`).
+//!
+//! Snapshots and `__cluster_metadata` segments are replayed into an in-memory image, which
+//! is exposed as a read-only file tree under `/image` and browsed with `ls`, `cat`, and
+//! `find`.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{BufRead, Write},
+    path::{Path, PathBuf},
+};
+
+use regex::Regex;
+use serde::Serialize;
+
+use crate::{
+    dump_log,
+    error::{Error, Result},
+    metadata_records::{
+        AccessControlEntryRecord, DelegationTokenRecord, MetadataRecord, PartitionChangeRecord,
+        PartitionRecord, ProducerIdsRecord, QuotaEntity, RegisterBrokerRecord,
+        RegisterControllerRecord, UserScramCredentialRecord, decode_metadata_record,
+    },
+};
+
+const CLUSTER_METADATA_DIR: &str = "__cluster_metadata-0";
+const PROMPT: &str = ">> ";
+
+/// Metadata shell options.
+#[derive(Debug, Clone)]
+pub struct MetadataShellOptions {
+    /// Metadata log directory, or a single snapshot or segment file.
+    pub snapshot: PathBuf,
+    /// Command to run instead of starting the interactive prompt.
+    pub command: Vec<String>,
+}
+
+/// Loads the metadata image and runs one command or an interactive prompt on stdin.
+pub fn metadata_shell(opts: &MetadataShellOptions) -> Result<()> {
+    let stdout = std::io::stdout();
+    let mut out = stdout.lock();
+    let stderr = std::io::stderr();
+    let mut err = stderr.lock();
+
+    let image = load_image(&opts.snapshot, &mut err)?;
+    let mut shell = MetadataShell::new(image.tree(&opts.snapshot));
+    if !opts.command.is_empty() {
+        shell.execute(&opts.command, &mut out, &mut err)?;
+        return Ok(());
+    }
+
+    run_interactive(&mut shell, std::io::stdin().lock(), &mut out, &mut err)
+}
+
+/// Reads commands line by line until `exit` or end of input.
+fn run_interactive(
+    shell: &mut MetadataShell,
+    input: impl BufRead,
+    out: &mut impl Write,
+    err: &mut impl Write,
+) -> Result<()> {
+    writeln!(out, "[ Kafka Metadata Shell ]")?;
+    let mut lines = input.lines();
+    loop {
+        write!(out, "{PROMPT}")?;
+        out.flush()?;
+        let Some(line) = lines.next().transpose()? else {
+            writeln!(out)?;
+            break;
+        };
+        let args = line
+            .split_whitespace()
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+        if !shell.execute(&args, out, err)? {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Replays the newest snapshot and every later log segment found at `path`.
+///
+/// `path` may be a metadata log directory (or its parent log directory) or one
+/// snapshot/segment file. Records that fail to decode are reported to `err` and skipped.
+pub fn load_image(path: &Path, err: &mut impl Write) -> Result<MetadataImage> {
+    let mut image = MetadataImage::default();
+    if path.is_file() {
+        image.last_offset = replay_file(&mut image, path, 0, err)?;
+        return Ok(image);
+    }
+
+    let nested = path.join(CLUSTER_METADATA_DIR);
+    let directory = if nested.is_dir() { nested } else { path.into() };
+    let mut snapshots = Vec::new();
+    let mut segments = Vec::new();
+    for entry in fs::read_dir(&directory)? {
+        let file = entry?.path();
+        let Some(name) = file.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        if let Some(id) = dump_log::kraft_snapshot_id(name)
+            && name.ends_with(".checkpoint")
+        {
+            snapshots.push((id, file));
+        } else if let Some(base) = name
+            .strip_suffix(".log")
+            .and_then(|base| base.parse::<i64>().ok())
+        {
+            segments.push((base, file));
+        }
+    }
+    if snapshots.is_empty() && segments.is_empty() {
+        return Err(Error::Usage(format!(
+            "no KRaft snapshots or log segments found in {}",
+            directory.display()
+        )));
+    }
+    snapshots.sort();
+    segments.sort();
+
+    let mut start_offset = 0;
+    if let Some(((end_offset, _), snapshot)) = snapshots.last() {
+        replay_file(&mut image, snapshot, 0, err)?;
+        start_offset = *end_offset;
+        image.last_offset = Some(end_offset - 1);
+    }
+    for (_, segment) in &segments {
+        if let Some(last) = replay_file(&mut image, segment, start_offset, err)? {
+            image.last_offset = Some(last);
+        }
+    }
+    Ok(image)
+}
+
+/// Replays records at or after `start_offset`, returning the last replayed offset.
+fn replay_file(
+    image: &mut MetadataImage,
+    path: &Path,
+    start_offset: i64,
+    err: &mut impl Write,
+) -> Result<Option<i64>> {
+    let mut last_offset = None;
+    for (offset, value) in dump_log::read_record_values(path)? {
+        if offset < start_offset {
+            continue;
+        }
+        let Some(value) = value else {
+            continue;
+        };
+        match decode_metadata_record(&value) {
+            Ok(record) => image.replay(record.record),
+            Err(error) => writeln!(
+                err,
+                "Skipping record at offset {offset} in {}: {error}",
+                path.display()
+            )?,
+        }
+        last_offset = Some(offset);
+    }
+    Ok(last_offset)
+}
+
+/// Cluster metadata state reconstructed from metadata records.
+#[derive(Debug, Default)]
+pub struct MetadataImage {
+    last_offset: Option<i64>,
+    features: BTreeMap<String, i16>,
+    brokers: BTreeMap<i32, RegisterBrokerRecord>,
+    controllers: BTreeMap<i32, RegisterControllerRecord>,
+    topics: BTreeMap<String, TopicImage>,
+    topic_names: BTreeMap<String, String>,
+    configs: BTreeMap<(&'static str, String), BTreeMap<String, String>>,
+    acls: BTreeMap<String, AccessControlEntryRecord>,
+    client_quotas: BTreeMap<String, BTreeMap<String, f64>>,
+    producer_ids: Option<ProducerIdsRecord>,
+    delegation_tokens: BTreeMap<String, DelegationTokenRecord>,
+    scram_credentials: BTreeMap<(&'static str, String), UserScramCredentialRecord>,
+    zk_migration_state: Option<i8>,
+    pending_transaction: Option<Vec<MetadataRecord>>,
+}
+
+#[derive(Debug)]
+struct TopicImage {
+    id: String,
+    partitions: BTreeMap<i32, PartitionRecord>,
+}
+
+impl MetadataImage {
+    /// Applies one record; records inside a metadata transaction wait for its end marker.
+    pub fn replay(&mut self, record: MetadataRecord) {
+        match record {
+            MetadataRecord::BeginTransaction(_) => self.pending_transaction = Some(Vec::new()),
+            MetadataRecord::EndTransaction(_) => {
+                for record in self.pending_transaction.take().unwrap_or_default() {
+                    self.apply(record);
+                }
+            }
+            MetadataRecord::AbortTransaction(_) => self.pending_transaction = None,
+            record => match &mut self.pending_transaction {
+                Some(pending) => pending.push(record),
+                None => self.apply(record),
+            },
+        }
+    }
+
+    fn apply(&mut self, record: MetadataRecord) {
+        match record {
+            MetadataRecord::RegisterBroker(registration) => {
+                self.brokers.insert(registration.broker_id, registration);
+            }
+            MetadataRecord::UnregisterBroker(record) => {
+                self.brokers.remove(&record.broker_id);
+            }
+            MetadataRecord::FenceBroker(record) => {
+                if let Some(broker) = self.brokers.get_mut(&record.id) {
+                    broker.fenced = true;
+                }
+            }
+            MetadataRecord::UnfenceBroker(record) => {
+                if let Some(broker) = self.brokers.get_mut(&record.id) {
+                    broker.fenced = false;
+                }
+            }
+            MetadataRecord::BrokerRegistrationChange(change) => {
+                if let Some(broker) = self.brokers.get_mut(&change.broker_id) {
+                    match change.fenced {
+                        Some(1) => broker.fenced = true,
+                        Some(-1) => broker.fenced = false,
+                        _ => {}
+                    }
+                    if let Some(state) = change.in_controlled_shutdown {
+                        broker.in_controlled_shutdown = Some(state == 1);
+                    }
+                    if change.log_dirs.is_some() {
+                        broker.log_dirs = change.log_dirs;
+                    }
+                }
+            }
+            MetadataRecord::RegisterController(registration) => {
+                self.controllers
+                    .insert(registration.controller_id, registration);
+            }
+            MetadataRecord::Topic(topic) => {
+                self.topic_names
+                    .insert(topic.topic_id.clone(), topic.name.clone());
+                self.topics.insert(
+                    topic.name,
+                    TopicImage {
+                        id: topic.topic_id,
+                        partitions: BTreeMap::new(),
+                    },
+                );
+            }
+            MetadataRecord::Partition(partition) => {
+                if let Some(topic) = self.topic_by_id(&partition.topic_id) {
+                    topic.partitions.insert(partition.partition_id, partition);
+                }
+            }
+            MetadataRecord::PartitionChange(change) => self.apply_partition_change(change),
+            MetadataRecord::RemoveTopic(record) => {
+                if let Some(name) = self.topic_names.remove(&record.topic_id) {
+                    self.topics.remove(&name);
+                    self.configs.remove(&("TOPIC", name));
+                }
+            }
+            record => self.apply_security_and_config(record),
+        }
+    }
+
+    fn apply_security_and_config(&mut self, record: MetadataRecord) {
+        match record {
+            MetadataRecord::Config(config) => {
+                let key = (
+                    config_resource_type_name(config.resource_type),
+                    config.resource_name,
+                );
+                let entries = self.configs.entry(key.clone()).or_default();
+                match config.value {
+                    Some(value) => {
+                        entries.insert(config.name, value);
+                    }
+                    None => {
+                        entries.remove(&config.name);
+                    }
+                }
+                if entries.is_empty() {
+                    self.configs.remove(&key);
+                }
+            }
+            MetadataRecord::AccessControlEntry(acl) => {
+                self.acls.insert(acl.id.clone(), acl);
+            }
+            MetadataRecord::RemoveAccessControlEntry(record) => {
+                self.acls.remove(&record.id);
+            }
+            MetadataRecord::FeatureLevel(feature) if feature.feature_level == 0 => {
+                self.features.remove(&feature.name);
+            }
+            MetadataRecord::FeatureLevel(feature) => {
+                self.features.insert(feature.name, feature.feature_level);
+            }
+            MetadataRecord::ClientQuota(quota) => {
+                let entity = quota_entity_name(&quota.entity);
+                let values = self.client_quotas.entry(entity.clone()).or_default();
+                if quota.remove {
+                    values.remove(&quota.key);
+                } else {
+                    values.insert(quota.key, quota.value);
+                }
+                if values.is_empty() {
+                    self.client_quotas.remove(&entity);
+                }
+            }
+            MetadataRecord::ProducerIds(record) => self.producer_ids = Some(record),
+            MetadataRecord::DelegationToken(token) => {
+                self.delegation_tokens.insert(token.token_id.clone(), token);
+            }
+            MetadataRecord::RemoveDelegationToken(record) => {
+                self.delegation_tokens.remove(&record.token_id);
+            }
+            MetadataRecord::UserScramCredential(credential) => {
+                let key = (
+                    scram_mechanism_name(credential.mechanism),
+                    credential.name.clone(),
+                );
+                self.scram_credentials.insert(key, credential);
+            }
+            MetadataRecord::RemoveUserScramCredential(record) => {
+                self.scram_credentials
+                    .remove(&(scram_mechanism_name(record.mechanism), record.name));
+            }
+            MetadataRecord::ZkMigrationState(record) => {
+                self.zk_migration_state = Some(record.zk_migration_state);
+            }
+            _ => {}
+        }
+    }
+
+    fn topic_by_id(&mut self, topic_id: &str) -> Option<&mut TopicImage> {
+        let name = self.topic_names.get(topic_id)?;
+        self.topics.get_mut(name)
+    }
+
+    /// Applies a partition delta the way `PartitionRegistration.merge` does.
+    fn apply_partition_change(&mut self, change: PartitionChangeRecord) {
+        let Some(partition) = self
+            .topic_by_id(&change.topic_id)
+            .and_then(|topic| topic.partitions.get_mut(&change.partition_id))
+        else {
+            return;
+        };
+        if let Some(leader) = change.leader {
+            partition.leader = leader;
+            partition.leader_epoch += 1;
+        }
+        let replace = |target: &mut Vec<i32>, update: Option<Vec<i32>>| {
+            if let Some(update) = update {
+                *target = update;
+            }
+        };
+        replace(&mut partition.isr, change.isr);
+        replace(&mut partition.replicas, change.replicas);
+        replace(&mut partition.removing_replicas, change.removing_replicas);
+        replace(&mut partition.adding_replicas, change.adding_replicas);
+        if let Some(state) = change.leader_recovery_state {
+            partition.leader_recovery_state = state;
+        }
+        if change.directories.is_some() {
+            partition.directories = change.directories;
+        }
+        if change.eligible_leader_replicas.is_some() {
+            partition.eligible_leader_replicas = change.eligible_leader_replicas;
+        }
+        if change.last_known_elr.is_some() {
+            partition.last_known_elr = change.last_known_elr;
+        }
+        partition.partition_epoch += 1;
+    }
+
+    /// Renders the image as the shell's file tree.
+    fn tree(&self, source: &Path) -> Node {
+        let mut root = Node::directory();
+        root.insert(&["local", "source"], source.display().to_string());
+        if let Some(offset) = self.last_offset {
+            root.insert(&["local", "lastOffset"], offset.to_string());
+        }
+        for (name, level) in &self.features {
+            root.insert(&["image", "features", name], level.to_string());
+        }
+        for (id, broker) in &self.brokers {
+            root.insert(
+                &["image", "cluster", "brokers", &id.to_string()],
+                json(broker),
+            );
+        }
+        for (id, controller) in &self.controllers {
+            let id = id.to_string();
+            root.insert(&["image", "cluster", "controllers", &id], json(controller));
+        }
+        for (name, topic) in &self.topics {
+            root.insert(&["image", "topics", "byName", name, "id"], topic.id.clone());
+            root.insert(&["image", "topics", "byId", &topic.id], name.clone());
+            for (partition_id, partition) in &topic.partitions {
+                let partition_id = partition_id.to_string();
+                let path = ["image", "topics", "byName", name, &partition_id];
+                root.insert(&path, json(partition));
+            }
+        }
+        for ((resource_type, resource_name), entries) in &self.configs {
+            let resource_name = if resource_name.is_empty() {
+                "<default>"
+            } else {
+                resource_name
+            };
+            for (key, value) in entries {
+                let path = ["image", "configs", resource_type, resource_name, key];
+                root.insert(&path, value.clone());
+            }
+        }
+        for (id, acl) in &self.acls {
+            root.insert(&["image", "acls", "byId", id], json(acl));
+        }
+        for (entity, values) in &self.client_quotas {
+            for (key, value) in values {
+                let path = ["image", "clientQuotas", entity, key];
+                root.insert(&path, value.to_string());
+            }
+        }
+        if let Some(producer_ids) = &self.producer_ids {
+            root.insert(&["image", "producerIds"], json(producer_ids));
+        }
+        for (id, token) in &self.delegation_tokens {
+            root.insert(&["image", "delegationTokens", id], json(token));
+        }
+        for ((mechanism, user), credential) in &self.scram_credentials {
+            root.insert(&["image", "scram", mechanism, user], json(credential));
+        }
+        if let Some(state) = self.zk_migration_state {
+            root.insert(&["image", "zkMigrationState"], state.to_string());
+        }
+        root
+    }
+}
+
+fn json(value: &impl Serialize) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_default()
+}
+
+/// `ConfigResource.Type` names used for `/image/configs` directories.
+const fn config_resource_type_name(id: i8) -> &'static str {
+    match id {
+        2 => "TOPIC",
+        4 => "BROKER",
+        8 => "BROKER_LOGGER",
+        16 => "CLIENT_METRICS",
+        32 => "GROUP",
+        _ => "UNKNOWN",
+    }
+}
+
+const fn scram_mechanism_name(id: i8) -> &'static str {
+    match id {
+        1 => "SCRAM-SHA-256",
+        2 => "SCRAM-SHA-512",
+        _ => "UNKNOWN",
+    }
+}
+
+/// Formats a quota entity like `user=alice,client-id=<default>`.
+fn quota_entity_name(entity: &[QuotaEntity]) -> String {
+    let mut parts = entity.to_vec();
+    parts.sort();
+    parts
+        .iter()
+        .map(|part| {
+            format!(
+                "{}={}",
+                part.entity_type,
+                part.entity_name.as_deref().unwrap_or("<default>")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Read-only file tree backing the shell.
+#[derive(Debug)]
+enum Node {
+    Directory(BTreeMap<String, Self>),
+    File(String),
+}
+
+impl Node {
+    const fn directory() -> Self {
+        Self::Directory(BTreeMap::new())
+    }
+
+    fn insert(&mut self, path: &[&str], contents: String) {
+        let Self::Directory(children) = self else {
+            return;
+        };
+        match path {
+            [] => {}
+            [name] => {
+                children.insert((*name).to_owned(), Self::File(contents));
+            }
+            [name, rest @ ..] => children
+                .entry((*name).to_owned())
+                .or_insert_with(Self::directory)
+                .insert(rest, contents),
+        }
+    }
+
+    fn get(&self, path: &[String]) -> Option<&Self> {
+        path.iter().try_fold(self, |node, name| match node {
+            Self::Directory(children) => children.get(name),
+            Self::File(_) => None,
+        })
+    }
+}
+
+/// Shell state: the image tree and the working directory.
+struct MetadataShell {
+    root: Node,
+    cwd: Vec<String>,
+}
+
+impl MetadataShell {
+    const fn new(root: Node) -> Self {
+        Self {
+            root,
+            cwd: Vec::new(),
+        }
+    }
+
+    /// Runs one command line; returns `false` when the shell should exit.
+    fn execute(
+        &mut self,
+        args: &[String],
+        out: &mut impl Write,
+        err: &mut impl Write,
+    ) -> Result<bool> {
+        let Some((command, operands)) = args.split_first() else {
+            return Ok(true);
+        };
+        match command.as_str() {
+            "ls" => self.ls(operands, out, err)?,
+            "cat" => self.cat(operands, out, err)?,
+            "find" => self.find(operands, out, err)?,
+            "cd" => self.cd(operands, err)?,
+            "pwd" => writeln!(out, "{}", display_path(&self.cwd))?,
+            "help" => {
+                for line in [
+                    "cat [paths...]   Show the contents of metadata files.",
+                    "cd [path]        Move into a different directory.",
+                    "exit             Exit the metadata shell.",
+                    "find [paths...]  Search for nodes in the directory hierarchy.",
+                    "help             Display this help message.",
+                    "ls [paths...]    List metadata nodes.",
+                    "pwd              Print the current working directory.",
+                ] {
+                    writeln!(out, "{line}")?;
+                }
+            }
+            "exit" | "quit" => return Ok(false),
+            other => writeln!(
+                err,
+                "Unknown command {other}. Type help to see a list of commands."
+            )?,
+        }
+        Ok(true)
+    }
+
+    fn ls(&self, operands: &[String], out: &mut impl Write, err: &mut impl Write) -> Result<()> {
+        let targets = if operands.is_empty() {
+            vec![".".to_owned()]
+        } else {
+            operands.to_vec()
+        };
+        let mut first = true;
+        for target in &targets {
+            let paths = self.expand(target);
+            if paths.is_empty() {
+                writeln!(err, "ls: {target}: no such file or directory.")?;
+            }
+            for path in paths {
+                match self.root.get(&path) {
+                    Some(Node::Directory(children)) => {
+                        if targets.len() > 1 {
+                            if !first {
+                                writeln!(out)?;
+                            }
+                            writeln!(out, "{}:", display_path(&path))?;
+                        }
+                        for name in children.keys() {
+                            writeln!(out, "{name}")?;
+                        }
+                    }
+                    Some(Node::File(_)) => writeln!(out, "{}", display_path(&path))?,
+                    None => {}
+                }
+                first = false;
+            }
+        }
+        Ok(())
+    }
+
+    fn cat(&self, operands: &[String], out: &mut impl Write, err: &mut impl Write) -> Result<()> {
+        for target in operands {
+            let paths = self.expand(target);
+            if paths.is_empty() {
+                writeln!(err, "cat: {target}: no such file or directory.")?;
+            }
+            for path in paths {
+                match self.root.get(&path) {
+                    Some(Node::File(contents)) => writeln!(out, "{contents}")?,
+                    Some(Node::Directory(_)) => {
+                        writeln!(err, "cat: {}: is a directory.", display_path(&path))?;
+                    }
+                    None => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn find(&self, operands: &[String], out: &mut impl Write, err: &mut impl Write) -> Result<()> {
+        let targets = if operands.is_empty() {
+            vec![".".to_owned()]
+        } else {
+            operands.to_vec()
+        };
+        for target in &targets {
+            let paths = self.expand(target);
+            if paths.is_empty() {
+                writeln!(err, "find: {target}: no such file or directory.")?;
+            }
+            for path in paths {
+                if let Some(node) = self.root.get(&path) {
+                    write_find(node, &mut path.clone(), out)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn cd(&mut self, operands: &[String], err: &mut impl Write) -> Result<()> {
+        let target = operands.first().map_or("/", String::as_str);
+        let paths = self.expand(target);
+        match paths.as_slice() {
+            [path] if matches!(self.root.get(path), Some(Node::Directory(_))) => {
+                self.cwd.clone_from(path);
+            }
+            [path] => writeln!(err, "cd: {}: not a directory.", display_path(path))?,
+            [] => writeln!(err, "cd: {target}: no such directory.")?,
+            _ => writeln!(err, "cd: {target}: more than one directory matches.")?,
+        }
+        Ok(())
+    }
+
+    /// Resolves a path with `.`, `..`, and `*`/`?` globs into existing node paths.
+    fn expand(&self, target: &str) -> Vec<Vec<String>> {
+        let mut paths = vec![if target.starts_with('/') {
+            Vec::new()
+        } else {
+            self.cwd.clone()
+        }];
+        for component in target.split('/').filter(|part| !part.is_empty()) {
+            paths = match component {
+                "." => paths,
+                ".." => paths
+                    .into_iter()
+                    .map(|mut path| {
+                        path.pop();
+                        path
+                    })
+                    .collect(),
+                pattern if pattern.contains(['*', '?']) => {
+                    let matcher = glob_regex(pattern);
+                    paths
+                        .into_iter()
+                        .flat_map(|path| {
+                            let names = match self.root.get(&path) {
+                                Some(Node::Directory(children)) => children
+                                    .keys()
+                                    .filter(|name| matcher.is_match(name))
+                                    .cloned()
+                                    .collect(),
+                                _ => Vec::new(),
+                            };
+                            names.into_iter().map(move |name| {
+                                let mut child = path.clone();
+                                child.push(name);
+                                child
+                            })
+                        })
+                        .collect()
+                }
+                name => paths
+                    .into_iter()
+                    .map(|mut path| {
+                        path.push(name.to_owned());
+                        path
+                    })
+                    .collect(),
+            };
+        }
+        paths.retain(|path| self.root.get(path).is_some());
+        paths
+    }
+}
+
+fn glob_regex(pattern: &str) -> Regex {
+    let mut expression = String::from("^");
+    for character in pattern.chars() {
+        match character {
+            '*' => expression.push_str(".*"),
+            '?' => expression.push('.'),
+            other => expression.push_str(&regex::escape(&other.to_string())),
+        }
+    }
+    expression.push('$');
+    Regex::new(&expression).expect("escaped glob is a valid regex")
+}
+
+fn write_find(node: &Node, path: &mut Vec<String>, out: &mut impl Write) -> Result<()> {
+    writeln!(out, "{}", display_path(path))?;
+    if let Node::Directory(children) = node {
+        for (name, child) in children {
+            path.push(name.clone());
+            write_find(child, path, out)?;
+            path.pop();
+        }
+    }
+    Ok(())
+}
+
+fn display_path(path: &[String]) -> String {
+    format!("/{}", path.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::metadata_records::{ConfigRecord, FeatureLevelRecord, TopicRecord};
+
+    fn run(shell: &mut MetadataShell, line: &str) -> (String, String) {
+        let args = line
+            .split_whitespace()
+            .map(str::to_owned)
+            .collect::<Vec<_>>();
+        let mut out = Vec::new();
+        let mut err = Vec::new();
+        shell.execute(&args, &mut out, &mut err).expect("execute");
+        (
+            String::from_utf8(out).expect("utf8"),
+            String::from_utf8(err).expect("utf8"),
+        )
+    }
+
+    fn partition(partition_id: i32, leader: i32) -> PartitionRecord {
+        PartitionRecord {
+            partition_id,
+            topic_id: "topic-a".into(),
+            replicas: vec![1, 2],
+            isr: vec![1, 2],
+            removing_replicas: Vec::new(),
+            adding_replicas: Vec::new(),
+            leader,
+            leader_recovery_state: 0,
+            leader_epoch: 0,
+            partition_epoch: 0,
+            directories: None,
+            eligible_leader_replicas: None,
+            last_known_elr: None,
+        }
+    }
+
+    fn sample_image() -> MetadataImage {
+        let mut image = MetadataImage::default();
+        image.replay(MetadataRecord::FeatureLevel(FeatureLevelRecord {
+            name: "metadata.version".into(),
+            feature_level: 21,
+        }));
+        image.replay(MetadataRecord::Topic(TopicRecord {
+            name: "orders".into(),
+            topic_id: "topic-a".into(),
+        }));
+        image.replay(MetadataRecord::Partition(partition(0, 1)));
+        image.replay(MetadataRecord::Partition(partition(1, 2)));
+        image.replay(MetadataRecord::Config(ConfigRecord {
+            resource_type: 2,
+            resource_name: "orders".into(),
+            name: "retention.ms".into(),
+            value: Some("1000".into()),
+        }));
+        image
+    }
+
+    #[test]
+    fn image_should_apply_partition_changes_and_topic_removal() {
+        let mut image = sample_image();
+        image.replay(MetadataRecord::PartitionChange(PartitionChangeRecord {
+            partition_id: 0,
+            topic_id: "topic-a".into(),
+            isr: Some(vec![2]),
+            leader: Some(2),
+            ..PartitionChangeRecord::default()
+        }));
+        let partition = &image.topics["orders"].partitions[&0];
+        assert_eq!(partition.leader, 2);
+        assert_eq!(partition.isr, vec![2]);
+        assert_eq!(partition.leader_epoch, 1);
+        assert_eq!(partition.partition_epoch, 1);
+
+        image.replay(MetadataRecord::RemoveTopic(
+            crate::metadata_records::RemoveTopicRecord {
+                topic_id: "topic-a".into(),
+            },
+        ));
+        assert!(image.topics.is_empty());
+        assert!(image.configs.is_empty());
+    }
+
+    #[test]
+    fn image_should_discard_aborted_metadata_transactions() {
+        let mut image = MetadataImage::default();
+        image.replay(MetadataRecord::BeginTransaction(
+            crate::metadata_records::BeginTransactionRecord { name: None },
+        ));
+        image.replay(MetadataRecord::FeatureLevel(FeatureLevelRecord {
+            name: "kraft.version".into(),
+            feature_level: 1,
+        }));
+        assert!(image.features.is_empty());
+        image.replay(MetadataRecord::AbortTransaction(
+            crate::metadata_records::AbortTransactionRecord { reason: None },
+        ));
+        assert!(image.features.is_empty());
+        assert!(image.pending_transaction.is_none());
+    }
+
+    #[test]
+    fn shell_should_list_cat_and_find_image_nodes() {
+        let mut shell = MetadataShell::new(sample_image().tree(Path::new("meta")));
+        assert_eq!(
+            run(&mut shell, "ls /image").0,
+            "configs\nfeatures\ntopics\n"
+        );
+        assert_eq!(
+            run(&mut shell, "cat /image/features/metadata.version").0,
+            "21\n"
+        );
+        assert_eq!(
+            run(&mut shell, "cat /image/configs/TOPIC/orders/retention.ms").0,
+            "1000\n"
+        );
+
+        run(&mut shell, "cd /image/topics/byName");
+        assert_eq!(run(&mut shell, "pwd").0, "/image/topics/byName\n");
+        let (out, _) = run(&mut shell, "cat orders/1");
+        assert!(out.contains("\"leader\": 2"), "{out}");
+        assert_eq!(
+            run(&mut shell, "find orders").0,
+            "/image/topics/byName/orders\n/image/topics/byName/orders/0\n/image/topics/byName/orders/1\n/image/topics/byName/orders/id\n"
+        );
+        assert_eq!(
+            run(&mut shell, "ls ../byId/*").0,
+            "/image/topics/byId/topic-a\n"
+        );
+    }
+
+    #[test]
+    fn shell_should_report_missing_paths_and_unknown_commands() {
+        let mut shell = MetadataShell::new(sample_image().tree(Path::new("meta")));
+        let (_, err) = run(&mut shell, "cat /image/missing");
+        assert!(err.contains("no such file or directory"), "{err}");
+        let (_, err) = run(&mut shell, "cat /image");
+        assert!(err.contains("is a directory"), "{err}");
+        let (_, err) = run(&mut shell, "frobnicate");
+        assert!(err.contains("Unknown command frobnicate"), "{err}");
+
+        let mut out = Vec::new();
+        run_interactive(
+            &mut shell,
+            &b"pwd\nexit\nls\n"[..],
+            &mut out,
+            &mut Vec::new(),
+        )
+        .expect("interactive");
+        assert_eq!(
+            String::from_utf8(out).expect("utf8"),
+            "[ Kafka Metadata Shell ]\n>> /\n>> "
+        );
+    }
+}
@@ -0,0 +1,333 @@
+//! SASL/OAUTHBEARER tokens for the pure-Rust admin transport.
+//!
+//! The properties mirror librdkafka, so one command config file serves both transports:
+//!
+//! - `sasl.oauthbearer.method=oidc` fetches tokens with the OAuth client-credentials grant from
+//!   `sasl.oauthbearer.token.endpoint.url`, using `sasl.oauthbearer.client.id`,
+//!   `sasl.oauthbearer.client.secret`, and the optional `sasl.oauthbearer.scope`.
+//! - A `file://` token endpoint URL reads a pre-fetched static token from that file instead.
+//! - The default method builds the unsecured JWT that librdkafka derives from
+//!   `sasl.oauthbearer.config` (`principal=... scope=... lifeSeconds=...`), for test clusters.
+//!
+//! `sasl.oauthbearer.extensions` (`key=value` pairs separated by commas) is sent with every
+//! token.
+
+use std::{collections::HashMap, sync::Arc, time::Duration};
+
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+use krafka::{
+    KrafkaError,
+    auth::{AuthConfig, OAuthBearerToken, TlsConfig},
+};
+use serde::Deserialize;
+
+use crate::error::{Error, Result};
+
+const TOKEN_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+const DEFAULT_UNSECURED_LIFETIME_SECONDS: i64 = 3600;
+
+/// Builds the OAUTHBEARER configuration, over TLS when `tls` is set.
+pub fn oauthbearer_auth(
+    values: &HashMap<String, String>,
+    tls: Option<TlsConfig>,
+) -> Result<AuthConfig> {
+    let extensions = values
+        .get("sasl.oauthbearer.extensions")
+        .map(|extensions| key_values(extensions, ','))
+        .transpose()?
+        .unwrap_or_default();
+    let method = values
+        .get("sasl.oauthbearer.method")
+        .map_or("default", String::as_str)
+        .to_ascii_lowercase();
+    let endpoint = values.get("sasl.oauthbearer.token.endpoint.url");
+    if let Some(path) = endpoint.and_then(|url| url.strip_prefix("file://")) {
+        let source = TokenSource::File(path.to_owned());
+        return Ok(provider_auth(source, extensions, tls));
+    }
+    match method.as_str() {
+        "oidc" => {
+            let required = |key: &str| {
+                values
+                    .get(key)
+                    .cloned()
+                    .ok_or_else(|| Error::Config(format!("{key} is required for OIDC")))
+            };
+            let source = TokenSource::ClientCredentials {
+                endpoint: required("sasl.oauthbearer.token.endpoint.url")?,
+                client_id: required("sasl.oauthbearer.client.id")?,
+                client_secret: required("sasl.oauthbearer.client.secret")?,
+                scope: values.get("sasl.oauthbearer.scope").cloned(),
+            };
+            Ok(provider_auth(source, extensions, tls))
+        }
+        "default" => {
+            let config = values
+                .get("sasl.oauthbearer.config")
+                .ok_or_else(|| {
+                    Error::Config(
+                        "OAUTHBEARER requires sasl.oauthbearer.method=oidc, a file:// token endpoint, or sasl.oauthbearer.config"
+                            .into(),
+                    )
+                })?;
+            let token = with_extensions(unsecured_token(config)?, &extensions);
+            Ok(match tls {
+                Some(tls) => AuthConfig::sasl_oauthbearer_token_ssl(token, tls),
+                None => AuthConfig::sasl_oauthbearer_token(token),
+            })
+        }
+        method => Err(Error::Config(format!(
+            "unsupported sasl.oauthbearer.method: {method}"
+        ))),
+    }
+}
+
+/// Where a refreshable token comes from; fetched again whenever krafka needs a fresh one.
+#[derive(Debug)]
+enum TokenSource {
+    File(String),
+    ClientCredentials {
+        endpoint: String,
+        client_id: String,
+        client_secret: String,
+        scope: Option<String>,
+    },
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    expires_in: Option<i64>,
+}
+
+impl TokenSource {
+    fn fetch(&self) -> std::result::Result<OAuthBearerToken, String> {
+        match self {
+            Self::File(path) => std::fs::read_to_string(path)
+                .map(|token| OAuthBearerToken::new(token.trim()))
+                .map_err(|error| format!("failed to read OAUTHBEARER token from {path}: {error}")),
+            Self::ClientCredentials {
+                endpoint,
+                client_id,
+                client_secret,
+                scope,
+            } => {
+                let agent = ureq::Agent::new_with_config(
+                    ureq::Agent::config_builder()
+                        .timeout_global(Some(TOKEN_REQUEST_TIMEOUT))
+                        .build(),
+                );
+                let credentials = base64::engine::general_purpose::STANDARD
+                    .encode(format!("{client_id}:{client_secret}"));
+                let mut form = vec![("grant_type", "client_credentials")];
+                if let Some(scope) = scope {
+                    form.push(("scope", scope));
+                }
+                let body = agent
+                    .post(endpoint)
+                    .header("Authorization", format!("Basic {credentials}"))
+                    .header("Accept", "application/json")
+                    .send_form(form)
+                    .and_then(|mut response| response.body_mut().read_to_string())
+                    .map_err(|error| format!("token request to {endpoint} failed: {error}"))?;
+                let response = serde_json::from_str::<TokenResponse>(&body)
+                    .map_err(|error| format!("invalid token response from {endpoint}: {error}"))?;
+                let token = OAuthBearerToken::new(response.access_token);
+                Ok(match response.expires_in {
+                    Some(seconds) => token.with_lifetime_ms(epoch_millis() + seconds * 1000),
+                    None => token,
+                })
+            }
+        }
+    }
+}
+
+fn provider_auth(
+    source: TokenSource,
+    extensions: Vec<(String, String)>,
+    tls: Option<TlsConfig>,
+) -> AuthConfig {
+    let source = Arc::new(source);
+    let extensions = Arc::new(extensions);
+    let provider = move || {
+        let source = Arc::clone(&source);
+        let extensions = Arc::clone(&extensions);
+        async move {
+            // Token endpoints are reached with a blocking HTTP client.
+            let token = tokio::task::spawn_blocking(move || source.fetch())
+                .await
+                .map_err(|error| KrafkaError::auth(error.to_string()))?
+                .map_err(KrafkaError::auth)?;
+            Ok(with_extensions(token, &extensions))
+        }
+    };
+    match tls {
+        Some(tls) => AuthConfig::sasl_oauthbearer_provider_ssl(provider, tls),
+        None => AuthConfig::sasl_oauthbearer_provider(provider),
+    }
+}
+
+fn with_extensions(token: OAuthBearerToken, extensions: &[(String, String)]) -> OAuthBearerToken {
+    extensions.iter().fold(token, |token, (key, value)| {
+        token.with_extension(key, value)
+    })
+}
+
+/// Builds librdkafka's unsecured JWT from `sasl.oauthbearer.config`.
+fn unsecured_token(config: &str) -> Result<OAuthBearerToken> {
+    let options = key_values(config, ' ')?;
+    let (jwt, expires_ms) = unsecured_jwt(&options)?;
+    Ok(options
+        .iter()
+        .filter_map(|(key, value)| Some((key.strip_prefix("extension_")?, value)))
+        .fold(
+            OAuthBearerToken::new(jwt).with_lifetime_ms(expires_ms),
+            |token, (key, value)| token.with_extension(key, value),
+        ))
+}
+
+/// Returns the unsigned JWT and its expiry in epoch milliseconds.
+fn unsecured_jwt(options: &[(String, String)]) -> Result<(String, i64)> {
+    let option = |name: &str| {
+        options
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    };
+    let principal = option("principal")
+        .ok_or_else(|| Error::Config("sasl.oauthbearer.config requires principal=<name>".into()))?;
+    let lifetime = option("lifeSeconds")
+        .map(|seconds| {
+            seconds
+                .parse::<i64>()
+                .ok()
+                .filter(|seconds| *seconds > 0)
+                .ok_or_else(|| Error::Config(format!("invalid lifeSeconds: {seconds}")))
+        })
+        .transpose()?
+        .unwrap_or(DEFAULT_UNSECURED_LIFETIME_SECONDS);
+    let issued = epoch_millis() / 1000;
+    let mut claims = serde_json::Map::new();
+    claims.insert(
+        option("principalClaimName").unwrap_or("sub").to_owned(),
+        principal.into(),
+    );
+    claims.insert("iat".into(), issued.into());
+    claims.insert("exp".into(), (issued + lifetime).into());
+    if let Some(scope) = option("scope") {
+        claims.insert("scope".into(), scope.split(',').collect::<Vec<_>>().into());
+    }
+    let jwt = format!(
+        "{}.{}.",
+        URL_SAFE_NO_PAD.encode(br#"{"alg":"none"}"#),
+        URL_SAFE_NO_PAD.encode(serde_json::Value::Object(claims).to_string())
+    );
+    Ok((jwt, (issued + lifetime) * 1000))
+}
+
+fn key_values(text: &str, separator: char) -> Result<Vec<(String, String)>> {
+    text.split(separator)
+        .map(str::trim)
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            pair.split_once('=')
+                .map(|(key, value)| (key.trim().to_owned(), value.trim().to_owned()))
+                .ok_or_else(|| Error::Config(format!("expected key=value, got {pair}")))
+        })
+        .collect()
+}
+
+fn epoch_millis() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{Read, Write};
+
+    use super::*;
+
+    #[test]
+    fn unsecured_tokens_should_carry_principal_scope_and_lifetime() {
+        let options =
+            key_values("principal=admin scope=read,write lifeSeconds=60", ' ').expect("options");
+        let (jwt, expires_ms) = unsecured_jwt(&options).expect("token");
+        let claims = jwt
+            .split('.')
+            .nth(1)
+            .map(|claims| URL_SAFE_NO_PAD.decode(claims).expect("base64 claims"))
+            .map(|claims| serde_json::from_slice::<serde_json::Value>(&claims).expect("claims"))
+            .expect("claims segment");
+        assert_eq!(claims["sub"], "admin");
+        assert_eq!(claims["scope"], serde_json::json!(["read", "write"]));
+        let issued = claims["iat"].as_i64().expect("iat");
+        assert_eq!(claims["exp"].as_i64(), Some(issued + 60));
+        assert_eq!(expires_ms, (issued + 60) * 1000);
+        assert!(jwt.ends_with('.'));
+        assert!(unsecured_token("scope=read").is_err());
+        assert!(unsecured_token("principal=admin lifeSeconds=0").is_err());
+    }
+
+    #[test]
+    fn client_credentials_should_post_the_grant_and_read_the_token() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("listener");
+        let endpoint = format!("http://{}/token", listener.local_addr().expect("address"));
+        let server = std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().expect("connection");
+            let mut request = Vec::new();
+            let mut buffer = [0; 4096];
+            while !String::from_utf8_lossy(&request).contains("grant_type") {
+                let read = stream.read(&mut buffer).expect("request");
+                request.extend_from_slice(&buffer[..read]);
+            }
+            let body = r#"{"access_token":"jwt-value","expires_in":300}"#;
+            write!(
+                stream,
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+                body.len()
+            )
+            .expect("response");
+            String::from_utf8_lossy(&request).into_owned()
+        });
+
+        let token = TokenSource::ClientCredentials {
+            endpoint,
+            client_id: "cli".into(),
+            client_secret: "secret".into(),
+            scope: Some("kafka".into()),
+        }
+        .fetch()
+        .expect("token");
+        let request = server.join().expect("server");
+        assert!(request.starts_with("POST /token"), "{request}");
+        assert!(request.contains("Basic Y2xpOnNlY3JldA=="), "{request}");
+        assert!(
+            request.contains("grant_type=client_credentials&scope=kafka"),
+            "{request}"
+        );
+        assert!(
+            token
+                .lifetime_ms()
+                .is_some_and(|expiry| expiry > epoch_millis())
+        );
+    }
+
+    #[test]
+    fn oauthbearer_auth_should_require_oidc_client_credentials() {
+        let mut values = HashMap::from([
+            ("sasl.oauthbearer.method".into(), "oidc".into()),
+            (
+                "sasl.oauthbearer.token.endpoint.url".into(),
+                "https://idp.example/token".into(),
+            ),
+            ("sasl.oauthbearer.client.id".into(), "cli".into()),
+        ]);
+        let error = oauthbearer_auth(&values, None).expect_err("secret required");
+        assert!(error.to_string().contains("client.secret"), "{error}");
+
+        values.insert("sasl.oauthbearer.client.secret".into(), "secret".into());
+        let auth = oauthbearer_auth(&values, Some(TlsConfig::new())).expect("oidc auth");
+        assert!(auth.requires_tls());
+        assert!(auth.oauthbearer_provider().is_some());
+    }
+}
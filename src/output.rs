@@ -1,9 +1,16 @@
 //! Human-readable and machine-readable output helpers.
 
-use std::io::{self, Write};
+use std::{
+    fmt,
+    io::{self, Write},
+};
 
 use comfy_table::{Table, presets::UTF8_FULL};
-use serde::Serialize;
+use serde::{
+    Deserializer, Serialize,
+    de::{MapAccess, Visitor},
+};
+use serde_json::{Value, value::RawValue};
 
 use crate::error::Result;
 
@@ -15,6 +22,14 @@ pub enum OutputFormat {
     Table,
     /// Stable JSON envelope.
     Json,
+    /// The JSON envelope as YAML.
+    Yaml,
+    /// One compact JSON object per row, without the envelope.
+    Jsonl,
+    /// Comma-separated values with a header line.
+    Csv,
+    /// Tab-separated values with a header line.
+    Tsv,
 }
 
 #[derive(Serialize)]
@@ -44,29 +59,148 @@ pub fn write_value_with_errors<T: Serialize>(
     table: impl FnOnce(&T) -> String,
 ) -> Result<()> {
     let mut stdout = io::stdout().lock();
+    let envelope = Envelope {
+        schema_version: 1,
+        command,
+        data: value,
+        errors: errors.to_vec(),
+    };
     match format {
         OutputFormat::Table => {
             writeln!(stdout, "{}", table(value))?;
             for error in errors {
                 writeln!(stdout, "Error: {error}")?;
             }
+            return Ok(());
+        }
+        OutputFormat::Json => {
+            serde_json::to_writer_pretty(&mut stdout, &envelope)?;
+            writeln!(stdout)?;
+            return Ok(());
+        }
+        OutputFormat::Yaml => {
+            serde_norway::to_writer(&mut stdout, &envelope).map_err(io::Error::other)?;
+            return Ok(());
+        }
+        OutputFormat::Jsonl => {
+            for row in rows(value)? {
+                writeln!(stdout, "{}", row.get())?;
+            }
+        }
+        OutputFormat::Csv => write_delimited(&mut stdout, b',', value)?,
+        OutputFormat::Tsv => write_delimited(&mut stdout, b'\t', value)?,
+    }
+    // Row formats keep stdout machine-readable, so batch errors go to stderr.
+    for error in errors {
+        eprintln!("Error: {error}");
+    }
+    Ok(())
+}
+
+/// Splits a result into compact JSON rows: each element of a list, or the whole value
+/// otherwise. Rows stay raw so objects keep their field order, which `Value` would sort.
+fn rows<T: Serialize>(value: &T) -> Result<Vec<Box<RawValue>>> {
+    let json = serde_json::to_string(value)?;
+    Ok(match json.as_str() {
+        "null" => Vec::new(),
+        list if list.starts_with('[') => serde_json::from_str(list)?,
+        _ => vec![RawValue::from_string(json)?],
+    })
+}
+
+/// One delimited row: an object's fields in the order they were written, or any other value.
+enum DelimitedRow {
+    Fields(Vec<(String, Value)>),
+    Value(Value),
+}
+
+impl DelimitedRow {
+    fn parse(row: &RawValue) -> Result<Self> {
+        if !row.get().starts_with('{') {
+            return Ok(Self::Value(serde_json::from_str(row.get())?));
+        }
+        let mut deserializer = serde_json::Deserializer::from_str(row.get());
+        Ok(Self::Fields(deserializer.deserialize_map(FieldsVisitor)?))
+    }
+}
+
+/// Collects object fields in order; `Value` would sort them.
+struct FieldsVisitor;
+
+impl<'de> Visitor<'de> for FieldsVisitor {
+    type Value = Vec<(String, Value)>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a JSON object")
+    }
+
+    fn visit_map<A: MapAccess<'de>>(
+        self,
+        mut map: A,
+    ) -> std::result::Result<Self::Value, A::Error> {
+        let mut fields = Vec::new();
+        while let Some(field) = map.next_entry()? {
+            fields.push(field);
+        }
+        Ok(fields)
+    }
+}
+
+/// Writes rows as delimited text; the header is every field in first-seen order, and rows
+/// that are not objects are written as a single `value` column.
+fn write_delimited<T: Serialize>(writer: impl Write, delimiter: u8, value: &T) -> Result<()> {
+    let rows = rows(value)?
+        .iter()
+        .map(|row| DelimitedRow::parse(row))
+        .collect::<Result<Vec<_>>>()?;
+    let mut headers = Vec::<String>::new();
+    for row in &rows {
+        match row {
+            DelimitedRow::Fields(fields) => {
+                for (field, _) in fields {
+                    if !headers.contains(field) {
+                        headers.push(field.clone());
+                    }
+                }
+            }
+            DelimitedRow::Value(_) if !headers.iter().any(|header| header == "value") => {
+                headers.push("value".into());
+            }
+            DelimitedRow::Value(_) => {}
         }
-        OutputFormat::Json => serde_json::to_writer_pretty(
-            &mut stdout,
-            &Envelope {
-                schema_version: 1,
-                command,
-                data: value,
-                errors: errors.to_vec(),
-            },
-        )?,
     }
-    if format == OutputFormat::Json {
-        writeln!(stdout)?;
+    let mut writer = csv::WriterBuilder::new()
+        .delimiter(delimiter)
+        .from_writer(writer);
+    writer.write_record(&headers).map_err(io::Error::from)?;
+    for row in &rows {
+        writer
+            .write_record(headers.iter().map(|header| {
+                match row {
+                    DelimitedRow::Fields(fields) => fields
+                        .iter()
+                        .find(|(field, _)| field == header)
+                        .map(|(_, value)| cell(value))
+                        .unwrap_or_default(),
+                    DelimitedRow::Value(value) if header == "value" => cell(value),
+                    DelimitedRow::Value(_) => String::new(),
+                }
+            }))
+            .map_err(io::Error::from)?;
     }
+    writer.flush()?;
     Ok(())
 }
 
+/// Renders one delimited cell; nested lists and objects are written as compact JSON.
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(value) => value.clone(),
+        value => value.to_string(),
+    }
+}
+
 /// Writes a single JSON Lines record.
 pub fn write_json_line<T: Serialize>(value: &T) -> Result<()> {
     let mut stdout = io::stdout().lock();
@@ -119,4 +253,73 @@ mod tests {
 
         assert_eq!(value["errors"], serde_json::json!(["group is active"]));
     }
+
+    #[derive(Serialize)]
+    struct Row {
+        topic: String,
+        partition: i32,
+        leader: Option<i32>,
+        replicas: Vec<i32>,
+    }
+
+    #[test]
+    fn delimited_output_should_follow_row_fields_and_quote_cells() {
+        let rows = vec![
+            Row {
+                topic: "events, audit".into(),
+                partition: 0,
+                leader: Some(1),
+                replicas: vec![1, 2],
+            },
+            Row {
+                topic: "orders".into(),
+                partition: 3,
+                leader: None,
+                replicas: Vec::new(),
+            },
+        ];
+
+        let mut csv = Vec::new();
+        write_delimited(&mut csv, b',', &rows).expect("write CSV");
+        let mut tsv = Vec::new();
+        write_delimited(&mut tsv, b'\t', &rows).expect("write TSV");
+
+        assert_eq!(
+            String::from_utf8(csv).expect("UTF-8"),
+            "topic,partition,leader,replicas\n\"events, audit\",0,1,\"[1,2]\"\norders,3,,[]\n"
+        );
+        assert_eq!(
+            String::from_utf8(tsv).expect("UTF-8"),
+            "topic\tpartition\tleader\treplicas\nevents, audit\t0\t1\t[1,2]\norders\t3\t\t[]\n"
+        );
+    }
+
+    #[test]
+    fn rows_should_split_lists_and_wrap_single_values() {
+        assert_eq!(rows(&["a", "b"]).expect("list").len(), 2);
+        assert_eq!(
+            rows(&serde_json::json!({"topic": "a"}))
+                .expect("object")
+                .len(),
+            1
+        );
+        assert!(rows(&Value::Null).expect("null").is_empty());
+        let row = Row {
+            topic: "events".into(),
+            partition: 0,
+            leader: None,
+            replicas: vec![1],
+        };
+        assert_eq!(
+            rows(&row).expect("struct")[0].get(),
+            r#"{"topic":"events","partition":0,"leader":null,"replicas":[1]}"#
+        );
+
+        let mut csv = Vec::new();
+        write_delimited(&mut csv, b',', &vec!["events", "audit"]).expect("write CSV");
+        assert_eq!(
+            String::from_utf8(csv).expect("UTF-8"),
+            "value\nevents\naudit\n"
+        );
+    }
 }
@@ -0,0 +1,1289 @@
+//! `.proto` schema parsing and binary decoding for Schema Registry payloads.
+//!
+//! Values follow the canonical protobuf JSON mapping: lowerCamelCase field
+//! names, 64-bit integers as strings, enums by name, and base64 `bytes`.
+//! Only fields present on the wire are rendered. Encoding accepts the same
+//! mapping, with either JSON names or the original field names as keys.
+
+use std::collections::{HashMap, HashSet};
+
+use base64::{Engine as _, engine::general_purpose::STANDARD};
+use serde_json::Value;
+
+use crate::error::{Error, Result};
+use crate::schema_registry::SchemaValue;
+
+const WELL_KNOWN_IMPORTS: &[(&str, &str)] = &[
+    (
+        "google/protobuf/timestamp.proto",
+        "syntax = \"proto3\"; package google.protobuf; message Timestamp { int64 seconds = 1; int32 nanos = 2; }",
+    ),
+    (
+        "google/protobuf/duration.proto",
+        "syntax = \"proto3\"; package google.protobuf; message Duration { int64 seconds = 1; int32 nanos = 2; }",
+    ),
+    (
+        "google/protobuf/empty.proto",
+        "syntax = \"proto3\"; package google.protobuf; message Empty {}",
+    ),
+    (
+        "google/protobuf/wrappers.proto",
+        "syntax = \"proto3\"; package google.protobuf;
+        message DoubleValue { double value = 1; } message FloatValue { float value = 1; }
+        message Int64Value { int64 value = 1; } message UInt64Value { uint64 value = 1; }
+        message Int32Value { int32 value = 1; } message UInt32Value { uint32 value = 1; }
+        message BoolValue { bool value = 1; } message StringValue { string value = 1; }
+        message BytesValue { bytes value = 1; }",
+    ),
+];
+
+/// Returns the built-in source for a `google/protobuf/*.proto` import.
+#[must_use]
+pub fn well_known_import(path: &str) -> Option<&'static str> {
+    WELL_KNOWN_IMPORTS
+        .iter()
+        .find(|(name, _)| *name == path)
+        .map(|(_, source)| *source)
+}
+
+/// One parsed `.proto` file whose type references are not yet resolved.
+#[derive(Debug)]
+pub struct ProtoFile {
+    /// Paths named by `import` statements.
+    pub imports: Vec<String>,
+    top_level: Vec<String>,
+    messages: Vec<RawMessage>,
+    enums: Vec<(String, EnumDef)>,
+}
+
+#[derive(Debug)]
+struct RawMessage {
+    name: String,
+    fields: Vec<RawField>,
+    nested: Vec<String>,
+}
+
+#[derive(Debug)]
+struct RawField {
+    name: String,
+    number: u32,
+    repeated: bool,
+    type_name: String,
+    map_key: Option<String>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Scalar {
+    Double,
+    Float,
+    Int32,
+    Int64,
+    Uint32,
+    Uint64,
+    Sint32,
+    Sint64,
+    Fixed32,
+    Fixed64,
+    Sfixed32,
+    Sfixed64,
+    Bool,
+    String,
+    Bytes,
+}
+
+impl Scalar {
+    fn parse(name: &str) -> Option<Self> {
+        Some(match name {
+            "double" => Self::Double,
+            "float" => Self::Float,
+            "int32" => Self::Int32,
+            "int64" => Self::Int64,
+            "uint32" => Self::Uint32,
+            "uint64" => Self::Uint64,
+            "sint32" => Self::Sint32,
+            "sint64" => Self::Sint64,
+            "fixed32" => Self::Fixed32,
+            "fixed64" => Self::Fixed64,
+            "sfixed32" => Self::Sfixed32,
+            "sfixed64" => Self::Sfixed64,
+            "bool" => Self::Bool,
+            "string" => Self::String,
+            "bytes" => Self::Bytes,
+            _ => return None,
+        })
+    }
+
+    const fn packable(self) -> bool {
+        !matches!(self, Self::String | Self::Bytes)
+    }
+
+    const fn wire_type(self) -> u32 {
+        match self {
+            Self::Double | Self::Fixed64 | Self::Sfixed64 => 1,
+            Self::Float | Self::Fixed32 | Self::Sfixed32 => 5,
+            Self::String | Self::Bytes => 2,
+            _ => 0,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+enum FieldKind {
+    Scalar(Scalar),
+    Enum(String),
+    Message(String),
+    Map(Scalar, Box<Self>),
+}
+
+#[derive(Debug)]
+struct FieldDef {
+    name: String,
+    json_name: String,
+    number: u32,
+    repeated: bool,
+    kind: FieldKind,
+}
+
+#[derive(Debug)]
+struct MessageDef {
+    fields: Vec<FieldDef>,
+    nested: Vec<String>,
+}
+
+#[derive(Debug)]
+struct EnumDef {
+    values: Vec<(i32, String)>,
+}
+
+/// A `.proto` file resolved against its imports.
+#[derive(Debug)]
+pub struct ProtoSchema {
+    top_level: Vec<String>,
+    messages: HashMap<String, MessageDef>,
+    enums: HashMap<String, EnumDef>,
+}
+
+impl ProtoFile {
+    /// Parses the declarations of one `.proto` source file.
+    pub fn parse(source: &str) -> Result<Self> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            position: 0,
+        };
+        let mut file = Self {
+            imports: Vec::new(),
+            top_level: Vec::new(),
+            messages: Vec::new(),
+            enums: Vec::new(),
+        };
+        let mut package = String::new();
+        while let Some(token) = parser.next() {
+            match token.as_str() {
+                "package" => {
+                    package = parser.word()?;
+                    parser.expect(";")?;
+                }
+                "import" => {
+                    let mut path = parser.word()?;
+                    if path == "public" || path == "weak" {
+                        path = parser.word()?;
+                    }
+                    file.imports.push(unquote(&path));
+                    parser.expect(";")?;
+                }
+                "message" => {
+                    let name = parser.message(&package, &mut file)?;
+                    file.top_level.push(name);
+                }
+                "enum" => parser.enumeration(&package, &mut file)?,
+                "service" | "extend" => parser.skip_block()?,
+                ";" => {}
+                _ => parser.skip_statement(),
+            }
+        }
+        Ok(file)
+    }
+}
+
+impl ProtoSchema {
+    /// Resolves `file` against `dependencies`, adding well-known imports that were not supplied.
+    pub fn new(file: ProtoFile, mut dependencies: Vec<ProtoFile>) -> Result<Self> {
+        let imports: HashSet<String> = file
+            .imports
+            .iter()
+            .chain(
+                dependencies
+                    .iter()
+                    .flat_map(|dependency| &dependency.imports),
+            )
+            .cloned()
+            .collect();
+        for import in imports {
+            if let Some(source) = well_known_import(&import) {
+                dependencies.push(ProtoFile::parse(source)?);
+            }
+        }
+        let top_level = file.top_level.clone();
+        let files: Vec<ProtoFile> = std::iter::once(file).chain(dependencies).collect();
+        let mut enums = HashMap::new();
+        let mut names = HashSet::new();
+        for file in &files {
+            for message in &file.messages {
+                names.insert(message.name.clone());
+            }
+        }
+        let mut raw_messages = Vec::new();
+        for file in files {
+            raw_messages.extend(file.messages);
+            for (name, definition) in file.enums {
+                enums.insert(name, definition);
+            }
+        }
+        let mut messages = HashMap::new();
+        for message in raw_messages {
+            let fields = message
+                .fields
+                .iter()
+                .map(|field| {
+                    let kind = resolve_kind(&field.type_name, &message.name, &names, &enums)?;
+                    let kind = match &field.map_key {
+                        Some(key) => FieldKind::Map(
+                            Scalar::parse(key).ok_or_else(|| {
+                                Error::Config(format!("invalid protobuf map key type {key}"))
+                            })?,
+                            Box::new(kind),
+                        ),
+                        None => kind,
+                    };
+                    Ok(FieldDef {
+                        name: field.name.clone(),
+                        json_name: json_name(&field.name),
+                        number: field.number,
+                        repeated: field.repeated,
+                        kind,
+                    })
+                })
+                .collect::<Result<_>>()?;
+            messages.insert(
+                message.name,
+                MessageDef {
+                    fields,
+                    nested: message.nested,
+                },
+            );
+        }
+        Ok(Self {
+            top_level,
+            messages,
+            enums,
+        })
+    }
+
+    /// Returns the fully qualified message named by Confluent message indexes.
+    pub fn message_name(&self, indexes: &[usize]) -> Result<&str> {
+        let mut candidates = &self.top_level;
+        let mut name = None;
+        for index in indexes {
+            let found = candidates.get(*index).ok_or_else(|| {
+                Error::Config(format!("protobuf schema has no message at index {index}"))
+            })?;
+            candidates = &self.messages[found].nested;
+            name = Some(found.as_str());
+        }
+        name.ok_or_else(|| Error::Config("protobuf schema declares no messages".into()))
+    }
+
+    /// Returns the first top-level message, the default for unqualified production.
+    pub fn first_message(&self) -> Result<&str> {
+        self.message_name(&[0])
+    }
+
+    /// Returns the Confluent message index path of a fully qualified message.
+    pub fn message_indexes(&self, message: &str) -> Result<Vec<usize>> {
+        fn search(
+            schema: &ProtoSchema,
+            candidates: &[String],
+            message: &str,
+        ) -> Option<Vec<usize>> {
+            candidates.iter().enumerate().find_map(|(index, name)| {
+                if name == message {
+                    Some(vec![index])
+                } else {
+                    search(schema, &schema.messages[name].nested, message).map(|mut path| {
+                        path.insert(0, index);
+                        path
+                    })
+                }
+            })
+        }
+        search(self, &self.top_level, message.trim_start_matches('.')).ok_or_else(|| {
+            Error::Usage(format!(
+                "protobuf schema does not declare message {message}"
+            ))
+        })
+    }
+
+    /// Validates a JSON object against `message` and returns its binary encoding.
+    pub fn encode(&self, message: &str, value: &Value) -> Result<Vec<u8>> {
+        let mut output = Vec::new();
+        self.write_message(message, value, message, &mut output)?;
+        Ok(output)
+    }
+
+    fn write_message(
+        &self,
+        message: &str,
+        value: &Value,
+        path: &str,
+        output: &mut Vec<u8>,
+    ) -> Result<()> {
+        let definition = self
+            .messages
+            .get(message)
+            .ok_or_else(|| Error::Config(format!("unknown protobuf message {message}")))?;
+        let Value::Object(object) = value else {
+            return Err(Error::Usage(format!(
+                "{path}: expected a {message} object but found {value}"
+            )));
+        };
+        for (key, value) in object {
+            let field = definition
+                .fields
+                .iter()
+                .find(|field| field.json_name == *key || field.name == *key)
+                .ok_or_else(|| {
+                    Error::Usage(format!("{path}: message {message} has no field {key}"))
+                })?;
+            let path = format!("{path}.{key}");
+            match (&field.kind, value) {
+                (_, Value::Null) => {}
+                (FieldKind::Map(key_type, value_type), Value::Object(entries)) => {
+                    for (key, value) in entries {
+                        let mut entry = Vec::new();
+                        self.write_field(
+                            1,
+                            &FieldKind::Scalar(*key_type),
+                            &Value::String(key.clone()),
+                            &path,
+                            &mut entry,
+                        )?;
+                        self.write_field(
+                            2,
+                            value_type,
+                            value,
+                            &format!("{path}.{key}"),
+                            &mut entry,
+                        )?;
+                        write_tag(output, field.number, 2);
+                        write_length_delimited(output, &entry);
+                    }
+                }
+                (FieldKind::Scalar(scalar), Value::Array(values))
+                    if field.repeated && scalar.packable() =>
+                {
+                    let mut packed = Vec::new();
+                    for (index, value) in values.iter().enumerate() {
+                        write_scalar(*scalar, value, &format!("{path}[{index}]"), &mut packed)?;
+                    }
+                    write_tag(output, field.number, 2);
+                    write_length_delimited(output, &packed);
+                }
+                (kind, Value::Array(values)) if field.repeated => {
+                    for (index, value) in values.iter().enumerate() {
+                        self.write_field(
+                            field.number,
+                            kind,
+                            value,
+                            &format!("{path}[{index}]"),
+                            output,
+                        )?;
+                    }
+                }
+                (FieldKind::Map(..), value) => {
+                    return Err(Error::Usage(format!(
+                        "{path}: expected a map object but found {value}"
+                    )));
+                }
+                (_, value) if field.repeated => {
+                    return Err(Error::Usage(format!(
+                        "{path}: expected an array but found {value}"
+                    )));
+                }
+                (kind, value) => self.write_field(field.number, kind, value, &path, output)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn write_field(
+        &self,
+        number: u32,
+        kind: &FieldKind,
+        value: &Value,
+        path: &str,
+        output: &mut Vec<u8>,
+    ) -> Result<()> {
+        match kind {
+            FieldKind::Scalar(scalar) => {
+                write_tag(output, number, scalar.wire_type());
+                write_scalar(*scalar, value, path, output)
+            }
+            FieldKind::Enum(name) => {
+                let values = &self.enums[name].values;
+                let number_value = match value {
+                    Value::String(symbol) => values
+                        .iter()
+                        .find(|(_, candidate)| candidate == symbol)
+                        .map(|(number, _)| *number),
+                    Value::Number(number) => number
+                        .as_i64()
+                        .and_then(|number| i32::try_from(number).ok()),
+                    _ => None,
+                }
+                .ok_or_else(|| {
+                    Error::Usage(format!("{path}: expected a {name} value but found {value}"))
+                })?;
+                write_tag(output, number, 0);
+                write_varint(output, i64::from(number_value).cast_unsigned());
+                Ok(())
+            }
+            FieldKind::Message(name) => {
+                let mut nested = Vec::new();
+                self.write_message(name, value, path, &mut nested)?;
+                write_tag(output, number, 2);
+                write_length_delimited(output, &nested);
+                Ok(())
+            }
+            FieldKind::Map(..) => Err(Error::Config(format!(
+                "{path}: map entries are written with their message"
+            ))),
+        }
+    }
+
+    /// Decodes one message of type `message`.
+    pub fn decode(&self, message: &str, bytes: &[u8]) -> Result<SchemaValue> {
+        let definition = self
+            .messages
+            .get(message)
+            .ok_or_else(|| Error::Config(format!("unknown protobuf message {message}")))?;
+        let mut values: Vec<Vec<SchemaValue>> = vec![Vec::new(); definition.fields.len()];
+        let mut reader = WireReader { buffer: bytes };
+        while !reader.buffer.is_empty() {
+            let tag = reader.varint()?;
+            let number = u32::try_from(tag >> 3)
+                .map_err(|_| Error::Config(format!("invalid protobuf tag {tag}")))?;
+            let wire = WireValue::read(&mut reader, tag & 7)?;
+            let Some(index) = definition
+                .fields
+                .iter()
+                .position(|field| field.number == number)
+            else {
+                continue;
+            };
+            let field = &definition.fields[index];
+            match (&field.kind, wire) {
+                (FieldKind::Scalar(scalar), WireValue::Bytes(packed))
+                    if field.repeated && scalar.packable() =>
+                {
+                    let mut packed_reader = WireReader { buffer: packed };
+                    while !packed_reader.buffer.is_empty() {
+                        let element =
+                            WireValue::read(&mut packed_reader, u64::from(scalar.wire_type()))?;
+                        values[index].push(self.value(&field.kind, element)?);
+                    }
+                }
+                (FieldKind::Enum(_), WireValue::Bytes(packed)) if field.repeated => {
+                    let mut packed_reader = WireReader { buffer: packed };
+                    while !packed_reader.buffer.is_empty() {
+                        let element = WireValue::Varint(packed_reader.varint()?);
+                        values[index].push(self.value(&field.kind, element)?);
+                    }
+                }
+                (kind, wire) => {
+                    let value = self.value(kind, wire)?;
+                    if field.repeated || matches!(kind, FieldKind::Map(..)) {
+                        values[index].push(value);
+                    } else {
+                        values[index] = vec![value];
+                    }
+                }
+            }
+        }
+        Ok(SchemaValue::Object(
+            definition
+                .fields
+                .iter()
+                .zip(values)
+                .filter(|(_, values)| !values.is_empty())
+                .map(|(field, mut values)| {
+                    let value = match &field.kind {
+                        FieldKind::Map(..) => SchemaValue::Object(
+                            values
+                                .into_iter()
+                                .filter_map(|entry| match entry {
+                                    SchemaValue::Object(mut pair) if pair.len() == 1 => pair.pop(),
+                                    _ => None,
+                                })
+                                .collect(),
+                        ),
+                        _ if field.repeated => SchemaValue::Array(values),
+                        _ => values.pop().unwrap_or(SchemaValue::Null),
+                    };
+                    (field.json_name.clone(), value)
+                })
+                .collect(),
+        ))
+    }
+
+    fn value(&self, kind: &FieldKind, wire: WireValue<'_>) -> Result<SchemaValue> {
+        Ok(match (kind, wire) {
+            (FieldKind::Scalar(scalar), wire) => scalar_value(*scalar, wire)?,
+            (FieldKind::Enum(name), WireValue::Varint(value)) => {
+                let number = varint_i32(value);
+                self.enums[name]
+                    .values
+                    .iter()
+                    .find(|(candidate, _)| *candidate == number)
+                    .map_or_else(
+                        || SchemaValue::Int(i64::from(number)),
+                        |(_, name)| SchemaValue::String(name.clone()),
+                    )
+            }
+            (FieldKind::Message(name), WireValue::Bytes(bytes)) => self.decode(name, bytes)?,
+            (FieldKind::Map(key_type, value_type), WireValue::Bytes(bytes)) => {
+                let mut key = None;
+                let mut value = None;
+                let mut reader = WireReader { buffer: bytes };
+                while !reader.buffer.is_empty() {
+                    let tag = reader.varint()?;
+                    let wire = WireValue::read(&mut reader, tag & 7)?;
+                    match tag >> 3 {
+                        1 => key = Some(scalar_value(*key_type, wire)?),
+                        2 => value = Some(self.value(value_type, wire)?),
+                        _ => {}
+                    }
+                }
+                let key = match key {
+                    Some(SchemaValue::String(key)) => key,
+                    Some(SchemaValue::Int(key)) => key.to_string(),
+                    Some(SchemaValue::Bool(key)) => key.to_string(),
+                    Some(other) => {
+                        return Err(Error::Config(format!("invalid protobuf map key {other:?}")));
+                    }
+                    None => default_key(*key_type),
+                };
+                let value = value.unwrap_or_else(|| self.default_value(value_type));
+                SchemaValue::Object(vec![(key, value)])
+            }
+            (_, wire) => {
+                return Err(Error::Config(format!(
+                    "unexpected protobuf wire type {} for {kind:?}",
+                    wire.wire_type()
+                )));
+            }
+        })
+    }
+
+    fn default_value(&self, kind: &FieldKind) -> SchemaValue {
+        match kind {
+            FieldKind::Scalar(Scalar::Bool) => SchemaValue::Bool(false),
+            FieldKind::Scalar(Scalar::String | Scalar::Bytes) => SchemaValue::String(String::new()),
+            FieldKind::Scalar(
+                Scalar::Int64
+                | Scalar::Uint64
+                | Scalar::Sint64
+                | Scalar::Fixed64
+                | Scalar::Sfixed64,
+            ) => SchemaValue::String("0".into()),
+            FieldKind::Scalar(Scalar::Double | Scalar::Float) => SchemaValue::Float(0.0),
+            FieldKind::Scalar(_) => SchemaValue::Int(0),
+            FieldKind::Enum(name) => self.enums[name]
+                .values
+                .first()
+                .map_or(SchemaValue::Int(0), |(_, name)| {
+                    SchemaValue::String(name.clone())
+                }),
+            FieldKind::Message(_) | FieldKind::Map(..) => SchemaValue::Object(Vec::new()),
+        }
+    }
+}
+
+fn default_key(scalar: Scalar) -> String {
+    match scalar {
+        Scalar::Bool => "false".into(),
+        Scalar::String => String::new(),
+        _ => "0".into(),
+    }
+}
+
+#[expect(
+    clippy::cast_possible_truncation,
+    reason = "int32 and enum values are sign-extended to 64 bits on the wire"
+)]
+const fn varint_i32(value: u64) -> i32 {
+    value as i32
+}
+
+#[expect(
+    clippy::cast_possible_truncation,
+    clippy::cast_possible_wrap,
+    reason = "protobuf scalar types reinterpret the low bits of the wire value"
+)]
+fn scalar_value(scalar: Scalar, wire: WireValue<'_>) -> Result<SchemaValue> {
+    Ok(match (scalar, wire) {
+        (Scalar::Int32, WireValue::Varint(value)) => SchemaValue::Int(i64::from(varint_i32(value))),
+        (Scalar::Int64, WireValue::Varint(value))
+        | (Scalar::Sfixed64, WireValue::Fixed64(value)) => {
+            SchemaValue::String((value as i64).to_string())
+        }
+        (Scalar::Uint32, WireValue::Varint(value)) => SchemaValue::Int(i64::from(value as u32)),
+        (Scalar::Uint64, WireValue::Varint(value))
+        | (Scalar::Fixed64, WireValue::Fixed64(value)) => SchemaValue::String(value.to_string()),
+        (Scalar::Sint32, WireValue::Varint(value)) => {
+            SchemaValue::Int(i64::from(zigzag(value) as i32))
+        }
+        (Scalar::Sint64, WireValue::Varint(value)) => {
+            SchemaValue::String(zigzag(value).to_string())
+        }
+        (Scalar::Bool, WireValue::Varint(value)) => SchemaValue::Bool(value != 0),
+        (Scalar::Fixed32, WireValue::Fixed32(value)) => SchemaValue::Int(i64::from(value)),
+        (Scalar::Sfixed32, WireValue::Fixed32(value)) => SchemaValue::Int(i64::from(value as i32)),
+        (Scalar::Float, WireValue::Fixed32(value)) => {
+            SchemaValue::Float(f64::from(f32::from_bits(value)))
+        }
+        (Scalar::Double, WireValue::Fixed64(value)) => SchemaValue::Float(f64::from_bits(value)),
+        (Scalar::String, WireValue::Bytes(bytes)) => SchemaValue::String(
+            String::from_utf8(bytes.to_vec())
+                .map_err(|_| Error::Config("protobuf string is not valid UTF-8".into()))?,
+        ),
+        (Scalar::Bytes, WireValue::Bytes(bytes)) => SchemaValue::String(STANDARD.encode(bytes)),
+        (scalar, wire) => {
+            return Err(Error::Config(format!(
+                "unexpected protobuf wire type {} for {scalar:?}",
+                wire.wire_type()
+            )));
+        }
+    })
+}
+
+const fn zigzag(value: u64) -> i64 {
+    let magnitude = (value >> 1).cast_signed();
+    if value & 1 == 0 {
+        magnitude
+    } else {
+        !magnitude
+    }
+}
+
+pub fn write_varint(output: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        output.push((value & 0x7f) as u8 | 0x80);
+        value >>= 7;
+    }
+    output.push((value & 0x7f) as u8);
+}
+
+fn write_tag(output: &mut Vec<u8>, number: u32, wire_type: u32) {
+    write_varint(output, u64::from((number << 3) | wire_type));
+}
+
+fn write_length_delimited(output: &mut Vec<u8>, bytes: &[u8]) {
+    write_varint(output, bytes.len() as u64);
+    output.extend_from_slice(bytes);
+}
+
+/// Reads a JSON number or numeric string as an integer, as the JSON mapping allows both.
+fn json_integer(value: &Value) -> Option<i128> {
+    match value {
+        Value::Number(number) => number
+            .as_i64()
+            .map(i128::from)
+            .or_else(|| number.as_u64().map(i128::from)),
+        Value::String(text) => text.parse().ok(),
+        _ => None,
+    }
+}
+
+fn write_scalar(scalar: Scalar, value: &Value, path: &str, output: &mut Vec<u8>) -> Result<()> {
+    let mismatch = || Error::Usage(format!("{path}: expected {scalar:?} but found {value}"));
+    let integer = || json_integer(value).ok_or_else(mismatch);
+    match scalar {
+        Scalar::Int32 | Scalar::Int64 | Scalar::Sint32 | Scalar::Sint64 => {
+            let value = integer()?;
+            let value = if matches!(scalar, Scalar::Int32 | Scalar::Sint32) {
+                i64::from(i32::try_from(value).map_err(|_| mismatch())?)
+            } else {
+                i64::try_from(value).map_err(|_| mismatch())?
+            };
+            if matches!(scalar, Scalar::Sint32 | Scalar::Sint64) {
+                write_varint(
+                    output,
+                    (value << 1).cast_unsigned() ^ (value >> 63).cast_unsigned(),
+                );
+            } else {
+                write_varint(output, value.cast_unsigned());
+            }
+        }
+        Scalar::Uint32 => write_varint(
+            output,
+            u64::from(u32::try_from(integer()?).map_err(|_| mismatch())?),
+        ),
+        Scalar::Uint64 => write_varint(output, u64::try_from(integer()?).map_err(|_| mismatch())?),
+        Scalar::Fixed32 => output.extend_from_slice(
+            &u32::try_from(integer()?)
+                .map_err(|_| mismatch())?
+                .to_le_bytes(),
+        ),
+        Scalar::Sfixed32 => output.extend_from_slice(
+            &i32::try_from(integer()?)
+                .map_err(|_| mismatch())?
+                .to_le_bytes(),
+        ),
+        Scalar::Fixed64 => output.extend_from_slice(
+            &u64::try_from(integer()?)
+                .map_err(|_| mismatch())?
+                .to_le_bytes(),
+        ),
+        Scalar::Sfixed64 => output.extend_from_slice(
+            &i64::try_from(integer()?)
+                .map_err(|_| mismatch())?
+                .to_le_bytes(),
+        ),
+        Scalar::Float => {
+            #[expect(
+                clippy::cast_possible_truncation,
+                reason = "protobuf floats are single precision"
+            )]
+            let value = value.as_f64().ok_or_else(mismatch)? as f32;
+            output.extend_from_slice(&value.to_le_bytes());
+        }
+        Scalar::Double => {
+            output.extend_from_slice(&value.as_f64().ok_or_else(mismatch)?.to_le_bytes());
+        }
+        Scalar::Bool => match value {
+            Value::Bool(value) => output.push(u8::from(*value)),
+            Value::String(text) if text == "true" || text == "false" => {
+                output.push(u8::from(text == "true"));
+            }
+            _ => return Err(mismatch()),
+        },
+        Scalar::String => {
+            write_length_delimited(output, value.as_str().ok_or_else(mismatch)?.as_bytes());
+        }
+        Scalar::Bytes => {
+            let bytes = value
+                .as_str()
+                .and_then(|text| STANDARD.decode(text).ok())
+                .ok_or_else(mismatch)?;
+            write_length_delimited(output, &bytes);
+        }
+    }
+    Ok(())
+}
+
+fn resolve_kind(
+    type_name: &str,
+    scope: &str,
+    messages: &HashSet<String>,
+    enums: &HashMap<String, EnumDef>,
+) -> Result<FieldKind> {
+    if let Some(scalar) = Scalar::parse(type_name) {
+        return Ok(FieldKind::Scalar(scalar));
+    }
+    let mut candidates = Vec::new();
+    if let Some(absolute) = type_name.strip_prefix('.') {
+        candidates.push(absolute.to_owned());
+    } else {
+        let mut prefix = Some(scope);
+        while let Some(current) = prefix {
+            if current.is_empty() {
+                candidates.push(type_name.to_owned());
+                prefix = None;
+            } else {
+                candidates.push(format!("{current}.{type_name}"));
+                prefix = Some(current.rsplit_once('.').map_or("", |(parent, _)| parent));
+            }
+        }
+    }
+    candidates
+        .into_iter()
+        .find_map(|candidate| {
+            if messages.contains(&candidate) {
+                Some(FieldKind::Message(candidate))
+            } else if enums.contains_key(&candidate) {
+                Some(FieldKind::Enum(candidate))
+            } else {
+                None
+            }
+        })
+        .ok_or_else(|| Error::Config(format!("unknown protobuf type {type_name} in {scope}")))
+}
+
+fn json_name(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    let mut upper = false;
+    for character in name.chars() {
+        if character == '_' {
+            upper = true;
+        } else if upper {
+            result.extend(character.to_uppercase());
+            upper = false;
+        } else {
+            result.push(character);
+        }
+    }
+    result
+}
+
+fn unquote(token: &str) -> String {
+    token
+        .trim_matches(|character| character == '"' || character == '\'')
+        .to_owned()
+}
+
+fn qualify(scope: &str, name: &str) -> String {
+    if scope.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{scope}.{name}")
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut characters = source.chars().peekable();
+    while let Some(character) = characters.next() {
+        match character {
+            character if character.is_whitespace() => {}
+            '/' if characters.peek() == Some(&'/') => {
+                for next in characters.by_ref() {
+                    if next == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if characters.peek() == Some(&'*') => {
+                characters.next();
+                let mut previous = ' ';
+                loop {
+                    let next = characters.next().ok_or_else(|| {
+                        Error::Config("unterminated comment in protobuf schema".into())
+                    })?;
+                    if previous == '*' && next == '/' {
+                        break;
+                    }
+                    previous = next;
+                }
+            }
+            '"' | '\'' => {
+                let mut token = String::from(character);
+                loop {
+                    let next = characters.next().ok_or_else(|| {
+                        Error::Config("unterminated string in protobuf schema".into())
+                    })?;
+                    token.push(next);
+                    if next == '\\' {
+                        token.extend(characters.next());
+                    } else if next == character {
+                        break;
+                    }
+                }
+                tokens.push(token);
+            }
+            character if is_word(character) => {
+                let mut token = String::from(character);
+                while let Some(next) = characters.next_if(|next| is_word(*next)) {
+                    token.push(next);
+                }
+                tokens.push(token);
+            }
+            character => tokens.push(character.to_string()),
+        }
+    }
+    Ok(tokens)
+}
+
+const fn is_word(character: char) -> bool {
+    character.is_ascii_alphanumeric() || matches!(character, '_' | '.' | '-' | '+')
+}
+
+struct Parser {
+    tokens: Vec<String>,
+    position: usize,
+}
+
+impl Parser {
+    fn next(&mut self) -> Option<String> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+        token
+    }
+
+    fn peek(&self) -> Option<&str> {
+        self.tokens.get(self.position).map(String::as_str)
+    }
+
+    fn word(&mut self) -> Result<String> {
+        self.next()
+            .ok_or_else(|| Error::Config("unexpected end of protobuf schema".into()))
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<()> {
+        let token = self.word()?;
+        if token == expected {
+            Ok(())
+        } else {
+            Err(Error::Config(format!(
+                "expected '{expected}' in protobuf schema but found '{token}'"
+            )))
+        }
+    }
+
+    fn skip_statement(&mut self) {
+        let mut depth = 0_usize;
+        while let Some(token) = self.next() {
+            match token.as_str() {
+                "[" | "(" | "{" => depth += 1,
+                "]" | ")" | "}" => depth = depth.saturating_sub(1),
+                ";" if depth == 0 => return,
+                _ => {}
+            }
+        }
+    }
+
+    fn skip_block(&mut self) -> Result<()> {
+        while self.word()? != "{" {}
+        let mut depth = 1_usize;
+        while depth > 0 {
+            match self.word()?.as_str() {
+                "{" => depth += 1,
+                "}" => depth -= 1,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn message(&mut self, scope: &str, file: &mut ProtoFile) -> Result<String> {
+        let name = qualify(scope, &self.word()?);
+        self.expect("{")?;
+        let mut message = RawMessage {
+            name: name.clone(),
+            fields: Vec::new(),
+            nested: Vec::new(),
+        };
+        loop {
+            let token = self.word()?;
+            match token.as_str() {
+                "}" => break,
+                ";" => {}
+                "message" => {
+                    let nested = self.message(&name, file)?;
+                    message.nested.push(nested);
+                }
+                "enum" => self.enumeration(&name, file)?,
+                "extend" => self.skip_block()?,
+                "option" | "reserved" | "extensions" => self.skip_statement(),
+                "oneof" => {
+                    self.word()?;
+                    self.expect("{")?;
+                    loop {
+                        let token = self.word()?;
+                        match token.as_str() {
+                            "}" => break,
+                            "option" => self.skip_statement(),
+                            ";" => {}
+                            _ => message.fields.push(self.field(token)?),
+                        }
+                    }
+                }
+                _ => message.fields.push(self.field(token)?),
+            }
+        }
+        file.messages.push(message);
+        Ok(name)
+    }
+
+    fn field(&mut self, first: String) -> Result<RawField> {
+        let mut repeated = false;
+        let mut type_name = first;
+        if matches!(type_name.as_str(), "optional" | "required" | "repeated") {
+            repeated = type_name == "repeated";
+            type_name = self.word()?;
+        }
+        if type_name == "group" {
+            return Err(Error::Unsupported(
+                "protobuf groups are not supported".into(),
+            ));
+        }
+        let mut map_key = None;
+        if type_name == "map" && self.peek() == Some("<") {
+            self.expect("<")?;
+            map_key = Some(self.word()?);
+            self.expect(",")?;
+            type_name = self.word()?;
+            self.expect(">")?;
+        }
+        let name = self.word()?;
+        self.expect("=")?;
+        let number = self.word()?;
+        let number = number.parse().map_err(|_| {
+            Error::Config(format!(
+                "invalid protobuf field number '{number}' for {name}"
+            ))
+        })?;
+        self.skip_statement();
+        Ok(RawField {
+            name,
+            number,
+            repeated,
+            type_name,
+            map_key,
+        })
+    }
+
+    fn enumeration(&mut self, scope: &str, file: &mut ProtoFile) -> Result<()> {
+        let name = qualify(scope, &self.word()?);
+        self.expect("{")?;
+        let mut values = Vec::new();
+        loop {
+            let token = self.word()?;
+            match token.as_str() {
+                "}" => break,
+                ";" => {}
+                "option" | "reserved" => self.skip_statement(),
+                _ => {
+                    self.expect("=")?;
+                    let number = self.word()?;
+                    let number = parse_enum_number(&number).ok_or_else(|| {
+                        Error::Config(format!(
+                            "invalid protobuf enum number '{number}' for {token}"
+                        ))
+                    })?;
+                    self.skip_statement();
+                    values.push((number, token));
+                }
+            }
+        }
+        file.enums.push((name, EnumDef { values }));
+        Ok(())
+    }
+}
+
+fn parse_enum_number(token: &str) -> Option<i32> {
+    let (negative, digits) = token
+        .strip_prefix('-')
+        .map_or((false, token), |digits| (true, digits));
+    let value = if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        i64::from_str_radix(hex, 16).ok()?
+    } else {
+        digits.parse::<i64>().ok()?
+    };
+    i32::try_from(if negative { -value } else { value }).ok()
+}
+
+#[derive(Clone, Copy)]
+enum WireValue<'a> {
+    Varint(u64),
+    Fixed64(u64),
+    Bytes(&'a [u8]),
+    Fixed32(u32),
+}
+
+impl<'a> WireValue<'a> {
+    fn read(reader: &mut WireReader<'a>, wire_type: u64) -> Result<Self> {
+        Ok(match wire_type {
+            0 => Self::Varint(reader.varint()?),
+            1 => Self::Fixed64(u64::from_le_bytes(reader.fixed()?)),
+            2 => {
+                let length = reader.varint()?;
+                let length = usize::try_from(length)
+                    .map_err(|_| Error::Config(format!("invalid protobuf length {length}")))?;
+                Self::Bytes(reader.take(length)?)
+            }
+            5 => Self::Fixed32(u32::from_le_bytes(reader.fixed()?)),
+            other => {
+                return Err(Error::Unsupported(format!(
+                    "protobuf wire type {other} is not supported"
+                )));
+            }
+        })
+    }
+
+    const fn wire_type(&self) -> u8 {
+        match self {
+            Self::Varint(_) => 0,
+            Self::Fixed64(_) => 1,
+            Self::Bytes(_) => 2,
+            Self::Fixed32(_) => 5,
+        }
+    }
+}
+
+struct WireReader<'a> {
+    buffer: &'a [u8],
+}
+
+impl<'a> WireReader<'a> {
+    fn take(&mut self, length: usize) -> Result<&'a [u8]> {
+        if self.buffer.len() < length {
+            return Err(Error::Config("truncated protobuf message".into()));
+        }
+        let (head, tail) = self.buffer.split_at(length);
+        self.buffer = tail;
+        Ok(head)
+    }
+
+    fn fixed<const LENGTH: usize>(&mut self) -> Result<[u8; LENGTH]> {
+        let mut bytes = [0; LENGTH];
+        bytes.copy_from_slice(self.take(LENGTH)?);
+        Ok(bytes)
+    }
+
+    fn varint(&mut self) -> Result<u64> {
+        let mut value = 0_u64;
+        for shift in (0..70).step_by(7) {
+            let byte = self.take(1)?[0];
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(Error::Config("protobuf varint is too long".into()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SCHEMA: &str = r#"
+        syntax = "proto3";
+        package shop.v1;
+        import "google/protobuf/timestamp.proto";
+
+        // Orders placed through the storefront.
+        message Order {
+            string order_id = 1;
+            repeated int32 quantities = 2 [packed = true];
+            Status status = 3;
+            map<string, Line> lines = 4;
+            google.protobuf.Timestamp placed_at = 5;
+            oneof payment { string card = 6; bytes token = 7; }
+            int64 total_cents = 8;
+            sint32 adjustment = 9;
+
+            message Line { string sku = 1; double price = 2; }
+            enum Status { UNKNOWN = 0; PLACED = 1; SHIPPED = 2 [deprecated = true]; }
+        }
+
+        message Refund { string order_id = 1; }
+    "#;
+
+    fn schema() -> ProtoSchema {
+        ProtoSchema::new(ProtoFile::parse(SCHEMA).expect("parses"), Vec::new()).expect("resolves")
+    }
+
+    fn length_delimited(field: u8, bytes: &[u8]) -> Vec<u8> {
+        let mut result = vec![(field << 3) | 2, u8::try_from(bytes.len()).expect("short")];
+        result.extend_from_slice(bytes);
+        result
+    }
+
+    #[test]
+    fn proto_schema_should_decode_messages_with_canonical_json_names() {
+        let schema = schema();
+        let mut line = length_delimited(1, b"A1");
+        line.push((2 << 3) | 1);
+        line.extend(2.5_f64.to_le_bytes());
+        let mut entry = length_delimited(1, b"first");
+        entry.extend(length_delimited(2, &line));
+        let mut message = length_delimited(1, b"o-1");
+        message.extend(length_delimited(2, &[1, 2, 0x96, 0x01]));
+        message.extend([3 << 3, 2]);
+        message.extend(length_delimited(4, &entry));
+        message.extend(length_delimited(5, &[1 << 3, 10]));
+        message.extend(length_delimited(7, &[0xde, 0xad]));
+        message.extend([8 << 3, 0xe8, 0x07, 9 << 3, 3]);
+
+        let name = schema.message_name(&[0]).expect("top-level message");
+        let decoded = schema.decode(name, &message).expect("decodes");
+
+        assert_eq!(name, "shop.v1.Order");
+        assert_eq!(
+            serde_json::to_string(&decoded).expect("serializes"),
+            r#"{"orderId":"o-1","quantities":[1,2,150],"status":"SHIPPED","lines":{"first":{"sku":"A1","price":2.5}},"placedAt":{"seconds":"10"},"token":"3q0=","totalCents":"1000","adjustment":-2}"#
+        );
+    }
+
+    #[test]
+    fn proto_schema_should_follow_nested_message_indexes() {
+        let schema = schema();
+
+        assert_eq!(
+            schema.message_name(&[0, 0]).expect("nested"),
+            "shop.v1.Order.Line"
+        );
+        assert_eq!(schema.message_name(&[1]).expect("second"), "shop.v1.Refund");
+        assert!(schema.message_name(&[2]).is_err());
+        assert!(
+            ProtoSchema::new(
+                ProtoFile::parse("message A { Missing value = 1; }").expect("parses"),
+                Vec::new()
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn proto_schema_should_encode_validated_json_objects() {
+        let schema = schema();
+        let order = serde_json::json!({
+            "orderId": "o-1",
+            "quantities": [1, 2, 150],
+            "status": "SHIPPED",
+            "lines": {"first": {"sku": "A1", "price": 2.5}},
+            "placed_at": {"seconds": "10"},
+            "token": "3q0=",
+            "totalCents": 1000,
+            "adjustment": -2,
+        });
+
+        let encoded = schema.encode("shop.v1.Order", &order).expect("encodes");
+
+        assert_eq!(
+            serde_json::to_string(&schema.decode("shop.v1.Order", &encoded).expect("decodes"))
+                .expect("json"),
+            r#"{"orderId":"o-1","quantities":[1,2,150],"status":"SHIPPED","lines":{"first":{"sku":"A1","price":2.5}},"placedAt":{"seconds":"10"},"token":"3q0=","totalCents":"1000","adjustment":-2}"#
+        );
+        assert_eq!(schema.first_message().expect("first"), "shop.v1.Order");
+        assert_eq!(
+            schema
+                .message_indexes("shop.v1.Order.Line")
+                .expect("nested"),
+            vec![0, 0]
+        );
+        assert_eq!(
+            schema.message_indexes("shop.v1.Refund").expect("second"),
+            vec![1]
+        );
+        for invalid in [
+            serde_json::json!({"unknown": 1}),
+            serde_json::json!({"status": "LOST"}),
+            serde_json::json!({"adjustment": 4_294_967_296_i64}),
+            serde_json::json!({"quantities": 1}),
+            serde_json::json!([]),
+        ] {
+            assert!(
+                matches!(
+                    schema.encode("shop.v1.Order", &invalid),
+                    Err(Error::Usage(_))
+                ),
+                "{invalid}"
+            );
+        }
+    }
+}
@@ -0,0 +1,330 @@
+//! Primitive decoding for Kafka generated message schemas stored in internal topics.
+
+use base64::{Engine as _, engine::general_purpose::URL_SAFE_NO_PAD};
+
+use crate::error::{Error, Result};
+
+/// Rejects schema versions newer than the highest one this build understands.
+pub fn check_version(schema: &str, version: i16, highest: i16) -> Result<()> {
+    if (0..=highest).contains(&version) {
+        Ok(())
+    } else {
+        Err(Error::Config(format!(
+            "unsupported {schema} version {version}"
+        )))
+    }
+}
+
+/// Cursor over a version-prefixed generated message.
+///
+/// Flexible versions use compact lengths and tagged fields; older versions use
+/// fixed-width `i16`/`i32` lengths.
+pub struct SchemaReader<'a> {
+    buffer: &'a [u8],
+    flexible: bool,
+}
+
+impl<'a> SchemaReader<'a> {
+    pub const fn new(buffer: &'a [u8], flexible: bool) -> Self {
+        Self { buffer, flexible }
+    }
+
+    pub const fn remaining(&self) -> usize {
+        self.buffer.len()
+    }
+
+    fn take(&mut self, length: usize, what: &str) -> Result<&'a [u8]> {
+        if self.buffer.len() < length {
+            return Err(Error::Config(format!("truncated {what}")));
+        }
+        let (head, tail) = self.buffer.split_at(length);
+        self.buffer = tail;
+        Ok(head)
+    }
+
+    fn fixed<const N: usize>(&mut self, what: &str) -> Result<[u8; N]> {
+        let bytes = self.take(N, what)?;
+        Ok(bytes.try_into().unwrap_or([0; N]))
+    }
+
+    pub fn bool(&mut self) -> Result<bool> {
+        Ok(self.fixed::<1>("boolean")?[0] != 0)
+    }
+
+    pub fn i8(&mut self) -> Result<i8> {
+        Ok(i8::from_be_bytes(self.fixed("int8")?))
+    }
+
+    pub fn i16(&mut self) -> Result<i16> {
+        Ok(i16::from_be_bytes(self.fixed("int16")?))
+    }
+
+    pub fn u16(&mut self) -> Result<u16> {
+        Ok(u16::from_be_bytes(self.fixed("uint16")?))
+    }
+
+    pub fn i32(&mut self) -> Result<i32> {
+        Ok(i32::from_be_bytes(self.fixed("int32")?))
+    }
+
+    pub fn i64(&mut self) -> Result<i64> {
+        Ok(i64::from_be_bytes(self.fixed("int64")?))
+    }
+
+    pub fn f64(&mut self) -> Result<f64> {
+        Ok(f64::from_be_bytes(self.fixed("float64")?))
+    }
+
+    /// Reads a Kafka UUID rendered the way `Uuid.toString` prints it.
+    pub fn uuid(&mut self) -> Result<String> {
+        Ok(URL_SAFE_NO_PAD.encode(self.take(16, "UUID")?))
+    }
+
+    pub fn unsigned_varint(&mut self) -> Result<u32> {
+        let mut value = 0_u32;
+        for shift in (0..35).step_by(7) {
+            let byte = self.fixed::<1>("unsigned varint")?[0];
+            value |= u32::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(Error::Config("unsigned varint is too long".into()))
+    }
+
+    /// Reads the `AbstractApiMessageSerde` frame: frame version, API key, and message version.
+    pub fn message_frame(&mut self, what: &str) -> Result<(u32, i16)> {
+        let frame_version = self.unsigned_varint()?;
+        if frame_version != 0 {
+            return Err(Error::Config(format!(
+                "Could not deserialize {what} due to unknown frame version {frame_version}"
+            )));
+        }
+        let api_key = self.unsigned_varint()?;
+        let version = i16::try_from(self.unsigned_varint()?)
+            .map_err(|_| Error::Config(format!("{what} version is out of range")))?;
+        Ok((api_key, version))
+    }
+
+    /// Reads a string, array, or bytes length; `None` is the null marker.
+    fn length(&mut self, wide: bool) -> Result<Option<usize>> {
+        let length = if self.flexible {
+            i64::from(self.unsigned_varint()?) - 1
+        } else if wide {
+            i64::from(self.i32()?)
+        } else {
+            i64::from(self.i16()?)
+        };
+        Ok(usize::try_from(length).ok())
+    }
+
+    pub fn nullable_string(&mut self) -> Result<Option<String>> {
+        let Some(length) = self.length(false)? else {
+            return Ok(None);
+        };
+        let bytes = self.take(length, "string")?;
+        String::from_utf8(bytes.to_vec())
+            .map(Some)
+            .map_err(|error| Error::Config(format!("invalid UTF-8: {error}")))
+    }
+
+    pub fn string(&mut self) -> Result<String> {
+        self.nullable_string()?
+            .ok_or_else(|| Error::Config("unexpected null string".into()))
+    }
+
+    pub fn nullable_bytes(&mut self) -> Result<Option<&'a [u8]>> {
+        let Some(length) = self.length(true)? else {
+            return Ok(None);
+        };
+        self.take(length, "bytes").map(Some)
+    }
+
+    pub fn bytes(&mut self) -> Result<&'a [u8]> {
+        self.nullable_bytes()?
+            .ok_or_else(|| Error::Config("unexpected null bytes".into()))
+    }
+
+    /// Reads a nullable array whose elements are decoded by `element`.
+    pub fn nullable_array<T>(
+        &mut self,
+        mut element: impl FnMut(&mut Self) -> Result<T>,
+    ) -> Result<Option<Vec<T>>> {
+        let Some(length) = self.length(true)? else {
+            return Ok(None);
+        };
+        let mut values = Vec::with_capacity(length.min(self.remaining()));
+        for _ in 0..length {
+            values.push(element(self)?);
+        }
+        Ok(Some(values))
+    }
+
+    /// Reads an array whose elements are decoded by `element`; null arrays decode as empty.
+    pub fn array<T>(&mut self, element: impl FnMut(&mut Self) -> Result<T>) -> Result<Vec<T>> {
+        Ok(self.nullable_array(element)?.unwrap_or_default())
+    }
+
+    /// Reads tagged fields, handing known tags to `field` and skipping the rest.
+    ///
+    /// `field` returns `true` when it consumed the tag's payload.
+    pub fn tagged_fields(
+        &mut self,
+        mut field: impl FnMut(u32, &mut Self) -> Result<bool>,
+    ) -> Result<()> {
+        if !self.flexible {
+            return Ok(());
+        }
+        let count = self.unsigned_varint()?;
+        for _ in 0..count {
+            let tag = self.unsigned_varint()?;
+            let size = usize::try_from(self.unsigned_varint()?).unwrap_or(usize::MAX);
+            let payload = self.take(size, "tagged field")?;
+            let mut reader = SchemaReader::new(payload, true);
+            field(tag, &mut reader)?;
+        }
+        Ok(())
+    }
+
+    /// Skips the trailing tagged-field section of a flexible struct.
+    pub fn skip_tagged_fields(&mut self) -> Result<()> {
+        self.tagged_fields(|_, _| Ok(false))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+
+    /// Minimal encoder used to build schema fixtures in decoder tests.
+    pub struct SchemaWriter {
+        buffer: Vec<u8>,
+        flexible: bool,
+    }
+
+    impl SchemaWriter {
+        pub fn new(flexible: bool) -> Self {
+            Self {
+                buffer: Vec::new(),
+                flexible,
+            }
+        }
+
+        pub fn bool(&mut self, value: bool) -> &mut Self {
+            self.buffer.push(u8::from(value));
+            self
+        }
+
+        pub fn uuid(&mut self, value: [u8; 16]) -> &mut Self {
+            self.buffer.extend_from_slice(&value);
+            self
+        }
+
+        pub fn i8(&mut self, value: i8) -> &mut Self {
+            self.buffer.extend_from_slice(&value.to_be_bytes());
+            self
+        }
+
+        pub fn i16(&mut self, value: i16) -> &mut Self {
+            self.buffer.extend_from_slice(&value.to_be_bytes());
+            self
+        }
+
+        pub fn i32(&mut self, value: i32) -> &mut Self {
+            self.buffer.extend_from_slice(&value.to_be_bytes());
+            self
+        }
+
+        pub fn i64(&mut self, value: i64) -> &mut Self {
+            self.buffer.extend_from_slice(&value.to_be_bytes());
+            self
+        }
+
+        pub fn unsigned_varint(&mut self, mut value: u32) -> &mut Self {
+            while value >= 0x80 {
+                self.buffer
+                    .push(u8::try_from(value & 0x7f).unwrap_or(0) | 0x80);
+                value >>= 7;
+            }
+            self.buffer.push(u8::try_from(value).unwrap_or(0));
+            self
+        }
+
+        fn length(&mut self, length: Option<usize>, wide: bool) -> &mut Self {
+            if self.flexible {
+                let encoded = length.map_or(0, |length| length + 1);
+                self.unsigned_varint(u32::try_from(encoded).unwrap_or(u32::MAX))
+            } else if wide {
+                self.i32(length.map_or(-1, |length| i32::try_from(length).unwrap_or(-1)))
+            } else {
+                self.i16(length.map_or(-1, |length| i16::try_from(length).unwrap_or(-1)))
+            }
+        }
+
+        pub fn nullable_string(&mut self, value: Option<&str>) -> &mut Self {
+            self.length(value.map(str::len), false);
+            if let Some(value) = value {
+                self.buffer.extend_from_slice(value.as_bytes());
+            }
+            self
+        }
+
+        pub fn string(&mut self, value: &str) -> &mut Self {
+            self.nullable_string(Some(value))
+        }
+
+        pub fn bytes(&mut self, value: &[u8]) -> &mut Self {
+            self.length(Some(value.len()), true);
+            self.buffer.extend_from_slice(value);
+            self
+        }
+
+        pub fn array_len(&mut self, length: usize) -> &mut Self {
+            self.length(Some(length), true)
+        }
+
+        pub fn null_array(&mut self) -> &mut Self {
+            self.length(None, true)
+        }
+
+        pub fn empty_tagged_fields(&mut self) -> &mut Self {
+            if self.flexible {
+                self.unsigned_varint(0);
+            }
+            self
+        }
+
+        pub fn finish(&mut self) -> Vec<u8> {
+            std::mem::take(&mut self.buffer)
+        }
+    }
+
+    #[test]
+    fn reader_should_decode_classic_and_compact_strings() {
+        let classic = SchemaWriter::new(false)
+            .string("group")
+            .nullable_string(None)
+            .finish();
+        let mut reader = SchemaReader::new(&classic, false);
+        assert_eq!(reader.string().expect("string"), "group");
+        assert_eq!(reader.nullable_string().expect("null"), None);
+
+        let compact = SchemaWriter::new(true)
+            .string("group")
+            .nullable_string(None)
+            .empty_tagged_fields()
+            .finish();
+        let mut reader = SchemaReader::new(&compact, true);
+        assert_eq!(reader.string().expect("string"), "group");
+        assert_eq!(reader.nullable_string().expect("null"), None);
+        reader.skip_tagged_fields().expect("tags");
+        assert_eq!(reader.remaining(), 0);
+    }
+
+    #[test]
+    fn reader_should_surface_truncated_input() {
+        let mut reader = SchemaReader::new(&[0, 5, b'a'], false);
+        let err = reader.string().expect_err("truncated");
+        assert!(err.to_string().contains("truncated"), "{err}");
+    }
+}
@@ -0,0 +1,711 @@
+//! Schema Registry aware decoding of Confluent wire-format payloads.
+//!
+//! Payloads start with magic byte `0` and a big-endian schema id. Protobuf
+//! payloads additionally carry the zigzag-varint message index path that
+//! selects a message within the `.proto` file. Schemas are fetched from a
+//! registry, or read from a local directory holding `<id>.avsc`, `<id>.proto`,
+//! or `<id>.json` files for offline use, and cached by id. Producers encode
+//! JSON records the same way through [`RecordEncoder`].
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError};
+
+use krafka::schema_registry::{
+    ConfluentSchemaRegistry, Schema, SchemaId, SchemaReference, SchemaRegistryClient, SchemaType,
+    SchemaVersion, decode_wire_format, encode_wire_format,
+};
+use serde::de::{Deserialize, Deserializer, MapAccess, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeMap, SerializeSeq, Serializer};
+
+use crate::avro::AvroSchema;
+use crate::error::{Error, Result};
+use crate::protobuf::{ProtoFile, ProtoSchema};
+
+/// A decoded value whose object members keep their schema declaration order.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SchemaValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Uint(u64),
+    Float(f64),
+    String(String),
+    Array(Vec<Self>),
+    Object(Vec<(String, Self)>),
+}
+
+impl Serialize for SchemaValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        match self {
+            Self::Null => serializer.serialize_unit(),
+            Self::Bool(value) => serializer.serialize_bool(*value),
+            Self::Int(value) => serializer.serialize_i64(*value),
+            Self::Uint(value) => serializer.serialize_u64(*value),
+            Self::Float(value) => serializer.serialize_f64(*value),
+            Self::String(value) => serializer.serialize_str(value),
+            Self::Array(values) => {
+                let mut sequence = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    sequence.serialize_element(value)?;
+                }
+                sequence.end()
+            }
+            Self::Object(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for SchemaValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        deserializer.deserialize_any(SchemaValueVisitor)
+    }
+}
+
+struct SchemaValueVisitor;
+
+impl<'de> Visitor<'de> for SchemaValueVisitor {
+    type Value = SchemaValue;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str("a JSON value")
+    }
+
+    fn visit_unit<E>(self) -> std::result::Result<Self::Value, E> {
+        Ok(SchemaValue::Null)
+    }
+
+    fn visit_bool<E>(self, value: bool) -> std::result::Result<Self::Value, E> {
+        Ok(SchemaValue::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> std::result::Result<Self::Value, E> {
+        Ok(SchemaValue::Int(value))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> std::result::Result<Self::Value, E> {
+        Ok(i64::try_from(value).map_or(SchemaValue::Uint(value), SchemaValue::Int))
+    }
+
+    fn visit_f64<E>(self, value: f64) -> std::result::Result<Self::Value, E> {
+        Ok(SchemaValue::Float(value))
+    }
+
+    fn visit_str<E>(self, value: &str) -> std::result::Result<Self::Value, E> {
+        Ok(SchemaValue::String(value.to_owned()))
+    }
+
+    fn visit_string<E>(self, value: String) -> std::result::Result<Self::Value, E> {
+        Ok(SchemaValue::String(value))
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(
+        self,
+        mut access: A,
+    ) -> std::result::Result<Self::Value, A::Error> {
+        let mut values = Vec::new();
+        while let Some(value) = access.next_element()? {
+            values.push(value);
+        }
+        Ok(SchemaValue::Array(values))
+    }
+
+    fn visit_map<A: MapAccess<'de>>(
+        self,
+        mut access: A,
+    ) -> std::result::Result<Self::Value, A::Error> {
+        let mut entries = Vec::new();
+        while let Some(entry) = access.next_entry()? {
+            entries.push(entry);
+        }
+        Ok(SchemaValue::Object(entries))
+    }
+}
+
+#[derive(Debug)]
+enum ParsedSchema {
+    Avro(AvroSchema),
+    Protobuf(ProtoSchema),
+    Json,
+}
+
+/// Fetches, parses, and caches the schemas referenced by wire-format payloads.
+pub struct SchemaResolver {
+    registry: Option<ConfluentSchemaRegistry>,
+    schema_dir: Option<PathBuf>,
+    schemas: Mutex<HashMap<SchemaId, Arc<ParsedSchema>>>,
+}
+
+impl SchemaResolver {
+    /// Creates a resolver backed by a registry URL, a local schema directory, or both.
+    ///
+    /// `user_info` is the Confluent `basic.auth.user.info` value in `user:password` form.
+    pub fn new(
+        url: Option<&str>,
+        user_info: Option<&str>,
+        schema_dir: Option<&Path>,
+    ) -> Result<Self> {
+        if url.is_none() && schema_dir.is_none() {
+            return Err(Error::Usage(
+                "schema-aware deserializers require --schema-registry-url or --schema-dir".into(),
+            ));
+        }
+        Ok(Self {
+            registry: url.map(|url| registry_client(url, user_info)).transpose()?,
+            schema_dir: schema_dir.map(Path::to_path_buf),
+            schemas: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Ensures the schema referenced by `payload` is cached for [`Self::decode`].
+    pub async fn load(&self, payload: Option<&[u8]>) -> Result<()> {
+        let Some(payload) = payload else {
+            return Ok(());
+        };
+        let id = schema_id(payload)?;
+        if self.cached(id).is_some() {
+            return Ok(());
+        }
+        let registry_error = match &self.registry {
+            Some(registry) => match fetch_from_registry(registry, id).await {
+                Ok(schema) => {
+                    self.insert(id, schema);
+                    return Ok(());
+                }
+                Err(error) => Some(error),
+            },
+            None => None,
+        };
+        match (&self.schema_dir, registry_error) {
+            (Some(directory), registry_error) => match read_from_directory(directory, id) {
+                Ok(schema) => {
+                    self.insert(id, schema);
+                    Ok(())
+                }
+                Err(error) => Err(registry_error.unwrap_or(error)),
+            },
+            (None, Some(error)) => Err(error),
+            (None, None) => Err(Error::Config(format!(
+                "schema {id} cannot be loaded without a schema registry or schema directory"
+            ))),
+        }
+    }
+
+    /// Decodes a wire-format payload whose schema was previously [loaded](Self::load).
+    pub fn decode(&self, payload: &[u8]) -> Result<SchemaValue> {
+        let (id, body) = decode_wire_format(payload).map_err(|error| {
+            Error::Config(format!(
+                "payload is not in Schema Registry wire format: {error}"
+            ))
+        })?;
+        let schema = self
+            .cached(id)
+            .ok_or_else(|| Error::Config(format!("schema {id} has not been loaded")))?;
+        match schema.as_ref() {
+            ParsedSchema::Avro(schema) => schema.decode(body),
+            ParsedSchema::Protobuf(schema) => {
+                let (indexes, body) = message_indexes(body)?;
+                schema.decode(schema.message_name(&indexes)?, body)
+            }
+            ParsedSchema::Json => serde_json::from_slice(body).map_err(Error::from),
+        }
+    }
+
+    fn cached(&self, id: SchemaId) -> Option<Arc<ParsedSchema>> {
+        self.schemas
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .get(&id)
+            .cloned()
+    }
+
+    fn insert(&self, id: SchemaId, schema: ParsedSchema) {
+        self.schemas
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(id, Arc::new(schema));
+    }
+}
+
+/// Serializes JSON records into wire-format payloads for one Avro or Protobuf schema.
+pub struct RecordEncoder {
+    id: SchemaId,
+    schema: EncodingSchema,
+}
+
+/// A schema records can be produced with; JSON Schema is only decoded.
+enum EncodingSchema {
+    Avro(AvroSchema),
+    /// A Protobuf schema with the message type records are encoded as and its index path.
+    Protobuf {
+        schema: ProtoSchema,
+        message: String,
+        indexes: Vec<usize>,
+    },
+}
+
+impl RecordEncoder {
+    /// Resolves the producing schema from a local schema file, a registry subject, or both.
+    ///
+    /// With a registry, a schema file is registered under `subject`, with its local Protobuf
+    /// imports registered first as references, and the returned id is used; without a file the subject's latest schema is fetched. Without a registry the
+    /// id comes from a file named `<id>.avsc` or `<id>.proto`. `message` selects the Protobuf
+    /// message type and defaults to the first one declared.
+    pub async fn resolve(
+        registry: Option<&ConfluentSchemaRegistry>,
+        schema_file: Option<&Path>,
+        subject: &str,
+        message: Option<&str>,
+    ) -> Result<Self> {
+        let (id, schema) = match (schema_file, registry) {
+            (Some(path), registry) => {
+                let schema_type = match path.extension().and_then(|extension| extension.to_str()) {
+                    Some("avsc") => SchemaType::Avro,
+                    Some("proto") => SchemaType::Protobuf,
+                    _ => {
+                        return Err(Error::Usage(format!(
+                            "schema file {} must have an .avsc or .proto extension",
+                            path.display()
+                        )));
+                    }
+                };
+                let source = fs::read_to_string(path)?;
+                let references = if schema_type == SchemaType::Protobuf {
+                    local_imports(path.parent().unwrap_or_else(|| Path::new(".")), &source)?
+                } else {
+                    Vec::new()
+                };
+                let schema = parse_schema(schema_type, &source, &references)?;
+                let id = match registry {
+                    Some(registry) => {
+                        let references = if schema_type == SchemaType::Protobuf {
+                            let directory = path.parent().unwrap_or_else(|| Path::new("."));
+                            register_imports(registry, directory, &source, &mut HashMap::new())
+                                .await?
+                        } else {
+                            Vec::new()
+                        };
+                        registry
+                            .register_schema(subject, &source, schema_type, &references)
+                            .await?
+                    }
+                    None => path
+                        .file_stem()
+                        .and_then(|stem| stem.to_str())
+                        .and_then(|stem| stem.parse().ok())
+                        .ok_or_else(|| {
+                            Error::Usage(format!(
+                                "schema file {} must be named <id>.{} without --schema-registry-url",
+                                path.display(),
+                                if schema_type == SchemaType::Avro { "avsc" } else { "proto" }
+                            ))
+                        })?,
+                };
+                (id, schema)
+            }
+            (None, Some(registry)) => {
+                let latest = registry.get_latest_schema(subject).await?;
+                let references = fetch_references(registry, &latest.references).await?;
+                (
+                    latest.id,
+                    parse_schema(latest.schema_type, &latest.schema, &references)?,
+                )
+            }
+            (None, None) => {
+                return Err(Error::Usage(
+                    "schema-encoded production requires a schema file or --schema-registry-url"
+                        .into(),
+                ));
+            }
+        };
+        let schema = match schema {
+            ParsedSchema::Avro(schema) => EncodingSchema::Avro(schema),
+            ParsedSchema::Protobuf(schema) => {
+                let message = match message {
+                    Some(message) => message.trim_start_matches('.').to_owned(),
+                    None => schema.first_message()?.to_owned(),
+                };
+                let indexes = schema.message_indexes(&message)?;
+                EncodingSchema::Protobuf {
+                    schema,
+                    message,
+                    indexes,
+                }
+            }
+            ParsedSchema::Json => {
+                return Err(Error::Unsupported(format!(
+                    "subject {subject} uses JSON Schema, which cannot be produced natively"
+                )));
+            }
+        };
+        Ok(Self { id, schema })
+    }
+
+    /// Validates `value` against the schema and frames its encoding for the wire.
+    pub fn encode(&self, value: &serde_json::Value) -> Result<Vec<u8>> {
+        let payload = match &self.schema {
+            EncodingSchema::Avro(schema) => schema.encode(value)?,
+            EncodingSchema::Protobuf {
+                schema,
+                message,
+                indexes,
+            } => {
+                let mut payload = Vec::new();
+                if indexes.as_slice() == [0] {
+                    payload.push(0);
+                } else {
+                    for index in std::iter::once(indexes.len()).chain(indexes.iter().copied()) {
+                        write_zigzag_index(&mut payload, index);
+                    }
+                }
+                payload.extend(schema.encode(message, value)?);
+                payload
+            }
+        };
+        Ok(encode_wire_format(self.id, &payload).to_vec())
+    }
+}
+
+fn write_zigzag_index(output: &mut Vec<u8>, index: usize) {
+    crate::protobuf::write_varint(output, (index as u64) << 1);
+}
+
+/// Builds a registry client; `user_info` is the `basic.auth.user.info` `user:password` value.
+pub fn registry_client(url: &str, user_info: Option<&str>) -> Result<ConfluentSchemaRegistry> {
+    let mut builder = ConfluentSchemaRegistry::builder().url(url);
+    if let Some(user_info) = user_info {
+        let (user, password) = user_info.split_once(':').ok_or_else(|| {
+            Error::Config("basic.auth.user.info must be in user:password form".into())
+        })?;
+        builder = builder.basic_auth(user, password);
+    }
+    builder.build().map_err(Error::from)
+}
+
+fn schema_id(payload: &[u8]) -> Result<SchemaId> {
+    decode_wire_format(payload)
+        .map(|(id, _)| id)
+        .map_err(|error| {
+            Error::Config(format!(
+                "payload is not in Schema Registry wire format: {error}"
+            ))
+        })
+}
+
+async fn fetch_from_registry(
+    registry: &ConfluentSchemaRegistry,
+    id: SchemaId,
+) -> Result<ParsedSchema> {
+    let schema = registry.get_schema_by_id(id).await?;
+    let references = fetch_references(registry, &schema.references).await?;
+    parse_schema(schema.schema_type, &schema.schema, &references)
+}
+
+/// Fetches the sources of every schema transitively referenced by `references`.
+async fn fetch_references(
+    registry: &ConfluentSchemaRegistry,
+    references: &[SchemaReference],
+) -> Result<Vec<String>> {
+    let mut sources = Vec::new();
+    let mut pending = references.to_vec();
+    let mut seen = HashSet::new();
+    while let Some(reference) = pending.pop() {
+        if !seen.insert((reference.subject.clone(), reference.version)) {
+            continue;
+        }
+        let dependency: Schema = registry
+            .get_schema_by_version(&reference.subject, reference.version)
+            .await?;
+        pending.extend(dependency.references.iter().cloned());
+        sources.push(dependency.schema);
+    }
+    Ok(sources)
+}
+
+/// Registers the `.proto` files `source` imports from `directory`, dependencies first, each
+/// under its import path as Confluent serializers do, and returns them as references.
+/// Imports missing from `directory`, such as the well-known types, are left to the registry.
+async fn register_imports(
+    registry: &ConfluentSchemaRegistry,
+    directory: &Path,
+    source: &str,
+    registered: &mut HashMap<String, SchemaReference>,
+) -> Result<Vec<SchemaReference>> {
+    let mut references = Vec::new();
+    for import in ProtoFile::parse(source)?.imports {
+        if let Some(reference) = registered.get(&import) {
+            references.push(reference.clone());
+            continue;
+        }
+        let path = directory.join(&import);
+        if !path.is_file() {
+            continue;
+        }
+        let imported = fs::read_to_string(path)?;
+        let nested = Box::pin(register_imports(registry, directory, &imported, registered)).await?;
+        let id = registry
+            .register_schema(&import, &imported, SchemaType::Protobuf, &nested)
+            .await?;
+        let reference = SchemaReference::new(
+            import.clone(),
+            import.clone(),
+            registered_version(registry, &import, id).await?,
+        );
+        registered.insert(import, reference.clone());
+        references.push(reference);
+    }
+    Ok(references)
+}
+
+/// Finds the version of `subject` that holds schema `id`, newest first.
+async fn registered_version(
+    registry: &ConfluentSchemaRegistry,
+    subject: &str,
+    id: SchemaId,
+) -> Result<SchemaVersion> {
+    for version in registry.get_versions(subject).await?.into_iter().rev() {
+        if registry.get_schema_by_version(subject, version).await?.id == id {
+            return Ok(version);
+        }
+    }
+    Err(Error::Config(format!(
+        "schema {id} was registered but no version of subject {subject} holds it"
+    )))
+}
+
+/// Reads the `.proto` files transitively imported by `source` from `directory`.
+fn local_imports(directory: &Path, source: &str) -> Result<Vec<String>> {
+    let mut sources = Vec::new();
+    let mut pending = ProtoFile::parse(source)?.imports;
+    let mut seen = HashSet::new();
+    while let Some(import) = pending.pop() {
+        let path = directory.join(&import);
+        if !seen.insert(import) || !path.is_file() {
+            continue;
+        }
+        let imported = fs::read_to_string(path)?;
+        pending.extend(ProtoFile::parse(&imported)?.imports);
+        sources.push(imported);
+    }
+    Ok(sources)
+}
+
+fn read_from_directory(directory: &Path, id: SchemaId) -> Result<ParsedSchema> {
+    for (extension, schema_type) in [
+        ("avsc", SchemaType::Avro),
+        ("proto", SchemaType::Protobuf),
+        ("json", SchemaType::Json),
+    ] {
+        let path = directory.join(format!("{id}.{extension}"));
+        if !path.is_file() {
+            continue;
+        }
+        let source = fs::read_to_string(&path)?;
+        let references = if schema_type == SchemaType::Protobuf {
+            local_imports(directory, &source)?
+        } else {
+            Vec::new()
+        };
+        return parse_schema(schema_type, &source, &references);
+    }
+    Err(Error::Config(format!(
+        "schema {id} was not found in {}",
+        directory.display()
+    )))
+}
+
+fn parse_schema(
+    schema_type: SchemaType,
+    source: &str,
+    references: &[String],
+) -> Result<ParsedSchema> {
+    match schema_type {
+        SchemaType::Avro => {
+            let references: Vec<&str> = references.iter().map(String::as_str).collect();
+            AvroSchema::parse(source, &references).map(ParsedSchema::Avro)
+        }
+        SchemaType::Protobuf => ProtoSchema::new(
+            ProtoFile::parse(source)?,
+            references
+                .iter()
+                .map(|reference| ProtoFile::parse(reference))
+                .collect::<Result<_>>()?,
+        )
+        .map(ParsedSchema::Protobuf),
+        SchemaType::Json => Ok(ParsedSchema::Json),
+        other => Err(Error::Unsupported(format!(
+            "{other} schemas cannot be decoded by the native client"
+        ))),
+    }
+}
+
+/// Splits the Confluent protobuf message index path from the message body.
+fn message_indexes(body: &[u8]) -> Result<(Vec<usize>, &[u8])> {
+    let mut remaining = body;
+    let mut next = || -> Result<usize> {
+        let mut value = 0_u64;
+        for shift in (0..35).step_by(7) {
+            let (&byte, tail) = remaining
+                .split_first()
+                .ok_or_else(|| Error::Config("truncated protobuf message indexes".into()))?;
+            remaining = tail;
+            value |= u64::from(byte & 0x7f) << shift;
+            if byte & 0x80 == 0 {
+                if value & 1 == 1 {
+                    break;
+                }
+                return usize::try_from(value >> 1)
+                    .map_err(|_| Error::Config("invalid protobuf message index".into()));
+            }
+        }
+        Err(Error::Config("invalid protobuf message index".into()))
+    };
+    let count = next()?;
+    let indexes = if count == 0 {
+        vec![0]
+    } else {
+        (0..count).map(|_| next()).collect::<Result<_>>()?
+    };
+    Ok((indexes, remaining))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolver(directory: &Path) -> SchemaResolver {
+        SchemaResolver::new(None, None, Some(directory)).expect("resolver")
+    }
+
+    #[tokio::test]
+    async fn schema_resolver_should_decode_avro_from_schema_directory() {
+        let directory = tempfile::tempdir().expect("temp dir");
+        fs::write(
+            directory.path().join("7.avsc"),
+            r#"{"type":"record","name":"Greeting","fields":[{"name":"text","type":"string"},{"name":"count","type":"int"}]}"#,
+        )
+        .expect("schema written");
+        let resolver = resolver(directory.path());
+        let payload = encode_wire_format(7, &[4, b'h', b'i', 6]);
+
+        resolver.load(Some(&payload)).await.expect("schema loads");
+
+        assert_eq!(
+            serde_json::to_string(&resolver.decode(&payload).expect("decodes")).expect("json"),
+            r#"{"text":"hi","count":3}"#
+        );
+        assert!(resolver.decode(&encode_wire_format(8, &[])).is_err());
+        assert!(
+            resolver
+                .load(Some(&encode_wire_format(8, &[])))
+                .await
+                .is_err()
+        );
+        assert!(resolver.load(Some(b"plain")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn schema_resolver_should_decode_protobuf_message_indexes_and_json_schema() {
+        let directory = tempfile::tempdir().expect("temp dir");
+        fs::write(
+            directory.path().join("1.proto"),
+            "syntax = \"proto3\"; import \"common.proto\"; message A { string name = 1; } message B { Tag tag = 1; }",
+        )
+        .expect("schema written");
+        fs::write(
+            directory.path().join("common.proto"),
+            "syntax = \"proto3\"; message Tag { int32 id = 1; }",
+        )
+        .expect("import written");
+        fs::write(directory.path().join("2.json"), r#"{"type":"object"}"#).expect("schema written");
+        let resolver = resolver(directory.path());
+        let mut body = vec![2, 2];
+        body.extend([10, 2, 8, 5]);
+        let second = encode_wire_format(1, &body);
+        let first = encode_wire_format(1, &[0, 10, 1, b'x']);
+        let json = encode_wire_format(2, br#"{"z":1,"a":[true,null]}"#);
+
+        for payload in [&second, &first, &json] {
+            resolver.load(Some(payload)).await.expect("schema loads");
+        }
+
+        let render = |payload: &[u8]| {
+            serde_json::to_string(&resolver.decode(payload).expect("decodes")).expect("json")
+        };
+        assert_eq!(render(&second), r#"{"tag":{"id":5}}"#);
+        assert_eq!(render(&first), r#"{"name":"x"}"#);
+        assert_eq!(render(&json), r#"{"z":1,"a":[true,null]}"#);
+    }
+
+    #[test]
+    fn schema_resolver_should_require_a_schema_source() {
+        assert!(SchemaResolver::new(None, None, None).is_err());
+        assert!(
+            SchemaResolver::new(Some("http://localhost:8081"), Some("missing-colon"), None)
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn record_encoder_should_frame_local_schemas_by_file_id() {
+        let directory = tempfile::tempdir().expect("temp dir");
+        let avro = directory.path().join("12.avsc");
+        fs::write(
+            &avro,
+            r#"{"type":"record","name":"R","fields":[{"name":"n","type":"int"}]}"#,
+        )
+        .expect("schema written");
+        let proto = directory.path().join("13.proto");
+        fs::write(&proto, "syntax = \"proto3\"; message A { int32 n = 1; } message B { message C { string s = 1; } }")
+            .expect("schema written");
+
+        let encoder = RecordEncoder::resolve(None, Some(&avro), "t-value", None)
+            .await
+            .expect("avro encoder");
+        assert_eq!(
+            encoder
+                .encode(&serde_json::json!({"n": 1}))
+                .expect("encodes"),
+            [0, 0, 0, 0, 12, 2]
+        );
+        assert!(matches!(
+            encoder.encode(&serde_json::json!({"n": "1"})),
+            Err(Error::Usage(_))
+        ));
+
+        let encoder = RecordEncoder::resolve(None, Some(&proto), "t-value", Some("B.C"))
+            .await
+            .expect("protobuf encoder");
+        let payload = encoder
+            .encode(&serde_json::json!({"s": "x"}))
+            .expect("encodes");
+        assert_eq!(payload, [0, 0, 0, 0, 13, 4, 2, 0, 10, 1, b'x']);
+        let resolver = resolver(directory.path());
+        resolver.load(Some(&payload)).await.expect("schema loads");
+        assert_eq!(
+            serde_json::to_string(&resolver.decode(&payload).expect("decodes")).expect("json"),
+            r#"{"s":"x"}"#
+        );
+
+        let unnamed = directory.path().join("value.avsc");
+        fs::write(&unnamed, r#""string""#).expect("schema written");
+        assert!(
+            RecordEncoder::resolve(None, Some(&unnamed), "t-value", None)
+                .await
+                .is_err()
+        );
+        assert!(
+            RecordEncoder::resolve(None, None, "t-value", None)
+                .await
+                .is_err()
+        );
+    }
+}
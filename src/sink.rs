@@ -0,0 +1,331 @@
+//! Destinations for consumed records: stdout, or files rotated by size and age.
+//!
+//! Records are written whole, so a rotation never splits one across segments. Segments can be
+//! gzip- or zstd-compressed; each is finished when it is rotated out or the sink is closed, so
+//! every closed segment is a complete stream.
+
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+};
+
+use flate2::{Compression, write::GzEncoder};
+
+use crate::{
+    cli::OutputCompression,
+    error::{Error, Result},
+};
+
+/// When a file sink starts a new segment.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Rotation {
+    /// Uncompressed bytes after which the segment is rotated.
+    pub bytes: Option<u64>,
+    /// Segment age after which it is rotated.
+    pub interval: Option<Duration>,
+}
+
+impl Rotation {
+    const fn is_enabled(self) -> bool {
+        self.bytes.is_some() || self.interval.is_some()
+    }
+}
+
+/// Where consumed records go.
+pub enum RecordSink {
+    Stdout,
+    File(Box<RotatingFile>),
+}
+
+impl RecordSink {
+    /// Opens a sink, writing `preamble` (such as a CSV header row) at the start of stdout or of
+    /// every file segment.
+    pub fn open(
+        path: Option<&Path>,
+        compression: OutputCompression,
+        rotation: Rotation,
+        preamble: Option<Vec<u8>>,
+    ) -> Result<Self> {
+        let Some(path) = path else {
+            if let Some(preamble) = preamble {
+                io::stdout().lock().write_all(&preamble)?;
+            }
+            return Ok(Self::Stdout);
+        };
+        RotatingFile::open(path.to_path_buf(), compression, rotation, preamble)
+            .map(|file| Self::File(Box::new(file)))
+    }
+
+    /// Writes one complete record.
+    pub fn write_record(&mut self, record: &[u8]) -> Result<()> {
+        match self {
+            Self::Stdout => io::stdout().lock().write_all(record)?,
+            Self::File(file) => file.write_record(record)?,
+        }
+        Ok(())
+    }
+
+    /// Flushes stdout or finishes the current file segment.
+    pub fn finish(self) -> Result<()> {
+        match self {
+            Self::Stdout => io::stdout().lock().flush()?,
+            Self::File(file) => file.segment.finish()?,
+        }
+        Ok(())
+    }
+}
+
+/// A file sink that starts a numbered segment whenever its rotation limits are reached.
+pub struct RotatingFile {
+    path: PathBuf,
+    compression: OutputCompression,
+    rotation: Rotation,
+    preamble: Option<Vec<u8>>,
+    sequence: u32,
+    segment: Segment,
+}
+
+impl RotatingFile {
+    fn open(
+        path: PathBuf,
+        compression: OutputCompression,
+        rotation: Rotation,
+        preamble: Option<Vec<u8>>,
+    ) -> Result<Self> {
+        let mut sequence = 0;
+        let segment = if rotation.is_enabled() {
+            next_segment(&path, &mut sequence, compression, preamble.as_deref())?
+        } else {
+            let file = create_file(
+                &path,
+                OpenOptions::new().write(true).create(true).truncate(true),
+            )
+            .map_err(|error| create_error(&path, &error))?;
+            Segment::new(file, compression, preamble.as_deref())?
+        };
+        Ok(Self {
+            path,
+            compression,
+            rotation,
+            preamble,
+            sequence,
+            segment,
+        })
+    }
+
+    fn write_record(&mut self, record: &[u8]) -> Result<()> {
+        let full = self
+            .rotation
+            .bytes
+            .is_some_and(|limit| self.segment.written >= limit);
+        let expired = self
+            .rotation
+            .interval
+            .is_some_and(|interval| self.segment.opened.elapsed() >= interval);
+        if full || expired {
+            let next = next_segment(
+                &self.path,
+                &mut self.sequence,
+                self.compression,
+                self.preamble.as_deref(),
+            )?;
+            std::mem::replace(&mut self.segment, next).finish()?;
+        }
+        self.segment.write(record)
+    }
+}
+
+/// Creates the segment after `sequence`, skipping numbers whose files already exist so an
+/// earlier capture is never overwritten.
+fn next_segment(
+    path: &Path,
+    sequence: &mut u32,
+    compression: OutputCompression,
+    preamble: Option<&[u8]>,
+) -> Result<Segment> {
+    loop {
+        *sequence += 1;
+        let path = segment_path(path, *sequence);
+        match create_file(&path, OpenOptions::new().write(true).create_new(true)) {
+            Ok(file) => return Segment::new(file, compression, preamble),
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => {}
+            Err(error) => return Err(create_error(&path, &error)),
+        }
+    }
+}
+
+fn create_file(path: &Path, options: &OpenOptions) -> io::Result<File> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)?;
+    }
+    options.open(path)
+}
+
+fn create_error(path: &Path, error: &io::Error) -> Error {
+    Error::Usage(format!(
+        "failed to create output file {}: {error}",
+        path.display()
+    ))
+}
+
+/// Inserts a segment number before the first extension: `capture.jsonl.gz` becomes
+/// `capture-000002.jsonl.gz`.
+fn segment_path(path: &Path, sequence: u32) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let name = match name.split_once('.') {
+        Some((stem, extension)) if !stem.is_empty() => {
+            format!("{stem}-{sequence:06}.{extension}")
+        }
+        _ => format!("{name}-{sequence:06}"),
+    };
+    path.with_file_name(name)
+}
+
+struct Segment {
+    output: SegmentWriter,
+    opened: Instant,
+    written: u64,
+}
+
+enum SegmentWriter {
+    Plain(BufWriter<File>),
+    Gzip(GzEncoder<BufWriter<File>>),
+    Zstd(zstd::Encoder<'static, BufWriter<File>>),
+}
+
+impl Segment {
+    fn new(file: File, compression: OutputCompression, preamble: Option<&[u8]>) -> Result<Self> {
+        let file = BufWriter::new(file);
+        let output = match compression {
+            OutputCompression::None => SegmentWriter::Plain(file),
+            OutputCompression::Gzip => {
+                SegmentWriter::Gzip(GzEncoder::new(file, Compression::default()))
+            }
+            OutputCompression::Zstd => SegmentWriter::Zstd(zstd::Encoder::new(file, 0)?),
+        };
+        let mut segment = Self {
+            output,
+            opened: Instant::now(),
+            written: 0,
+        };
+        if let Some(preamble) = preamble {
+            segment.write(preamble)?;
+        }
+        Ok(segment)
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        match &mut self.output {
+            SegmentWriter::Plain(output) => output.write_all(bytes)?,
+            SegmentWriter::Gzip(output) => output.write_all(bytes)?,
+            SegmentWriter::Zstd(output) => output.write_all(bytes)?,
+        }
+        self.written += bytes.len() as u64;
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        match self.output {
+            SegmentWriter::Plain(mut output) => output.flush()?,
+            SegmentWriter::Gzip(output) => output.finish()?.flush()?,
+            SegmentWriter::Zstd(output) => output.finish()?.flush()?,
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{BufReader, Read};
+
+    use flate2::read::GzDecoder;
+
+    use super::*;
+
+    fn read_segment(path: &Path, compression: OutputCompression) -> String {
+        let file = File::open(path).expect("segment exists");
+        let mut text = String::new();
+        match compression {
+            OutputCompression::None => BufReader::new(file).read_to_string(&mut text),
+            OutputCompression::Gzip => GzDecoder::new(file).read_to_string(&mut text),
+            OutputCompression::Zstd => zstd::Decoder::new(file)
+                .expect("zstd stream")
+                .read_to_string(&mut text),
+        }
+        .expect("segment readable");
+        text
+    }
+
+    #[test]
+    fn file_sinks_should_rotate_whole_records_with_a_preamble_per_segment() {
+        let directory = tempfile::tempdir().expect("temp dir");
+        for compression in [
+            OutputCompression::None,
+            OutputCompression::Gzip,
+            OutputCompression::Zstd,
+        ] {
+            let path = directory
+                .path()
+                .join(format!("{compression:?}"))
+                .join("capture.csv");
+            let mut sink = RecordSink::open(
+                Some(&path),
+                compression,
+                Rotation {
+                    bytes: Some(12),
+                    interval: None,
+                },
+                Some(b"k,v\n".to_vec()),
+            )
+            .expect("sink");
+            for record in ["a,1\n", "b,2\n", "c,3\n"] {
+                sink.write_record(record.as_bytes())
+                    .expect("record written");
+            }
+            sink.finish().expect("finished");
+
+            assert_eq!(
+                read_segment(&segment_path(&path, 1), compression),
+                "k,v\na,1\nb,2\n"
+            );
+            assert_eq!(
+                read_segment(&segment_path(&path, 2), compression),
+                "k,v\nc,3\n"
+            );
+            assert!(!segment_path(&path, 3).exists());
+
+            let sink = RecordSink::open(
+                Some(&path),
+                compression,
+                Rotation {
+                    bytes: Some(12),
+                    interval: None,
+                },
+                Some(b"k,v\n".to_vec()),
+            )
+            .expect("sink after an earlier capture");
+            sink.finish().expect("finished");
+            assert_eq!(
+                read_segment(&segment_path(&path, 1), compression),
+                "k,v\na,1\nb,2\n"
+            );
+            assert_eq!(read_segment(&segment_path(&path, 3), compression), "k,v\n");
+        }
+        assert_eq!(
+            segment_path(Path::new("out/capture.jsonl.gz"), 7),
+            Path::new("out/capture-000007.jsonl.gz")
+        );
+        assert_eq!(
+            segment_path(Path::new(".capture"), 2),
+            Path::new(".capture-000002")
+        );
+    }
+}
@@ -561,6 +561,28 @@ fn all_command_families_work_against_kafka_4_3_1() {
         .assert()
         .success()
         .stdout(predicate::str::contains("first"));
+    let now_ms = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("clock after epoch")
+        .as_millis()
+        .to_string();
+    Command::cargo_bin("kafka")
+        .expect("kafka binary")
+        .args([
+            "--bootstrap-server",
+            &bootstrap,
+            "consume",
+            "--topic",
+            "integration-json",
+            "--from-time",
+            "1970-01-01T00:00:00Z",
+            "--to-time",
+            &now_ms,
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("first"))
+        .stdout(predicate::str::contains("second"));
     Command::cargo_bin("kafka")
         .expect("kafka binary")
         .args([
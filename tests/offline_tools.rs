@@ -1,6 +1,7 @@
 //! Offline tool coverage that does not require a live Kafka broker.
 //!
-//! Exercises the shipped `kafka` binary for `storage` and `dump-log` end-to-end.
+//! Exercises the shipped `kafka` binary for `storage`, `dump-log`, and `metadata-shell`
+//! end-to-end.
 
 use std::{fs, path::PathBuf, process::Command as ProcessCommand};
 
@@ -338,6 +339,105 @@ fn dump_log_should_reject_custom_decoder_and_missing_files_via_binary() {
         );
 }
 
+#[test]
+fn dump_log_offsets_decoder_should_print_group_metadata_json_via_binary() {
+    let mut key = Vec::new();
+    key.extend_from_slice(&2_i16.to_be_bytes());
+    key.extend_from_slice(&5_i16.to_be_bytes());
+    key.extend_from_slice(b"audit");
+    let mut batch = RecordBatch::new();
+    batch.add_record(Record::new(Some(bytes::Bytes::from(key)), None));
+    let dir = TempDir::new().expect("dir");
+    let log_path = dir.path().join("00000000000000000000.log");
+    fs::write(&log_path, batch.encode().expect("encode")).expect("log");
+    kafka_bin()
+        .args([
+            "dump-log",
+            "--files",
+            log_path.to_str().expect("p"),
+            "--offsets-decoder",
+        ])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains(
+            r#"key: {"type":"2","data":{"group":"audit"}} payload: <DELETE>"#,
+        ));
+}
+
+#[test]
+fn metadata_shell_should_replay_snapshot_and_later_segments_via_binary() {
+    let dir = TempDir::new().expect("dir");
+    let metadata_dir = dir.path().join("__cluster_metadata-0");
+    fs::create_dir_all(&metadata_dir).expect("mkdir");
+    let mut topic = compact_string("orders");
+    topic.extend_from_slice(&[7; 16]);
+    let mut snapshot = RecordBatch::new();
+    snapshot.last_offset_delta = 1;
+    snapshot.add_record(Record::new(None, Some(metadata_record(2, &topic))).with_offset_delta(0));
+    snapshot.add_record(
+        Record::new(None, Some(metadata_record(12, &feature_level(20)))).with_offset_delta(1),
+    );
+    fs::write(
+        metadata_dir.join("00000000000000000002-0000000001.checkpoint"),
+        snapshot.encode().expect("encode snapshot"),
+    )
+    .expect("snapshot");
+    let mut segment = RecordBatch::new();
+    segment.last_offset_delta = 2;
+    segment.add_record(
+        Record::new(None, Some(metadata_record(12, &feature_level(5)))).with_offset_delta(0),
+    );
+    segment.add_record(
+        Record::new(None, Some(metadata_record(12, &feature_level(21)))).with_offset_delta(2),
+    );
+    fs::write(
+        metadata_dir.join("00000000000000000000.log"),
+        segment.encode().expect("encode segment"),
+    )
+    .expect("segment");
+
+    let snapshot_arg = dir.path().to_str().expect("path");
+    kafka_bin()
+        .args(["metadata-shell", "--snapshot", snapshot_arg])
+        .args(["cat", "/image/features/metadata.version"])
+        .assert()
+        .success()
+        .stdout("21\n");
+    kafka_bin()
+        .args(["metadata-shell", "--snapshot", snapshot_arg])
+        .args(["ls", "/image/topics/byName"])
+        .assert()
+        .success()
+        .stdout("orders\n");
+    kafka_bin()
+        .args(["metadata-shell", "--snapshot", snapshot_arg])
+        .write_stdin("cd /local\nls\nexit\n")
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("[ Kafka Metadata Shell ]"))
+        .stdout(predicate::str::contains("lastOffset\nsource\n"));
+}
+
+/// Frames a version 0 `KRaft` metadata record body with empty tagged fields.
+fn metadata_record(api_key: u8, body: &[u8]) -> bytes::Bytes {
+    let mut value = vec![0, api_key, 0];
+    value.extend_from_slice(body);
+    value.push(0);
+    bytes::Bytes::from(value)
+}
+
+fn compact_string(value: &str) -> Vec<u8> {
+    let mut encoded = vec![u8::try_from(value.len() + 1).expect("short string")];
+    encoded.extend_from_slice(value.as_bytes());
+    encoded
+}
+
+fn feature_level(level: i16) -> Vec<u8> {
+    let mut body = compact_string("metadata.version");
+    body.extend_from_slice(&level.to_be_bytes());
+    body
+}
+
 #[test]
 fn storage_version_mapping_and_feature_dependencies_via_binary() {
     kafka_bin()